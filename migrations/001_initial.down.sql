@@ -0,0 +1,4 @@
+-- Revert the initial schema
+
+DROP TABLE IF EXISTS bundle_states;
+DROP TABLE IF EXISTS agent_states;
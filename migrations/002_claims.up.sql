@@ -0,0 +1,18 @@
+-- Issue claims held by agents, with a lease that must be renewed while working
+
+CREATE TABLE IF NOT EXISTS claims (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    branch TEXT,
+    claimed_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    lease_expires_at DATETIME NOT NULL,
+    released_at DATETIME,
+    release_reason TEXT
+);
+
+-- At most one live claim per issue
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claims_active_issue
+    ON claims(issue_number) WHERE released_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_claims_agent_id ON claims(agent_id);
+CREATE INDEX IF NOT EXISTS idx_claims_lease_expires_at ON claims(lease_expires_at);
@@ -0,0 +1,20 @@
+-- Every attempt to get an agent's work integrated, from bottling to merge
+
+CREATE TABLE IF NOT EXISTS integration_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    correlation_id TEXT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    agent_id TEXT NOT NULL,
+    attempted_at DATETIME NOT NULL,
+    phase TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    duration_seconds INTEGER,
+    error_message TEXT,
+    pr_number INTEGER,
+    -- Quality gate report and anything else too rich for columns, as JSON
+    details TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_integration_attempts_attempted_at ON integration_attempts(attempted_at);
+CREATE INDEX IF NOT EXISTS idx_integration_attempts_issue ON integration_attempts(issue_number);
+CREATE INDEX IF NOT EXISTS idx_integration_attempts_agent ON integration_attempts(agent_id, attempted_at);
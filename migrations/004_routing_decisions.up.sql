@@ -0,0 +1,20 @@
+-- Outcome of each routing pass: what was considered and who got what
+
+CREATE TABLE IF NOT EXISTS routing_decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    correlation_id TEXT NOT NULL,
+    decided_at DATETIME NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    issues_evaluated INTEGER NOT NULL,
+    agents_available INTEGER NOT NULL,
+    -- task_assigned, no_tasks_available, no_agents_available or filtered_out
+    outcome TEXT NOT NULL,
+    issue_number INTEGER,
+    agent_id TEXT,
+    reason TEXT,
+    -- JSON array of detected bottlenecks
+    bottlenecks TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_routing_decisions_decided_at ON routing_decisions(decided_at);
+CREATE INDEX IF NOT EXISTS idx_routing_decisions_issue ON routing_decisions(issue_number);
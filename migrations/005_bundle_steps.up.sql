@@ -0,0 +1,14 @@
+-- One row per saved step of a bundle run, so a run can be replayed and audited
+
+CREATE TABLE IF NOT EXISTS bundle_steps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bundle_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    operation TEXT,
+    completed_branches INTEGER NOT NULL DEFAULT 0,
+    failed_branches INTEGER NOT NULL DEFAULT 0,
+    pr_number INTEGER,
+    recorded_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_bundle_steps_bundle_id ON bundle_steps(bundle_id, id);
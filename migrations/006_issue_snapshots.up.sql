@@ -0,0 +1,17 @@
+-- Last known state of a GitHub issue, to avoid refetching unchanged issues
+
+CREATE TABLE IF NOT EXISTS issue_snapshots (
+    issue_number INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    state TEXT NOT NULL,
+    -- JSON array of label names
+    labels TEXT NOT NULL,
+    assignee TEXT,
+    -- GitHub's updated_at, compared before trusting the snapshot
+    github_updated_at DATETIME,
+    fetched_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- Full issue JSON as fetched
+    payload TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_issue_snapshots_fetched_at ON issue_snapshots(fetched_at);
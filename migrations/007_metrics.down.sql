@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS metrics_rollups;
+DROP TABLE IF EXISTS performance_bottlenecks;
+DROP TABLE IF EXISTS coordination_decisions;
+DROP TABLE IF EXISTS agent_utilization;
@@ -0,0 +1,69 @@
+-- Raw metrics records not covered by earlier tables, and their rollups
+
+CREATE TABLE IF NOT EXISTS agent_utilization (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    recorded_at DATETIME NOT NULL,
+    current_capacity INTEGER NOT NULL,
+    max_capacity INTEGER NOT NULL,
+    utilization_percentage REAL NOT NULL,
+    -- JSON array of issue numbers
+    active_issues TEXT NOT NULL,
+    state TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_utilization_recorded_at ON agent_utilization(recorded_at);
+
+CREATE TABLE IF NOT EXISTS coordination_decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    correlation_id TEXT NOT NULL,
+    decided_at DATETIME NOT NULL,
+    operation TEXT NOT NULL,
+    agent_id TEXT,
+    issue_number INTEGER,
+    rationale TEXT NOT NULL,
+    execution_time_ms INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    -- JSON object
+    metadata TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_coordination_decisions_decided_at ON coordination_decisions(decided_at);
+
+CREATE TABLE IF NOT EXISTS performance_bottlenecks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    detected_at DATETIME NOT NULL,
+    bottleneck_type TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    description TEXT NOT NULL,
+    suggested_action TEXT NOT NULL,
+    -- JSON object of the measurements that triggered it
+    metrics TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_performance_bottlenecks_detected_at ON performance_bottlenecks(detected_at);
+
+-- Running aggregates per hour and per day, updated with every raw record
+CREATE TABLE IF NOT EXISTS metrics_rollups (
+    -- 'hour' or 'day'
+    granularity TEXT NOT NULL,
+    -- Unix time of the start of the bucket, in UTC
+    bucket_start INTEGER NOT NULL,
+    -- routing, utilization, coordination or integration
+    metric TEXT NOT NULL,
+    -- Agent id or operation name, empty for routing
+    dimension TEXT NOT NULL DEFAULT '',
+    sample_count INTEGER NOT NULL DEFAULT 0,
+    success_count INTEGER NOT NULL DEFAULT 0,
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    -- Durations in ms (routing, coordination), seconds (integration) or utilization %
+    value_sum REAL NOT NULL DEFAULT 0,
+    value_count INTEGER NOT NULL DEFAULT 0,
+    value_max REAL,
+    success_value_sum REAL NOT NULL DEFAULT 0,
+    success_value_count INTEGER NOT NULL DEFAULT 0,
+    last_seen INTEGER NOT NULL,
+    PRIMARY KEY (granularity, bucket_start, metric, dimension)
+);
+
+CREATE INDEX IF NOT EXISTS idx_metrics_rollups_window ON metrics_rollups(granularity, metric, bucket_start);
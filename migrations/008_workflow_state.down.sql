@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS workflow_checkpoints;
+DROP TABLE IF EXISTS workflow_states;
@@ -0,0 +1,30 @@
+-- Autonomous workflow state: the latest state per agent and its checkpoints
+
+CREATE TABLE IF NOT EXISTS workflow_states (
+    agent_id TEXT PRIMARY KEY,
+    checkpoint_id TEXT NOT NULL,
+    -- PersistentWorkflowState as JSON
+    state TEXT NOT NULL,
+    -- SHA-256 of the state column, checked whenever the row is read
+    content_hash TEXT NOT NULL,
+    saved_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS workflow_checkpoints (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    checkpoint_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    -- Summary columns so checkpoints can be listed without decoding states
+    current_state_type TEXT,
+    transitions_count INTEGER NOT NULL,
+    recovery_attempts INTEGER NOT NULL,
+    uptime_minutes INTEGER,
+    state TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    UNIQUE(agent_id, checkpoint_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_checkpoints_agent_created ON workflow_checkpoints(agent_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_workflow_checkpoints_created ON workflow_checkpoints(created_at);
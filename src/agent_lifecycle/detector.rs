@@ -1,5 +1,250 @@
-//! Agent state detection module
+//! Pre-flight checks run before an agent bottles its work
 //!
-//! This module previously contained an `AgentStateDetector` implementation
-//! but it was removed as it was not being used in the codebase
-//! and was generating clippy warnings.
+//! Each check maps onto a `PreFlightIssue`. Git state is read with git2 from
+//! the local repository and remote-tracking refs; the label check works on the
+//! issue's labels as GitHub reports them.
+
+use super::types::{PreFlightFix, PreFlightIssue, PreFlightSeverity};
+use anyhow::Result;
+use git2::{BranchType, Oid, Repository};
+use serde::Serialize;
+
+/// Inspects a branch's git state for pre-flight issues
+pub struct PreflightDetector<'a> {
+    repo: &'a Repository,
+    base_branch: String,
+    remote: String,
+}
+
+impl<'a> PreflightDetector<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        Self {
+            repo,
+            base_branch: "main".to_string(),
+            remote: "origin".to_string(),
+        }
+    }
+
+    /// Branch the work is compared against, `main` by default
+    pub fn with_base_branch(mut self, base_branch: &str) -> Self {
+        self.base_branch = base_branch.to_string();
+        self
+    }
+
+    /// Missing branch, unpushed commits, lag behind the base and conflicts with it
+    pub fn detect(&self, branch: &str) -> Result<Vec<PreFlightIssue>> {
+        let Some(tip) = self.local_tip(branch) else {
+            return Ok(vec![PreFlightIssue::BranchMissing {
+                branch: branch.to_string(),
+            }]);
+        };
+        let base = self.base_tip()?;
+
+        let mut issues = Vec::new();
+
+        let (ahead_of_base, behind_base) = self.repo.graph_ahead_behind(tip, base)?;
+        let unpushed = match self.remote_tip(branch) {
+            Some(remote) => self.repo.graph_ahead_behind(tip, remote)?.0,
+            // Never pushed: everything the branch adds is local only
+            None => ahead_of_base,
+        };
+        if unpushed > 0 {
+            issues.push(PreFlightIssue::UnpushedCommits {
+                count: unpushed as u32,
+            });
+        }
+        if behind_base > 0 {
+            issues.push(PreFlightIssue::BehindMain {
+                commits: behind_base as u32,
+            });
+        }
+
+        let files = self.conflicting_files(branch, tip, base)?;
+        if !files.is_empty() {
+            issues.push(PreFlightIssue::MergeConflicts { files });
+        }
+
+        Ok(issues)
+    }
+
+    fn local_tip(&self, branch: &str) -> Option<Oid> {
+        self.repo
+            .find_branch(branch, BranchType::Local)
+            .ok()
+            .and_then(|b| b.get().target())
+    }
+
+    fn remote_tip(&self, branch: &str) -> Option<Oid> {
+        self.repo
+            .find_reference(&format!("refs/remotes/{}/{}", self.remote, branch))
+            .ok()
+            .and_then(|r| r.target())
+    }
+
+    /// The remote's base when it is known, since that is what bundles build on
+    fn base_tip(&self) -> Result<Oid> {
+        self.remote_tip(&self.base_branch)
+            .or_else(|| self.local_tip(&self.base_branch))
+            .ok_or_else(|| anyhow::anyhow!("Base branch {} not found", self.base_branch))
+    }
+
+    /// Files left conflicted in the index, or that would conflict merging with the base
+    fn conflicting_files(&self, branch: &str, tip: Oid, base: Oid) -> Result<Vec<String>> {
+        let checked_out = self
+            .repo
+            .head()
+            .ok()
+            .is_some_and(|head| head.shorthand() == Some(branch));
+        if checked_out {
+            let files = conflict_paths(&self.repo.index()?)?;
+            if !files.is_empty() {
+                return Ok(files);
+            }
+        }
+
+        let merged = self.repo.merge_commits(
+            &self.repo.find_commit(base)?,
+            &self.repo.find_commit(tip)?,
+            None,
+        )?;
+        conflict_paths(&merged)
+    }
+}
+
+fn conflict_paths(index: &git2::Index) -> Result<Vec<String>> {
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let entry = conflict
+            .our
+            .or(conflict.their)
+            .or(conflict.ancestor)
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string());
+        if let Some(path) = entry {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// `LabelMismatch` when the issue lost the label of the agent working on it
+pub fn detect_label_mismatch(agent_id: &str, labels: &[String]) -> Option<PreFlightIssue> {
+    let expected = vec![agent_id.to_string()];
+    if expected.iter().all(|label| labels.contains(label)) {
+        return None;
+    }
+    Some(PreFlightIssue::LabelMismatch {
+        expected,
+        actual: labels.to_vec(),
+    })
+}
+
+/// One detected issue and what happened to it
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightFinding {
+    pub issue: PreFlightIssue,
+    pub severity: PreFlightSeverity,
+    pub fix: Option<PreFlightFix>,
+    pub fixed: bool,
+    pub fix_error: Option<String>,
+}
+
+/// Everything pre-flight found for one agent branch
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub branch: String,
+    pub agent_id: String,
+    pub issue_number: u64,
+    pub findings: Vec<PreflightFinding>,
+}
+
+impl PreflightReport {
+    /// Pair each issue with its fix; rebasing is only offered when it can't conflict
+    pub fn new(
+        branch: &str,
+        agent_id: &str,
+        issue_number: u64,
+        issues: Vec<PreFlightIssue>,
+    ) -> Self {
+        let conflicted = issues
+            .iter()
+            .any(|issue| matches!(issue, PreFlightIssue::MergeConflicts { .. }));
+        let findings = issues
+            .into_iter()
+            .map(|issue| {
+                let fix = match issue.fix() {
+                    Some(PreFlightFix::Rebase) if conflicted => None,
+                    fix => fix,
+                };
+                PreflightFinding {
+                    severity: issue.severity(),
+                    issue,
+                    fix,
+                    fixed: false,
+                    fix_error: None,
+                }
+            })
+            .collect();
+
+        Self {
+            branch: branch.to_string(),
+            agent_id: agent_id.to_string(),
+            issue_number,
+            findings,
+        }
+    }
+
+    /// Unfixed errors that stop bottling
+    pub fn blocking(&self) -> Vec<&PreflightFinding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == PreFlightSeverity::Error && !f.fixed)
+            .collect()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.iter().all(|f| f.fixed)
+    }
+
+    pub fn format_text(&self) -> String {
+        let mut text = format!(
+            "🛫 Pre-flight for {} ({} on issue #{})\n",
+            self.branch, self.agent_id, self.issue_number
+        );
+        if self.findings.is_empty() {
+            text.push_str("   ✅ All checks passed\n");
+            return text;
+        }
+
+        for finding in &self.findings {
+            let icon = match (finding.fixed, finding.severity) {
+                (true, _) => "🔧",
+                (false, PreFlightSeverity::Error) => "❌",
+                (false, PreFlightSeverity::Warning) => "⚠️ ",
+            };
+            text.push_str(&format!("   {icon} {}", finding.issue.describe()));
+            if finding.fixed {
+                text.push_str(" (fixed)");
+            } else if let Some(error) = &finding.fix_error {
+                text.push_str(&format!(" (fix failed: {error})"));
+            } else if let Some(fix) = &finding.fix {
+                text.push_str(&format!(" (fixable: {})", describe_fix(fix)));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn describe_fix(fix: &PreFlightFix) -> String {
+    match fix {
+        PreFlightFix::Push => "push".to_string(),
+        PreFlightFix::Rebase => "rebase onto main".to_string(),
+        PreFlightFix::Relabel { add } => format!("add {}", add.join(", ")),
+    }
+}
@@ -0,0 +1,265 @@
+//! Append-only log of agent lifecycle events
+//!
+//! Every event handed to the agent state machine is appended as one JSON line
+//! to `.my-little-soda/events.jsonl` together with the states before and after
+//! it, so `history` can tell what happened to an agent or an issue and when.
+
+use super::state_machine::{AgentEvent, AgentStateMachine};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use statig::prelude::*;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Where the event log lives unless a store is opened elsewhere
+pub const DEFAULT_EVENT_LOG: &str = ".my-little-soda/events.jsonl";
+
+const CSV_HEADER: &str =
+    "timestamp,agent_id,event,from_state,to_state,issue,branch,correlation_id,host";
+
+/// One event handled by an agent state machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleEvent {
+    pub timestamp: DateTime<Utc>,
+    pub agent_id: String,
+    pub event: AgentEvent,
+    pub from_state: String,
+    pub to_state: String,
+    pub issue: Option<u64>,
+    pub branch: Option<String>,
+    pub correlation_id: Option<String>,
+    pub host: String,
+}
+
+impl LifecycleEvent {
+    /// Whether the event moved the agent to another state
+    pub fn transitioned(&self) -> bool {
+        self.from_state != self.to_state
+    }
+
+    /// Issues the event concerns, including every issue of a bundle
+    pub fn issues(&self) -> Vec<u64> {
+        let mut issues: Vec<u64> = self.issue.into_iter().collect();
+        if let AgentEvent::Bundle {
+            issues: bundled, ..
+        } = &self.event
+        {
+            issues.extend(bundled.iter().filter(|i| Some(**i) != self.issue));
+        }
+        issues
+    }
+
+    fn csv_row(&self) -> String {
+        [
+            self.timestamp.to_rfc3339(),
+            self.agent_id.clone(),
+            self.event.kind().to_string(),
+            self.from_state.clone(),
+            self.to_state.clone(),
+            self.issue.map(|i| i.to_string()).unwrap_or_default(),
+            self.branch.clone().unwrap_or_default(),
+            self.correlation_id.clone().unwrap_or_default(),
+            self.host.clone(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Which events `history` shows
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub agent: Option<String>,
+    pub issue: Option<u64>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &LifecycleEvent) -> bool {
+        self.agent.as_ref().is_none_or(|a| *a == event.agent_id)
+            && self.issue.is_none_or(|i| event.issues().contains(&i))
+            && self.since.is_none_or(|s| event.timestamp >= s)
+    }
+}
+
+/// The event log file
+#[derive(Debug, Clone)]
+pub struct EventStore {
+    path: PathBuf,
+}
+
+impl Default for EventStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_LOG)
+    }
+}
+
+impl EventStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Append one event as a single line, so concurrent writers never interleave
+    pub fn append(&self, event: &LifecycleEvent) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| anyhow!("Failed to append to {}: {}", self.path.display(), e))
+    }
+
+    /// Events matching `filter`, oldest first
+    pub fn query(&self, filter: &EventFilter) -> Result<Vec<LifecycleEvent>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(anyhow!("Failed to read {}: {}", self.path.display(), e)),
+        };
+
+        let mut events = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<LifecycleEvent>(line) {
+                Ok(event) if filter.matches(&event) => events.push(event),
+                Ok(_) => {}
+                // A line cut short by a crash should not hide the rest of the log
+                Err(e) => tracing::warn!(
+                    path = %self.path.display(),
+                    line = index + 1,
+                    error = %e,
+                    "Skipping unreadable lifecycle event"
+                ),
+            }
+        }
+        events.sort_by_key(|e| e.timestamp);
+        Ok(events)
+    }
+
+    /// Hand `event` to the state machine and record what it did
+    ///
+    /// Failing to record is logged rather than returned, so the log can never
+    /// block a transition.
+    pub fn dispatch(
+        &self,
+        machine: &mut StateMachine<AgentStateMachine>,
+        event: &AgentEvent,
+        correlation_id: Option<&str>,
+    ) -> LifecycleEvent {
+        let from_state = machine.state().name().to_string();
+        // Abandoning clears the issue and branch, so remember them first
+        let issue_before = machine.current_issue();
+        let branch_before = machine.current_branch().map(str::to_string);
+
+        machine.handle(event);
+
+        let recorded = LifecycleEvent {
+            timestamp: Utc::now(),
+            agent_id: machine.agent_id().to_string(),
+            event: event.clone(),
+            from_state,
+            to_state: machine.state().name().to_string(),
+            issue: machine.current_issue().or(issue_before),
+            branch: machine
+                .current_branch()
+                .map(str::to_string)
+                .or(branch_before),
+            correlation_id: correlation_id.map(str::to_string),
+            host: hostname::get()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        };
+        if let Err(e) = self.append(&recorded) {
+            tracing::warn!(error = %e, "Failed to record lifecycle event");
+        }
+        recorded
+    }
+}
+
+/// Parse `--since`: a relative age like `30m`, `12h` or `7d`, a date or an RFC 3339 time
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let invalid = || {
+        anyhow!(
+            "Invalid --since '{}': use e.g. 30m, 12h, 7d or 2024-05-01",
+            value
+        )
+    };
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(now - age)
+}
+
+pub fn format_text(events: &[LifecycleEvent]) -> String {
+    if events.is_empty() {
+        return "No lifecycle events recorded\n".to_string();
+    }
+    let mut text = String::new();
+    for event in events {
+        let states = if event.transitioned() {
+            format!("{} → {}", event.from_state, event.to_state)
+        } else {
+            format!("{} (unchanged)", event.from_state)
+        };
+        let issue = event.issue.map(|i| format!(" #{i}")).unwrap_or_default();
+        let branch = event
+            .branch
+            .as_ref()
+            .map(|b| format!(" {b}"))
+            .unwrap_or_default();
+        text.push_str(&format!(
+            "{}  {:<9} {:<14} {:<26}{}{}  [{}]\n",
+            event.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            event.agent_id,
+            event.event.kind(),
+            states,
+            issue,
+            branch,
+            event.host
+        ));
+    }
+    text
+}
+
+pub fn to_csv(events: &[LifecycleEvent]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for event in events {
+        csv.push_str(&event.csv_row());
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1 +1,141 @@
-//! This module is currently unused and serves as a placeholder for future command execution functionality.
+//! Applies the automatic fixes offered by pre-flight checks
+//!
+//! Only fixes that can't lose work are applied: relabelling the issue,
+//! rebasing a branch that merges cleanly with the base, and pushing with a
+//! lease so commits pushed from elsewhere are never overwritten.
+
+use super::detector::PreflightReport;
+use super::types::PreFlightFix;
+use crate::github::GitHubClient;
+use anyhow::{anyhow, Result};
+use git2::Repository;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct PreflightExecutor {
+    workdir: PathBuf,
+    base_branch: String,
+    remote: String,
+}
+
+impl PreflightExecutor {
+    pub fn new(workdir: impl AsRef<Path>) -> Self {
+        Self {
+            workdir: workdir.as_ref().to_path_buf(),
+            base_branch: "main".to_string(),
+            remote: "origin".to_string(),
+        }
+    }
+
+    /// Branch fixes rebase onto, `main` by default
+    pub fn with_base_branch(mut self, base_branch: &str) -> Self {
+        self.base_branch = base_branch.to_string();
+        self
+    }
+
+    /// Apply every offered fix and record the result on each finding
+    pub async fn apply(&self, report: &mut PreflightReport, client: &GitHubClient) {
+        let issue_number = report.issue_number;
+        for finding in &mut report.findings {
+            let Some(PreFlightFix::Relabel { add }) = &finding.fix else {
+                continue;
+            };
+            let mut result = Ok(());
+            for label in add {
+                if let Err(e) = client.add_label_to_issue(issue_number, label).await {
+                    result = Err(anyhow!("{}", e));
+                    break;
+                }
+            }
+            record(finding, result);
+        }
+
+        self.apply_git_fixes(report);
+    }
+
+    /// Rebase first so that the push carries the rebased branch
+    pub fn apply_git_fixes(&self, report: &mut PreflightReport) {
+        let branch = report.branch.clone();
+
+        let mut needs_push = false;
+        if let Some(finding) = report
+            .findings
+            .iter_mut()
+            .find(|f| f.fix == Some(PreFlightFix::Rebase))
+        {
+            let result = self.rebase(&branch);
+            needs_push = result.is_ok() && self.has_remote_branch(&branch);
+            record(finding, result);
+        }
+
+        let push_finding = report
+            .findings
+            .iter_mut()
+            .find(|f| f.fix == Some(PreFlightFix::Push));
+        if push_finding.is_some() || needs_push {
+            let result = self.push(&branch);
+            match push_finding {
+                Some(finding) => record(finding, result),
+                None => {
+                    if let Err(e) = result {
+                        println!("⚠️  Rebased {branch} but could not push it: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn rebase(&self, branch: &str) -> Result<()> {
+        let onto = if self.has_remote_branch(&self.base_branch) {
+            format!("{}/{}", self.remote, self.base_branch)
+        } else {
+            self.base_branch.clone()
+        };
+
+        if let Err(e) = self.git(&["rebase", &onto, branch]) {
+            let _ = self.git(&["rebase", "--abort"]);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// `--force-with-lease` also covers a first push and a plain fast-forward
+    fn push(&self, branch: &str) -> Result<()> {
+        crate::git::secrets::ensure_no_secrets(
+            &Repository::open(&self.workdir)?,
+            branch,
+            &self.remote,
+            &self.base_branch,
+        )?;
+        self.git(&["push", "--force-with-lease", "-u", &self.remote, branch])
+    }
+
+    fn has_remote_branch(&self, branch: &str) -> bool {
+        Repository::open(&self.workdir).ok().is_some_and(|repo| {
+            repo.find_reference(&format!("refs/remotes/{}/{}", self.remote, branch))
+                .is_ok()
+        })
+    }
+
+    fn git(&self, args: &[&str]) -> Result<()> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.workdir)
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn record(finding: &mut super::detector::PreflightFinding, result: Result<()>) {
+    match result {
+        Ok(()) => finding.fixed = true,
+        Err(e) => finding.fix_error = Some(e.to_string()),
+    }
+}
@@ -5,7 +5,10 @@
 
 pub mod commands;
 pub mod detector;
+pub mod events;
 pub mod executor;
+pub mod quality_gates;
+pub mod rehydration;
 pub mod state_machine;
 pub mod traits;
 pub mod types;
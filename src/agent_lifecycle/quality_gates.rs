@@ -0,0 +1,295 @@
+//! Quality gates run on an agent's branch before it is bottled
+//!
+//! Gates are the `[quality_gates]` commands from the config, run in order with
+//! a timeout each. Once a required gate fails the remaining gates are skipped;
+//! optional gates only warn.
+
+use crate::config::{QualityGate, QualityGatesConfig};
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+/// Lines of output kept per gate for the report
+const OUTPUT_TAIL_LINES: usize = 30;
+
+/// Default gates for the ecosystems found at the repository root
+pub fn suggested_gates(exists: impl Fn(&str) -> bool) -> Vec<QualityGate> {
+    let mut gates = Vec::new();
+    if exists("Cargo.toml") {
+        gates.push(QualityGate::new("fmt", "cargo fmt --all -- --check").with_timeout(300));
+        gates.push(QualityGate::new(
+            "clippy",
+            "cargo clippy --all-targets -- -D warnings",
+        ));
+        gates.push(QualityGate::new("test", "cargo test").with_timeout(1800));
+    }
+    if exists("package.json") {
+        gates.push(QualityGate::new("npm-test", "npm test"));
+    }
+    if [
+        "pyproject.toml",
+        "setup.py",
+        "pytest.ini",
+        "requirements.txt",
+    ]
+    .iter()
+    .any(|file| exists(file))
+    {
+        gates.push(QualityGate::new("pytest", "pytest"));
+    }
+    if exists("go.mod") {
+        gates.push(QualityGate::new("go-test", "go test ./..."));
+    }
+    gates
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GateStatus {
+    Passed,
+    Failed {
+        exit_code: Option<i32>,
+    },
+    TimedOut,
+    /// The command couldn't be started
+    Error {
+        message: String,
+    },
+    /// Not run because an earlier required gate failed
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateResult {
+    pub name: String,
+    pub command: String,
+    pub required: bool,
+    #[serde(flatten)]
+    pub status: GateStatus,
+    pub duration_ms: u64,
+    pub output_tail: String,
+}
+
+impl GateResult {
+    pub fn passed(&self) -> bool {
+        self.status == GateStatus::Passed
+    }
+
+    fn status_text(&self) -> String {
+        match &self.status {
+            GateStatus::Passed => "passed".to_string(),
+            GateStatus::Failed {
+                exit_code: Some(code),
+            } => format!("failed (exit {code})"),
+            GateStatus::Failed { exit_code: None } => "failed (killed)".to_string(),
+            GateStatus::TimedOut => "timed out".to_string(),
+            GateStatus::Error { message } => format!("could not run: {message}"),
+            GateStatus::Skipped => "skipped".to_string(),
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match (&self.status, self.required) {
+            (GateStatus::Passed, _) => "✅",
+            (GateStatus::Skipped, _) => "⏭️ ",
+            (_, true) => "❌",
+            (_, false) => "⚠️ ",
+        }
+    }
+}
+
+/// Results of running every gate on one branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityGateReport {
+    pub branch: String,
+    pub commit: Option<String>,
+    pub results: Vec<GateResult>,
+    pub duration_ms: u64,
+}
+
+impl QualityGateReport {
+    /// Whether every required gate passed
+    pub fn passed(&self) -> bool {
+        self.failures().is_empty()
+    }
+
+    /// Required gates that did not pass
+    pub fn failures(&self) -> Vec<&GateResult> {
+        self.results
+            .iter()
+            .filter(|r| r.required && !r.passed() && r.status != GateStatus::Skipped)
+            .collect()
+    }
+
+    pub fn format_text(&self) -> String {
+        let mut text = format!("🚦 Quality gates for {}\n", self.branch);
+        for result in &self.results {
+            text.push_str(&format!(
+                "   {} {}: {} ({:.1}s)\n",
+                result.icon(),
+                result.name,
+                result.status_text(),
+                result.duration_ms as f64 / 1000.0
+            ));
+        }
+        text
+    }
+
+    /// Markdown comment for the agent's issue
+    pub fn format_comment(&self) -> String {
+        let verdict = if self.passed() {
+            "✅ **Quality gates passed**"
+        } else {
+            "❌ **Quality gates failed**"
+        };
+        let mut comment = format!("{verdict}\n\nBranch `{}`", self.branch);
+        if let Some(commit) = &self.commit {
+            comment.push_str(&format!(" at `{}`", &commit[..commit.len().min(8)]));
+        }
+        comment.push_str("\n\n| Gate | Command | Result | Time |\n|---|---|---|---|\n");
+        for result in &self.results {
+            comment.push_str(&format!(
+                "| {} {}{} | `{}` | {} | {:.1}s |\n",
+                result.icon(),
+                result.name,
+                if result.required { "" } else { " (optional)" },
+                result.command,
+                result.status_text(),
+                result.duration_ms as f64 / 1000.0
+            ));
+        }
+
+        for result in self
+            .results
+            .iter()
+            .filter(|r| !r.passed() && r.status != GateStatus::Skipped && !r.output_tail.is_empty())
+        {
+            comment.push_str(&format!(
+                "\n<details><summary>{} output</summary>\n\n```\n{}\n```\n</details>\n",
+                result.name, result.output_tail
+            ));
+        }
+        comment
+    }
+}
+
+/// Runs quality gates in a working tree
+pub struct QualityGateRunner {
+    root: PathBuf,
+}
+
+impl QualityGateRunner {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    pub async fn run(&self, config: &QualityGatesConfig, branch: &str) -> QualityGateReport {
+        let started = Instant::now();
+        let mut results = Vec::new();
+        let mut blocked = false;
+
+        for gate in &config.gates {
+            if blocked {
+                results.push(GateResult {
+                    name: gate.name.clone(),
+                    command: gate.command.clone(),
+                    required: gate.required,
+                    status: GateStatus::Skipped,
+                    duration_ms: 0,
+                    output_tail: String::new(),
+                });
+                continue;
+            }
+
+            let result = self.run_gate(gate).await;
+            blocked = gate.required && !result.passed();
+            results.push(result);
+        }
+
+        QualityGateReport {
+            branch: branch.to_string(),
+            commit: Repository::open(&self.root)
+                .ok()
+                .and_then(|repo| repo.head().ok()?.target())
+                .map(|oid| oid.to_string()),
+            results,
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+
+    async fn run_gate(&self, gate: &QualityGate) -> GateResult {
+        let started = Instant::now();
+        let dir = match &gate.working_dir {
+            Some(dir) => self.root.join(dir),
+            None => self.root.clone(),
+        };
+
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let child = Command::new(shell)
+            .arg(flag)
+            .arg(&gate.command)
+            .current_dir(&dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+
+        let (status, output_tail) = match child {
+            Err(e) => (
+                GateStatus::Error {
+                    message: e.to_string(),
+                },
+                String::new(),
+            ),
+            Ok(child) => {
+                let timeout = Duration::from_secs(gate.timeout_seconds);
+                match tokio::time::timeout(timeout, child.wait_with_output()).await {
+                    // Dropping the future kills the child
+                    Err(_) => (GateStatus::TimedOut, String::new()),
+                    Ok(Err(e)) => (
+                        GateStatus::Error {
+                            message: e.to_string(),
+                        },
+                        String::new(),
+                    ),
+                    Ok(Ok(output)) => {
+                        let status = if output.status.success() {
+                            GateStatus::Passed
+                        } else {
+                            GateStatus::Failed {
+                                exit_code: output.status.code(),
+                            }
+                        };
+                        let mut combined = String::from_utf8_lossy(&output.stdout).to_string();
+                        combined.push_str(&String::from_utf8_lossy(&output.stderr));
+                        (status, output_tail(&combined))
+                    }
+                }
+            }
+        };
+
+        GateResult {
+            name: gate.name.clone(),
+            command: gate.command.clone(),
+            required: gate.required,
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+            output_tail,
+        }
+    }
+}
+
+fn output_tail(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n")
+}
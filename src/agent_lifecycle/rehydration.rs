@@ -0,0 +1,308 @@
+//! Rebuild an agent's state machine from GitHub and git
+//!
+//! Every CLI invocation starts with a fresh machine in `idle`, so before any
+//! event is handled the state is derived from what outlives the process: the
+//! agent label on open issues, the agent's branches and how far they are ahead
+//! of the base, and the open bundle PRs. The events those facts imply are
+//! replayed onto a new machine, so its guards decide where it ends up. Where
+//! the sources disagree, the disagreement is reported as a `StuckAgentPattern`
+//! instead of being smoothed over.
+
+use super::state_machine::{AgentEvent, AgentStateMachine, Inconsistency, StuckAgentPattern};
+use crate::agents::integrator::{pr_issues, PrKind};
+use crate::github::labels::REVIEW_LABEL;
+use crate::github::GitHubClient;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use git2::{BranchType, Oid, Repository};
+use statig::prelude::*;
+
+/// An open issue and its labels, as GitHub reports them
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenIssue {
+    pub number: u64,
+    pub labels: Vec<String>,
+}
+
+impl OpenIssue {
+    fn has_label(&self, label: &str) -> bool {
+        self.labels.iter().any(|l| l == label)
+    }
+}
+
+/// An `agentNNN/<issue>` branch, local, on the remote or both
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentBranch {
+    pub name: String,
+    pub agent_id: String,
+    pub issue: u64,
+    pub local: bool,
+    pub on_remote: bool,
+    /// Commits on the branch that the base branch does not have
+    pub commits_ahead: u32,
+}
+
+/// An open bundle PR and the issues it carries
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenBundle {
+    pub pr_number: u64,
+    pub issues: Vec<u64>,
+}
+
+/// What GitHub and git say about the agents
+#[derive(Debug, Clone, Default)]
+pub struct LifecycleFacts {
+    pub open_issues: Vec<OpenIssue>,
+    pub branches: Vec<AgentBranch>,
+    pub bundles: Vec<OpenBundle>,
+}
+
+impl LifecycleFacts {
+    /// Open issues and bundle PRs from GitHub, agent branches from `repo`
+    pub async fn gather(github: &GitHubClient, repo: &Repository) -> Result<Self> {
+        let open_issues = github
+            .fetch_issues_with_state(Some(octocrab::params::State::Open))
+            .await?
+            .into_iter()
+            .filter(|issue| issue.pull_request.is_none())
+            .map(|issue| OpenIssue {
+                number: issue.number,
+                labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            })
+            .collect();
+
+        let bundles = github
+            .fetch_open_pull_requests()
+            .await?
+            .into_iter()
+            .filter(|pr| PrKind::from_branch(&pr.head.ref_field) == Some(PrKind::Bundle))
+            .map(|pr| OpenBundle {
+                pr_number: pr.number,
+                issues: pr_issues(&PrKind::Bundle, pr.body.as_deref().unwrap_or_default())
+                    .into_iter()
+                    .map(|issue| issue.issue_number)
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            open_issues,
+            branches: scan_branches(repo, "origin", "main")?,
+            bundles,
+        })
+    }
+
+    fn open_issue(&self, number: u64) -> Option<&OpenIssue> {
+        self.open_issues.iter().find(|issue| issue.number == number)
+    }
+}
+
+/// Tip of the base branch agent work is measured against
+///
+/// The remote's base when it is known, since that is what bundles build on,
+/// and the local base otherwise.
+pub fn base_tip(repo: &Repository, remote: &str, base_branch: &str) -> Result<Oid> {
+    [
+        format!("refs/remotes/{remote}/{base_branch}"),
+        format!("refs/heads/{base_branch}"),
+    ]
+    .iter()
+    .find_map(|name| repo.refname_to_id(name).ok())
+    .ok_or_else(|| anyhow!("Base branch {} not found", base_branch))
+}
+
+/// Agent branches in `repo`, local and remote-tracking, sorted by name
+///
+/// Commits ahead are counted against [`base_tip`].
+pub fn scan_branches(
+    repo: &Repository,
+    remote: &str,
+    base_branch: &str,
+) -> Result<Vec<AgentBranch>> {
+    let base = base_tip(repo, remote, base_branch)?;
+
+    let remote_prefix = format!("{remote}/");
+    let mut tips: Vec<(String, bool, Oid)> = Vec::new();
+    for branch in repo.branches(None)? {
+        let (branch, kind) = branch?;
+        let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+        match kind {
+            BranchType::Local => tips.push((name.to_string(), true, tip)),
+            BranchType::Remote => {
+                if let Some(name) = name.strip_prefix(&remote_prefix) {
+                    tips.push((name.to_string(), false, tip));
+                }
+            }
+        }
+    }
+
+    let mut branches: Vec<AgentBranch> = Vec::new();
+    for (name, local, tip) in tips {
+        let Some(PrKind::Agent {
+            agent_id,
+            issue_number,
+        }) = PrKind::from_branch(&name)
+        else {
+            continue;
+        };
+        if let Some(existing) = branches.iter_mut().find(|b| b.name == name) {
+            existing.local |= local;
+            existing.on_remote |= !local;
+            // The local tip is the one the agent works on
+            if local {
+                existing.commits_ahead = repo.graph_ahead_behind(tip, base)?.0 as u32;
+            }
+            continue;
+        }
+        branches.push(AgentBranch {
+            name,
+            agent_id,
+            issue: issue_number,
+            local,
+            on_remote: !local,
+            commits_ahead: repo.graph_ahead_behind(tip, base)?.0 as u32,
+        });
+    }
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(branches)
+}
+
+/// A machine rebuilt from the facts, and where the facts disagree
+pub struct Rehydration {
+    pub machine: StateMachine<AgentStateMachine>,
+    /// Events replayed onto a fresh machine to reach its state
+    pub replayed: Vec<AgentEvent>,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+/// Derive `agent_id`'s state machine from `facts`
+///
+/// The events are replayed without being recorded, since they already
+/// happened; only events handled afterwards belong in the event log.
+pub fn rehydrate(agent_id: &str, facts: &LifecycleFacts) -> Rehydration {
+    let mut patterns = Vec::new();
+    let own_branches: Vec<&AgentBranch> = facts
+        .branches
+        .iter()
+        .filter(|b| b.agent_id == agent_id)
+        .collect();
+
+    // Branches whose open issue shows no sign of this agent ever having had it
+    for branch in &own_branches {
+        let Some(issue) = facts.open_issue(branch.issue) else {
+            // Merged or closed; leftover branches are for cleanup, not rehydration
+            continue;
+        };
+        if !issue.has_label(agent_id) && !issue.has_label(REVIEW_LABEL) {
+            patterns.push(StuckAgentPattern::BranchButNoLabel {
+                agent_id: agent_id.to_string(),
+                branch: branch.name.clone(),
+            });
+        }
+    }
+
+    let labeled: Vec<&OpenIssue> = facts
+        .open_issues
+        .iter()
+        .filter(|issue| issue.has_label(agent_id))
+        .collect();
+    if labeled.len() > 1 {
+        patterns.push(StuckAgentPattern::AssignedToSeveralIssues {
+            agent_id: agent_id.to_string(),
+            issues: labeled.iter().map(|issue| issue.number).collect(),
+        });
+    }
+
+    let best_branch = |issue: u64| {
+        own_branches
+            .iter()
+            .filter(|b| b.issue == issue)
+            .max_by_key(|b| (b.local, b.commits_ahead))
+            .copied()
+    };
+    // With several labels, follow the one with the most work behind it
+    let current = labeled
+        .iter()
+        .max_by_key(|issue| {
+            let commits = best_branch(issue.number).map(|b| b.commits_ahead);
+            (commits, std::cmp::Reverse(issue.number))
+        })
+        .copied();
+
+    let mut replayed = Vec::new();
+    if let Some(issue) = current {
+        let branch = best_branch(issue.number);
+        let landed = issue.has_label(REVIEW_LABEL);
+        let commits_ahead = branch.map_or(0, |b| b.commits_ahead);
+
+        match branch {
+            None => patterns.push(StuckAgentPattern::LabeledButNoBranch {
+                agent_id: agent_id.to_string(),
+                issue: issue.number,
+            }),
+            // A pushed or landed branch with nothing on it has lost its work
+            Some(b) if commits_ahead == 0 && (b.on_remote || landed) => {
+                patterns.push(StuckAgentPattern::WorkingButNoCommits {
+                    agent_id: agent_id.to_string(),
+                    issue: issue.number,
+                })
+            }
+            Some(_) => {}
+        }
+        if landed {
+            patterns.push(StuckAgentPattern::LandedButNotFreed {
+                agent_id: agent_id.to_string(),
+                issue: issue.number,
+            });
+        }
+
+        replayed.push(AgentEvent::Assign {
+            agent_id: agent_id.to_string(),
+            issue: issue.number,
+            // `land` accepts the bare prefix, so a missing branch can be recreated under it
+            branch: branch.map_or_else(
+                || format!("{agent_id}/{}", issue.number),
+                |b| b.name.clone(),
+            ),
+        });
+        if commits_ahead > 0 || landed {
+            replayed.push(AgentEvent::StartWork { commits_ahead });
+        }
+        if landed {
+            replayed.push(AgentEvent::CompleteWork);
+            if let Some(bundle) = facts
+                .bundles
+                .iter()
+                .find(|bundle| bundle.issues.contains(&issue.number))
+            {
+                replayed.push(AgentEvent::Bundle {
+                    bundle_pr: bundle.pr_number,
+                    issues: bundle.issues.clone(),
+                });
+            }
+        }
+    }
+
+    let mut machine = AgentStateMachine::new(agent_id.to_string()).state_machine();
+    for event in &replayed {
+        machine.handle(event);
+    }
+
+    let detected_at = Utc::now();
+    let inconsistencies = patterns
+        .into_iter()
+        .map(|pattern| Inconsistency {
+            agent_id: agent_id.to_string(),
+            pattern,
+            detected_at,
+        })
+        .collect();
+
+    Rehydration {
+        machine,
+        replayed,
+        inconsistencies,
+    }
+}
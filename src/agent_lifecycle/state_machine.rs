@@ -24,6 +24,21 @@ pub enum AgentEvent {
     ForceReset,
 }
 
+impl AgentEvent {
+    /// Short name of the event, as recorded in the lifecycle event log
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AgentEvent::Assign { .. } => "assign",
+            AgentEvent::StartWork { .. } => "start_work",
+            AgentEvent::CompleteWork => "complete_work",
+            AgentEvent::Bundle { .. } => "bundle",
+            AgentEvent::Merge => "merge",
+            AgentEvent::Abandon => "abandon",
+            AgentEvent::ForceReset => "force_reset",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum TransitionError {
     #[error("Validation failed: {reason}")]
@@ -45,12 +60,61 @@ pub struct Inconsistency {
     pub detected_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StuckAgentPattern {
-    LabeledButNoBranch { agent_id: String, issue: u64 },
-    BranchButNoLabel { agent_id: String, branch: String },
-    WorkingButNoCommits { agent_id: String, issue: u64 },
-    LandedButNotFreed { agent_id: String, issue: u64 },
+    LabeledButNoBranch {
+        agent_id: String,
+        issue: u64,
+    },
+    BranchButNoLabel {
+        agent_id: String,
+        branch: String,
+    },
+    WorkingButNoCommits {
+        agent_id: String,
+        issue: u64,
+    },
+    LandedButNotFreed {
+        agent_id: String,
+        issue: u64,
+    },
+    /// More than one open issue carries the agent's label
+    AssignedToSeveralIssues {
+        agent_id: String,
+        issues: Vec<u64>,
+    },
+}
+
+impl std::fmt::Display for StuckAgentPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StuckAgentPattern::LabeledButNoBranch { agent_id, issue } => {
+                write!(f, "Issue #{issue} is labeled {agent_id} but has no branch")
+            }
+            StuckAgentPattern::BranchButNoLabel { agent_id, branch } => {
+                write!(
+                    f,
+                    "Branch {branch} exists but its issue is not labeled {agent_id}"
+                )
+            }
+            StuckAgentPattern::WorkingButNoCommits { agent_id, issue } => write!(
+                f,
+                "{agent_id} has a branch for issue #{issue} with no commits ahead of main"
+            ),
+            StuckAgentPattern::LandedButNotFreed { agent_id, issue } => write!(
+                f,
+                "Issue #{issue} has landed but is still labeled {agent_id}"
+            ),
+            StuckAgentPattern::AssignedToSeveralIssues { agent_id, issues } => {
+                let issues: Vec<String> = issues.iter().map(|i| format!("#{i}")).collect();
+                write!(
+                    f,
+                    "{agent_id} is labeled on several issues: {}",
+                    issues.join(", ")
+                )
+            }
+        }
+    }
 }
 
 #[derive(Default)]
@@ -252,6 +316,20 @@ impl AgentStateMachine {
     }
 }
 
+impl State {
+    /// Name of the state, as recorded in the lifecycle event log
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Idle { .. } => "idle",
+            State::Assigned { .. } => "assigned",
+            State::Working { .. } => "working",
+            State::Landed { .. } => "landed",
+            State::Bundled { .. } => "bundled",
+            State::Merged { .. } => "merged",
+        }
+    }
+}
+
 impl AgentStateMachine {
     fn reset_state(&mut self) {
         self.current_issue = None;
@@ -1,5 +1,7 @@
 // Core types for the agent lifecycle state machine
 
+use serde::{Deserialize, Serialize};
+
 /// Agent states in the lifecycle
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
@@ -22,8 +24,8 @@ pub enum AgentState {
 }
 
 /// Issues detected during pre-flight checks
-#[derive(Debug, Clone, PartialEq)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum PreFlightIssue {
     /// Commits exist but haven't been pushed
     UnpushedCommits { count: u32 },
@@ -40,8 +42,84 @@ pub enum PreFlightIssue {
     },
 }
 
+/// How much a pre-flight issue matters for bottling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreFlightSeverity {
+    /// Blocks bottling unless forced
+    Error,
+    /// Reported, but bottling goes ahead
+    Warning,
+}
+
+/// Automatic fix for a pre-flight issue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreFlightFix {
+    /// Push the branch to the remote
+    Push,
+    /// Rebase the branch onto the base branch
+    Rebase,
+    /// Add missing labels to the issue
+    Relabel { add: Vec<String> },
+}
+
+impl PreFlightIssue {
+    pub fn severity(&self) -> PreFlightSeverity {
+        match self {
+            PreFlightIssue::UnpushedCommits { .. } | PreFlightIssue::BehindMain { .. } => {
+                PreFlightSeverity::Warning
+            }
+            PreFlightIssue::MergeConflicts { .. }
+            | PreFlightIssue::BranchMissing { .. }
+            | PreFlightIssue::LabelMismatch { .. } => PreFlightSeverity::Error,
+        }
+    }
+
+    /// Fix that can be applied without risking work; conflicts and missing
+    /// branches need a person
+    pub fn fix(&self) -> Option<PreFlightFix> {
+        match self {
+            PreFlightIssue::UnpushedCommits { .. } => Some(PreFlightFix::Push),
+            PreFlightIssue::BehindMain { .. } => Some(PreFlightFix::Rebase),
+            PreFlightIssue::LabelMismatch { expected, actual } => Some(PreFlightFix::Relabel {
+                add: expected
+                    .iter()
+                    .filter(|label| !actual.contains(label))
+                    .cloned()
+                    .collect(),
+            }),
+            PreFlightIssue::MergeConflicts { .. } | PreFlightIssue::BranchMissing { .. } => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            PreFlightIssue::UnpushedCommits { count } => {
+                format!("{count} commit(s) not pushed to the remote")
+            }
+            PreFlightIssue::BehindMain { commits } => {
+                format!("{commits} commit(s) behind main")
+            }
+            PreFlightIssue::MergeConflicts { files } => {
+                format!("conflicts with main in {}", files.join(", "))
+            }
+            PreFlightIssue::BranchMissing { branch } => format!("branch {branch} does not exist"),
+            PreFlightIssue::LabelMismatch { expected, actual } => format!(
+                "issue labels [{}] are missing [{}]",
+                actual.join(", "),
+                expected
+                    .iter()
+                    .filter(|label| !actual.contains(label))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
 /// Parse agent branch pattern (agent001/123 or agent001/123-description)
-#[allow(dead_code)]
 pub fn parse_agent_branch(branch: &str) -> Option<(String, u64)> {
     let parts: Vec<&str> = branch.split('/').collect();
     if parts.len() == 2 {
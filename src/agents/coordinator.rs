@@ -1,6 +1,9 @@
 // Agent State Management - GitHub-native coordination
 // Following VERBOTEN rules: GitHub is source of truth, no local state files
 
+use crate::agent_lifecycle::events::EventStore;
+use crate::agent_lifecycle::rehydration::{rehydrate, LifecycleFacts};
+use crate::agent_lifecycle::state_machine::Inconsistency;
 use crate::agent_lifecycle::{AgentEvent, AgentStateMachine};
 #[cfg(feature = "autonomous")]
 use crate::autonomous::CheckpointReason;
@@ -49,6 +52,10 @@ pub struct AgentCoordinator {
     metrics_tracker: MetricsTracker,
     // State machine for the single agent lifecycle
     agent_state_machine: Arc<Mutex<StateMachine<AgentStateMachine>>>,
+    // Append-only log of every event handed to the state machine
+    event_store: EventStore,
+    // Where GitHub and git disagreed when the state machine was rehydrated
+    inconsistencies: Vec<Inconsistency>,
     // Work continuity manager for persistent state across restarts
     #[cfg(feature = "autonomous")]
     work_continuity: Arc<Mutex<Option<WorkContinuityManager>>>,
@@ -66,21 +73,74 @@ impl AgentCoordinator {
         #[cfg(feature = "metrics")]
         let metrics_tracker = MetricsTracker::new();
 
-        // Initialize state machine for the single agent
-        let agent_state_machine = AgentStateMachine::new("agent001".to_string()).state_machine();
+        // Rehydrate the single agent's state machine before it handles any event
+        let (agent_state_machine, inconsistencies) =
+            Self::rehydrate_agent("agent001", &github_client, verbose).await;
+        let current_assignment = agent_state_machine.inner().current_issue();
 
         Ok(Self {
             github_client,
-            current_assignment: Arc::new(Mutex::new(None)),
+            current_assignment: Arc::new(Mutex::new(current_assignment)),
             #[cfg(feature = "metrics")]
             metrics_tracker,
             agent_state_machine: Arc::new(Mutex::new(agent_state_machine)),
+            event_store: EventStore::default(),
+            inconsistencies,
             #[cfg(feature = "autonomous")]
             work_continuity: Arc::new(Mutex::new(None)),
             verbose,
         })
     }
 
+    /// Derive the agent's state from GitHub and git, starting idle if they can't be read
+    async fn rehydrate_agent(
+        agent_id: &str,
+        github_client: &GitHubClient,
+        verbose: bool,
+    ) -> (StateMachine<AgentStateMachine>, Vec<Inconsistency>) {
+        let facts = match git2::Repository::open(".") {
+            Ok(repo) => LifecycleFacts::gather(github_client, &repo).await,
+            Err(e) => Err(e.into()),
+        };
+        let facts = match facts {
+            Ok(facts) => facts,
+            Err(e) => {
+                warn!(
+                    agent_id = %agent_id,
+                    error = %e,
+                    "Could not rehydrate agent state, starting idle"
+                );
+                let machine = AgentStateMachine::new(agent_id.to_string()).state_machine();
+                return (machine, Vec::new());
+            }
+        };
+
+        let rehydration = rehydrate(agent_id, &facts);
+        info!(
+            agent_id = %agent_id,
+            state = rehydration.machine.state().name(),
+            issue = ?rehydration.machine.inner().current_issue(),
+            replayed = ?rehydration.replayed.iter().map(AgentEvent::kind).collect::<Vec<_>>(),
+            "Rehydrated agent state from GitHub and git"
+        );
+        for inconsistency in &rehydration.inconsistencies {
+            warn!(
+                agent_id = %agent_id,
+                pattern = ?inconsistency.pattern,
+                "GitHub and git disagree about agent state"
+            );
+            if verbose {
+                println!("⚠️  {}", inconsistency.pattern);
+            }
+        }
+        (rehydration.machine, rehydration.inconsistencies)
+    }
+
+    /// Disagreements between GitHub and git found when the agent was rehydrated
+    pub fn inconsistencies(&self) -> &[Inconsistency] {
+        &self.inconsistencies
+    }
+
     pub async fn get_available_agents(&self) -> Result<Vec<Agent>, GitHubError> {
         // Single agent system: Check if agent001 is available
         let mut agents = Vec::new();
@@ -227,7 +287,7 @@ impl AgentCoordinator {
                     Some(agent_id),
                     Some(issue_number),
                     &format!("Agent {} not available for assignment (current state: available={})", agent_id, state_machine.inner().is_available()),
-                        execution_start,
+                        _execution_start,
                         false,
                         HashMap::new(),
                     ).await;
@@ -239,11 +299,15 @@ impl AgentCoordinator {
                 }
 
                 // Attempt the state machine transition
-                state_machine.handle(&AgentEvent::Assign {
-                    agent_id: agent_id.to_string(),
-                    issue: issue_number,
-                    branch: branch_name.clone(),
-                });
+                self.event_store.dispatch(
+                    &mut state_machine,
+                    &AgentEvent::Assign {
+                        agent_id: agent_id.to_string(),
+                        issue: issue_number,
+                        branch: branch_name.clone(),
+                    },
+                    Some(&correlation_id),
+                );
 
                 // Verify the transition succeeded
                 if state_machine.inner().current_issue() != Some(issue_number) {
@@ -255,7 +319,7 @@ impl AgentCoordinator {
                         Some(agent_id),
                         Some(issue_number),
                         &format!("State machine transition failed for agent {agent_id}"),
-                        execution_start,
+                        _execution_start,
                         false,
                         HashMap::new(),
                     ).await;
@@ -290,7 +354,7 @@ impl AgentCoordinator {
                     Some(agent_id),
                     Some(issue_number),
                     &format!("Assignment conflict: Agent already assigned to issue #{existing_issue}"),
-                    execution_start,
+                    _execution_start,
                     false,
                     HashMap::new(),
                 ).await;
@@ -332,7 +396,7 @@ impl AgentCoordinator {
                     Some(agent_id),
                     Some(issue_number),
                     &format!("GitHub assignment failed: {e:?}"),
-                    execution_start,
+                    _execution_start,
                     false,
                     metadata,
                 ).await;
@@ -386,7 +450,7 @@ impl AgentCoordinator {
             Some(agent_id),
             Some(issue_number),
             &format!("Successfully assigned agent {agent_id} to issue #{issue_number}"),
-            execution_start,
+            _execution_start,
             true,
             metadata,
         ).await;
@@ -491,11 +555,17 @@ impl AgentCoordinator {
             )));
         }
 
+        let correlation_id = generate_correlation_id();
         let mut state_machine = self.agent_state_machine.lock().await;
-        state_machine.handle(&AgentEvent::CompleteWork);
+        self.event_store.dispatch(
+            &mut state_machine,
+            &AgentEvent::CompleteWork,
+            Some(&correlation_id),
+        );
 
         tracing::info!(
             agent_id = %agent_id,
+            correlation_id = %correlation_id,
             "Agent completed work via state machine"
         );
 
@@ -523,8 +593,13 @@ impl AgentCoordinator {
             )));
         }
 
+        let correlation_id = generate_correlation_id();
         let mut state_machine = self.agent_state_machine.lock().await;
-        state_machine.handle(&AgentEvent::Abandon);
+        self.event_store.dispatch(
+            &mut state_machine,
+            &AgentEvent::Abandon,
+            Some(&correlation_id),
+        );
 
         // Clear internal state tracking
         {
@@ -534,6 +609,7 @@ impl AgentCoordinator {
 
         tracing::info!(
             agent_id = %agent_id,
+            correlation_id = %correlation_id,
             "Agent abandoned work via state machine"
         );
 
@@ -669,20 +745,8 @@ impl AgentCoordinator {
                 preserve_partial_work: config.agents.work_continuity.preserve_partial_work,
             };
 
-            let persistence_config = PersistenceConfig {
-                enable_persistence: continuity_config.enable_continuity,
-                persistence_directory: continuity_config
-                    .state_file_path
-                    .parent()
-                    .unwrap_or(&std::path::PathBuf::from(".my-little-soda"))
-                    .to_path_buf(),
-                auto_save_interval_minutes: continuity_config.backup_interval_minutes,
-                max_state_history_entries: 1000,
-                max_recovery_history_entries: 500,
-                compress_old_states: true,
-                backup_retention_days: 7,
-                enable_integrity_checks: true,
-            };
+            let persistence_config =
+                PersistenceConfig::from_work_continuity(&config.agents.work_continuity);
 
             if !continuity_config.enable_continuity {
                 info!("Work continuity disabled for agent {}", agent_id);
@@ -1 +1,608 @@
-//! This module is currently unused and serves as a placeholder for future work integration functionality.
+//! Merge phase: lands approved bundle and agent PRs and cleans up after them
+//!
+//! A PR opened by the bundler or an agent is merged once it has enough
+//! approvals, no outstanding change requests and green checks. After the merge
+//! the issues it closes are closed, their `route:*` and agent labels removed and
+//! the merged branches deleted, so nothing lingers in the queue. A PR held back
+//! by failing checks gets a comment summarising what failed (see [`crate::ci`]),
+//! and with `autonomous` the recovery the `[recovery]` rules pick for it.
+
+use crate::bundling::types::BundleManifest;
+use crate::bundling::unblocker::{UnblockerTask, BLOCKED_LABEL, UNBLOCKER_LABEL};
+use crate::ci::{ingest_failures, post_summary, FailureParsers};
+use crate::config::MergeConfig;
+use crate::github::pulls::CiStatus;
+use crate::github::types::SafeMergeResult;
+use crate::github::GitHubClient;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// How a PR is merged into the base branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMethod {
+    /// Squash the PR into a single commit
+    #[default]
+    Squash,
+    /// Create a merge commit
+    Merge,
+    /// Rebase the PR commits onto the base branch
+    Rebase,
+}
+
+impl MergeMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Squash => "squash",
+            Self::Merge => "merge",
+            Self::Rebase => "rebase",
+        }
+    }
+}
+
+/// Which automation opened a PR, judged by its head branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrKind {
+    /// `bundle/...` branch assembled from several agent branches
+    Bundle,
+    /// `agent001/123` or `agent001/123-description` branch
+    Agent { agent_id: String, issue_number: u64 },
+}
+
+impl PrKind {
+    /// Classify a head branch; `None` for PRs opened by people
+    pub fn from_branch(head_branch: &str) -> Option<Self> {
+        if head_branch.starts_with("bundle/") {
+            return Some(Self::Bundle);
+        }
+
+        let (agent_id, issue_part) = head_branch.split_once('/')?;
+        if !is_agent_label(agent_id) {
+            return None;
+        }
+        let issue_number = issue_part
+            .split_once('-')
+            .map_or(issue_part, |(number, _)| number)
+            .parse()
+            .ok()?;
+        Some(Self::Agent {
+            agent_id: agent_id.to_string(),
+            issue_number,
+        })
+    }
+}
+
+/// Whether a label (or branch prefix) names an agent, e.g. `agent001`
+pub fn is_agent_label(label: &str) -> bool {
+    label
+        .strip_prefix("agent")
+        .is_some_and(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// An issue a PR will close when merged
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueRef {
+    pub issue_number: u64,
+    /// Agent that did the work, `unknown` if it can't be told from the PR
+    pub agent_id: String,
+    /// Agent branch carrying the work, if it differs from the PR head
+    pub branch_name: Option<String>,
+    pub labels: Vec<String>,
+}
+
+/// Issues closed by a PR: the bundle manifest or agent branch first, then any
+/// `Closes #N` style references in the body
+pub fn pr_issues(kind: &PrKind, body: &str) -> Vec<IssueRef> {
+    let mut issues: Vec<IssueRef> = match kind {
+        PrKind::Bundle => BundleManifest::from_pr_body(body)
+            .map(|manifest| {
+                manifest
+                    .issues
+                    .into_iter()
+                    .map(|issue| IssueRef {
+                        issue_number: issue.issue_number,
+                        agent_id: issue
+                            .branch_name
+                            .split_once('/')
+                            .map_or("unknown", |(agent, _)| agent)
+                            .to_string(),
+                        branch_name: Some(issue.branch_name),
+                        labels: Vec::new(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        PrKind::Agent {
+            agent_id,
+            issue_number,
+        } => vec![IssueRef {
+            issue_number: *issue_number,
+            agent_id: agent_id.clone(),
+            branch_name: None,
+            labels: Vec::new(),
+        }],
+    };
+
+    let default_agent = match kind {
+        PrKind::Agent { agent_id, .. } => agent_id.as_str(),
+        PrKind::Bundle => "unknown",
+    };
+    for issue_number in closing_references(body) {
+        if !issues.iter().any(|i| i.issue_number == issue_number) {
+            issues.push(IssueRef {
+                issue_number,
+                agent_id: default_agent.to_string(),
+                branch_name: None,
+                labels: Vec::new(),
+            });
+        }
+    }
+    issues
+}
+
+/// Issue numbers referenced with GitHub's closing keywords (`Closes #12`, `fixes: #3`)
+pub fn closing_references(body: &str) -> Vec<u64> {
+    static CLOSING: OnceLock<Regex> = OnceLock::new();
+    let pattern = CLOSING.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:close[sd]?|fix(?:e[sd])?|resolve[sd]?):?\s+#(\d+)\b").unwrap()
+    });
+
+    let mut issues = Vec::new();
+    for capture in pattern.captures_iter(body) {
+        if let Ok(issue) = capture[1].parse::<u64>() {
+            if !issues.contains(&issue) {
+                issues.push(issue);
+            }
+        }
+    }
+    issues
+}
+
+/// Labels removed from an issue once its work is merged
+pub fn labels_to_strip(labels: &[String]) -> Vec<String> {
+    labels
+        .iter()
+        .filter(|label| label.starts_with("route:") || is_agent_label(label))
+        .cloned()
+        .collect()
+}
+
+/// An open bundle or agent PR with everything needed to decide whether to merge it
+#[derive(Debug, Clone)]
+pub struct MergeCandidate {
+    pub pr_number: u64,
+    pub title: String,
+    pub head_branch: String,
+    pub head_sha: String,
+    pub kind: PrKind,
+    pub draft: bool,
+    pub mergeable: Option<bool>,
+    pub approvals: usize,
+    pub changes_requested: usize,
+    pub ci: CiStatus,
+    pub labels: Vec<String>,
+    pub issues: Vec<IssueRef>,
+}
+
+/// Whether a candidate can be merged now
+#[derive(Debug, Clone, PartialEq)]
+pub enum Readiness {
+    Ready,
+    /// Not yet; the reason is shown to the user
+    Waiting(String),
+}
+
+impl MergeCandidate {
+    pub fn readiness(&self, config: &MergeConfig) -> Readiness {
+        let waiting = |reason: String| Readiness::Waiting(reason);
+
+        if self.draft {
+            return waiting("draft PR".to_string());
+        }
+        if self.changes_requested > 0 {
+            return waiting(format!(
+                "{} reviewer(s) requested changes",
+                self.changes_requested
+            ));
+        }
+        if self.approvals < config.required_approvals as usize {
+            return waiting(format!(
+                "{}/{} approvals",
+                self.approvals, config.required_approvals
+            ));
+        }
+        match self.ci {
+            CiStatus::Failure => return waiting("checks failing".to_string()),
+            CiStatus::Pending => return waiting("checks still running".to_string()),
+            CiStatus::NoChecks if !config.allow_no_checks => {
+                return waiting("no checks reported".to_string())
+            }
+            CiStatus::Success | CiStatus::NoChecks => {}
+        }
+        match self.mergeable {
+            Some(true) => Readiness::Ready,
+            Some(false) => waiting("merge conflicts with the base branch".to_string()),
+            None => waiting("GitHub is still computing mergeability".to_string()),
+        }
+    }
+
+    /// Method for the first label with a configured override, PR labels before issue labels
+    pub fn merge_method(&self, config: &MergeConfig) -> MergeMethod {
+        self.labels
+            .iter()
+            .chain(self.issues.iter().flat_map(|issue| issue.labels.iter()))
+            .find_map(|label| config.label_methods.get(label).copied())
+            .unwrap_or(config.default_method)
+    }
+
+    /// PR head plus, for bundles, the agent branches it was assembled from
+    pub fn branches_to_delete(&self) -> Vec<String> {
+        let mut branches = vec![self.head_branch.clone()];
+        for branch in self.issues.iter().filter_map(|i| i.branch_name.as_ref()) {
+            if !branches.contains(branch) {
+                branches.push(branch.clone());
+            }
+        }
+        branches
+    }
+}
+
+/// What happened to one PR during a merge pass
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeOutcome {
+    Merged {
+        method: MergeMethod,
+    },
+    /// Dry run: the PR is ready and would be merged this way
+    WouldMerge {
+        method: MergeMethod,
+    },
+    Waiting(String),
+    /// Merge conflicts were found at merge time and a recovery PR opened
+    RecoveryOpened {
+        recovery_pr: u64,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeReport {
+    pub pr_number: u64,
+    pub title: String,
+    pub outcome: MergeOutcome,
+}
+
+/// Print the recovery the `[recovery]` rules pick for a PR's failed checks
+#[cfg(feature = "autonomous")]
+fn report_recovery(pr_number: u64, report: &crate::ci::CiFailureReport) {
+    use crate::autonomous::recovery_rules::describe_strategy;
+    use crate::autonomous::{AutonomousErrorRecovery, RecoveryRules};
+
+    let rules = RecoveryRules::load().unwrap_or_else(|e| {
+        println!("⚠️  Invalid [recovery] rules, using the built-in ones: {e}");
+        RecoveryRules::builtin()
+    });
+    let recovery = AutonomousErrorRecovery::without_client().with_rules(rules);
+    let Some(plan) = report.recovery_plan(&recovery) else {
+        return;
+    };
+    println!(
+        "🩹 Recovery for PR #{pr_number} (rule `{}`): {}",
+        plan.rule,
+        describe_strategy(plan.first())
+    );
+    let jobs = report.workflow_failures();
+    let fixable = jobs.iter().filter(|job| job.auto_fixable).count();
+    println!(
+        "   {fixable} of {} failed job(s) look fixable by the agent",
+        jobs.len()
+    );
+}
+
+/// Merges ready PRs and runs post-merge cleanup
+pub struct WorkIntegrator {
+    client: GitHubClient,
+    config: MergeConfig,
+    dry_run: bool,
+}
+
+impl WorkIntegrator {
+    pub fn new(client: GitHubClient, config: MergeConfig) -> Self {
+        Self {
+            client,
+            config,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Open bundle and agent PRs, optionally narrowed to a single PR
+    pub async fn candidates(&self, only_pr: Option<u64>) -> Result<Vec<MergeCandidate>> {
+        let pulls = self.client.fetch_open_pull_requests().await?;
+
+        let mut candidates = Vec::new();
+        for pr in pulls {
+            if only_pr.is_some_and(|number| number != pr.number) {
+                continue;
+            }
+            let Some(kind) = PrKind::from_branch(&pr.head.ref_field) else {
+                continue;
+            };
+
+            let status = self.client.get_pr_status(pr.number).await?;
+            let mut issues = pr_issues(&kind, pr.body.as_deref().unwrap_or_default());
+            for issue in &mut issues {
+                if let Ok(details) = self.client.fetch_issue(issue.issue_number).await {
+                    issue.labels = details.labels.into_iter().map(|l| l.name).collect();
+                }
+            }
+
+            candidates.push(MergeCandidate {
+                pr_number: pr.number,
+                title: pr.title.clone().unwrap_or_default(),
+                head_branch: pr.head.ref_field.clone(),
+                head_sha: status.head_sha.clone(),
+                kind,
+                draft: pr.draft.unwrap_or(false),
+                mergeable: status.mergeable,
+                approvals: status.approved_reviews,
+                changes_requested: status.requested_changes,
+                ci: status.ci_status,
+                labels: pr
+                    .labels
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|l| l.name)
+                    .collect(),
+                issues,
+            });
+        }
+
+        candidates.sort_by_key(|c| c.pr_number);
+        Ok(candidates)
+    }
+
+    /// Merge every ready candidate once
+    pub async fn run_once(&self, only_pr: Option<u64>) -> Result<Vec<MergeReport>> {
+        let candidates = self.candidates(only_pr).await?;
+        if let Some(number) = only_pr {
+            if candidates.is_empty() {
+                return Err(anyhow!("PR #{} is not an open bundle or agent PR", number));
+            }
+        }
+
+        let mut reports = Vec::new();
+        for candidate in candidates {
+            let outcome = match candidate.readiness(&self.config) {
+                Readiness::Waiting(reason) => {
+                    if candidate.ci == CiStatus::Failure && !self.dry_run {
+                        self.report_ci_failures(&candidate).await;
+                    }
+                    MergeOutcome::Waiting(reason)
+                }
+                Readiness::Ready if self.dry_run => MergeOutcome::WouldMerge {
+                    method: candidate.merge_method(&self.config),
+                },
+                Readiness::Ready => self.merge(&candidate).await,
+            };
+            reports.push(MergeReport {
+                pr_number: candidate.pr_number,
+                title: candidate.title,
+                outcome,
+            });
+        }
+        Ok(reports)
+    }
+
+    async fn merge(&self, candidate: &MergeCandidate) -> MergeOutcome {
+        let method = candidate.merge_method(&self.config);
+        let started = Instant::now();
+
+        let outcome = match &candidate.kind {
+            PrKind::Agent {
+                agent_id,
+                issue_number,
+            } => match self
+                .client
+                .safe_merge_pull_request(
+                    candidate.pr_number,
+                    agent_id,
+                    *issue_number,
+                    Some(method.as_str()),
+                )
+                .await
+            {
+                Ok(SafeMergeResult::SuccessfulMerge { .. }) => MergeOutcome::Merged { method },
+                Ok(SafeMergeResult::ConflictDetected { recovery_pr, .. }) => {
+                    MergeOutcome::RecoveryOpened { recovery_pr }
+                }
+                Ok(SafeMergeResult::MergeFailed { error, .. }) => MergeOutcome::Failed(error),
+                Err(e) => MergeOutcome::Failed(e.to_string()),
+            },
+            PrKind::Bundle => match self
+                .client
+                .merge_pull_request(candidate.pr_number, Some(method.as_str()))
+                .await
+            {
+                Ok(_) => MergeOutcome::Merged { method },
+                Err(e) => MergeOutcome::Failed(e.to_string()),
+            },
+        };
+
+        self.record_attempts(candidate, &outcome, started.elapsed())
+            .await;
+        if matches!(outcome, MergeOutcome::Merged { .. }) {
+            self.clean_up(candidate).await;
+        }
+        outcome
+    }
+
+    /// Summarise why the PR's checks failed in a comment, once per head commit
+    async fn report_ci_failures(&self, candidate: &MergeCandidate) {
+        let report = match ingest_failures(
+            &self.client.actions,
+            &candidate.head_sha,
+            &FailureParsers::default(),
+        )
+        .await
+        {
+            Ok(report) => report,
+            Err(e) => {
+                println!(
+                    "⚠️  Failed to read CI logs for PR #{}: {e}",
+                    candidate.pr_number
+                );
+                return;
+            }
+        };
+        match post_summary(&self.client, candidate.pr_number, &report).await {
+            Ok(true) => println!(
+                "🔴 Posted CI failure summary on PR #{}",
+                candidate.pr_number
+            ),
+            Ok(false) => {}
+            Err(e) => println!("⚠️  Failed to comment on PR #{}: {e}", candidate.pr_number),
+        }
+        #[cfg(feature = "autonomous")]
+        report_recovery(candidate.pr_number, &report);
+    }
+
+    /// Close the merged issues, strip their queue labels and delete merged branches
+    async fn clean_up(&self, candidate: &MergeCandidate) {
+        for issue in &candidate.issues {
+            if let Err(e) = self.client.close_issue(issue.issue_number).await {
+                println!("⚠️  Failed to close issue #{}: {}", issue.issue_number, e);
+            }
+            for label in labels_to_strip(&issue.labels) {
+                if let Err(e) = self
+                    .client
+                    .remove_label_from_issue(issue.issue_number, &label)
+                    .await
+                {
+                    println!(
+                        "⚠️  Failed to remove {} from issue #{}: {}",
+                        label, issue.issue_number, e
+                    );
+                }
+            }
+            if issue.labels.iter().any(|l| l == UNBLOCKER_LABEL) {
+                self.requeue_unblocked(issue.issue_number, candidate.pr_number)
+                    .await;
+            }
+        }
+
+        if !self.config.delete_branches {
+            return;
+        }
+        let repo = git2::Repository::open(".").ok();
+        for branch in candidate.branches_to_delete() {
+            if let Err(e) = self.client.delete_branch(&branch).await {
+                println!("⚠️  Failed to delete remote branch {branch}: {e}");
+            }
+            // The local copy goes too, unless it is checked out
+            if let Some(mut local) = repo
+                .as_ref()
+                .and_then(|r| r.find_branch(&branch, git2::BranchType::Local).ok())
+            {
+                if !local.is_head() {
+                    let _ = local.delete();
+                }
+            }
+        }
+    }
+
+    /// Put the issue an unblocker was opened for back into the bundle queue
+    async fn requeue_unblocked(&self, unblocker_issue: u64, pr_number: u64) {
+        let task = match self.client.fetch_issue(unblocker_issue).await {
+            Ok(issue) => issue
+                .body
+                .as_deref()
+                .and_then(UnblockerTask::from_issue_body),
+            Err(e) => {
+                println!("⚠️  Failed to read unblocker #{unblocker_issue}: {e}");
+                return;
+            }
+        };
+        let Some(task) = task else {
+            // Opened by hand, nothing to re-queue
+            return;
+        };
+
+        let blocked = task.conflicted_branch.issue_number;
+        if let Err(e) = self
+            .client
+            .remove_label_from_issue(blocked, BLOCKED_LABEL)
+            .await
+        {
+            println!("⚠️  Failed to remove {BLOCKED_LABEL} from issue #{blocked}: {e}");
+        }
+        if let Err(e) = self
+            .client
+            .comments
+            .create_issue_comment(blocked, &task.requeue_comment(unblocker_issue, pr_number))
+            .await
+        {
+            println!("⚠️  Failed to comment on issue #{blocked}: {e}");
+        }
+        println!("🔓 Issue #{blocked} re-queued after unblocker #{unblocker_issue}");
+    }
+
+    #[cfg(feature = "metrics")]
+    async fn record_attempts(
+        &self,
+        candidate: &MergeCandidate,
+        outcome: &MergeOutcome,
+        duration: Duration,
+    ) {
+        use crate::metrics::{IntegrationOutcome, IntegrationPhase, MetricsTracker};
+
+        let (result, error) = match outcome {
+            MergeOutcome::Merged { .. } => (IntegrationOutcome::Success, None),
+            MergeOutcome::RecoveryOpened { recovery_pr } => (
+                IntegrationOutcome::Failed,
+                Some(format!("conflicts; recovery PR #{recovery_pr}")),
+            ),
+            MergeOutcome::Failed(error) => (IntegrationOutcome::Failed, Some(error.clone())),
+            MergeOutcome::WouldMerge { .. } | MergeOutcome::Waiting(_) => return,
+        };
+
+        let tracker = MetricsTracker::new();
+        for issue in &candidate.issues {
+            if let Err(e) = tracker
+                .track_integration_attempt(
+                    issue.issue_number,
+                    &issue.agent_id,
+                    IntegrationPhase::Merged,
+                    result.clone(),
+                    Some(duration),
+                    error.clone(),
+                    Some(candidate.pr_number),
+                )
+                .await
+            {
+                println!("⚠️  Failed to record merge metrics: {e}");
+            }
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    async fn record_attempts(
+        &self,
+        _candidate: &MergeCandidate,
+        _outcome: &MergeOutcome,
+        _duration: Duration,
+    ) {
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.config.poll_interval_seconds.max(1))
+    }
+}
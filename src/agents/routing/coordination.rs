@@ -190,7 +190,7 @@ impl RoutingCoordinator {
                         &agent.id,
                         1,
                         1, // Default capacity for single agent
-                        active_issues,
+                        _active_issues,
                         "Working", // Single agent state
                     )
                     .await;
@@ -205,8 +205,8 @@ impl RoutingCoordinator {
                     let _ = self
                         .metrics_tracker
                         .track_routing_metrics(
-                            correlation_id.clone(),
-                            routing_start,
+                            _correlation_id.clone(),
+                            _routing_start,
                             all_issues.len() as u64,
                             available_agents.len() as u64,
                             decision.clone(),
@@ -226,8 +226,8 @@ impl RoutingCoordinator {
                     let _ = self
                         .metrics_tracker
                         .track_routing_metrics(
-                            correlation_id.clone(),
-                            routing_start,
+                            _correlation_id.clone(),
+                            _routing_start,
                             all_issues.len() as u64,
                             0,
                             decision,
@@ -242,8 +242,8 @@ impl RoutingCoordinator {
                     let _ = self
                         .metrics_tracker
                         .track_routing_metrics(
-                            correlation_id.clone(),
-                            routing_start,
+                            _correlation_id.clone(),
+                            _routing_start,
                             all_issues.len() as u64,
                             available_agents.len() as u64,
                             decision,
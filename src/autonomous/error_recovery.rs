@@ -21,6 +21,7 @@ impl<T, E: std::fmt::Display> IntoRecoveryError<T> for Result<T, E> {
         self.map_err(|e| convert_error(e, context))
     }
 }
+use super::recovery_rules::{RecoveryPlan, RecoveryRules};
 use super::workflow_state_machine::{AbandonmentReason, AutonomousWorkflowState};
 
 /// Autonomous error recovery strategies for unattended operation
@@ -190,20 +191,21 @@ pub struct RecoveryMetrics {
 /// Comprehensive autonomous recovery system
 pub struct AutonomousErrorRecovery {
     #[allow(dead_code)]
-    github_client: GitHubClient,
+    github_client: Option<GitHubClient>,
     #[allow(dead_code)]
-    base_recovery: Box<dyn AutomaticRecovery + Send + Sync>,
+    base_recovery: Option<Box<dyn AutomaticRecovery + Send + Sync>>,
     recovery_history: Vec<AutonomousRecoveryAttempt>,
     max_recovery_attempts: u8,
     recovery_timeout_minutes: u32,
     enable_aggressive_recovery: bool,
+    rules: RecoveryRules,
 }
 
 impl std::fmt::Debug for AutonomousErrorRecovery {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AutonomousErrorRecovery")
-            .field("github_client", &"<GitHubClient>")
-            .field("base_recovery", &"<Box<dyn AutomaticRecovery>>")
+            .field("github_client", &self.github_client.is_some())
+            .field("base_recovery", &self.base_recovery.is_some())
             .field("recovery_history", &self.recovery_history)
             .field("max_recovery_attempts", &self.max_recovery_attempts)
             .field("recovery_timeout_minutes", &self.recovery_timeout_minutes)
@@ -211,6 +213,7 @@ impl std::fmt::Debug for AutonomousErrorRecovery {
                 "enable_aggressive_recovery",
                 &self.enable_aggressive_recovery,
             )
+            .field("rules", &self.rules)
             .finish()
     }
 }
@@ -221,12 +224,23 @@ impl AutonomousErrorRecovery {
         base_recovery: Box<dyn AutomaticRecovery + Send + Sync>,
     ) -> Self {
         Self {
-            github_client,
-            base_recovery,
+            github_client: Some(github_client),
+            base_recovery: Some(base_recovery),
+            ..Self::without_client()
+        }
+    }
+
+    /// Recovery that picks strategies and keeps the history without a GitHub
+    /// client, as `run` uses it to decide how the agent tries again
+    pub fn without_client() -> Self {
+        Self {
+            github_client: None,
+            base_recovery: None,
             recovery_history: Vec::new(),
             max_recovery_attempts: 3,
             recovery_timeout_minutes: 30,
             enable_aggressive_recovery: false,
+            rules: RecoveryRules::builtin(),
         }
     }
 
@@ -245,161 +259,43 @@ impl AutonomousErrorRecovery {
         self
     }
 
+    /// Rules deciding the strategy for each error, e.g. from `[recovery]` config
+    pub fn with_rules(mut self, rules: RecoveryRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
     /// Determine recovery strategy for a given error type
     pub fn determine_recovery_strategy(&self, error_type: &ErrorType) -> RecoveryStrategy {
-        match error_type {
-            ErrorType::GitOperationFailed { operation, .. } => match operation.as_str() {
-                "push" | "pull" | "fetch" => RecoveryStrategy::RetryWithBackoff {
-                    max_attempts: 3,
-                    base_delay_ms: 1000,
-                    max_delay_ms: 10000,
-                },
-                "merge" | "rebase" => RecoveryStrategy::AutomatedFix {
-                    fix_type: FixType::MergeConflictResolution,
-                    confidence: ConfidenceLevel::Medium,
-                },
-                _ => RecoveryStrategy::Fallback {
-                    alternative: AlternativeApproach::ManualProcess,
-                },
-            },
-
-            ErrorType::GitHubAPIError { status, .. } => match *status {
-                429 => RecoveryStrategy::RetryWithBackoff {
-                    max_attempts: 5,
-                    base_delay_ms: 2000,
-                    max_delay_ms: 30000,
-                },
-                500..=599 => RecoveryStrategy::RetryWithBackoff {
-                    max_attempts: 3,
-                    base_delay_ms: 5000,
-                    max_delay_ms: 20000,
-                },
-                _ => RecoveryStrategy::Escalate {
-                    urgency: UrgencyLevel::Medium,
-                    context: "GitHub API error requires investigation".to_string(),
-                },
-            },
-
-            ErrorType::MergeConflict {
-                files,
-                conflict_count,
-            } => {
-                if *conflict_count <= 5 && files.iter().all(|f| !f.contains("migration")) {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::MergeConflictResolution,
-                        confidence: ConfidenceLevel::High,
-                    }
-                } else {
-                    RecoveryStrategy::Escalate {
-                        urgency: UrgencyLevel::High,
-                        context: format!("Complex merge conflicts in {} files", files.len()),
-                    }
-                }
-            }
-
-            ErrorType::CIFailure { job, error, .. } => {
-                if error.contains("test") {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::TestFailureFix,
-                        confidence: ConfidenceLevel::Medium,
-                    }
-                } else if error.contains("build") || error.contains("compile") {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::BuildErrorFix,
-                        confidence: ConfidenceLevel::Low,
-                    }
-                } else {
-                    RecoveryStrategy::Escalate {
-                        urgency: UrgencyLevel::Medium,
-                        context: format!("CI failure in {job}: {error}"),
-                    }
-                }
-            }
-
-            ErrorType::TestFailure { failed_tests, .. } => {
-                if failed_tests.len() <= 3 {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::TestFailureFix,
-                        confidence: ConfidenceLevel::Medium,
-                    }
-                } else {
-                    RecoveryStrategy::Fallback {
-                        alternative: AlternativeApproach::SimplifiedSolution,
-                    }
-                }
-            }
-
-            ErrorType::BuildFailure { stage, error } => {
-                if stage == "dependencies" {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::DependencyUpdate,
-                        confidence: ConfidenceLevel::High,
-                    }
-                } else if error.contains("format") || error.contains("lint") {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::CodeFormatting,
-                        confidence: ConfidenceLevel::High,
-                    }
-                } else {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::BuildErrorFix,
-                        confidence: ConfidenceLevel::Low,
-                    }
-                }
-            }
+        self.recovery_plan(error_type).first().clone()
+    }
 
-            ErrorType::DependencyIssue {
-                version_conflict, ..
-            } => {
-                if *version_conflict {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::DependencyUpdate,
-                        confidence: ConfidenceLevel::Medium,
-                    }
-                } else {
-                    RecoveryStrategy::RetryWithBackoff {
-                        max_attempts: 3,
-                        base_delay_ms: 2000,
-                        max_delay_ms: 10000,
-                    }
-                }
-            }
+    /// The rule matching `error_type`, with its strategies and escalation target
+    pub fn recovery_plan(&self, error_type: &ErrorType) -> RecoveryPlan {
+        self.rules.plan_for(error_type)
+    }
 
-            ErrorType::NetworkIssue { timeout, .. } => {
-                if *timeout {
-                    RecoveryStrategy::RetryWithBackoff {
-                        max_attempts: 5,
-                        base_delay_ms: 1000,
-                        max_delay_ms: 15000,
-                    }
-                } else {
-                    RecoveryStrategy::Escalate {
-                        urgency: UrgencyLevel::Low,
-                        context: "Network connectivity issues".to_string(),
-                    }
-                }
-            }
+    /// The strategy for attempt `attempt` under `plan`, counted from 1, or
+    /// `None` once the rule's attempts or `max_recovery_attempts` are used up
+    pub fn strategy_for_attempt(
+        &self,
+        plan: &RecoveryPlan,
+        attempt: u8,
+    ) -> Option<RecoveryStrategy> {
+        plan.strategy(attempt)
+            .filter(|_| attempt <= self.max_recovery_attempts)
+            .cloned()
+    }
 
-            ErrorType::WorkspaceCorruption { files_affected } => {
-                if files_affected.len() <= 5 {
-                    RecoveryStrategy::AutomatedFix {
-                        fix_type: FixType::ConfigurationAdjustment,
-                        confidence: ConfidenceLevel::Medium,
-                    }
-                } else {
-                    RecoveryStrategy::AbandonAndReset {
-                        reason: AbandonmentReason::CriticalFailure {
-                            error: "Workspace corruption".to_string(),
-                        },
-                    }
-                }
-            }
+    /// Record an attempt carried out by the caller rather than by
+    /// `execute_recovery_strategy`
+    pub fn record_attempt(&mut self, attempt: AutonomousRecoveryAttempt) {
+        self.recovery_history.push(attempt);
+    }
 
-            ErrorType::StateInconsistency { .. } => RecoveryStrategy::AutomatedFix {
-                fix_type: FixType::ConfigurationAdjustment,
-                confidence: ConfidenceLevel::High,
-            },
-        }
+    /// Carry on from the history saved with an earlier checkpoint
+    pub fn restore_history(&mut self, history: Vec<AutonomousRecoveryAttempt>) {
+        self.recovery_history = history;
     }
 
     /// Execute automated recovery strategy
@@ -23,6 +23,11 @@
 pub mod error_recovery;
 pub mod integration;
 pub mod persistence;
+pub mod recovery_rules;
+pub mod runner;
+#[cfg(feature = "database")]
+pub mod sqlite_persistence;
+pub mod state_archive;
 pub mod state_validation;
 pub mod work_continuity;
 pub mod workflow_state_machine;
@@ -31,6 +36,7 @@ pub mod workflow_state_machine;
 pub use error_recovery::{
     AutonomousErrorRecovery, AutonomousRecoveryReport, ErrorType, RecoveryStrategy,
 };
+pub use recovery_rules::{ErrorFacts, RecoveryRules};
 pub use workflow_state_machine::{
     AbandonmentReason, AgentId, AutonomousEvent, AutonomousStatusReport, AutonomousWorkflowMachine,
     AutonomousWorkflowState, BlockerType, CIFailure, CompletedWork, ConflictInfo, Issue, Priority,
@@ -1,8 +1,13 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 use thiserror::Error;
 use tokio::fs;
 use tokio::io::AsyncReadExt;
@@ -13,6 +18,8 @@ use super::{
     AutonomousWorkflowState,
 };
 
+pub use crate::config::PersistenceBackend;
+
 /// Errors that can occur during state persistence operations
 #[derive(Debug, Error)]
 pub enum PersistenceError {
@@ -33,6 +40,9 @@ pub enum PersistenceError {
 
     #[error("Recovery failed: {reason}")]
     RecoveryFailed { reason: String },
+
+    #[error("Database error: {reason}")]
+    DatabaseError { reason: String },
 }
 
 /// Complete persistent state for autonomous workflow
@@ -47,6 +57,9 @@ pub struct PersistentWorkflowState {
     pub recovery_history: Vec<AutonomousRecoveryAttempt>,
     pub checkpoint_metadata: CheckpointMetadata,
     pub last_persisted: DateTime<Utc>,
+    /// What pruning dropped from the histories above, if anything
+    #[serde(default)]
+    pub history_summary: Option<HistorySummary>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +81,44 @@ pub enum CheckpointReason {
     UserRequested,
 }
 
+/// Totals for history entries pruned to stay within the configured limits
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistorySummary {
+    pub transitions: usize,
+    /// Pruned transitions by the state they entered
+    pub states_entered: BTreeMap<String, usize>,
+    pub recovery_attempts: usize,
+    pub successful_recoveries: usize,
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+}
+
+impl HistorySummary {
+    fn record_time(&mut self, at: DateTime<Utc>) {
+        self.earliest = Some(self.earliest.map_or(at, |earliest| earliest.min(at)));
+        self.latest = Some(self.latest.map_or(at, |latest| latest.max(at)));
+    }
+
+    pub fn add_transitions(&mut self, records: &[StateTransitionRecord]) {
+        for record in records {
+            self.transitions += 1;
+            let state = state_type_name(&record.to_state).unwrap_or_else(|| "Unknown".to_string());
+            *self.states_entered.entry(state).or_default() += 1;
+            self.record_time(record.timestamp);
+        }
+    }
+
+    pub fn add_recoveries(&mut self, attempts: &[AutonomousRecoveryAttempt]) {
+        for attempt in attempts {
+            self.recovery_attempts += 1;
+            if attempt.success {
+                self.successful_recoveries += 1;
+            }
+            self.record_time(attempt.started_at);
+        }
+    }
+}
+
 /// Configuration for state persistence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistenceConfig {
@@ -79,6 +130,42 @@ pub struct PersistenceConfig {
     pub compress_old_states: bool,
     pub backup_retention_days: u32,
     pub enable_integrity_checks: bool,
+    #[serde(default)]
+    pub backend: PersistenceBackend,
+    /// Most checkpoints kept per agent, newest first; 0 keeps them all
+    #[serde(default = "default_max_checkpoints_per_agent")]
+    pub max_checkpoints_per_agent: usize,
+    /// Newest checkpoints left uncompressed when `compress_old_states` is set
+    #[serde(default = "default_uncompressed_checkpoints")]
+    pub uncompressed_checkpoints: usize,
+}
+
+fn default_max_checkpoints_per_agent() -> usize {
+    50
+}
+
+fn default_uncompressed_checkpoints() -> usize {
+    3
+}
+
+impl PersistenceConfig {
+    /// Persistence for the `[agents.work_continuity]` configuration, next to its state file
+    pub fn from_work_continuity(config: &crate::config::WorkContinuityConfig) -> Self {
+        Self {
+            enable_persistence: config.enable_continuity,
+            persistence_directory: Path::new(&config.state_file_path)
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new(".my-little-soda"))
+                .to_path_buf(),
+            auto_save_interval_minutes: config.backup_interval_minutes,
+            compress_old_states: config.compress_old_states,
+            backup_retention_days: config.backup_retention_days,
+            backend: config.persistence_backend,
+            max_checkpoints_per_agent: config.max_checkpoints_per_agent,
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for PersistenceConfig {
@@ -92,6 +179,9 @@ impl Default for PersistenceConfig {
             compress_old_states: true,
             backup_retention_days: 7,
             enable_integrity_checks: true,
+            backend: PersistenceBackend::default(),
+            max_checkpoints_per_agent: default_max_checkpoints_per_agent(),
+            uncompressed_checkpoints: default_uncompressed_checkpoints(),
         }
     }
 }
@@ -132,8 +222,14 @@ pub trait StatePersistence {
         agent_id: &str,
     ) -> Result<Vec<CheckpointInfo>, PersistenceError>;
 
-    /// Clean up old checkpoints and state files
-    async fn cleanup_old_data(&self, agent_id: &str) -> Result<(), PersistenceError>;
+    /// Agents with a saved state or checkpoints
+    async fn list_agents(&self) -> Result<Vec<String>, PersistenceError>;
+
+    /// What retention would do to an agent's checkpoints, without doing it
+    async fn plan_cleanup(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError>;
+
+    /// Apply checkpoint retention and compression, returning what was done
+    async fn cleanup_old_data(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError>;
 
     /// Verify state integrity
     async fn verify_integrity(
@@ -151,7 +247,7 @@ pub struct CheckpointInfo {
     pub file_size: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StateSummary {
     pub current_state_type: Option<String>,
     pub transitions_count: usize,
@@ -159,6 +255,252 @@ pub struct StateSummary {
     pub uptime_minutes: Option<i64>,
 }
 
+impl StateSummary {
+    pub fn from_state(state: &PersistentWorkflowState) -> Self {
+        Self {
+            current_state_type: state.current_state.as_ref().and_then(state_type_name),
+            transitions_count: state.state_history.len(),
+            recovery_attempts: state.recovery_history.len(),
+            uptime_minutes: state
+                .start_time
+                .map(|start| (state.last_persisted - start).num_minutes()),
+        }
+    }
+}
+
+/// A checkpoint as retention sees it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointSize {
+    pub checkpoint_id: String,
+    pub created_at: DateTime<Utc>,
+    pub bytes: u64,
+    pub compressed: bool,
+}
+
+/// What retention does to one agent's checkpoints
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CleanupPlan {
+    pub agent_id: String,
+    /// Older than `backup_retention_days`
+    pub expired: Vec<CheckpointSize>,
+    /// Beyond the newest `max_checkpoints_per_agent`
+    pub over_limit: Vec<CheckpointSize>,
+    /// Old enough to be archived compressed
+    pub to_compress: Vec<CheckpointSize>,
+    /// Size of the compressed archives, once compressed
+    pub compressed_bytes: Option<u64>,
+}
+
+impl CleanupPlan {
+    pub fn is_empty(&self) -> bool {
+        self.expired.is_empty() && self.over_limit.is_empty() && self.to_compress.is_empty()
+    }
+
+    /// Checkpoints to delete, expired or over the limit
+    pub fn deleted(&self) -> impl Iterator<Item = &CheckpointSize> {
+        self.expired.iter().chain(&self.over_limit)
+    }
+
+    pub fn deleted_bytes(&self) -> u64 {
+        self.deleted().map(|c| c.bytes).sum()
+    }
+
+    pub fn compress_bytes(&self) -> u64 {
+        self.to_compress.iter().map(|c| c.bytes).sum()
+    }
+}
+
+/// Decide what retention does with an agent's checkpoints, listed newest first
+///
+/// A retention of 0 days keeps checkpoints regardless of age. Backends that
+/// cannot compress pass `can_compress: false`.
+pub(crate) fn plan_retention(
+    config: &PersistenceConfig,
+    agent_id: &str,
+    checkpoints: Vec<CheckpointSize>,
+    can_compress: bool,
+    now: DateTime<Utc>,
+) -> CleanupPlan {
+    let cutoff = (config.backup_retention_days > 0)
+        .then(|| now - chrono::Duration::days(config.backup_retention_days as i64));
+    let mut plan = CleanupPlan {
+        agent_id: agent_id.to_string(),
+        ..CleanupPlan::default()
+    };
+    for (index, checkpoint) in checkpoints.into_iter().enumerate() {
+        if cutoff.is_some_and(|cutoff| checkpoint.created_at < cutoff) {
+            plan.expired.push(checkpoint);
+        } else if config.max_checkpoints_per_agent > 0 && index >= config.max_checkpoints_per_agent
+        {
+            plan.over_limit.push(checkpoint);
+        } else if can_compress
+            && config.compress_old_states
+            && index >= config.uncompressed_checkpoints
+            && !checkpoint.compressed
+        {
+            plan.to_compress.push(checkpoint);
+        }
+    }
+    plan
+}
+
+/// Variant name of a workflow state, e.g. `InProgress`
+fn state_type_name(state: &AutonomousWorkflowState) -> Option<String> {
+    match serde_json::to_value(state).ok()? {
+        serde_json::Value::String(name) => Some(name),
+        serde_json::Value::Object(fields) => fields.keys().next().cloned(),
+        _ => None,
+    }
+}
+
+/// Unique enough identifier for a checkpoint
+pub(crate) fn new_checkpoint_id() -> String {
+    format!("{}_{}", Utc::now().timestamp(), rand::rng().random::<u32>())
+}
+
+/// Metadata for a checkpoint taken by this process
+pub(crate) fn checkpoint_metadata(
+    checkpoint_id: &str,
+    reason: CheckpointReason,
+    integrity_hash: String,
+) -> CheckpointMetadata {
+    CheckpointMetadata {
+        checkpoint_id: checkpoint_id.to_string(),
+        creation_reason: reason,
+        integrity_hash,
+        agent_pid: std::process::id().into(),
+        hostname: hostname::get()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+    }
+}
+
+/// Calculate integrity hash for state
+pub(crate) fn calculate_integrity_hash(state: &PersistentWorkflowState) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+
+    // Hash key fields that shouldn't change unexpectedly
+    state.agent_id.hash(&mut hasher);
+    state.version.hash(&mut hasher);
+    state.state_history.len().hash(&mut hasher);
+    state.recovery_history.len().hash(&mut hasher);
+
+    if let Some(start_time) = state.start_time {
+        start_time.timestamp().hash(&mut hasher);
+    }
+
+    format!("{:x}", hasher.finish())
+}
+
+/// Prune state history to stay within limits, summarizing what is dropped
+pub(crate) fn prune_state_history(config: &PersistenceConfig, state: &mut PersistentWorkflowState) {
+    if state.state_history.len() > config.max_state_history_entries {
+        let excess = state.state_history.len() - config.max_state_history_entries;
+        let pruned: Vec<_> = state.state_history.drain(0..excess).collect();
+        state
+            .history_summary
+            .get_or_insert_with(HistorySummary::default)
+            .add_transitions(&pruned);
+        info!(
+            agent_id = %state.agent_id,
+            pruned = %excess,
+            remaining = %state.state_history.len(),
+            "Pruned excess state history entries"
+        );
+    }
+
+    if state.recovery_history.len() > config.max_recovery_history_entries {
+        let excess = state.recovery_history.len() - config.max_recovery_history_entries;
+        let pruned: Vec<_> = state.recovery_history.drain(0..excess).collect();
+        state
+            .history_summary
+            .get_or_insert_with(HistorySummary::default)
+            .add_recoveries(&pruned);
+        info!(
+            agent_id = %state.agent_id,
+            pruned = %excess,
+            remaining = %state.recovery_history.len(),
+            "Pruned excess recovery history entries"
+        );
+    }
+}
+
+/// Persistence for the configured backend
+pub fn create_persistence(config: &PersistenceConfig) -> Arc<dyn StatePersistence + Send + Sync> {
+    match config.backend {
+        PersistenceBackend::Filesystem => Arc::new(FileSystemPersistence::new(config.clone())),
+        #[cfg(feature = "database")]
+        PersistenceBackend::Sqlite => Arc::new(super::sqlite_persistence::SqlitePersistence::new(
+            config.clone(),
+        )),
+        #[cfg(not(feature = "database"))]
+        PersistenceBackend::Sqlite => {
+            warn!("SQLite persistence needs the database feature; using files instead");
+            Arc::new(FileSystemPersistence::new(config.clone()))
+        }
+    }
+}
+
+const CHECKPOINT_SUFFIX: &str = ".checkpoint.json";
+const COMPRESSED_CHECKPOINT_SUFFIX: &str = ".checkpoint.json.gz";
+
+/// A checkpoint file, plain or gzip-compressed
+struct CheckpointFile {
+    checkpoint_id: String,
+    path: PathBuf,
+    compressed: bool,
+    modified: SystemTime,
+    size: u64,
+}
+
+impl CheckpointFile {
+    fn size_info(&self) -> CheckpointSize {
+        CheckpointSize {
+            checkpoint_id: self.checkpoint_id.clone(),
+            created_at: DateTime::<Utc>::from(self.modified),
+            bytes: self.size,
+            compressed: self.compressed,
+        }
+    }
+
+    async fn read(&self) -> Result<String, PersistenceError> {
+        let bytes = fs::read(&self.path).await?;
+        let mut contents = String::new();
+        if self.compressed {
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut contents)?;
+        } else {
+            contents = String::from_utf8(bytes).map_err(|e| PersistenceError::StateCorruption {
+                reason: format!("Checkpoint is not valid UTF-8: {e}"),
+            })?;
+        }
+        Ok(contents)
+    }
+
+    /// Replace the file with a compressed copy, returning its size
+    async fn compress(&self) -> Result<u64, PersistenceError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&fs::read(&self.path).await?)?;
+        let compressed = encoder.finish()?;
+
+        let target = self.path.with_file_name(format!(
+            "{}{COMPRESSED_CHECKPOINT_SUFFIX}",
+            self.checkpoint_id
+        ));
+        fs::write(&target, &compressed).await?;
+        // Checkpoints are aged and ordered by modification time, so keep it
+        std::fs::File::options()
+            .write(true)
+            .open(&target)?
+            .set_modified(self.modified)?;
+        fs::remove_file(&self.path).await?;
+        Ok(compressed.len() as u64)
+    }
+}
+
 /// File system implementation of state persistence
 pub struct FileSystemPersistence {
     config: PersistenceConfig,
@@ -186,69 +528,53 @@ impl FileSystemPersistence {
     /// Get the checkpoint file path
     fn get_checkpoint_file_path(&self, agent_id: &str, checkpoint_id: &str) -> PathBuf {
         self.get_checkpoint_dir(agent_id)
-            .join(format!("{checkpoint_id}.checkpoint.json"))
-    }
-
-    /// Ensure directories exist
-    async fn ensure_directories(&self, agent_id: &str) -> Result<(), PersistenceError> {
-        fs::create_dir_all(&self.config.persistence_directory).await?;
-        fs::create_dir_all(self.get_checkpoint_dir(agent_id)).await?;
-        Ok(())
+            .join(format!("{checkpoint_id}{CHECKPOINT_SUFFIX}"))
     }
 
-    /// Calculate integrity hash for state
-    fn calculate_integrity_hash(&self, state: &PersistentWorkflowState) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-
-        // Hash key fields that shouldn't change unexpectedly
-        state.agent_id.hash(&mut hasher);
-        state.version.hash(&mut hasher);
-        state.state_history.len().hash(&mut hasher);
-        state.recovery_history.len().hash(&mut hasher);
-
-        if let Some(start_time) = state.start_time {
-            start_time.timestamp().hash(&mut hasher);
+    /// Checkpoint files of an agent, newest first
+    async fn checkpoint_files(
+        &self,
+        agent_id: &str,
+    ) -> Result<Vec<CheckpointFile>, PersistenceError> {
+        let checkpoint_dir = self.get_checkpoint_dir(agent_id);
+        if !checkpoint_dir.exists() {
+            return Ok(vec![]);
         }
 
-        format!("{:x}", hasher.finish())
-    }
-
-    /// Compress old state data if enabled
-    async fn compress_if_needed(&self, _file_path: &Path) -> Result<(), PersistenceError> {
-        // In a real implementation, this would compress old files
-        // For now, just log that compression would happen
-        if self.config.compress_old_states {
-            debug!("Would compress old state file: {:?}", _file_path);
+        let mut files = Vec::new();
+        let mut entries = fs::read_dir(&checkpoint_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (checkpoint_id, compressed) =
+                if let Some(id) = name.strip_suffix(COMPRESSED_CHECKPOINT_SUFFIX) {
+                    (id.to_string(), true)
+                } else if let Some(id) = name.strip_suffix(CHECKPOINT_SUFFIX) {
+                    (id.to_string(), false)
+                } else {
+                    continue;
+                };
+            let metadata = entry.metadata().await?;
+            files.push(CheckpointFile {
+                checkpoint_id,
+                path: entry.path(),
+                compressed,
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                size: metadata.len(),
+            });
         }
-        Ok(())
+        files.sort_by(|a, b| {
+            b.modified
+                .cmp(&a.modified)
+                .then_with(|| b.checkpoint_id.cmp(&a.checkpoint_id))
+        });
+        Ok(files)
     }
 
-    /// Prune state history to stay within limits
-    fn prune_state_history(&self, state: &mut PersistentWorkflowState) {
-        if state.state_history.len() > self.config.max_state_history_entries {
-            let excess = state.state_history.len() - self.config.max_state_history_entries;
-            state.state_history.drain(0..excess);
-            info!(
-                agent_id = %state.agent_id,
-                pruned = %excess,
-                remaining = %state.state_history.len(),
-                "Pruned excess state history entries"
-            );
-        }
-
-        if state.recovery_history.len() > self.config.max_recovery_history_entries {
-            let excess = state.recovery_history.len() - self.config.max_recovery_history_entries;
-            state.recovery_history.drain(0..excess);
-            info!(
-                agent_id = %state.agent_id,
-                pruned = %excess,
-                remaining = %state.recovery_history.len(),
-                "Pruned excess recovery history entries"
-            );
-        }
+    /// Ensure directories exist
+    async fn ensure_directories(&self, agent_id: &str) -> Result<(), PersistenceError> {
+        fs::create_dir_all(&self.config.persistence_directory).await?;
+        fs::create_dir_all(self.get_checkpoint_dir(agent_id)).await?;
+        Ok(())
     }
 }
 
@@ -269,27 +595,19 @@ impl StatePersistence for FileSystemPersistence {
         state_to_save.last_persisted = Utc::now();
 
         // Prune history if needed
-        self.prune_state_history(&mut state_to_save);
+        prune_state_history(&self.config, &mut state_to_save);
 
         // Calculate integrity hash
         let integrity_hash = if self.config.enable_integrity_checks {
-            self.calculate_integrity_hash(&state_to_save)
+            calculate_integrity_hash(&state_to_save)
         } else {
             "integrity_disabled".to_string()
         };
 
         // Update metadata
-        let checkpoint_id = format!("{}_{}", Utc::now().timestamp(), rand::rng().random::<u32>());
-        state_to_save.checkpoint_metadata = CheckpointMetadata {
-            checkpoint_id: checkpoint_id.clone(),
-            creation_reason: reason.clone(),
-            integrity_hash,
-            agent_pid: std::process::id().into(),
-            hostname: hostname::get()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string(),
-        };
+        let checkpoint_id = new_checkpoint_id();
+        state_to_save.checkpoint_metadata =
+            checkpoint_metadata(&checkpoint_id, reason.clone(), integrity_hash);
 
         let state_file = self.get_state_file_path(&state.agent_id);
         let serialized = serde_json::to_string_pretty(&state_to_save)?;
@@ -299,9 +617,6 @@ impl StatePersistence for FileSystemPersistence {
         fs::write(&temp_file, serialized).await?;
         fs::rename(&temp_file, &state_file).await?;
 
-        // Compress old data if needed
-        self.compress_if_needed(&state_file).await?;
-
         info!(
             agent_id = %state.agent_id,
             checkpoint_id = %checkpoint_id,
@@ -366,20 +681,15 @@ impl StatePersistence for FileSystemPersistence {
 
         self.ensure_directories(&state.agent_id).await?;
 
-        let checkpoint_id = format!("{}_{}", Utc::now().timestamp(), rand::rng().random::<u32>());
+        let checkpoint_id = new_checkpoint_id();
         let checkpoint_file = self.get_checkpoint_file_path(&state.agent_id, &checkpoint_id);
 
         let mut checkpoint_state = state.clone();
-        checkpoint_state.checkpoint_metadata = CheckpointMetadata {
-            checkpoint_id: checkpoint_id.clone(),
-            creation_reason: reason.clone(),
-            integrity_hash: self.calculate_integrity_hash(&checkpoint_state),
-            agent_pid: std::process::id().into(),
-            hostname: hostname::get()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string(),
-        };
+        checkpoint_state.checkpoint_metadata = checkpoint_metadata(
+            &checkpoint_id,
+            reason.clone(),
+            calculate_integrity_hash(&checkpoint_state),
+        );
 
         let serialized = serde_json::to_string_pretty(&checkpoint_state)?;
         fs::write(&checkpoint_file, serialized).await?;
@@ -392,6 +702,11 @@ impl StatePersistence for FileSystemPersistence {
             "Checkpoint created successfully"
         );
 
+        // Every new checkpoint ages the others, so apply retention right away
+        if let Err(e) = self.cleanup_old_data(&state.agent_id).await {
+            warn!(agent_id = %state.agent_id, error = %e, "Checkpoint retention failed");
+        }
+
         Ok(checkpoint_id)
     }
 
@@ -400,18 +715,18 @@ impl StatePersistence for FileSystemPersistence {
         agent_id: &str,
         checkpoint_id: &str,
     ) -> Result<PersistentWorkflowState, PersistenceError> {
-        let checkpoint_file = self.get_checkpoint_file_path(agent_id, checkpoint_id);
-
-        if !checkpoint_file.exists() {
-            return Err(PersistenceError::IoError(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Checkpoint {checkpoint_id} not found for agent {agent_id}"),
-            )));
-        }
-
-        let mut file = fs::File::open(&checkpoint_file).await?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).await?;
+        let checkpoint_file = self
+            .checkpoint_files(agent_id)
+            .await?
+            .into_iter()
+            .find(|file| file.checkpoint_id == checkpoint_id)
+            .ok_or_else(|| {
+                PersistenceError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("Checkpoint {checkpoint_id} not found for agent {agent_id}"),
+                ))
+            })?;
+        let contents = checkpoint_file.read().await?;
 
         let state: PersistentWorkflowState = serde_json::from_str(&contents)?;
 
@@ -434,100 +749,135 @@ impl StatePersistence for FileSystemPersistence {
         &self,
         agent_id: &str,
     ) -> Result<Vec<CheckpointInfo>, PersistenceError> {
-        let checkpoint_dir = self.get_checkpoint_dir(agent_id);
+        let mut checkpoints = Vec::new();
+        for file in self.checkpoint_files(agent_id).await? {
+            // Reason and summary come from the checkpoint itself when it can be read
+            let state = file.read().await.ok().and_then(|contents| {
+                serde_json::from_str::<PersistentWorkflowState>(&contents).ok()
+            });
+            checkpoints.push(CheckpointInfo {
+                checkpoint_id: file.checkpoint_id.clone(),
+                creation_time: DateTime::<Utc>::from(file.modified),
+                reason: state
+                    .as_ref()
+                    .map(|s| s.checkpoint_metadata.creation_reason.clone())
+                    .unwrap_or(CheckpointReason::PeriodicSave),
+                state_summary: state
+                    .as_ref()
+                    .map(StateSummary::from_state)
+                    .unwrap_or_default(),
+                file_size: file.size,
+            });
+        }
 
-        if !checkpoint_dir.exists() {
+        Ok(checkpoints)
+    }
+
+    async fn list_agents(&self) -> Result<Vec<String>, PersistenceError> {
+        let directory = &self.config.persistence_directory;
+        if !directory.exists() {
             return Ok(vec![]);
         }
 
-        let mut checkpoints = Vec::new();
-        let mut entries = fs::read_dir(&checkpoint_dir).await?;
-
+        let mut agents = BTreeSet::new();
+        let mut entries = fs::read_dir(directory).await?;
         while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    if stem.ends_with(".checkpoint") {
-                        let checkpoint_id = stem.trim_end_matches(".checkpoint");
-
-                        // Load minimal info for the checkpoint
-                        if let Ok(metadata) = fs::metadata(&path).await {
-                            // For full implementation, would parse the file to get detailed info
-                            let info = CheckpointInfo {
-                                checkpoint_id: checkpoint_id.to_string(),
-                                creation_time: metadata
-                                    .created()
-                                    .or_else(|_| metadata.modified())
-                                    .map(DateTime::<Utc>::from)
-                                    .unwrap_or_else(|_| Utc::now()),
-                                reason: CheckpointReason::PeriodicSave, // Would be loaded from file
-                                state_summary: StateSummary {
-                                    current_state_type: None,
-                                    transitions_count: 0,
-                                    recovery_attempts: 0,
-                                    uptime_minutes: None,
-                                },
-                                file_size: metadata.len(),
-                            };
-                            checkpoints.push(info);
-                        }
-                    }
-                }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(agent_id) = name
+                .strip_suffix(".state.json")
+                .or_else(|| name.strip_suffix("_checkpoints"))
+            {
+                agents.insert(agent_id.to_string());
             }
         }
+        Ok(agents.into_iter().collect())
+    }
 
-        // Sort by creation time, newest first
-        checkpoints.sort_by(|a, b| b.creation_time.cmp(&a.creation_time));
-
-        Ok(checkpoints)
+    async fn plan_cleanup(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError> {
+        let files = self.checkpoint_files(agent_id).await?;
+        Ok(plan_retention(
+            &self.config,
+            agent_id,
+            files.iter().map(CheckpointFile::size_info).collect(),
+            true,
+            Utc::now(),
+        ))
     }
 
-    async fn cleanup_old_data(&self, agent_id: &str) -> Result<(), PersistenceError> {
-        let retention_duration = chrono::Duration::days(self.config.backup_retention_days as i64);
-        let cutoff_time = Utc::now() - retention_duration;
+    async fn cleanup_old_data(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError> {
+        let files = self.checkpoint_files(agent_id).await?;
+        let planned = plan_retention(
+            &self.config,
+            agent_id,
+            files.iter().map(CheckpointFile::size_info).collect(),
+            true,
+            Utc::now(),
+        );
+        let file_for = |checkpoint: &CheckpointSize| {
+            files
+                .iter()
+                .find(|file| file.checkpoint_id == checkpoint.checkpoint_id)
+        };
 
-        let checkpoint_dir = self.get_checkpoint_dir(agent_id);
-        if !checkpoint_dir.exists() {
-            return Ok(());
+        // Only report what actually happened
+        let mut done = CleanupPlan {
+            agent_id: agent_id.to_string(),
+            ..CleanupPlan::default()
+        };
+        for (checkpoints, removed) in [
+            (&planned.expired, &mut done.expired),
+            (&planned.over_limit, &mut done.over_limit),
+        ] {
+            for checkpoint in checkpoints {
+                let Some(file) = file_for(checkpoint) else {
+                    continue;
+                };
+                match fs::remove_file(&file.path).await {
+                    Ok(()) => {
+                        debug!(file = ?file.path, "Removed old checkpoint file");
+                        removed.push(checkpoint.clone());
+                    }
+                    Err(e) => warn!(
+                        file = ?file.path,
+                        error = %e,
+                        "Failed to remove old checkpoint file"
+                    ),
+                }
+            }
         }
 
-        let mut entries = fs::read_dir(&checkpoint_dir).await?;
-        let mut cleaned_count = 0;
-
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if let Ok(metadata) = fs::metadata(&path).await {
-                let file_time = metadata
-                    .created()
-                    .or_else(|_| metadata.modified())
-                    .map(DateTime::<Utc>::from)
-                    .unwrap_or_else(|_| Utc::now());
-
-                if file_time < cutoff_time {
-                    if let Err(e) = fs::remove_file(&path).await {
-                        warn!(
-                            file = ?path,
-                            error = %e,
-                            "Failed to remove old checkpoint file"
-                        );
-                    } else {
-                        cleaned_count += 1;
-                        debug!(file = ?path, "Removed old checkpoint file");
-                    }
+        let mut compressed_bytes = 0;
+        for checkpoint in &planned.to_compress {
+            let Some(file) = file_for(checkpoint) else {
+                continue;
+            };
+            match file.compress().await {
+                Ok(bytes) => {
+                    compressed_bytes += bytes;
+                    done.to_compress.push(checkpoint.clone());
                 }
+                Err(e) => warn!(
+                    file = ?file.path,
+                    error = %e,
+                    "Failed to compress checkpoint file"
+                ),
             }
         }
+        if !done.to_compress.is_empty() {
+            done.compressed_bytes = Some(compressed_bytes);
+        }
 
-        if cleaned_count > 0 {
+        if !done.is_empty() {
             info!(
                 agent_id = %agent_id,
-                cleaned_count = %cleaned_count,
+                removed = done.expired.len() + done.over_limit.len(),
+                compressed = done.to_compress.len(),
                 retention_days = %self.config.backup_retention_days,
-                "Cleaned up old checkpoint files"
+                "Applied checkpoint retention"
             );
         }
 
-        Ok(())
+        Ok(done)
     }
 
     async fn verify_integrity(
@@ -539,7 +889,7 @@ impl StatePersistence for FileSystemPersistence {
         }
 
         let expected_hash = &state.checkpoint_metadata.integrity_hash;
-        let actual_hash = self.calculate_integrity_hash(state);
+        let actual_hash = calculate_integrity_hash(state);
 
         let is_valid = expected_hash == &actual_hash;
 
@@ -558,16 +908,22 @@ impl StatePersistence for FileSystemPersistence {
 
 /// High-level state persistence manager
 pub struct StatePersistenceManager {
-    persistence: Box<dyn StatePersistence + Send + Sync>,
+    persistence: Arc<dyn StatePersistence + Send + Sync>,
     config: PersistenceConfig,
     auto_save_handle: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl StatePersistenceManager {
-    /// Create new state persistence manager
+    /// Create new state persistence manager for the configured backend
     pub fn new(config: PersistenceConfig) -> Self {
-        let persistence = Box::new(FileSystemPersistence::new(config.clone()));
+        Self::with_persistence(create_persistence(&config), config)
+    }
 
+    /// Create a manager around an existing persistence implementation
+    pub fn with_persistence(
+        persistence: Arc<dyn StatePersistence + Send + Sync>,
+        config: PersistenceConfig,
+    ) -> Self {
         Self {
             persistence,
             config,
@@ -587,8 +943,8 @@ impl StatePersistenceManager {
         let interval_duration =
             tokio::time::Duration::from_secs(self.config.auto_save_interval_minutes as u64 * 60);
 
-        // Create a shared reference to persistence that can be moved into the async task
-        let persistence = Box::new(FileSystemPersistence::new(self.config.clone()));
+        // Share the persistence with the async task
+        let persistence = self.persistence.clone();
 
         let handle = tokio::spawn(async move {
             let mut interval = tokio::time::interval(interval_duration);
@@ -705,7 +1061,15 @@ impl StatePersistenceManager {
         self.persistence.list_checkpoints(agent_id).await
     }
 
-    pub async fn cleanup_old_data(&self, agent_id: &str) -> Result<(), PersistenceError> {
+    pub async fn list_agents(&self) -> Result<Vec<String>, PersistenceError> {
+        self.persistence.list_agents().await
+    }
+
+    pub async fn plan_cleanup(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError> {
+        self.persistence.plan_cleanup(agent_id).await
+    }
+
+    pub async fn cleanup_old_data(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError> {
         self.persistence.cleanup_old_data(agent_id).await
     }
 
@@ -747,6 +1111,7 @@ mod tests {
                 hostname: "test-host".to_string(),
             },
             last_persisted: Utc::now(),
+            history_summary: None,
         }
     }
 
@@ -0,0 +1,828 @@
+//! Declarative rules mapping errors to recovery strategies
+//!
+//! An [`ErrorType`] is reduced to [`ErrorFacts`]: its kind, what failed
+//! (the subject), the message, a status code, the files involved and a count.
+//! Rules from `[[recovery.rules]]` are checked against those facts in order,
+//! followed by the built-in rules below, and the first rule whose conditions
+//! all hold decides the strategies to try, how often, and who hears about it
+//! when they run out.
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+use super::error_recovery::{
+    AlternativeApproach, ConfidenceLevel, ErrorType, FixType, RecoveryStrategy, UrgencyLevel,
+};
+use super::workflow_state_machine::AbandonmentReason;
+use crate::bundling::path_policy::glob_to_regex;
+use crate::config::{
+    EscalationConfig, RecoveryActionConfig, RecoveryConfig, RecoveryRuleConfig, StatusCodeMatch,
+};
+
+/// Error kinds rules match on, one per [`ErrorType`] variant
+pub const ERROR_KINDS: &[&str] = &[
+    "git_operation",
+    "github_api",
+    "merge_conflict",
+    "ci_failure",
+    "test_failure",
+    "build_failure",
+    "dependency_issue",
+    "network_issue",
+    "workspace_corruption",
+    "state_inconsistency",
+];
+
+/// The mapping the workflow used before rules were configurable
+const BUILT_IN_RULES: &str = r#"
+[[rules]]
+name = "git-sync-retry"
+error_type = "git_operation"
+subject = "^(push|pull|fetch)$"
+strategies = [{ action = "retry" }]
+backoff = { base_delay_ms = 1000, max_delay_ms = 10000 }
+
+[[rules]]
+name = "git-merge-fix"
+error_type = "git_operation"
+subject = "^(merge|rebase)$"
+strategies = [{ action = "fix", fix = "merge_conflict_resolution", confidence = "medium" }]
+
+[[rules]]
+name = "git-manual"
+error_type = "git_operation"
+strategies = [{ action = "fallback", alternative = "manual_process" }]
+
+[[rules]]
+name = "github-rate-limited"
+error_type = "github_api"
+status = [429]
+strategies = [{ action = "retry" }]
+max_attempts = 5
+backoff = { base_delay_ms = 2000, max_delay_ms = 30000 }
+
+[[rules]]
+name = "github-server-error"
+error_type = "github_api"
+status = ["500-599"]
+strategies = [{ action = "retry" }]
+backoff = { base_delay_ms = 5000, max_delay_ms = 20000 }
+
+[[rules]]
+name = "github-other"
+error_type = "github_api"
+strategies = [{ action = "escalate", urgency = "medium", context = "GitHub API error requires investigation" }]
+
+[[rules]]
+name = "merge-conflict-migrations"
+error_type = "merge_conflict"
+files = ["*migration*"]
+strategies = [{ action = "escalate", urgency = "high", context = "Complex merge conflicts in {file_count} files" }]
+
+[[rules]]
+name = "merge-conflict-small"
+error_type = "merge_conflict"
+max_count = 5
+strategies = [{ action = "fix", fix = "merge_conflict_resolution", confidence = "high" }]
+
+[[rules]]
+name = "merge-conflict-large"
+error_type = "merge_conflict"
+strategies = [{ action = "escalate", urgency = "high", context = "Complex merge conflicts in {file_count} files" }]
+
+[[rules]]
+name = "ci-tests"
+error_type = "ci_failure"
+message = "test"
+strategies = [{ action = "fix", fix = "test_failure_fix", confidence = "medium" }]
+
+[[rules]]
+name = "ci-build"
+error_type = "ci_failure"
+message = "build|compile"
+strategies = [{ action = "fix", fix = "build_error_fix", confidence = "low" }]
+
+[[rules]]
+name = "ci-other"
+error_type = "ci_failure"
+strategies = [{ action = "escalate", urgency = "medium", context = "CI failure in {subject}: {message}" }]
+
+[[rules]]
+name = "tests-few"
+error_type = "test_failure"
+max_count = 3
+strategies = [{ action = "fix", fix = "test_failure_fix", confidence = "medium" }]
+
+[[rules]]
+name = "tests-many"
+error_type = "test_failure"
+strategies = [{ action = "fallback", alternative = "simplified_solution" }]
+
+[[rules]]
+name = "build-dependencies"
+error_type = "build_failure"
+subject = "^dependencies$"
+strategies = [{ action = "fix", fix = "dependency_update", confidence = "high" }]
+
+[[rules]]
+name = "build-formatting"
+error_type = "build_failure"
+message = "format|lint"
+strategies = [{ action = "fix", fix = "code_formatting", confidence = "high" }]
+
+[[rules]]
+name = "build-other"
+error_type = "build_failure"
+strategies = [{ action = "fix", fix = "build_error_fix", confidence = "low" }]
+
+[[rules]]
+name = "dependency-conflict"
+error_type = "dependency_issue"
+message = "^version conflict$"
+strategies = [{ action = "fix", fix = "dependency_update", confidence = "medium" }]
+
+[[rules]]
+name = "dependency-retry"
+error_type = "dependency_issue"
+strategies = [{ action = "retry" }]
+backoff = { base_delay_ms = 2000, max_delay_ms = 10000 }
+
+[[rules]]
+name = "network-timeout"
+error_type = "network_issue"
+message = "^timed out$"
+strategies = [{ action = "retry" }]
+max_attempts = 5
+backoff = { base_delay_ms = 1000, max_delay_ms = 15000 }
+
+[[rules]]
+name = "network-unreachable"
+error_type = "network_issue"
+strategies = [{ action = "escalate", urgency = "low", context = "Network connectivity issues" }]
+
+[[rules]]
+name = "workspace-repair"
+error_type = "workspace_corruption"
+max_count = 5
+strategies = [{ action = "fix", fix = "configuration_adjustment", confidence = "medium" }]
+
+[[rules]]
+name = "workspace-reset"
+error_type = "workspace_corruption"
+strategies = [{ action = "abandon", reason = "Workspace corruption" }]
+
+[[rules]]
+name = "state-inconsistency"
+error_type = "state_inconsistency"
+strategies = [{ action = "fix", fix = "configuration_adjustment", confidence = "high" }]
+"#;
+
+const FIX_TYPES: &[(&str, FixType)] = &[
+    (
+        "merge_conflict_resolution",
+        FixType::MergeConflictResolution,
+    ),
+    ("test_failure_fix", FixType::TestFailureFix),
+    ("build_error_fix", FixType::BuildErrorFix),
+    ("dependency_update", FixType::DependencyUpdate),
+    ("configuration_adjustment", FixType::ConfigurationAdjustment),
+    ("code_formatting", FixType::CodeFormatting),
+];
+
+const CONFIDENCE_LEVELS: &[(&str, ConfidenceLevel)] = &[
+    ("high", ConfidenceLevel::High),
+    ("medium", ConfidenceLevel::Medium),
+    ("low", ConfidenceLevel::Low),
+];
+
+const ALTERNATIVES: &[(&str, AlternativeApproach)] = &[
+    (
+        "different_implementation",
+        AlternativeApproach::DifferentImplementation,
+    ),
+    (
+        "simplified_solution",
+        AlternativeApproach::SimplifiedSolution,
+    ),
+    ("manual_process", AlternativeApproach::ManualProcess),
+    ("external_tool", AlternativeApproach::ExternalTool),
+];
+
+const URGENCY_LEVELS: &[(&str, UrgencyLevel)] = &[
+    ("low", UrgencyLevel::Low),
+    ("medium", UrgencyLevel::Medium),
+    ("high", UrgencyLevel::High),
+    ("critical", UrgencyLevel::Critical),
+];
+
+fn lookup<T: Copy>(what: &str, name: &str, options: &[(&str, T)]) -> Result<T> {
+    options
+        .iter()
+        .find(|(option, _)| *option == name)
+        .map(|(_, value)| *value)
+        .ok_or_else(|| {
+            let names: Vec<&str> = options.iter().map(|(option, _)| *option).collect();
+            anyhow!(
+                "Unknown {} '{}', expected one of {}",
+                what,
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// What rules can match on, taken from an [`ErrorType`] or given on the command line
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ErrorFacts {
+    pub kind: String,
+    /// What failed: git operation, API endpoint, CI job, test suite, build stage,
+    /// dependency, service or expected state
+    pub subject: String,
+    pub message: String,
+    pub status: Option<u16>,
+    pub files: Vec<String>,
+    /// Conflicts, failed tests or affected files
+    pub count: usize,
+}
+
+impl ErrorFacts {
+    /// Facts for an error of `kind`, one of [`ERROR_KINDS`]
+    pub fn new(kind: &str) -> Result<Self> {
+        if !ERROR_KINDS.contains(&kind) {
+            bail!(
+                "Unknown error type '{}', expected one of {}",
+                kind,
+                ERROR_KINDS.join(", ")
+            );
+        }
+        Ok(Self {
+            kind: kind.to_string(),
+            ..Self::default()
+        })
+    }
+
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    pub fn with_status(mut self, status: Option<u16>) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_files(mut self, files: Vec<String>) -> Self {
+        self.files = files;
+        self
+    }
+
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// `template` with the `{placeholders}` filled in from these facts
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("{kind}", &self.kind)
+            .replace("{subject}", &self.subject)
+            .replace("{message}", &self.message)
+            .replace(
+                "{status}",
+                &self.status.map(|s| s.to_string()).unwrap_or_default(),
+            )
+            .replace("{count}", &self.count.to_string())
+            .replace("{file_count}", &self.files.len().to_string())
+    }
+}
+
+impl From<&ErrorType> for ErrorFacts {
+    fn from(error: &ErrorType) -> Self {
+        let facts = |kind: &str| Self {
+            kind: kind.to_string(),
+            ..Self::default()
+        };
+        match error {
+            ErrorType::GitOperationFailed { operation, error } => facts("git_operation")
+                .with_subject(operation)
+                .with_message(error),
+            ErrorType::GitHubAPIError {
+                endpoint,
+                status,
+                message,
+            } => facts("github_api")
+                .with_subject(endpoint)
+                .with_message(message)
+                .with_status(Some(*status)),
+            ErrorType::MergeConflict {
+                files,
+                conflict_count,
+            } => facts("merge_conflict")
+                .with_files(files.clone())
+                .with_count(*conflict_count as usize),
+            ErrorType::CIFailure { job, error, .. } => {
+                facts("ci_failure").with_subject(job).with_message(error)
+            }
+            ErrorType::TestFailure {
+                test_suite,
+                failed_tests,
+            } => facts("test_failure")
+                .with_subject(test_suite)
+                .with_message(failed_tests.join(", "))
+                .with_count(failed_tests.len()),
+            ErrorType::BuildFailure { stage, error } => facts("build_failure")
+                .with_subject(stage)
+                .with_message(error),
+            ErrorType::DependencyIssue {
+                dependency,
+                version_conflict,
+            } => facts("dependency_issue")
+                .with_subject(dependency)
+                .with_message(if *version_conflict {
+                    "version conflict"
+                } else {
+                    "unavailable"
+                }),
+            ErrorType::NetworkIssue { service, timeout } => facts("network_issue")
+                .with_subject(service)
+                .with_message(if *timeout { "timed out" } else { "unreachable" }),
+            ErrorType::WorkspaceCorruption { files_affected } => facts("workspace_corruption")
+                .with_files(files_affected.clone())
+                .with_count(files_affected.len()),
+            ErrorType::StateInconsistency {
+                expected_state,
+                actual_state,
+            } => facts("state_inconsistency")
+                .with_subject(expected_state)
+                .with_message(actual_state),
+        }
+    }
+}
+
+/// A strategy of a rule, with names resolved and templates still to fill in
+#[derive(Debug, Clone)]
+enum RuleAction {
+    Retry,
+    Fix(FixType, ConfidenceLevel),
+    Fallback(AlternativeApproach),
+    Escalate(UrgencyLevel, String),
+    Abandon(String),
+}
+
+impl RuleAction {
+    fn compile(config: &RecoveryActionConfig) -> Result<Self> {
+        Ok(match config {
+            RecoveryActionConfig::Retry => Self::Retry,
+            RecoveryActionConfig::Fix { fix, confidence } => Self::Fix(
+                lookup("fix", fix, FIX_TYPES)?,
+                lookup("confidence", confidence, CONFIDENCE_LEVELS)?,
+            ),
+            RecoveryActionConfig::Fallback { alternative } => {
+                Self::Fallback(lookup("alternative", alternative, ALTERNATIVES)?)
+            }
+            RecoveryActionConfig::Escalate { urgency, context } => {
+                Self::Escalate(lookup("urgency", urgency, URGENCY_LEVELS)?, context.clone())
+            }
+            RecoveryActionConfig::Abandon { reason } => Self::Abandon(reason.clone()),
+        })
+    }
+}
+
+/// A rule ready to be matched
+#[derive(Debug, Clone)]
+pub struct RecoveryRule {
+    pub name: String,
+    /// One of the defaults rather than a rule from config
+    pub builtin: bool,
+    error_type: Option<String>,
+    status: Vec<RangeInclusive<u16>>,
+    files: Vec<(String, Regex)>,
+    subject: Option<Regex>,
+    message: Option<Regex>,
+    max_count: Option<usize>,
+    strategies: Vec<RuleAction>,
+    max_attempts: u8,
+    base_delay_ms: u64,
+    max_delay_ms: u64,
+    escalation: EscalationConfig,
+}
+
+impl RecoveryRule {
+    pub fn compile(config: &RecoveryRuleConfig, builtin: bool) -> Result<Self> {
+        let context = || format!("Invalid recovery rule '{}'", config.name);
+        let regex = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| anyhow!(e))
+        };
+
+        if let Some(kind) = &config.error_type {
+            ErrorFacts::new(kind).with_context(context)?;
+        }
+        if config.strategies.is_empty() {
+            return Err(anyhow!("No strategies given")).with_context(context);
+        }
+        if config.max_attempts == 0 {
+            return Err(anyhow!("max_attempts must be at least 1")).with_context(context);
+        }
+
+        Ok(Self {
+            name: config.name.clone(),
+            builtin,
+            error_type: config.error_type.clone(),
+            status: config
+                .status
+                .iter()
+                .map(status_range)
+                .collect::<Result<_>>()
+                .with_context(context)?,
+            files: config
+                .files
+                .iter()
+                .map(|glob| Ok((glob.clone(), glob_to_regex(glob)?)))
+                .collect::<Result<_>>()
+                .with_context(context)?,
+            subject: regex(&config.subject).with_context(context)?,
+            message: regex(&config.message).with_context(context)?,
+            max_count: config.max_count,
+            strategies: config
+                .strategies
+                .iter()
+                .map(RuleAction::compile)
+                .collect::<Result<_>>()
+                .with_context(context)?,
+            max_attempts: config.max_attempts,
+            base_delay_ms: config.backoff.base_delay_ms,
+            max_delay_ms: config.backoff.max_delay_ms,
+            escalation: config.escalation.clone(),
+        })
+    }
+
+    /// Why the rule does not apply to `facts`, or `None` when it does
+    pub fn mismatch(&self, facts: &ErrorFacts) -> Option<String> {
+        if let Some(kind) = self.error_type.as_ref().filter(|k| **k != facts.kind) {
+            return Some(format!("only for {kind} errors"));
+        }
+        if !self.status.is_empty() {
+            match facts.status {
+                Some(status) if self.status.iter().any(|r| r.contains(&status)) => {}
+                Some(status) => return Some(format!("status {status} is not matched")),
+                None => return Some("the error has no status code".to_string()),
+            }
+        }
+        if !self.files.is_empty()
+            && !facts
+                .files
+                .iter()
+                .any(|file| self.files.iter().any(|(_, glob)| glob.is_match(file)))
+        {
+            let globs: Vec<&str> = self.files.iter().map(|(glob, _)| glob.as_str()).collect();
+            return Some(format!("no file matches {}", globs.join(", ")));
+        }
+        if let Some(subject) = self
+            .subject
+            .as_ref()
+            .filter(|r| !r.is_match(&facts.subject))
+        {
+            return Some(format!("'{}' does not match /{subject}/", facts.subject));
+        }
+        if let Some(message) = self
+            .message
+            .as_ref()
+            .filter(|r| !r.is_match(&facts.message))
+        {
+            return Some(format!("message does not match /{message}/"));
+        }
+        if let Some(max) = self.max_count.filter(|max| facts.count > *max) {
+            return Some(format!("count {} is over {max}", facts.count));
+        }
+        None
+    }
+
+    /// Whether the rule could apply to errors of `kind` at all
+    fn covers(&self, kind: &str) -> bool {
+        self.error_type.as_deref().is_none_or(|k| k == kind)
+    }
+
+    fn plan(&self, facts: &ErrorFacts) -> RecoveryPlan {
+        let strategies = self
+            .strategies
+            .iter()
+            .map(|action| match action {
+                RuleAction::Retry => RecoveryStrategy::RetryWithBackoff {
+                    max_attempts: self.max_attempts,
+                    base_delay_ms: self.base_delay_ms,
+                    max_delay_ms: self.max_delay_ms,
+                },
+                RuleAction::Fix(fix_type, confidence) => RecoveryStrategy::AutomatedFix {
+                    fix_type: *fix_type,
+                    confidence: *confidence,
+                },
+                RuleAction::Fallback(alternative) => RecoveryStrategy::Fallback {
+                    alternative: *alternative,
+                },
+                RuleAction::Escalate(urgency, context) => RecoveryStrategy::Escalate {
+                    urgency: *urgency,
+                    context: facts.render(context),
+                },
+                RuleAction::Abandon(reason) => RecoveryStrategy::AbandonAndReset {
+                    reason: AbandonmentReason::CriticalFailure {
+                        error: facts.render(reason),
+                    },
+                },
+            })
+            .collect();
+        RecoveryPlan {
+            rule: self.name.clone(),
+            strategies,
+            max_attempts: self.max_attempts,
+            escalation: self.escalation.clone(),
+        }
+    }
+}
+
+fn status_range(status: &StatusCodeMatch) -> Result<RangeInclusive<u16>> {
+    match status {
+        StatusCodeMatch::Code(code) => Ok(*code..=*code),
+        StatusCodeMatch::Range(range) => {
+            let parse = |bound: &str| {
+                bound
+                    .trim()
+                    .parse::<u16>()
+                    .map_err(|_| anyhow!("Invalid status range '{}'", range))
+            };
+            let (low, high) = match range.split_once('-') {
+                Some((low, high)) => (parse(low)?, parse(high)?),
+                None => (parse(range)?, parse(range)?),
+            };
+            if low > high {
+                bail!("Invalid status range '{}'", range);
+            }
+            Ok(low..=high)
+        }
+    }
+}
+
+/// What the rule that fired does about one error
+#[derive(Debug, Clone)]
+pub struct RecoveryPlan {
+    pub rule: String,
+    pub strategies: Vec<RecoveryStrategy>,
+    pub max_attempts: u8,
+    pub escalation: EscalationConfig,
+}
+
+impl RecoveryPlan {
+    /// The strategy to try first
+    pub fn first(&self) -> &RecoveryStrategy {
+        &self.strategies[0]
+    }
+
+    /// The strategy for attempt `attempt`, counted from 1, or `None` once the
+    /// rule's attempts are used up
+    pub fn strategy(&self, attempt: u8) -> Option<&RecoveryStrategy> {
+        if attempt == 0 || attempt > self.max_attempts {
+            return None;
+        }
+        self.strategies
+            .get(attempt as usize - 1)
+            .or(self.strategies.last())
+    }
+}
+
+/// The rules errors are checked against, in order
+#[derive(Debug, Clone)]
+pub struct RecoveryRules {
+    rules: Vec<RecoveryRule>,
+}
+
+impl Default for RecoveryRules {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl RecoveryRules {
+    /// Just the built-in rules
+    pub fn builtin() -> Self {
+        Self {
+            rules: built_in_rules().to_vec(),
+        }
+    }
+
+    /// The configured rules, followed by the built-in ones unless turned off
+    pub fn from_config(config: &RecoveryConfig) -> Result<Self> {
+        let mut rules = config
+            .rules
+            .iter()
+            .map(|rule| RecoveryRule::compile(rule, false))
+            .collect::<Result<Vec<_>>>()?;
+        if config.builtin_rules {
+            rules.extend(built_in_rules().iter().cloned());
+        }
+        Ok(Self { rules })
+    }
+
+    /// Rules from the loaded config, or the built-in ones without a config
+    pub fn load() -> Result<Self> {
+        let config = crate::config::config()
+            .map(|c| c.recovery.clone())
+            .unwrap_or_default();
+        Self::from_config(&config)
+    }
+
+    /// The first rule that applies to `facts`
+    pub fn matching(&self, facts: &ErrorFacts) -> Option<&RecoveryRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.mismatch(facts).is_none())
+    }
+
+    /// What to do about `error`; escalated when no rule applies
+    pub fn plan_for(&self, error: &ErrorType) -> RecoveryPlan {
+        self.plan_for_facts(&ErrorFacts::from(error))
+    }
+
+    pub fn plan_for_facts(&self, facts: &ErrorFacts) -> RecoveryPlan {
+        match self.matching(facts) {
+            Some(rule) => rule.plan(facts),
+            None => unmatched_plan(facts),
+        }
+    }
+
+    /// The rules checked for `facts`, why each was passed over, and the plan
+    pub fn explain(&self, facts: &ErrorFacts) -> RecoveryExplanation {
+        let mut checked = Vec::new();
+        let mut skipped = 0;
+        for rule in &self.rules {
+            if !rule.covers(&facts.kind) {
+                skipped += 1;
+                continue;
+            }
+            let mismatch = rule.mismatch(facts);
+            let fired = mismatch.is_none();
+            checked.push(RuleCheck {
+                rule: rule.name.clone(),
+                builtin: rule.builtin,
+                mismatch,
+            });
+            if fired {
+                break;
+            }
+        }
+        RecoveryExplanation {
+            facts: facts.clone(),
+            checked,
+            skipped,
+            plan: self.plan_for_facts(facts),
+        }
+    }
+}
+
+fn built_in_rules() -> &'static [RecoveryRule] {
+    static RULES: OnceLock<Vec<RecoveryRule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        #[derive(Deserialize)]
+        struct BuiltIn {
+            rules: Vec<RecoveryRuleConfig>,
+        }
+        let built_in: BuiltIn =
+            toml::from_str(BUILT_IN_RULES).expect("built-in recovery rules are valid TOML");
+        built_in
+            .rules
+            .iter()
+            .map(|rule| RecoveryRule::compile(rule, true).expect("built-in recovery rule"))
+            .collect()
+    })
+}
+
+/// Escalate what no rule knows how to handle
+fn unmatched_plan(facts: &ErrorFacts) -> RecoveryPlan {
+    RecoveryPlan {
+        rule: "unmatched".to_string(),
+        strategies: vec![RecoveryStrategy::Escalate {
+            urgency: UrgencyLevel::Medium,
+            context: format!("No recovery rule matches this {} error", facts.kind),
+        }],
+        max_attempts: 1,
+        escalation: EscalationConfig::default(),
+    }
+}
+
+/// A rule that was checked, and why it did not fire
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleCheck {
+    pub rule: String,
+    pub builtin: bool,
+    pub mismatch: Option<String>,
+}
+
+/// How the rules decide on one error, for `agent recover --explain`
+#[derive(Debug, Clone)]
+pub struct RecoveryExplanation {
+    pub facts: ErrorFacts,
+    pub checked: Vec<RuleCheck>,
+    /// Rules for other kinds of error, not shown
+    pub skipped: usize,
+    pub plan: RecoveryPlan,
+}
+
+impl RecoveryExplanation {
+    /// The rule that fires, `None` when the error falls through to escalation
+    pub fn fired(&self) -> Option<&str> {
+        self.checked
+            .last()
+            .filter(|check| check.mismatch.is_none())
+            .map(|check| check.rule.as_str())
+    }
+}
+
+impl fmt::Display for RecoveryExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "🔎 Recovery rules for a {} error", self.facts.kind)?;
+        for check in &self.checked {
+            let source = if check.builtin { " (built-in)" } else { "" };
+            match &check.mismatch {
+                Some(reason) => writeln!(f, "  ✗ {}{source}: {reason}", check.rule)?,
+                None => writeln!(f, "  ✓ {}{source}", check.rule)?,
+            }
+        }
+        if self.skipped > 0 {
+            writeln!(
+                f,
+                "  … {} rule(s) for other error types skipped",
+                self.skipped
+            )?;
+        }
+        writeln!(f)?;
+
+        match self.fired() {
+            Some(rule) => writeln!(f, "Rule '{rule}' fires:")?,
+            None => writeln!(f, "No rule matches, so the error is escalated:")?,
+        }
+        let plan = &self.plan;
+        for attempt in 1..=plan.max_attempts {
+            let strategy = plan.strategy(attempt).expect("attempt within max_attempts");
+            writeln!(f, "  {attempt}. {}", describe_strategy(strategy))?;
+            if matches!(
+                strategy,
+                RecoveryStrategy::Escalate { .. } | RecoveryStrategy::AbandonAndReset { .. }
+            ) {
+                break;
+            }
+            // The last strategy repeats; no need to list every repetition
+            if attempt as usize >= plan.strategies.len() && attempt < plan.max_attempts {
+                writeln!(f, "     (repeated up to attempt {})", plan.max_attempts)?;
+                break;
+            }
+        }
+        let mut targets: Vec<String> = plan
+            .escalation
+            .labels
+            .iter()
+            .map(|label| format!("label `{label}`"))
+            .collect();
+        if plan.escalation.comment {
+            targets.push("issue comment".to_string());
+        }
+        targets.extend(plan.escalation.mention.iter().cloned());
+        if targets.is_empty() {
+            targets.push("nobody".to_string());
+        }
+        write!(f, "Then escalated to: {}", targets.join(", "))
+    }
+}
+
+/// One line describing a strategy
+pub fn describe_strategy(strategy: &RecoveryStrategy) -> String {
+    match strategy {
+        RecoveryStrategy::RetryWithBackoff {
+            base_delay_ms,
+            max_delay_ms,
+            ..
+        } => format!("retry, backing off from {base_delay_ms}ms up to {max_delay_ms}ms"),
+        RecoveryStrategy::AutomatedFix {
+            fix_type,
+            confidence,
+        } => format!("automated fix: {fix_type:?} ({confidence:?} confidence)"),
+        RecoveryStrategy::Fallback { alternative } => format!("fall back to {alternative:?}"),
+        RecoveryStrategy::Escalate { urgency, context } => {
+            format!("escalate ({urgency:?} urgency): {context}")
+        }
+        RecoveryStrategy::AbandonAndReset { reason } => match reason {
+            AbandonmentReason::CriticalFailure { error } => format!("abandon and reset: {error}"),
+            other => format!("abandon and reset: {other:?}"),
+        },
+    }
+}
@@ -0,0 +1,897 @@
+//! Unattended run loop behind `my-little-soda run`
+//!
+//! One task at a time: pop an issue, start the configured agent on its
+//! branch, watch the branch and the workflow state while the agent runs, then
+//! bottle the work or apply the recovery strategy for what went wrong. Every
+//! transition is checkpointed, so a runner stopped by a signal or by
+//! `max_work_hours` picks the same task up again on its next start.
+//!
+//! GitHub and the repository are only reached through [`RunBackend`], and the
+//! agent is whatever program [`AgentLauncher`] starts, so the loop itself runs
+//! against fakes in tests.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use git2::Repository;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+use tokio::time::{sleep, sleep_until, Instant};
+use tracing::{info, warn};
+
+use super::error_recovery::{
+    AlternativeApproach, AutonomousErrorRecovery, AutonomousRecoveryAttempt, ErrorType,
+    RecoveryMetrics, RecoveryStrategy,
+};
+use super::persistence::{
+    CheckpointMetadata, CheckpointReason, HistorySummary, PersistentWorkflowState,
+    StatePersistenceManager,
+};
+use super::recovery_rules::RecoveryRules;
+use super::workflow_state_machine::{
+    AbandonmentReason, AgentId, AutonomousEvent, AutonomousWorkflowMachine,
+    AutonomousWorkflowState, BlockerType, Issue, WorkspaceState,
+};
+use super::CoordinationConfig;
+use crate::agent_lifecycle::rehydration::base_tip;
+use crate::config::{AgentProcessConfig, EscalationConfig};
+
+/// Lines of the agent's stderr kept to explain a failure
+const STDERR_TAIL_LINES: usize = 20;
+
+/// An issue the runner is working on and the branch the agent commits to
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunTask {
+    pub issue: Issue,
+    pub branch: String,
+}
+
+/// How far a branch has moved beyond the base
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BranchProgress {
+    pub commits: u32,
+    pub files_changed: u32,
+}
+
+/// What the runner needs from GitHub and the repository
+///
+/// Not `Send`: bottling goes through `land`, which holds git handles across awaits.
+#[async_trait(?Send)]
+pub trait RunBackend {
+    /// Claim the next issue for `agent_id` and check out its branch
+    async fn pop_task(&self, agent_id: &str) -> Result<Option<RunTask>>;
+
+    /// Check out the branch of a task resumed from a checkpoint
+    async fn resume_task(&self, task: &RunTask) -> Result<()>;
+
+    /// Commits and changed files on the task's branch
+    async fn progress(&self, task: &RunTask) -> Result<BranchProgress>;
+
+    /// Why to stop, if the workflow state has drifted from GitHub beyond automatic correction
+    async fn check_drift(&self, state: &AutonomousWorkflowState) -> Result<Option<String>>;
+
+    /// Push the branch and mark the issue ready for bundling
+    async fn bottle(&self, task: &RunTask) -> Result<()>;
+
+    /// Hand the issue to a human through `target`, explaining `reason`
+    async fn escalate(&self, task: &RunTask, reason: &str, target: &EscalationConfig)
+        -> Result<()>;
+}
+
+/// Commits and changed files on `branch` that `base_branch` does not have
+///
+/// The local branch is preferred over the remote-tracking one, since that is
+/// where the agent commits.
+pub fn branch_progress(
+    repo: &Repository,
+    branch: &str,
+    remote: &str,
+    base_branch: &str,
+) -> Result<BranchProgress> {
+    let base = base_tip(repo, remote, base_branch)?;
+    let tip = [
+        format!("refs/heads/{branch}"),
+        format!("refs/remotes/{remote}/{branch}"),
+    ]
+    .iter()
+    .find_map(|name| repo.refname_to_id(name).ok())
+    .ok_or_else(|| anyhow!("Branch {} not found", branch))?;
+
+    let (ahead, _) = repo.graph_ahead_behind(tip, base)?;
+    let fork_point = repo.find_commit(repo.merge_base(tip, base)?)?.tree()?;
+    let tip_tree = repo.find_commit(tip)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&fork_point), Some(&tip_tree), None)?;
+
+    Ok(BranchProgress {
+        commits: ahead as u32,
+        files_changed: diff.deltas().len() as u32,
+    })
+}
+
+/// Starts the agent process for a task
+#[derive(Debug, Clone)]
+pub struct AgentLauncher {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    enabled: bool,
+    work_dir: Option<PathBuf>,
+}
+
+impl AgentLauncher {
+    /// A launcher for `program`, which gets the task prompt as its last argument
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            timeout: Duration::from_secs(30 * 60),
+            enabled: true,
+            work_dir: None,
+        }
+    }
+
+    /// The agent configured under `[agents.process_management]`
+    pub fn from_config(config: &AgentProcessConfig) -> Self {
+        Self {
+            enabled: config.enable_real_agents,
+            ..Self::new(config.claude_code_path.clone())
+        }
+        .with_args(vec!["-p".to_string()])
+        .with_timeout(Duration::from_secs(config.timeout_minutes as u64 * 60))
+    }
+
+    /// Arguments passed before the prompt
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// How long one run of the agent may take before it is stopped
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Directory the agent runs in, the current one by default
+    pub fn with_work_dir(mut self, work_dir: PathBuf) -> Self {
+        self.work_dir = Some(work_dir);
+        self
+    }
+
+    /// Fail unless `enable_real_agents` allows starting the agent
+    pub fn ensure_enabled(&self) -> Result<()> {
+        if !self.enabled {
+            bail!(
+                "Real agents are disabled; set enable_real_agents = true under \
+                 [agents.process_management] to let `run` start {}",
+                self.program
+            );
+        }
+        Ok(())
+    }
+
+    /// Start the agent on `task`, with `note` explaining why it is running again
+    fn spawn(&self, agent_id: &str, task: &RunTask, note: Option<&str>) -> Result<Child> {
+        self.ensure_enabled()?;
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .arg(task_prompt(task, note))
+            .env("MY_LITTLE_SODA_AGENT", agent_id)
+            .env("MY_LITTLE_SODA_ISSUE", task.issue.number.to_string())
+            .env("MY_LITTLE_SODA_BRANCH", &task.branch)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(work_dir) = &self.work_dir {
+            command.current_dir(work_dir);
+        }
+        command
+            .spawn()
+            .map_err(|e| anyhow!("Failed to start agent {}: {}", self.program, e))
+    }
+}
+
+fn task_prompt(task: &RunTask, note: Option<&str>) -> String {
+    let mut prompt = format!(
+        "Work on GitHub issue #{}: {}\n\n{}\n\nCommit your changes to the branch {}, \
+         which is checked out. Exit once the issue is done.",
+        task.issue.number, task.issue.title, task.issue.body, task.branch
+    );
+    if let Some(note) = note {
+        prompt.push_str("\n\n");
+        prompt.push_str(note);
+    }
+    prompt
+}
+
+/// How long the runner works and how it paces itself
+#[derive(Debug, Clone)]
+pub struct RunLimits {
+    /// Hours before the runner stops, and before a single task is abandoned
+    pub max_work_hours: u8,
+    /// Recoveries tried on one task before it is escalated
+    pub max_recovery_attempts: u8,
+    /// How often progress and drift are checked while the agent runs
+    pub monitoring_interval: Duration,
+    /// How long to wait before asking for work again when there was none
+    pub idle_poll_interval: Duration,
+    /// Stop when no task is available instead of waiting for one
+    pub stop_when_idle: bool,
+}
+
+impl RunLimits {
+    pub fn from_coordination(config: &CoordinationConfig) -> Self {
+        let monitoring_interval =
+            Duration::from_secs(config.monitoring_interval_minutes as u64 * 60);
+        Self {
+            max_work_hours: config.max_work_hours,
+            max_recovery_attempts: config.max_recovery_attempts,
+            monitoring_interval,
+            idle_poll_interval: monitoring_interval,
+            stop_when_idle: false,
+        }
+    }
+}
+
+impl Default for RunLimits {
+    fn default() -> Self {
+        Self::from_coordination(&CoordinationConfig::default())
+    }
+}
+
+/// Why the runner stopped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StopReason {
+    /// Asked to stop, e.g. by SIGINT or SIGTERM
+    #[default]
+    Requested,
+    /// `max_work_hours` ran out
+    OutOfTime,
+    /// No task was available and `stop_when_idle` is set
+    Idle,
+}
+
+/// What a run did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    /// Issue picked up again from the last checkpoint
+    pub resumed: Option<u64>,
+    pub bottled: Vec<u64>,
+    pub escalated: Vec<u64>,
+    pub stopped: StopReason,
+}
+
+/// How the agent's work on a task ended
+enum TaskOutcome {
+    Bottled,
+    Escalated,
+    Interrupted(StopReason),
+}
+
+/// How one run of the agent ended
+enum AgentRun {
+    Exited { success: bool, stderr_tail: String },
+    TimedOut,
+    Abandoned(AbandonmentReason),
+    Interrupted(StopReason),
+}
+
+/// What to do about a failed run of the agent
+enum RecoveryStep {
+    Relaunch {
+        delay: Duration,
+        note: String,
+    },
+    Escalate {
+        message: String,
+        target: EscalationConfig,
+    },
+}
+
+/// Drives the autonomous workflow machine for one agent until stopped
+pub struct AutonomousRunner {
+    agent_id: String,
+    backend: Box<dyn RunBackend>,
+    launcher: AgentLauncher,
+    persistence: StatePersistenceManager,
+    limits: RunLimits,
+    machine: AutonomousWorkflowMachine,
+    recovery: AutonomousErrorRecovery,
+    history_summary: Option<HistorySummary>,
+    base_branch: String,
+}
+
+impl AutonomousRunner {
+    pub fn new(
+        agent_id: impl Into<String>,
+        backend: Box<dyn RunBackend>,
+        launcher: AgentLauncher,
+        persistence: StatePersistenceManager,
+    ) -> Self {
+        let limits = RunLimits::default();
+        let agent_id = agent_id.into();
+        Self {
+            machine: AutonomousWorkflowMachine::new(limits.max_work_hours)
+                .with_agent_id(agent_id.clone()),
+            agent_id,
+            backend,
+            launcher,
+            persistence,
+            recovery: AutonomousErrorRecovery::without_client()
+                .with_max_attempts(limits.max_recovery_attempts),
+            limits,
+            history_summary: None,
+            base_branch: "main".to_string(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: RunLimits) -> Self {
+        self.machine.max_work_hours = limits.max_work_hours;
+        self.recovery = self
+            .recovery
+            .with_max_attempts(limits.max_recovery_attempts);
+        self.limits = limits;
+        self
+    }
+
+    /// Rules picking the recovery for each failure, the built-in ones by default
+    pub fn with_recovery_rules(mut self, rules: RecoveryRules) -> Self {
+        self.recovery = self.recovery.with_rules(rules);
+        self
+    }
+
+    /// Branch tasks are based on, `main` by default
+    pub fn with_base_branch(mut self, base_branch: &str) -> Self {
+        self.base_branch = base_branch.to_string();
+        self
+    }
+
+    /// Work through tasks until `stop` turns true, the queue runs dry with
+    /// `stop_when_idle`, or `max_work_hours` pass
+    pub async fn run(&mut self, mut stop: watch::Receiver<bool>) -> Result<RunSummary> {
+        self.launcher.ensure_enabled()?;
+        let deadline =
+            Instant::now() + Duration::from_secs(self.limits.max_work_hours as u64 * 3600);
+        let mut summary = RunSummary::default();
+
+        let mut next = self.restore().await?;
+        if let Some(task) = &next {
+            info!(agent_id = %self.agent_id, issue = task.issue.number, "Resuming task from checkpoint");
+            self.backend.resume_task(task).await?;
+            summary.resumed = Some(task.issue.number);
+        }
+
+        loop {
+            if *stop.borrow() {
+                summary.stopped = StopReason::Requested;
+                break;
+            }
+            if Instant::now() >= deadline {
+                summary.stopped = StopReason::OutOfTime;
+                break;
+            }
+
+            let task = match next.take() {
+                Some(task) => task,
+                None => match self.backend.pop_task(&self.agent_id).await {
+                    Ok(Some(task)) => {
+                        self.assign(&task).await?;
+                        task
+                    }
+                    result => {
+                        if let Err(e) = result {
+                            warn!(agent_id = %self.agent_id, error = %e, "Could not pop a task");
+                        } else if self.limits.stop_when_idle {
+                            summary.stopped = StopReason::Idle;
+                            break;
+                        }
+                        tokio::select! {
+                            _ = sleep(self.limits.idle_poll_interval) => {}
+                            _ = stop_requested(&mut stop) => {}
+                            _ = sleep_until(deadline) => {}
+                        }
+                        continue;
+                    }
+                },
+            };
+
+            match self.work_on(&task, &mut stop, deadline).await? {
+                TaskOutcome::Bottled => summary.bottled.push(task.issue.number),
+                TaskOutcome::Escalated => summary.escalated.push(task.issue.number),
+                TaskOutcome::Interrupted(reason) => {
+                    summary.stopped = reason;
+                    break;
+                }
+            }
+        }
+
+        self.checkpoint(CheckpointReason::BeforeShutdown).await;
+        info!(agent_id = %self.agent_id, summary = ?summary, "Autonomous run stopped");
+        Ok(summary)
+    }
+
+    /// Rebuild the machine from the last checkpoint, returning the task it was on
+    async fn restore(&mut self) -> Result<Option<RunTask>> {
+        let Some(saved) = self.persistence.load_state(&self.agent_id).await? else {
+            return Ok(None);
+        };
+        self.machine.current_state = saved.current_state;
+        self.machine.start_time = saved.start_time;
+        self.machine.state_history = saved.state_history;
+        self.machine.agent_id = Some(AgentId(self.agent_id.clone()));
+        self.recovery.restore_history(saved.recovery_history);
+        self.history_summary = saved.history_summary;
+
+        match self.machine.current_state.clone() {
+            Some(
+                state @ (AutonomousWorkflowState::Assigned { .. }
+                | AutonomousWorkflowState::InProgress { .. }
+                | AutonomousWorkflowState::Blocked { .. }),
+            ) => {
+                let issue = state.issue().clone();
+                let branch = self.assigned_branch(issue.number);
+                Ok(Some(RunTask { issue, branch }))
+            }
+            // Bottling happens before `CompleteWork`, so the work has already been handed over
+            Some(
+                AutonomousWorkflowState::ReadyForReview { .. }
+                | AutonomousWorkflowState::Merged { .. }
+                | AutonomousWorkflowState::Abandoned { .. },
+            ) => {
+                self.handle(AutonomousEvent::Reset).await?;
+                Ok(None)
+            }
+            Some(state) => {
+                warn!(agent_id = %self.agent_id, state = ?state, "Checkpoint is in a state `run` does not drive, starting fresh");
+                self.machine.current_state = None;
+                self.machine.start_time = None;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Branch recorded when `issue` was assigned, or the bare prefix `land` accepts
+    fn assigned_branch(&self, issue: u64) -> String {
+        self.machine
+            .state_history
+            .iter()
+            .rev()
+            .find_map(|record| match &record.to_state {
+                AutonomousWorkflowState::Assigned {
+                    issue: assigned,
+                    workspace,
+                    ..
+                } if assigned.number == issue => Some(workspace.branch_name.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| format!("{}/{}", self.agent_id, issue))
+    }
+
+    async fn assign(&mut self, task: &RunTask) -> Result<()> {
+        info!(agent_id = %self.agent_id, issue = task.issue.number, branch = %task.branch, "Popped task");
+        self.machine.queue_issue(
+            task.issue.clone(),
+            WorkspaceState {
+                branch_name: task.branch.clone(),
+                base_branch: self.base_branch.clone(),
+                workspace_setup: true,
+                dependencies_installed: false,
+            },
+        );
+        self.handle(AutonomousEvent::AssignAgent {
+            agent: AgentId(self.agent_id.clone()),
+            workspace_ready: true,
+        })
+        .await
+    }
+
+    async fn work_on(
+        &mut self,
+        task: &RunTask,
+        stop: &mut watch::Receiver<bool>,
+        deadline: Instant,
+    ) -> Result<TaskOutcome> {
+        let mut attempts: u8 = 0;
+        let mut note = matches!(
+            self.machine.current_state,
+            Some(AutonomousWorkflowState::InProgress { .. })
+                | Some(AutonomousWorkflowState::Blocked { .. })
+        )
+        .then(|| {
+            "The previous session was interrupted. Continue from the commits already \
+             on the branch."
+                .to_string()
+        });
+
+        loop {
+            match self.machine.current_state {
+                Some(AutonomousWorkflowState::Assigned { .. }) => {
+                    self.handle(AutonomousEvent::StartWork).await?
+                }
+                Some(AutonomousWorkflowState::Blocked { .. }) => {
+                    self.handle(AutonomousEvent::ResolveBlocker).await?
+                }
+                _ => {}
+            }
+            if let Some(reason) = self.abandonment() {
+                let message = abandonment_message(&reason);
+                return self
+                    .give_up(task, reason, message, &EscalationConfig::default())
+                    .await;
+            }
+
+            let error = match self.supervise(task, note.take(), stop, deadline).await? {
+                AgentRun::Interrupted(reason) => return Ok(TaskOutcome::Interrupted(reason)),
+                AgentRun::Abandoned(reason) => {
+                    let message = abandonment_message(&reason);
+                    return self
+                        .give_up(task, reason, message, &EscalationConfig::default())
+                        .await;
+                }
+                AgentRun::TimedOut => ErrorType::BuildFailure {
+                    stage: "agent".to_string(),
+                    error: format!("timed out after {:?}", self.launcher.timeout),
+                },
+                AgentRun::Exited {
+                    success: false,
+                    stderr_tail,
+                } => ErrorType::BuildFailure {
+                    stage: "agent".to_string(),
+                    error: if stderr_tail.is_empty() {
+                        "exited unsuccessfully".to_string()
+                    } else {
+                        stderr_tail
+                    },
+                },
+                AgentRun::Exited { success: true, .. } => match self.finish(task).await? {
+                    Some(error) => error,
+                    None => return Ok(TaskOutcome::Bottled),
+                },
+            };
+
+            attempts += 1;
+            match self.recover(task, &error, attempts).await? {
+                RecoveryStep::Relaunch { delay, note: why } => {
+                    tokio::select! {
+                        _ = sleep(delay) => {}
+                        _ = stop_requested(stop) => return Ok(TaskOutcome::Interrupted(StopReason::Requested)),
+                        _ = sleep_until(deadline) => return Ok(TaskOutcome::Interrupted(StopReason::OutOfTime)),
+                    }
+                    note = Some(why);
+                }
+                RecoveryStep::Escalate { message, target } => {
+                    let reason = AbandonmentReason::UnresolvableBlocker {
+                        blocker: blocker_for(&error),
+                    };
+                    return self.give_up(task, reason, message, &target).await;
+                }
+            }
+        }
+    }
+
+    /// Bottle a task the agent finished, or the error that stopped it
+    async fn finish(&mut self, task: &RunTask) -> Result<Option<ErrorType>> {
+        let progress = self.record_progress(task).await?;
+        if progress.commits == 0 {
+            return Ok(Some(ErrorType::StateInconsistency {
+                expected_state: format!("commits on {}", task.branch),
+                actual_state: "the agent exited without committing".to_string(),
+            }));
+        }
+        if let Err(e) = self.backend.bottle(task).await {
+            return Ok(Some(ErrorType::BuildFailure {
+                stage: "bottle".to_string(),
+                error: e.to_string(),
+            }));
+        }
+        info!(agent_id = %self.agent_id, issue = task.issue.number, commits = progress.commits, "Bottled task");
+        self.handle(AutonomousEvent::CompleteWork).await?;
+        self.handle(AutonomousEvent::Reset).await?;
+        Ok(None)
+    }
+
+    /// Run the agent once, checking progress and drift until it exits or has to be stopped
+    async fn supervise(
+        &mut self,
+        task: &RunTask,
+        note: Option<String>,
+        stop: &mut watch::Receiver<bool>,
+        deadline: Instant,
+    ) -> Result<AgentRun> {
+        let mut child = self.launcher.spawn(&self.agent_id, task, note.as_deref())?;
+        let stderr = child.stderr.take();
+        let stderr_tail = tokio::spawn(async move {
+            let mut output = String::new();
+            if let Some(mut stderr) = stderr {
+                let _ = stderr.read_to_string(&mut output).await;
+            }
+            let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+            lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+        });
+
+        let agent_deadline = Instant::now() + self.launcher.timeout;
+        let mut monitor = tokio::time::interval_at(
+            Instant::now() + self.limits.monitoring_interval,
+            self.limits.monitoring_interval,
+        );
+        loop {
+            tokio::select! {
+                status = child.wait() => {
+                    let status = status?;
+                    return Ok(AgentRun::Exited {
+                        success: status.success(),
+                        stderr_tail: stderr_tail.await.unwrap_or_default(),
+                    });
+                }
+                _ = monitor.tick() => {
+                    if let Err(e) = self.record_progress(task).await {
+                        warn!(agent_id = %self.agent_id, error = %e, "Could not read branch progress");
+                    }
+                    if let Some(reason) = self.abandonment() {
+                        stop_agent(&mut child).await;
+                        return Ok(AgentRun::Abandoned(reason));
+                    }
+                    if let Some(reason) = self.drift().await {
+                        stop_agent(&mut child).await;
+                        return Ok(AgentRun::Abandoned(AbandonmentReason::CriticalFailure { error: reason }));
+                    }
+                }
+                _ = stop_requested(stop) => {
+                    stop_agent(&mut child).await;
+                    return Ok(AgentRun::Interrupted(StopReason::Requested));
+                }
+                _ = sleep_until(deadline) => {
+                    stop_agent(&mut child).await;
+                    return Ok(AgentRun::Interrupted(StopReason::OutOfTime));
+                }
+                _ = sleep_until(agent_deadline) => {
+                    stop_agent(&mut child).await;
+                    return Ok(AgentRun::TimedOut);
+                }
+            }
+        }
+    }
+
+    /// Bring the machine's progress up to what the branch shows
+    async fn record_progress(&mut self, task: &RunTask) -> Result<BranchProgress> {
+        let progress = self.backend.progress(task).await?;
+        if let Some(AutonomousWorkflowState::InProgress {
+            progress: known, ..
+        }) = &self.machine.current_state
+        {
+            let commits = progress.commits.saturating_sub(known.commits_made);
+            let files_changed = progress.files_changed.saturating_sub(known.files_changed);
+            if commits > 0 || files_changed > 0 {
+                self.handle(AutonomousEvent::MakeProgress {
+                    commits,
+                    files_changed,
+                })
+                .await?;
+            }
+        }
+        Ok(progress)
+    }
+
+    /// Why to stop, when drift needs a person; a failed check only warns
+    async fn drift(&self) -> Option<String> {
+        let state = self.machine.current_state.as_ref()?;
+        match self.backend.check_drift(state).await {
+            Ok(reason) => reason,
+            Err(e) => {
+                warn!(agent_id = %self.agent_id, error = %e, "Drift check failed");
+                None
+            }
+        }
+    }
+
+    /// Block the task on `error` and decide how to recover from it
+    async fn recover(
+        &mut self,
+        task: &RunTask,
+        error: &ErrorType,
+        attempts: u8,
+    ) -> Result<RecoveryStep> {
+        self.handle(AutonomousEvent::EncounterBlocker {
+            blocker: blocker_for(error),
+        })
+        .await?;
+
+        let plan = self.recovery.recovery_plan(error);
+        info!(agent_id = %self.agent_id, issue = task.issue.number, rule = %plan.rule, attempt = attempts, "Applying recovery rule");
+        let what = describe(error);
+        // The rule's strategy for this attempt, until it or the run limit gives out
+        let strategy = self.recovery.strategy_for_attempt(&plan, attempts);
+        let step = match &strategy {
+            Some(RecoveryStrategy::RetryWithBackoff {
+                base_delay_ms,
+                max_delay_ms,
+                ..
+            }) => {
+                let delay = base_delay_ms
+                    .saturating_mul(1 << (attempts - 1).min(16))
+                    .min(*max_delay_ms);
+                RecoveryStep::Relaunch {
+                    delay: Duration::from_millis(delay),
+                    note: format!("The previous attempt failed ({what}). Try again."),
+                }
+            }
+            Some(RecoveryStrategy::AutomatedFix { fix_type, .. }) => RecoveryStep::Relaunch {
+                delay: Duration::ZERO,
+                note: format!(
+                    "The previous attempt failed ({what}). Fix it ({fix_type:?}) and commit."
+                ),
+            },
+            Some(RecoveryStrategy::Fallback { alternative })
+                if !matches!(alternative, AlternativeApproach::ManualProcess) =>
+            {
+                RecoveryStep::Relaunch {
+                    delay: Duration::ZERO,
+                    note: format!(
+                        "The previous attempt failed ({what}). Take another approach \
+                         ({alternative:?})."
+                    ),
+                }
+            }
+            Some(RecoveryStrategy::Escalate { context, .. }) => RecoveryStep::Escalate {
+                message: format!("{context}: {what}"),
+                target: plan.escalation.clone(),
+            },
+            Some(RecoveryStrategy::AbandonAndReset { reason }) => RecoveryStep::Escalate {
+                message: format!("{reason:?}: {what}"),
+                target: plan.escalation.clone(),
+            },
+            _ => RecoveryStep::Escalate {
+                message: format!("Gave up after {} recovery attempt(s): {what}", attempts - 1),
+                target: plan.escalation.clone(),
+            },
+        };
+        let strategy = strategy.unwrap_or_else(|| plan.first().clone());
+
+        let now = Utc::now();
+        self.recovery.record_attempt(AutonomousRecoveryAttempt {
+            attempt_id: format!("run-{}-{}-{}", task.issue.number, now.timestamp(), attempts),
+            error_type: error.clone(),
+            strategy,
+            started_at: now,
+            completed_at: Some(now),
+            success: matches!(step, RecoveryStep::Relaunch { .. }),
+            error_message: match &step {
+                RecoveryStep::Escalate { message, .. } => Some(message.clone()),
+                RecoveryStep::Relaunch { .. } => None,
+            },
+            recovery_actions: Vec::new(),
+            metrics: RecoveryMetrics {
+                attempts_count: attempts as u32,
+                total_duration_ms: 0,
+                actions_executed: 0,
+                files_affected: 0,
+                git_operations: 0,
+                network_requests: 0,
+            },
+        });
+        self.checkpoint(CheckpointReason::BeforeRecovery).await;
+        Ok(step)
+    }
+
+    /// Abandon the task, hand it to a human with `message` and free the agent
+    async fn give_up(
+        &mut self,
+        task: &RunTask,
+        reason: AbandonmentReason,
+        message: String,
+        target: &EscalationConfig,
+    ) -> Result<TaskOutcome> {
+        info!(agent_id = %self.agent_id, issue = task.issue.number, reason = %message, "Escalating task");
+        if self.abandonment().is_none() {
+            self.handle(AutonomousEvent::ForceAbandon { reason })
+                .await?;
+        }
+        if let Err(e) = self.backend.escalate(task, &message, target).await {
+            warn!(agent_id = %self.agent_id, issue = task.issue.number, error = %e, "Could not escalate task");
+        }
+        self.handle(AutonomousEvent::Reset).await?;
+        Ok(TaskOutcome::Escalated)
+    }
+
+    /// Why the task was abandoned, if it was; the machine abandons timed out tasks itself
+    fn abandonment(&self) -> Option<AbandonmentReason> {
+        match &self.machine.current_state {
+            Some(AutonomousWorkflowState::Abandoned { reason, .. }) => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    async fn handle(&mut self, event: AutonomousEvent) -> Result<()> {
+        self.machine.handle_event(event).await?;
+        self.checkpoint(CheckpointReason::StateTransition).await;
+        Ok(())
+    }
+
+    /// Save the machine; a failed save is logged rather than stopping the work
+    async fn checkpoint(&self, reason: CheckpointReason) {
+        let state = PersistentWorkflowState {
+            version: "1.0.0".to_string(),
+            agent_id: self.agent_id.clone(),
+            current_state: self.machine.current_state.clone(),
+            start_time: self.machine.start_time,
+            max_work_hours: self.machine.max_work_hours,
+            state_history: self.machine.state_history.clone(),
+            recovery_history: self.recovery.recovery_history().to_vec(),
+            // Filled in by the persistence when it saves
+            checkpoint_metadata: CheckpointMetadata {
+                checkpoint_id: String::new(),
+                creation_reason: reason.clone(),
+                integrity_hash: String::new(),
+                agent_pid: Some(std::process::id()),
+                hostname: String::new(),
+            },
+            last_persisted: Utc::now(),
+            history_summary: self.history_summary.clone(),
+        };
+        if let Err(e) = self.persistence.save_state(&state, reason).await {
+            warn!(agent_id = %self.agent_id, error = %e, "Failed to checkpoint autonomous state");
+        }
+    }
+}
+
+/// Resolve once `stop` turns true; never, once nobody can send it
+async fn stop_requested(stop: &mut watch::Receiver<bool>) {
+    if stop.wait_for(|stop| *stop).await.is_err() {
+        std::future::pending::<()>().await
+    }
+}
+
+async fn stop_agent(child: &mut Child) {
+    if let Err(e) = child.kill().await {
+        warn!(error = %e, "Failed to stop agent process");
+    }
+}
+
+fn blocker_for(error: &ErrorType) -> BlockerType {
+    match error {
+        ErrorType::TestFailure {
+            test_suite,
+            failed_tests,
+        } => BlockerType::TestFailure {
+            test_name: failed_tests.join(", "),
+            error: format!("{test_suite} failed"),
+        },
+        ErrorType::DependencyIssue { dependency, .. } => BlockerType::DependencyIssue {
+            dependency: dependency.clone(),
+            error: describe(error),
+        },
+        ErrorType::NetworkIssue { .. } => BlockerType::NetworkIssue {
+            error: describe(error),
+        },
+        _ => BlockerType::BuildFailure {
+            error: describe(error),
+        },
+    }
+}
+
+fn describe(error: &ErrorType) -> String {
+    match error {
+        ErrorType::BuildFailure { stage, error } => format!("{stage} failed: {error}"),
+        ErrorType::StateInconsistency {
+            expected_state,
+            actual_state,
+        } => format!("expected {expected_state}, but {actual_state}"),
+        other => format!("{other:?}"),
+    }
+}
+
+fn abandonment_message(reason: &AbandonmentReason) -> String {
+    match reason {
+        AbandonmentReason::TimeoutExceeded { max_hours } => {
+            format!("not finished within {max_hours} hours")
+        }
+        AbandonmentReason::CriticalFailure { error } => error.clone(),
+        other => format!("{other:?}"),
+    }
+}
@@ -0,0 +1,543 @@
+//! SQLite persistence for autonomous workflow state
+//!
+//! Keeps the latest state of every agent and its checkpoints in the database
+//! configured under `[database]`. Each row stores a SHA-256 of its serialized
+//! state, so a row damaged outside of my-little-soda is refused rather than
+//! resumed from. Existing `.my-little-soda/autonomous_state` directories can be
+//! imported with [`SqlitePersistence::import_directory`].
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use super::persistence::{
+    calculate_integrity_hash, checkpoint_metadata, new_checkpoint_id, plan_retention,
+    prune_state_history, CheckpointInfo, CheckpointReason, CheckpointSize, CleanupPlan,
+    PersistenceConfig, PersistenceError, PersistentWorkflowState, StatePersistence, StateSummary,
+};
+
+/// What importing a state directory did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub states_imported: usize,
+    pub checkpoints_imported: usize,
+    /// States and checkpoints the database already had, left untouched
+    pub already_present: usize,
+    /// Files that could not be read as workflow state
+    pub failed: Vec<PathBuf>,
+}
+
+/// Workflow state persistence in the SQLite database
+#[derive(Clone)]
+pub struct SqlitePersistence {
+    config: PersistenceConfig,
+    pool: Option<SqlitePool>,
+}
+
+impl SqlitePersistence {
+    /// Persist into the database configured under `[database]`
+    pub fn new(config: PersistenceConfig) -> Self {
+        Self { config, pool: None }
+    }
+
+    /// Persist into `pool` instead of the configured database
+    pub fn with_pool(mut self, pool: SqlitePool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    async fn pool(&self) -> Result<SqlitePool, PersistenceError> {
+        if let Some(pool) = &self.pool {
+            return Ok(pool.clone());
+        }
+        let not_configured = || PersistenceError::DatabaseError {
+            reason: "No database configured; add a [database] section to my-little-soda.toml"
+                .to_string(),
+        };
+        let db = crate::database::database()
+            .await
+            .ok_or_else(not_configured)?;
+        let guard = db.read().await;
+        Ok(guard.as_ref().ok_or_else(not_configured)?.pool().clone())
+    }
+
+    /// Copy the state and checkpoint files of a filesystem store into the database
+    ///
+    /// Rows the database already has are kept, so an interrupted import can be
+    /// run again.
+    pub async fn import_directory(
+        &self,
+        directory: &Path,
+    ) -> Result<ImportReport, PersistenceError> {
+        let pool = self.pool().await?;
+        let mut report = ImportReport::default();
+
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(directory)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if path.is_dir() && name.ends_with("_checkpoints") {
+                let mut checkpoints: Vec<PathBuf> = std::fs::read_dir(&path)?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|p| p.to_string_lossy().ends_with(".checkpoint.json"))
+                    .collect();
+                checkpoints.sort();
+                for file in checkpoints {
+                    let Some(state) = read_state_file(&file, &mut report) else {
+                        continue;
+                    };
+                    let created_at = std::fs::metadata(&file)
+                        .and_then(|m| m.modified())
+                        .map(DateTime::<Utc>::from)
+                        .unwrap_or(state.last_persisted);
+                    let inserted = insert_checkpoint(&pool, &state, created_at, true)
+                        .await
+                        .map_err(database_error)?;
+                    if inserted {
+                        report.checkpoints_imported += 1;
+                    } else {
+                        report.already_present += 1;
+                    }
+                }
+            } else if name.ends_with(".state.json") {
+                let Some(state) = read_state_file(&path, &mut report) else {
+                    continue;
+                };
+                let (json, hash) = encode(&state)?;
+                let inserted = sqlx::query(
+                    r#"
+                    INSERT INTO workflow_states (agent_id, checkpoint_id, state, content_hash, saved_at)
+                    VALUES (?1, ?2, ?3, ?4, datetime(?5, 'unixepoch'))
+                    ON CONFLICT(agent_id) DO NOTHING
+                    "#,
+                )
+                .bind(&state.agent_id)
+                .bind(&state.checkpoint_metadata.checkpoint_id)
+                .bind(json)
+                .bind(hash)
+                .bind(state.last_persisted.timestamp())
+                .execute(&pool)
+                .await
+                .map_err(database_error)?
+                .rows_affected()
+                    > 0;
+                if inserted {
+                    report.states_imported += 1;
+                } else {
+                    report.already_present += 1;
+                }
+            }
+        }
+
+        info!(
+            directory = ?directory,
+            states = report.states_imported,
+            checkpoints = report.checkpoints_imported,
+            already_present = report.already_present,
+            failed = report.failed.len(),
+            "Imported workflow state files"
+        );
+        Ok(report)
+    }
+
+    /// Decode a stored state, refusing rows whose content or integrity hash is off
+    /// Retention after a new checkpoint; failing it never fails the save
+    async fn apply_retention(&self, agent_id: &str) {
+        if let Err(e) = self.cleanup_old_data(agent_id).await {
+            warn!(agent_id = %agent_id, error = %e, "Checkpoint retention failed");
+        }
+    }
+
+    async fn decode(
+        &self,
+        json: &str,
+        content_hash: &str,
+    ) -> Result<PersistentWorkflowState, PersistenceError> {
+        if content_hash != sha256(json) {
+            return Err(PersistenceError::StateCorruption {
+                reason: "Stored state does not match its content hash".to_string(),
+            });
+        }
+        let state: PersistentWorkflowState = serde_json::from_str(json)?;
+        if self.config.enable_integrity_checks && !self.verify_integrity(&state).await? {
+            return Err(PersistenceError::StateCorruption {
+                reason: "Integrity check failed".to_string(),
+            });
+        }
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl StatePersistence for SqlitePersistence {
+    /// Save the state as the agent's latest and keep it as a checkpoint, in one transaction
+    async fn save_state(
+        &self,
+        state: &PersistentWorkflowState,
+        reason: CheckpointReason,
+    ) -> Result<String, PersistenceError> {
+        if !self.config.enable_persistence {
+            return Ok("persistence_disabled".to_string());
+        }
+        let pool = self.pool().await?;
+
+        let mut state_to_save = state.clone();
+        state_to_save.last_persisted = Utc::now();
+        prune_state_history(&self.config, &mut state_to_save);
+
+        let integrity_hash = if self.config.enable_integrity_checks {
+            calculate_integrity_hash(&state_to_save)
+        } else {
+            "integrity_disabled".to_string()
+        };
+        let checkpoint_id = new_checkpoint_id();
+        state_to_save.checkpoint_metadata =
+            checkpoint_metadata(&checkpoint_id, reason.clone(), integrity_hash);
+        let (json, hash) = encode(&state_to_save)?;
+
+        let mut tx = pool.begin().await.map_err(database_error)?;
+        sqlx::query(
+            r#"
+            INSERT INTO workflow_states (agent_id, checkpoint_id, state, content_hash, saved_at)
+            VALUES (?1, ?2, ?3, ?4, datetime(?5, 'unixepoch'))
+            ON CONFLICT(agent_id) DO UPDATE SET
+                checkpoint_id = excluded.checkpoint_id,
+                state = excluded.state,
+                content_hash = excluded.content_hash,
+                saved_at = excluded.saved_at
+            "#,
+        )
+        .bind(&state_to_save.agent_id)
+        .bind(&checkpoint_id)
+        .bind(json)
+        .bind(hash)
+        .bind(state_to_save.last_persisted.timestamp())
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+        insert_checkpoint(
+            &mut *tx,
+            &state_to_save,
+            state_to_save.last_persisted,
+            false,
+        )
+        .await
+        .map_err(database_error)?;
+        tx.commit().await.map_err(database_error)?;
+
+        info!(
+            agent_id = %state.agent_id,
+            checkpoint_id = %checkpoint_id,
+            reason = ?reason,
+            "State saved to database"
+        );
+        self.apply_retention(&state.agent_id).await;
+        Ok(checkpoint_id)
+    }
+
+    async fn load_state(
+        &self,
+        agent_id: &str,
+    ) -> Result<Option<PersistentWorkflowState>, PersistenceError> {
+        if !self.config.enable_persistence {
+            return Ok(None);
+        }
+        let row =
+            sqlx::query("SELECT state, content_hash FROM workflow_states WHERE agent_id = ?1")
+                .bind(agent_id)
+                .fetch_optional(&self.pool().await?)
+                .await
+                .map_err(database_error)?;
+        let Some(row) = row else {
+            info!(agent_id = %agent_id, "No existing state in database");
+            return Ok(None);
+        };
+        let state = self
+            .decode(row.get("state"), row.get("content_hash"))
+            .await?;
+        info!(
+            agent_id = %agent_id,
+            checkpoint_id = %state.checkpoint_metadata.checkpoint_id,
+            last_persisted = %state.last_persisted,
+            "State loaded from database"
+        );
+        Ok(Some(state))
+    }
+
+    async fn create_checkpoint(
+        &self,
+        state: &PersistentWorkflowState,
+        reason: CheckpointReason,
+    ) -> Result<String, PersistenceError> {
+        if !self.config.enable_persistence {
+            return Ok("persistence_disabled".to_string());
+        }
+        let checkpoint_id = new_checkpoint_id();
+        let mut checkpoint_state = state.clone();
+        checkpoint_state.checkpoint_metadata = checkpoint_metadata(
+            &checkpoint_id,
+            reason.clone(),
+            calculate_integrity_hash(&checkpoint_state),
+        );
+        insert_checkpoint(&self.pool().await?, &checkpoint_state, Utc::now(), false)
+            .await
+            .map_err(database_error)?;
+
+        info!(
+            agent_id = %state.agent_id,
+            checkpoint_id = %checkpoint_id,
+            reason = ?reason,
+            "Checkpoint created in database"
+        );
+        self.apply_retention(&state.agent_id).await;
+        Ok(checkpoint_id)
+    }
+
+    async fn restore_from_checkpoint(
+        &self,
+        agent_id: &str,
+        checkpoint_id: &str,
+    ) -> Result<PersistentWorkflowState, PersistenceError> {
+        let row = sqlx::query(
+            "SELECT state, content_hash FROM workflow_checkpoints WHERE agent_id = ?1 AND checkpoint_id = ?2",
+        )
+        .bind(agent_id)
+        .bind(checkpoint_id)
+        .fetch_optional(&self.pool().await?)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            PersistenceError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Checkpoint {checkpoint_id} not found for agent {agent_id}"),
+            ))
+        })?;
+        let state = self
+            .decode(row.get("state"), row.get("content_hash"))
+            .await?;
+        info!(
+            agent_id = %agent_id,
+            checkpoint_id = %checkpoint_id,
+            "Restored from database checkpoint"
+        );
+        Ok(state)
+    }
+
+    /// Checkpoints newest first, read from the indexed summary columns
+    async fn list_checkpoints(
+        &self,
+        agent_id: &str,
+    ) -> Result<Vec<CheckpointInfo>, PersistenceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT checkpoint_id, reason, CAST(strftime('%s', created_at) AS INTEGER) AS created,
+                   current_state_type, transitions_count, recovery_attempts, uptime_minutes,
+                   length(state) AS size
+            FROM workflow_checkpoints
+            WHERE agent_id = ?1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(agent_id)
+        .fetch_all(&self.pool().await?)
+        .await
+        .map_err(database_error)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| CheckpointInfo {
+                checkpoint_id: row.get("checkpoint_id"),
+                creation_time: DateTime::from_timestamp(row.get("created"), 0).unwrap_or_default(),
+                reason: serde_json::from_value(serde_json::Value::String(row.get("reason")))
+                    .unwrap_or(CheckpointReason::PeriodicSave),
+                state_summary: StateSummary {
+                    current_state_type: row.get("current_state_type"),
+                    transitions_count: row.get::<i64, _>("transitions_count") as usize,
+                    recovery_attempts: row.get::<i64, _>("recovery_attempts") as usize,
+                    uptime_minutes: row.get("uptime_minutes"),
+                },
+                file_size: row.get::<i64, _>("size") as u64,
+            })
+            .collect())
+    }
+
+    async fn list_agents(&self) -> Result<Vec<String>, PersistenceError> {
+        let rows = sqlx::query(
+            "SELECT agent_id FROM workflow_states UNION SELECT agent_id FROM workflow_checkpoints ORDER BY agent_id",
+        )
+        .fetch_all(&self.pool().await?)
+        .await
+        .map_err(database_error)?;
+        Ok(rows.iter().map(|row| row.get("agent_id")).collect())
+    }
+
+    /// Rows are already stored once, so checkpoints are only ever deleted
+    async fn plan_cleanup(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT checkpoint_id, CAST(strftime('%s', created_at) AS INTEGER) AS created,
+                   length(state) AS size
+            FROM workflow_checkpoints
+            WHERE agent_id = ?1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(agent_id)
+        .fetch_all(&self.pool().await?)
+        .await
+        .map_err(database_error)?;
+
+        let checkpoints = rows
+            .iter()
+            .map(|row| CheckpointSize {
+                checkpoint_id: row.get("checkpoint_id"),
+                created_at: DateTime::from_timestamp(row.get("created"), 0).unwrap_or_default(),
+                bytes: row.get::<i64, _>("size") as u64,
+                compressed: false,
+            })
+            .collect();
+        Ok(plan_retention(
+            &self.config,
+            agent_id,
+            checkpoints,
+            false,
+            Utc::now(),
+        ))
+    }
+
+    /// Drop checkpoints past `backup_retention_days` or `max_checkpoints_per_agent`
+    async fn cleanup_old_data(&self, agent_id: &str) -> Result<CleanupPlan, PersistenceError> {
+        let plan = self.plan_cleanup(agent_id).await?;
+        if plan.is_empty() {
+            return Ok(plan);
+        }
+
+        let mut tx = self.pool().await?.begin().await.map_err(database_error)?;
+        for checkpoint in plan.deleted() {
+            sqlx::query(
+                "DELETE FROM workflow_checkpoints WHERE agent_id = ?1 AND checkpoint_id = ?2",
+            )
+            .bind(agent_id)
+            .bind(&checkpoint.checkpoint_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(database_error)?;
+        }
+        tx.commit().await.map_err(database_error)?;
+
+        info!(
+            agent_id = %agent_id,
+            expired = plan.expired.len(),
+            over_limit = plan.over_limit.len(),
+            retention_days = %self.config.backup_retention_days,
+            "Cleaned up old database checkpoints"
+        );
+        Ok(plan)
+    }
+
+    async fn verify_integrity(
+        &self,
+        state: &PersistentWorkflowState,
+    ) -> Result<bool, PersistenceError> {
+        if !self.config.enable_integrity_checks {
+            return Ok(true);
+        }
+        let expected_hash = &state.checkpoint_metadata.integrity_hash;
+        let actual_hash = calculate_integrity_hash(state);
+        let is_valid = *expected_hash == actual_hash;
+        if !is_valid {
+            warn!(
+                agent_id = %state.agent_id,
+                expected_hash = %expected_hash,
+                actual_hash = %actual_hash,
+                "State integrity check failed"
+            );
+        }
+        Ok(is_valid)
+    }
+}
+
+/// Insert `state` as a checkpoint, returning whether a row was added
+async fn insert_checkpoint<'e, E>(
+    executor: E,
+    state: &PersistentWorkflowState,
+    created_at: DateTime<Utc>,
+    keep_existing: bool,
+) -> anyhow::Result<bool>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let (json, hash) = encode(state)?;
+    let summary = StateSummary::from_state(state);
+    let conflict = if keep_existing { "OR IGNORE" } else { "" };
+    let inserted = sqlx::query(&format!(
+        r#"
+        INSERT {conflict} INTO workflow_checkpoints
+            (agent_id, checkpoint_id, reason, created_at, current_state_type,
+             transitions_count, recovery_attempts, uptime_minutes, state, content_hash)
+        VALUES (?1, ?2, ?3, datetime(?4, 'unixepoch'), ?5, ?6, ?7, ?8, ?9, ?10)
+        "#
+    ))
+    .bind(&state.agent_id)
+    .bind(&state.checkpoint_metadata.checkpoint_id)
+    .bind(reason_name(&state.checkpoint_metadata.creation_reason))
+    .bind(created_at.timestamp())
+    .bind(summary.current_state_type)
+    .bind(summary.transitions_count as i64)
+    .bind(summary.recovery_attempts as i64)
+    .bind(summary.uptime_minutes)
+    .bind(json)
+    .bind(hash)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(inserted > 0)
+}
+
+fn read_state_file(path: &Path, report: &mut ImportReport) -> Option<PersistentWorkflowState> {
+    match std::fs::read_to_string(path)
+        .map_err(PersistenceError::from)
+        .and_then(|contents| Ok(serde_json::from_str(&contents)?))
+    {
+        Ok(state) => Some(state),
+        Err(e) => {
+            warn!(file = ?path, error = %e, "Skipping unreadable workflow state file");
+            report.failed.push(path.to_path_buf());
+            None
+        }
+    }
+}
+
+/// Serialized state and the hash stored next to it
+fn encode(state: &PersistentWorkflowState) -> Result<(String, String), PersistenceError> {
+    let json = serde_json::to_string(state)?;
+    let hash = sha256(&json);
+    Ok((json, hash))
+}
+
+fn sha256(contents: &str) -> String {
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
+
+fn reason_name(reason: &CheckpointReason) -> String {
+    match serde_json::to_value(reason) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{reason:?}"),
+    }
+}
+
+fn database_error(e: impl std::fmt::Display) -> PersistenceError {
+    PersistenceError::DatabaseError {
+        reason: e.to_string(),
+    }
+}
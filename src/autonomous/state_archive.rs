@@ -0,0 +1,651 @@
+//! Portable agent state archives
+//!
+//! `state export` packs what an agent needs to carry its in-flight work to
+//! another machine into a single `.tar.gz`: a [`PersistentAgentState`] snapshot
+//! of the work context, the persisted workflow state and its checkpoints, and a
+//! git bundle of the commits on the agent's branch that never reached the
+//! remote. `state import` reads it back, checks it against GitHub, restores the
+//! branch and hands the agent state to the work continuity manager to resume.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use git2::{BranchType, Oid, Repository, Status, StatusOptions};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::persistence::{CheckpointReason, PersistentWorkflowState, StatePersistence};
+use super::work_continuity::{
+    AgentStateMachineData, Issue, PersistentAgentState, WorkProgress, WorkspaceSnapshot,
+};
+use crate::agents::integrator::PrKind;
+
+/// Version of the archive layout written by this build
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const AGENT_STATE_ENTRY: &str = "agent-state.json";
+const WORKFLOW_STATE_ENTRY: &str = "workflow-state.json";
+const CHECKPOINTS_DIR: &str = "checkpoints/";
+const GIT_BUNDLE_ENTRY: &str = "commits.bundle";
+
+/// What an archive holds and where it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub agent_id: String,
+    pub exported_at: DateTime<Utc>,
+    pub hostname: String,
+    /// `owner/repo` of the remote the agent pushes to, if known
+    pub repository: Option<String>,
+    pub branch: Option<String>,
+    pub issue: Option<u64>,
+    /// Tip of the branch when exported
+    pub head_commit: Option<String>,
+    /// Commits the remote doesn't have, carried in the git bundle
+    pub unpushed_commits: usize,
+    pub checkpoints: usize,
+}
+
+/// Everything exported for one agent
+#[derive(Debug, Clone)]
+pub struct StateArchive {
+    pub manifest: ArchiveManifest,
+    pub agent_state: PersistentAgentState,
+    pub workflow_state: Option<PersistentWorkflowState>,
+    /// Oldest first
+    pub checkpoints: Vec<PersistentWorkflowState>,
+    pub git_bundle: Option<Vec<u8>>,
+}
+
+impl StateArchive {
+    /// Collect the agent's persisted state and unpushed commits
+    pub async fn export(
+        agent_id: &str,
+        persistence: &(dyn StatePersistence + Send + Sync),
+        workspace: &AgentWorkspace,
+        branch: Option<String>,
+    ) -> Result<Self> {
+        let workflow_state = persistence.load_state(agent_id).await?;
+        let mut checkpoints = Vec::new();
+        for checkpoint in persistence.list_checkpoints(agent_id).await?.iter().rev() {
+            match persistence
+                .restore_from_checkpoint(agent_id, &checkpoint.checkpoint_id)
+                .await
+            {
+                Ok(state) => checkpoints.push(state),
+                Err(e) => warn!(
+                    agent_id = %agent_id,
+                    checkpoint_id = %checkpoint.checkpoint_id,
+                    error = %e,
+                    "Leaving unreadable checkpoint out of the export"
+                ),
+            }
+        }
+
+        let branch = match branch {
+            Some(branch) => Some(branch),
+            None => workspace.agent_branch(agent_id)?,
+        };
+        if workflow_state.is_none() && checkpoints.is_empty() && branch.is_none() {
+            bail!("Nothing to export: {agent_id} has no persisted state and no branch");
+        }
+
+        let (head_commit, unpushed, git_bundle) = match &branch {
+            Some(branch) => {
+                let head = workspace.branch_tip(branch)?;
+                let unpushed = workspace.unpushed_commits(branch)?;
+                let bundle = workspace.create_bundle(branch, unpushed.len())?;
+                (Some(head.to_string()), unpushed.len(), bundle)
+            }
+            None => (None, 0, None),
+        };
+        let issue = branch
+            .as_deref()
+            .and_then(|branch| match PrKind::from_branch(branch) {
+                Some(PrKind::Agent { issue_number, .. }) => Some(issue_number),
+                _ => None,
+            });
+
+        let agent_state = PersistentAgentState {
+            current_issue: issue.map(|number| {
+                work_issue(
+                    number,
+                    workflow_state.as_ref(),
+                    workspace.repository.as_deref(),
+                )
+            }),
+            current_branch: branch.clone(),
+            workspace_state: workspace.snapshot(branch.as_deref())?,
+            progress_checkpoint: WorkProgress {
+                commits_made: unpushed as u32,
+                files_modified: vec![],
+                tests_written: 0,
+                last_commit_sha: head_commit.clone(),
+                progress_description: format!(
+                    "Exported from {} with {unpushed} unpushed commit(s)",
+                    hostname()
+                ),
+                estimated_completion: None,
+            },
+            last_github_sync: Utc::now(),
+            pending_operations: vec![],
+            error_recovery_context: None,
+            session_id: Uuid::new_v4().to_string(),
+            uptime_start: workflow_state
+                .as_ref()
+                .and_then(|state| state.start_time)
+                .unwrap_or_else(Utc::now),
+            operation_history: vec![],
+            autonomous_state: workflow_state
+                .as_ref()
+                .and_then(|state| state.current_state.clone()),
+            last_autonomous_event: None,
+            state_machine_data: AgentStateMachineData {
+                agent_id: agent_id.to_string(),
+                current_issue: issue,
+                current_branch: branch.clone(),
+                commits_ahead: unpushed as u32,
+                bundle_issues: vec![],
+                bundle_pr: None,
+            },
+        };
+
+        Ok(Self {
+            manifest: ArchiveManifest {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                agent_id: agent_id.to_string(),
+                exported_at: Utc::now(),
+                hostname: hostname(),
+                repository: workspace.repository.clone(),
+                branch,
+                issue,
+                head_commit,
+                unpushed_commits: unpushed,
+                checkpoints: checkpoints.len(),
+            },
+            agent_state,
+            workflow_state,
+            checkpoints,
+            git_bundle,
+        })
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+        let mut append = |name: &str, data: &[u8]| -> Result<()> {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(self.manifest.exported_at.timestamp().max(0) as u64);
+            header.set_cksum();
+            tar.append_data(&mut header, name, data)?;
+            Ok(())
+        };
+        append(MANIFEST_ENTRY, &serde_json::to_vec_pretty(&self.manifest)?)?;
+        append(
+            AGENT_STATE_ENTRY,
+            &serde_json::to_vec_pretty(&self.agent_state)?,
+        )?;
+        if let Some(state) = &self.workflow_state {
+            append(WORKFLOW_STATE_ENTRY, &serde_json::to_vec_pretty(state)?)?;
+        }
+        for (index, checkpoint) in self.checkpoints.iter().enumerate() {
+            // Numbered so the original order survives the round trip
+            append(
+                &format!(
+                    "{CHECKPOINTS_DIR}{index:04}-{}.json",
+                    checkpoint.checkpoint_metadata.checkpoint_id
+                ),
+                &serde_json::to_vec_pretty(checkpoint)?,
+            )?;
+        }
+        if let Some(bundle) = &self.git_bundle {
+            append(GIT_BUNDLE_ENTRY, bundle)?;
+        }
+
+        tar.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut tar = tar::Archive::new(GzDecoder::new(file));
+
+        let mut manifest: Option<ArchiveManifest> = None;
+        let mut agent_state = None;
+        let mut workflow_state = None;
+        let mut checkpoints = Vec::new();
+        let mut git_bundle = None;
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().to_string();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            match name.as_str() {
+                MANIFEST_ENTRY => manifest = Some(serde_json::from_slice(&data)?),
+                AGENT_STATE_ENTRY => agent_state = Some(serde_json::from_slice(&data)?),
+                WORKFLOW_STATE_ENTRY => workflow_state = Some(serde_json::from_slice(&data)?),
+                GIT_BUNDLE_ENTRY => git_bundle = Some(data),
+                _ if name.starts_with(CHECKPOINTS_DIR) => {
+                    checkpoints.push((name, serde_json::from_slice(&data)?))
+                }
+                _ => warn!(entry = %name, "Ignoring unknown entry in state archive"),
+            }
+        }
+
+        let manifest = manifest
+            .ok_or_else(|| anyhow!("{} is not a state archive: no manifest", path.display()))?;
+        if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+            bail!(
+                "{} was written in archive format {}, this build reads up to {}",
+                path.display(),
+                manifest.format_version,
+                ARCHIVE_FORMAT_VERSION
+            );
+        }
+        checkpoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(Self {
+            agent_state: agent_state
+                .ok_or_else(|| anyhow!("{} has no agent state", path.display()))?,
+            manifest,
+            workflow_state,
+            checkpoints: checkpoints.into_iter().map(|(_, state)| state).collect(),
+            git_bundle,
+        })
+    }
+
+    /// Save the workflow state and checkpoints, returning how many checkpoints were kept
+    pub async fn restore_workflow_state(
+        &self,
+        persistence: &(dyn StatePersistence + Send + Sync),
+    ) -> Result<usize> {
+        for checkpoint in &self.checkpoints {
+            persistence
+                .create_checkpoint(
+                    checkpoint,
+                    checkpoint.checkpoint_metadata.creation_reason.clone(),
+                )
+                .await?;
+        }
+        if let Some(state) = &self.workflow_state {
+            persistence
+                .save_state(state, CheckpointReason::UserRequested)
+                .await?;
+        }
+        Ok(self.checkpoints.len())
+    }
+}
+
+/// What GitHub currently says about the exported work
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitHubFacts {
+    /// `owner/repo` the importing machine works on
+    pub repository: String,
+    /// `Some(open)` if the issue exists
+    pub issue_open: Option<bool>,
+    pub issue_labels: Vec<String>,
+    pub branch_on_remote: bool,
+}
+
+/// Reasons the archive no longer matches GitHub, empty if it can be imported
+pub fn validate_against_github(manifest: &ArchiveManifest, facts: &GitHubFacts) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(repository) = &manifest.repository {
+        if !repository.eq_ignore_ascii_case(&facts.repository) {
+            problems.push(format!(
+                "Exported from {repository}, but this checkout works on {}",
+                facts.repository
+            ));
+        }
+    }
+    if let Some(issue) = manifest.issue {
+        match facts.issue_open {
+            None => problems.push(format!("Issue #{issue} no longer exists")),
+            Some(false) => problems.push(format!("Issue #{issue} has been closed")),
+            Some(true) if !facts.issue_labels.contains(&manifest.agent_id) => problems.push(
+                format!("Issue #{issue} is no longer labeled {}", manifest.agent_id),
+            ),
+            Some(true) => {}
+        }
+    }
+    if let Some(branch) = &manifest.branch {
+        if manifest.unpushed_commits == 0 && !facts.branch_on_remote {
+            problems.push(format!(
+                "{branch} has no unpushed commits in the archive and is not on GitHub"
+            ));
+        }
+    }
+    problems
+}
+
+/// How the agent's branch was brought back
+#[derive(Debug, Clone, PartialEq)]
+pub enum BranchRestore {
+    /// Unpushed commits were fetched from the bundle
+    FromBundle { branch: String, head: Oid },
+    /// Everything was already on the remote
+    FromRemote { branch: String, head: Oid },
+    /// The local branch already pointed at the exported tip
+    AlreadyPresent { branch: String, head: Oid },
+}
+
+/// The git checkout an agent works in
+pub struct AgentWorkspace {
+    path: PathBuf,
+    remote: String,
+    /// `owner/repo` of the remote, when it is on GitHub
+    pub repository: Option<String>,
+}
+
+impl AgentWorkspace {
+    pub fn open(path: impl Into<PathBuf>, remote: &str) -> Result<Self> {
+        let path = path.into();
+        let repo = Repository::open(&path)
+            .with_context(|| format!("{} is not a git repository", path.display()))?;
+        let repository = repo
+            .find_remote(remote)
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string))
+            .and_then(|url| github_repository(&url));
+        Ok(Self {
+            path,
+            remote: remote.to_string(),
+            repository,
+        })
+    }
+
+    fn repo(&self) -> Result<Repository> {
+        Ok(Repository::open(&self.path)?)
+    }
+
+    /// The agent's branch: the checked out one if it is the agent's, else its newest
+    pub fn agent_branch(&self, agent_id: &str) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let is_agents = |name: &str| matches!(PrKind::from_branch(name), Some(PrKind::Agent { agent_id: ref id, .. }) if id == agent_id);
+        if let Some(head) = repo
+            .head()
+            .ok()
+            .and_then(|h| h.shorthand().map(str::to_string))
+        {
+            if is_agents(&head) {
+                return Ok(Some(head));
+            }
+        }
+
+        let mut newest: Option<(i64, String)> = None;
+        for branch in repo.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch?;
+            let Some(name) = branch.name()?.map(str::to_string) else {
+                continue;
+            };
+            if !is_agents(&name) {
+                continue;
+            }
+            let time = branch.get().peel_to_commit()?.time().seconds();
+            if newest.as_ref().is_none_or(|(newest, _)| time > *newest) {
+                newest = Some((time, name));
+            }
+        }
+        Ok(newest.map(|(_, name)| name))
+    }
+
+    pub fn branch_tip(&self, branch: &str) -> Result<Oid> {
+        self.repo()?
+            .find_branch(branch, BranchType::Local)
+            .with_context(|| format!("No local branch {branch}"))?
+            .get()
+            .target()
+            .ok_or_else(|| anyhow!("{branch} does not point at a commit"))
+    }
+
+    /// Commits on `branch` that no ref of the remote contains, newest first
+    pub fn unpushed_commits(&self, branch: &str) -> Result<Vec<Oid>> {
+        let repo = self.repo()?;
+        let mut walk = repo.revwalk()?;
+        walk.push(self.branch_tip(branch)?)?;
+        for reference in repo.references_glob(&format!("refs/remotes/{}/*", self.remote))? {
+            if let Some(target) = reference?.resolve().ok().and_then(|r| r.target()) {
+                walk.hide(target)?;
+            }
+        }
+        Ok(walk.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// A git bundle of the unpushed commits, `None` if there are none
+    fn create_bundle(&self, branch: &str, unpushed: usize) -> Result<Option<Vec<u8>>> {
+        if unpushed == 0 {
+            return Ok(None);
+        }
+        let bundle_path = std::env::temp_dir().join(format!("mls-{}.bundle", Uuid::new_v4()));
+        let result = self
+            .git(&[
+                "bundle",
+                "create",
+                &bundle_path.to_string_lossy(),
+                &format!("refs/heads/{branch}"),
+                "--not",
+                &format!("--remotes={}", self.remote),
+            ])
+            .and_then(|_| Ok(std::fs::read(&bundle_path)?));
+        let _ = std::fs::remove_file(&bundle_path);
+        result.map(Some)
+    }
+
+    /// Bring the remote-tracking refs up to date, so bundles find their base
+    pub fn fetch(&self) -> Result<()> {
+        self.git(&["fetch", "--quiet", &self.remote])
+    }
+
+    /// Recreate the agent's branch at the exported tip
+    ///
+    /// Never moves an existing local branch: one that points elsewhere is
+    /// reported so the person importing can decide what to keep.
+    pub fn restore_branch(
+        &self,
+        manifest: &ArchiveManifest,
+        bundle: Option<&[u8]>,
+    ) -> Result<BranchRestore> {
+        let branch = manifest
+            .branch
+            .clone()
+            .ok_or_else(|| anyhow!("The archive has no branch to restore"))?;
+        let expected = manifest
+            .head_commit
+            .as_deref()
+            .map(Oid::from_str)
+            .transpose()?;
+        let repo = self.repo()?;
+
+        if let Ok(existing) = repo.find_branch(&branch, BranchType::Local) {
+            let head = existing
+                .get()
+                .target()
+                .ok_or_else(|| anyhow!("{branch} does not point at a commit"))?;
+            if expected.is_none_or(|expected| expected == head) {
+                return Ok(BranchRestore::AlreadyPresent { branch, head });
+            }
+            bail!(
+                "{branch} already exists here at {head}, but the archive has it at {}; rename or delete the local branch first",
+                manifest.head_commit.as_deref().unwrap_or("an unknown commit")
+            );
+        }
+
+        let restore = match bundle {
+            Some(bundle) => {
+                let bundle_path =
+                    std::env::temp_dir().join(format!("mls-{}.bundle", Uuid::new_v4()));
+                std::fs::write(&bundle_path, bundle)?;
+                let bundle_arg = bundle_path.to_string_lossy().to_string();
+                let result = self
+                    .git(&["bundle", "verify", "--quiet", &bundle_arg])
+                    .context(format!(
+                        "The bundle builds on commits this checkout doesn't have; fetch {} and try again",
+                        self.remote
+                    ))
+                    .and_then(|_| {
+                        self.git(&[
+                            "fetch",
+                            "--quiet",
+                            &bundle_arg,
+                            &format!("refs/heads/{branch}:refs/heads/{branch}"),
+                        ])
+                    });
+                let _ = std::fs::remove_file(&bundle_path);
+                result?;
+                BranchRestore::FromBundle {
+                    head: self.branch_tip(&branch)?,
+                    branch: branch.clone(),
+                }
+            }
+            None => {
+                let remote_ref = format!("refs/remotes/{}/{branch}", self.remote);
+                let commit = repo
+                    .find_reference(&remote_ref)
+                    .with_context(|| format!("{branch} is not on {}; fetch it first", self.remote))?
+                    .peel_to_commit()?;
+                repo.branch(&branch, &commit, false)?;
+                BranchRestore::FromRemote {
+                    head: commit.id(),
+                    branch: branch.clone(),
+                }
+            }
+        };
+
+        let (BranchRestore::FromBundle { head, .. }
+        | BranchRestore::FromRemote { head, .. }
+        | BranchRestore::AlreadyPresent { head, .. }) = &restore;
+        if let Some(expected) = expected {
+            if *head != expected {
+                bail!("Restored {branch} at {head}, but the archive expected {expected}");
+            }
+        }
+        info!(branch = %branch, head = %head, "Restored agent branch");
+        Ok(restore)
+    }
+
+    /// Current checkout, as recorded in the agent state
+    fn snapshot(&self, branch: Option<&str>) -> Result<WorkspaceSnapshot> {
+        let repo = self.repo()?;
+        let head = repo.head().ok();
+        let current_branch = head
+            .as_ref()
+            .and_then(|h| h.shorthand().map(str::to_string))
+            .unwrap_or_default();
+
+        let mut snapshot = WorkspaceSnapshot {
+            current_directory: self.path.clone(),
+            git_branch: branch.map(str::to_string).unwrap_or(current_branch.clone()),
+            git_commit: head
+                .and_then(|h| h.target())
+                .map(|oid| oid.to_string())
+                .unwrap_or_default(),
+            uncommitted_changes: false,
+            staged_files: vec![],
+            modified_files: vec![],
+            untracked_files: vec![],
+        };
+        // Uncommitted changes only belong to the branch that is checked out
+        if branch.is_some_and(|branch| branch != current_branch) {
+            return Ok(snapshot);
+        }
+        let mut options = StatusOptions::new();
+        options.include_untracked(true);
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            let Some(path) = entry.path().map(str::to_string) else {
+                continue;
+            };
+            let status = entry.status();
+            if status.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED,
+            ) {
+                snapshot.staged_files.push(path.clone());
+            }
+            if status.intersects(Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED) {
+                snapshot.modified_files.push(path.clone());
+            }
+            if status.contains(Status::WT_NEW) {
+                snapshot.untracked_files.push(path);
+            }
+        }
+        snapshot.uncommitted_changes = !snapshot.staged_files.is_empty()
+            || !snapshot.modified_files.is_empty()
+            || !snapshot.untracked_files.is_empty();
+        Ok(snapshot)
+    }
+
+    fn git(&self, args: &[&str]) -> Result<()> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.path)
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The issue the agent works on, with whatever the workflow state knows about it
+fn work_issue(
+    number: u64,
+    workflow_state: Option<&PersistentWorkflowState>,
+    repository: Option<&str>,
+) -> Issue {
+    let known = workflow_state
+        .and_then(|state| state.current_state.as_ref())
+        .map(|state| state.issue())
+        .filter(|issue| issue.number == number);
+    Issue {
+        number,
+        title: known.map(|i| i.title.clone()).unwrap_or_default(),
+        body: known.map(|i| i.body.clone()).unwrap_or_default(),
+        labels: known.map(|i| i.labels.clone()).unwrap_or_default(),
+        assignee: None,
+        milestone: None,
+        url: repository
+            .map(|repository| format!("https://github.com/{repository}/issues/{number}"))
+            .unwrap_or_default(),
+    }
+}
+
+/// `owner/repo` from a GitHub remote URL
+fn github_repository(url: &str) -> Option<String> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.strip_prefix("https://github.com/"))
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))?;
+    Some(
+        path.trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_string(),
+    )
+}
+
+fn hostname() -> String {
+    hostname::get()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
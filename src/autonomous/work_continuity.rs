@@ -211,6 +211,21 @@ impl Default for WorkContinuityConfig {
     }
 }
 
+impl WorkContinuityConfig {
+    /// Settings from `[agents.work_continuity]`
+    pub fn from_config(config: &crate::config::WorkContinuityConfig) -> Self {
+        Self {
+            enable_continuity: config.enable_continuity,
+            state_file_path: PathBuf::from(&config.state_file_path),
+            backup_interval_minutes: config.backup_interval_minutes,
+            max_recovery_attempts: config.max_recovery_attempts,
+            validation_timeout_seconds: config.validation_timeout_seconds,
+            force_fresh_start_after_hours: config.force_fresh_start_after_hours,
+            preserve_partial_work: config.preserve_partial_work,
+        }
+    }
+}
+
 /// Main work continuity manager
 pub struct WorkContinuityManager {
     config: WorkContinuityConfig,
@@ -485,6 +500,25 @@ impl WorkContinuityManager {
         }
     }
 
+    /// Take over agent state carried from another machine
+    ///
+    /// The state becomes the current one and the returned action says how to
+    /// resume it, as after a restart (see [`Self::resume_interrupted_work`]).
+    pub async fn adopt_imported_state(
+        &self,
+        agent_state: PersistentAgentState,
+    ) -> Result<ResumeAction, WorkContinuityError> {
+        let resume_action = self.determine_resume_action(&agent_state).await?;
+        info!(
+            agent_id = %agent_state.state_machine_data.agent_id,
+            session_id = %agent_state.session_id,
+            action = ?resume_action,
+            "Adopted imported agent state"
+        );
+        *self.current_state.write().await = Some(agent_state);
+        Ok(resume_action)
+    }
+
     /// Get current work continuity status
     pub async fn get_continuity_status(
         &self,
@@ -589,6 +623,7 @@ impl WorkContinuityManager {
                     .to_string(),
             },
             last_persisted: agent_state.last_github_sync,
+            history_summary: None,
         })
     }
 
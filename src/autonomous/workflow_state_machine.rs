@@ -81,6 +81,26 @@ pub enum AutonomousWorkflowState {
     },
 }
 
+impl AutonomousWorkflowState {
+    /// The issue this state is about; every state has one
+    pub fn issue(&self) -> &Issue {
+        match self {
+            AutonomousWorkflowState::Unassigned { issue }
+            | AutonomousWorkflowState::Assigned { issue, .. }
+            | AutonomousWorkflowState::InProgress { issue, .. }
+            | AutonomousWorkflowState::Blocked { issue, .. }
+            | AutonomousWorkflowState::ReadyForReview { issue, .. }
+            | AutonomousWorkflowState::UnderReview { issue, .. }
+            | AutonomousWorkflowState::ChangesRequested { issue, .. }
+            | AutonomousWorkflowState::Approved { issue, .. }
+            | AutonomousWorkflowState::MergeConflict { issue, .. }
+            | AutonomousWorkflowState::CIFailure { issue, .. }
+            | AutonomousWorkflowState::Merged { issue, .. }
+            | AutonomousWorkflowState::Abandoned { issue, .. } => issue,
+        }
+    }
+}
+
 /// Events that can trigger state transitions in autonomous workflow
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AutonomousEvent {
@@ -296,6 +316,8 @@ pub struct AutonomousWorkflowMachine {
     pub github_client: Option<GitHubClient>,
     pub recovery_client: Option<Box<dyn AutomaticRecovery + Send + Sync>>,
     pub state_history: Vec<StateTransitionRecord>,
+    /// Issue and workspace the next `AssignAgent` starts on
+    queued_issue: Option<(Issue, WorkspaceState)>,
 }
 
 impl std::fmt::Debug for AutonomousWorkflowMachine {
@@ -308,6 +330,7 @@ impl std::fmt::Debug for AutonomousWorkflowMachine {
             .field("github_client", &self.github_client.is_some())
             .field("recovery_client", &self.recovery_client.is_some())
             .field("state_history", &self.state_history)
+            .field("queued_issue", &self.queued_issue)
             .finish()
     }
 }
@@ -347,6 +370,11 @@ impl AutonomousWorkflowMachine {
         self
     }
 
+    /// Work on `issue` in `workspace` on the next `AssignAgent` instead of a placeholder
+    pub fn queue_issue(&mut self, issue: Issue, workspace: WorkspaceState) {
+        self.queued_issue = Some((issue, workspace));
+    }
+
     /// Record state transition for audit trail
     fn record_transition(
         &mut self,
@@ -419,8 +447,8 @@ impl AutonomousWorkflowMachine {
         let start_time = std::time::Instant::now();
         let from_state = self.current_state.clone();
 
-        // Check timeout before processing any event
-        if self.is_timeout_exceeded() {
+        // Check timeout before processing any event; resetting is how an abandoned task is left
+        if event != AutonomousEvent::Reset && self.is_timeout_exceeded() {
             let timeout_event = AutonomousEvent::ForceAbandon {
                 reason: AbandonmentReason::TimeoutExceeded {
                     max_hours: self.max_work_hours,
@@ -441,16 +469,20 @@ impl AutonomousWorkflowMachine {
                 self.agent_id = Some(agent.clone());
                 self.start_time = Some(Utc::now());
 
+                let (issue, workspace) = self.queued_issue.take().unwrap_or_else(|| {
+                    (
+                        self.create_placeholder_issue(),
+                        self.create_workspace_state(),
+                    )
+                });
                 if *workspace_ready {
                     Some(AutonomousWorkflowState::Assigned {
-                        issue: self.create_placeholder_issue(),
+                        issue,
                         agent: agent.clone(),
-                        workspace: self.create_workspace_state(),
+                        workspace,
                     })
                 } else {
-                    Some(AutonomousWorkflowState::Unassigned {
-                        issue: self.create_placeholder_issue(),
-                    })
+                    Some(AutonomousWorkflowState::Unassigned { issue })
                 }
             }
 
@@ -721,11 +753,13 @@ impl AutonomousWorkflowMachine {
                 })
             }
 
-            // Reset transitions (terminal -> initial)
+            // Reset transitions (terminal -> initial); work handed to bundling frees the agent too
             (Some(AutonomousWorkflowState::Merged { .. }), AutonomousEvent::Reset)
-            | (Some(AutonomousWorkflowState::Abandoned { .. }), AutonomousEvent::Reset) => {
+            | (Some(AutonomousWorkflowState::Abandoned { .. }), AutonomousEvent::Reset)
+            | (Some(AutonomousWorkflowState::ReadyForReview { .. }), AutonomousEvent::Reset) => {
                 self.agent_id = None;
                 self.start_time = None;
+                self.current_state = None;
                 None // Back to initial state
             }
 
@@ -778,20 +812,7 @@ impl AutonomousWorkflowMachine {
 
     /// Extract issue from any state that contains one
     fn extract_issue_from_state(&self, state: &AutonomousWorkflowState) -> Option<Issue> {
-        match state {
-            AutonomousWorkflowState::Unassigned { issue }
-            | AutonomousWorkflowState::Assigned { issue, .. }
-            | AutonomousWorkflowState::InProgress { issue, .. }
-            | AutonomousWorkflowState::Blocked { issue, .. }
-            | AutonomousWorkflowState::ReadyForReview { issue, .. }
-            | AutonomousWorkflowState::UnderReview { issue, .. }
-            | AutonomousWorkflowState::ChangesRequested { issue, .. }
-            | AutonomousWorkflowState::Approved { issue, .. }
-            | AutonomousWorkflowState::MergeConflict { issue, .. }
-            | AutonomousWorkflowState::CIFailure { issue, .. }
-            | AutonomousWorkflowState::Merged { issue, .. }
-            | AutonomousWorkflowState::Abandoned { issue, .. } => Some(issue.clone()),
-        }
+        Some(state.issue().clone())
     }
 
     /// Create placeholder issue for error cases
@@ -1031,4 +1052,75 @@ mod tests {
         ));
         assert!(matches!(history[1].event, AutonomousEvent::StartWork));
     }
+
+    #[test]
+    fn test_reset_after_work_is_handed_over_frees_the_agent() {
+        let mut workflow = AutonomousWorkflowMachine::new(8);
+
+        tokio_test::block_on(async {
+            workflow
+                .handle_event(AutonomousEvent::AssignAgent {
+                    agent: AgentId("agent001".to_string()),
+                    workspace_ready: true,
+                })
+                .await
+                .unwrap();
+            workflow
+                .handle_event(AutonomousEvent::StartWork)
+                .await
+                .unwrap();
+            workflow
+                .handle_event(AutonomousEvent::CompleteWork)
+                .await
+                .unwrap();
+            assert!(matches!(
+                workflow.current_state(),
+                Some(AutonomousWorkflowState::ReadyForReview { .. })
+            ));
+
+            workflow.handle_event(AutonomousEvent::Reset).await.unwrap();
+            assert!(workflow.current_state().is_none());
+            assert!(workflow.agent_id.is_none());
+            assert!(workflow.start_time.is_none());
+
+            // The agent can take the next issue
+            workflow
+                .handle_event(AutonomousEvent::AssignAgent {
+                    agent: AgentId("agent001".to_string()),
+                    workspace_ready: true,
+                })
+                .await
+                .unwrap();
+        });
+
+        assert!(matches!(
+            workflow.current_state(),
+            Some(AutonomousWorkflowState::Assigned { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reset_leaves_a_timed_out_task() {
+        let mut workflow = AutonomousWorkflowMachine::new(0);
+
+        tokio_test::block_on(async {
+            workflow
+                .handle_event(AutonomousEvent::AssignAgent {
+                    agent: AgentId("agent001".to_string()),
+                    workspace_ready: true,
+                })
+                .await
+                .unwrap();
+            let _ = workflow.handle_event(AutonomousEvent::StartWork).await;
+            assert!(matches!(
+                workflow.current_state(),
+                Some(AutonomousWorkflowState::Abandoned { .. })
+            ));
+
+            // Resetting is not itself abandoned for running out of time
+            workflow.handle_event(AutonomousEvent::Reset).await.unwrap();
+        });
+
+        assert!(workflow.current_state().is_none());
+    }
 }
@@ -1,23 +1,55 @@
 use anyhow::{anyhow, Result};
+use chrono::Utc;
 use fd_lock::{RwLock, RwLockWriteGuard};
 use std::collections::HashMap;
 use std::fs::File;
 
+use super::path_policy::{hold_for_human_review, PathPolicy};
+use super::size_limits::{combined_files, SizeReport};
 use super::{
-    git_ops::{ConflictCompatibilityReport, ConflictStrategy, GitOperations},
-    types::{BundleAuditEntry, BundleOperationStatus, BundleResult, BundleState, BundleWindow},
+    git_ops::{
+        AssemblyMode, BranchChangelog, BranchRebase, CherryPickOutcome,
+        ConflictCompatibilityReport, ConflictStrategy, GitOperations, HunkRange,
+    },
+    ordering::{self, BranchFiles},
+    persistence,
+    pr_body::{BundlePrDescription, IssueChangelog},
+    rebuild::{self, RebuildPlan, ReviewerComment},
+    refresh::{self, BranchRefresh, RefreshOutcome, NEEDS_REBASE_LABEL},
+    types::{
+        BundleAuditEntry, BundleHistoryEntry, BundleManifest, BundleOperationStatus, BundleResult,
+        BundleState, BundleStatus, BundleWindow, PendingConflict, RecoveryData,
+    },
+    unblocker::{self, UnblockerTask, BLOCKED_LABEL, UNBLOCKER_LABEL},
 };
+use crate::agent_lifecycle::types::PreFlightIssue;
+use crate::config::DiffLimitsConfig;
+use crate::git::secrets::{secrets_detected, SecretsDetected};
+use crate::github::labels::HUMAN_ONLY_LABEL;
 use crate::github::GitHubClient;
 use crate::train_schedule::QueuedBranch;
+use git2::Oid;
 use std::fs;
 
+/// Base branch every bundle is assembled on
+const BUNDLE_BASE_BRANCH: &str = "main";
+
+const BUNDLE_STATE_PATH: &str = ".my-little-soda/bundle_state.json";
+
 /// Main bundle management system
 pub struct BundleManager {
     git_ops: GitOperations,
     github_client: GitHubClient,
     _lock_guard: Option<RwLockWriteGuard<'static, File>>,
-    #[allow(dead_code)]
     bundle_state: Option<BundleState>,
+    conflict_strategy: ConflictStrategy,
+    assembly_mode: AssemblyMode,
+    trial_merge: bool,
+    max_branches_per_bundle: Option<usize>,
+    open_unblockers: bool,
+    rebase_queued_branches: bool,
+    diff_limits: DiffLimitsConfig,
+    path_policy: PathPolicy,
 }
 
 impl BundleManager {
@@ -36,11 +68,53 @@ impl BundleManager {
         let git_ops = GitOperations::new()?;
         let github_client = GitHubClient::with_verbose(false)?;
 
-        let bundle_manager = Self {
+        let (
+            conflict_strategy,
+            assembly_mode,
+            trial_merge,
+            max_branches_per_bundle,
+            open_unblockers,
+            rebase_queued_branches,
+        ) = crate::config::config()
+            .map(|c| {
+                (
+                    c.agents.bundle_processing.conflict_strategy.clone(),
+                    c.agents.bundle_processing.assembly_mode.clone(),
+                    c.agents.bundle_processing.trial_merge,
+                    c.agents.bundle_processing.max_branches_per_bundle,
+                    c.agents.bundle_processing.open_unblockers,
+                    c.agents.bundle_processing.rebase_queued_branches,
+                )
+            })
+            .unwrap_or((
+                ConflictStrategy::default(),
+                AssemblyMode::default(),
+                true,
+                None,
+                true,
+                true,
+            ));
+
+        let mut bundle_manager = Self {
             git_ops,
             github_client,
             _lock_guard: Some(guard),
             bundle_state: None,
+            conflict_strategy,
+            assembly_mode,
+            trial_merge,
+            max_branches_per_bundle,
+            open_unblockers,
+            rebase_queued_branches,
+            diff_limits: crate::config::config()
+                .map(|c| c.diff_limits.clone())
+                .unwrap_or_default(),
+            path_policy: PathPolicy::for_repository(
+                std::path::Path::new("."),
+                &crate::config::config()
+                    .map(|c| c.protected_paths.clone())
+                    .unwrap_or_default(),
+            )?,
         };
 
         // Try to restore any previous state
@@ -49,18 +123,67 @@ impl BundleManager {
         Ok(bundle_manager)
     }
 
+    /// Override the configured conflict strategy
+    pub fn with_conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = strategy;
+        self
+    }
+
+    /// Override the configured assembly mode
+    pub fn with_assembly_mode(mut self, mode: AssemblyMode) -> Self {
+        self.assembly_mode = mode;
+        self
+    }
+
     /// Try to restore previous bundle state
-    fn try_restore_state(&self) -> Result<()> {
-        let state_path = ".my-little-soda/bundle_state.json";
-        if std::path::Path::new(state_path).exists() {
+    ///
+    /// Only a bundle paused for manual conflict resolution is kept; any other
+    /// leftover state belongs to an interrupted run and is discarded.
+    fn try_restore_state(&mut self) -> Result<()> {
+        let state_path = std::path::Path::new(BUNDLE_STATE_PATH);
+        if state_path.exists() {
             println!("🔄 Found previous bundle state, checking for recovery...");
-            // For now, just remove the old state file
-            // In production, we'd implement proper state restoration
-            fs::remove_file(state_path).ok();
+            let state = fs::read_to_string(state_path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<BundleState>(&content).ok());
+
+            match state {
+                Some(state) if state.pending_conflict.is_some() => {
+                    println!(
+                        "⏸️  Bundle {} is waiting for manual conflict resolution",
+                        state.bundle_branch
+                    );
+                    self.bundle_state = Some(state);
+                }
+                _ => {
+                    fs::remove_file(state_path).ok();
+                }
+            }
         }
         Ok(())
     }
 
+    /// Persist the bundle state so a paused bundle survives the process
+    fn save_bundle_state(&mut self, state: BundleState) -> Result<()> {
+        let content = serde_json::to_string_pretty(&state)?;
+        fs::write(BUNDLE_STATE_PATH, content)?;
+        self.bundle_state = Some(state);
+        Ok(())
+    }
+
+    /// Forget the bundle state once the bundle is finished or abandoned
+    fn clear_bundle_state(&mut self) {
+        self.bundle_state = None;
+        fs::remove_file(BUNDLE_STATE_PATH).ok();
+    }
+
+    /// Pending conflict of a paused bundle, if any
+    pub fn pending_conflict(&self) -> Option<&PendingConflict> {
+        self.bundle_state
+            .as_ref()
+            .and_then(|state| state.pending_conflict.as_ref())
+    }
+
     /// Generate deterministic bundle branch name
     pub fn generate_bundle_branch_name(&self, queued_branches: &[QueuedBranch]) -> String {
         let window = BundleWindow::current();
@@ -68,6 +191,424 @@ impl BundleManager {
         window.bundle_branch_name(&issues)
     }
 
+    /// Split the queue into the bundles to create, each in assembly order
+    ///
+    /// Without `max_branches_per_bundle` everything goes into a single bundle.
+    pub fn plan_bundles(&self, queued_branches: &[QueuedBranch]) -> Vec<Vec<QueuedBranch>> {
+        let files = self.branch_files(queued_branches, BUNDLE_BASE_BRANCH);
+        let ordered = self.order_queued_branches(queued_branches, &files);
+
+        match self.max_branches_per_bundle {
+            Some(max) if ordered.len() > max => {
+                let bundles = ordering::partition_branches(&ordered, &files, max);
+                println!(
+                    "🧩 Partitioned {} branches into {} independent bundles",
+                    ordered.len(),
+                    bundles.len()
+                );
+                bundles
+            }
+            _ => vec![ordered],
+        }
+    }
+
+    /// Files changed by each queued branch relative to the bundle base
+    fn branch_files(&self, queued_branches: &[QueuedBranch], base_branch: &str) -> BranchFiles {
+        let changelogs = self.branch_changelogs(queued_branches, base_branch);
+        changelog_files(queued_branches, &changelogs)
+    }
+
+    /// Changes of each queued branch relative to the base, by branch name
+    ///
+    /// Branches whose changes can't be read are missing from the map.
+    fn branch_changelogs(
+        &self,
+        queued_branches: &[QueuedBranch],
+        base_branch: &str,
+    ) -> HashMap<String, BranchChangelog> {
+        queued_branches
+            .iter()
+            .filter_map(|branch| {
+                match self
+                    .git_ops
+                    .branch_changelog(&branch.branch_name, base_branch)
+                {
+                    Ok(changelog) => Some((branch.branch_name.clone(), changelog)),
+                    Err(e) => {
+                        println!(
+                            "⚠️  Could not list changes of {}: {}",
+                            branch.branch_name, e
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Order branches by dependencies, then file overlap, then issue number
+    fn order_queued_branches(
+        &self,
+        queued_branches: &[QueuedBranch],
+        files: &BranchFiles,
+    ) -> Vec<QueuedBranch> {
+        let order = ordering::order_branches(queued_branches, files);
+        if !order.dependency_cycle.is_empty() {
+            println!(
+                "⚠️  Dependency cycle between issues {}, applying them by overlap order",
+                order
+                    .dependency_cycle
+                    .iter()
+                    .map(|n| format!("#{n}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        order.branches
+    }
+
+    /// Rebase queued branches that fell behind the base, before they are bundled
+    ///
+    /// Returns nothing when the refresh is disabled. Branches whose rebase
+    /// conflicts are flagged on their issue; `BranchRefresh::is_bundleable` tells
+    /// the caller to leave them out.
+    pub async fn refresh_queued_branches(
+        &mut self,
+        queued_branches: &[QueuedBranch],
+        dry_run: bool,
+    ) -> Vec<BranchRefresh> {
+        if !self.rebase_queued_branches {
+            return Vec::new();
+        }
+
+        // The remote-tracking refs are the leases for the force-pushes below
+        if !dry_run {
+            let branch_names: Vec<String> = queued_branches
+                .iter()
+                .map(|b| b.branch_name.clone())
+                .collect();
+            if let Err(e) = self.git_ops.fetch_branches(&branch_names, "origin") {
+                println!("⚠️  Could not fetch queued branches from origin: {e}");
+            }
+        }
+
+        let mut refreshes = Vec::new();
+        for branch in queued_branches {
+            let outcome = self
+                .refresh_branch(branch, BUNDLE_BASE_BRANCH, dry_run)
+                .await;
+            refreshes.push(BranchRefresh {
+                branch: branch.clone(),
+                outcome,
+            });
+        }
+        refreshes
+    }
+
+    /// Rebase one branch onto the base in a scratch worktree and force-push it
+    async fn refresh_branch(
+        &mut self,
+        branch: &QueuedBranch,
+        base_branch: &str,
+        dry_run: bool,
+    ) -> RefreshOutcome {
+        let behind = match self.git_ops.behind_base(&branch.branch_name, base_branch) {
+            Ok(Some(PreFlightIssue::BehindMain { commits })) => commits,
+            Ok(_) => {
+                if !dry_run {
+                    self.clear_needs_rebase(branch).await;
+                }
+                return RefreshOutcome::UpToDate;
+            }
+            Err(e) => return RefreshOutcome::Failed(e.to_string()),
+        };
+        if dry_run {
+            return RefreshOutcome::WouldRebase { behind };
+        }
+
+        let previous_tip = match self.git_ops.branch_tip(&branch.branch_name) {
+            Ok(tip) => tip,
+            Err(e) => return RefreshOutcome::Failed(e.to_string()),
+        };
+        let remote_tip = self.git_ops.remote_tip(&branch.branch_name, "origin");
+        if let Some(remote_tip) = remote_tip {
+            // Rebasing without the remote's commits would force-push them away
+            match self.git_ops.is_ancestor(remote_tip, previous_tip) {
+                Ok(true) => {}
+                Ok(false) => {
+                    return RefreshOutcome::Failed(format!(
+                        "origin/{} has commits the local branch lacks",
+                        branch.branch_name
+                    ))
+                }
+                Err(e) => return RefreshOutcome::Failed(e.to_string()),
+            }
+        }
+        let tip = match self
+            .git_ops
+            .rebase_in_worktree(&branch.branch_name, base_branch)
+        {
+            Ok(BranchRebase::Rebased { tip }) => tip,
+            Ok(BranchRebase::Conflicted {
+                conflicted_files, ..
+            }) => {
+                self.flag_needs_rebase(branch, base_branch, behind, &conflicted_files)
+                    .await;
+                return RefreshOutcome::NeedsResolution {
+                    behind,
+                    conflicted_files,
+                };
+            }
+            Err(e) => return RefreshOutcome::Failed(e.to_string()),
+        };
+        if let Err(e) = self.git_ops.set_branch_tip(&branch.branch_name, tip) {
+            return RefreshOutcome::Failed(e.to_string());
+        }
+
+        let pushed = match remote_tip {
+            Some(expected) => {
+                if let Err(e) =
+                    self.git_ops
+                        .force_push_with_lease(&branch.branch_name, "origin", expected)
+                {
+                    // Keep the local branch in step with the remote; retried next bundle
+                    let _ = self
+                        .git_ops
+                        .set_branch_tip(&branch.branch_name, previous_tip);
+                    if let Some(detected) = secrets_detected(&e) {
+                        self.flag_secrets(&[branch.issue_number], detected).await;
+                    }
+                    return RefreshOutcome::Failed(e.to_string());
+                }
+                true
+            }
+            None => false,
+        };
+        self.clear_needs_rebase(branch).await;
+        RefreshOutcome::Rebased { behind, pushed }
+    }
+
+    /// Take issues whose commits hold suspected secrets away from the agents
+    async fn flag_secrets(&self, issue_numbers: &[u64], detected: &SecretsDetected) {
+        println!("🔐 {detected}");
+        for &issue_number in issue_numbers {
+            if self.issue_has_label(issue_number, HUMAN_ONLY_LABEL).await {
+                continue;
+            }
+            if let Err(e) = self
+                .github_client
+                .comments
+                .create_issue_comment(issue_number, &detected.issue_comment())
+                .await
+            {
+                println!("⚠️  Failed to comment on issue #{issue_number}: {e}");
+            }
+            if let Err(e) = self
+                .github_client
+                .add_label_to_issue(issue_number, HUMAN_ONLY_LABEL)
+                .await
+            {
+                println!("⚠️  Failed to label issue #{issue_number} {HUMAN_ONLY_LABEL}: {e}");
+            }
+        }
+    }
+
+    /// Label and comment on an issue whose branch needs a manual rebase, once
+    async fn flag_needs_rebase(
+        &self,
+        branch: &QueuedBranch,
+        base_branch: &str,
+        behind: u32,
+        conflicted_files: &[String],
+    ) {
+        if self
+            .issue_has_label(branch.issue_number, NEEDS_REBASE_LABEL)
+            .await
+        {
+            return;
+        }
+        let comment = refresh::needs_rebase_comment(branch, base_branch, behind, conflicted_files);
+        if let Err(e) = self
+            .github_client
+            .comments
+            .create_issue_comment(branch.issue_number, &comment)
+            .await
+        {
+            println!(
+                "⚠️  Failed to comment on issue #{}: {}",
+                branch.issue_number, e
+            );
+        }
+        if let Err(e) = self
+            .github_client
+            .add_label_to_issue(branch.issue_number, NEEDS_REBASE_LABEL)
+            .await
+        {
+            println!(
+                "⚠️  Failed to add {} label to issue #{}: {}",
+                NEEDS_REBASE_LABEL, branch.issue_number, e
+            );
+        }
+    }
+
+    /// Drop the `needs-rebase` flag from an issue whose branch is current again
+    async fn clear_needs_rebase(&self, branch: &QueuedBranch) {
+        if !self
+            .issue_has_label(branch.issue_number, NEEDS_REBASE_LABEL)
+            .await
+        {
+            return;
+        }
+        if let Err(e) = self
+            .github_client
+            .remove_label_from_issue(branch.issue_number, NEEDS_REBASE_LABEL)
+            .await
+        {
+            println!(
+                "⚠️  Failed to remove {} label from issue #{}: {}",
+                NEEDS_REBASE_LABEL, branch.issue_number, e
+            );
+        }
+    }
+
+    async fn issue_has_label(&self, issue_number: u64, label: &str) -> bool {
+        self.github_client
+            .fetch_issue(issue_number)
+            .await
+            .is_ok_and(|issue| issue.labels.iter().any(|l| l.name == label))
+    }
+
+    /// Leave out branches over the branch limits and refuse a bundle over the bundle limits
+    async fn apply_diff_limits(
+        &self,
+        queued_branches: &[QueuedBranch],
+        changelogs: &HashMap<String, BranchChangelog>,
+    ) -> Result<Vec<QueuedBranch>> {
+        if !self.diff_limits.enabled {
+            return Ok(queued_branches.to_vec());
+        }
+
+        let mut kept = Vec::new();
+        let mut measured = Vec::new();
+        for branch in queued_branches {
+            match changelogs.get(&branch.branch_name) {
+                Some(changelog) => {
+                    let report = SizeReport::check(
+                        &branch.branch_name,
+                        changelog.files.clone(),
+                        &self.diff_limits.branch,
+                    );
+                    if report.is_oversized() {
+                        print!("{}", report.format_text(&self.diff_limits.branch));
+                        println!("   ⏭️  Left out of the bundle");
+                        self.report_oversized(branch.issue_number, &report).await;
+                        continue;
+                    }
+                    measured.push(changelog);
+                }
+                None => println!("⚠️  Could not measure {}", branch.branch_name),
+            }
+            kept.push(branch.clone());
+        }
+
+        if kept.is_empty() {
+            return Err(anyhow!("Every queued branch is over the diff size limits"));
+        }
+
+        let bundle =
+            SizeReport::check("bundle", combined_files(measured), &self.diff_limits.bundle);
+        if bundle.is_oversized() {
+            print!("{}", bundle.format_text(&self.diff_limits.bundle));
+            return Err(anyhow!(
+                "Bundle of {} branches is over the diff size limits; lower max_branches_per_bundle or raise [diff_limits.bundle]",
+                kept.len()
+            ));
+        }
+        Ok(kept)
+    }
+
+    /// Explain on the issue why its branch was refused, once per branch
+    async fn report_oversized(&self, issue_number: u64, report: &SizeReport) {
+        if self.has_comment_with(issue_number, &report.marker()).await {
+            return;
+        }
+        if let Err(e) = self
+            .github_client
+            .comments
+            .create_issue_comment(
+                issue_number,
+                &report.issue_comment(&self.diff_limits.branch),
+            )
+            .await
+        {
+            println!("⚠️  Failed to comment on issue #{issue_number}: {e}");
+        }
+    }
+
+    /// Whether an earlier comment on the issue carries `marker`
+    async fn has_comment_with(&self, issue_number: u64, marker: &str) -> bool {
+        self.github_client
+            .comments
+            .get_issue_comments(issue_number)
+            .await
+            .is_ok_and(|comments| {
+                comments
+                    .iter()
+                    .any(|c| c.body.as_deref().is_some_and(|b| b.contains(marker)))
+            })
+    }
+
+    /// Hold branches touching protected paths out of the bundle
+    ///
+    /// A branch whose changes can't be read is held out too, since it can't be
+    /// shown to stay clear of the protected paths.
+    async fn apply_path_policy(
+        &self,
+        queued_branches: &[QueuedBranch],
+        changelogs: &HashMap<String, BranchChangelog>,
+        base_branch: &str,
+    ) -> Result<Vec<QueuedBranch>> {
+        if self.path_policy.is_empty() {
+            return Ok(queued_branches.to_vec());
+        }
+
+        let mut kept = Vec::new();
+        for branch in queued_branches {
+            let Some(changelog) = changelogs.get(&branch.branch_name) else {
+                println!(
+                    "⚠️  Could not check {} for protected paths, left out of the bundle",
+                    branch.branch_name
+                );
+                continue;
+            };
+            let change = self.path_policy.check(
+                &branch.branch_name,
+                changelog.files.iter().map(|f| f.path.as_str()),
+            );
+            if change.is_protected() {
+                print!("{}", change.format_text());
+                println!("   ⏭️  Left out of the bundle for human review");
+                hold_for_human_review(
+                    &self.github_client,
+                    &change,
+                    branch.issue_number,
+                    &branch.description,
+                    base_branch,
+                )
+                .await;
+                continue;
+            }
+            kept.push(branch.clone());
+        }
+
+        if kept.is_empty() {
+            return Err(anyhow!(
+                "Every queued branch touches protected paths and waits for human review"
+            ));
+        }
+        Ok(kept)
+    }
+
     /// Create a bundle PR from queued branches
     pub async fn create_bundle(
         &mut self,
@@ -79,11 +620,62 @@ impl BundleManager {
             });
         }
 
+        if let Some(pending) = self.pending_conflict() {
+            return Ok(BundleResult::Failed {
+                error: anyhow!(
+                    "Bundle is paused on a conflict in {}; run 'bundle --continue' or 'bundle --abort' first",
+                    pending.branch_name
+                ),
+            });
+        }
+
+        // Bring branches that fell behind the base up to date first
+        let refreshes = self.refresh_queued_branches(queued_branches, false).await;
+        for refresh in &refreshes {
+            if refresh.outcome != RefreshOutcome::UpToDate {
+                println!("   {}", refresh.summary());
+            }
+        }
+        let refreshed: Vec<QueuedBranch> = queued_branches
+            .iter()
+            .filter(|branch| {
+                refreshes
+                    .iter()
+                    .all(|r| r.branch.branch_name != branch.branch_name || r.is_bundleable())
+            })
+            .cloned()
+            .collect();
+        if refreshed.is_empty() {
+            return Ok(BundleResult::Failed {
+                error: anyhow!(
+                    "No branches left to bundle after rebasing onto {BUNDLE_BASE_BRANCH}"
+                ),
+            });
+        }
+
+        let base_branch = BUNDLE_BASE_BRANCH;
+        let changelogs = self.branch_changelogs(&refreshed, base_branch);
+        let allowed = match self
+            .apply_path_policy(&refreshed, &changelogs, base_branch)
+            .await
+        {
+            Ok(allowed) => allowed,
+            Err(error) => return Ok(BundleResult::Failed { error }),
+        };
+        let sized = match self.apply_diff_limits(&allowed, &changelogs).await {
+            Ok(sized) => sized,
+            Err(error) => return Ok(BundleResult::Failed { error }),
+        };
+        let queued_branches = sized.as_slice();
+
         // Remember the current branch to restore it later
-        let _original_branch = self.get_current_branch()?;
+        let original_branch = self.get_current_branch()?;
+
+        let files = changelog_files(queued_branches, &changelogs);
+        let ordered = self.order_queued_branches(queued_branches, &files);
+        let queued_branches = ordered.as_slice();
 
         let bundle_branch = self.generate_bundle_branch_name(queued_branches);
-        let base_branch = "main";
 
         // Check if bundle branch already exists (idempotency)
         if self.git_ops.branch_exists(&bundle_branch) {
@@ -109,25 +701,34 @@ impl BundleManager {
             .collect();
 
         println!("🔍 Analyzing bundle compatibility...");
-        match self
-            .git_ops
-            .analyze_bundle_conflicts(&branch_names, base_branch)
-        {
+        let mut conflict_report = None;
+        match self.git_ops.analyze_bundle_conflicts_with(
+            &branch_names,
+            base_branch,
+            self.trial_merge,
+        ) {
             Ok(compatibility_report) => {
                 self.print_compatibility_report(&compatibility_report);
 
                 if !compatibility_report.is_bundle_safe
                     && compatibility_report.compatibility_score < 75.0
                 {
-                    println!("⚠️  High conflict risk detected (score: {:.1}%), falling back to individual PRs",
-                        compatibility_report.compatibility_score);
-                    return self
-                        .create_individual_prs_with_context(
-                            queued_branches,
-                            Some(compatibility_report),
-                        )
-                        .await;
+                    if self.conflict_strategy == ConflictStrategy::IndividualFallback {
+                        println!("⚠️  High conflict risk detected (score: {:.1}%), falling back to individual PRs",
+                            compatibility_report.compatibility_score);
+                        return self
+                            .create_individual_prs_with_context(
+                                queued_branches,
+                                Some(compatibility_report),
+                            )
+                            .await;
+                    }
+                    println!(
+                        "⚠️  High conflict risk detected (score: {:.1}%), continuing with {:?} strategy",
+                        compatibility_report.compatibility_score, self.conflict_strategy
+                    );
                 }
+                conflict_report = Some(compatibility_report);
             }
             Err(e) => {
                 println!("⚠️  Conflict analysis failed: {e}, proceeding with caution");
@@ -151,84 +752,995 @@ impl BundleManager {
             });
         }
 
-        // Cherry-pick commits from each queued branch
-        let mut conflicts_detected = false;
-        let mut successfully_bundled = Vec::new();
+        let mut state = BundleState {
+            bundle_branch,
+            base_branch: base_branch.to_string(),
+            queued_branches: queued_branches.to_vec(),
+            target_branches: branch_names,
+            completed_branches: Vec::new(),
+            failed_branches: Vec::new(),
+            current_operation: None,
+            audit_trail: Vec::new(),
+            recovery_data: Some(RecoveryData {
+                last_successful_commit: None,
+                cleanup_commands: Vec::new(),
+                rollback_branch: Some(original_branch),
+                temp_files: Vec::new(),
+            }),
+            conflict_strategy: self.conflict_strategy.clone(),
+            assembly_mode: self.assembly_mode.clone(),
+            pending_conflict: None,
+            conflict_report,
+            pushed: false,
+            pr_number: None,
+            labels_applied: false,
+        };
+        if let (Ok(head), Some(recovery)) =
+            (self.git_ops.head_commit_id(), state.recovery_data.as_mut())
+        {
+            recovery.last_successful_commit = Some(head.to_string());
+        }
 
-        for queued_branch in queued_branches {
+        self.record_step(
+            &mut state,
+            "create_bundle_branch",
+            None,
+            BundleOperationStatus::Completed,
+            BundleStatus::InProgress,
+        )
+        .await;
+
+        self.cherry_pick_remaining(state).await
+    }
+
+    /// Resume a bundle whose process died before it finished, from its last completed step
+    pub async fn resume_bundle(&mut self) -> Result<BundleResult> {
+        if let Some(pending) = self.pending_conflict() {
+            return Err(anyhow!(
+                "Bundle is paused on a conflict in {}; use 'bundle --continue' or 'bundle --abort'",
+                pending.branch_name
+            ));
+        }
+
+        let entry = persistence::load_interrupted_bundle()
+            .await?
+            .ok_or_else(|| anyhow!("No interrupted bundle to resume"))?;
+        let state = entry.state.ok_or_else(|| {
+            anyhow!(
+                "Recorded state for bundle {} is unreadable",
+                entry.bundle_id
+            )
+        })?;
+
+        println!(
+            "🔄 Resuming bundle {} ({}/{} branches handled)",
+            state.bundle_branch,
+            state.completed_branches.len() + state.failed_branches.len(),
+            state.queued_branches.len()
+        );
+
+        if !state.pushed {
+            if !self.git_ops.branch_exists(&state.bundle_branch) {
+                return Err(anyhow!(
+                    "Bundle branch {} no longer exists locally; abandon it and bundle again",
+                    state.bundle_branch
+                ));
+            }
+
+            // A cherry-pick cut short leaves a half-applied branch behind; rewind to the
+            // last branch that was fully applied before replaying the rest
+            if self.get_current_branch()? != state.bundle_branch {
+                self.git_ops.checkout_branch(&state.bundle_branch)?;
+            }
+            let last_commit = state
+                .recovery_data
+                .as_ref()
+                .and_then(|data| data.last_successful_commit.as_deref())
+                .map(Oid::from_str)
+                .transpose()?;
+            match last_commit {
+                Some(commit) => self.git_ops.reset_to_commit(commit)?,
+                None => self.git_ops.abort_cherry_pick()?,
+            }
+        }
+
+        self.cherry_pick_remaining(state).await
+    }
+
+    /// Rebuild an open bundle PR without one of its issues
+    ///
+    /// The bundle branch is reassembled on the base branch from the remaining
+    /// issues in the PR's manifest, force-pushed with a lease on the PR head the
+    /// rebuild started from, and the PR description is re-rendered. The dropped
+    /// issue goes back to `route:ready` with the review comments about it.
+    pub async fn rebuild_bundle(
+        &mut self,
+        pr_number: u64,
+        drop_issue: u64,
+    ) -> Result<BundleResult> {
+        if let Some(pending) = self.pending_conflict() {
+            return Err(anyhow!(
+                "Bundle is paused on a conflict in {}; use 'bundle --continue' or 'bundle --abort'",
+                pending.branch_name
+            ));
+        }
+
+        let pr = self.github_client.get_pull_request(pr_number).await?;
+        if pr.merged_at.is_some() || pr.closed_at.is_some() {
+            return Err(anyhow!("PR #{} is no longer open", pr_number));
+        }
+        let manifest = pr
+            .body
+            .as_deref()
+            .and_then(BundleManifest::from_pr_body)
+            .ok_or_else(|| {
+                anyhow!(
+                    "PR #{} has no bundle manifest; only bundle PRs can be rebuilt",
+                    pr_number
+                )
+            })?;
+        let plan = RebuildPlan::new(&manifest, drop_issue)?;
+        let lease = Oid::from_str(&pr.head.sha)?;
+        let bundle_branch = manifest.bundle_branch.clone();
+
+        println!(
+            "🔨 Rebuilding {} without #{} ({} issues remain)",
+            bundle_branch,
+            drop_issue,
+            plan.remaining.len()
+        );
+
+        // The bundle branch can't be moved while it is checked out
+        let original_branch = self.get_current_branch()?;
+        self.git_ops.checkout_branch(&manifest.base_branch)?;
+        self.git_ops
+            .reset_branch_to_base(&bundle_branch, &manifest.base_branch)?;
+        self.git_ops.checkout_branch(&bundle_branch)?;
+
+        let mut state = BundleState {
+            bundle_branch: bundle_branch.clone(),
+            base_branch: manifest.base_branch.clone(),
+            queued_branches: plan
+                .remaining
+                .iter()
+                .chain(&plan.skipped)
+                .cloned()
+                .collect(),
+            target_branches: plan
+                .remaining
+                .iter()
+                .map(|b| b.branch_name.clone())
+                .collect(),
+            completed_branches: Vec::new(),
+            failed_branches: plan.skipped.iter().map(|b| b.branch_name.clone()).collect(),
+            current_operation: None,
+            audit_trail: Vec::new(),
+            recovery_data: None,
+            conflict_strategy: ConflictStrategy::SkipConflicts,
+            assembly_mode: manifest.assembly_mode.clone(),
+            pending_conflict: None,
+            conflict_report: None,
+            pushed: false,
+            pr_number: Some(pr_number),
+            labels_applied: false,
+        };
+
+        // Progress is only persisted once the rebuild is done: an interrupted
+        // rebuild can't be resumed, since the branch has to be force-pushed
+        for queued_branch in &plan.remaining {
+            println!("🍒 Reapplying {}...", queued_branch.branch_name);
+            let reason = match self.apply_branch(queued_branch, &state) {
+                Ok(CherryPickOutcome::Applied { .. }) => {
+                    state
+                        .completed_branches
+                        .push(queued_branch.branch_name.clone());
+                    continue;
+                }
+                Ok(CherryPickOutcome::Conflicted {
+                    conflicted_files, ..
+                })
+                | Ok(CherryPickOutcome::AwaitingResolution {
+                    conflicted_files, ..
+                }) => format!("conflicts in {}", conflicted_files.join(", ")),
+                Err(e) => e.to_string(),
+            };
+            self.git_ops.abort_cherry_pick()?;
+            self.restore_bundle_branch(
+                &bundle_branch,
+                &manifest.base_branch,
+                lease,
+                &original_branch,
+            )?;
+            return Ok(BundleResult::Failed {
+                error: anyhow!(
+                    "#{} no longer applies without #{} ({}); PR #{} was left unchanged",
+                    queued_branch.issue_number,
+                    drop_issue,
+                    reason,
+                    pr_number
+                ),
+            });
+        }
+
+        if let Err(e) = self
+            .git_ops
+            .force_push_with_lease(&bundle_branch, "origin", lease)
+        {
+            self.restore_bundle_branch(
+                &bundle_branch,
+                &manifest.base_branch,
+                lease,
+                &original_branch,
+            )?;
+            return Ok(BundleResult::Failed { error: e });
+        }
+        state.pushed = true;
+
+        let pr_title = self.generate_bundle_pr_title(&plan.remaining);
+        let pr_body =
+            self.generate_bundle_pr_body(&state, &plan.remaining, &plan.skipped, &plan.all_dropped);
+        if let Err(e) = self
+            .github_client
+            .update_pull_request(pr_number, &pr_title, &pr_body)
+            .await
+        {
+            println!("⚠️  Failed to update the description of PR #{pr_number}: {e}");
+        }
+
+        self.return_dropped_issue(pr_number, &plan).await;
+        for queued_branch in &plan.remaining {
+            if let Err(e) = self
+                .github_client
+                .add_label_to_issue(queued_branch.issue_number, "route:review")
+                .await
+            {
+                println!(
+                    "⚠️  Failed to add route:review label to issue #{}: {}",
+                    queued_branch.issue_number, e
+                );
+            }
+        }
+        state.labels_applied = true;
+
+        self.record_step(
+            &mut state,
+            "rebuild_without_issue",
+            Some(&plan.dropped.branch_name),
+            BundleOperationStatus::Completed,
+            BundleStatus::Completed,
+        )
+        .await;
+
+        if original_branch != bundle_branch {
+            self.git_ops.checkout_branch(&original_branch)?;
+        }
+
+        Ok(BundleResult::Success {
+            pr_number,
+            bundle_branch,
+        })
+    }
+
+    /// Put the bundle branch back on the PR head after a rebuild gave up
+    fn restore_bundle_branch(
+        &mut self,
+        bundle_branch: &str,
+        base_branch: &str,
+        pr_head: Oid,
+        original_branch: &str,
+    ) -> Result<()> {
+        // The bundle branch can't be moved while it is checked out
+        self.git_ops.checkout_branch(base_branch)?;
+        self.git_ops.set_branch_tip(bundle_branch, pr_head)?;
+        self.git_ops.checkout_branch(original_branch)
+    }
+
+    /// Send a dropped issue back to `route:ready` with the review feedback about it
+    async fn return_dropped_issue(&self, pr_number: u64, plan: &RebuildPlan) {
+        let issue_number = plan.dropped.issue_number;
+
+        let mut comments = Vec::new();
+        match self.github_client.comments.get_pr_comments(pr_number).await {
+            Ok(conversation) => {
+                comments.extend(conversation.into_iter().map(|c| ReviewerComment {
+                    author: c.user.login,
+                    body: c.body.unwrap_or_default(),
+                    path: None,
+                    url: c.html_url.to_string(),
+                }))
+            }
+            Err(e) => println!("⚠️  Failed to read comments on PR #{pr_number}: {e}"),
+        }
+        match self
+            .github_client
+            .comments
+            .get_pr_review_comments(pr_number)
+            .await
+        {
+            Ok(review) => comments.extend(review.into_iter().map(|c| {
+                ReviewerComment {
+                    author: c
+                        .user
+                        .map(|u| u.login)
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    body: c.body,
+                    path: Some(c.path),
+                    url: c.html_url,
+                }
+            })),
+            Err(e) => println!("⚠️  Failed to read review comments on PR #{pr_number}: {e}"),
+        }
+
+        let feedback = rebuild::feedback_for_issue(&plan.dropped, &comments);
+        if let Err(e) = self
+            .github_client
+            .comments
+            .create_issue_comment(issue_number, &rebuild::drop_comment(pr_number, &feedback))
+            .await
+        {
+            println!("⚠️  Failed to comment on issue #{issue_number}: {e}");
+        }
+
+        let labels: Vec<String> = match self.github_client.fetch_issue(issue_number).await {
+            Ok(issue) => issue.labels.into_iter().map(|l| l.name).collect(),
+            Err(e) => {
+                println!("⚠️  Failed to read labels of issue #{issue_number}: {e}");
+                vec!["route:review".to_string()]
+            }
+        };
+        for label in rebuild::labels_to_remove_on_drop(&labels) {
+            if let Err(e) = self
+                .github_client
+                .remove_label_from_issue(issue_number, &label)
+                .await
+            {
+                println!("⚠️  Failed to remove {label} label from issue #{issue_number}: {e}");
+            }
+        }
+        if let Err(e) = self
+            .github_client
+            .add_label_to_issue(issue_number, "route:ready")
+            .await
+        {
+            println!("⚠️  Failed to add route:ready label to issue #{issue_number}: {e}");
+        }
+
+        // Still ahead of the base, the branch would otherwise be queued again
+        let branch_name = &plan.dropped.branch_name;
+        let dropped_branch = rebuild::dropped_branch_name(branch_name);
+        match self
+            .git_ops
+            .rename_branch(branch_name, &dropped_branch, "origin")
+        {
+            Ok(()) => println!("📁 Moved {branch_name} to {dropped_branch}"),
+            Err(e) => println!("⚠️  Failed to move {branch_name} out of the queue: {e}"),
+        }
+    }
+
+    /// Past bundles with their outcomes, newest first
+    pub async fn bundle_history(limit: u32) -> Result<Vec<BundleHistoryEntry>> {
+        persistence::bundle_history(limit).await
+    }
+
+    /// Every recorded step of one bundle, oldest first
+    #[cfg(feature = "database")]
+    pub async fn bundle_steps(bundle_id: &str) -> Result<Vec<crate::database::BundleStep>> {
+        persistence::bundle_steps(bundle_id).await
+    }
+
+    /// Add a step to the bundle's audit trail and persist the state
+    ///
+    /// Persistence failures are reported but never fail the bundle itself.
+    async fn record_step(
+        &self,
+        state: &mut BundleState,
+        operation: &str,
+        branch_name: Option<&str>,
+        status: BundleOperationStatus,
+        bundle_status: BundleStatus,
+    ) {
+        let affected_issues = state
+            .queued_branches
+            .iter()
+            .filter(|b| branch_name.is_none_or(|name| b.branch_name == name))
+            .map(|b| b.issue_number)
+            .collect();
+
+        state.audit_trail.push(BundleAuditEntry {
+            timestamp: Utc::now(),
+            operation: operation.to_string(),
+            branch_name: branch_name.map(|name| name.to_string()),
+            affected_issues,
+            status,
+            error: None,
+            recovery_action: None,
+            execution_time_ms: 0,
+            correlation_id: state.bundle_branch.clone(),
+        });
+
+        if let Err(e) = persistence::save_bundle(state, bundle_status).await {
+            println!("⚠️  Failed to persist bundle state: {e}");
+        }
+    }
+
+    /// Resume a bundle paused by the `ManualResolve` strategy once the conflict is resolved
+    pub async fn continue_bundle(&mut self) -> Result<BundleResult> {
+        let mut state = self
+            .bundle_state
+            .clone()
+            .ok_or_else(|| anyhow!("No paused bundle to continue"))?;
+        let pending = state
+            .pending_conflict
+            .take()
+            .ok_or_else(|| anyhow!("No paused bundle to continue"))?;
+
+        let current_branch = self.get_current_branch()?;
+        if current_branch != state.bundle_branch {
+            return Err(anyhow!(
+                "Expected to be on bundle branch {} but HEAD is {}",
+                state.bundle_branch,
+                current_branch
+            ));
+        }
+
+        println!(
+            "▶️  Continuing bundle {} from {}...",
+            state.bundle_branch, pending.branch_name
+        );
+        let commit = Oid::from_str(&pending.commit)?;
+
+        match self.git_ops.continue_cherry_pick(
+            &pending.branch_name,
+            commit,
+            &state.assembly_mode,
+        )? {
+            CherryPickOutcome::Applied { commits } => {
+                println!(
+                    "✅ Successfully cherry-picked {} commits from {}",
+                    commits.len(),
+                    pending.branch_name
+                );
+                self.mark_branch_applied(&mut state, pending.branch_name)
+                    .await;
+            }
+            CherryPickOutcome::AwaitingResolution {
+                commit,
+                conflicted_files,
+                ..
+            } => {
+                return self
+                    .pause_for_resolution(state, pending.branch_name, commit, conflicted_files)
+                    .await;
+            }
+            CherryPickOutcome::Conflicted { .. } => {
+                self.mark_branch_skipped(&mut state, pending.branch_name)
+                    .await;
+            }
+        }
+
+        self.cherry_pick_remaining(state).await
+    }
+
+    /// Abandon a paused bundle, deleting the bundle branch and returning to the original branch
+    pub async fn abort_bundle(&mut self) -> Result<()> {
+        let mut state = self
+            .bundle_state
+            .clone()
+            .ok_or_else(|| anyhow!("No paused bundle to abort"))?;
+
+        self.git_ops.abort_cherry_pick()?;
+
+        let original_branch = state
+            .recovery_data
+            .as_ref()
+            .and_then(|data| data.rollback_branch.clone())
+            .unwrap_or_else(|| state.base_branch.clone());
+        self.git_ops.checkout_branch(&original_branch)?;
+        self.git_ops.delete_local_branch(&state.bundle_branch)?;
+
+        self.clear_bundle_state();
+        self.record_step(
+            &mut state,
+            "abort_bundle",
+            None,
+            BundleOperationStatus::Aborted,
+            BundleStatus::Aborted,
+        )
+        .await;
+        println!(
+            "🛑 Aborted bundle {}, back on {original_branch}",
+            state.bundle_branch
+        );
+        Ok(())
+    }
+
+    /// Cherry-pick every queued branch not yet handled, then push and open the PR
+    async fn cherry_pick_remaining(&mut self, mut state: BundleState) -> Result<BundleResult> {
+        let remaining: Vec<QueuedBranch> = state
+            .queued_branches
+            .iter()
+            .filter(|b| {
+                !state.completed_branches.contains(&b.branch_name)
+                    && !state.failed_branches.contains(&b.branch_name)
+            })
+            .cloned()
+            .collect();
+
+        for queued_branch in remaining {
             println!("🍒 Cherry-picking from {}...", queued_branch.branch_name);
 
-            match self.git_ops.cherry_pick_branch(
-                &queued_branch.branch_name,
-                ConflictStrategy::IndividualFallback,
-            ) {
-                Ok(commits) => {
+            match self.apply_branch(&queued_branch, &state) {
+                Ok(CherryPickOutcome::Applied { commits }) => {
                     println!(
                         "✅ Successfully cherry-picked {} commits from {}",
                         commits.len(),
                         queued_branch.branch_name
                     );
-                    successfully_bundled.push(queued_branch.clone());
+                    self.mark_branch_applied(&mut state, queued_branch.branch_name)
+                        .await;
+                }
+                Ok(CherryPickOutcome::AwaitingResolution {
+                    commit,
+                    conflicted_files,
+                    ..
+                }) => {
+                    return self
+                        .pause_for_resolution(
+                            state,
+                            queued_branch.branch_name,
+                            commit,
+                            conflicted_files,
+                        )
+                        .await;
+                }
+                Ok(CherryPickOutcome::Conflicted {
+                    commit,
+                    conflicted_files,
+                }) if state.conflict_strategy == ConflictStrategy::SkipConflicts => {
+                    println!(
+                        "⚠️  Skipping {} (commit {} conflicts in: {})",
+                        queued_branch.branch_name,
+                        &commit.to_string()[..8],
+                        conflicted_files.join(", ")
+                    );
+                    self.open_unblocker(&state, &queued_branch, commit, conflicted_files)
+                        .await;
+                    self.mark_branch_skipped(&mut state, queued_branch.branch_name)
+                        .await;
+                }
+                Err(e) if state.conflict_strategy == ConflictStrategy::SkipConflicts => {
+                    println!("⚠️  Skipping {}: {}", queued_branch.branch_name, e);
+                    self.mark_branch_skipped(&mut state, queued_branch.branch_name)
+                        .await;
+                }
+                Ok(CherryPickOutcome::Conflicted {
+                    commit,
+                    conflicted_files,
+                }) => {
+                    println!(
+                        "⚠️  Conflict detected with {}: {}",
+                        queued_branch.branch_name,
+                        conflicted_files.join(", ")
+                    );
+                    if self
+                        .open_unblocker(&state, &queued_branch, commit, conflicted_files)
+                        .await
+                    {
+                        // Blocked on the unblocker, so it gets no PR of its own
+                        state.drop_branch(&queued_branch.branch_name);
+                    }
+                    return self.fall_back_to_individual_prs(state).await;
                 }
                 Err(e) => {
                     println!(
                         "⚠️  Conflict detected with {}: {}",
                         queued_branch.branch_name, e
                     );
-                    conflicts_detected = true;
-                    break;
+                    return self.fall_back_to_individual_prs(state).await;
                 }
             }
         }
 
-        // Handle conflicts by falling back to individual PRs
-        if conflicts_detected {
-            println!("🔄 Conflicts detected, falling back to individual PRs...");
-            return self
-                .create_individual_prs_with_context(queued_branches, None)
-                .await;
+        self.finish_bundle(state).await
+    }
+
+    /// Open a `route:unblocker` issue for a branch that conflicted with the bundle
+    ///
+    /// The conflicted issue is labelled so the branch stays out of the queue
+    /// until the unblocker is resolved; see `WorkIntegrator` for the re-queue.
+    /// Returns whether an unblocker now tracks the branch.
+    async fn open_unblocker(
+        &self,
+        state: &BundleState,
+        queued_branch: &QueuedBranch,
+        commit: Oid,
+        conflicted_files: Vec<String>,
+    ) -> bool {
+        if !self.open_unblockers {
+            return false;
         }
 
-        // Push bundle branch
-        if let Err(e) = self.git_ops.push_branch(&bundle_branch, "origin") {
-            return Ok(BundleResult::Failed {
-                error: anyhow!("Failed to push bundle branch: {}", e),
-            });
+        let base_sha = match self.git_ops.branch_tip(&state.base_branch) {
+            Ok(oid) => oid.to_string(),
+            Err(e) => {
+                println!(
+                    "⚠️  Not opening an unblocker for {}: {}",
+                    queued_branch.branch_name, e
+                );
+                return false;
+            }
+        };
+        let applied: Vec<QueuedBranch> = state
+            .queued_branches
+            .iter()
+            .filter(|b| state.completed_branches.contains(&b.branch_name))
+            .cloned()
+            .collect();
+        let files = self.branch_files(&applied, &state.base_branch);
+        let task = UnblockerTask {
+            conflicted_branch: queued_branch.clone(),
+            conflicting_branches: unblocker::conflicting_branches(
+                &conflicted_files,
+                &applied,
+                &files,
+            ),
+            conflicted_files,
+            commit: Some(commit.to_string()),
+            bundle_branch: state.bundle_branch.clone(),
+            base_branch: state.base_branch.clone(),
+            base_sha,
+        };
+
+        // A previous bundle run may already have opened one for this branch
+        match self.github_client.fetch_issues().await {
+            Ok(issues) => {
+                if let Some(existing) = issues.iter().find(|issue| {
+                    issue.labels.iter().any(|l| l.name == UNBLOCKER_LABEL)
+                        && issue
+                            .body
+                            .as_deref()
+                            .and_then(UnblockerTask::from_issue_body)
+                            .is_some_and(|open| {
+                                open.conflicted_branch.branch_name
+                                    == task.conflicted_branch.branch_name
+                            })
+                }) {
+                    println!(
+                        "🚧 Unblocker #{} already tracks {}",
+                        existing.number, queued_branch.branch_name
+                    );
+                    return true;
+                }
+            }
+            Err(e) => println!("⚠️  Failed to look for existing unblockers: {e}"),
         }
 
-        // Create bundle PR
-        let pr_title = self.generate_bundle_pr_title(queued_branches);
-        let pr_body = self.generate_bundle_pr_body(queued_branches);
+        let unblocker_issue = match self
+            .github_client
+            .create_issue(
+                &task.title(),
+                &task.issue_body(),
+                vec![UNBLOCKER_LABEL.to_string()],
+            )
+            .await
+        {
+            Ok(issue) => issue.number,
+            Err(e) => {
+                println!(
+                    "⚠️  Failed to open unblocker for {}: {}",
+                    queued_branch.branch_name, e
+                );
+                return false;
+            }
+        };
 
-        match self
+        for issue_number in task.original_issues() {
+            if let Err(e) = self
+                .github_client
+                .comments
+                .create_issue_comment(
+                    issue_number,
+                    &task.blocked_comment(unblocker_issue, issue_number),
+                )
+                .await
+            {
+                println!("⚠️  Failed to comment on issue #{issue_number}: {e}");
+            }
+        }
+        if let Err(e) = self
             .github_client
-            .pulls
-            .create_pull_request(&pr_title, &bundle_branch, base_branch, &pr_body)
+            .add_label_to_issue(queued_branch.issue_number, BLOCKED_LABEL)
             .await
         {
-            Ok(pr) => {
-                // Add route:review labels to bundled issues
-                for queued_branch in &successfully_bundled {
-                    if let Err(e) = self
-                        .github_client
-                        .add_label_to_issue(queued_branch.issue_number, "route:review")
-                        .await
-                    {
-                        println!(
-                            "⚠️  Failed to add route:review label to issue #{}: {}",
-                            queued_branch.issue_number, e
-                        );
+            println!(
+                "⚠️  Failed to add {} label to issue #{}: {}",
+                BLOCKED_LABEL, queued_branch.issue_number, e
+            );
+        }
+        true
+    }
+
+    /// Record a branch fully applied onto the bundle branch
+    async fn mark_branch_applied(&self, state: &mut BundleState, branch_name: String) {
+        if let (Ok(head), Some(recovery)) =
+            (self.git_ops.head_commit_id(), state.recovery_data.as_mut())
+        {
+            recovery.last_successful_commit = Some(head.to_string());
+        }
+        state.completed_branches.push(branch_name.clone());
+        self.record_step(
+            state,
+            "cherry_pick",
+            Some(&branch_name),
+            BundleOperationStatus::Completed,
+            BundleStatus::InProgress,
+        )
+        .await;
+    }
+
+    /// Record a branch left out of the bundle because it conflicted
+    async fn mark_branch_skipped(&self, state: &mut BundleState, branch_name: String) {
+        state.failed_branches.push(branch_name.clone());
+        self.record_step(
+            state,
+            "cherry_pick",
+            Some(&branch_name),
+            BundleOperationStatus::Failed,
+            BundleStatus::InProgress,
+        )
+        .await;
+    }
+
+    /// Apply one queued branch onto the bundle branch using the bundle's assembly mode
+    fn apply_branch(
+        &self,
+        queued_branch: &QueuedBranch,
+        state: &BundleState,
+    ) -> Result<CherryPickOutcome> {
+        let strategy = state.conflict_strategy.clone();
+        match state.assembly_mode {
+            AssemblyMode::CherryPick => self
+                .git_ops
+                .cherry_pick_branch(&queued_branch.branch_name, strategy),
+            AssemblyMode::Squash => {
+                let summaries = self
+                    .git_ops
+                    .branch_commit_summaries(&queued_branch.branch_name)?;
+                let message = Self::squash_commit_message(queued_branch, &summaries);
+                self.git_ops
+                    .squash_branch(&queued_branch.branch_name, &message, strategy)
+            }
+            AssemblyMode::Rebase => self
+                .git_ops
+                .rebase_branch(&queued_branch.branch_name, strategy),
+        }
+    }
+
+    /// Commit message for a branch squashed into a single commit
+    fn squash_commit_message(queued_branch: &QueuedBranch, summaries: &[String]) -> String {
+        let mut message = format!(
+            "{} (#{})\n\nSquashed {} commits from {}:\n",
+            queued_branch.description,
+            queued_branch.issue_number,
+            summaries.len(),
+            queued_branch.branch_name
+        );
+        for summary in summaries {
+            message.push_str(&format!("- {summary}\n"));
+        }
+        message.push_str(&format!("\nCloses #{}\n", queued_branch.issue_number));
+        message
+    }
+
+    /// Record the paused cherry-pick and hand control back to the user
+    async fn pause_for_resolution(
+        &mut self,
+        mut state: BundleState,
+        branch_name: String,
+        commit: Oid,
+        conflicted_files: Vec<String>,
+    ) -> Result<BundleResult> {
+        println!(
+            "⏸️  Bundle paused: resolve the conflicts from {branch_name}, stage them with 'git add',"
+        );
+        println!("   then run 'my-little-soda bundle --continue' (or 'bundle --abort')");
+        for file in &conflicted_files {
+            println!("   • {file}");
+        }
+
+        let bundle_branch = state.bundle_branch.clone();
+        state.pending_conflict = Some(PendingConflict {
+            branch_name: branch_name.clone(),
+            commit: commit.to_string(),
+            conflicted_files: conflicted_files.clone(),
+        });
+        self.record_step(
+            &mut state,
+            "pause_for_resolution",
+            Some(&branch_name),
+            BundleOperationStatus::InProgress {
+                progress_percent: 0,
+            },
+            BundleStatus::Paused,
+        )
+        .await;
+        self.save_bundle_state(state)?;
+
+        Ok(BundleResult::ManualResolutionRequired {
+            bundle_branch,
+            conflicted_branch: branch_name,
+            conflicted_files,
+        })
+    }
+
+    /// Abandon the bundle branch and open one PR per queued branch
+    async fn fall_back_to_individual_prs(
+        &mut self,
+        mut state: BundleState,
+    ) -> Result<BundleResult> {
+        println!("🔄 Conflicts detected, falling back to individual PRs...");
+        self.clear_bundle_state();
+        self.record_step(
+            &mut state,
+            "fall_back_to_individual_prs",
+            None,
+            BundleOperationStatus::Aborted,
+            BundleStatus::FellBack,
+        )
+        .await;
+        self.create_individual_prs_with_context(&state.queued_branches, None)
+            .await
+    }
+
+    /// Push the assembled bundle branch and open the bundle PR
+    ///
+    /// Steps already recorded in the state (push, PR, labels) are skipped, so a
+    /// resumed bundle picks up where the interrupted run stopped.
+    async fn finish_bundle(&mut self, mut state: BundleState) -> Result<BundleResult> {
+        self.clear_bundle_state();
+
+        let bundled: Vec<QueuedBranch> = state
+            .queued_branches
+            .iter()
+            .filter(|b| state.completed_branches.contains(&b.branch_name))
+            .cloned()
+            .collect();
+        let skipped: Vec<QueuedBranch> = state
+            .queued_branches
+            .iter()
+            .filter(|b| state.failed_branches.contains(&b.branch_name))
+            .cloned()
+            .collect();
+
+        if bundled.is_empty() {
+            self.record_step(
+                &mut state,
+                "finish_bundle",
+                None,
+                BundleOperationStatus::Failed,
+                BundleStatus::Failed,
+            )
+            .await;
+            return Ok(BundleResult::Failed {
+                error: anyhow!("Every queued branch conflicted, nothing left to bundle"),
+            });
+        }
+
+        let bundle_branch = state.bundle_branch.clone();
+
+        // Push bundle branch
+        if !state.pushed {
+            if let Err(e) = self.git_ops.push_branch(&bundle_branch, "origin") {
+                if let Some(detected) = secrets_detected(&e) {
+                    let issues: Vec<u64> = state
+                        .queued_branches
+                        .iter()
+                        .filter(|b| state.completed_branches.contains(&b.branch_name))
+                        .map(|b| b.issue_number)
+                        .collect();
+                    self.flag_secrets(&issues, detected).await;
+                }
+                self.record_step(
+                    &mut state,
+                    "push_bundle_branch",
+                    None,
+                    BundleOperationStatus::Failed,
+                    BundleStatus::Failed,
+                )
+                .await;
+                return Ok(BundleResult::Failed {
+                    error: anyhow!("Failed to push bundle branch: {}", e),
+                });
+            }
+            state.pushed = true;
+            self.record_step(
+                &mut state,
+                "push_bundle_branch",
+                None,
+                BundleOperationStatus::Completed,
+                BundleStatus::InProgress,
+            )
+            .await;
+        }
+
+        // Create bundle PR
+        let pr_number = match state.pr_number {
+            Some(pr_number) => pr_number,
+            None => {
+                let pr_title = self.generate_bundle_pr_title(&bundled);
+                let pr_body = self.generate_bundle_pr_body(&state, &bundled, &skipped, &[]);
+
+                match self
+                    .github_client
+                    .pulls
+                    .create_pull_request(&pr_title, &bundle_branch, &state.base_branch, &pr_body)
+                    .await
+                {
+                    Ok(pr) => {
+                        state.pr_number = Some(pr.number);
+                        self.record_step(
+                            &mut state,
+                            "create_bundle_pr",
+                            None,
+                            BundleOperationStatus::Completed,
+                            BundleStatus::InProgress,
+                        )
+                        .await;
+                        pr.number
+                    }
+                    Err(e) => {
+                        self.record_step(
+                            &mut state,
+                            "create_bundle_pr",
+                            None,
+                            BundleOperationStatus::Failed,
+                            BundleStatus::Failed,
+                        )
+                        .await;
+                        return Ok(BundleResult::Failed {
+                            error: anyhow!("Failed to create bundle PR: {}", e),
+                        });
                     }
                 }
+            }
+        };
 
-                Ok(BundleResult::Success {
-                    pr_number: pr.number,
-                    bundle_branch,
-                })
+        // Add route:review labels to bundled issues
+        if !state.labels_applied {
+            for queued_branch in &bundled {
+                if let Err(e) = self
+                    .github_client
+                    .add_label_to_issue(queued_branch.issue_number, "route:review")
+                    .await
+                {
+                    println!(
+                        "⚠️  Failed to add route:review label to issue #{}: {}",
+                        queued_branch.issue_number, e
+                    );
+                }
             }
-            Err(e) => Ok(BundleResult::Failed {
-                error: anyhow!("Failed to create bundle PR: {}", e),
-            }),
+            state.labels_applied = true;
+            self.record_step(
+                &mut state,
+                "apply_review_labels",
+                None,
+                BundleOperationStatus::Completed,
+                BundleStatus::Completed,
+            )
+            .await;
+        }
+
+        if skipped.is_empty() {
+            Ok(BundleResult::Success {
+                pr_number,
+                bundle_branch,
+            })
+        } else {
+            Ok(BundleResult::PartialSuccess {
+                pr_number,
+                bundle_branch,
+                skipped_branches: skipped.into_iter().map(|b| b.branch_name).collect(),
+            })
         }
     }
 
@@ -325,46 +1837,48 @@ impl BundleManager {
         )
     }
 
-    /// Generate bundle PR body with issue references
-    fn generate_bundle_pr_body(&self, queued_branches: &[QueuedBranch]) -> String {
-        let window = BundleWindow::current();
-
-        let mut body = format!(
-            "🚄 **Train Bundle - {} Departure**\n\n\
-            This bundle combines multiple completed agent tasks into a single PR for efficient review.\n\n\
-            **Bundle Window:** {} - {}\n\
-            **Issues Included:** {}\n\n\
-            ## Bundled Work\n\n",
-            window.start.format("%H:%M"),
-            window.start.format("%Y-%m-%d %H:%M"),
-            window.end.format("%H:%M"),
-            queued_branches.len()
-        );
+    /// Generate bundle PR body with a changelog per issue and a hidden manifest
+    fn generate_bundle_pr_body(
+        &self,
+        state: &BundleState,
+        bundled: &[QueuedBranch],
+        skipped: &[QueuedBranch],
+        dropped: &[QueuedBranch],
+    ) -> String {
+        let issues = bundled
+            .iter()
+            .map(|branch| {
+                let changelog = self
+                    .git_ops
+                    .branch_changelog(&branch.branch_name, &state.base_branch)
+                    .unwrap_or_else(|e| {
+                        println!(
+                            "⚠️  Failed to collect changes for {}: {}",
+                            branch.branch_name, e
+                        );
+                        BranchChangelog::default()
+                    });
+                IssueChangelog {
+                    branch: branch.clone(),
+                    changelog,
+                }
+            })
+            .collect();
 
-        for (i, branch) in queued_branches.iter().enumerate() {
-            body.push_str(&format!(
-                "{}. **Issue #{}**: {}\n   - Branch: `{}`\n   - [View Issue](https://github.com/{}/{}/issues/{})\n\n",
-                i + 1,
-                branch.issue_number,
-                branch.description,
-                branch.branch_name,
-                self.github_client.owner(),
-                self.github_client.repo(),
-                branch.issue_number
-            ));
+        BundlePrDescription {
+            owner: self.github_client.owner().to_string(),
+            repo: self.github_client.repo().to_string(),
+            bundle_branch: state.bundle_branch.clone(),
+            base_branch: state.base_branch.clone(),
+            assembly_mode: state.assembly_mode.clone(),
+            window: BundleWindow::current(),
+            created_at: Utc::now(),
+            issues,
+            skipped: skipped.to_vec(),
+            dropped: dropped.to_vec(),
+            conflict_report: state.conflict_report.clone(),
         }
-
-        body.push_str(&format!(
-            "## Review Notes\n\n\
-            - ✅ All branches have been automatically cherry-picked and tested\n\
-            - 🔍 Each issue should be reviewed individually for code quality\n\
-            - 🚀 Merge this PR to close all {} included issues\n\n\
-            ---\n\
-            🤖 Generated by Clambake bundling system",
-            queued_branches.len()
-        ));
-
-        body
+        .render()
     }
 
     /// Get the current branch name
@@ -398,14 +1912,21 @@ impl BundleManager {
         );
         println!("   • Analyzed Branches: {}", report.analyzed_branches.len());
 
-        if !report.potential_conflicts.is_empty() {
-            println!("   • Potential Conflicts:");
-            for (file, branches) in &report.potential_conflicts {
+        if !report.conflicting_hunks.is_empty() {
+            println!("   • Conflicting Hunks:");
+            for conflict in &report.conflicting_hunks {
                 println!(
-                    "     - {}: {} ({})",
-                    file,
-                    branches.len(),
-                    branches.join(", ")
+                    "     - {}: {} [{}] vs {} [{}]{}",
+                    conflict.file,
+                    conflict.first_branch,
+                    format_hunks(&conflict.first_hunks),
+                    conflict.second_branch,
+                    format_hunks(&conflict.second_hunks),
+                    if conflict.confirmed_by_trial_merge {
+                        " (confirmed by trial merge)"
+                    } else {
+                        ""
+                    }
                 );
             }
         }
@@ -441,21 +1962,38 @@ impl BundleManager {
                 report.analysis_timestamp.format("%Y-%m-%d %H:%M:%S UTC")
             ));
 
-            if !report.potential_conflicts.is_empty() {
-                body.push_str("**Detected File Conflicts:**\n");
-                for (file, branches) in &report.potential_conflicts {
-                    if branches.contains(&queued_branch.branch_name) {
-                        body.push_str(&format!(
-                            "- `{}` (conflicts with: {})\n",
-                            file,
-                            branches
-                                .iter()
-                                .filter(|b| *b != &queued_branch.branch_name)
-                                .cloned()
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        ));
-                    }
+            let own_conflicts: Vec<_> = report
+                .conflicting_hunks
+                .iter()
+                .filter(|c| {
+                    c.first_branch == queued_branch.branch_name
+                        || c.second_branch == queued_branch.branch_name
+                })
+                .collect();
+            if !own_conflicts.is_empty() {
+                body.push_str("**Detected Conflicting Hunks:**\n");
+                for conflict in own_conflicts {
+                    let (own_hunks, other_branch, other_hunks) =
+                        if conflict.first_branch == queued_branch.branch_name {
+                            (
+                                &conflict.first_hunks,
+                                &conflict.second_branch,
+                                &conflict.second_hunks,
+                            )
+                        } else {
+                            (
+                                &conflict.second_hunks,
+                                &conflict.first_branch,
+                                &conflict.first_hunks,
+                            )
+                        };
+                    body.push_str(&format!(
+                        "- `{}` {} (conflicts with `{}` {})\n",
+                        conflict.file,
+                        format_hunks(own_hunks),
+                        other_branch,
+                        format_hunks(other_hunks)
+                    ));
                 }
                 body.push('\n');
             }
@@ -548,3 +2086,29 @@ impl BundleManager {
         println!("═══════════════════════════════════");
     }
 }
+
+/// Render hunk ranges as a compact list, e.g. `L3-7, L12`
+fn format_hunks(hunks: &[HunkRange]) -> String {
+    hunks
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Files each queued branch changes, empty for branches whose changes couldn't be read
+fn changelog_files(
+    queued_branches: &[QueuedBranch],
+    changelogs: &HashMap<String, BranchChangelog>,
+) -> BranchFiles {
+    queued_branches
+        .iter()
+        .map(|branch| {
+            let files = changelogs
+                .get(&branch.branch_name)
+                .map(|changelog| changelog.files.iter().map(|f| f.path.clone()).collect())
+                .unwrap_or_default();
+            (branch.branch_name.clone(), files)
+        })
+        .collect()
+}
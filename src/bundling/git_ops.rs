@@ -1,29 +1,150 @@
 use super::types::{BundleAuditEntry, BundleErrorType, BundleOperationStatus};
+use crate::agent_lifecycle::types::PreFlightIssue;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
-use git2::{BranchType, DiffOptions, ErrorCode, Oid, Repository};
+use clap::ValueEnum;
+use git2::{BranchType, DiffOptions, ErrorCode, Oid, Repository, ResetType};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 use uuid::Uuid;
 
 /// Strategy for handling merge conflicts during bundling
-#[derive(Debug, Clone)]
-#[allow(dead_code)] // Architectural design - conflict resolution strategies
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
 pub enum ConflictStrategy {
     /// Abort bundle and create individual PRs
+    #[default]
     IndividualFallback,
-    /// Skip conflicting commits
+    /// Drop the conflicting branch and bundle the rest
     SkipConflicts,
-    /// Manual resolution required
+    /// Pause with the conflicted index so a human can resolve it
     ManualResolve,
 }
 
-/// Report on bundle compatibility and potential conflicts
+/// How each agent branch is assembled onto the bundle branch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AssemblyMode {
+    /// Cherry-pick every commit individually
+    #[default]
+    CherryPick,
+    /// Squash each branch into a single commit per issue
+    Squash,
+    /// Replay commits onto the bundle base, keeping authors and dropping emptied commits
+    Rebase,
+}
+
+impl AssemblyMode {
+    /// Human-readable description for bundle PR bodies
+    pub fn description(&self) -> &'static str {
+        match self {
+            AssemblyMode::CherryPick => "cherry-pick (every agent commit applied individually)",
+            AssemblyMode::Squash => "squash (one commit per issue)",
+            AssemblyMode::Rebase => "rebase (agent commits replayed onto the bundle base)",
+        }
+    }
+}
+
+/// Result of replaying a single commit
+enum PickStep {
+    Committed(Oid),
+    Empty,
+    Conflicted,
+}
+
+/// Outcome of cherry-picking one branch onto the bundle branch
 #[derive(Debug, Clone)]
+pub enum CherryPickOutcome {
+    /// Every commit was applied cleanly
+    Applied { commits: Vec<Oid> },
+    /// A commit conflicted and the bundle branch was rolled back to where it was before this branch
+    Conflicted {
+        commit: Oid,
+        conflicted_files: Vec<String>,
+    },
+    /// A commit conflicted and the conflicted index was left in place for manual resolution
+    AwaitingResolution {
+        applied: Vec<Oid>,
+        commit: Oid,
+        conflicted_files: Vec<String>,
+    },
+}
+
+/// Outcome of rebasing a queued branch onto the latest base
+#[derive(Debug, Clone, PartialEq)]
+pub enum BranchRebase {
+    /// Every commit replayed cleanly; the rebased tip
+    Rebased { tip: Oid },
+    /// A commit conflicted; the branch was left untouched
+    Conflicted {
+        commit: Oid,
+        conflicted_files: Vec<String>,
+    },
+}
+
+/// Lines of a file touched by a hunk, in merge-base coordinates (1-based, inclusive)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HunkRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl HunkRange {
+    /// Build a range from a diff hunk's old-side position
+    ///
+    /// Pure insertions touch no old lines, so they're anchored on the line they follow.
+    pub fn from_old_side(old_start: u32, old_lines: u32) -> Self {
+        Self {
+            start: old_start,
+            end: old_start + old_lines.saturating_sub(1),
+        }
+    }
+
+    /// Whether two ranges overlap or touch; git's merge treats adjacent edits as a conflict
+    pub fn overlaps(&self, other: &HunkRange) -> bool {
+        self.start <= other.end + 1 && other.start <= self.end + 1
+    }
+}
+
+impl std::fmt::Display for HunkRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "L{}", self.start)
+        } else {
+            write!(f, "L{}-{}", self.start, self.end)
+        }
+    }
+}
+
+/// Hunks in one file that two branches both change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HunkConflict {
+    pub file: String,
+    pub first_branch: String,
+    pub second_branch: String,
+    pub first_hunks: Vec<HunkRange>,
+    pub second_hunks: Vec<HunkRange>,
+    /// Whether an in-memory trial merge of the two branches conflicted on this file
+    pub confirmed_by_trial_merge: bool,
+}
+
+/// Per-file hunks a branch changes relative to its merge base with the bundle base
+#[derive(Debug, Clone)]
+struct BranchHunks {
+    merge_base: Oid,
+    files: HashMap<String, Vec<HunkRange>>,
+    binary_files: HashSet<String>,
+}
+
+/// Report on bundle compatibility and potential conflicts
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConflictCompatibilityReport {
     pub is_bundle_safe: bool,
     pub compatibility_score: f64, // 0-100, higher = better
     pub potential_conflicts: HashMap<String, Vec<String>>, // file -> conflicting branches
+    #[serde(default)]
+    pub conflicting_hunks: Vec<HunkConflict>,
     pub safe_files: Vec<String>,
     pub analyzed_branches: Vec<String>,
     pub analysis_errors: Vec<String>,
@@ -42,6 +163,7 @@ impl ConflictCompatibilityReport {
             is_bundle_safe: true,
             compatibility_score: 100.0,
             potential_conflicts: HashMap::new(),
+            conflicting_hunks: Vec::new(),
             safe_files: Vec::new(),
             analyzed_branches: Vec::new(),
             analysis_errors: Vec::new(),
@@ -50,6 +172,33 @@ impl ConflictCompatibilityReport {
     }
 }
 
+/// Lines added and removed in one file by a branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChangeStat {
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub binary: bool,
+}
+
+/// Commits and per-file diffstat a branch adds on top of the base branch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BranchChangelog {
+    /// Commit summaries, oldest first, formatted as `abcd1234 summary`
+    pub commits: Vec<String>,
+    pub files: Vec<FileChangeStat>,
+}
+
+impl BranchChangelog {
+    pub fn insertions(&self) -> usize {
+        self.files.iter().map(|f| f.insertions).sum()
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.files.iter().map(|f| f.deletions).sum()
+    }
+}
+
 /// Prediction for cherry-pick conflicts
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // Architectural - conflict prediction fields for future bundling safety
@@ -81,6 +230,15 @@ impl GitOperations {
         })
     }
 
+    /// Wrap an already opened repository
+    pub fn with_repository(repo: Repository) -> Self {
+        Self {
+            repo,
+            audit_trail: Vec::new(),
+            correlation_id: Uuid::new_v4().to_string(),
+        }
+    }
+
     /// Log operation to audit trail
     fn log_operation(
         &mut self,
@@ -206,6 +364,22 @@ impl GitOperations {
         result.map_err(|_| anyhow!("Failed to create bundle branch"))
     }
 
+    /// Point an existing branch back at the tip of `base_branch`, creating it if missing
+    ///
+    /// The branch must not be checked out.
+    pub fn reset_branch_to_base(&self, branch_name: &str, base_branch: &str) -> Result<()> {
+        let base_ref = self
+            .repo
+            .find_branch(base_branch, BranchType::Local)
+            .or_else(|_| {
+                self.repo
+                    .find_branch(&format!("origin/{base_branch}"), BranchType::Remote)
+            })?;
+        let base_commit = base_ref.get().peel_to_commit()?;
+        self.repo.branch(branch_name, &base_commit, true)?;
+        Ok(())
+    }
+
     /// Checkout the specified branch with error handling
     pub fn checkout_branch(&mut self, branch_name: &str) -> Result<()> {
         let operation = "checkout_branch";
@@ -257,38 +431,444 @@ impl GitOperations {
     }
 
     /// Cherry-pick commits from source branch onto current branch
+    ///
+    /// On conflict the bundle branch is rolled back to where it was before this
+    /// branch, unless the strategy is `ManualResolve`, in which case the conflicted
+    /// index is left in place for `continue_cherry_pick`.
     pub fn cherry_pick_branch(
         &self,
         source_branch: &str,
         strategy: ConflictStrategy,
-    ) -> Result<Vec<Oid>> {
-        let source_ref = self
+    ) -> Result<CherryPickOutcome> {
+        let rollback_to = self.repo.head()?.peel_to_commit()?.id();
+        let commits_to_pick = self.commits_to_pick(source_branch)?;
+
+        self.pick_commits(
+            &commits_to_pick,
+            &strategy,
+            rollback_to,
+            &AssemblyMode::CherryPick,
+        )
+    }
+
+    /// Squash all commits of the source branch into one commit on the current branch
+    ///
+    /// The squashed commit keeps the author of the branch tip and uses `message`.
+    pub fn squash_branch(
+        &self,
+        source_branch: &str,
+        message: &str,
+        strategy: ConflictStrategy,
+    ) -> Result<CherryPickOutcome> {
+        let rollback_to = self.repo.head()?.peel_to_commit()?.id();
+        if self.commits_to_pick(source_branch)?.is_empty() {
+            return Ok(CherryPickOutcome::Applied {
+                commits: Vec::new(),
+            });
+        }
+
+        let source_commit = self.find_branch_commit(source_branch)?;
+        let merge_base = self.repo.merge_base(source_commit.id(), rollback_to)?;
+        let merge_base_commit = self.repo.find_commit(merge_base)?;
+
+        // A detached commit holding the whole branch diff, cherry-picked like any other
+        let author = source_commit.author();
+        let squashed = self.repo.commit(
+            None,
+            &author,
+            &author,
+            message,
+            &source_commit.tree()?,
+            &[&merge_base_commit],
+        )?;
+
+        self.pick_commits(&[squashed], &strategy, rollback_to, &AssemblyMode::Squash)
+    }
+
+    /// Replay the source branch's commits onto the current branch like a rebase-merge
+    ///
+    /// Original authors are kept, the bundler becomes the committer and commits
+    /// that end up empty on the bundle base are dropped.
+    pub fn rebase_branch(
+        &self,
+        source_branch: &str,
+        strategy: ConflictStrategy,
+    ) -> Result<CherryPickOutcome> {
+        let rollback_to = self.repo.head()?.peel_to_commit()?.id();
+        let commits_to_pick = self.commits_to_pick(source_branch)?;
+
+        self.pick_commits(
+            &commits_to_pick,
+            &strategy,
+            rollback_to,
+            &AssemblyMode::Rebase,
+        )
+    }
+
+    /// Finish a cherry-pick that was paused for manual resolution, then apply the
+    /// rest of the source branch's commits
+    pub fn continue_cherry_pick(
+        &self,
+        source_branch: &str,
+        conflicted_commit: Oid,
+        mode: &AssemblyMode,
+    ) -> Result<CherryPickOutcome> {
+        let mut index = self.repo.index()?;
+        index.read(false)?;
+
+        if index.has_conflicts() {
+            return Ok(CherryPickOutcome::AwaitingResolution {
+                applied: Vec::new(),
+                commit: conflicted_commit,
+                conflicted_files: self.conflicted_files()?,
+            });
+        }
+
+        // Commit the resolution with the original commit's authorship
+        let commit = self.repo.find_commit(conflicted_commit)?;
+        let tree_oid = index.write_tree()?;
+        let tree = self.repo.find_tree(tree_oid)?;
+        let head = self.repo.head()?.peel_to_commit()?;
+
+        let mut applied = Vec::new();
+        if tree_oid != head.tree_id() {
+            let signature = commit.author();
+            let committer = self.committer_signature(&commit, mode)?;
+            let new_oid = self.repo.commit(
+                Some("HEAD"),
+                &signature,
+                &committer,
+                commit.message().unwrap_or("Cherry-picked commit"),
+                &tree,
+                &[&head],
+            )?;
+            applied.push(new_oid);
+            println!(
+                "✅ Resolved and committed: {} ({})",
+                &commit.id().to_string()[..8],
+                commit.summary().unwrap_or("No message")
+            );
+        }
+        self.repo.cleanup_state()?;
+
+        // A squashed commit isn't on the source branch, so nothing remains after it
+        let remaining: Vec<Oid> = self
+            .commits_to_pick(source_branch)?
+            .into_iter()
+            .skip_while(|oid| *oid != conflicted_commit)
+            .skip(1)
+            .collect();
+
+        let rollback_to = self.repo.head()?.peel_to_commit()?.id();
+        match self.pick_commits(
+            &remaining,
+            &ConflictStrategy::ManualResolve,
+            rollback_to,
+            mode,
+        )? {
+            CherryPickOutcome::Applied { commits } => {
+                applied.extend(commits);
+                Ok(CherryPickOutcome::Applied { commits: applied })
+            }
+            CherryPickOutcome::AwaitingResolution {
+                applied: more,
+                commit,
+                conflicted_files,
+            } => {
+                applied.extend(more);
+                Ok(CherryPickOutcome::AwaitingResolution {
+                    applied,
+                    commit,
+                    conflicted_files,
+                })
+            }
+            conflicted => Ok(conflicted),
+        }
+    }
+
+    /// Discard an in-progress cherry-pick and reset the working tree to HEAD
+    pub fn abort_cherry_pick(&self) -> Result<()> {
+        let head = self.repo.head()?.peel_to_commit()?;
+        self.repo.cleanup_state()?;
+        self.repo.reset(head.as_object(), ResetType::Hard, None)?;
+        Ok(())
+    }
+
+    /// Commit the current branch points at
+    pub fn head_commit_id(&self) -> Result<Oid> {
+        Ok(self.repo.head()?.peel_to_commit()?.id())
+    }
+
+    /// Drop any in-progress cherry-pick and hard-reset the current branch to `commit`
+    pub fn reset_to_commit(&self, commit: Oid) -> Result<()> {
+        let commit = self.repo.find_commit(commit)?;
+        self.repo.cleanup_state()?;
+        self.repo.reset(commit.as_object(), ResetType::Hard, None)?;
+        Ok(())
+    }
+
+    /// Summaries of the commits the source branch would contribute, oldest first
+    pub fn branch_commit_summaries(&self, source_branch: &str) -> Result<Vec<String>> {
+        self.commits_to_pick(source_branch)?
+            .into_iter()
+            .map(|oid| {
+                let commit = self.repo.find_commit(oid)?;
+                Ok(format!(
+                    "{} {}",
+                    &oid.to_string()[..8],
+                    commit.summary().unwrap_or("No message")
+                ))
+            })
+            .collect()
+    }
+
+    /// Commits and diffstat of a branch relative to its merge base with `base_branch`
+    ///
+    /// Measured against the original branch rather than the bundle, so the numbers
+    /// don't depend on assembly mode or on which branches were bundled before it.
+    pub fn branch_changelog(
+        &self,
+        source_branch: &str,
+        base_branch: &str,
+    ) -> Result<BranchChangelog> {
+        let source_commit = self.find_branch_commit(source_branch)?;
+        let base_commit = self.find_branch_commit(base_branch)?;
+        let merge_base = self.repo.merge_base(source_commit.id(), base_commit.id())?;
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(source_commit.id())?;
+        revwalk.hide(merge_base)?;
+        let mut oids: Vec<Oid> = revwalk.collect::<Result<Vec<_>, _>>()?;
+        oids.reverse();
+
+        let mut changelog = BranchChangelog::default();
+        for oid in oids {
+            let commit = self.repo.find_commit(oid)?;
+            changelog.commits.push(format!(
+                "{} {}",
+                &oid.to_string()[..8],
+                commit.summary().unwrap_or("No message")
+            ));
+        }
+
+        let base_tree = self.repo.find_commit(merge_base)?.tree()?;
+        let diff =
+            self.repo
+                .diff_tree_to_tree(Some(&base_tree), Some(&source_commit.tree()?), None)?;
+
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .and_then(|p| p.to_str())
+            else {
+                continue;
+            };
+
+            // Loading the patch is what detects binary content
+            let (insertions, deletions, binary) = match git2::Patch::from_diff(&diff, idx)? {
+                Some(patch) if !patch.delta().flags().is_binary() => {
+                    let (_, insertions, deletions) = patch.line_stats()?;
+                    (insertions, deletions, false)
+                }
+                _ => (0, 0, true),
+            };
+
+            changelog.files.push(FileChangeStat {
+                path: path.to_string(),
+                insertions,
+                deletions,
+                binary,
+            });
+        }
+
+        Ok(changelog)
+    }
+
+    /// Commit a branch points at, local first then `origin/<branch>`
+    pub fn branch_tip(&self, branch_name: &str) -> Result<Oid> {
+        Ok(self.find_branch_commit(branch_name)?.id())
+    }
+
+    /// Last known tip of a branch on a remote, from its remote-tracking ref
+    pub fn remote_tip(&self, branch_name: &str, remote_name: &str) -> Option<Oid> {
+        self.repo
+            .find_reference(&format!("refs/remotes/{remote_name}/{branch_name}"))
+            .ok()
+            .and_then(|reference| reference.target())
+    }
+
+    /// Update the remote-tracking refs of `branch_names` from the remote
+    ///
+    /// Branches the remote doesn't have are left alone.
+    pub fn fetch_branches(&self, branch_names: &[String], remote_name: &str) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let refspecs: Vec<String> = branch_names
+            .iter()
+            .map(|name| format!("+refs/heads/{name}:refs/remotes/{remote_name}/{name}"))
+            .collect();
+        remote.fetch(&refspecs, None, None)?;
+        Ok(())
+    }
+
+    /// Whether `ancestor` is `descendant` or one of its ancestors
+    pub fn is_ancestor(&self, ancestor: Oid, descendant: Oid) -> Result<bool> {
+        Ok(ancestor == descendant || self.repo.graph_descendant_of(descendant, ancestor)?)
+    }
+
+    /// `BehindMain` if the base has commits the branch doesn't
+    pub fn behind_base(
+        &self,
+        branch_name: &str,
+        base_branch: &str,
+    ) -> Result<Option<PreFlightIssue>> {
+        let branch = self.find_branch_commit(branch_name)?.id();
+        let base = self.find_branch_commit(base_branch)?.id();
+        let (_, behind) = self.repo.graph_ahead_behind(branch, base)?;
+
+        Ok((behind > 0).then_some(PreFlightIssue::BehindMain {
+            commits: behind as u32,
+        }))
+    }
+
+    /// Replay a branch onto the base in a scratch worktree
+    ///
+    /// The current checkout is never touched and the branch itself isn't moved;
+    /// callers decide what to do with the rebased tip (see `set_branch_tip`).
+    pub fn rebase_in_worktree(&self, branch_name: &str, base_branch: &str) -> Result<BranchRebase> {
+        let base = self.find_branch_commit(base_branch)?;
+        let scratch = format!("mls-rebase-{}", Uuid::new_v4().simple());
+        let scratch_path = std::env::temp_dir().join(&scratch);
+        let scratch_branch = self.repo.branch(&scratch, &base, false)?;
+
+        let result = (|| -> Result<BranchRebase> {
+            let mut options = git2::WorktreeAddOptions::new();
+            options.reference(Some(scratch_branch.get()));
+            let worktree = self
+                .repo
+                .worktree(&scratch, &scratch_path, Some(&options))?;
+            let scratch_ops =
+                GitOperations::with_repository(Repository::open_from_worktree(&worktree)?);
+
+            match scratch_ops.rebase_branch(branch_name, ConflictStrategy::SkipConflicts)? {
+                CherryPickOutcome::Applied { .. } => Ok(BranchRebase::Rebased {
+                    tip: scratch_ops.head_commit_id()?,
+                }),
+                CherryPickOutcome::Conflicted {
+                    commit,
+                    conflicted_files,
+                }
+                | CherryPickOutcome::AwaitingResolution {
+                    commit,
+                    conflicted_files,
+                    ..
+                } => Ok(BranchRebase::Conflicted {
+                    commit,
+                    conflicted_files,
+                }),
+            }
+        })();
+
+        // The scratch worktree and its branch go away whatever happened
+        if let Ok(worktree) = self.repo.find_worktree(&scratch) {
+            let mut prune = git2::WorktreePruneOptions::new();
+            prune.valid(true).working_tree(true);
+            let _ = worktree.prune(Some(&mut prune));
+        }
+        let _ = std::fs::remove_dir_all(&scratch_path);
+        if let Ok(mut branch) = self.repo.find_branch(&scratch, BranchType::Local) {
+            let _ = branch.delete();
+        }
+
+        result
+    }
+
+    /// Point a local branch at `tip`, creating it if it only exists on the remote
+    ///
+    /// Refuses to move the branch that is currently checked out.
+    pub fn set_branch_tip(&self, branch_name: &str, tip: Oid) -> Result<()> {
+        if self
             .repo
-            .find_branch(source_branch, BranchType::Local)
+            .head()
+            .ok()
+            .is_some_and(|head| head.shorthand() == Some(branch_name))
+        {
+            return Err(anyhow!(
+                "{} is checked out; switch branches before it can be rebased",
+                branch_name
+            ));
+        }
+        self.repo.reference(
+            &format!("refs/heads/{branch_name}"),
+            tip,
+            true,
+            "my-little-soda: rebase onto base branch",
+        )?;
+        Ok(())
+    }
+
+    /// Tip commit of a local branch, falling back to its origin counterpart
+    fn find_branch_commit(&self, branch_name: &str) -> Result<git2::Commit<'_>> {
+        let branch_ref = self
+            .repo
+            .find_branch(branch_name, BranchType::Local)
             .or_else(|_| {
                 self.repo
-                    .find_branch(&format!("origin/{source_branch}"), BranchType::Remote)
+                    .find_branch(&format!("origin/{branch_name}"), BranchType::Remote)
             })?;
 
-        let source_commit = source_ref.get().peel_to_commit()?;
+        Ok(branch_ref.get().peel_to_commit()?)
+    }
+
+    /// Committer for a replayed commit: the agent itself, or the bundler when rebasing
+    fn committer_signature(
+        &self,
+        commit: &git2::Commit,
+        mode: &AssemblyMode,
+    ) -> Result<git2::Signature<'static>> {
+        if *mode == AssemblyMode::Rebase {
+            match self.repo.signature() {
+                Ok(signature) => Ok(signature),
+                Err(_) => Ok(git2::Signature::now(
+                    "My Little Soda Bundler",
+                    "noreply@my-little-soda.dev",
+                )?),
+            }
+        } else {
+            Ok(commit.author().to_owned())
+        }
+    }
 
-        // Get the current HEAD to find commits to cherry-pick
+    /// Commits on the source branch that aren't on the current branch, oldest first
+    fn commits_to_pick(&self, source_branch: &str) -> Result<Vec<Oid>> {
+        let source_commit = self.find_branch_commit(source_branch)?;
         let head = self.repo.head()?.peel_to_commit()?;
 
-        // Find commits on source branch that aren't on current branch
         let mut revwalk = self.repo.revwalk()?;
         revwalk.push(source_commit.id())?;
         revwalk.hide(head.id())?;
 
-        let commits_to_pick: Vec<Oid> = revwalk.collect::<Result<Vec<_>, _>>()?;
+        let mut commits: Vec<Oid> = revwalk.collect::<Result<Vec<_>, _>>()?;
+        commits.reverse();
+        Ok(commits)
+    }
+
+    /// Apply commits in order, handling the first conflict according to the strategy
+    fn pick_commits(
+        &self,
+        commits: &[Oid],
+        strategy: &ConflictStrategy,
+        rollback_to: Oid,
+        mode: &AssemblyMode,
+    ) -> Result<CherryPickOutcome> {
         let mut picked_commits = Vec::new();
 
-        for commit_oid in commits_to_pick.iter().rev() {
-            // Cherry-pick in chronological order
+        for commit_oid in commits {
             let commit = self.repo.find_commit(*commit_oid)?;
 
-            match self.cherry_pick_commit(&commit) {
-                Ok(new_oid) => {
+            match self.cherry_pick_commit(&commit, mode)? {
+                PickStep::Committed(new_oid) => {
                     picked_commits.push(new_oid);
                     println!(
                         "✅ Cherry-picked: {} ({})",
@@ -296,34 +876,56 @@ impl GitOperations {
                         commit.summary().unwrap_or("No message")
                     );
                 }
-                Err(e) => match strategy {
-                    ConflictStrategy::IndividualFallback => {
-                        return Err(anyhow!("Conflict cherry-picking {}: {}", commit.id(), e));
-                    }
-                    ConflictStrategy::SkipConflicts => {
+                PickStep::Empty => {
+                    println!(
+                        "⏭️  Dropped empty commit: {} ({})",
+                        &commit.id().to_string()[..8],
+                        commit.summary().unwrap_or("No message")
+                    );
+                }
+                PickStep::Conflicted => {
+                    let conflicted_files = self.conflicted_files()?;
+
+                    if *strategy == ConflictStrategy::ManualResolve {
                         println!(
-                            "⚠️  Skipping conflicted commit: {} ({})",
+                            "⏸️  Conflict in commit {} ({}), leaving it for manual resolution",
                             &commit.id().to_string()[..8],
                             commit.summary().unwrap_or("No message")
                         );
-                        continue;
+                        return Ok(CherryPickOutcome::AwaitingResolution {
+                            applied: picked_commits,
+                            commit: commit.id(),
+                            conflicted_files,
+                        });
                     }
-                    ConflictStrategy::ManualResolve => {
-                        return Err(anyhow!(
-                            "Manual resolution required for commit {}: {}",
-                            commit.id(),
-                            e
-                        ));
-                    }
-                },
+
+                    println!(
+                        "⚠️  Conflict in commit {} ({}), rolling back branch",
+                        &commit.id().to_string()[..8],
+                        commit.summary().unwrap_or("No message")
+                    );
+                    let rollback_commit = self.repo.find_commit(rollback_to)?;
+                    self.repo.cleanup_state()?;
+                    self.repo
+                        .reset(rollback_commit.as_object(), ResetType::Hard, None)?;
+
+                    return Ok(CherryPickOutcome::Conflicted {
+                        commit: commit.id(),
+                        conflicted_files,
+                    });
+                }
             }
         }
 
-        Ok(picked_commits)
+        Ok(CherryPickOutcome::Applied {
+            commits: picked_commits,
+        })
     }
 
     /// Cherry-pick a single commit using git2's built-in cherry-pick
-    fn cherry_pick_commit(&self, commit: &git2::Commit) -> Result<Oid> {
+    ///
+    /// In rebase mode a commit whose changes are already on the bundle branch is dropped.
+    fn cherry_pick_commit(&self, commit: &git2::Commit, mode: &AssemblyMode) -> Result<PickStep> {
         // Use git2's built-in cherry-pick functionality
         let mut cherrypick_options = git2::CherrypickOptions::new();
         self.repo
@@ -332,30 +934,63 @@ impl GitOperations {
         // Check if there are conflicts
         let mut index = self.repo.index()?;
         if index.has_conflicts() {
-            return Err(anyhow!("Cherry-pick resulted in conflicts"));
+            return Ok(PickStep::Conflicted);
         }
 
         // Commit the cherry-pick
         let signature = commit.author();
+        let committer = self.committer_signature(commit, mode)?;
         let tree_oid = index.write_tree()?;
         let tree = self.repo.find_tree(tree_oid)?;
         let head = self.repo.head()?.peel_to_commit()?;
 
+        if *mode == AssemblyMode::Rebase && tree_oid == head.tree_id() {
+            self.repo.cleanup_state()?;
+            return Ok(PickStep::Empty);
+        }
+
         let new_commit_oid = self.repo.commit(
             Some("HEAD"),
             &signature,
-            &signature,
+            &committer,
             commit.message().unwrap_or("Cherry-picked commit"),
             &tree,
             &[&head],
         )?;
+        self.repo.cleanup_state()?;
 
-        Ok(new_commit_oid)
+        Ok(PickStep::Committed(new_commit_oid))
+    }
+
+    /// Paths with unresolved conflicts in the index
+    pub fn conflicted_files(&self) -> Result<Vec<String>> {
+        let index = self.repo.index()?;
+        let mut files = Vec::new();
+
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+            if let Some(entry) = entry {
+                let path = String::from_utf8_lossy(&entry.path).to_string();
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+
+        Ok(files)
     }
 
     /// Push branch to remote with error handling
     pub fn push_branch(&mut self, branch_name: &str, remote_name: &str) -> Result<()> {
         let operation = "push_branch";
+        // Nothing is published before the secret scan passes
+        crate::git::secrets::ensure_no_secrets(
+            &self.repo,
+            branch_name,
+            remote_name,
+            &crate::config::base_branch(),
+        )?;
         let start_time = Instant::now();
 
         let result: Result<(), BundleErrorType> = (|| {
@@ -401,6 +1036,151 @@ impl GitOperations {
         result.map_err(|_| anyhow!("Failed to push branch"))
     }
 
+    /// Force-push a rewritten branch, refusing if the remote moved away from `expected`
+    ///
+    /// `expected` is the remote tip the rewrite was based on, so commits pushed by
+    /// someone else in the meantime are never overwritten.
+    pub fn force_push_with_lease(
+        &mut self,
+        branch_name: &str,
+        remote_name: &str,
+        expected: Oid,
+    ) -> Result<()> {
+        let operation = "force_push_with_lease";
+        crate::git::secrets::ensure_no_secrets(
+            &self.repo,
+            branch_name,
+            remote_name,
+            &crate::config::base_branch(),
+        )?;
+        let start_time = Instant::now();
+        let remote_ref = format!("refs/heads/{branch_name}");
+
+        let result: Result<(), BundleErrorType> = (|| {
+            let mut remote = self
+                .repo
+                .find_remote(remote_name)
+                .map_err(|e| self.handle_git_error(operation, e))?;
+
+            remote
+                .connect(git2::Direction::Push)
+                .map_err(|e| self.handle_git_error(operation, e))?;
+            let current = remote
+                .list()
+                .map_err(|e| self.handle_git_error(operation, e))?
+                .iter()
+                .find(|head| head.name() == remote_ref)
+                .map(|head| head.oid());
+            remote
+                .disconnect()
+                .map_err(|e| self.handle_git_error(operation, e))?;
+
+            if current != Some(expected) {
+                return Err(BundleErrorType::GitOperation {
+                    operation: operation.to_string(),
+                    details: format!(
+                        "stale lease: {remote_name}/{branch_name} is at {}, expected {expected}",
+                        current.map_or("nothing".to_string(), |oid| oid.to_string())
+                    ),
+                });
+            }
+
+            remote
+                .push(&[&format!("+{remote_ref}:{remote_ref}")], None)
+                .map_err(|e| self.handle_git_error(operation, e))?;
+            Ok(())
+        })();
+
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let (status, error) = match &result {
+            Ok(_) => {
+                println!("✅ Force-pushed branch {branch_name} to {remote_name}");
+                (BundleOperationStatus::Completed, None)
+            }
+            Err(error) => (BundleOperationStatus::Failed, Some(error.clone())),
+        };
+        self.log_operation(
+            operation,
+            Some(branch_name.to_string()),
+            vec![],
+            status,
+            error.clone(),
+            execution_time,
+        );
+
+        match error {
+            None => Ok(()),
+            Some(BundleErrorType::GitOperation { details, .. }) => {
+                Err(anyhow!("Failed to force-push {}: {}", branch_name, details))
+            }
+            Some(_) => Err(anyhow!("Failed to force-push {}", branch_name)),
+        }
+    }
+
+    /// Delete a local branch
+    pub fn delete_local_branch(&mut self, branch_name: &str) -> Result<()> {
+        let operation = "delete_local_branch";
+        let start_time = Instant::now();
+
+        let result: Result<(), BundleErrorType> = (|| {
+            let mut branch = self
+                .repo
+                .find_branch(branch_name, BranchType::Local)
+                .map_err(|e| self.handle_git_error(operation, e))?;
+            branch
+                .delete()
+                .map_err(|e| self.handle_git_error(operation, e))?;
+            Ok(())
+        })();
+
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        let (status, error) = match &result {
+            Ok(_) => (BundleOperationStatus::Completed, None),
+            Err(error) => (BundleOperationStatus::Failed, Some(error.clone())),
+        };
+        self.log_operation(
+            operation,
+            Some(branch_name.to_string()),
+            vec![],
+            status,
+            error,
+            execution_time,
+        );
+
+        result.map_err(|_| anyhow!("Failed to delete branch {}", branch_name))
+    }
+
+    /// Rename a branch locally and on the remote, dropping the old remote-tracking ref
+    ///
+    /// Either side is skipped when the branch doesn't exist there.
+    pub fn rename_branch(
+        &self,
+        branch_name: &str,
+        new_name: &str,
+        remote_name: &str,
+    ) -> Result<()> {
+        if let Ok(mut branch) = self.repo.find_branch(branch_name, BranchType::Local) {
+            branch.rename(new_name, false)?;
+        }
+
+        let tracking_ref = format!("refs/remotes/{remote_name}/{branch_name}");
+        if self.repo.find_reference(&tracking_ref).is_ok() {
+            let mut remote = self.repo.find_remote(remote_name)?;
+            remote.push(
+                &[
+                    format!("{tracking_ref}:refs/heads/{new_name}"),
+                    format!(":refs/heads/{branch_name}"),
+                ],
+                None,
+            )?;
+            // The push updates the tracking refs itself when the remote is configured to
+            if let Ok(mut tracking) = self.repo.find_reference(&tracking_ref) {
+                tracking.delete()?;
+            }
+        }
+        Ok(())
+    }
+
     /// Check if a branch exists
     pub fn branch_exists(&self, branch_name: &str) -> bool {
         self.repo
@@ -440,24 +1220,24 @@ impl GitOperations {
     }
 
     /// Pre-flight conflict analysis for multiple branches
-    pub fn analyze_bundle_conflicts(
+    ///
+    /// Two branches only conflict on a file when the hunks they change there overlap
+    /// or touch. With `trial_merge` each pair sharing a file is also merged in memory
+    /// with `merge_trees`, and that result decides whether the file really conflicts.
+    pub fn analyze_bundle_conflicts_with(
         &self,
         branches: &[String],
         base_branch: &str,
+        trial_merge: bool,
     ) -> Result<ConflictCompatibilityReport> {
         let mut report = ConflictCompatibilityReport::new();
-        let mut file_changes: HashMap<String, Vec<String>> = HashMap::new();
+        let mut branch_hunks: Vec<(String, BranchHunks)> = Vec::new();
 
-        // Analyze file changes for each branch
+        // Collect hunks for each branch
         for branch_name in branches {
-            match self.get_changed_files(branch_name, base_branch) {
-                Ok(changed_files) => {
-                    for file in changed_files {
-                        file_changes
-                            .entry(file.clone())
-                            .or_default()
-                            .push(branch_name.clone());
-                    }
+            match self.get_changed_hunks(branch_name, base_branch) {
+                Ok(hunks) => {
+                    branch_hunks.push((branch_name.clone(), hunks));
                     report.analyzed_branches.push(branch_name.clone());
                 }
                 Err(e) => {
@@ -468,15 +1248,104 @@ impl GitOperations {
             }
         }
 
-        // Identify potential conflicts (same file modified by multiple branches)
-        for (file, modifying_branches) in file_changes {
-            if modifying_branches.len() > 1 {
-                report.potential_conflicts.insert(file, modifying_branches);
-            } else {
-                report.safe_files.extend(modifying_branches);
+        let mut touched_files: HashSet<String> = HashSet::new();
+        let mut conflicting_files: HashSet<String> = HashSet::new();
+
+        // Compare every pair of branches on the files they share
+        for (i, (first_branch, first)) in branch_hunks.iter().enumerate() {
+            touched_files.extend(first.files.keys().cloned());
+
+            for (second_branch, second) in branch_hunks.iter().skip(i + 1) {
+                let mut shared_files: Vec<&String> = first
+                    .files
+                    .keys()
+                    .filter(|file| second.files.contains_key(*file))
+                    .collect();
+                if shared_files.is_empty() {
+                    continue;
+                }
+                shared_files.sort();
+
+                let merge_conflicts = if trial_merge {
+                    match self.trial_merge_conflicts(first_branch, second_branch) {
+                        Ok(files) => Some(files),
+                        Err(e) => {
+                            report.analysis_errors.push(format!(
+                                "Trial merge of {first_branch} and {second_branch} failed: {e}"
+                            ));
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                for file in shared_files {
+                    let first_ranges = &first.files[file];
+                    let second_ranges = &second.files[file];
+
+                    // Line numbers are only comparable when both diffs share a merge base
+                    let comparable = first.merge_base == second.merge_base;
+                    let binary =
+                        first.binary_files.contains(file) || second.binary_files.contains(file);
+
+                    let first_overlapping: Vec<HunkRange> = first_ranges
+                        .iter()
+                        .filter(|a| second_ranges.iter().any(|b| a.overlaps(b)))
+                        .cloned()
+                        .collect();
+                    let second_overlapping: Vec<HunkRange> = second_ranges
+                        .iter()
+                        .filter(|b| first_ranges.iter().any(|a| a.overlaps(b)))
+                        .cloned()
+                        .collect();
+
+                    let confirmed = merge_conflicts
+                        .as_ref()
+                        .map(|files| files.contains(file))
+                        .unwrap_or(false);
+                    let is_conflict = match &merge_conflicts {
+                        Some(_) => confirmed,
+                        None => binary || !comparable || !first_overlapping.is_empty(),
+                    };
+
+                    if !is_conflict {
+                        continue;
+                    }
+
+                    conflicting_files.insert(file.clone());
+                    let branches = report.potential_conflicts.entry(file.clone()).or_default();
+                    for branch in [first_branch, second_branch] {
+                        if !branches.contains(branch) {
+                            branches.push(branch.clone());
+                        }
+                    }
+
+                    // Without overlapping hunks (binary files, diverged bases) show everything
+                    let (first_hunks, second_hunks) = if first_overlapping.is_empty() {
+                        (first_ranges.clone(), second_ranges.clone())
+                    } else {
+                        (first_overlapping, second_overlapping)
+                    };
+                    report.conflicting_hunks.push(HunkConflict {
+                        file: file.clone(),
+                        first_branch: first_branch.clone(),
+                        second_branch: second_branch.clone(),
+                        first_hunks,
+                        second_hunks,
+                        confirmed_by_trial_merge: confirmed,
+                    });
+                }
             }
         }
 
+        let mut safe_files: Vec<String> = touched_files
+            .difference(&conflicting_files)
+            .cloned()
+            .collect();
+        safe_files.sort();
+        report.safe_files = safe_files;
+
         // Calculate compatibility score
         let total_branches = branches.len();
         let conflicting_branches: HashSet<_> = report
@@ -497,49 +1366,89 @@ impl GitOperations {
         Ok(report)
     }
 
-    /// Get list of files changed in a branch compared to base
-    pub fn get_changed_files(&self, branch_name: &str, base_branch: &str) -> Result<Vec<String>> {
-        let branch_ref = self
-            .repo
-            .find_branch(branch_name, BranchType::Local)
-            .or_else(|_| {
-                self.repo
-                    .find_branch(&format!("origin/{branch_name}"), BranchType::Remote)
-            })?;
-        let base_ref = self
-            .repo
-            .find_branch(base_branch, BranchType::Local)
-            .or_else(|_| {
-                self.repo
-                    .find_branch(&format!("origin/{base_branch}"), BranchType::Remote)
-            })?;
+    /// Hunks changed on a branch relative to its merge base with the base branch
+    fn get_changed_hunks(&self, branch_name: &str, base_branch: &str) -> Result<BranchHunks> {
+        let branch_commit = self.find_branch_commit(branch_name)?;
+        let base_commit = self.find_branch_commit(base_branch)?;
+        let merge_base = self.repo.merge_base(branch_commit.id(), base_commit.id())?;
 
-        let branch_tree = branch_ref.get().peel_to_tree()?;
-        let base_tree = base_ref.get().peel_to_tree()?;
+        let base_tree = self.repo.find_commit(merge_base)?.tree()?;
+        let branch_tree = branch_commit.tree()?;
 
         let mut diff_opts = DiffOptions::new();
+        diff_opts.context_lines(0);
         let diff = self.repo.diff_tree_to_tree(
             Some(&base_tree),
             Some(&branch_tree),
             Some(&mut diff_opts),
         )?;
 
-        let mut changed_files = Vec::new();
-        diff.foreach(
-            &mut |delta: git2::DiffDelta, _progress: f32| -> bool {
-                if let Some(path) = delta.new_file().path() {
-                    if let Some(path_str) = path.to_str() {
-                        changed_files.push(path_str.to_string());
+        let mut hunks = BranchHunks {
+            merge_base,
+            files: HashMap::new(),
+            binary_files: HashSet::new(),
+        };
+
+        for (idx, delta) in diff.deltas().enumerate() {
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .and_then(|p| p.to_str())
+                .map(|p| p.to_string());
+            let Some(path) = path else {
+                continue;
+            };
+
+            let mut ranges = Vec::new();
+            match git2::Patch::from_diff(&diff, idx)? {
+                Some(patch) if !patch.delta().flags().is_binary() => {
+                    for hunk_idx in 0..patch.num_hunks() {
+                        let (hunk, _) = patch.hunk(hunk_idx)?;
+                        ranges.push(HunkRange::from_old_side(hunk.old_start(), hunk.old_lines()));
                     }
                 }
-                true
-            },
-            None,
-            None,
-            None,
-        )?;
+                _ => {
+                    hunks.binary_files.insert(path.clone());
+                }
+            }
+
+            // Mode-only changes have no hunks but still touch the whole file
+            if ranges.is_empty() {
+                ranges.push(HunkRange { start: 0, end: 0 });
+            }
+            hunks.files.insert(path, ranges);
+        }
+
+        Ok(hunks)
+    }
+
+    /// Merge two branches in memory and return the files that conflict
+    pub fn trial_merge_conflicts(
+        &self,
+        first_branch: &str,
+        second_branch: &str,
+    ) -> Result<HashSet<String>> {
+        let first = self.find_branch_commit(first_branch)?;
+        let second = self.find_branch_commit(second_branch)?;
+        let ancestor = self.repo.merge_base(first.id(), second.id())?;
+        let ancestor_tree = self.repo.find_commit(ancestor)?.tree()?;
+
+        let index = self
+            .repo
+            .merge_trees(&ancestor_tree, &first.tree()?, &second.tree()?, None)?;
+
+        let mut files = HashSet::new();
+        if index.has_conflicts() {
+            for conflict in index.conflicts()? {
+                let conflict = conflict?;
+                if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                    files.insert(String::from_utf8_lossy(&entry.path).to_string());
+                }
+            }
+        }
 
-        Ok(changed_files)
+        Ok(files)
     }
 
     /// Simulate cherry-pick to detect conflicts without making changes
@@ -4,7 +4,15 @@
 
 pub mod bundler;
 pub mod git_ops;
+pub mod ordering;
+pub mod path_policy;
+pub mod persistence;
+pub mod pr_body;
+pub mod rebuild;
+pub mod refresh;
+pub mod size_limits;
 pub mod types;
+pub mod unblocker;
 
 pub use bundler::BundleManager;
 pub use types::BundleResult;
@@ -0,0 +1,194 @@
+//! Deterministic ordering and partitioning of queued branches
+//!
+//! Cherry-pick order decides whether a bundle conflicts and how its history
+//! reads, so branches are ordered by declared issue dependencies first, then
+//! with branches that share no files ahead of overlapping clusters, then by
+//! issue number.
+
+use crate::train_schedule::QueuedBranch;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Files changed by each branch, keyed by branch name
+pub type BranchFiles = HashMap<String, HashSet<String>>;
+
+/// Queued branches in the order they should be applied
+#[derive(Debug, Clone)]
+pub struct BranchOrder {
+    pub branches: Vec<QueuedBranch>,
+    /// Issues whose dependencies form a cycle; they keep their overlap order
+    pub dependency_cycle: Vec<u64>,
+}
+
+/// Order branches by dependencies, then file-overlap clusters, then issue number
+pub fn order_branches(branches: &[QueuedBranch], files: &BranchFiles) -> BranchOrder {
+    let clusters = overlap_clusters(branches, files);
+
+    // Standalone branches first, then clusters by their lowest issue number
+    let mut cluster_keys: HashMap<usize, (bool, u64)> = HashMap::new();
+    for (idx, branch) in branches.iter().enumerate() {
+        let cluster = clusters[idx];
+        let size = clusters.iter().filter(|c| **c == cluster).count();
+        let key = cluster_keys
+            .entry(cluster)
+            .or_insert((size > 1, branch.issue_number));
+        key.1 = key.1.min(branch.issue_number);
+    }
+    let priority = |idx: usize| {
+        let (overlapping, lowest_issue) = cluster_keys[&clusters[idx]];
+        (
+            overlapping,
+            lowest_issue,
+            branches[idx].issue_number,
+            branches[idx].branch_name.clone(),
+        )
+    };
+
+    // Kahn's algorithm, always taking the highest-priority ready branch
+    let index_of_issue: HashMap<u64, usize> = branches
+        .iter()
+        .enumerate()
+        .map(|(idx, b)| (b.issue_number, idx))
+        .collect();
+    let mut waiting_on: Vec<HashSet<usize>> = branches
+        .iter()
+        .map(|b| {
+            b.depends_on
+                .iter()
+                .filter_map(|issue| index_of_issue.get(issue).copied())
+                .filter(|dep| branches[*dep].issue_number != b.issue_number)
+                .collect()
+        })
+        .collect();
+
+    let mut ready: BTreeSet<(bool, u64, u64, String, usize)> = BTreeSet::new();
+    for (idx, deps) in waiting_on.iter().enumerate() {
+        if deps.is_empty() {
+            let (a, b, c, d) = priority(idx);
+            ready.insert((a, b, c, d, idx));
+        }
+    }
+
+    let mut placed = vec![false; branches.len()];
+    let mut ordered = Vec::with_capacity(branches.len());
+    while let Some(next) = ready.pop_first() {
+        let idx = next.4;
+        placed[idx] = true;
+        ordered.push(branches[idx].clone());
+
+        for (other, deps) in waiting_on.iter_mut().enumerate() {
+            if !placed[other] && deps.remove(&idx) && deps.is_empty() {
+                let (a, b, c, d) = priority(other);
+                ready.insert((a, b, c, d, other));
+            }
+        }
+    }
+
+    // Whatever is left is stuck in a cycle; keep it in overlap order
+    let mut stuck: Vec<usize> = (0..branches.len()).filter(|idx| !placed[*idx]).collect();
+    stuck.sort_by_key(|idx| priority(*idx));
+    let dependency_cycle = stuck
+        .iter()
+        .map(|idx| branches[*idx].issue_number)
+        .collect();
+    ordered.extend(stuck.into_iter().map(|idx| branches[idx].clone()));
+
+    BranchOrder {
+        branches: ordered,
+        dependency_cycle,
+    }
+}
+
+/// Split ordered branches into independent bundles of at most `max_per_bundle` branches
+///
+/// Branches sharing a file or linked by a dependency always stay in the same
+/// bundle, so no two bundles touch the same file. A group larger than the limit
+/// becomes a bundle of its own rather than being split.
+pub fn partition_branches(
+    ordered: &[QueuedBranch],
+    files: &BranchFiles,
+    max_per_bundle: usize,
+) -> Vec<Vec<QueuedBranch>> {
+    let max_per_bundle = max_per_bundle.max(1);
+    let groups = linked_clusters(ordered, files);
+
+    // Groups in order of their first branch
+    let mut group_order: Vec<usize> = Vec::new();
+    for group in &groups {
+        if !group_order.contains(group) {
+            group_order.push(*group);
+        }
+    }
+
+    // Members are taken in `ordered` order, so each bundle keeps the dependency order
+    let mut bundles: Vec<Vec<QueuedBranch>> = Vec::new();
+    for group in group_order {
+        let members: Vec<QueuedBranch> = ordered
+            .iter()
+            .zip(&groups)
+            .filter(|(_, g)| **g == group)
+            .map(|(branch, _)| branch.clone())
+            .collect();
+
+        match bundles.last_mut() {
+            Some(bundle) if bundle.len() + members.len() <= max_per_bundle => {
+                bundle.extend(members)
+            }
+            _ => bundles.push(members),
+        }
+    }
+
+    bundles
+}
+
+/// Cluster id per branch, joining branches that change a common file
+fn overlap_clusters(branches: &[QueuedBranch], files: &BranchFiles) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..branches.len()).collect();
+    join_overlapping(branches, files, &mut parent);
+    (0..branches.len()).map(|i| find(&mut parent, i)).collect()
+}
+
+/// Cluster id per branch, joining branches that share a file or a dependency
+fn linked_clusters(branches: &[QueuedBranch], files: &BranchFiles) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..branches.len()).collect();
+    join_overlapping(branches, files, &mut parent);
+
+    for (i, branch) in branches.iter().enumerate() {
+        for dep in &branch.depends_on {
+            if let Some(j) = branches.iter().position(|b| b.issue_number == *dep) {
+                union(&mut parent, i, j);
+            }
+        }
+    }
+    (0..branches.len()).map(|i| find(&mut parent, i)).collect()
+}
+
+fn join_overlapping(branches: &[QueuedBranch], files: &BranchFiles, parent: &mut [usize]) {
+    let mut owner: HashMap<&str, usize> = HashMap::new();
+    for (i, branch) in branches.iter().enumerate() {
+        let Some(changed) = files.get(&branch.branch_name) else {
+            continue;
+        };
+        for file in changed {
+            match owner.get(file.as_str()) {
+                Some(&j) => union(parent, i, j),
+                None => {
+                    owner.insert(file.as_str(), i);
+                }
+            }
+        }
+    }
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    if parent[i] != i {
+        parent[i] = find(parent, parent[i]);
+    }
+    parent[i]
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (root_a, root_b) = (find(parent, a), find(parent, b));
+    if root_a != root_b {
+        parent[root_a.max(root_b)] = root_a.min(root_b);
+    }
+}
@@ -0,0 +1,388 @@
+//! Protected paths that need human review
+//!
+//! Rules come from `[protected_paths]` and, when enabled, from CODEOWNERS. A
+//! branch touching a protected path is held out of auto-bundling, its issue is
+//! routed to humans and the owners are requested as reviewers.
+
+use crate::config::ProtectedPathsConfig;
+use crate::github::labels::HUMAN_ONLY_LABEL;
+use crate::github::GitHubClient;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Hidden marker on protected path comments, so one branch is only reported once
+pub const PROTECTED_MARKER: &str = "<!-- my-little-soda:protected-paths";
+
+/// Where CODEOWNERS is looked for when `codeowners_file` is unset, in GitHub's order
+const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+struct PathRule {
+    pattern: String,
+    matcher: Regex,
+    owners: Vec<String>,
+}
+
+impl PathRule {
+    fn new(pattern: &str, owners: Vec<String>) -> Result<Self> {
+        Ok(Self {
+            pattern: pattern.to_string(),
+            matcher: glob_to_regex(pattern)?,
+            owners,
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        self.matcher.is_match(path)
+    }
+}
+
+/// A changed file caught by a rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProtectedPath {
+    pub path: String,
+    /// Pattern of the first rule that matched
+    pub rule: String,
+    pub owners: Vec<String>,
+}
+
+/// The protected paths one branch touches
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtectedChange {
+    pub branch: String,
+    pub paths: Vec<ProtectedPath>,
+}
+
+impl ProtectedChange {
+    pub fn is_protected(&self) -> bool {
+        !self.paths.is_empty()
+    }
+
+    /// Every owner of a touched path, deduplicated
+    pub fn owners(&self) -> Vec<String> {
+        self.paths
+            .iter()
+            .flat_map(|p| p.owners.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Owners split into user logins and team slugs, as the reviewers API takes them
+    ///
+    /// Email owners cannot be requested and are only mentioned in the comment.
+    pub fn reviewers(&self) -> (Vec<String>, Vec<String>) {
+        let mut users = Vec::new();
+        let mut teams = Vec::new();
+        for owner in self.owners() {
+            let Some(name) = owner.strip_prefix('@') else {
+                continue;
+            };
+            match name.split_once('/') {
+                Some((_, team)) => teams.push(team.to_string()),
+                None => users.push(name.to_string()),
+            }
+        }
+        (users, teams)
+    }
+
+    pub fn format_text(&self) -> String {
+        let mut text = format!(
+            "🛡️  {} touches {} protected path(s):\n",
+            self.branch,
+            self.paths.len()
+        );
+        for path in &self.paths {
+            text.push_str(&format!("     {} (rule {})\n", path.path, path.rule));
+        }
+        text
+    }
+
+    /// Markdown comment listing the touched paths and their owners
+    pub fn issue_comment(&self) -> String {
+        let mut comment = format!(
+            "🛡️ **Protected paths need human review**\n\n`{}` changes paths an agent may not \
+            change without sign-off:\n\n| Path | Rule | Owners |\n|---|---|---|\n",
+            self.branch
+        );
+        for path in &self.paths {
+            let owners = if path.owners.is_empty() {
+                "-".to_string()
+            } else {
+                path.owners.join(" ")
+            };
+            comment.push_str(&format!(
+                "| `{}` | `{}` | {} |\n",
+                path.path, path.rule, owners
+            ));
+        }
+        comment.push_str(&format!(
+            "\nThe branch is held out of auto-bundling and the issue is labeled \
+            `{HUMAN_ONLY_LABEL}`. It goes up as its own PR"
+        ));
+        let owners = self.owners();
+        if owners.is_empty() {
+            comment.push_str(" for a maintainer to review.\n");
+        } else {
+            comment.push_str(&format!(
+                " with {} requested as reviewers.\n",
+                owners.join(", ")
+            ));
+        }
+        comment.push_str(&format!("\n{}\n", self.marker()));
+        comment
+    }
+
+    /// Body of the PR opened for the branch in place of bundling it
+    pub fn pr_body(&self, issue_number: u64) -> String {
+        let mut body = String::from(
+            "## Protected paths\n\nThis change touches paths that need human sign-off, so it \
+            was left out of the bundle:\n\n",
+        );
+        for path in &self.paths {
+            body.push_str(&format!("- `{}`\n", path.path));
+        }
+        body.push_str(&format!("\nFixes #{issue_number}\n"));
+        body
+    }
+
+    /// Marker identifying this branch in an issue comment
+    pub fn marker(&self) -> String {
+        format!("{PROTECTED_MARKER} {} -->", self.branch)
+    }
+}
+
+/// Rules deciding which paths need human review
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicy {
+    rules: Vec<PathRule>,
+    /// CODEOWNERS entries, where the last matching line wins
+    codeowners: Vec<PathRule>,
+}
+
+impl PathPolicy {
+    /// Policy from the configured rules, without CODEOWNERS
+    pub fn new(config: &ProtectedPathsConfig) -> Result<Self> {
+        if !config.enabled {
+            return Ok(Self::default());
+        }
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| PathRule::new(&rule.pattern, rule.owners.clone()))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            rules,
+            codeowners: Vec::new(),
+        })
+    }
+
+    /// Add the entries of a CODEOWNERS file
+    pub fn with_codeowners(mut self, contents: &str) -> Result<Self> {
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let rule = PathRule::new(pattern, fields.map(str::to_string).collect())
+                .map_err(|e| anyhow!("CODEOWNERS line {}: {}", index + 1, e))?;
+            self.codeowners.push(rule);
+        }
+        Ok(self)
+    }
+
+    /// Policy for the repository at `root`, reading CODEOWNERS when configured
+    pub fn for_repository(root: &Path, config: &ProtectedPathsConfig) -> Result<Self> {
+        let policy = Self::new(config)?;
+        if !config.enabled || !config.use_codeowners {
+            return Ok(policy);
+        }
+
+        let candidates: Vec<&str> = match &config.codeowners_file {
+            Some(file) => vec![file.as_str()],
+            None => CODEOWNERS_LOCATIONS.to_vec(),
+        };
+        for candidate in candidates {
+            let path = root.join(candidate);
+            if path.exists() {
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+                return policy.with_codeowners(&contents);
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Whether no path is protected at all
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.codeowners.is_empty()
+    }
+
+    /// Protected paths among the files a branch changes
+    pub fn check<'a>(
+        &self,
+        branch: &str,
+        files: impl IntoIterator<Item = &'a str>,
+    ) -> ProtectedChange {
+        let paths = files
+            .into_iter()
+            .filter_map(|file| self.protect(file))
+            .collect();
+        ProtectedChange {
+            branch: branch.to_string(),
+            paths,
+        }
+    }
+
+    fn protect(&self, path: &str) -> Option<ProtectedPath> {
+        let matching: Vec<&PathRule> = self.rules.iter().filter(|r| r.matches(path)).collect();
+        // A CODEOWNERS line without owners clears ownership, so only the last match counts
+        let owned = self
+            .codeowners
+            .iter()
+            .rev()
+            .find(|r| r.matches(path))
+            .filter(|r| !r.owners.is_empty());
+
+        let rule = matching.first().copied().or(owned)?;
+        let owners = matching
+            .iter()
+            .chain(owned.iter())
+            .flat_map(|r| r.owners.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        Some(ProtectedPath {
+            path: path.to_string(),
+            rule: rule.pattern.clone(),
+            owners,
+        })
+    }
+}
+
+/// Route the issue to humans and open a PR of its own with the owners as reviewers
+///
+/// Shared by bottle and bundle. The comment, label and PR are each added once,
+/// so holding the same branch again changes nothing.
+pub async fn hold_for_human_review(
+    client: &GitHubClient,
+    change: &ProtectedChange,
+    issue_number: u64,
+    description: &str,
+    base_branch: &str,
+) {
+    let marker = change.marker();
+    let already_reported = client
+        .comments
+        .get_issue_comments(issue_number)
+        .await
+        .is_ok_and(|comments| {
+            comments
+                .iter()
+                .any(|c| c.body.as_deref().is_some_and(|b| b.contains(&marker)))
+        });
+    if !already_reported {
+        if let Err(e) = client
+            .comments
+            .create_issue_comment(issue_number, &change.issue_comment())
+            .await
+        {
+            println!("⚠️  Failed to comment on issue #{issue_number}: {e}");
+        }
+    }
+    let labelled = client
+        .fetch_issue(issue_number)
+        .await
+        .is_ok_and(|issue| issue.labels.iter().any(|l| l.name == HUMAN_ONLY_LABEL));
+    if !labelled {
+        if let Err(e) = client
+            .add_label_to_issue(issue_number, HUMAN_ONLY_LABEL)
+            .await
+        {
+            println!("⚠️  Failed to label issue #{issue_number} {HUMAN_ONLY_LABEL}: {e}");
+        }
+    }
+
+    let already_open = client
+        .pulls
+        .fetch_open_pull_requests()
+        .await
+        .is_ok_and(|prs| prs.iter().any(|pr| pr.head.ref_field == change.branch));
+    if already_open {
+        return;
+    }
+
+    let pr = match client
+        .pulls
+        .create_pull_request(
+            &format!("[HUMAN REVIEW] {description}"),
+            &change.branch,
+            base_branch,
+            &change.pr_body(issue_number),
+        )
+        .await
+    {
+        Ok(pr) => pr,
+        Err(e) => {
+            println!("❌ Failed to create PR for {}: {}", change.branch, e);
+            return;
+        }
+    };
+
+    let (users, teams) = change.reviewers();
+    if users.is_empty() && teams.is_empty() {
+        return;
+    }
+    if let Err(e) = client
+        .pulls
+        .request_reviewers(pr.number, users, teams)
+        .await
+    {
+        println!("⚠️  Failed to request reviewers on PR #{}: {e}", pr.number);
+    }
+}
+
+/// Compile a gitignore-style pattern as CODEOWNERS uses them
+///
+/// A pattern without a slash except at the end matches at any depth, a
+/// trailing slash matches only directories, and a match on a directory covers
+/// everything under it.
+pub fn glob_to_regex(pattern: &str) -> Result<Regex> {
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let body = trimmed.trim_start_matches('/');
+    if body.is_empty() {
+        return Err(anyhow!("Empty path pattern '{}'", pattern));
+    }
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // A trailing slash names a directory, so the path must continue below it
+    regex.push_str(if pattern.ends_with('/') {
+        "/.*$"
+    } else {
+        "(?:/.*)?$"
+    });
+
+    Regex::new(&regex).map_err(|e| anyhow!("Invalid path pattern '{}': {}", pattern, e))
+}
@@ -0,0 +1,78 @@
+//! Durable bundle records
+//!
+//! Mirrors every bundle step into the `bundle_states` table so an interrupted
+//! bundle can be resumed and past bundles can be listed. Without the `database`
+//! feature, or when no database is configured, saving is a no-op.
+
+use super::types::{BundleHistoryEntry, BundleState, BundleStatus};
+use anyhow::Result;
+
+/// Save the bundle state if a database is available
+#[cfg(feature = "database")]
+pub async fn save_bundle(state: &BundleState, status: BundleStatus) -> Result<()> {
+    if let Some(db) = crate::database::database().await {
+        if let Some(manager) = db.read().await.as_ref() {
+            manager.save_bundle(state, status).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "database"))]
+pub async fn save_bundle(_state: &BundleState, _status: BundleStatus) -> Result<()> {
+    Ok(())
+}
+
+/// Most recent bundle that was interrupted before finishing
+#[cfg(feature = "database")]
+pub async fn load_interrupted_bundle() -> Result<Option<BundleHistoryEntry>> {
+    match crate::database::database().await {
+        Some(db) => match db.read().await.as_ref() {
+            Some(manager) => manager.get_interrupted_bundle().await,
+            None => Err(anyhow::anyhow!(
+                "No database configured; add a [database] section to my-little-soda.toml"
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "database"))]
+pub async fn load_interrupted_bundle() -> Result<Option<BundleHistoryEntry>> {
+    Err(anyhow::anyhow!(
+        "Resuming bundles requires my-little-soda built with the 'database' feature"
+    ))
+}
+
+/// Past and running bundles, newest first
+#[cfg(feature = "database")]
+pub async fn bundle_history(limit: u32) -> Result<Vec<BundleHistoryEntry>> {
+    match crate::database::database().await {
+        Some(db) => match db.read().await.as_ref() {
+            Some(manager) => manager.get_bundle_history(limit).await,
+            None => Err(anyhow::anyhow!(
+                "No database configured; add a [database] section to my-little-soda.toml"
+            )),
+        },
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(not(feature = "database"))]
+pub async fn bundle_history(_limit: u32) -> Result<Vec<BundleHistoryEntry>> {
+    Err(anyhow::anyhow!(
+        "Bundle history requires my-little-soda built with the 'database' feature"
+    ))
+}
+
+/// Recorded steps of one bundle, oldest first
+#[cfg(feature = "database")]
+pub async fn bundle_steps(bundle_id: &str) -> Result<Vec<crate::database::BundleStep>> {
+    match crate::database::database().await {
+        Some(db) => match db.read().await.as_ref() {
+            Some(manager) => manager.get_bundle_steps(bundle_id).await,
+            None => Ok(Vec::new()),
+        },
+        None => Ok(Vec::new()),
+    }
+}
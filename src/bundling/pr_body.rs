@@ -0,0 +1,320 @@
+//! Bundle PR descriptions
+//!
+//! Renders the markdown body of a bundle PR: a changelog per issue, a combined
+//! file table, the pre-flight conflict summary and a hidden manifest block.
+
+use super::git_ops::{
+    AssemblyMode, BranchChangelog, ConflictCompatibilityReport, FileChangeStat, HunkRange,
+};
+use super::types::{BundleManifest, BundleManifestIssue, BundleWindow};
+use crate::train_schedule::QueuedBranch;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+/// A bundled branch together with what it changes
+#[derive(Debug, Clone)]
+pub struct IssueChangelog {
+    pub branch: QueuedBranch,
+    pub changelog: BranchChangelog,
+}
+
+/// Everything shown in a bundle PR description
+#[derive(Debug, Clone)]
+pub struct BundlePrDescription {
+    pub owner: String,
+    pub repo: String,
+    pub bundle_branch: String,
+    pub base_branch: String,
+    pub assembly_mode: AssemblyMode,
+    pub window: BundleWindow,
+    pub created_at: DateTime<Utc>,
+    pub issues: Vec<IssueChangelog>,
+    pub skipped: Vec<QueuedBranch>,
+    /// Issues dropped from the bundle after review
+    pub dropped: Vec<QueuedBranch>,
+    pub conflict_report: Option<ConflictCompatibilityReport>,
+}
+
+impl BundlePrDescription {
+    /// Machine-readable summary embedded at the end of the body
+    pub fn manifest(&self) -> BundleManifest {
+        BundleManifest {
+            version: BundleManifest::VERSION,
+            bundle_branch: self.bundle_branch.clone(),
+            base_branch: self.base_branch.clone(),
+            assembly_mode: self.assembly_mode.clone(),
+            created_at: self.created_at,
+            issues: self
+                .issues
+                .iter()
+                .map(|issue| BundleManifestIssue {
+                    issue_number: issue.branch.issue_number,
+                    branch_name: issue.branch.branch_name.clone(),
+                    title: issue.branch.description.clone(),
+                    commits: issue.changelog.commits.clone(),
+                    files: issue
+                        .changelog
+                        .files
+                        .iter()
+                        .map(|f| f.path.clone())
+                        .collect(),
+                    insertions: issue.changelog.insertions(),
+                    deletions: issue.changelog.deletions(),
+                })
+                .collect(),
+            skipped: self.skipped.iter().map(unbundled_entry).collect(),
+            dropped: self.dropped.iter().map(unbundled_entry).collect(),
+            compatibility_score: self.conflict_report.as_ref().map(|r| r.compatibility_score),
+        }
+    }
+
+    /// Render the full PR body
+    pub fn render(&self) -> String {
+        let (files, insertions, deletions) = self.totals();
+
+        let mut body = format!(
+            "🚄 **Train Bundle - {} Departure**\n\n\
+            This bundle combines multiple completed agent tasks into a single PR for efficient review.\n\n\
+            **Bundle Window:** {} - {}\n\
+            **Issues Included:** {}\n\
+            **Assembly Mode:** {}\n\
+            **Changes:** {}\n\n\
+            ## Bundled Work\n\n",
+            self.window.start.format("%H:%M"),
+            self.window.start.format("%Y-%m-%d %H:%M"),
+            self.window.end.format("%H:%M"),
+            self.issues.len(),
+            self.assembly_mode.description(),
+            diffstat_summary(files, insertions, deletions)
+        );
+
+        for issue in &self.issues {
+            self.push_issue_section(&mut body, issue);
+        }
+
+        self.push_file_table(&mut body);
+        self.push_conflict_summary(&mut body);
+
+        if !self.skipped.is_empty() {
+            body.push_str("## Skipped Due to Conflicts\n\n");
+            for branch in &self.skipped {
+                body.push_str(&format!(
+                    "- **Issue #{}**: {} (`{}`) - stays queued for a later train\n",
+                    branch.issue_number, branch.description, branch.branch_name
+                ));
+            }
+            body.push('\n');
+        }
+
+        if !self.dropped.is_empty() {
+            body.push_str("## Dropped After Review\n\n");
+            for branch in &self.dropped {
+                body.push_str(&format!(
+                    "- **Issue #{}**: {} (`{}`) - returned to the queue with review feedback\n",
+                    branch.issue_number, branch.description, branch.branch_name
+                ));
+            }
+            body.push('\n');
+        }
+
+        body.push_str(&format!(
+            "## Review Notes\n\n\
+            - ✅ All branches have been automatically applied and tested\n\
+            - 🔍 Each issue should be reviewed individually for code quality\n\
+            - 🚀 Merge this PR to close all {} included issues\n\n\
+            ---\n\
+            🤖 Generated by Clambake bundling system\n",
+            self.issues.len()
+        ));
+
+        match self.manifest().to_hidden_block() {
+            Ok(block) => {
+                body.push('\n');
+                body.push_str(&block);
+                body.push('\n');
+            }
+            Err(e) => println!("⚠️  Failed to serialize bundle manifest: {e}"),
+        }
+
+        body
+    }
+
+    fn push_issue_section(&self, body: &mut String, issue: &IssueChangelog) {
+        let branch = &issue.branch;
+        let changelog = &issue.changelog;
+
+        body.push_str(&format!(
+            "### #{}: {}\n\n\
+            [View Issue](https://github.com/{}/{}/issues/{}) · Branch: `{}`\n\n\
+            Closes #{}\n\n",
+            branch.issue_number,
+            branch.description,
+            self.owner,
+            self.repo,
+            branch.issue_number,
+            branch.branch_name,
+            branch.issue_number
+        ));
+
+        if !changelog.commits.is_empty() {
+            body.push_str("**Commits:**\n");
+            for commit in &changelog.commits {
+                match commit.split_once(' ') {
+                    Some((sha, summary)) => body.push_str(&format!("- `{sha}` {summary}\n")),
+                    None => body.push_str(&format!("- `{commit}`\n")),
+                }
+            }
+            body.push('\n');
+        }
+
+        body.push_str(&format!(
+            "**Diffstat:** {}\n",
+            diffstat_summary(
+                changelog.files.len(),
+                changelog.insertions(),
+                changelog.deletions()
+            )
+        ));
+        for file in &changelog.files {
+            body.push_str(&format!("- `{}` {}\n", file.path, line_counts(file)));
+        }
+        body.push('\n');
+    }
+
+    fn push_file_table(&self, body: &mut String) {
+        // path -> (issues, insertions, deletions, binary)
+        let mut files: BTreeMap<&str, (Vec<u64>, usize, usize, bool)> = BTreeMap::new();
+        for issue in &self.issues {
+            for file in &issue.changelog.files {
+                let entry = files.entry(file.path.as_str()).or_default();
+                entry.0.push(issue.branch.issue_number);
+                entry.1 += file.insertions;
+                entry.2 += file.deletions;
+                entry.3 |= file.binary;
+            }
+        }
+
+        if files.is_empty() {
+            return;
+        }
+
+        body.push_str("## File Changes\n\n| File | Issues | + | - |\n|------|--------|---|---|\n");
+        for (path, (issues, insertions, deletions, binary)) in files {
+            let issues = issues
+                .iter()
+                .map(|n| format!("#{n}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let (added, removed) = if binary {
+                ("bin".to_string(), "bin".to_string())
+            } else {
+                (insertions.to_string(), deletions.to_string())
+            };
+            body.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                path.replace('|', "\\|"),
+                issues,
+                added,
+                removed
+            ));
+        }
+        body.push('\n');
+    }
+
+    fn push_conflict_summary(&self, body: &mut String) {
+        let Some(report) = &self.conflict_report else {
+            return;
+        };
+
+        body.push_str(&format!(
+            "## Conflict Analysis\n\n\
+            **Compatibility Score:** {:.1}%\n\
+            **Analyzed Branches:** {}\n\n",
+            report.compatibility_score,
+            report.analyzed_branches.len()
+        ));
+
+        if report.conflicting_hunks.is_empty() {
+            body.push_str("No overlapping changes were detected between bundled branches.\n\n");
+        } else {
+            body.push_str("**Overlapping Hunks:**\n");
+            for conflict in &report.conflicting_hunks {
+                let ranges = |hunks: &[HunkRange]| {
+                    hunks
+                        .iter()
+                        .map(|h| h.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                body.push_str(&format!(
+                    "- `{}`: `{}` [{}] vs `{}` [{}]{}\n",
+                    conflict.file,
+                    conflict.first_branch,
+                    ranges(&conflict.first_hunks),
+                    conflict.second_branch,
+                    ranges(&conflict.second_hunks),
+                    if conflict.confirmed_by_trial_merge {
+                        " (confirmed by trial merge)"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+            body.push('\n');
+        }
+
+        if !report.analysis_errors.is_empty() {
+            body.push_str("**Analysis Errors:**\n");
+            for error in &report.analysis_errors {
+                body.push_str(&format!("- {error}\n"));
+            }
+            body.push('\n');
+        }
+    }
+
+    /// Distinct files touched across the bundle, plus total insertions and deletions
+    fn totals(&self) -> (usize, usize, usize) {
+        let mut paths: Vec<&str> = self
+            .issues
+            .iter()
+            .flat_map(|i| i.changelog.files.iter().map(|f| f.path.as_str()))
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+
+        let insertions = self.issues.iter().map(|i| i.changelog.insertions()).sum();
+        let deletions = self.issues.iter().map(|i| i.changelog.deletions()).sum();
+        (paths.len(), insertions, deletions)
+    }
+}
+
+/// Manifest entry for a branch whose changes aren't part of the bundle
+fn unbundled_entry(branch: &QueuedBranch) -> BundleManifestIssue {
+    BundleManifestIssue {
+        issue_number: branch.issue_number,
+        branch_name: branch.branch_name.clone(),
+        title: branch.description.clone(),
+        commits: Vec::new(),
+        files: Vec::new(),
+        insertions: 0,
+        deletions: 0,
+    }
+}
+
+fn diffstat_summary(files: usize, insertions: usize, deletions: usize) -> String {
+    format!(
+        "{} file{} changed, +{} -{}",
+        files,
+        if files == 1 { "" } else { "s" },
+        insertions,
+        deletions
+    )
+}
+
+fn line_counts(file: &FileChangeStat) -> String {
+    if file.binary {
+        "(binary)".to_string()
+    } else {
+        format!("+{} -{}", file.insertions, file.deletions)
+    }
+}
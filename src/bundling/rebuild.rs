@@ -0,0 +1,179 @@
+//! Rebuilding an open bundle without one of its issues
+//!
+//! When review turns up a problem with a single issue, `bundle --rebuild <pr>
+//! --drop <issue>` reassembles the bundle from its manifest without that issue's
+//! commits. The review feedback about the dropped issue is carried over to the
+//! issue itself so whoever picks it up again knows why it was sent back.
+
+use super::types::{BundleManifest, BundleManifestIssue};
+use crate::agents::integrator::is_agent_label;
+use crate::train_schedule::QueuedBranch;
+use anyhow::{anyhow, Result};
+
+/// What a rebuild keeps and what it drops, taken from the bundle manifest
+#[derive(Debug, Clone)]
+pub struct RebuildPlan {
+    pub dropped: BundleManifestIssue,
+    /// Bundled issues that stay, in their original order
+    pub remaining: Vec<QueuedBranch>,
+    pub skipped: Vec<QueuedBranch>,
+    /// Everything dropped so far, including this rebuild's issue
+    pub all_dropped: Vec<QueuedBranch>,
+}
+
+impl RebuildPlan {
+    /// Split the bundle's issues around the one being dropped
+    pub fn new(manifest: &BundleManifest, drop_issue: u64) -> Result<Self> {
+        let Some(dropped) = manifest
+            .issues
+            .iter()
+            .find(|issue| issue.issue_number == drop_issue)
+            .cloned()
+        else {
+            let reason = if manifest
+                .dropped
+                .iter()
+                .any(|i| i.issue_number == drop_issue)
+            {
+                "was already dropped from"
+            } else if manifest
+                .skipped
+                .iter()
+                .any(|i| i.issue_number == drop_issue)
+            {
+                "was skipped by"
+            } else {
+                "is not part of"
+            };
+            return Err(anyhow!(
+                "Issue #{} {} bundle {}",
+                drop_issue,
+                reason,
+                manifest.bundle_branch
+            ));
+        };
+
+        let remaining: Vec<QueuedBranch> = manifest
+            .issues
+            .iter()
+            .filter(|issue| issue.issue_number != drop_issue)
+            .map(BundleManifestIssue::to_queued_branch)
+            .collect();
+        if remaining.is_empty() {
+            return Err(anyhow!(
+                "Issue #{} is the only issue in bundle {}; close the PR instead",
+                drop_issue,
+                manifest.bundle_branch
+            ));
+        }
+
+        let mut all_dropped: Vec<QueuedBranch> = manifest
+            .dropped
+            .iter()
+            .map(BundleManifestIssue::to_queued_branch)
+            .collect();
+        all_dropped.push(dropped.to_queued_branch());
+
+        Ok(Self {
+            remaining,
+            skipped: manifest
+                .skipped
+                .iter()
+                .map(BundleManifestIssue::to_queued_branch)
+                .collect(),
+            all_dropped,
+            dropped,
+        })
+    }
+}
+
+/// A comment left on the bundle PR
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewerComment {
+    pub author: String,
+    pub body: String,
+    /// File an inline review comment is attached to
+    pub path: Option<String>,
+    pub url: String,
+}
+
+/// Comments about one issue: inline comments on files it changed, and
+/// conversation comments that mention it by number
+pub fn feedback_for_issue(
+    issue: &BundleManifestIssue,
+    comments: &[ReviewerComment],
+) -> Vec<ReviewerComment> {
+    comments
+        .iter()
+        .filter(|comment| match &comment.path {
+            Some(path) => issue.files.contains(path),
+            None => mentions_issue(&comment.body, issue.issue_number),
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether `text` references `#issue_number` (and not e.g. `#123` for `#12`)
+pub fn mentions_issue(text: &str, issue_number: u64) -> bool {
+    let needle = format!("#{issue_number}");
+    text.match_indices(&needle).any(|(idx, _)| {
+        let before = text[..idx].chars().next_back();
+        let after = text[idx + needle.len()..].chars().next();
+        !before.is_some_and(|c| c.is_alphanumeric() || c == '/')
+            && !after.is_some_and(|c| c.is_ascii_digit())
+    })
+}
+
+/// Labels to take off an issue that goes back to `route:ready`
+///
+/// The agent label is removed too, otherwise routing treats the issue as still
+/// assigned and never hands it out again.
+pub fn labels_to_remove_on_drop(labels: &[String]) -> Vec<String> {
+    labels
+        .iter()
+        .filter(|label| label.as_str() == "route:review" || is_agent_label(label))
+        .cloned()
+        .collect()
+}
+
+/// Where a dropped issue's branch is moved to
+///
+/// Outside the `agent*` namespace the train schedule never queues it again, while
+/// the rejected commits stay around for whoever picks the issue up next.
+pub fn dropped_branch_name(branch_name: &str) -> String {
+    format!("dropped/{branch_name}")
+}
+
+/// Comment posted on the dropped issue
+pub fn drop_comment(pr_number: u64, feedback: &[ReviewerComment]) -> String {
+    let mut comment = format!(
+        "🔙 **Dropped from bundle PR #{pr_number}**\n\n\
+        This issue was removed from the bundle during review and is back in the \
+        queue (`route:ready`). The rest of the bundle was rebuilt without it.\n\n"
+    );
+
+    if feedback.is_empty() {
+        comment.push_str(
+            "No review comments referenced this issue directly; see the bundle PR for context.\n",
+        );
+        return comment;
+    }
+
+    comment.push_str("## Review Feedback\n\n");
+    for item in feedback {
+        let location = item
+            .path
+            .as_ref()
+            .map(|path| format!(" on `{path}`"))
+            .unwrap_or_default();
+        comment.push_str(&format!(
+            "**@{}**{} ([link]({})):\n\n",
+            item.author, location, item.url
+        ));
+        for line in item.body.lines() {
+            comment.push_str(&format!("> {line}\n"));
+        }
+        comment.push('\n');
+    }
+    comment
+}
@@ -0,0 +1,96 @@
+//! Bringing queued branches up to date with the base before bundling
+//!
+//! Agent branches can sit in the queue for hours while the base moves on.
+//! Before a bundle is assembled every queued branch that is behind the base is
+//! rebased onto it; branches whose rebase conflicts are left out of the bundle
+//! and flagged on their issue instead.
+
+use super::unblocker::rebase_instructions;
+use crate::train_schedule::QueuedBranch;
+
+/// Label flagging an issue whose branch can't be rebased without help
+pub const NEEDS_REBASE_LABEL: &str = "needs-rebase";
+
+/// What the pre-bundle refresh did with one queued branch
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshOutcome {
+    UpToDate,
+    /// Rebased onto the base; `pushed` is false for branches never pushed
+    Rebased {
+        behind: u32,
+        pushed: bool,
+    },
+    /// Would have been rebased (dry run)
+    WouldRebase {
+        behind: u32,
+    },
+    /// The rebase conflicted, the branch is left out of this bundle
+    NeedsResolution {
+        behind: u32,
+        conflicted_files: Vec<String>,
+    },
+    /// Couldn't be refreshed; bundled against its current base as before
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchRefresh {
+    pub branch: QueuedBranch,
+    pub outcome: RefreshOutcome,
+}
+
+impl BranchRefresh {
+    /// Whether the branch should still go into the bundle
+    pub fn is_bundleable(&self) -> bool {
+        !matches!(self.outcome, RefreshOutcome::NeedsResolution { .. })
+    }
+
+    /// One-line summary for the bundle output
+    pub fn summary(&self) -> String {
+        let name = &self.branch.branch_name;
+        match &self.outcome {
+            RefreshOutcome::UpToDate => format!("✅ {name} is up to date"),
+            RefreshOutcome::Rebased { behind, pushed } => format!(
+                "🔄 {name} rebased over {behind} new commit(s){}",
+                if *pushed { " and force-pushed" } else { "" }
+            ),
+            RefreshOutcome::WouldRebase { behind } => {
+                format!("🔧 {name} is {behind} commit(s) behind, would be rebased")
+            }
+            RefreshOutcome::NeedsResolution {
+                conflicted_files, ..
+            } => format!(
+                "⚠️  {name} needs manual rebase (conflicts in {}), left out of the bundle",
+                conflicted_files.join(", ")
+            ),
+            RefreshOutcome::Failed(error) => {
+                format!("⚠️  {name} could not be refreshed ({error}), bundling as is")
+            }
+        }
+    }
+}
+
+/// Comment left on an issue whose branch needs a manual rebase
+pub fn needs_rebase_comment(
+    branch: &QueuedBranch,
+    base_branch: &str,
+    behind: u32,
+    conflicted_files: &[String],
+) -> String {
+    let mut comment = format!(
+        "🔀 **Manual rebase needed**\n\n\
+        `{}` is {} commit(s) behind `{}` and no longer rebases cleanly, so it was \
+        left out of the bundle. Conflicting files:\n\n",
+        branch.branch_name, behind, base_branch
+    );
+    for file in conflicted_files {
+        comment.push_str(&format!("- `{file}`\n"));
+    }
+    comment.push_str(&format!(
+        "\nResolve the conflicts and push:\n\n```sh\n{}```\n\n\
+        The `{NEEDS_REBASE_LABEL}` label is removed automatically once the branch is \
+        up to date.\n",
+        rebase_instructions(&branch.branch_name, base_branch)
+    ));
+    comment
+}
@@ -0,0 +1,304 @@
+//! Diff size guardrails for agent branches and bundles
+//!
+//! Work that goes over the `[diff_limits]` is refused with a breakdown of where
+//! the changes are, and a split plan grouping the files by directory so each
+//! group can become its own follow-up issue.
+
+use super::git_ops::{BranchChangelog, FileChangeStat};
+use crate::config::DiffLimits;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Label for follow-up issues carved out of an oversized change
+pub const SPLIT_LABEL: &str = "supertask-decomposition";
+
+/// Deepest directory level the split plan descends to
+const MAX_SPLIT_DEPTH: usize = 4;
+
+/// Name used for files at the top of the repository
+const ROOT_GROUP: &str = "(root)";
+
+/// Hidden marker on size comments, so one change is only reported once
+pub const SIZE_MARKER: &str = "<!-- my-little-soda:oversized";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LimitViolation {
+    pub limit: String,
+    pub actual: usize,
+    pub max: usize,
+}
+
+/// Changes under one directory
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DirectoryStat {
+    pub directory: String,
+    pub files: usize,
+    pub lines: usize,
+    pub binary_files: usize,
+}
+
+/// A group of files that could be reviewed on its own
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SplitGroup {
+    pub directory: String,
+    pub files: Vec<String>,
+    pub lines: usize,
+}
+
+impl SplitGroup {
+    pub fn issue_title(&self, parent_issue: Option<u64>) -> String {
+        match parent_issue {
+            Some(issue) => format!("Split of #{issue}: changes in {}", self.directory),
+            None => format!("Split: changes in {}", self.directory),
+        }
+    }
+
+    pub fn issue_body(&self, parent_issue: Option<u64>, source: &str) -> String {
+        let mut body = format!(
+            "Part of an oversized change on `{source}`, split out so it can be reviewed on \
+            its own.\n\n**{} file(s), {} changed line(s) in `{}`:**\n\n",
+            self.files.len(),
+            self.lines,
+            self.directory
+        );
+        for file in &self.files {
+            body.push_str(&format!("- `{file}`\n"));
+        }
+        if let Some(issue) = parent_issue {
+            body.push_str(&format!("\nRelated to #{issue}\n"));
+        }
+        body
+    }
+}
+
+/// Size of one branch or bundle measured against its limits
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeReport {
+    /// What was measured, e.g. a branch name or "bundle"
+    pub scope: String,
+    pub files: Vec<FileChangeStat>,
+    pub violations: Vec<LimitViolation>,
+}
+
+impl SizeReport {
+    pub fn check(scope: &str, files: Vec<FileChangeStat>, limits: &DiffLimits) -> Self {
+        let mut report = Self {
+            scope: scope.to_string(),
+            files,
+            violations: Vec::new(),
+        };
+        for (limit, actual, max) in [
+            (
+                "changed lines",
+                report.changed_lines(),
+                limits.max_changed_lines,
+            ),
+            (
+                "changed files",
+                report.files.len(),
+                limits.max_changed_files,
+            ),
+            (
+                "binary files",
+                report.binary_files(),
+                limits.max_binary_files,
+            ),
+        ] {
+            if max > 0 && actual > max {
+                report.violations.push(LimitViolation {
+                    limit: limit.to_string(),
+                    actual,
+                    max,
+                });
+            }
+        }
+        report
+    }
+
+    pub fn is_oversized(&self) -> bool {
+        !self.violations.is_empty()
+    }
+
+    pub fn changed_lines(&self) -> usize {
+        self.files.iter().map(changed_lines).sum()
+    }
+
+    pub fn binary_files(&self) -> usize {
+        self.files.iter().filter(|f| f.binary).count()
+    }
+
+    /// Changes per top-level directory, largest first
+    pub fn by_directory(&self) -> Vec<DirectoryStat> {
+        let mut stats: BTreeMap<String, DirectoryStat> = BTreeMap::new();
+        for file in &self.files {
+            let directory = directory_at(&file.path, 1);
+            let stat = stats.entry(directory.clone()).or_insert(DirectoryStat {
+                directory,
+                files: 0,
+                lines: 0,
+                binary_files: 0,
+            });
+            stat.files += 1;
+            stat.lines += changed_lines(file);
+            stat.binary_files += usize::from(file.binary);
+        }
+        let mut stats: Vec<DirectoryStat> = stats.into_values().collect();
+        stats.sort_by(|a, b| b.lines.cmp(&a.lines).then(b.files.cmp(&a.files)));
+        stats
+    }
+
+    /// Groups of files by directory, each within `limits` where the tree allows
+    ///
+    /// A directory that is still too big is split into its subdirectories, down
+    /// to `MAX_SPLIT_DEPTH` levels.
+    pub fn split_plan(&self, limits: &DiffLimits) -> Vec<SplitGroup> {
+        let files: Vec<&FileChangeStat> = self.files.iter().collect();
+        let mut plan = Vec::new();
+        split(&files, 1, limits, &mut plan);
+        plan.sort_by(|a, b| a.directory.cmp(&b.directory));
+        plan
+    }
+
+    pub fn format_text(&self, limits: &DiffLimits) -> String {
+        let mut text = format!(
+            "📏 {}: {} file(s), {} changed line(s), {} binary\n",
+            self.scope,
+            self.files.len(),
+            self.changed_lines(),
+            self.binary_files()
+        );
+        if !self.is_oversized() {
+            return text;
+        }
+        for violation in &self.violations {
+            text.push_str(&format!(
+                "   ❌ {} {} over the limit of {}\n",
+                violation.actual, violation.limit, violation.max
+            ));
+        }
+        text.push_str("   By directory:\n");
+        for stat in self.by_directory() {
+            text.push_str(&format!(
+                "     {:<30} {:>4} file(s) {:>6} line(s)\n",
+                stat.directory, stat.files, stat.lines
+            ));
+        }
+        text.push_str("   Suggested split:\n");
+        for (index, group) in self.split_plan(limits).iter().enumerate() {
+            text.push_str(&format!(
+                "     {}. {} ({} file(s), {} line(s))\n",
+                index + 1,
+                group.directory,
+                group.files.len(),
+                group.lines
+            ));
+        }
+        text
+    }
+
+    /// Markdown comment explaining the refusal and the suggested split
+    pub fn issue_comment(&self, limits: &DiffLimits) -> String {
+        let mut comment = format!(
+            "📏 **Change too large to review**\n\n`{}` changes {} file(s) and {} line(s) \
+            ({} binary).\n\n",
+            self.scope,
+            self.files.len(),
+            self.changed_lines(),
+            self.binary_files()
+        );
+        for violation in &self.violations {
+            comment.push_str(&format!(
+                "- {} {} (limit {})\n",
+                violation.actual, violation.limit, violation.max
+            ));
+        }
+        comment.push_str("\n| Directory | Files | Lines |\n|---|---|---|\n");
+        for stat in self.by_directory() {
+            comment.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                stat.directory, stat.files, stat.lines
+            ));
+        }
+        comment.push_str(&format!(
+            "\n**Suggested split** (each could become a `{SPLIT_LABEL}` issue):\n\n"
+        ));
+        for (index, group) in self.split_plan(limits).iter().enumerate() {
+            comment.push_str(&format!(
+                "{}. `{}`: {} file(s), {} line(s)\n",
+                index + 1,
+                group.directory,
+                group.files.len(),
+                group.lines
+            ));
+        }
+        comment.push_str(&format!("\n{}\n", self.marker()));
+        comment
+    }
+
+    /// Marker identifying this change in an issue comment
+    pub fn marker(&self) -> String {
+        format!("{SIZE_MARKER} {} -->", self.scope)
+    }
+}
+
+/// All files a set of branches change, with the stats of a file changed twice summed
+pub fn combined_files<'a>(
+    changelogs: impl IntoIterator<Item = &'a BranchChangelog>,
+) -> Vec<FileChangeStat> {
+    let mut files: BTreeMap<String, FileChangeStat> = BTreeMap::new();
+    for file in changelogs.into_iter().flat_map(|c| &c.files) {
+        files
+            .entry(file.path.clone())
+            .and_modify(|existing| {
+                existing.insertions += file.insertions;
+                existing.deletions += file.deletions;
+                existing.binary |= file.binary;
+            })
+            .or_insert_with(|| file.clone());
+    }
+    files.into_values().collect()
+}
+
+fn split(files: &[&FileChangeStat], depth: usize, limits: &DiffLimits, plan: &mut Vec<SplitGroup>) {
+    let mut groups: BTreeMap<String, Vec<&FileChangeStat>> = BTreeMap::new();
+    for file in files {
+        groups
+            .entry(directory_at(&file.path, depth))
+            .or_default()
+            .push(file);
+    }
+
+    for (directory, files) in groups {
+        let lines: usize = files.iter().map(|f| changed_lines(f)).sum();
+        let too_big = (limits.max_changed_lines > 0 && lines > limits.max_changed_lines)
+            || (limits.max_changed_files > 0 && files.len() > limits.max_changed_files);
+        // Only descend when that actually separates the files
+        let deeper = files
+            .iter()
+            .map(|f| directory_at(&f.path, depth + 1))
+            .collect::<std::collections::BTreeSet<_>>();
+        if too_big && depth < MAX_SPLIT_DEPTH && deeper.len() > 1 {
+            split(&files, depth + 1, limits, plan);
+        } else {
+            plan.push(SplitGroup {
+                directory,
+                files: files.iter().map(|f| f.path.clone()).collect(),
+                lines,
+            });
+        }
+    }
+}
+
+fn changed_lines(file: &FileChangeStat) -> usize {
+    file.insertions + file.deletions
+}
+
+/// The first `depth` directories of `path`
+fn directory_at(path: &str, depth: usize) -> String {
+    let parts: Vec<&str> = path.split('/').collect();
+    let directories = &parts[..parts.len() - 1];
+    if directories.is_empty() {
+        return ROOT_GROUP.to_string();
+    }
+    directories[..depth.min(directories.len())].join("/")
+}
@@ -1,3 +1,5 @@
+use super::git_ops::{AssemblyMode, ConflictCompatibilityReport, ConflictStrategy};
+use crate::train_schedule::QueuedBranch;
 use chrono::{DateTime, Local, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -53,6 +55,18 @@ pub enum BundleResult {
         pr_number: u64,
         bundle_branch: String,
     },
+    /// Bundle PR created without the branches that conflicted
+    PartialSuccess {
+        pr_number: u64,
+        bundle_branch: String,
+        skipped_branches: Vec<String>,
+    },
+    /// Bundling paused on a conflict that has to be resolved by hand
+    ManualResolutionRequired {
+        bundle_branch: String,
+        conflicted_branch: String,
+        conflicted_files: Vec<String>,
+    },
     /// Conflicts detected, fell back to individual PRs
     ConflictFallback {
         individual_prs: HashMap<String, u64>, // branch_name -> pr_number
@@ -144,12 +158,170 @@ pub enum BundleOperationStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BundleState {
     pub bundle_branch: String,
+    pub base_branch: String,
+    pub queued_branches: Vec<QueuedBranch>,
     pub target_branches: Vec<String>,
     pub completed_branches: Vec<String>,
     pub failed_branches: Vec<String>,
     pub current_operation: Option<String>,
     pub audit_trail: Vec<BundleAuditEntry>,
     pub recovery_data: Option<RecoveryData>,
+    pub conflict_strategy: ConflictStrategy,
+    pub assembly_mode: AssemblyMode,
+    pub pending_conflict: Option<PendingConflict>,
+    /// Pre-flight analysis, kept for the bundle PR description
+    #[serde(default)]
+    pub conflict_report: Option<ConflictCompatibilityReport>,
+    /// Whether the bundle branch has been pushed
+    #[serde(default)]
+    pub pushed: bool,
+    /// Bundle PR, once created
+    #[serde(default)]
+    pub pr_number: Option<u64>,
+    /// Whether bundled issues have been labeled for review
+    #[serde(default)]
+    pub labels_applied: bool,
+}
+
+impl BundleState {
+    /// Take a branch out of the bundle run altogether
+    ///
+    /// Used for a branch handed to an unblocker, so falling back to individual
+    /// PRs doesn't open one for it.
+    pub fn drop_branch(&mut self, branch_name: &str) {
+        self.queued_branches
+            .retain(|b| b.branch_name != branch_name);
+        self.target_branches.retain(|name| name != branch_name);
+    }
+}
+
+/// Lifecycle of a persisted bundle run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleStatus {
+    /// Assembling, pushing or opening the PR; resumable if the process died
+    InProgress,
+    /// Waiting for manual conflict resolution
+    Paused,
+    Completed,
+    Failed,
+    /// Abandoned in favour of individual PRs
+    FellBack,
+    Aborted,
+}
+
+impl BundleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BundleStatus::InProgress => "in_progress",
+            BundleStatus::Paused => "paused",
+            BundleStatus::Completed => "completed",
+            BundleStatus::Failed => "failed",
+            BundleStatus::FellBack => "fell_back",
+            BundleStatus::Aborted => "aborted",
+        }
+    }
+
+    #[cfg(feature = "database")]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "in_progress" => Some(BundleStatus::InProgress),
+            "paused" => Some(BundleStatus::Paused),
+            "completed" => Some(BundleStatus::Completed),
+            "failed" => Some(BundleStatus::Failed),
+            "fell_back" => Some(BundleStatus::FellBack),
+            "aborted" => Some(BundleStatus::Aborted),
+            _ => None,
+        }
+    }
+}
+
+/// A past or running bundle as recorded in the database
+#[derive(Debug, Clone)]
+pub struct BundleHistoryEntry {
+    pub bundle_id: String,
+    pub status: BundleStatus,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Full state snapshot; `None` if it couldn't be parsed
+    pub state: Option<BundleState>,
+}
+
+/// Marker opening the hidden JSON block in bundle PR descriptions
+pub const BUNDLE_MANIFEST_MARKER: &str = "<!-- my-little-soda:bundle-manifest";
+
+/// Machine-readable description of a bundle, embedded in its PR body
+///
+/// Tooling that needs to know what a bundle PR contains (unbundling, metrics,
+/// cleanup) should read this instead of parsing the rendered markdown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub bundle_branch: String,
+    pub base_branch: String,
+    pub assembly_mode: AssemblyMode,
+    pub created_at: DateTime<Utc>,
+    pub issues: Vec<BundleManifestIssue>,
+    pub skipped: Vec<BundleManifestIssue>,
+    /// Issues removed from the bundle after review by `bundle --rebuild --drop`
+    #[serde(default)]
+    pub dropped: Vec<BundleManifestIssue>,
+    pub compatibility_score: Option<f64>,
+}
+
+/// One issue in a bundle manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifestIssue {
+    pub issue_number: u64,
+    pub branch_name: String,
+    pub title: String,
+    #[serde(default)]
+    pub commits: Vec<String>,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub insertions: usize,
+    #[serde(default)]
+    pub deletions: usize,
+}
+
+impl BundleManifest {
+    pub const VERSION: u32 = 1;
+
+    /// Render as an HTML comment that GitHub hides from the PR description
+    pub fn to_hidden_block(&self) -> Result<String, serde_json::Error> {
+        // `>` only appears inside JSON strings, so escaping it keeps a `-->` in an
+        // issue title from closing the comment early
+        let json = serde_json::to_string_pretty(self)?.replace('>', "\\u003e");
+        Ok(format!("{BUNDLE_MANIFEST_MARKER}\n{json}\n-->"))
+    }
+
+    /// Extract the manifest from a bundle PR body, if it has one
+    pub fn from_pr_body(body: &str) -> Option<Self> {
+        let start = body.find(BUNDLE_MANIFEST_MARKER)? + BUNDLE_MANIFEST_MARKER.len();
+        let end = start + body[start..].find("-->")?;
+        serde_json::from_str(body[start..end].trim()).ok()
+    }
+}
+
+impl BundleManifestIssue {
+    /// The queued branch this entry was bundled from
+    pub fn to_queued_branch(&self) -> QueuedBranch {
+        QueuedBranch {
+            branch_name: self.branch_name.clone(),
+            issue_number: self.issue_number,
+            description: self.title.clone(),
+            depends_on: Vec::new(),
+        }
+    }
+}
+
+/// Cherry-pick paused on a conflict, waiting for `bundle --continue` or `--abort`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingConflict {
+    pub branch_name: String,
+    pub commit: String,
+    pub conflicted_files: Vec<String>,
 }
 
 /// Recovery data for resuming failed operations
@@ -0,0 +1,212 @@
+//! Turning bundle conflicts into routable unblocker tasks
+//!
+//! When a queued branch can't be applied onto a bundle, a `route:unblocker`
+//! issue is opened describing the conflict: the files involved, the branches
+//! on both sides, the base commit and commands to reproduce it. The conflicted
+//! issue is labelled `merge-conflict`, which keeps its branch out of the bundle
+//! queue until the unblocker's resolution lands and the issue is re-queued.
+
+use super::ordering::BranchFiles;
+use crate::train_schedule::QueuedBranch;
+use serde::{Deserialize, Serialize};
+
+/// Label that routes the unblocker issue ahead of regular work
+pub const UNBLOCKER_LABEL: &str = "route:unblocker";
+
+/// Label keeping a conflicted issue's branch out of the bundle queue
+pub const BLOCKED_LABEL: &str = "merge-conflict";
+
+pub const UNBLOCKER_MARKER: &str = "<!-- my-little-soda:unblocker";
+
+/// A conflict hit while assembling a bundle, embedded in the unblocker issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnblockerTask {
+    /// Branch that could not be applied
+    pub conflicted_branch: QueuedBranch,
+    /// Already-bundled branches touching the same files
+    pub conflicting_branches: Vec<QueuedBranch>,
+    pub conflicted_files: Vec<String>,
+    /// Commit whose cherry-pick conflicted, if known
+    pub commit: Option<String>,
+    pub bundle_branch: String,
+    pub base_branch: String,
+    pub base_sha: String,
+}
+
+impl UnblockerTask {
+    /// Every issue involved in the conflict, conflicted one first
+    pub fn original_issues(&self) -> Vec<u64> {
+        let mut issues = vec![self.conflicted_branch.issue_number];
+        for branch in &self.conflicting_branches {
+            if !issues.contains(&branch.issue_number) {
+                issues.push(branch.issue_number);
+            }
+        }
+        issues
+    }
+
+    pub fn title(&self) -> String {
+        format!(
+            "Resolve bundle conflict: {} (#{})",
+            self.conflicted_branch.branch_name, self.conflicted_branch.issue_number
+        )
+    }
+
+    /// Body of the `route:unblocker` issue
+    pub fn issue_body(&self) -> String {
+        let mut body = format!(
+            "## 🚧 Bundle Conflict\n\n\
+            `{}` (#{}) could not be applied onto bundle `{}`.\n\n\
+            - **Base**: `{}` at `{}`\n",
+            self.conflicted_branch.branch_name,
+            self.conflicted_branch.issue_number,
+            self.bundle_branch,
+            self.base_branch,
+            self.base_sha
+        );
+        if let Some(commit) = &self.commit {
+            body.push_str(&format!("- **Conflicting commit**: `{commit}`\n"));
+        }
+        if self.conflicting_branches.is_empty() {
+            body.push_str("- **Conflicts with**: the bundle base\n");
+        } else {
+            let others: Vec<String> = self
+                .conflicting_branches
+                .iter()
+                .map(|b| format!("`{}` (#{})", b.branch_name, b.issue_number))
+                .collect();
+            body.push_str(&format!("- **Conflicts with**: {}\n", others.join(", ")));
+        }
+
+        body.push_str("\n## Conflicting Files\n\n");
+        for file in &self.conflicted_files {
+            body.push_str(&format!("- `{file}`\n"));
+        }
+
+        body.push_str(&format!(
+            "\n## Reproduce\n\n```sh\n{}```\n",
+            self.reproduction_commands()
+        ));
+
+        body.push_str(
+            "\n## Resolution\n\n\
+            Make the conflicting changes compatible on this issue's branch, based on \
+            the base branch. Once that PR is merged this issue is closed and the \
+            blocked issue goes back into the bundle queue with rebase instructions \
+            for its branch.\n",
+        );
+
+        let originals: Vec<String> = self
+            .original_issues()
+            .iter()
+            .map(|n| format!("#{n}"))
+            .collect();
+        body.push_str(&format!(
+            "\n**Blocks**: #{}\n**Related**: {}\n\n{}\n",
+            self.conflicted_branch.issue_number,
+            originals.join(", "),
+            self.to_marker()
+        ));
+        body
+    }
+
+    /// Commands that replay the bundle up to the conflict
+    pub fn reproduction_commands(&self) -> String {
+        let mut commands = format!(
+            "git fetch origin\ngit checkout -b repro/{} {}\n",
+            self.conflicted_branch.issue_number, self.base_sha
+        );
+        for branch in &self.conflicting_branches {
+            commands.push_str(&format!(
+                "git cherry-pick {}..origin/{}\n",
+                self.base_sha, branch.branch_name
+            ));
+        }
+        commands.push_str(&format!(
+            "git cherry-pick {}..origin/{}  # conflicts here\n",
+            self.base_sha, self.conflicted_branch.branch_name
+        ));
+        commands
+    }
+
+    /// Comment linking an original issue to its unblocker
+    pub fn blocked_comment(&self, unblocker_issue: u64, issue_number: u64) -> String {
+        if issue_number == self.conflicted_branch.issue_number {
+            format!(
+                "🚧 **Blocked by #{unblocker_issue}**\n\n\
+                `{}` conflicts with the bundle in {}. The branch stays out of the \
+                bundle queue (`{BLOCKED_LABEL}`) until #{unblocker_issue} is resolved.\n",
+                self.conflicted_branch.branch_name,
+                format_files(&self.conflicted_files)
+            )
+        } else {
+            format!(
+                "🔗 **Involved in #{unblocker_issue}**\n\n\
+                `{}` (#{}) conflicts with this issue's changes in {}.\n",
+                self.conflicted_branch.branch_name,
+                self.conflicted_branch.issue_number,
+                format_files(&self.conflicted_files)
+            )
+        }
+    }
+
+    /// Comment re-queuing the conflicted issue once the resolution landed
+    pub fn requeue_comment(&self, unblocker_issue: u64, resolution_pr: u64) -> String {
+        format!(
+            "✅ **Unblocked by #{unblocker_issue}** (resolved in PR #{resolution_pr})\n\n\
+            This issue is back in the bundle queue. Rebase its branch onto the \
+            resolution before the next bundle:\n\n```sh\n{}```\n",
+            rebase_instructions(&self.conflicted_branch.branch_name, &self.base_branch)
+        )
+    }
+
+    pub fn to_marker(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        format!("{UNBLOCKER_MARKER}\n{json}\n-->")
+    }
+
+    /// Extract the task from an unblocker issue body, if it has one
+    pub fn from_issue_body(body: &str) -> Option<Self> {
+        let start = body.find(UNBLOCKER_MARKER)? + UNBLOCKER_MARKER.len();
+        let end = start + body[start..].find("-->")?;
+        serde_json::from_str(body[start..end].trim()).ok()
+    }
+}
+
+/// Commands an agent runs to bring its branch up to date with the base branch
+pub fn rebase_instructions(branch_name: &str, base_branch: &str) -> String {
+    format!(
+        "git fetch origin\n\
+        git checkout {branch_name}\n\
+        git rebase origin/{base_branch}\n\
+        git push --force-with-lease origin {branch_name}\n"
+    )
+}
+
+/// Already-applied branches that change any of the conflicted files
+pub fn conflicting_branches(
+    conflicted_files: &[String],
+    applied: &[QueuedBranch],
+    files: &BranchFiles,
+) -> Vec<QueuedBranch> {
+    applied
+        .iter()
+        .filter(|branch| {
+            files
+                .get(&branch.branch_name)
+                .is_some_and(|changed| conflicted_files.iter().any(|file| changed.contains(file)))
+        })
+        .cloned()
+        .collect()
+}
+
+fn format_files(files: &[String]) -> String {
+    if files.is_empty() {
+        return "its changes".to_string();
+    }
+    files
+        .iter()
+        .map(|f| format!("`{f}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,146 @@
+//! Fetching the logs and reports of failed CI jobs
+
+use super::{clean_log, CiFailureReport, FailureParser, FailureParsers, JobFailures, JunitParser};
+use crate::github::actions::{ActionsHandler, FailedJob};
+use crate::github::GitHubClient;
+use anyhow::Result;
+use async_trait::async_trait;
+use tracing::warn;
+
+/// Where failed jobs and their output come from
+#[async_trait]
+pub trait CiLogSource {
+    /// Failed jobs of the workflow runs on a commit
+    async fn failed_jobs(&self, head_sha: &str) -> Result<Vec<FailedJob>>;
+
+    /// Raw log of a job
+    async fn job_log(&self, job_id: u64) -> Result<String>;
+
+    /// JUnit reports a run uploaded, as file name and contents
+    async fn junit_reports(&self, run_id: u64) -> Result<Vec<(String, String)>>;
+}
+
+/// Whether an artifact looks like it holds test reports
+fn is_report_artifact(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("junit") || name.contains("test")
+}
+
+#[async_trait]
+impl CiLogSource for ActionsHandler {
+    async fn failed_jobs(&self, head_sha: &str) -> Result<Vec<FailedJob>> {
+        Ok(ActionsHandler::failed_jobs(self, head_sha).await?)
+    }
+
+    async fn job_log(&self, job_id: u64) -> Result<String> {
+        Ok(ActionsHandler::job_log(self, job_id).await?)
+    }
+
+    async fn junit_reports(&self, run_id: u64) -> Result<Vec<(String, String)>> {
+        let mut reports = Vec::new();
+        for artifact in self.run_artifacts(run_id).await? {
+            if artifact.expired || !is_report_artifact(&artifact.name) {
+                continue;
+            }
+            let archive = self.download_artifact(artifact.id).await?;
+            reports.extend(
+                super::junit::xml_reports_in_zip(&archive)?
+                    .into_iter()
+                    .filter(|(_, xml)| xml.contains("<testsuite")),
+            );
+        }
+        Ok(reports)
+    }
+}
+
+/// Last `##[error]` annotation GitHub wrote into the log
+fn last_error_line(log: &str) -> Option<String> {
+    log.lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("##[error]"))
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+/// Parse every failed job on a commit. Jobs whose log can't be fetched are
+/// still reported, and JUnit reports fill in failures the logs don't show.
+pub async fn ingest_failures(
+    source: &(dyn CiLogSource + Sync),
+    head_sha: &str,
+    parsers: &FailureParsers,
+) -> Result<CiFailureReport> {
+    let failed_jobs = source.failed_jobs(head_sha).await?;
+
+    let mut report = CiFailureReport {
+        head_sha: head_sha.to_string(),
+        jobs: Vec::new(),
+    };
+    for job in &failed_jobs {
+        let log = source.job_log(job.id).await.unwrap_or_else(|e| {
+            warn!("Could not fetch the log of job {}: {}", job.name, e);
+            String::new()
+        });
+        report.jobs.push(JobFailures {
+            job: job.name.clone(),
+            step: job.failed_step.clone(),
+            url: job.html_url.clone(),
+            failures: parsers.parse(&log),
+            error_line: last_error_line(&clean_log(&log)),
+        });
+    }
+
+    let mut run_ids: Vec<u64> = failed_jobs.iter().map(|job| job.run_id).collect();
+    run_ids.sort_unstable();
+    run_ids.dedup();
+    for run_id in run_ids {
+        let reports = source.junit_reports(run_id).await.unwrap_or_else(|e| {
+            warn!("Could not fetch test reports of run {}: {}", run_id, e);
+            Vec::new()
+        });
+        let known: Vec<String> = report.failures().map(|f| f.name.clone()).collect();
+        let mut failures: Vec<_> = reports
+            .iter()
+            .flat_map(|(_, xml)| JunitParser.parse(xml))
+            .filter(|failure| !known.contains(&failure.name))
+            .collect();
+        if failures.is_empty() {
+            continue;
+        }
+
+        // The reports most likely explain a job of the run whose log didn't
+        let in_run = |index: &usize| failed_jobs[*index].run_id == run_id;
+        let target = (0..failed_jobs.len())
+            .filter(in_run)
+            .find(|&index| report.jobs[index].failures.is_empty())
+            .or_else(|| (0..failed_jobs.len()).find(in_run));
+        if let Some(index) = target {
+            report.jobs[index].failures.append(&mut failures);
+        }
+    }
+    Ok(report)
+}
+
+/// Comment the summary on the PR unless this commit was already reported;
+/// returns whether a comment was posted
+pub async fn post_summary(
+    client: &GitHubClient,
+    pr_number: u64,
+    report: &CiFailureReport,
+) -> Result<bool> {
+    if report.is_empty() {
+        return Ok(false);
+    }
+    let marker = report.marker();
+    let comments = client.comments.get_issue_comments(pr_number).await?;
+    if comments
+        .iter()
+        .any(|c| c.body.as_deref().is_some_and(|b| b.contains(&marker)))
+    {
+        return Ok(false);
+    }
+    client
+        .comments
+        .create_issue_comment(pr_number, &report.summary_comment())
+        .await?;
+    Ok(true)
+}
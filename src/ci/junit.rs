@@ -0,0 +1,181 @@
+//! JUnit XML reports, whether printed in a log or uploaded as an artifact
+//!
+//! Reports are read with a few patterns rather than a full XML parser: test
+//! runners write them flat and predictably. Artifacts come as zip archives,
+//! of which the stored and deflated entries are read.
+
+use super::{FailureKind, FailureParser, ParsedFailure};
+use anyhow::{anyhow, Result};
+use flate2::read::DeflateDecoder;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::OnceLock;
+
+/// `<testcase>` elements with a `<failure>` or `<error>` child
+pub struct JunitParser;
+
+impl FailureParser for JunitParser {
+    fn name(&self) -> &'static str {
+        "junit"
+    }
+
+    fn parse(&self, log: &str) -> Vec<ParsedFailure> {
+        static SUITE: OnceLock<Regex> = OnceLock::new();
+        static CASE: OnceLock<Regex> = OnceLock::new();
+        static PROBLEM: OnceLock<Regex> = OnceLock::new();
+        let suite = SUITE.get_or_init(|| Regex::new(r"<testsuite\b([^>]*)>").unwrap());
+        let case = CASE.get_or_init(|| {
+            Regex::new(r"(?s)<testcase\b([^>]*?)(?:/>|>(.*?)</testcase>)").unwrap()
+        });
+        let problem = PROBLEM.get_or_init(|| {
+            Regex::new(r"(?s)<(failure|error)\b([^>]*?)(?:/>|>(.*?)</(?:failure|error)>)").unwrap()
+        });
+
+        let suites: Vec<(usize, HashMap<String, String>)> = suite
+            .captures_iter(log)
+            .map(|c| (c.get(0).unwrap().start(), attributes(&c[1])))
+            .collect();
+
+        let mut failures = Vec::new();
+        for caps in case.captures_iter(log) {
+            let Some(body) = caps.get(2) else {
+                continue;
+            };
+            let Some(found) = problem.captures(body.as_str()) else {
+                continue;
+            };
+
+            let attrs = attributes(&caps[1]);
+            let start = caps.get(0).unwrap().start();
+            let suite_name = suites
+                .iter()
+                .rev()
+                .find(|(position, _)| *position < start)
+                .and_then(|(_, attrs)| attrs.get("name").cloned());
+            let name = match (attrs.get("classname"), attrs.get("name")) {
+                (Some(class), Some(name)) if !class.is_empty() => format!("{class}::{name}"),
+                (_, Some(name)) => name.clone(),
+                _ => continue,
+            };
+
+            let problem_attrs = attributes(&found[2]);
+            let message = problem_attrs
+                .get("message")
+                .filter(|m| !m.is_empty())
+                .cloned()
+                .or_else(|| {
+                    found
+                        .get(3)
+                        .and_then(|text| unescape(text.as_str()).lines().next().map(str::to_string))
+                })
+                .unwrap_or_else(|| format!("test {}", &found[1]));
+
+            failures.push(
+                ParsedFailure::new(FailureKind::Test, name, message.trim())
+                    .with_suite(suite_name)
+                    .with_location(
+                        attrs.get("file").cloned(),
+                        attrs.get("line").and_then(|l| l.parse().ok()),
+                    ),
+            );
+        }
+        failures
+    }
+}
+
+/// `name="value"` pairs of an element, unescaped
+fn attributes(text: &str) -> HashMap<String, String> {
+    static ATTRIBUTE: OnceLock<Regex> = OnceLock::new();
+    let attribute = ATTRIBUTE.get_or_init(|| Regex::new(r#"([\w:-]+)="([^"]*)""#).unwrap());
+    attribute
+        .captures_iter(text)
+        .map(|c| (c[1].to_string(), unescape(&c[2])))
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#10;", "\n")
+        .replace("&amp;", "&")
+}
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+
+/// The `.xml` files in a zip archive, by name
+pub fn xml_reports_in_zip(archive: &[u8]) -> Result<Vec<(String, String)>> {
+    let u16_at = |offset: usize| -> Result<usize> {
+        archive
+            .get(offset..offset + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or_else(|| anyhow!("Zip archive is truncated"))
+    };
+    let u32_at = |offset: usize| -> Result<usize> {
+        archive
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or_else(|| anyhow!("Zip archive is truncated"))
+    };
+
+    // The end record sits at the very end, unless the archive has a comment
+    let end = (0..archive.len().saturating_sub(21))
+        .rev()
+        .find(|&offset| u32_at(offset).ok() == Some(END_OF_CENTRAL_DIRECTORY as usize))
+        .ok_or_else(|| anyhow!("Not a zip archive"))?;
+    let entries = u16_at(end + 10)?;
+    let mut offset = u32_at(end + 16)?;
+
+    let mut reports = Vec::new();
+    for _ in 0..entries {
+        if u32_at(offset)? != CENTRAL_DIRECTORY_ENTRY as usize {
+            return Err(anyhow!("Corrupt zip central directory"));
+        }
+        let method = u16_at(offset + 10)?;
+        let compressed_size = u32_at(offset + 20)?;
+        let name_length = u16_at(offset + 28)?;
+        let extra_length = u16_at(offset + 30)?;
+        let comment_length = u16_at(offset + 32)?;
+        let local_header = u32_at(offset + 42)?;
+        let name = String::from_utf8_lossy(
+            archive
+                .get(offset + 46..offset + 46 + name_length)
+                .ok_or_else(|| anyhow!("Zip archive is truncated"))?,
+        )
+        .to_string();
+        offset += 46 + name_length + extra_length + comment_length;
+
+        if !name.to_lowercase().ends_with(".xml") {
+            continue;
+        }
+        if u32_at(local_header)? != LOCAL_FILE_HEADER as usize {
+            return Err(anyhow!("Corrupt zip entry {}", name));
+        }
+        let data_start =
+            local_header + 30 + u16_at(local_header + 26)? + u16_at(local_header + 28)?;
+        let data = archive
+            .get(data_start..data_start + compressed_size)
+            .ok_or_else(|| anyhow!("Zip entry {} is truncated", name))?;
+        let contents = match method {
+            0 => String::from_utf8_lossy(data).to_string(),
+            8 => {
+                let mut text = String::new();
+                DeflateDecoder::new(data).read_to_string(&mut text)?;
+                text
+            }
+            other => {
+                return Err(anyhow!(
+                    "Zip entry {} uses unsupported method {}",
+                    name,
+                    other
+                ))
+            }
+        };
+        reports.push((name, contents));
+    }
+    Ok(reports)
+}
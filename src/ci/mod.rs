@@ -0,0 +1,328 @@
+//! CI failure ingestion
+//!
+//! Logs of failed GitHub Actions jobs (and JUnit reports they upload) are run
+//! through a set of [`FailureParser`]s, each recognising one tool's output:
+//! cargo test, rustc/clippy diagnostics, JUnit XML, pytest and jest. The
+//! structured failures they find pick the recovery strategy for the agent and
+//! make up the summary comment posted on the PR.
+
+pub mod ingest;
+pub mod junit;
+pub mod parsers;
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+use tracing::debug;
+
+pub use ingest::{ingest_failures, post_summary};
+pub use junit::JunitParser;
+pub use parsers::{CargoTestParser, JestParser, PytestParser, RustcDiagnosticParser};
+
+/// Hidden marker on CI summary comments, so one commit is only reported once
+pub const CI_FAILURE_MARKER: &str = "<!-- my-little-soda:ci-failures";
+
+/// Failures listed per job in the PR comment before the rest are counted
+const MAX_LISTED_FAILURES: usize = 10;
+
+/// What kind of problem a failure is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// A test that ran and failed
+    Test,
+    /// The code did not compile
+    Compile,
+    /// A lint denied by the build, e.g. clippy with `-D warnings`
+    Lint,
+}
+
+/// One failure found in a CI log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParsedFailure {
+    pub kind: FailureKind,
+    /// Test name, or the diagnostic code or lint for build failures
+    pub name: String,
+    pub message: String,
+    /// Test binary, file or suite the failure was reported under
+    pub suite: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl ParsedFailure {
+    pub fn new(kind: FailureKind, name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            message: message.into(),
+            suite: None,
+            file: None,
+            line: None,
+        }
+    }
+
+    pub fn with_suite(mut self, suite: Option<String>) -> Self {
+        self.suite = suite;
+        self
+    }
+
+    pub fn with_location(mut self, file: Option<String>, line: Option<u32>) -> Self {
+        self.file = file;
+        self.line = line;
+        self
+    }
+
+    /// `file:line`, or just the file, when known
+    pub fn location(&self) -> Option<String> {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => Some(format!("{file}:{line}")),
+            (Some(file), None) => Some(file.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Recognises failures in the output of one tool
+pub trait FailureParser: Send + Sync {
+    /// Short name for logging
+    fn name(&self) -> &'static str;
+
+    /// Failures found in `log`, which has been through [`clean_log`]
+    fn parse(&self, log: &str) -> Vec<ParsedFailure>;
+}
+
+/// The parsers a log is run through
+pub struct FailureParsers {
+    parsers: Vec<Box<dyn FailureParser>>,
+}
+
+impl Default for FailureParsers {
+    fn default() -> Self {
+        Self::empty()
+            .with_parser(Box::new(CargoTestParser))
+            .with_parser(Box::new(RustcDiagnosticParser))
+            .with_parser(Box::new(JunitParser))
+            .with_parser(Box::new(PytestParser))
+            .with_parser(Box::new(JestParser))
+    }
+}
+
+impl FailureParsers {
+    /// No parsers at all, to build a custom set
+    pub fn empty() -> Self {
+        Self {
+            parsers: Vec::new(),
+        }
+    }
+
+    pub fn with_parser(mut self, parser: Box<dyn FailureParser>) -> Self {
+        self.parsers.push(parser);
+        self
+    }
+
+    /// Failures every parser finds in a raw job log, each reported once
+    pub fn parse(&self, raw_log: &str) -> Vec<ParsedFailure> {
+        let log = clean_log(raw_log);
+        let mut failures: Vec<ParsedFailure> = Vec::new();
+        let found = self.parsers.iter().flat_map(|parser| {
+            let found = parser.parse(&log);
+            debug!(
+                parser = parser.name(),
+                failures = found.len(),
+                "Parsed CI log"
+            );
+            found
+        });
+        for failure in found {
+            match failures
+                .iter_mut()
+                .find(|f| f.kind == failure.kind && f.name == failure.name)
+            {
+                // A later parser may know where an already reported failure is
+                Some(existing) => {
+                    if existing.file.is_none() {
+                        existing.file = failure.file;
+                        existing.line = failure.line;
+                    }
+                }
+                None => failures.push(failure),
+            }
+        }
+        failures
+    }
+}
+
+/// Strip the timestamps GitHub puts in front of every log line and ANSI colours
+pub fn clean_log(raw_log: &str) -> String {
+    static TIMESTAMP: OnceLock<Regex> = OnceLock::new();
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    let timestamp = TIMESTAMP.get_or_init(|| {
+        Regex::new(r"(?m)^\x{feff}?\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z ").unwrap()
+    });
+    let ansi = ANSI.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap());
+
+    let log = timestamp.replace_all(raw_log, "");
+    ansi.replace_all(&log, "").replace("\r\n", "\n")
+}
+
+/// Failures of one CI job
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobFailures {
+    pub job: String,
+    pub step: Option<String>,
+    pub url: String,
+    pub failures: Vec<ParsedFailure>,
+    /// Last error line of the log, for jobs no parser understood
+    pub error_line: Option<String>,
+}
+
+impl JobFailures {
+    /// What went wrong, in one line
+    pub fn headline(&self) -> String {
+        if let Some(failure) = self.failures.first() {
+            match failure.location() {
+                Some(location) => format!("{} ({location})", failure.message),
+                None => failure.message.clone(),
+            }
+        } else {
+            self.error_line
+                .clone()
+                .unwrap_or_else(|| "job failed without a recognisable error".to_string())
+        }
+    }
+}
+
+/// Failures found in the CI runs of one commit
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CiFailureReport {
+    pub head_sha: String,
+    pub jobs: Vec<JobFailures>,
+}
+
+impl CiFailureReport {
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Every parsed failure across jobs
+    pub fn failures(&self) -> impl Iterator<Item = &ParsedFailure> {
+        self.jobs.iter().flat_map(|job| &job.failures)
+    }
+
+    /// Markdown summary for the PR, marked so it is posted once per commit
+    pub fn summary_comment(&self) -> String {
+        let short_sha = &self.head_sha[..self.head_sha.len().min(7)];
+        let mut comment = format!(
+            "🔴 **CI failed on `{short_sha}`**: {} job(s)\n",
+            self.jobs.len()
+        );
+        for job in &self.jobs {
+            comment.push_str(&format!("\n**[{}]({})**", job.job, job.url));
+            if let Some(step) = &job.step {
+                comment.push_str(&format!(", step `{step}`"));
+            }
+            comment.push('\n');
+
+            if job.failures.is_empty() {
+                comment.push_str(&format!("- {}\n", job.headline()));
+                continue;
+            }
+            for failure in job.failures.iter().take(MAX_LISTED_FAILURES) {
+                let location = failure
+                    .location()
+                    .map(|l| format!(" at `{l}`"))
+                    .unwrap_or_default();
+                comment.push_str(&format!(
+                    "- `{}`{location}: {}\n",
+                    failure.name, failure.message
+                ));
+            }
+            if job.failures.len() > MAX_LISTED_FAILURES {
+                comment.push_str(&format!(
+                    "- …and {} more\n",
+                    job.failures.len() - MAX_LISTED_FAILURES
+                ));
+            }
+        }
+        comment.push_str(&format!("\n{}\n", self.marker()));
+        comment
+    }
+
+    /// Marker identifying this commit in a PR comment
+    pub fn marker(&self) -> String {
+        format!("{CI_FAILURE_MARKER} {} -->", self.head_sha)
+    }
+
+    /// The error recovery sees: build breakage first, since tests can't run
+    /// until it is fixed, then failing tests, then whatever the job said
+    #[cfg(feature = "autonomous")]
+    pub fn error_type(&self) -> Option<crate::autonomous::ErrorType> {
+        use crate::autonomous::ErrorType;
+
+        let build = self
+            .failures()
+            .find(|f| f.kind == FailureKind::Compile)
+            .or_else(|| self.failures().find(|f| f.kind == FailureKind::Lint));
+        if let Some(failure) = build {
+            let (stage, prefix) = match failure.kind {
+                FailureKind::Lint => ("clippy", "lint "),
+                _ => ("compile", ""),
+            };
+            let location = failure
+                .location()
+                .map(|l| format!(" at {l}"))
+                .unwrap_or_default();
+            return Some(ErrorType::BuildFailure {
+                stage: stage.to_string(),
+                error: format!("{prefix}{}: {}{location}", failure.name, failure.message),
+            });
+        }
+
+        let tests: Vec<&ParsedFailure> = self
+            .failures()
+            .filter(|f| f.kind == FailureKind::Test)
+            .collect();
+        if let Some(first) = tests.first() {
+            return Some(ErrorType::TestFailure {
+                test_suite: first
+                    .suite
+                    .clone()
+                    .unwrap_or_else(|| self.jobs[0].job.clone()),
+                failed_tests: tests.iter().map(|f| f.name.clone()).collect(),
+            });
+        }
+
+        self.jobs.first().map(|job| ErrorType::CIFailure {
+            job: job.job.clone(),
+            step: job.step.clone().unwrap_or_default(),
+            error: job.headline(),
+        })
+    }
+
+    /// The recovery `recovery`'s rules pick for these failures, `None` when
+    /// no job failed
+    #[cfg(feature = "autonomous")]
+    pub fn recovery_plan(
+        &self,
+        recovery: &crate::autonomous::AutonomousErrorRecovery,
+    ) -> Option<crate::autonomous::recovery_rules::RecoveryPlan> {
+        self.error_type()
+            .map(|error| recovery.recovery_plan(&error))
+    }
+
+    /// The failures as the autonomous workflow tracks them; a job is fixable
+    /// by the agent when its failures were understood
+    #[cfg(feature = "autonomous")]
+    pub fn workflow_failures(&self) -> Vec<crate::autonomous::CIFailure> {
+        self.jobs
+            .iter()
+            .map(|job| crate::autonomous::CIFailure {
+                job_name: job.job.clone(),
+                step: job.step.clone().unwrap_or_default(),
+                error: job.headline(),
+                auto_fixable: !job.failures.is_empty(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,284 @@
+//! Parsers for the plain text output of test runners and compilers
+
+use super::{FailureKind, FailureParser, ParsedFailure};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// Failing tests reported by `cargo test`, with the panic location when the
+/// captured output is shown
+pub struct CargoTestParser;
+
+impl FailureParser for CargoTestParser {
+    fn name(&self) -> &'static str {
+        "cargo test"
+    }
+
+    fn parse(&self, log: &str) -> Vec<ParsedFailure> {
+        static RUNNING: OnceLock<Regex> = OnceLock::new();
+        static FAILED: OnceLock<Regex> = OnceLock::new();
+        static OUTPUT: OnceLock<Regex> = OnceLock::new();
+        static PANIC: OnceLock<Regex> = OnceLock::new();
+        static OLD_PANIC: OnceLock<Regex> = OnceLock::new();
+        let running = regex(&RUNNING, r"^\s*Running (?:unittests )?(\S+)");
+        let failed = regex(&FAILED, r"^test (.+?) \.\.\. FAILED$");
+        let output = regex(&OUTPUT, r"^---- (.+?) stdout ----$");
+        let panic = regex(&PANIC, r"^thread '(.+?)' panicked at ([^\s:]+):(\d+):\d+:$");
+        let old_panic = regex(
+            &OLD_PANIC,
+            r"^thread '(.+?)' panicked at '(.*)', ([^\s:]+):(\d+):\d+$",
+        );
+
+        let lines: Vec<&str> = log.lines().collect();
+        let mut suite = None;
+        let mut failures: Vec<ParsedFailure> = Vec::new();
+        // Panic message and location per test, from the captured output sections
+        let mut panics: HashMap<String, (String, String, u32)> = HashMap::new();
+        let mut in_output = None;
+
+        for (index, line) in lines.iter().enumerate() {
+            if let Some(caps) = running.captures(line) {
+                suite = Some(caps[1].to_string());
+                in_output = None;
+            } else if let Some(caps) = failed.captures(line) {
+                let name = caps[1].to_string();
+                if !failures.iter().any(|f| f.name == name) {
+                    failures.push(
+                        ParsedFailure::new(FailureKind::Test, name, "test failed")
+                            .with_suite(suite.clone()),
+                    );
+                }
+            } else if let Some(caps) = output.captures(line) {
+                in_output = Some(caps[1].to_string());
+            } else if let Some(test) = &in_output {
+                if let Some(caps) = panic.captures(line) {
+                    let message = lines[index + 1..]
+                        .iter()
+                        .map(|l| l.trim())
+                        .find(|l| !l.is_empty())
+                        .unwrap_or("panicked");
+                    panics.entry(test.clone()).or_insert((
+                        message.to_string(),
+                        caps[2].to_string(),
+                        caps[3].parse().unwrap_or_default(),
+                    ));
+                } else if let Some(caps) = old_panic.captures(line) {
+                    panics.entry(test.clone()).or_insert((
+                        caps[2].to_string(),
+                        caps[3].to_string(),
+                        caps[4].parse().unwrap_or_default(),
+                    ));
+                }
+            }
+        }
+
+        for failure in &mut failures {
+            if let Some((message, file, line)) = panics.remove(&failure.name) {
+                failure.message = message;
+                failure.file = Some(file);
+                failure.line = Some(line);
+            }
+        }
+        failures
+    }
+}
+
+/// Errors from rustc, including warnings and clippy lints denied with `-D`
+pub struct RustcDiagnosticParser;
+
+impl FailureParser for RustcDiagnosticParser {
+    fn name(&self) -> &'static str {
+        "rustc"
+    }
+
+    fn parse(&self, log: &str) -> Vec<ParsedFailure> {
+        static HEADER: OnceLock<Regex> = OnceLock::new();
+        static LOCATION: OnceLock<Regex> = OnceLock::new();
+        static IMPLIED: OnceLock<Regex> = OnceLock::new();
+        static DENY: OnceLock<Regex> = OnceLock::new();
+        let header = regex(&HEADER, r"^(error|warning)(?:\[(E\d{4})\])?: (.+)$");
+        let location = regex(&LOCATION, r"^\s*--> ([^\s:]+):(\d+):\d+");
+        let implied = regex(&IMPLIED, r"`-D ([\w:-]+)` implied by `-D warnings`");
+        let deny = regex(&DENY, r"#\[deny\(([\w:]+)\)\]");
+
+        let lines: Vec<&str> = log.lines().collect();
+        let mut failures: Vec<ParsedFailure> = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            let Some(caps) = header.captures(line) else {
+                continue;
+            };
+            if &caps[1] != "error" {
+                continue;
+            }
+            let block: Vec<&str> = lines[index + 1..]
+                .iter()
+                .take_while(|l| !header.is_match(l))
+                .copied()
+                .collect();
+            // Summaries like "could not compile" have no location
+            let Some(at) = block.iter().find_map(|l| location.captures(l)) else {
+                continue;
+            };
+
+            let message = caps[3].to_string();
+            let lint = block.iter().find_map(|l| {
+                implied
+                    .captures(l)
+                    .or_else(|| deny.captures(l))
+                    .map(|c| c[1].replace('-', "_"))
+            });
+            let (kind, name) = match (&lint, caps.get(2)) {
+                (Some(lint), _) => (FailureKind::Lint, lint.clone()),
+                (None, Some(code)) => (FailureKind::Compile, code.as_str().to_string()),
+                (None, None) => (FailureKind::Compile, message.clone()),
+            };
+            let file = at[1].to_string();
+            let line = at[2].parse().ok();
+            if failures
+                .iter()
+                .any(|f| f.name == name && f.file.as_ref() == Some(&file) && f.line == line)
+            {
+                continue;
+            }
+            failures.push(ParsedFailure::new(kind, name, message).with_location(Some(file), line));
+        }
+        failures
+    }
+}
+
+/// The short test summary pytest prints at the end of a failing run
+pub struct PytestParser;
+
+impl FailureParser for PytestParser {
+    fn name(&self) -> &'static str {
+        "pytest"
+    }
+
+    fn parse(&self, log: &str) -> Vec<ParsedFailure> {
+        static SUMMARY: OnceLock<Regex> = OnceLock::new();
+        static ENTRY: OnceLock<Regex> = OnceLock::new();
+        let summary = regex(&SUMMARY, r"^=+ short test summary info =+$");
+        let entry = regex(&ENTRY, r"^(FAILED|ERROR) (\S+)(?: - (.+))?$");
+
+        let lines: Vec<&str> = log.lines().collect();
+        let Some(start) = lines.iter().position(|l| summary.is_match(l)) else {
+            return Vec::new();
+        };
+
+        let mut failures: Vec<ParsedFailure> = Vec::new();
+        for caps in lines[start + 1..].iter().map_while(|l| entry.captures(l)) {
+            let node_id = caps[2].to_string();
+            let file = node_id.split("::").next().unwrap_or_default().to_string();
+            let message = caps.get(3).map_or_else(
+                || {
+                    if &caps[1] == "ERROR" {
+                        "error during collection or setup"
+                    } else {
+                        "test failed"
+                    }
+                    .to_string()
+                },
+                |m| m.as_str().to_string(),
+            );
+            // The traceback shows `path.py:12: AssertionError` at the failing line
+            let line = lines[..start]
+                .iter()
+                .find_map(|l| l.strip_prefix(&format!("{file}:")))
+                .and_then(|rest| rest.split(':').next())
+                .and_then(|number| number.parse().ok());
+            if failures.iter().any(|f| f.name == node_id) {
+                continue;
+            }
+            failures.push(
+                ParsedFailure::new(FailureKind::Test, node_id, message)
+                    .with_suite(Some(file.clone()))
+                    .with_location(Some(file), line),
+            );
+        }
+        failures
+    }
+}
+
+/// Failing test blocks jest prints under each `FAIL path` header
+pub struct JestParser;
+
+impl FailureParser for JestParser {
+    fn name(&self) -> &'static str {
+        "jest"
+    }
+
+    fn parse(&self, log: &str) -> Vec<ParsedFailure> {
+        static SUITE: OnceLock<Regex> = OnceLock::new();
+        static TEST: OnceLock<Regex> = OnceLock::new();
+        static FRAME: OnceLock<Regex> = OnceLock::new();
+        let suite_header = regex(&SUITE, r"^\s*(FAIL|PASS)\s+(\S+)");
+        let test_header = regex(&TEST, r"^\s*● (.+)$");
+        let frame = regex(&FRAME, r"at (?:.*? \()?([^\s()]+):(\d+):\d+\)?$");
+
+        let lines: Vec<&str> = log.lines().collect();
+        let mut failures: Vec<ParsedFailure> = Vec::new();
+        let mut suite: Option<String> = None;
+        for (index, line) in lines.iter().enumerate() {
+            if let Some(caps) = suite_header.captures(line) {
+                suite = (&caps[1] == "FAIL").then(|| caps[2].to_string());
+                continue;
+            }
+            let (Some(file), Some(caps)) = (&suite, test_header.captures(line)) else {
+                continue;
+            };
+
+            let block: Vec<&str> = lines[index + 1..]
+                .iter()
+                .take_while(|l| {
+                    !test_header.is_match(l)
+                        && !suite_header.is_match(l)
+                        && !l.starts_with("Test Suites:")
+                })
+                .copied()
+                .collect();
+            let title = caps[1].trim().to_string();
+            let name = if title == "Test suite failed to run" {
+                file.clone()
+            } else {
+                title
+            };
+            if failures.iter().any(|f| f.name == name) {
+                continue;
+            }
+            let message = block
+                .iter()
+                .map(|l| l.trim())
+                .find(|l| !l.is_empty())
+                .unwrap_or("test failed");
+            // Prefer a stack frame in the test file over frames inside libraries
+            let frames: Vec<(String, u32)> = block
+                .iter()
+                .filter_map(|l| frame.captures(l.trim()))
+                .filter_map(|c| Some((c[1].to_string(), c[2].parse().ok()?)))
+                .collect();
+            let (path, line) = match frames
+                .iter()
+                .find(|(path, _)| path.ends_with(file.as_str()))
+                .or_else(|| {
+                    frames
+                        .iter()
+                        .find(|(path, _)| !path.contains("node_modules"))
+                }) {
+                Some((path, line)) if path.ends_with(file.as_str()) => (file.clone(), Some(*line)),
+                Some((path, line)) => (path.clone(), Some(*line)),
+                None => (file.clone(), None),
+            };
+
+            failures.push(
+                ParsedFailure::new(FailureKind::Test, name, message)
+                    .with_suite(Some(file.clone()))
+                    .with_location(Some(path), line),
+            );
+        }
+        failures
+    }
+}
@@ -335,10 +335,23 @@ async fn diagnose_all_agents(_router: &crate::agents::AgentRouter) -> Result<()>
     Ok(())
 }
 
+/// An error described on the command line, to explain how it would be recovered
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(not(feature = "autonomous"), allow(dead_code))]
+pub struct RecoveryQuery {
+    pub error_type: String,
+    pub status: Option<u16>,
+    pub subject: Option<String>,
+    pub message: Option<String>,
+    pub files: Vec<String>,
+    pub count: Option<usize>,
+}
+
 pub struct AgentRecoverCommand {
     agent_id: Option<String>,
     all: bool,
     dry_run: bool,
+    explain: Option<RecoveryQuery>,
     ci_mode: bool,
 }
 
@@ -348,10 +361,17 @@ impl AgentRecoverCommand {
             agent_id,
             all,
             dry_run,
+            explain: None,
             ci_mode: false,
         }
     }
 
+    /// Explain which recovery rule fires for `query` instead of recovering
+    pub fn with_explain(mut self, query: Option<RecoveryQuery>) -> Self {
+        self.explain = query;
+        self
+    }
+
     pub fn with_ci_mode(mut self, ci_mode: bool) -> Self {
         self.ci_mode = ci_mode;
         self
@@ -360,6 +380,9 @@ impl AgentRecoverCommand {
 
 impl Command for AgentRecoverCommand {
     async fn execute(&self) -> Result<()> {
+        if let Some(query) = &self.explain {
+            return explain_recovery(query);
+        }
         with_agent_router(|router| async move {
             if self.all {
                 recover_all_agents(&router, self.dry_run).await
@@ -374,6 +397,27 @@ impl Command for AgentRecoverCommand {
     }
 }
 
+#[cfg(feature = "autonomous")]
+fn explain_recovery(query: &RecoveryQuery) -> Result<()> {
+    use crate::autonomous::{ErrorFacts, RecoveryRules};
+
+    let rules = RecoveryRules::load()?;
+    // Without a count, the number of files given is the best guess
+    let facts = ErrorFacts::new(&query.error_type)?
+        .with_subject(query.subject.clone().unwrap_or_default())
+        .with_message(query.message.clone().unwrap_or_default())
+        .with_status(query.status)
+        .with_count(query.count.unwrap_or(query.files.len()))
+        .with_files(query.files.clone());
+    println!("{}", rules.explain(&facts));
+    Ok(())
+}
+
+#[cfg(not(feature = "autonomous"))]
+fn explain_recovery(_query: &RecoveryQuery) -> Result<()> {
+    anyhow::bail!("Recovery rules need my-little-soda built with the `autonomous` feature")
+}
+
 async fn recover_single_agent(
     agent_id: &str,
     router: &crate::agents::AgentRouter,
@@ -1,13 +1,27 @@
-use crate::bundling::BundleManager;
+use crate::bundling::git_ops::{AssemblyMode, ConflictStrategy};
+use crate::bundling::refresh::RefreshOutcome;
+use crate::bundling::types::BundleStatus;
+use crate::bundling::{BundleManager, BundleResult};
 use crate::train_schedule::TrainSchedule;
 use anyhow::Result;
 
+/// Number of bundles shown by `bundle --history`
+const HISTORY_LIMIT: u32 = 20;
+
 pub struct BundleCommand {
     pub force: bool,
     pub dry_run: bool,
     pub verbose: bool,
     pub diagnose: bool,
     pub ci_mode: bool,
+    pub conflict_strategy: Option<ConflictStrategy>,
+    pub assembly_mode: Option<AssemblyMode>,
+    pub continue_bundle: bool,
+    pub abort: bool,
+    pub resume: bool,
+    pub history: bool,
+    /// Bundle PR to rebuild and the issue to drop from it
+    pub rebuild: Option<(u64, u64)>,
 }
 
 impl BundleCommand {
@@ -18,6 +32,13 @@ impl BundleCommand {
             verbose,
             diagnose,
             ci_mode: false,
+            conflict_strategy: None,
+            assembly_mode: None,
+            continue_bundle: false,
+            abort: false,
+            resume: false,
+            history: false,
+            rebuild: None,
         }
     }
 
@@ -26,11 +47,95 @@ impl BundleCommand {
         self
     }
 
+    pub fn with_conflict_strategy(mut self, conflict_strategy: Option<ConflictStrategy>) -> Self {
+        self.conflict_strategy = conflict_strategy;
+        self
+    }
+
+    pub fn with_assembly_mode(mut self, assembly_mode: Option<AssemblyMode>) -> Self {
+        self.assembly_mode = assembly_mode;
+        self
+    }
+
+    pub fn with_continue(mut self, continue_bundle: bool) -> Self {
+        self.continue_bundle = continue_bundle;
+        self
+    }
+
+    pub fn with_abort(mut self, abort: bool) -> Self {
+        self.abort = abort;
+        self
+    }
+
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn with_history(mut self, history: bool) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn with_rebuild(mut self, rebuild: Option<(u64, u64)>) -> Self {
+        self.rebuild = rebuild;
+        self
+    }
+
     pub async fn execute(&self) -> Result<()> {
         if self.diagnose {
             return self.execute_diagnostics().await;
         }
 
+        if self.history {
+            return self.display_history().await;
+        }
+
+        if self.abort {
+            let mut bundle_manager = BundleManager::new()?;
+            return bundle_manager.abort_bundle().await;
+        }
+
+        if let Some((pr_number, drop_issue)) = self.rebuild {
+            println!(
+                "🚄 MY LITTLE SODA BUNDLE - Rebuild bundle PR #{pr_number} without #{drop_issue}"
+            );
+            println!("==========================================");
+            println!();
+            let mut bundle_manager = BundleManager::new()?;
+            return match bundle_manager.rebuild_bundle(pr_number, drop_issue).await? {
+                BundleResult::Failed { error } => {
+                    println!("❌ Bundle rebuild failed: {error}");
+                    Err(error)
+                }
+                _ => {
+                    println!("✅ Bundle PR #{pr_number} rebuilt without #{drop_issue}");
+                    println!(
+                        "   🔙 Issue #{drop_issue} returned to route:ready with review feedback"
+                    );
+                    Ok(())
+                }
+            };
+        }
+
+        if self.resume {
+            println!("🚄 MY LITTLE SODA BUNDLE - Resume interrupted bundle");
+            println!("==========================================");
+            println!();
+            let mut bundle_manager = BundleManager::new()?;
+            let result = bundle_manager.resume_bundle().await?;
+            return self.report_bundle_result(result, None);
+        }
+
+        if self.continue_bundle {
+            println!("🚄 MY LITTLE SODA BUNDLE - Continue paused bundle");
+            println!("==========================================");
+            println!();
+            let mut bundle_manager = BundleManager::new()?;
+            let result = bundle_manager.continue_bundle().await?;
+            return self.report_bundle_result(result, None);
+        }
+
         if self.dry_run {
             println!("🚄 MY LITTLE SODA BUNDLE - Create PR from queued branches (DRY RUN)");
         } else {
@@ -76,53 +181,212 @@ impl BundleCommand {
 
         // Initialize bundle manager
         let mut bundle_manager = BundleManager::new()?;
+        if let Some(strategy) = &self.conflict_strategy {
+            bundle_manager = bundle_manager.with_conflict_strategy(strategy.clone());
+        }
+        if let Some(mode) = &self.assembly_mode {
+            bundle_manager = bundle_manager.with_assembly_mode(mode.clone());
+        }
 
-        // Perform bundling
+        // Perform bundling; create_bundle rebases branches that fell behind main first
+        let bundles = bundle_manager.plan_bundles(&queued_branches);
         if self.dry_run {
+            let refreshes = bundle_manager
+                .refresh_queued_branches(&queued_branches, true)
+                .await;
+            for refresh in &refreshes {
+                if self.verbose || refresh.outcome != RefreshOutcome::UpToDate {
+                    println!("   {}", refresh.summary());
+                }
+            }
             println!(
-                "🔧 DRY RUN: Would create bundle PR with {} branches",
+                "🔧 DRY RUN: Would create {} bundle PR(s) from {} branches",
+                bundles.len(),
                 queued_branches.len()
             );
-            let bundle_branch = bundle_manager.generate_bundle_branch_name(&queued_branches);
-            println!("   Bundle branch: {bundle_branch}");
+            for bundle in &bundles {
+                let bundle_branch = bundle_manager.generate_bundle_branch_name(bundle);
+                println!("   Bundle branch: {bundle_branch}");
+                println!(
+                    "   Order: {}",
+                    bundle
+                        .iter()
+                        .map(|b| format!("#{}", b.issue_number))
+                        .collect::<Vec<_>>()
+                        .join(" → ")
+                );
+            }
+        } else {
+            let mut first_error = None;
+            for bundle in &bundles {
+                println!("🚄 Creating bundle PR...");
+                let result = bundle_manager.create_bundle(bundle).await?;
+                let paused = matches!(result, BundleResult::ManualResolutionRequired { .. });
+                if let Err(e) = self.report_bundle_result(result, Some(bundle.len())) {
+                    first_error.get_or_insert(e);
+                }
+                if paused {
+                    break;
+                }
+            }
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn report_bundle_result(
+        &self,
+        result: BundleResult,
+        queued_count: Option<usize>,
+    ) -> Result<()> {
+        match result {
+            BundleResult::Success {
+                pr_number,
+                bundle_branch,
+            } => {
+                println!("✅ Bundle PR created successfully!");
+                println!("   📋 PR: #{pr_number}");
+                println!("   🌿 Branch: {bundle_branch}");
+                if let Some(count) = queued_count {
+                    println!("   📦 Bundled {count} branches");
+                }
+            }
+            BundleResult::PartialSuccess {
+                pr_number,
+                bundle_branch,
+                skipped_branches,
+            } => {
+                println!("✅ Bundle PR created without conflicting branches");
+                println!("   📋 PR: #{pr_number}");
+                println!("   🌿 Branch: {bundle_branch}");
+                println!("   ⚠️  Skipped due to conflicts:");
+                for branch in skipped_branches {
+                    println!("      • {branch}");
+                }
+            }
+            BundleResult::ManualResolutionRequired {
+                bundle_branch,
+                conflicted_branch,
+                conflicted_files,
+            } => {
+                println!(
+                    "⏸️  Bundle {bundle_branch} paused on a conflict with {conflicted_branch}"
+                );
+                for file in conflicted_files {
+                    println!("   • {file}");
+                }
+                println!("💡 Resolve and 'git add' the files, then run 'my-little-soda bundle --continue'");
+                println!("💡 Or discard the bundle with 'my-little-soda bundle --abort'");
+            }
+            BundleResult::ConflictFallback { individual_prs } => {
+                println!("⚠️  Conflicts detected - created individual PRs:");
+                for (branch, pr) in individual_prs {
+                    println!("   • {branch} → PR #{pr}");
+                }
+            }
+            BundleResult::Failed { error } => {
+                println!("❌ Bundle creation failed: {error}");
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn display_history(&self) -> Result<()> {
+        println!("📜 MY LITTLE SODA BUNDLE HISTORY");
+        println!("=================================");
+        println!();
+
+        let history = BundleManager::bundle_history(HISTORY_LIMIT).await?;
+        if history.is_empty() {
+            println!("📭 No bundles recorded yet");
+            return Ok(());
+        }
+
+        for entry in history {
+            let icon = match entry.status {
+                BundleStatus::InProgress => "⏳",
+                BundleStatus::Paused => "⏸️ ",
+                BundleStatus::Completed => "✅",
+                BundleStatus::Failed => "❌",
+                BundleStatus::FellBack => "🔀",
+                BundleStatus::Aborted => "🛑",
+            };
             println!(
-                "   Issues: {}",
-                queued_branches
+                "{} {} [{}] started {} UTC",
+                icon,
+                entry.bundle_id,
+                entry.status.as_str(),
+                entry.created_at
+            );
+
+            if let Some(state) = &entry.state {
+                let issues = state
+                    .queued_branches
                     .iter()
+                    .filter(|b| state.completed_branches.contains(&b.branch_name))
                     .map(|b| format!("#{}", b.issue_number))
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            );
-        } else {
-            println!("🚄 Creating bundle PR...");
-            let result = bundle_manager.create_bundle(&queued_branches).await?;
-
-            match result {
-                crate::bundling::BundleResult::Success {
-                    pr_number,
-                    bundle_branch,
-                } => {
-                    println!("✅ Bundle PR created successfully!");
-                    println!("   📋 PR: #{pr_number}");
-                    println!("   🌿 Branch: {bundle_branch}");
-                    println!("   📦 Bundled {} branches", queued_branches.len());
+                    .collect::<Vec<_>>();
+                if !issues.is_empty() {
+                    println!("   └─ Bundled: {}", issues.join(", "));
                 }
-                crate::bundling::BundleResult::ConflictFallback { individual_prs } => {
-                    println!("⚠️  Conflicts detected - created individual PRs:");
-                    for (branch, pr) in individual_prs {
-                        println!("   • {branch} → PR #{pr}");
-                    }
+                if !state.failed_branches.is_empty() {
+                    println!("   └─ Skipped: {}", state.failed_branches.join(", "));
                 }
-                crate::bundling::BundleResult::Failed { error } => {
-                    println!("❌ Bundle creation failed: {error}");
-                    return Err(error);
+                if let Some(pr_number) = state.pr_number {
+                    println!("   └─ PR: #{pr_number}");
                 }
+                if let Some(last) = state.audit_trail.last() {
+                    println!(
+                        "   └─ Last step: {} at {}",
+                        last.operation, entry.updated_at
+                    );
+                }
+            }
+
+            #[cfg(feature = "database")]
+            if self.verbose {
+                self.display_steps(&entry.bundle_id).await;
             }
         }
 
+        if !self.ci_mode {
+            println!();
+            println!("💡 Resume an interrupted (⏳) bundle with 'my-little-soda bundle --resume'");
+        }
         Ok(())
     }
 
+    /// Every saved step of one bundle, for `--history --verbose`
+    #[cfg(feature = "database")]
+    async fn display_steps(&self, bundle_id: &str) {
+        match BundleManager::bundle_steps(bundle_id).await {
+            Ok(steps) => {
+                for step in steps {
+                    let mut line = format!(
+                        "      • {} [{}] {} bundled, {} skipped",
+                        step.recorded_at,
+                        step.status.as_str(),
+                        step.completed_branches,
+                        step.failed_branches
+                    );
+                    if let Some(operation) = &step.operation {
+                        line.push_str(&format!(" - {operation}"));
+                    }
+                    if let Some(pr_number) = step.pr_number {
+                        line.push_str(&format!(" (PR #{pr_number})"));
+                    }
+                    println!("{line}");
+                }
+            }
+            Err(e) => println!("      • Steps unavailable: {e}"),
+        }
+    }
+
     async fn execute_diagnostics(&self) -> Result<()> {
         println!("🔍 MY LITTLE SODA BUNDLE DIAGNOSTICS");
         println!("=====================================");
@@ -245,6 +509,19 @@ impl BundleCommand {
         println!("⚙️  Configuration");
         println!("───────────────");
 
+        let strategy = self.conflict_strategy.clone().unwrap_or_else(|| {
+            crate::config::config()
+                .map(|c| c.agents.bundle_processing.conflict_strategy.clone())
+                .unwrap_or_default()
+        });
+        println!("🔀 Conflict strategy: {strategy:?}");
+        let assembly_mode = self.assembly_mode.clone().unwrap_or_else(|| {
+            crate::config::config()
+                .map(|c| c.agents.bundle_processing.assembly_mode.clone())
+                .unwrap_or_default()
+        });
+        println!("🧩 Assembly mode: {}", assembly_mode.description());
+
         // Check for my-little-soda directory
         if std::path::Path::new(".my-little-soda").exists() {
             println!("✅ .my-little-soda directory: Present");
@@ -261,7 +538,7 @@ impl BundleCommand {
 
         // Check for previous state
         if std::path::Path::new(".my-little-soda/bundle_state.json").exists() {
-            println!("💾 Previous state: Found (may need recovery)");
+            println!("💾 Previous state: Found (run 'bundle --continue' or 'bundle --abort')");
         } else {
             println!("🆕 Previous state: Clean");
         }
@@ -274,12 +551,24 @@ impl BundleCommand {
         println!("📈 Recent Activity");
         println!("────────────────");
 
-        // This would show recent bundle attempts, PRs created, etc.
-        // For now, we'll provide a placeholder
-        println!("🔄 Last 24 hours: Data collection in progress");
-        println!("📊 Bundle attempts: Not tracked yet");
-        println!("✅ Successful bundles: Not tracked yet");
-        println!("⚠️  Fallback to individual PRs: Not tracked yet");
+        match BundleManager::bundle_history(HISTORY_LIMIT).await {
+            Ok(history) => {
+                let count =
+                    |status: BundleStatus| history.iter().filter(|e| e.status == status).count();
+                println!("📊 Recorded bundles: {}", history.len());
+                println!("✅ Completed: {}", count(BundleStatus::Completed));
+                println!(
+                    "⚠️  Fallback to individual PRs: {}",
+                    count(BundleStatus::FellBack)
+                );
+                println!("❌ Failed: {}", count(BundleStatus::Failed));
+                let interrupted = count(BundleStatus::InProgress);
+                if interrupted > 0 {
+                    println!("⏳ Interrupted: {interrupted} (resume with 'bundle --resume')");
+                }
+            }
+            Err(e) => println!("📊 Bundle history unavailable: {e}"),
+        }
 
         println!();
         Ok(())
@@ -0,0 +1,356 @@
+//! Database maintenance commands
+//!
+//! `db` works on the database configured under `[database]`, or the one given
+//! with `--url`, without the automatic migration done at startup, so a schema
+//! can be inspected, moved to a given version, compacted, backed up and
+//! exported, and autonomous workflow state files can be imported into it.
+
+use crate::cli::commands::{format_bytes, Command};
+use crate::cli::DoctorFormat;
+use crate::database::{DatabaseManager, SchemaStatus};
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+/// Where backups go when no output path is given
+pub const DEFAULT_BACKUP_DIR: &str = ".my-little-soda/backups";
+
+/// URL of the configured database
+fn configured_url() -> Result<String> {
+    crate::config::config()
+        .ok()
+        .and_then(|c| c.database.as_ref().map(|db| db.url.clone()))
+        .ok_or_else(|| {
+            anyhow!("No database configured; add a [database] section to my-little-soda.toml")
+        })
+}
+
+async fn open(url: Option<&str>) -> Result<DatabaseManager> {
+    match url {
+        Some(url) => DatabaseManager::connect(url).await,
+        None => DatabaseManager::connect(&configured_url()?).await,
+    }
+}
+
+pub fn format_status(status: &SchemaStatus) -> String {
+    let mut text = format!(
+        "🗄️  Schema version {} (this build knows up to {})\n",
+        status.current_version, status.latest_known
+    );
+    for migration in &status.migrations {
+        let applied = match &migration.applied_at {
+            Some(at) => format!("applied {at}"),
+            None => "pending".to_string(),
+        };
+        let reversible = if migration.reversible {
+            ""
+        } else {
+            " (irreversible)"
+        };
+        text.push_str(&format!(
+            "  {:03} {:<24} {}{}\n",
+            migration.version, migration.description, applied, reversible
+        ));
+    }
+    for version in &status.unknown_applied {
+        text.push_str(&format!(
+            "  {version:03} {:<24} applied by a newer build\n",
+            "?"
+        ));
+    }
+    if status.is_newer() {
+        text.push_str(
+            "❌ The database was migrated by a newer my-little-soda; upgrade before using it\n",
+        );
+    } else if !status.pending().is_empty() {
+        text.push_str(&format!(
+            "⚠️  {} migration(s) pending; run 'my-little-soda db migrate'\n",
+            status.pending().len()
+        ));
+    } else {
+        text.push_str("✅ Schema is up to date\n");
+    }
+    text
+}
+
+pub struct DbStatusCommand {
+    format: DoctorFormat,
+    url: Option<String>,
+}
+
+impl DbStatusCommand {
+    pub fn new(format: DoctorFormat) -> Self {
+        Self { format, url: None }
+    }
+
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+
+    pub async fn run(&self) -> Result<SchemaStatus> {
+        open(self.url.as_deref()).await?.schema_status().await
+    }
+}
+
+impl Command for DbStatusCommand {
+    async fn execute(&self) -> Result<()> {
+        let status = self.run().await?;
+        match self.format {
+            DoctorFormat::Text => print!("{}", format_status(&status)),
+            DoctorFormat::Json => println!("{}", serde_json::to_string_pretty(&status)?),
+        }
+        Ok(())
+    }
+}
+
+pub struct DbMigrateCommand {
+    target: Option<i64>,
+    dry_run: bool,
+    url: Option<String>,
+}
+
+impl DbMigrateCommand {
+    pub fn new(target: Option<i64>, dry_run: bool) -> Self {
+        Self {
+            target,
+            dry_run,
+            url: None,
+        }
+    }
+
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Migrate, or with `dry_run` only report where the schema stands
+    pub async fn run(&self) -> Result<SchemaStatus> {
+        let database = open(self.url.as_deref()).await?;
+        if self.dry_run {
+            database.schema_status().await
+        } else {
+            database.migrate(self.target).await
+        }
+    }
+}
+
+impl Command for DbMigrateCommand {
+    async fn execute(&self) -> Result<()> {
+        let before = open(self.url.as_deref()).await?.schema_status().await?;
+        let target = self.target.unwrap_or(before.latest_known);
+        if self.dry_run {
+            println!(
+                "🔍 Would migrate from version {} to {}",
+                before.current_version, target
+            );
+            return Ok(());
+        }
+
+        let after = self.run().await?;
+        println!(
+            "✅ Migrated from version {} to {}",
+            before.current_version, after.current_version
+        );
+        let auto_migrate = crate::config::config()
+            .ok()
+            .and_then(|c| c.database.as_ref().map(|db| db.auto_migrate))
+            .unwrap_or(false);
+        if auto_migrate && after.current_version < after.latest_known {
+            println!(
+                "⚠️  database.auto_migrate is enabled, so the next command will migrate back up to {}",
+                after.latest_known
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct DbVacuumCommand {
+    url: Option<String>,
+}
+
+impl DbVacuumCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+}
+
+impl Command for DbVacuumCommand {
+    async fn execute(&self) -> Result<()> {
+        let (before, after) = open(self.url.as_deref()).await?.vacuum().await?;
+        println!(
+            "🧹 Vacuumed database: {} → {} ({} reclaimed)",
+            format_bytes(before.total_bytes as u64),
+            format_bytes(after.total_bytes as u64),
+            format_bytes((before.total_bytes - after.total_bytes).max(0) as u64)
+        );
+        Ok(())
+    }
+}
+
+pub struct DbBackupCommand {
+    output: Option<PathBuf>,
+    url: Option<String>,
+}
+
+impl DbBackupCommand {
+    pub fn new(output: Option<PathBuf>) -> Self {
+        Self { output, url: None }
+    }
+
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+}
+
+impl Command for DbBackupCommand {
+    async fn execute(&self) -> Result<()> {
+        let path = self.output.clone().unwrap_or_else(|| {
+            PathBuf::from(DEFAULT_BACKUP_DIR).join(format!(
+                "my-little-soda-{}.db",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            ))
+        });
+        let database = open(self.url.as_deref()).await?;
+        database.backup(&path).await?;
+        let size = database.size().await?;
+        println!(
+            "💾 Backed up database ({}) to {}",
+            format_bytes(size.total_bytes as u64),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+pub struct DbExportCommand {
+    table: Option<String>,
+    output: Option<PathBuf>,
+    url: Option<String>,
+}
+
+impl DbExportCommand {
+    pub fn new(table: Option<String>, output: Option<PathBuf>) -> Self {
+        Self {
+            table,
+            output,
+            url: None,
+        }
+    }
+
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Rows of one table as an array, or every table as an object keyed by name
+    pub async fn run(&self) -> Result<serde_json::Value> {
+        let database = open(self.url.as_deref()).await?;
+        if let Some(table) = &self.table {
+            return Ok(database.export_table(table).await?.into());
+        }
+        let mut tables = serde_json::Map::new();
+        for table in database.tables().await? {
+            let rows = database.export_table(&table).await?;
+            tables.insert(table, rows.into());
+        }
+        Ok(tables.into())
+    }
+}
+
+impl Command for DbExportCommand {
+    async fn execute(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.run().await?)?;
+        match &self.output {
+            Some(path) => {
+                std::fs::write(path, json)?;
+                println!("📤 Exported database to {}", path.display());
+            }
+            None => println!("{json}"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "autonomous")]
+pub struct DbImportStateCommand {
+    from: Option<PathBuf>,
+    url: Option<String>,
+}
+
+#[cfg(feature = "autonomous")]
+impl DbImportStateCommand {
+    pub fn new(from: Option<PathBuf>) -> Self {
+        Self { from, url: None }
+    }
+
+    pub fn with_url(mut self, url: Option<String>) -> Self {
+        self.url = url;
+        self
+    }
+
+    fn directory(&self) -> PathBuf {
+        use crate::autonomous::persistence::PersistenceConfig;
+        self.from
+            .clone()
+            .unwrap_or_else(|| PersistenceConfig::default().persistence_directory)
+    }
+
+    pub async fn run(&self) -> Result<crate::autonomous::sqlite_persistence::ImportReport> {
+        use crate::autonomous::persistence::PersistenceConfig;
+        use crate::autonomous::sqlite_persistence::SqlitePersistence;
+
+        let directory = self.directory();
+        if !directory.is_dir() {
+            return Err(anyhow!("No state directory at {}", directory.display()));
+        }
+        let database = open(self.url.as_deref()).await?;
+        if !database
+            .tables()
+            .await?
+            .iter()
+            .any(|t| t == "workflow_checkpoints")
+        {
+            return Err(anyhow!(
+                "The database has no workflow state tables yet; run 'my-little-soda db migrate' first"
+            ));
+        }
+        Ok(SqlitePersistence::new(PersistenceConfig::default())
+            .with_pool(database.pool().clone())
+            .import_directory(&directory)
+            .await?)
+    }
+}
+
+#[cfg(feature = "autonomous")]
+impl Command for DbImportStateCommand {
+    async fn execute(&self) -> Result<()> {
+        let report = self.run().await?;
+        println!(
+            "📥 Imported {} state(s) and {} checkpoint(s) from {}",
+            report.states_imported,
+            report.checkpoints_imported,
+            self.directory().display()
+        );
+        if report.already_present > 0 {
+            println!(
+                "   {} already in the database were left as they were",
+                report.already_present
+            );
+        }
+        for path in &report.failed {
+            println!("⚠️  Could not read {}", path.display());
+        }
+        println!(
+            "💡 Set persistence_backend = \"sqlite\" under [agents.work_continuity] to use them"
+        );
+        Ok(())
+    }
+}
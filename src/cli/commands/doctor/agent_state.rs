@@ -16,6 +16,8 @@ use crate::github::{GitHubClient, GitHubError};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+#[cfg(feature = "autonomous")]
+use std::path::PathBuf;
 use tracing::{debug, info};
 
 /// Comprehensive agent state diagnostic report
@@ -563,6 +565,16 @@ impl AgentStateDiagnostic {
             );
         }
 
+        // Disagreements between GitHub and git found rehydrating the agent
+        if let Some(coordinator) = &self.agent_coordinator {
+            validation_errors.extend(
+                coordinator
+                    .inconsistencies()
+                    .iter()
+                    .map(|inconsistency| inconsistency.pattern.to_string()),
+            );
+        }
+
         let status = if validation_errors.is_empty() {
             DiagnosticStatus::Pass
         } else {
@@ -591,7 +603,7 @@ impl AgentStateDiagnostic {
                     Some(validation_errors.join("; "))
                 },
                 suggestion: if !validation_errors.is_empty() {
-                    Some("Reset agent state with 'my-little-soda reset --agent-state'".to_string())
+                    Some("Recover the agent with 'my-little-soda agent recover'".to_string())
                 } else {
                     None
                 },
@@ -655,20 +667,8 @@ impl AgentStateDiagnostic {
             preserve_partial_work: config.agents.work_continuity.preserve_partial_work,
         };
 
-        let persistence_config = PersistenceConfig {
-            enable_persistence: continuity_config.enable_continuity,
-            persistence_directory: continuity_config
-                .state_file_path
-                .parent()
-                .unwrap_or(&PathBuf::from(".my-little-soda"))
-                .to_path_buf(),
-            auto_save_interval_minutes: continuity_config.backup_interval_minutes,
-            max_state_history_entries: 1000,
-            max_recovery_history_entries: 500,
-            compress_old_states: true,
-            backup_retention_days: 7,
-            enable_integrity_checks: true,
-        };
+        let persistence_config =
+            PersistenceConfig::from_work_continuity(&config.agents.work_continuity);
 
         if !continuity_config.enable_continuity {
             return Ok(WorkContinuityStatus {
@@ -137,6 +137,9 @@ impl DoctorCommand {
         // Run comprehensive configuration validation
         self.check_toml_configuration(&mut checks)?;
 
+        // Check the state database schema against this build
+        self.check_database_schema(&mut checks).await;
+
         // Run comprehensive GitHub authentication diagnostics
         self.check_github_authentication(&mut checks).await;
 
@@ -963,6 +966,91 @@ impl DoctorCommand {
         Ok(())
     }
 
+    #[cfg(feature = "database")]
+    async fn check_database_schema(&self, checks: &mut HashMap<String, DiagnosticResult>) {
+        use crate::database::DatabaseManager;
+
+        let url = crate::config::config()
+            .ok()
+            .and_then(|c| c.database.as_ref().map(|db| db.url.clone()));
+        let Some(url) = url else {
+            checks.insert(
+                "database_schema".to_string(),
+                DiagnosticResult {
+                    status: DiagnosticStatus::Info,
+                    message: "No database configured".to_string(),
+                    details: None,
+                    suggestion: None,
+                },
+            );
+            return;
+        };
+        if !DatabaseManager::exists(&url).await.unwrap_or(false) {
+            checks.insert(
+                "database_schema".to_string(),
+                DiagnosticResult {
+                    status: DiagnosticStatus::Info,
+                    message: "Database not created yet".to_string(),
+                    details: Some(format!("{url} is created on first use")),
+                    suggestion: None,
+                },
+            );
+            return;
+        }
+
+        let status = match DatabaseManager::connect(&url).await {
+            Ok(database) => database.schema_status().await,
+            Err(e) => Err(e),
+        };
+        let result = match status {
+            Ok(status) if status.is_newer() => DiagnosticResult {
+                status: DiagnosticStatus::Fail,
+                message: format!(
+                    "Database schema version {} is newer than this build supports ({})",
+                    status.current_version, status.latest_known
+                ),
+                details: Some(format!("Database: {url}")),
+                suggestion: Some(
+                    "Upgrade my-little-soda, or restore a backup made with 'my-little-soda db backup'"
+                        .to_string(),
+                ),
+            },
+            Ok(status) if !status.pending().is_empty() => DiagnosticResult {
+                status: DiagnosticStatus::Warning,
+                message: format!(
+                    "Database schema version {} is behind ({} migration(s) pending)",
+                    status.current_version,
+                    status.pending().len()
+                ),
+                details: Some(format!("Database: {url}")),
+                suggestion: Some("Run 'my-little-soda db migrate'".to_string()),
+            },
+            Ok(status) => DiagnosticResult {
+                status: DiagnosticStatus::Pass,
+                message: format!(
+                    "Database schema is current (version {})",
+                    status.current_version
+                ),
+                details: if self.is_verbose() {
+                    Some(format!("Database: {url}"))
+                } else {
+                    None
+                },
+                suggestion: None,
+            },
+            Err(e) => DiagnosticResult {
+                status: DiagnosticStatus::Fail,
+                message: "Cannot read database schema".to_string(),
+                details: Some(e.to_string()),
+                suggestion: Some(format!("Check that {url} is a readable SQLite database")),
+            },
+        };
+        checks.insert("database_schema".to_string(), result);
+    }
+
+    #[cfg(not(feature = "database"))]
+    async fn check_database_schema(&self, _checks: &mut HashMap<String, DiagnosticResult>) {}
+
     fn check_dependencies(&self, checks: &mut HashMap<String, DiagnosticResult>) -> Result<()> {
         // Check Rust toolchain version and availability
         self.check_rust_toolchain(checks)?;
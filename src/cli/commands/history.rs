@@ -0,0 +1,69 @@
+use crate::agent_lifecycle::events::{
+    format_text, parse_since, to_csv, EventFilter, EventStore, LifecycleEvent,
+};
+use crate::cli::HistoryFormat;
+use anyhow::Result;
+use chrono::Utc;
+
+pub struct HistoryCommand {
+    pub format: HistoryFormat,
+    pub agent: Option<String>,
+    pub issue: Option<u64>,
+    pub since: Option<String>,
+    pub store: EventStore,
+}
+
+impl HistoryCommand {
+    pub fn new(format: HistoryFormat) -> Self {
+        Self {
+            format,
+            agent: None,
+            issue: None,
+            since: None,
+            store: EventStore::default(),
+        }
+    }
+
+    pub fn with_agent(mut self, agent: Option<String>) -> Self {
+        self.agent = agent;
+        self
+    }
+
+    pub fn with_issue(mut self, issue: Option<u64>) -> Self {
+        self.issue = issue;
+        self
+    }
+
+    pub fn with_since(mut self, since: Option<String>) -> Self {
+        self.since = since;
+        self
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        let events = self.run()?;
+        print!("{}", self.render(&events)?);
+        Ok(())
+    }
+
+    /// Recorded events matching the filters, oldest first
+    pub fn run(&self) -> Result<Vec<LifecycleEvent>> {
+        let since = self
+            .since
+            .as_deref()
+            .map(|since| parse_since(since, Utc::now()))
+            .transpose()?;
+        self.store.query(&EventFilter {
+            agent: self.agent.clone(),
+            issue: self.issue,
+            since,
+        })
+    }
+
+    pub fn render(&self, events: &[LifecycleEvent]) -> Result<String> {
+        Ok(match self.format {
+            HistoryFormat::Text => format_text(events),
+            HistoryFormat::Json => format!("{}\n", serde_json::to_string_pretty(events)?),
+            HistoryFormat::Csv => to_csv(events),
+        })
+    }
+}
@@ -26,9 +26,13 @@
 ///
 /// This approach ensures that my-little-soda can be initialized in any existing repository
 /// without risk of data loss or conflicts with existing project structure.
+use crate::agent_lifecycle::quality_gates::suggested_gates;
+use crate::bundling::git_ops::{AssemblyMode, ConflictStrategy};
 use crate::config::{
-    AgentConfig, AgentProcessConfig, BundleConfig, CIModeConfig, DatabaseConfig, GitHubConfig,
-    MyLittleSodaConfig, ObservabilityConfig, RateLimitConfig, WorkContinuityConfig,
+    AgentConfig, AgentProcessConfig, BundleConfig, CIModeConfig, DatabaseConfig, DiffLimitsConfig,
+    GitHubConfig, MergeConfig, MetricsConfig, MyLittleSodaConfig, ObservabilityConfig,
+    ProtectedPathsConfig, QualityGatesConfig, RateLimitConfig, RecoveryConfig,
+    SecretScanningConfig, WorkContinuityConfig,
 };
 use crate::fs::FileSystemOperations;
 use crate::github::client::GitHubClient;
@@ -857,6 +861,7 @@ impl InitCommand {
                 token: None, // Will be read from env var
                 owner,
                 repo,
+                base_branch: "main".to_string(),
                 rate_limit: RateLimitConfig {
                     requests_per_hour: 5000,
                     burst_capacity: 100,
@@ -873,6 +878,12 @@ impl InitCommand {
                 bundle_processing: BundleConfig {
                     max_queue_size: 50,
                     processing_timeout_seconds: 1800,
+                    conflict_strategy: ConflictStrategy::default(),
+                    assembly_mode: AssemblyMode::default(),
+                    trial_merge: true,
+                    max_branches_per_bundle: None,
+                    open_unblockers: true,
+                    rebase_queued_branches: true,
                 },
                 process_management: AgentProcessConfig {
                     claude_code_path: "claude-code".to_string(),
@@ -896,6 +907,17 @@ impl InitCommand {
                 max_connections: 10,
                 auto_migrate: true,
             }),
+            merge: MergeConfig::default(),
+            // Start from the usual checks for whatever the repository is built with
+            quality_gates: QualityGatesConfig {
+                gates: suggested_gates(|path| self.fs_ops.exists(path)),
+                ..QualityGatesConfig::default()
+            },
+            secret_scanning: SecretScanningConfig::default(),
+            diff_limits: DiffLimitsConfig::default(),
+            protected_paths: ProtectedPathsConfig::default(),
+            metrics: MetricsConfig::default(),
+            recovery: RecoveryConfig::default(),
         };
 
         config
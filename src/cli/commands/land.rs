@@ -1,14 +1,27 @@
+use super::preflight::PreflightCommand;
+use crate::agent_lifecycle::quality_gates::{QualityGateReport, QualityGateRunner};
 use crate::agents::AgentCoordinator;
+use crate::bundling::git_ops::GitOperations;
+use crate::bundling::path_policy::{hold_for_human_review, PathPolicy, ProtectedChange};
+use crate::bundling::size_limits::{SizeReport, SPLIT_LABEL};
+use crate::cli::DoctorFormat;
+use crate::git::secrets::{ensure_no_secrets, secrets_detected};
+use crate::github::labels::{HUMAN_ONLY_LABEL, REVIEW_LABEL};
 use crate::github::GitHubClient;
 use crate::train_schedule::{QueuedBranch, TrainSchedule};
 use anyhow::{anyhow, Result};
 use git2::Repository;
+use std::path::Path;
 use std::process::Command;
 
 pub struct LandCommand {
     pub dry_run: bool,
     pub verbose: bool,
     pub ci_mode: bool,
+    pub force: bool,
+    pub split: bool,
+    /// Branch the work lands on, from `[github] base_branch`
+    pub base_branch: String,
 }
 
 impl LandCommand {
@@ -17,6 +30,9 @@ impl LandCommand {
             dry_run,
             verbose,
             ci_mode: false,
+            force: false,
+            split: false,
+            base_branch: crate::config::base_branch(),
         }
     }
 
@@ -25,6 +41,16 @@ impl LandCommand {
         self
     }
 
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    pub fn with_split(mut self, split: bool) -> Self {
+        self.split = split;
+        self
+    }
+
     pub async fn execute(&self) -> Result<()> {
         if self.dry_run {
             println!("🚀 MY LITTLE SODA LAND - Mark Work Ready for Review (DRY RUN)");
@@ -38,6 +64,25 @@ impl LandCommand {
         let current_branch = self.get_current_branch()?;
         let (agent_id, issue_number) = self.parse_agent_branch(&current_branch)?;
 
+        // Pre-flight: catch conflicts, missing labels and the like before anything changes
+        let preflight = PreflightCommand::new(DoctorFormat::Text)
+            .with_branch(Some(current_branch.clone()))
+            .run()
+            .await?;
+        print!("{}", preflight.format_text());
+        println!();
+        let blocking = preflight.blocking().len();
+        if blocking > 0 && !self.dry_run {
+            if !self.force {
+                return Err(anyhow!(
+                    "Pre-flight found {} unresolved error(s). Fix them (try 'my-little-soda preflight --fix') or re-run with --force",
+                    blocking
+                ));
+            }
+            println!("⚠️  Bottling despite {blocking} pre-flight error(s) (--force)");
+            println!();
+        }
+
         // Validate ready to land (unless dry run - we want to show what would happen)
         if !self.dry_run {
             self.validate_ready_to_land(&current_branch)?;
@@ -68,17 +113,72 @@ impl LandCommand {
 
         println!("🔍 Processing agent work for issue #{issue_number}...");
 
+        // Step 0: Work too large to review is sent back with a suggested split
+        self.check_diff_size(&client, &current_branch, issue_number)
+            .await?;
+
+        // Work on protected paths still goes up, but only for human review
+        let protected = self.check_protected_paths(&current_branch)?;
+
+        // Quality gates must pass before the work goes up for review
+        self.run_quality_gates(&client, &current_branch, &agent_id, issue_number)
+            .await?;
+
         // Step 1: Push current branch to remote if needed
         if !self.dry_run {
             print!("📤 Ensuring branch is pushed to remote... ");
             std::io::Write::flush(&mut std::io::stdout()).unwrap();
 
-            self.push_current_branch(&current_branch)?;
+            if let Err(e) = self.push_current_branch(&current_branch) {
+                println!("❌");
+                if let Some(detected) = secrets_detected(&e) {
+                    // A human has to rotate the credential and rewrite the branch
+                    if let Err(e) = client
+                        .comments
+                        .create_issue_comment(issue_number, &detected.issue_comment())
+                        .await
+                    {
+                        eprintln!("⚠️  Warning: Could not comment on issue #{issue_number}: {e}");
+                    }
+                    if let Err(e) = client
+                        .add_label_to_issue(issue_number, HUMAN_ONLY_LABEL)
+                        .await
+                    {
+                        eprintln!("⚠️  Warning: Could not add {HUMAN_ONLY_LABEL} label: {e}");
+                    }
+                }
+                return Err(e);
+            }
             println!("✅");
         } else {
             println!("📤 [DRY RUN] Would push branch to remote: {current_branch}");
         }
 
+        // Protected work gets a PR of its own, with the path owners asked to review
+        if let Some(change) = &protected {
+            if !self.dry_run {
+                let description = client
+                    .fetch_issue(issue_number)
+                    .await
+                    .map(|issue| issue.title)
+                    .unwrap_or_else(|_| format!("Issue #{issue_number}"));
+                hold_for_human_review(
+                    &client,
+                    change,
+                    issue_number,
+                    &description,
+                    &self.base_branch,
+                )
+                .await;
+                println!("   🛡️  Held out of auto-bundling until a human reviews it");
+            } else {
+                println!("🛡️  [DRY RUN] Would label issue #{issue_number} {HUMAN_ONLY_LABEL}");
+                println!(
+                    "🛡️  [DRY RUN] Would open a PR for {current_branch} with the path owners as reviewers"
+                );
+            }
+        }
+
         // Step 2: Remove route:ready label (if present) to transition from ready to review
         if !self.dry_run {
             print!("🏷️  Removing route:ready label from issue #{issue_number}... ");
@@ -100,18 +200,21 @@ impl LandCommand {
             println!("🏷️  [DRY RUN] Would remove route:ready label from issue #{issue_number}");
         }
 
-        // Step 3: Add route:review label to mark as ready for bundling
-        if !self.dry_run {
-            print!("🏷️  Adding route:review label to issue #{issue_number}... ");
+        // Step 3: Add route:review label to mark as ready for bundling; protected work
+        // waits for its own PR instead
+        if protected.is_some() {
+            println!("🏷️  Skipping {REVIEW_LABEL} on issue #{issue_number}: held for human review");
+        } else if !self.dry_run {
+            print!("🏷️  Adding {REVIEW_LABEL} label to issue #{issue_number}... ");
             std::io::Write::flush(&mut std::io::stdout()).unwrap();
 
             client
-                .add_label_to_issue(issue_number, "route:review")
+                .add_label_to_issue(issue_number, REVIEW_LABEL)
                 .await
-                .map_err(|e| anyhow!("Failed to add route:review label: {}", e))?;
+                .map_err(|e| anyhow!("Failed to add {} label: {}", REVIEW_LABEL, e))?;
             println!("✅");
         } else {
-            println!("🏷️  [DRY RUN] Would add route:review label to issue #{issue_number}");
+            println!("🏷️  [DRY RUN] Would add {REVIEW_LABEL} label to issue #{issue_number}");
         }
 
         // Step 4: Trigger state machine transition to complete work
@@ -150,13 +253,24 @@ impl LandCommand {
 
         println!();
         println!("✅ Bottle complete:");
-        println!("   🌿 Branch {current_branch} is ready for bundling");
-        println!("   🏷️  Issue #{issue_number} label transition: route:ready → route:review");
+        if protected.is_some() {
+            println!("   🌿 Branch {current_branch} is up for human review in a PR of its own");
+            println!(
+                "   🏷️  Issue #{issue_number} label transition: route:ready → {HUMAN_ONLY_LABEL}"
+            );
+        } else {
+            println!("   🌿 Branch {current_branch} is ready for bundling");
+            println!("   🏷️  Issue #{issue_number} label transition: route:ready → {REVIEW_LABEL}");
+        }
         println!("   🤖 Agent {agent_id} is now free for new work");
         println!();
         println!("🎯 Next steps:");
         println!("   → Use 'my-little-soda pop' to get your next task");
-        println!("   → Branch will be bundled into PR during next bundle cycle");
+        if protected.is_some() {
+            println!("   → A path owner merges the PR once they have reviewed it");
+        } else {
+            println!("   → Branch will be bundled into PR during next bundle cycle");
+        }
 
         // Check if we're at departure time and trigger bundling if needed
         if TrainSchedule::is_departure_time() {
@@ -173,6 +287,165 @@ impl LandCommand {
         Ok(())
     }
 
+    /// The protected paths the branch touches, if any
+    fn check_protected_paths(&self, branch: &str) -> Result<Option<ProtectedChange>> {
+        let config = crate::config::config()
+            .map(|c| c.protected_paths.clone())
+            .unwrap_or_default();
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let policy = PathPolicy::for_repository(Path::new("."), &config)?;
+        let changelog = GitOperations::with_repository(Repository::open(".")?)
+            .branch_changelog(branch, &self.base_branch)
+            .map_err(|e| anyhow!("Failed to list the branch changes: {}", e))?;
+        let change = policy.check(branch, changelog.files.iter().map(|f| f.path.as_str()));
+        if !change.is_protected() {
+            return Ok(None);
+        }
+        print!("{}", change.format_text());
+        Ok(Some(change))
+    }
+
+    async fn check_diff_size(
+        &self,
+        client: &GitHubClient,
+        branch: &str,
+        issue_number: u64,
+    ) -> Result<()> {
+        let config = crate::config::config()
+            .map(|c| c.diff_limits.clone())
+            .unwrap_or_default();
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let changelog = GitOperations::with_repository(Repository::open(".")?)
+            .branch_changelog(branch, &self.base_branch)
+            .map_err(|e| anyhow!("Failed to measure the branch diff: {}", e))?;
+        let report = SizeReport::check(branch, changelog.files, &config.branch);
+        print!("{}", report.format_text(&config.branch));
+        if !report.is_oversized() {
+            return Ok(());
+        }
+        if self.dry_run {
+            println!("📏 [DRY RUN] Would refuse to bottle the oversized branch");
+            return Ok(());
+        }
+
+        if let Err(e) = client
+            .comments
+            .create_issue_comment(issue_number, &report.issue_comment(&config.branch))
+            .await
+        {
+            eprintln!("⚠️  Warning: Could not post the size report: {e}");
+        }
+
+        if self.split {
+            for group in report.split_plan(&config.branch) {
+                match client
+                    .create_issue(
+                        &group.issue_title(Some(issue_number)),
+                        &group.issue_body(Some(issue_number), branch),
+                        vec![SPLIT_LABEL.to_string()],
+                    )
+                    .await
+                {
+                    Ok(issue) => println!("   🧩 Opened #{} for {}", issue.number, group.directory),
+                    Err(e) => eprintln!(
+                        "⚠️  Warning: Could not open a split issue for {}: {e}",
+                        group.directory
+                    ),
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "Branch {} is over the diff size limits. Split the work{}",
+            branch,
+            if self.split {
+                " along the issues just opened"
+            } else {
+                " (run with --split to open the suggested follow-up issues)"
+            }
+        ))
+    }
+
+    async fn run_quality_gates(
+        &self,
+        client: &GitHubClient,
+        branch: &str,
+        agent_id: &str,
+        issue_number: u64,
+    ) -> Result<()> {
+        let config = crate::config::config()
+            .map(|c| c.quality_gates.clone())
+            .unwrap_or_default();
+        if !config.enabled || config.gates.is_empty() {
+            return Ok(());
+        }
+
+        if self.dry_run {
+            for gate in &config.gates {
+                println!(
+                    "🚦 [DRY RUN] Would run quality gate {}: {}",
+                    gate.name, gate.command
+                );
+            }
+            return Ok(());
+        }
+
+        println!("🚦 Running {} quality gate(s)...", config.gates.len());
+        let report = QualityGateRunner::new(".").run(&config, branch).await;
+        print!("{}", report.format_text());
+
+        if let Err(e) = client
+            .comments
+            .create_issue_comment(issue_number, &report.format_comment())
+            .await
+        {
+            eprintln!("⚠️  Warning: Could not post quality gate report: {e}");
+        }
+        self.record_quality_gates(agent_id, issue_number, &report)
+            .await;
+
+        let failures = report.failures();
+        if !failures.is_empty() {
+            let names: Vec<&str> = failures.iter().map(|r| r.name.as_str()).collect();
+            return Err(anyhow!(
+                "Quality gates failed: {}. Fix them and run 'my-little-soda bottle' again",
+                names.join(", ")
+            ));
+        }
+        println!();
+        Ok(())
+    }
+
+    #[cfg(feature = "metrics")]
+    async fn record_quality_gates(
+        &self,
+        agent_id: &str,
+        issue_number: u64,
+        report: &QualityGateReport,
+    ) {
+        if let Err(e) = crate::metrics::MetricsTracker::new()
+            .track_quality_gates(issue_number, agent_id, report)
+            .await
+        {
+            eprintln!("⚠️  Warning: Failed to record quality gate metrics: {e}");
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    async fn record_quality_gates(
+        &self,
+        _agent_id: &str,
+        _issue_number: u64,
+        _report: &QualityGateReport,
+    ) {
+    }
+
     /// Get the current git branch name
     fn get_current_branch(&self) -> Result<String> {
         let repo = Repository::open(".")?;
@@ -214,6 +487,13 @@ impl LandCommand {
 
     /// Push current branch to remote
     fn push_current_branch(&self, branch_name: &str) -> Result<()> {
+        ensure_no_secrets(
+            &Repository::open(".")?,
+            branch_name,
+            "origin",
+            &self.base_branch,
+        )?;
+
         let output = Command::new("git")
             .args(["push", "-u", "origin", branch_name])
             .output()?;
@@ -269,9 +549,10 @@ impl LandCommand {
             ));
         }
 
-        // Check for commits ahead of main
+        // Check for commits ahead of the base branch
+        let range = format!("{}..HEAD", self.base_branch);
         let commits_output = Command::new("git")
-            .args(["rev-list", "--count", "main..HEAD"])
+            .args(["rev-list", "--count", &range])
             .output()?;
 
         let commits_ahead: u32 = String::from_utf8_lossy(&commits_output.stdout)
@@ -334,31 +615,59 @@ impl LandCommand {
         let mut bundle_manager = BundleManager::new()
             .map_err(|e| anyhow!("Failed to initialize bundle manager: {}", e))?;
 
-        // Create bundle using the existing bundling system
-        println!("🚄 Creating bundle PR...");
-        let result = bundle_manager
-            .create_bundle(&queued_branches)
-            .await
-            .map_err(|e| anyhow!("Bundle creation failed: {}", e))?;
-
-        match result {
-            crate::bundling::BundleResult::Success {
-                pr_number,
-                bundle_branch,
-            } => {
-                println!("✅ Bundle PR created successfully!");
-                println!("   📋 PR: #{pr_number}");
-                println!("   🌿 Branch: {bundle_branch}");
-                println!("   📦 Bundled {} branches", queued_branches.len());
-            }
-            crate::bundling::BundleResult::ConflictFallback { individual_prs } => {
-                println!("⚠️  Conflicts detected - created individual PRs:");
-                for (branch, pr) in individual_prs {
-                    println!("   • {branch} → PR #{pr}");
+        // Create bundles using the existing bundling system
+        for bundle in bundle_manager.plan_bundles(&queued_branches) {
+            println!("🚄 Creating bundle PR...");
+            let result = bundle_manager
+                .create_bundle(&bundle)
+                .await
+                .map_err(|e| anyhow!("Bundle creation failed: {}", e))?;
+
+            match result {
+                crate::bundling::BundleResult::Success {
+                    pr_number,
+                    bundle_branch,
+                } => {
+                    println!("✅ Bundle PR created successfully!");
+                    println!("   📋 PR: #{pr_number}");
+                    println!("   🌿 Branch: {bundle_branch}");
+                    println!("   📦 Bundled {} branches", bundle.len());
+                }
+                crate::bundling::BundleResult::PartialSuccess {
+                    pr_number,
+                    bundle_branch,
+                    skipped_branches,
+                } => {
+                    println!("✅ Bundle PR created without conflicting branches");
+                    println!("   📋 PR: #{pr_number}");
+                    println!("   🌿 Branch: {bundle_branch}");
+                    println!(
+                        "   ⚠️  Skipped due to conflicts: {}",
+                        skipped_branches.join(", ")
+                    );
+                }
+                crate::bundling::BundleResult::ManualResolutionRequired {
+                    bundle_branch,
+                    conflicted_branch,
+                    ..
+                } => {
+                    println!(
+                        "⏸️  Bundle {bundle_branch} paused on a conflict with {conflicted_branch}"
+                    );
+                    println!(
+                        "💡 Resolve it, then run 'my-little-soda bundle --continue' (or --abort)"
+                    );
+                    return Ok(());
+                }
+                crate::bundling::BundleResult::ConflictFallback { individual_prs } => {
+                    println!("⚠️  Conflicts detected - created individual PRs:");
+                    for (branch, pr) in individual_prs {
+                        println!("   • {branch} → PR #{pr}");
+                    }
+                }
+                crate::bundling::BundleResult::Failed { error } => {
+                    return Err(anyhow!("Bundle creation failed: {}", error));
                 }
-            }
-            crate::bundling::BundleResult::Failed { error } => {
-                return Err(anyhow!("Bundle creation failed: {}", error));
             }
         }
 
@@ -0,0 +1,160 @@
+use crate::agents::integrator::{MergeMethod, MergeOutcome, MergeReport, WorkIntegrator};
+use crate::github::GitHubClient;
+use crate::shutdown::shutdown_signal;
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::warn;
+
+pub struct MergeCommand {
+    pub dry_run: bool,
+    pub ci_mode: bool,
+    pub pr: Option<u64>,
+    pub method: Option<MergeMethod>,
+    pub watch: bool,
+    pub interval: Option<u64>,
+}
+
+impl MergeCommand {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            ci_mode: false,
+            pr: None,
+            method: None,
+            watch: false,
+            interval: None,
+        }
+    }
+
+    pub fn with_ci_mode(mut self, ci_mode: bool) -> Self {
+        self.ci_mode = ci_mode;
+        self
+    }
+
+    pub fn with_pr(mut self, pr: Option<u64>) -> Self {
+        self.pr = pr;
+        self
+    }
+
+    pub fn with_method(mut self, method: Option<MergeMethod>) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_watch(mut self, watch: bool, interval: Option<u64>) -> Self {
+        self.watch = watch;
+        self.interval = interval;
+        self
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        if self.dry_run {
+            println!("🔀 MY LITTLE SODA MERGE - Land approved PRs (DRY RUN)");
+        } else {
+            println!("🔀 MY LITTLE SODA MERGE - Land approved PRs");
+        }
+        println!("==========================================");
+        println!();
+
+        let mut config = crate::config::config()
+            .map(|c| c.merge.clone())
+            .unwrap_or_default();
+        if let Some(method) = self.method {
+            // An explicit method applies to every PR, whatever its labels
+            config.default_method = method;
+            config.label_methods.clear();
+        }
+
+        let client = GitHubClient::with_verbose(false).map_err(|e| {
+            anyhow!(
+                "Failed to initialize GitHub client: {}. Try: my-little-soda doctor --verbose",
+                e
+            )
+        })?;
+        let integrator = WorkIntegrator::new(client, config).with_dry_run(self.dry_run);
+
+        if !self.watch {
+            let reports = integrator.run_once(self.pr).await?;
+            return self.print_reports(&reports);
+        }
+
+        let interval = self
+            .interval
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| integrator.poll_interval());
+        // A signal during a pass lets that pass finish before the watch stops
+        let (stop, mut stopped) = watch::channel(false);
+        tokio::spawn(async move {
+            match shutdown_signal().await {
+                Ok(signal) => {
+                    println!("🛑 {signal} received, stopping after the current pass...");
+                    let _ = stop.send(true);
+                }
+                Err(e) => warn!("Could not listen for shutdown signals: {}", e),
+            }
+        });
+
+        println!(
+            "👀 Watching for mergeable PRs every {}s (Ctrl+C to stop)",
+            interval.as_secs()
+        );
+        while !*stopped.borrow() {
+            match integrator.run_once(self.pr).await {
+                Ok(reports) => {
+                    let _ = self.print_reports(&reports);
+                }
+                Err(e) => println!("⚠️  Merge pass failed: {e}"),
+            }
+            println!();
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = stopped.changed() => {}
+            }
+        }
+        println!("👋 Stopped watching for mergeable PRs");
+        Ok(())
+    }
+
+    fn print_reports(&self, reports: &[MergeReport]) -> Result<()> {
+        println!("🕐 {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+        if reports.is_empty() {
+            println!("📭 No open bundle or agent PRs");
+            return Ok(());
+        }
+
+        let mut failures = 0;
+        for report in reports {
+            let line = match &report.outcome {
+                MergeOutcome::Merged { method } => {
+                    format!("✅ #{} merged ({})", report.pr_number, method.as_str())
+                }
+                MergeOutcome::WouldMerge { method } => format!(
+                    "🔧 #{} would be merged ({})",
+                    report.pr_number,
+                    method.as_str()
+                ),
+                MergeOutcome::Waiting(reason) => {
+                    format!("⏳ #{} waiting: {}", report.pr_number, reason)
+                }
+                MergeOutcome::RecoveryOpened { recovery_pr } => {
+                    failures += 1;
+                    format!(
+                        "🚨 #{} conflicted at merge time, recovery PR #{} opened",
+                        report.pr_number, recovery_pr
+                    )
+                }
+                MergeOutcome::Failed(error) => {
+                    failures += 1;
+                    format!("❌ #{} merge failed: {}", report.pr_number, error)
+                }
+            };
+            println!("{line}  {}", report.title);
+        }
+
+        if failures > 0 {
+            return Err(anyhow!("{} PR(s) could not be merged", failures));
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,26 @@
+#[cfg(feature = "database")]
+use crate::metrics::storage::MetricsStorage;
 use crate::metrics::MetricsTracker;
 use anyhow::Result;
 use serde_json;
 
+/// Tracker over the database `main` initialized, or the JSONL files without one
+async fn tracker() -> MetricsTracker {
+    #[cfg(feature = "database")]
+    if let Some(db) = crate::database::database().await {
+        if let Some(manager) = db.read().await.as_ref() {
+            let config = crate::config::config()
+                .map(|c| c.metrics.clone())
+                .unwrap_or_default();
+            return MetricsTracker::with_storage(MetricsStorage::with_database(
+                manager.pool().clone(),
+                config,
+            ));
+        }
+    }
+    MetricsTracker::new()
+}
+
 pub struct MetricsCommand {
     pub hours: u64,
     pub detailed: bool,
@@ -29,7 +48,7 @@ impl MetricsCommand {
     }
 
     pub async fn execute(&self) -> Result<()> {
-        let tracker = MetricsTracker::new();
+        let tracker = tracker().await;
 
         if self.ci_mode {
             self.execute_ci_mode(&tracker).await
@@ -44,6 +63,10 @@ impl MetricsCommand {
         println!();
         println!("⏰ Time window: {} hours", self.hours);
         println!("📈 Detailed: {}", self.detailed);
+        println!(
+            "🗃️  Source: {}",
+            tracker.describe_source(Some(self.hours)).await
+        );
         println!();
 
         match tracker.calculate_metrics(Some(self.hours)).await {
@@ -102,7 +125,7 @@ impl ExportMetricsCommand {
     }
 
     pub async fn execute(&self) -> Result<()> {
-        let tracker = MetricsTracker::new();
+        let tracker = tracker().await;
 
         if self.ci_mode {
             self.execute_ci_mode(&tracker).await
@@ -121,6 +144,10 @@ impl ExportMetricsCommand {
         } else {
             println!("📁 Output: stdout");
         }
+        println!(
+            "🗃️  Source: {}",
+            tracker.describe_source(Some(self.hours)).await
+        );
         println!();
 
         self.export_metrics(&tracker).await
@@ -4,17 +4,38 @@ use anyhow::Result;
 pub mod actions;
 pub mod agent;
 pub mod bundle;
+#[cfg(feature = "database")]
+pub mod db;
 pub mod doctor;
+pub mod history;
 pub mod init;
 pub mod land;
+pub mod merge;
 #[cfg(feature = "metrics")]
 pub mod metrics;
 pub mod peek;
 pub mod pop;
+pub mod preflight;
 pub mod reset;
 pub mod route;
+#[cfg(feature = "autonomous")]
+pub mod run;
+#[cfg(feature = "autonomous")]
+pub mod state;
 pub mod status;
 
+/// Human-readable size, e.g. `12.0 KiB`
+#[cfg(any(feature = "database", feature = "autonomous"))]
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
 #[allow(async_fn_in_trait)]
 pub trait Command {
     async fn execute(&self) -> Result<()>;
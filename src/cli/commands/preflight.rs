@@ -0,0 +1,123 @@
+use crate::agent_lifecycle::detector::{detect_label_mismatch, PreflightDetector, PreflightReport};
+use crate::agent_lifecycle::executor::PreflightExecutor;
+use crate::agent_lifecycle::types::parse_agent_branch;
+use crate::cli::DoctorFormat;
+use crate::github::GitHubClient;
+use anyhow::{anyhow, Result};
+use git2::Repository;
+
+pub struct PreflightCommand {
+    pub format: DoctorFormat,
+    pub branch: Option<String>,
+    pub fix: bool,
+    pub ci_mode: bool,
+}
+
+impl PreflightCommand {
+    pub fn new(format: DoctorFormat) -> Self {
+        Self {
+            format,
+            branch: None,
+            fix: false,
+            ci_mode: false,
+        }
+    }
+
+    pub fn with_branch(mut self, branch: Option<String>) -> Self {
+        self.branch = branch;
+        self
+    }
+
+    pub fn with_fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    pub fn with_ci_mode(mut self, ci_mode: bool) -> Self {
+        self.ci_mode = ci_mode;
+        self
+    }
+
+    pub async fn execute(&self) -> Result<()> {
+        let report = self.run().await?;
+
+        match self.format {
+            DoctorFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            DoctorFormat::Text => {
+                print!("{}", report.format_text());
+                if self.fix && !report.findings.is_empty() && report.is_clean() {
+                    println!("   ✅ Every issue was fixed");
+                } else if !self.fix && report.findings.iter().any(|f| f.fix.is_some()) {
+                    println!();
+                    println!("💡 Run 'my-little-soda preflight --fix' to apply the safe fixes");
+                }
+            }
+        }
+
+        let blocking = report.blocking().len();
+        if blocking > 0 {
+            return Err(anyhow!("{} pre-flight error(s) unresolved", blocking));
+        }
+        Ok(())
+    }
+
+    /// Detect issues on the branch (and fix them with `--fix`)
+    pub async fn run(&self) -> Result<PreflightReport> {
+        let repo = Repository::open(".")?;
+        let branch = match &self.branch {
+            Some(branch) => branch.clone(),
+            None => repo
+                .head()?
+                .shorthand()
+                .ok_or_else(|| anyhow!("Could not determine current branch name"))?
+                .to_string(),
+        };
+        let (agent_id, issue_number) = parse_agent_branch(&branch)
+            .filter(|(agent_id, _)| agent_id.starts_with("agent"))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Branch '{}' is not an agent branch. Expected format: agent001/123 or agent001/123-description",
+                    branch
+                )
+            })?;
+
+        let base_branch = crate::config::base_branch();
+        let mut issues = PreflightDetector::new(&repo)
+            .with_base_branch(&base_branch)
+            .detect(&branch)?;
+
+        // Label state lives on GitHub; without access the git checks still stand
+        let client = match GitHubClient::with_verbose(false) {
+            Ok(client) => {
+                match client.fetch_issue(issue_number).await {
+                    Ok(issue) => {
+                        let labels: Vec<String> =
+                            issue.labels.into_iter().map(|l| l.name).collect();
+                        issues.extend(detect_label_mismatch(&agent_id, &labels));
+                    }
+                    Err(e) => {
+                        println!("⚠️  Skipping label check, could not read #{issue_number}: {e}")
+                    }
+                }
+                Some(client)
+            }
+            Err(e) => {
+                println!("⚠️  Skipping label check, GitHub unavailable: {e}");
+                None
+            }
+        };
+
+        let mut report = PreflightReport::new(&branch, &agent_id, issue_number, issues);
+        if self.fix {
+            let workdir = repo
+                .workdir()
+                .ok_or_else(|| anyhow!("Pre-flight fixes need a working directory"))?;
+            let executor = PreflightExecutor::new(workdir).with_base_branch(&base_branch);
+            match &client {
+                Some(client) => executor.apply(&mut report, client).await,
+                None => executor.apply_git_fixes(&mut report),
+            }
+        }
+        Ok(report)
+    }
+}
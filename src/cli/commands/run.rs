@@ -0,0 +1,294 @@
+//! Unattended agent operation
+//!
+//! `run` keeps one agent busy without anyone at the keyboard: it pops tasks,
+//! starts the agent configured under `[agents.process_management]` on each,
+//! and bottles or escalates the result (see [`crate::autonomous::runner`]).
+//! State is checkpointed through the `[agents.work_continuity]` persistence,
+//! so SIGINT or SIGTERM stop it cleanly and the next `run` resumes the task.
+
+use crate::agents::AgentRouter;
+use crate::autonomous::persistence::{PersistenceConfig, StatePersistenceManager};
+use crate::autonomous::runner::{
+    branch_progress, AgentLauncher, AutonomousRunner, BranchProgress, RunBackend, RunLimits,
+    RunSummary, RunTask, StopReason,
+};
+use crate::autonomous::{
+    AutonomousWorkflowState, CorrectionAction, Issue, Priority, RecoveryRules, StateDriftDetector,
+};
+use crate::cli::commands::land::LandCommand;
+use crate::cli::commands::Command;
+use crate::config::EscalationConfig;
+use crate::git::{Git2Operations, GitOperations};
+use crate::github::GitHubClient;
+use crate::shutdown::shutdown_signal;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use git2::{BranchType, Repository};
+use std::path::Path;
+use tokio::sync::{watch, Mutex};
+use tracing::warn;
+
+/// Remote agent branches are pushed to
+const REMOTE: &str = "origin";
+
+pub struct RunCommand {
+    agent: String,
+    max_hours: Option<u8>,
+    until_idle: bool,
+}
+
+impl RunCommand {
+    pub fn new(agent: String, max_hours: Option<u8>, until_idle: bool) -> Self {
+        Self {
+            agent,
+            max_hours,
+            until_idle,
+        }
+    }
+}
+
+impl Command for RunCommand {
+    async fn execute(&self) -> Result<()> {
+        let config = crate::config::config().cloned().unwrap_or_default();
+        let repo_root = Repository::open(".")?
+            .workdir()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| anyhow!("`run` needs a repository with a work tree"))?;
+        let launcher =
+            AgentLauncher::from_config(&config.agents.process_management).with_work_dir(repo_root);
+        launcher.ensure_enabled()?;
+        let rules = RecoveryRules::from_config(&config.recovery)?;
+
+        let mut limits = RunLimits {
+            stop_when_idle: self.until_idle,
+            ..RunLimits::default()
+        };
+        if let Some(max_hours) = self.max_hours {
+            limits.max_work_hours = max_hours;
+        }
+        let persistence = StatePersistenceManager::new(PersistenceConfig::from_work_continuity(
+            &config.agents.work_continuity,
+        ));
+        let base_branch = config.github.base_branch.clone();
+        let backend = GitHubRunBackend::new(&self.agent, &base_branch).await?;
+
+        let (stop, stopped) = watch::channel(false);
+        tokio::spawn(async move {
+            match shutdown_signal().await {
+                Ok(signal) => {
+                    println!("🛑 {signal} received, checkpointing and stopping...");
+                    let _ = stop.send(true);
+                }
+                Err(e) => warn!("Could not listen for shutdown signals: {}", e),
+            }
+        });
+
+        println!(
+            "🤖 Running {} unattended for up to {} hours (Ctrl-C to stop)",
+            self.agent, limits.max_work_hours
+        );
+        let summary =
+            AutonomousRunner::new(self.agent.clone(), Box::new(backend), launcher, persistence)
+                .with_limits(limits)
+                .with_recovery_rules(rules)
+                .with_base_branch(&base_branch)
+                .run(stopped)
+                .await?;
+        print!("{}", format_summary(&summary));
+        Ok(())
+    }
+}
+
+/// Comment explaining to the people in `target` why the agent stopped
+fn escalation_comment(
+    agent_id: &str,
+    branch: &str,
+    reason: &str,
+    target: &EscalationConfig,
+) -> String {
+    let mut comment = format!(
+        "🙋 **{agent_id} needs a human**\n\n\
+        The unattended run stopped working on this issue: {reason}\n\n\
+        Work so far is on `{branch}`."
+    );
+    if !target.labels.is_empty() {
+        let labels: Vec<String> = target.labels.iter().map(|l| format!("`{l}`")).collect();
+        comment.push_str(&format!(
+            " The issue is labelled {} until someone picks it up.",
+            labels.join(", ")
+        ));
+    }
+    if !target.mention.is_empty() {
+        comment.push_str(&format!("\n\ncc {}", target.mention.join(" ")));
+    }
+    comment
+}
+
+pub fn format_summary(summary: &RunSummary) -> String {
+    let mut text = String::new();
+    if let Some(issue) = summary.resumed {
+        text.push_str(&format!(
+            "↩️  Resumed issue #{issue} from the last checkpoint\n"
+        ));
+    }
+    let issues = |numbers: &[u64]| {
+        numbers
+            .iter()
+            .map(|n| format!("#{n}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if !summary.bottled.is_empty() {
+        text.push_str(&format!("🍼 Bottled {}\n", issues(&summary.bottled)));
+    }
+    if !summary.escalated.is_empty() {
+        text.push_str(&format!(
+            "🙋 Escalated {} to a human\n",
+            issues(&summary.escalated)
+        ));
+    }
+    text.push_str(match summary.stopped {
+        StopReason::Requested => "⏹️  Stopped on request; `run` resumes from the checkpoint\n",
+        StopReason::OutOfTime => "⏰ Work hours used up; `run` resumes from the checkpoint\n",
+        StopReason::Idle => "✅ No more tasks available\n",
+    });
+    text
+}
+
+/// The runner's view of GitHub and the repository in the current directory
+struct GitHubRunBackend {
+    client: GitHubClient,
+    router: AgentRouter,
+    drift: Mutex<StateDriftDetector>,
+    /// Branch new agent branches start from, from `[github] base_branch`
+    base_branch: String,
+}
+
+impl GitHubRunBackend {
+    async fn new(agent_id: &str, base_branch: &str) -> Result<Self> {
+        let client = GitHubClient::new()?;
+        Ok(Self {
+            base_branch: base_branch.to_string(),
+            router: AgentRouter::new().await?,
+            drift: Mutex::new(StateDriftDetector::new(
+                client.clone(),
+                agent_id.to_string(),
+            )),
+            client,
+        })
+    }
+}
+
+/// Check out `branch`, creating it from the remote branch or the base when it is not local
+fn check_out(branch: &str, base_branch: &str) -> Result<()> {
+    let git = Git2Operations::new(".")?;
+    if let Err(e) = git.fetch(REMOTE) {
+        warn!("Could not fetch {}: {}", REMOTE, e);
+    }
+    let repo = Repository::open(".")?;
+    if repo.find_branch(branch, BranchType::Local).is_err() {
+        let start = repo
+            .refname_to_id(&format!("refs/remotes/{REMOTE}/{branch}"))
+            .or_else(|_| {
+                crate::agent_lifecycle::rehydration::base_tip(&repo, REMOTE, base_branch)
+            })?;
+        repo.branch(branch, &repo.find_commit(start)?, false)?;
+    }
+    git.checkout_branch(branch)
+}
+
+/// The autonomous workflow's view of a GitHub issue
+fn workflow_issue(issue: &octocrab::models::issues::Issue) -> Issue {
+    let labels: Vec<String> = issue.labels.iter().map(|l| l.name.clone()).collect();
+    let priority = match crate::priority::Priority::from_labels(&labels) {
+        crate::priority::Priority::Unblocker => Priority::Critical,
+        crate::priority::Priority::MergeReady
+        | crate::priority::Priority::VeryHigh
+        | crate::priority::Priority::High => Priority::High,
+        crate::priority::Priority::Medium | crate::priority::Priority::Normal => Priority::Medium,
+        crate::priority::Priority::Low => Priority::Low,
+    };
+    Issue {
+        number: issue.number,
+        title: issue.title.clone(),
+        body: issue.body.clone().unwrap_or_default(),
+        labels,
+        priority,
+        estimated_hours: None,
+    }
+}
+
+#[async_trait(?Send)]
+impl RunBackend for GitHubRunBackend {
+    async fn pop_task(&self, _agent_id: &str) -> Result<Option<RunTask>> {
+        let Some(assignment) = self.router.pop_any_available_task().await? else {
+            return Ok(None);
+        };
+        check_out(&assignment.branch_name, &self.base_branch)?;
+        Ok(Some(RunTask {
+            issue: workflow_issue(&assignment.issue),
+            branch: assignment.branch_name,
+        }))
+    }
+
+    async fn resume_task(&self, task: &RunTask) -> Result<()> {
+        check_out(&task.branch, &self.base_branch)
+    }
+
+    async fn progress(&self, task: &RunTask) -> Result<BranchProgress> {
+        branch_progress(
+            &Repository::open(".")?,
+            &task.branch,
+            REMOTE,
+            &self.base_branch,
+        )
+    }
+
+    async fn check_drift(&self, state: &AutonomousWorkflowState) -> Result<Option<String>> {
+        let mut detector = self.drift.lock().await;
+        if !detector.needs_validation(true) {
+            return Ok(None);
+        }
+        detector.update_expected_state(state).await?;
+        let drifts = detector.validate_state().await?;
+        if drifts.is_empty() {
+            return Ok(None);
+        }
+        let corrections = detector.correct_drifts(drifts).await?;
+        Ok(corrections
+            .into_iter()
+            .find_map(|correction| match correction {
+                CorrectionAction::RequireManualIntervention { reason, .. } => Some(reason),
+                _ => None,
+            }))
+    }
+
+    async fn bottle(&self, _task: &RunTask) -> Result<()> {
+        // The task's branch is checked out, which is what `land` works on
+        LandCommand::new(false, 0, false, false).execute().await
+    }
+
+    async fn escalate(
+        &self,
+        task: &RunTask,
+        reason: &str,
+        target: &EscalationConfig,
+    ) -> Result<()> {
+        let issue = task.issue.number;
+        let agent_id = task.branch.split('/').next().unwrap_or_default();
+        if target.comment {
+            let comment = escalation_comment(agent_id, &task.branch, reason, target);
+            self.client
+                .comments
+                .create_issue_comment(issue, &comment)
+                .await?;
+        }
+        for label in &target.labels {
+            self.client.add_label_to_issue(issue, label).await?;
+        }
+        if let Err(e) = self.client.remove_label_from_issue(issue, agent_id).await {
+            warn!("Could not remove {} from issue #{}: {}", agent_id, issue, e);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,474 @@
+//! Autonomous workflow state maintenance
+//!
+//! `state gc` applies the checkpoint retention configured under
+//! `[agents.work_continuity]` on demand: checkpoints older than
+//! `backup_retention_days` or beyond `max_checkpoints_per_agent` are deleted
+//! and older ones are compressed. `--dry-run` reports the same without
+//! touching anything.
+//!
+//! `state export` and `state import` move an agent to another machine with
+//! its state, checkpoints and unpushed commits (see
+//! [`crate::autonomous::state_archive`]).
+
+use crate::agent_lifecycle::state_machine::AgentStateMachine;
+use crate::autonomous::persistence::{
+    create_persistence, CleanupPlan, PersistenceConfig, StatePersistence,
+};
+use crate::autonomous::state_archive::{
+    validate_against_github, AgentWorkspace, BranchRestore, GitHubFacts, StateArchive,
+};
+use crate::autonomous::work_continuity::{
+    ResumeAction, WorkContinuityConfig, WorkContinuityManager,
+};
+use crate::cli::commands::{format_bytes, Command};
+use crate::github::GitHubClient;
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Remote the agent's branches are pushed to
+const REMOTE: &str = "origin";
+
+/// Persistence for the configured `[agents.work_continuity]` backend
+fn configured_persistence() -> Arc<dyn StatePersistence + Send + Sync> {
+    create_persistence(&PersistenceConfig::from_work_continuity(
+        &configured_continuity(),
+    ))
+}
+
+/// The configured backend with its checkpoints in `dir`
+fn persistence_in(dir: PathBuf) -> Arc<dyn StatePersistence + Send + Sync> {
+    create_persistence(&PersistenceConfig {
+        persistence_directory: dir,
+        ..PersistenceConfig::from_work_continuity(&configured_continuity())
+    })
+}
+
+fn configured_continuity() -> crate::config::WorkContinuityConfig {
+    crate::config::config()
+        .map(|c| c.agents.work_continuity.clone())
+        .unwrap_or_default()
+}
+
+pub struct StateGcCommand {
+    dry_run: bool,
+    agent: Option<String>,
+    persistence: Option<Arc<dyn StatePersistence + Send + Sync>>,
+}
+
+impl StateGcCommand {
+    pub fn new(dry_run: bool, agent: Option<String>) -> Self {
+        Self {
+            dry_run,
+            agent,
+            persistence: None,
+        }
+    }
+
+    /// Checkpoints in `dir` instead of the configured directory
+    pub fn with_state_dir(self, dir: Option<PathBuf>) -> Self {
+        match dir {
+            Some(dir) => self.with_persistence(persistence_in(dir)),
+            None => self,
+        }
+    }
+
+    pub fn with_persistence(
+        mut self,
+        persistence: Arc<dyn StatePersistence + Send + Sync>,
+    ) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    fn persistence(&self) -> Arc<dyn StatePersistence + Send + Sync> {
+        self.persistence
+            .clone()
+            .unwrap_or_else(configured_persistence)
+    }
+
+    /// What was (or, for a dry run, would be) cleaned up for every agent
+    pub async fn run(&self) -> Result<Vec<CleanupPlan>> {
+        let persistence = self.persistence();
+        let agents = match &self.agent {
+            Some(agent) => vec![agent.clone()],
+            None => persistence.list_agents().await?,
+        };
+
+        let mut plans = Vec::new();
+        for agent in agents {
+            plans.push(if self.dry_run {
+                persistence.plan_cleanup(&agent).await?
+            } else {
+                persistence.cleanup_old_data(&agent).await?
+            });
+        }
+        Ok(plans)
+    }
+}
+
+impl Command for StateGcCommand {
+    async fn execute(&self) -> Result<()> {
+        let plans = self.run().await?;
+        print!("{}", format_plans(&plans, self.dry_run));
+        Ok(())
+    }
+}
+
+pub fn format_plans(plans: &[CleanupPlan], dry_run: bool) -> String {
+    if plans.is_empty() {
+        return "No autonomous workflow state found\n".to_string();
+    }
+    let (delete, compress) = if dry_run {
+        ("would delete", "would compress")
+    } else {
+        ("deleted", "compressed")
+    };
+
+    let mut text = String::new();
+    for plan in plans {
+        let mut actions = Vec::new();
+        let deleted: Vec<String> = [
+            (plan.expired.iter(), "expired"),
+            (plan.over_limit.iter(), "over the limit"),
+        ]
+        .into_iter()
+        .filter_map(|(checkpoints, why)| {
+            let (count, bytes) =
+                checkpoints.fold((0, 0), |(count, bytes), c| (count + 1, bytes + c.bytes));
+            (count > 0).then(|| format!("{count} {why} ({})", format_bytes(bytes)))
+        })
+        .collect();
+        if !deleted.is_empty() {
+            actions.push(format!("{delete} {}", deleted.join(" and ")));
+        }
+        if !plan.to_compress.is_empty() {
+            actions.push(format!(
+                "{compress} {} ({})",
+                plan.to_compress.len(),
+                format_bytes(plan.compress_bytes())
+            ));
+        }
+        if actions.is_empty() {
+            actions.push("nothing to clean up".to_string());
+        }
+        text.push_str(&format!("{}: {}\n", plan.agent_id, actions.join("; ")));
+    }
+
+    let freed: u64 = plans.iter().map(CleanupPlan::deleted_bytes).sum();
+    let compressed: usize = plans.iter().map(|p| p.to_compress.len()).sum();
+    let compressed_from: u64 = plans.iter().map(CleanupPlan::compress_bytes).sum();
+    if dry_run {
+        text.push_str(&format!(
+            "🧹 Would free {} and compress {compressed} checkpoint(s) ({})\n",
+            format_bytes(freed),
+            format_bytes(compressed_from)
+        ));
+    } else {
+        let compressed_to: u64 = plans.iter().filter_map(|p| p.compressed_bytes).sum();
+        text.push_str(&format!(
+            "🧹 Freed {}; compressed {compressed} checkpoint(s) from {} to {}\n",
+            format_bytes(freed),
+            format_bytes(compressed_from),
+            format_bytes(compressed_to)
+        ));
+    }
+    text
+}
+
+pub struct StateExportCommand {
+    agent: String,
+    output: Option<PathBuf>,
+    branch: Option<String>,
+    workspace: PathBuf,
+    persistence: Option<Arc<dyn StatePersistence + Send + Sync>>,
+}
+
+impl StateExportCommand {
+    pub fn new(agent: String, output: Option<PathBuf>) -> Self {
+        Self {
+            agent,
+            output,
+            branch: None,
+            workspace: PathBuf::from("."),
+            persistence: None,
+        }
+    }
+
+    pub fn with_branch(mut self, branch: Option<String>) -> Self {
+        self.branch = branch;
+        self
+    }
+
+    pub fn with_workspace(mut self, workspace: PathBuf) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Checkpoints in `dir` instead of the configured directory
+    pub fn with_state_dir(self, dir: Option<PathBuf>) -> Self {
+        match dir {
+            Some(dir) => self.with_persistence(persistence_in(dir)),
+            None => self,
+        }
+    }
+
+    pub fn with_persistence(
+        mut self,
+        persistence: Arc<dyn StatePersistence + Send + Sync>,
+    ) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    fn output(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| {
+            PathBuf::from(format!(
+                "{}-state-{}.tar.gz",
+                self.agent,
+                Utc::now().format("%Y%m%d-%H%M%S")
+            ))
+        })
+    }
+
+    /// Write the archive, returning where it went and what it holds
+    pub async fn run(&self) -> Result<(PathBuf, StateArchive)> {
+        let persistence = self
+            .persistence
+            .clone()
+            .unwrap_or_else(configured_persistence);
+        let workspace = AgentWorkspace::open(&self.workspace, REMOTE)?;
+        let archive = StateArchive::export(
+            &self.agent,
+            persistence.as_ref(),
+            &workspace,
+            self.branch.clone(),
+        )
+        .await?;
+        let output = self.output();
+        archive.write_to(&output)?;
+        Ok((output, archive))
+    }
+}
+
+impl Command for StateExportCommand {
+    async fn execute(&self) -> Result<()> {
+        let (output, archive) = self.run().await?;
+        let manifest = &archive.manifest;
+        println!(
+            "📦 Exported {} to {} ({})",
+            manifest.agent_id,
+            output.display(),
+            format_bytes(std::fs::metadata(&output)?.len())
+        );
+        if let Some(branch) = &manifest.branch {
+            println!(
+                "   Branch {branch} with {} unpushed commit(s)",
+                manifest.unpushed_commits
+            );
+        }
+        println!(
+            "   {} checkpoint(s){}",
+            manifest.checkpoints,
+            if archive.workflow_state.is_some() {
+                " and the current workflow state"
+            } else {
+                ""
+            }
+        );
+        if archive.agent_state.workspace_state.uncommitted_changes {
+            println!("⚠️  Uncommitted changes are not included; commit them and export again to carry them over");
+        }
+        println!(
+            "💡 Run 'my-little-soda state import {}' on the other machine",
+            output.display()
+        );
+        Ok(())
+    }
+}
+
+/// What importing an archive did
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportOutcome {
+    /// Validation problems that were overridden with `--force`
+    pub overridden: Vec<String>,
+    pub branch: Option<BranchRestore>,
+    pub checkpoints: usize,
+}
+
+pub struct StateImportCommand {
+    archive: PathBuf,
+    force: bool,
+    workspace: PathBuf,
+    persistence: Option<Arc<dyn StatePersistence + Send + Sync>>,
+}
+
+impl StateImportCommand {
+    pub fn new(archive: PathBuf, force: bool) -> Self {
+        Self {
+            archive,
+            force,
+            workspace: PathBuf::from("."),
+            persistence: None,
+        }
+    }
+
+    pub fn with_workspace(mut self, workspace: PathBuf) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Checkpoints in `dir` instead of the configured directory
+    pub fn with_state_dir(self, dir: Option<PathBuf>) -> Self {
+        match dir {
+            Some(dir) => self.with_persistence(persistence_in(dir)),
+            None => self,
+        }
+    }
+
+    pub fn with_persistence(
+        mut self,
+        persistence: Arc<dyn StatePersistence + Send + Sync>,
+    ) -> Self {
+        self.persistence = Some(persistence);
+        self
+    }
+
+    /// Check the archive against `facts`, then restore the branch and persisted state
+    ///
+    /// Refuses on validation problems, or when the agent already has state
+    /// here, unless `--force` was given.
+    pub async fn restore(
+        &self,
+        archive: &StateArchive,
+        facts: &GitHubFacts,
+    ) -> Result<ImportOutcome> {
+        let manifest = &archive.manifest;
+        let problems = validate_against_github(manifest, facts);
+        if !problems.is_empty() && !self.force {
+            bail!(
+                "{} cannot be imported as is:\n  - {}\nPass --force to import anyway",
+                self.archive.display(),
+                problems.join("\n  - ")
+            );
+        }
+
+        let persistence = self
+            .persistence
+            .clone()
+            .unwrap_or_else(configured_persistence);
+        if !self.force && persistence.load_state(&manifest.agent_id).await?.is_some() {
+            bail!(
+                "{} already has workflow state on this machine; pass --force to replace it",
+                manifest.agent_id
+            );
+        }
+
+        let workspace = AgentWorkspace::open(&self.workspace, REMOTE)?;
+        let branch = match &manifest.branch {
+            Some(_) => {
+                if let Err(e) = workspace.fetch() {
+                    warn!(error = %e, "Could not fetch before restoring the agent branch");
+                }
+                Some(workspace.restore_branch(manifest, archive.git_bundle.as_deref())?)
+            }
+            None => None,
+        };
+        let checkpoints = archive.restore_workflow_state(persistence.as_ref()).await?;
+
+        Ok(ImportOutcome {
+            overridden: problems,
+            branch,
+            checkpoints,
+        })
+    }
+
+    async fn github_facts(github: &GitHubClient, archive: &StateArchive) -> Result<GitHubFacts> {
+        let mut facts = GitHubFacts {
+            repository: format!("{}/{}", github.owner(), github.repo()),
+            ..GitHubFacts::default()
+        };
+        if let Some(issue) = archive.manifest.issue {
+            match github.fetch_issue(issue).await {
+                Ok(issue) => {
+                    facts.issue_open = Some(issue.state == octocrab::models::IssueState::Open);
+                    facts.issue_labels = issue.labels.into_iter().map(|l| l.name).collect();
+                }
+                Err(e) => warn!(issue = %issue, error = %e, "Could not fetch the exported issue"),
+            }
+        }
+        if let Some(branch) = &archive.manifest.branch {
+            facts.branch_on_remote = github.branch_exists(branch).await?;
+        }
+        Ok(facts)
+    }
+}
+
+impl Command for StateImportCommand {
+    async fn execute(&self) -> Result<()> {
+        let archive = StateArchive::read_from(&self.archive)?;
+        let manifest = &archive.manifest;
+        println!(
+            "📥 Importing {} exported from {} at {}",
+            manifest.agent_id,
+            manifest.hostname,
+            manifest.exported_at.format("%Y-%m-%d %H:%M UTC")
+        );
+
+        let github = GitHubClient::with_verbose(false)
+            .map_err(|e| anyhow!("GitHub is needed to validate the import: {e}"))?;
+        let facts = Self::github_facts(&github, &archive).await?;
+        let outcome = self.restore(&archive, &facts).await?;
+        for problem in &outcome.overridden {
+            println!("⚠️  {problem} (imported anyway)");
+        }
+        match &outcome.branch {
+            Some(BranchRestore::FromBundle { branch, head }) => println!(
+                "   Restored {branch} at {head} with {} unpushed commit(s)",
+                manifest.unpushed_commits
+            ),
+            Some(BranchRestore::FromRemote { branch, head }) => {
+                println!("   Restored {branch} at {head} from {REMOTE}")
+            }
+            Some(BranchRestore::AlreadyPresent { branch, .. }) => {
+                println!("   {branch} was already here at the exported commit")
+            }
+            None => {}
+        }
+        println!("   Restored {} checkpoint(s)", outcome.checkpoints);
+
+        // Hand the work back to continuity, as if the agent had restarted here
+        let continuity = configured_continuity();
+        let manager = WorkContinuityManager::new(
+            WorkContinuityConfig::from_config(&continuity),
+            github,
+            PersistenceConfig::from_work_continuity(&continuity),
+        );
+        let mut agent = AgentStateMachine::new(manifest.agent_id.clone());
+        agent.current_issue = manifest.issue;
+        agent.current_branch = manifest.branch.clone();
+        agent.commits_ahead = manifest.unpushed_commits as u32;
+        let action = manager
+            .adopt_imported_state(archive.agent_state.clone())
+            .await?;
+        let summary = match &action {
+            ResumeAction::ContinueWork { issue, branch, .. } => {
+                format!("continuing issue #{} on {branch}", issue.number)
+            }
+            ResumeAction::CompletePartialOperation { operation } => {
+                format!("completing {:?}", operation.operation_type)
+            }
+            ResumeAction::RecoverFromError { context } => {
+                format!("recovering from {}", context.last_error)
+            }
+            ResumeAction::ValidateAndResync { reason } | ResumeAction::StartFresh { reason } => {
+                reason.clone()
+            }
+        };
+        manager.resume_interrupted_work(action, &mut agent).await?;
+        println!("✅ {} resumed: {summary}", manifest.agent_id);
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::agents::integrator::MergeMethod;
+use crate::bundling::git_ops::{AssemblyMode, ConflictStrategy};
 use clap::{Parser, Subcommand, ValueEnum};
 
 pub mod commands;
@@ -109,6 +111,15 @@ pub enum Commands {
         /// Show detailed information about the scan process
         #[arg(long, short = 'v', help = "Show detailed scan information")]
         verbose: bool,
+        /// Bottle even when pre-flight checks report errors
+        #[arg(long, help = "Bottle despite unresolved pre-flight errors")]
+        force: bool,
+        /// Open the suggested split as follow-up issues when the branch is too large
+        #[arg(
+            long,
+            help = "Open supertask-decomposition issues for the suggested split of an oversized branch"
+        )]
+        split: bool,
     },
     /// Bundle multiple completed branches into a single PR for efficient review
     Bundle {
@@ -127,6 +138,130 @@ pub enum Commands {
             help = "Display bundling system diagnostics and troubleshooting information"
         )]
         diagnose: bool,
+        /// How to handle cherry-pick conflicts (overrides config)
+        #[arg(
+            long,
+            value_enum,
+            help = "Conflict handling: individual-fallback, skip-conflicts or manual-resolve"
+        )]
+        conflict_strategy: Option<ConflictStrategy>,
+        /// How agent branches are applied onto the bundle (overrides config)
+        #[arg(
+            long,
+            value_enum,
+            help = "Bundle assembly: cherry-pick, squash (one commit per issue) or rebase"
+        )]
+        assembly_mode: Option<AssemblyMode>,
+        /// Resume a bundle paused for manual conflict resolution
+        #[arg(
+            long = "continue",
+            conflicts_with = "abort",
+            help = "Continue a bundle paused by manual-resolve after resolving the conflicts"
+        )]
+        continue_bundle: bool,
+        /// Abandon a bundle paused for manual conflict resolution
+        #[arg(long, help = "Abort a paused bundle and delete its bundle branch")]
+        abort: bool,
+        /// Resume a bundle interrupted before it finished
+        #[arg(
+            long,
+            conflicts_with_all = ["continue_bundle", "abort"],
+            help = "Resume an interrupted bundle from its last completed step (requires database)"
+        )]
+        resume: bool,
+        /// List past bundles and their outcomes
+        #[arg(
+            long,
+            help = "Show recorded bundles and their outcomes (requires database)"
+        )]
+        history: bool,
+        /// Rebuild an open bundle PR without the issue given by --drop
+        #[arg(
+            long,
+            value_name = "PR",
+            requires = "drop",
+            conflicts_with_all = ["continue_bundle", "abort", "resume"],
+            help = "Rebuild an open bundle PR, force-pushing it without the --drop issue"
+        )]
+        rebuild: Option<u64>,
+        /// Issue to remove from the bundle being rebuilt
+        #[arg(
+            long,
+            value_name = "ISSUE",
+            requires = "rebuild",
+            help = "Issue to drop from the rebuilt bundle; it returns to route:ready"
+        )]
+        drop: Option<u64>,
+    },
+    /// Merge approved bundle and agent PRs with green checks, then clean up after them
+    Merge {
+        /// Only consider this PR
+        #[arg(long, value_name = "PR", help = "Merge only this bundle or agent PR")]
+        pr: Option<u64>,
+        /// Merge method for every PR (overrides config)
+        #[arg(
+            long,
+            value_enum,
+            help = "Merge method: squash, merge or rebase (overrides [merge] config)"
+        )]
+        method: Option<MergeMethod>,
+        /// Keep polling and merge PRs as they become ready
+        #[arg(long, help = "Keep running, merging PRs as they become ready")]
+        watch: bool,
+        /// Seconds between polls in watch mode
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            requires = "watch",
+            help = "Seconds between polls with --watch (default from [merge] config)"
+        )]
+        interval: Option<u64>,
+        /// Show what would be merged without merging
+        #[arg(
+            long,
+            help = "Preview which PRs would be merged without changing anything"
+        )]
+        dry_run: bool,
+    },
+    /// Check an agent branch for problems that would break bottling or bundling
+    Preflight {
+        /// Branch to check (defaults to the current branch)
+        #[arg(long, help = "Agent branch to check instead of the current branch")]
+        branch: Option<String>,
+        /// Apply safe fixes: push, rebase onto main, restore labels
+        #[arg(long, help = "Push, rebase and relabel automatically where it is safe")]
+        fix: bool,
+        /// Output format
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format: text for human-readable, json for machine-readable"
+        )]
+        format: DoctorFormat,
+    },
+    /// Show recorded agent lifecycle events
+    History {
+        /// Only events of this agent
+        #[arg(long, help = "Only show events of this agent (e.g., agent001)")]
+        agent: Option<String>,
+        /// Only events concerning this issue
+        #[arg(long, help = "Only show events concerning this issue number")]
+        issue: Option<u64>,
+        /// Only events after this point
+        #[arg(
+            long,
+            help = "Only show events since an age (30m, 12h, 7d) or a date/time (2024-05-01)"
+        )]
+        since: Option<String>,
+        /// Output format
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format: text, json or csv"
+        )]
+        format: HistoryFormat,
     },
     /// Preview the next task in queue without claiming it
     Peek,
@@ -202,6 +337,52 @@ pub enum Commands {
         #[command(subcommand)]
         command: AgentCommands,
     },
+    /// Inspect and maintain the state database
+    #[cfg(feature = "database")]
+    Db {
+        /// Database to work on instead of the configured one
+        #[arg(
+            long,
+            global = true,
+            help = "Database URL to use instead of [database] url (e.g., sqlite://backup.db)"
+        )]
+        url: Option<String>,
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+    /// Work through tasks unattended with the configured agent
+    #[cfg(feature = "autonomous")]
+    Run {
+        /// Agent to run as
+        #[arg(
+            long,
+            default_value = "agent001",
+            help = "Agent to run as (e.g., agent001)"
+        )]
+        agent: String,
+        /// Hours to work before stopping
+        #[arg(long, help = "Hours to work before stopping (defaults to 8)")]
+        max_hours: Option<u8>,
+        /// Stop once the queue is empty
+        #[arg(
+            long,
+            help = "Stop when no task is available instead of waiting for new ones"
+        )]
+        until_idle: bool,
+    },
+    /// Maintain persisted autonomous workflow state
+    #[cfg(feature = "autonomous")]
+    State {
+        /// Directory holding the checkpoints instead of the configured one
+        #[arg(
+            long,
+            global = true,
+            help = "State directory to use instead of [agents.work_continuity] state_file_path"
+        )]
+        state_dir: Option<std::path::PathBuf>,
+        #[command(subcommand)]
+        command: StateCommands,
+    },
     /// Run system diagnostics and health checks
     Doctor {
         /// Output format for diagnostic results
@@ -250,6 +431,40 @@ pub enum AgentCommands {
         /// Show what would be recovered without making changes
         #[arg(long, help = "Preview recovery actions without making changes")]
         dry_run: bool,
+        /// Show which recovery rule fires for an error instead of recovering
+        #[arg(
+            long,
+            value_name = "ERROR_TYPE",
+            help = "Explain the recovery rule for an error type (e.g. github_api, merge_conflict, ci_failure)"
+        )]
+        explain: Option<String>,
+        /// HTTP status of the error to explain
+        #[arg(long, requires = "explain", help = "Status code of the error")]
+        status: Option<u16>,
+        /// What failed, e.g. the git operation or CI job
+        #[arg(
+            long,
+            requires = "explain",
+            help = "What failed: git operation, API endpoint, CI job, build stage, ..."
+        )]
+        subject: Option<String>,
+        /// Message of the error to explain
+        #[arg(long, requires = "explain", help = "Error message")]
+        message: Option<String>,
+        /// Files involved in the error
+        #[arg(
+            long = "file",
+            requires = "explain",
+            help = "File involved in the error (repeatable)"
+        )]
+        files: Vec<String>,
+        /// Conflicts, failed tests or affected files
+        #[arg(
+            long,
+            requires = "explain",
+            help = "Number of conflicts, failed tests or affected files"
+        )]
+        count: Option<usize>,
     },
     /// Force reset agent to idle state
     ForceReset {
@@ -271,6 +486,127 @@ pub enum AgentCommands {
     },
 }
 
+#[cfg(feature = "database")]
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Show the schema version and which migrations are applied
+    Status {
+        /// Output format
+        #[arg(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Output format: text for human-readable, json for machine-readable"
+        )]
+        format: DoctorFormat,
+    },
+    /// Apply pending migrations, or revert to an older schema version
+    Migrate {
+        /// Schema version to migrate to (defaults to the newest)
+        #[arg(long, help = "Schema version to migrate up or down to (e.g., 3)")]
+        target: Option<i64>,
+        /// Show the migration without running it
+        #[arg(
+            long,
+            help = "Show what would be migrated without changing the database"
+        )]
+        dry_run: bool,
+    },
+    /// Rebuild the database file to reclaim free space
+    Vacuum,
+    /// Write a consistent copy of the database
+    Backup {
+        /// Backup file path
+        #[arg(
+            long,
+            help = "Backup file path (defaults to .my-little-soda/backups/my-little-soda-<time>.db)"
+        )]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Export tables as JSON
+    Export {
+        /// Only export this table
+        #[arg(long, help = "Export a single table (e.g., claims)")]
+        table: Option<String>,
+        /// Output file path (default: stdout)
+        #[arg(
+            long,
+            help = "File path to write JSON (prints to stdout if not specified)"
+        )]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Copy autonomous workflow state files into the database
+    #[cfg(feature = "autonomous")]
+    ImportState {
+        /// State directory written by the filesystem backend
+        #[arg(
+            long,
+            help = "State directory to import (defaults to .my-little-soda/autonomous_state)"
+        )]
+        from: Option<std::path::PathBuf>,
+    },
+}
+
+#[cfg(feature = "autonomous")]
+#[derive(Subcommand)]
+pub enum StateCommands {
+    /// Compress and delete old checkpoints according to the retention settings
+    Gc {
+        /// Show what would be cleaned up without changing anything
+        #[arg(
+            long,
+            help = "Show what would be deleted and compressed without changing anything"
+        )]
+        dry_run: bool,
+        /// Only clean up this agent's checkpoints
+        #[arg(long, help = "Clean up a specific agent (e.g., agent001)")]
+        agent: Option<String>,
+    },
+    /// Pack an agent's state, checkpoints and unpushed commits into one archive
+    Export {
+        /// Agent to export
+        #[arg(long, help = "Agent to export (e.g., agent001)", required = true)]
+        agent: String,
+        /// Archive file path
+        #[arg(
+            long,
+            help = "Archive file path (defaults to <agent>-state-<time>.tar.gz)"
+        )]
+        output: Option<std::path::PathBuf>,
+        /// Branch holding the agent's work
+        #[arg(
+            long,
+            help = "Branch to export (defaults to the agent's checked out or newest branch)"
+        )]
+        branch: Option<String>,
+        /// Repository holding the agent's branch
+        #[arg(
+            long,
+            default_value = ".",
+            help = "Repository to export the branch from"
+        )]
+        repo: std::path::PathBuf,
+    },
+    /// Restore an exported agent on this machine and resume its work
+    Import {
+        /// Archive written by `state export`
+        archive: std::path::PathBuf,
+        /// Import even if GitHub or local state disagree with the archive
+        #[arg(
+            long,
+            help = "Import despite validation problems, replacing any local state for the agent"
+        )]
+        force: bool,
+        /// Repository to restore the agent's branch into
+        #[arg(
+            long,
+            default_value = ".",
+            help = "Repository to restore the branch into"
+        )]
+        repo: std::path::PathBuf,
+    },
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum DoctorFormat {
     /// Human-readable text output with colors and formatting
@@ -278,3 +614,13 @@ pub enum DoctorFormat {
     /// Machine-readable JSON output
     Json,
 }
+
+#[derive(ValueEnum, Clone, Debug)]
+pub enum HistoryFormat {
+    /// One line per event
+    Text,
+    /// JSON array of events
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+}
@@ -1,6 +1,9 @@
+use crate::agents::integrator::MergeMethod;
+use crate::bundling::git_ops::{AssemblyMode, ConflictStrategy};
 use anyhow::Result;
 use config::{Config, Environment, File};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Main configuration structure for My Little Soda
@@ -14,6 +17,27 @@ pub struct MyLittleSodaConfig {
     pub agents: AgentConfig,
     /// Database settings (optional)
     pub database: Option<DatabaseConfig>,
+    /// Automatic merging of approved bundle and agent PRs
+    #[serde(default)]
+    pub merge: MergeConfig,
+    /// Commands that must pass before work is bottled
+    #[serde(default)]
+    pub quality_gates: QualityGatesConfig,
+    /// Scanning of pushed commits for leaked credentials
+    #[serde(default)]
+    pub secret_scanning: SecretScanningConfig,
+    /// Size limits for agent branches and bundles
+    #[serde(default)]
+    pub diff_limits: DiffLimitsConfig,
+    /// Paths agents may not change without human review
+    #[serde(default)]
+    pub protected_paths: ProtectedPathsConfig,
+    /// Retention and rollups of metrics kept in the database
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// Rules mapping errors of the autonomous workflow to recovery strategies
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -24,10 +48,17 @@ pub struct GitHubConfig {
     pub owner: String,
     /// Repository name
     pub repo: String,
+    /// Branch agent work starts from and lands on
+    #[serde(default = "default_base_branch")]
+    pub base_branch: String,
     /// Rate limiting settings
     pub rate_limit: RateLimitConfig,
 }
 
+fn default_base_branch() -> String {
+    "main".to_string()
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RateLimitConfig {
     /// Requests per hour limit
@@ -98,6 +129,530 @@ pub struct BundleConfig {
     pub max_queue_size: u32,
     /// Bundle processing timeout
     pub processing_timeout_seconds: u64,
+    /// How cherry-pick conflicts are handled while assembling a bundle
+    #[serde(default)]
+    pub conflict_strategy: ConflictStrategy,
+    /// How each agent branch is applied onto the bundle branch
+    #[serde(default)]
+    pub assembly_mode: AssemblyMode,
+    /// Confirm predicted hunk conflicts with an in-memory trial merge
+    #[serde(default = "default_trial_merge")]
+    pub trial_merge: bool,
+    /// Split large queues into independent bundles of at most this many branches
+    #[serde(default)]
+    pub max_branches_per_bundle: Option<usize>,
+    /// Open a `route:unblocker` issue for each branch that conflicts with a bundle
+    #[serde(default = "default_open_unblockers")]
+    pub open_unblockers: bool,
+    /// Rebase queued branches that are behind the base before bundling them
+    #[serde(default = "default_rebase_queued_branches")]
+    pub rebase_queued_branches: bool,
+}
+
+fn default_trial_merge() -> bool {
+    true
+}
+
+fn default_open_unblockers() -> bool {
+    true
+}
+
+fn default_rebase_queued_branches() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MergeConfig {
+    /// Merge method used when no label in `label_methods` matches
+    #[serde(default)]
+    pub default_method: MergeMethod,
+    /// Merge method per label on the PR or the issues it closes
+    #[serde(default)]
+    pub label_methods: BTreeMap<String, MergeMethod>,
+    /// Approving reviews required before a PR is merged
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: u32,
+    /// Merge PRs whose head commit reports no checks at all
+    #[serde(default)]
+    pub allow_no_checks: bool,
+    /// Delete the merged branches, remote and local
+    #[serde(default = "default_delete_branches")]
+    pub delete_branches: bool,
+    /// Seconds between polls in `merge --watch`
+    #[serde(default = "default_merge_poll_interval")]
+    pub poll_interval_seconds: u64,
+}
+
+fn default_required_approvals() -> u32 {
+    1
+}
+
+fn default_delete_branches() -> bool {
+    true
+}
+
+fn default_merge_poll_interval() -> u64 {
+    300
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            default_method: MergeMethod::default(),
+            label_methods: BTreeMap::new(),
+            required_approvals: default_required_approvals(),
+            allow_no_checks: false,
+            delete_branches: default_delete_branches(),
+            poll_interval_seconds: default_merge_poll_interval(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QualityGatesConfig {
+    /// Run the gates when bottling
+    #[serde(default = "default_quality_gates_enabled")]
+    pub enabled: bool,
+    /// Gates in the order they run
+    #[serde(default)]
+    pub gates: Vec<QualityGate>,
+}
+
+fn default_quality_gates_enabled() -> bool {
+    true
+}
+
+impl Default for QualityGatesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_quality_gates_enabled(),
+            gates: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct QualityGate {
+    /// Short name used in reports
+    pub name: String,
+    /// Shell command; the gate passes when it exits with 0
+    pub command: String,
+    /// Seconds before the command is killed and the gate fails
+    #[serde(default = "default_gate_timeout")]
+    pub timeout_seconds: u64,
+    /// Block bottling when the gate fails; optional gates only warn
+    #[serde(default = "default_gate_required")]
+    pub required: bool,
+    /// Directory to run in, relative to the repository root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+}
+
+fn default_gate_timeout() -> u64 {
+    600
+}
+
+fn default_gate_required() -> bool {
+    true
+}
+
+impl QualityGate {
+    pub fn new(name: &str, command: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            timeout_seconds: default_gate_timeout(),
+            required: default_gate_required(),
+            working_dir: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout_seconds: u64) -> Self {
+        self.timeout_seconds = timeout_seconds;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SecretScanningConfig {
+    /// Scan commits before every push
+    #[serde(default = "default_secret_scanning_enabled")]
+    pub enabled: bool,
+    /// Extra rules on top of the built-in GitHub, AWS and private key rules
+    #[serde(default)]
+    pub rules: Vec<SecretRuleConfig>,
+    /// Flag long random-looking strings even when no rule matches
+    #[serde(default = "default_entropy_detection")]
+    pub entropy_detection: bool,
+    /// Shannon entropy (bits per character) above which a string is flagged
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f64,
+    /// Shortest string the entropy detector looks at
+    #[serde(default = "default_entropy_min_length")]
+    pub entropy_min_length: usize,
+    /// File of regexes, one per line, for paths or values that are not secrets
+    #[serde(default = "default_secret_allowlist_file")]
+    pub allowlist_file: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SecretRuleConfig {
+    pub name: String,
+    /// Regex matched against each added line
+    pub pattern: String,
+}
+
+fn default_secret_scanning_enabled() -> bool {
+    true
+}
+
+fn default_entropy_detection() -> bool {
+    true
+}
+
+fn default_entropy_threshold() -> f64 {
+    4.5
+}
+
+fn default_entropy_min_length() -> usize {
+    32
+}
+
+fn default_secret_allowlist_file() -> String {
+    ".my-little-soda/secrets-allowlist".to_string()
+}
+
+impl Default for SecretScanningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_secret_scanning_enabled(),
+            rules: Vec::new(),
+            entropy_detection: default_entropy_detection(),
+            entropy_threshold: default_entropy_threshold(),
+            entropy_min_length: default_entropy_min_length(),
+            allowlist_file: default_secret_allowlist_file(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiffLimitsConfig {
+    /// Refuse oversized branches in `bottle` and oversized bundles
+    #[serde(default = "default_diff_limits_enabled")]
+    pub enabled: bool,
+    /// Limits for a single agent branch
+    #[serde(default = "DiffLimits::branch_defaults")]
+    pub branch: DiffLimits,
+    /// Limits for everything a bundle PR changes
+    #[serde(default = "DiffLimits::bundle_defaults")]
+    pub bundle: DiffLimits,
+}
+
+fn default_diff_limits_enabled() -> bool {
+    true
+}
+
+impl Default for DiffLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_diff_limits_enabled(),
+            branch: DiffLimits::branch_defaults(),
+            bundle: DiffLimits::bundle_defaults(),
+        }
+    }
+}
+
+/// Size limits for one diff; 0 means no limit
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DiffLimits {
+    /// Inserted plus deleted lines
+    #[serde(default)]
+    pub max_changed_lines: usize,
+    #[serde(default)]
+    pub max_changed_files: usize,
+    #[serde(default)]
+    pub max_binary_files: usize,
+}
+
+impl DiffLimits {
+    pub fn branch_defaults() -> Self {
+        Self {
+            max_changed_lines: 1500,
+            max_changed_files: 50,
+            max_binary_files: 5,
+        }
+    }
+
+    pub fn bundle_defaults() -> Self {
+        Self {
+            max_changed_lines: 5000,
+            max_changed_files: 150,
+            max_binary_files: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProtectedPathsConfig {
+    /// Hold branches touching protected paths out of auto-bundling
+    #[serde(default = "default_protected_paths_enabled")]
+    pub enabled: bool,
+    /// Treat every path with an owner in CODEOWNERS as protected
+    #[serde(default)]
+    pub use_codeowners: bool,
+    /// CODEOWNERS location; .github/, the root and docs/ are searched when unset
+    #[serde(default)]
+    pub codeowners_file: Option<String>,
+    #[serde(default = "default_protected_path_rules")]
+    pub rules: Vec<ProtectedPathRule>,
+}
+
+fn default_protected_paths_enabled() -> bool {
+    true
+}
+
+fn default_protected_path_rules() -> Vec<ProtectedPathRule> {
+    vec![
+        ProtectedPathRule::new(".github/workflows/"),
+        ProtectedPathRule::new("migrations/"),
+    ]
+}
+
+impl Default for ProtectedPathsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_protected_paths_enabled(),
+            use_codeowners: false,
+            codeowners_file: None,
+            rules: default_protected_path_rules(),
+        }
+    }
+}
+
+/// Metrics retention in the database; the JSONL files are never pruned
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    /// Days to keep raw routing, utilization, coordination and integration records
+    #[serde(default = "default_metrics_raw_retention_days")]
+    pub raw_retention_days: u32,
+    /// Days to keep hourly rollups
+    #[serde(default = "default_metrics_hourly_retention_days")]
+    pub hourly_retention_days: u32,
+    /// Days to keep daily rollups
+    #[serde(default = "default_metrics_daily_retention_days")]
+    pub daily_retention_days: u32,
+    /// Time windows longer than this many hours are answered from rollups
+    #[serde(default = "default_metrics_rollup_after_hours")]
+    pub rollup_after_hours: u64,
+}
+
+fn default_metrics_raw_retention_days() -> u32 {
+    14
+}
+
+fn default_metrics_hourly_retention_days() -> u32 {
+    90
+}
+
+fn default_metrics_daily_retention_days() -> u32 {
+    730
+}
+
+fn default_metrics_rollup_after_hours() -> u64 {
+    48
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            raw_retention_days: default_metrics_raw_retention_days(),
+            hourly_retention_days: default_metrics_hourly_retention_days(),
+            daily_retention_days: default_metrics_daily_retention_days(),
+            rollup_after_hours: default_metrics_rollup_after_hours(),
+        }
+    }
+}
+
+/// How the autonomous workflow recovers from errors
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RecoveryConfig {
+    /// Fall back to the built-in rules for errors no custom rule matches
+    #[serde(default = "default_builtin_recovery_rules")]
+    pub builtin_rules: bool,
+    /// Tried in order before the built-in rules; the first match decides
+    #[serde(default)]
+    pub rules: Vec<RecoveryRuleConfig>,
+}
+
+fn default_builtin_recovery_rules() -> bool {
+    true
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            builtin_rules: default_builtin_recovery_rules(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// When a recovery rule applies and what it does. Every condition that is
+/// set has to hold.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RecoveryRuleConfig {
+    pub name: String,
+    /// Kind of error, e.g. `git_operation`, `github_api` or `ci_failure`
+    #[serde(default)]
+    pub error_type: Option<String>,
+    /// HTTP status codes, or ranges like `"500-599"`
+    #[serde(default)]
+    pub status: Vec<StatusCodeMatch>,
+    /// Gitignore-style globs, one of which a file involved in the error must match
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Regex on what failed: git operation, API endpoint, CI job, build stage, ...
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Regex on the error message
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Most conflicts, failed tests or affected files the rule handles
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    /// One strategy per attempt, in order; the last one is repeated
+    pub strategies: Vec<RecoveryActionConfig>,
+    /// Attempts before the error is escalated
+    #[serde(default = "default_recovery_max_attempts")]
+    pub max_attempts: u8,
+    #[serde(default)]
+    pub backoff: RecoveryBackoffConfig,
+    /// Who hears about it once the attempts run out or a strategy escalates
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+}
+
+fn default_recovery_max_attempts() -> u8 {
+    3
+}
+
+/// A status code or an inclusive range of them
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum StatusCodeMatch {
+    Code(u16),
+    Range(String),
+}
+
+/// A recovery strategy. Names are the snake_case variants of the workflow's
+/// fix types, confidence levels, alternatives and urgencies; `context` and
+/// `reason` may use `{kind}`, `{subject}`, `{message}`, `{status}`, `{count}`
+/// and `{file_count}`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RecoveryActionConfig {
+    /// Try again after the rule's backoff
+    Retry,
+    /// Have the agent fix it, e.g. `test_failure_fix` with `medium` confidence
+    Fix {
+        fix: String,
+        #[serde(default = "default_fix_confidence")]
+        confidence: String,
+    },
+    /// Take another approach, e.g. `simplified_solution`
+    Fallback { alternative: String },
+    /// Hand the work to a human
+    Escalate {
+        #[serde(default = "default_escalation_urgency")]
+        urgency: String,
+        context: String,
+    },
+    /// Drop the work and start over
+    Abandon { reason: String },
+}
+
+fn default_fix_confidence() -> String {
+    "medium".to_string()
+}
+
+fn default_escalation_urgency() -> String {
+    "medium".to_string()
+}
+
+/// Exponential backoff between retries
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RecoveryBackoffConfig {
+    #[serde(default = "default_backoff_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_backoff_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_backoff_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_backoff_max_delay_ms() -> u64 {
+    10000
+}
+
+impl Default for RecoveryBackoffConfig {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: default_backoff_base_delay_ms(),
+            max_delay_ms: default_backoff_max_delay_ms(),
+        }
+    }
+}
+
+/// How an issue is handed to a human
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EscalationConfig {
+    /// Labels put on the issue; the human-only label keeps agents off it
+    #[serde(default = "default_escalation_labels")]
+    pub labels: Vec<String>,
+    /// Explain on the issue why the agent stopped
+    #[serde(default = "default_escalation_comment")]
+    pub comment: bool,
+    /// GitHub users (`@login`) or teams (`@org/team`) mentioned in the comment
+    #[serde(default)]
+    pub mention: Vec<String>,
+}
+
+fn default_escalation_labels() -> Vec<String> {
+    vec![crate::github::labels::HUMAN_ONLY_LABEL.to_string()]
+}
+
+fn default_escalation_comment() -> bool {
+    true
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            labels: default_escalation_labels(),
+            comment: default_escalation_comment(),
+            mention: Vec::new(),
+        }
+    }
+}
+
+/// A gitignore-style pattern, with the owners to request as reviewers
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProtectedPathRule {
+    pub pattern: String,
+    /// GitHub users (`@login`) or teams (`@org/team`)
+    #[serde(default)]
+    pub owners: Vec<String>,
+}
+
+impl ProtectedPathRule {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            owners: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -126,6 +681,41 @@ pub struct WorkContinuityConfig {
     pub force_fresh_start_after_hours: u32,
     /// Preserve partial work during recovery
     pub preserve_partial_work: bool,
+    /// Where autonomous workflow state and checkpoints are stored
+    #[serde(default)]
+    pub persistence_backend: PersistenceBackend,
+    /// Days to keep checkpoints
+    #[serde(default = "default_backup_retention_days")]
+    pub backup_retention_days: u32,
+    /// Most checkpoints kept per agent, newest first
+    #[serde(default = "default_max_checkpoints_per_agent")]
+    pub max_checkpoints_per_agent: usize,
+    /// Archive all but the newest few checkpoints gzip-compressed
+    #[serde(default = "default_compress_old_states")]
+    pub compress_old_states: bool,
+}
+
+fn default_backup_retention_days() -> u32 {
+    7
+}
+
+fn default_max_checkpoints_per_agent() -> usize {
+    50
+}
+
+fn default_compress_old_states() -> bool {
+    true
+}
+
+/// Storage for autonomous workflow state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PersistenceBackend {
+    /// JSON files, one per agent plus a directory of checkpoints
+    #[default]
+    Filesystem,
+    /// The database configured under `[database]` (needs the `database` feature)
+    Sqlite,
 }
 
 impl Default for WorkContinuityConfig {
@@ -138,6 +728,10 @@ impl Default for WorkContinuityConfig {
             validation_timeout_seconds: 30,
             force_fresh_start_after_hours: 24,
             preserve_partial_work: true,
+            persistence_backend: PersistenceBackend::default(),
+            backup_retention_days: default_backup_retention_days(),
+            max_checkpoints_per_agent: default_max_checkpoints_per_agent(),
+            compress_old_states: default_compress_old_states(),
         }
     }
 }
@@ -149,6 +743,7 @@ impl Default for MyLittleSodaConfig {
                 token: None, // Will be read from env var or .my-little-soda-rc
                 owner: "johnhkchen".to_string(),
                 repo: "my-little-soda".to_string(),
+                base_branch: default_base_branch(),
                 rate_limit: RateLimitConfig {
                     requests_per_hour: 5000,
                     burst_capacity: 100,
@@ -165,6 +760,12 @@ impl Default for MyLittleSodaConfig {
                 bundle_processing: BundleConfig {
                     max_queue_size: 50,
                     processing_timeout_seconds: 1800, // 30 minutes
+                    conflict_strategy: ConflictStrategy::IndividualFallback,
+                    assembly_mode: AssemblyMode::CherryPick,
+                    trial_merge: true,
+                    max_branches_per_bundle: None,
+                    open_unblockers: true,
+                    rebase_queued_branches: true,
                 },
                 process_management: AgentProcessConfig {
                     claude_code_path: "claude-code".to_string(),
@@ -181,21 +782,20 @@ impl Default for MyLittleSodaConfig {
                     ci_timeout_adjustment: 300, // Additional 5 minutes for CI environments
                     enhanced_error_reporting: true,
                 },
-                work_continuity: WorkContinuityConfig {
-                    enable_continuity: true,
-                    state_file_path: ".my-little-soda/agent-state.json".to_string(),
-                    backup_interval_minutes: 5,
-                    max_recovery_attempts: 3,
-                    validation_timeout_seconds: 30,
-                    force_fresh_start_after_hours: 24,
-                    preserve_partial_work: true,
-                },
+                work_continuity: WorkContinuityConfig::default(),
             },
             database: Some(DatabaseConfig {
                 url: ".my-little-soda/my-little-soda.db".to_string(),
                 max_connections: 10,
                 auto_migrate: true,
             }),
+            merge: MergeConfig::default(),
+            quality_gates: QualityGatesConfig::default(),
+            secret_scanning: SecretScanningConfig::default(),
+            diff_limits: DiffLimitsConfig::default(),
+            protected_paths: ProtectedPathsConfig::default(),
+            metrics: MetricsConfig::default(),
+            recovery: RecoveryConfig::default(),
         }
     }
 }
@@ -275,6 +875,13 @@ pub fn config() -> Result<&'static MyLittleSodaConfig> {
         .map_err(|e| anyhow::anyhow!("Failed to load configuration: {}", e))
 }
 
+/// The configured base branch, `main` when the configuration can't be loaded
+pub fn base_branch() -> String {
+    config()
+        .map(|c| c.github.base_branch.clone())
+        .unwrap_or_else(|_| default_base_branch())
+}
+
 /// Initialize configuration (called at startup)
 pub fn init_config() -> Result<()> {
     let _config = config()?;
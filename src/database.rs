@@ -1,37 +1,305 @@
 #[cfg(feature = "database")]
-use anyhow::Result;
+use crate::bundling::types::{BundleHistoryEntry, BundleStatus};
 #[cfg(feature = "database")]
-use sqlx::{migrate::MigrateDatabase, Row, SqlitePool};
+use anyhow::{anyhow, Result};
+#[cfg(feature = "database")]
+use serde::Serialize;
+#[cfg(feature = "database")]
+use sqlx::migrate::{Migrate, Migrator};
+#[cfg(feature = "database")]
+use sqlx::{migrate::MigrateDatabase, Column, Row, SqlitePool, TypeInfo, ValueRef};
 #[cfg(feature = "database")]
 use tracing::info;
 
+/// Migrations compiled into this build; the highest version is the newest schema it knows
+#[cfg(feature = "database")]
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 #[cfg(feature = "database")]
 /// Database manager for persistent state storage
 pub struct DatabaseManager {
     pool: SqlitePool,
 }
 
+/// A migration known to this build, and when it was applied
+#[cfg(feature = "database")]
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    pub reversible: bool,
+    pub applied_at: Option<String>,
+}
+
+/// Where the database schema stands against the migrations this build knows
+#[cfg(feature = "database")]
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaStatus {
+    /// Highest applied migration, 0 for an empty database
+    pub current_version: i64,
+    pub latest_known: i64,
+    pub migrations: Vec<MigrationInfo>,
+    /// Applied versions this build has no migration for
+    pub unknown_applied: Vec<i64>,
+}
+
+#[cfg(feature = "database")]
+impl SchemaStatus {
+    /// Whether a newer build has migrated the database past what this one knows
+    pub fn is_newer(&self) -> bool {
+        !self.unknown_applied.is_empty() || self.current_version > self.latest_known
+    }
+
+    pub fn pending(&self) -> Vec<&MigrationInfo> {
+        self.migrations
+            .iter()
+            .filter(|m| m.applied_at.is_none())
+            .collect()
+    }
+}
+
+/// Size of the database file in pages
+#[cfg(feature = "database")]
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DatabaseSize {
+    pub total_bytes: i64,
+    pub free_bytes: i64,
+}
+
 #[cfg(feature = "database")]
 impl DatabaseManager {
     /// Initialize database with automatic migrations
+    ///
+    /// Refuses a database migrated by a newer build, since this build would
+    /// misread tables it does not know.
     pub async fn new(database_url: &str, auto_migrate: bool) -> Result<Self> {
+        let manager = Self::connect(database_url).await?;
+
+        let status = manager.schema_status().await?;
+        if status.is_newer() {
+            return Err(anyhow!(
+                "Database schema version {} is newer than this build of my-little-soda supports ({}); upgrade my-little-soda or restore a backup",
+                status.current_version,
+                status.latest_known
+            ));
+        }
+
+        // Run migrations if enabled
+        if auto_migrate {
+            info!("Running database migrations...");
+            MIGRATOR.run(&manager.pool).await?;
+            info!("Database migrations completed");
+        }
+
+        Ok(manager)
+    }
+
+    /// Connect without checking or migrating the schema, for maintenance commands
+    pub async fn connect(database_url: &str) -> Result<Self> {
         // Create database if it doesn't exist
         if !sqlx::Sqlite::database_exists(database_url).await? {
             info!("Creating database at {}", database_url);
             sqlx::Sqlite::create_database(database_url).await?;
         }
 
-        // Connect to database
         let pool = SqlitePool::connect(database_url).await?;
+        Ok(Self { pool })
+    }
 
-        // Run migrations if enabled
-        if auto_migrate {
-            info!("Running database migrations...");
-            sqlx::migrate!("./migrations").run(&pool).await?;
-            info!("Database migrations completed");
+    /// Whether the database has been created yet
+    pub async fn exists(database_url: &str) -> Result<bool> {
+        Ok(sqlx::Sqlite::database_exists(database_url).await?)
+    }
+
+    /// Applied migrations compared with the ones this build knows
+    pub async fn schema_status(&self) -> Result<SchemaStatus> {
+        let has_table: Option<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let applied: Vec<(i64, String)> = if has_table.is_some() {
+            sqlx::query_as(
+                "SELECT version, CAST(installed_on AS TEXT) FROM _sqlx_migrations WHERE success = 1 ORDER BY version",
+            )
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            Vec::new()
+        };
+
+        let migrations: Vec<MigrationInfo> = MIGRATOR
+            .iter()
+            .filter(|m| m.migration_type.is_up_migration())
+            .map(|m| MigrationInfo {
+                version: m.version,
+                description: m.description.to_string(),
+                reversible: m.migration_type.is_reversible(),
+                applied_at: applied
+                    .iter()
+                    .find(|(version, _)| *version == m.version)
+                    .map(|(_, at)| at.clone()),
+            })
+            .collect();
+        let unknown_applied = applied
+            .iter()
+            .map(|(version, _)| *version)
+            .filter(|version| !migrations.iter().any(|m| m.version == *version))
+            .collect();
+
+        Ok(SchemaStatus {
+            current_version: applied.iter().map(|(v, _)| *v).max().unwrap_or(0),
+            latest_known: migrations.iter().map(|m| m.version).max().unwrap_or(0),
+            migrations,
+            unknown_applied,
+        })
+    }
+
+    /// Migrate up or down to `target`, or up to the newest known version
+    pub async fn migrate(&self, target: Option<i64>) -> Result<SchemaStatus> {
+        let status = self.schema_status().await?;
+        if status.is_newer() {
+            return Err(anyhow!(
+                "Database schema version {} is newer than this build supports ({})",
+                status.current_version,
+                status.latest_known
+            ));
+        }
+        let target = target.unwrap_or(status.latest_known);
+        if target > status.latest_known || target < 0 {
+            return Err(anyhow!(
+                "Unknown schema version {}; this build knows versions up to {}",
+                target,
+                status.latest_known
+            ));
         }
 
-        Ok(Self { pool })
+        if target < status.current_version {
+            if let Some(migration) = status
+                .migrations
+                .iter()
+                .find(|m| m.version > target && m.applied_at.is_some() && !m.reversible)
+            {
+                return Err(anyhow!(
+                    "Migration {} ({}) cannot be reverted",
+                    migration.version,
+                    migration.description
+                ));
+            }
+            MIGRATOR.undo(&self.pool, target).await?;
+        } else if target == status.latest_known {
+            MIGRATOR.run(&self.pool).await?;
+        } else {
+            let mut conn = self.pool.acquire().await?;
+            conn.ensure_migrations_table().await?;
+            for migration in MIGRATOR.iter().filter(|m| {
+                m.migration_type.is_up_migration()
+                    && m.version <= target
+                    && status
+                        .migrations
+                        .iter()
+                        .any(|known| known.version == m.version && known.applied_at.is_none())
+            }) {
+                conn.apply(migration).await?;
+            }
+        }
+
+        self.schema_status().await
+    }
+
+    pub async fn size(&self) -> Result<DatabaseSize> {
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+            .fetch_one(&self.pool)
+            .await?;
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+            .fetch_one(&self.pool)
+            .await?;
+        let free_pages: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(DatabaseSize {
+            total_bytes: page_size * page_count,
+            free_bytes: page_size * free_pages,
+        })
+    }
+
+    /// Rebuild the database file to reclaim free pages
+    pub async fn vacuum(&self) -> Result<(DatabaseSize, DatabaseSize)> {
+        let before = self.size().await?;
+        sqlx::query("VACUUM").execute(&self.pool).await?;
+        Ok((before, self.size().await?))
+    }
+
+    /// Write a consistent copy of the database to `path`, which must not exist
+    pub async fn backup(&self, path: &std::path::Path) -> Result<()> {
+        if path.exists() {
+            return Err(anyhow!("{} already exists", path.display()));
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        sqlx::query("VACUUM INTO ?1")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Application tables, excluding SQLite and migration bookkeeping
+    pub async fn tables(&self) -> Result<Vec<String>> {
+        Ok(sqlx::query_scalar(
+            r#"
+            SELECT name FROM sqlite_master
+            WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_sqlx_migrations'
+            ORDER BY name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Every row of `table` as a JSON object keyed by column
+    pub async fn export_table(&self, table: &str) -> Result<Vec<serde_json::Value>> {
+        self.ensure_table(table).await?;
+        let rows = sqlx::query(&format!("SELECT * FROM \"{table}\" ORDER BY rowid"))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut exported = Vec::new();
+        for row in rows {
+            let mut object = serde_json::Map::new();
+            for column in row.columns() {
+                let index = column.ordinal();
+                let raw = row.try_get_raw(index)?;
+                let value = if raw.is_null() {
+                    serde_json::Value::Null
+                } else {
+                    match raw.type_info().name() {
+                        "INTEGER" => row.try_get::<i64, _>(index)?.into(),
+                        "REAL" => row.try_get::<f64, _>(index)?.into(),
+                        "BLOB" => row
+                            .try_get::<Vec<u8>, _>(index)?
+                            .iter()
+                            .map(|b| format!("{b:02x}"))
+                            .collect::<String>()
+                            .into(),
+                        _ => row.try_get::<String, _>(index)?.into(),
+                    }
+                };
+                object.insert(column.name().to_string(), value);
+            }
+            exported.push(serde_json::Value::Object(object));
+        }
+        Ok(exported)
+    }
+
+    /// Table names are spliced into SQL, so only known tables are accepted
+    async fn ensure_table(&self, table: &str) -> Result<()> {
+        if self.tables().await?.iter().any(|t| t == table) {
+            Ok(())
+        } else {
+            Err(anyhow!("No table named '{}'", table))
+        }
     }
 
     /// Get database pool for queries
@@ -115,13 +383,133 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// Save a bundle run, keeping its original creation time
+    pub async fn save_bundle(
+        &self,
+        state: &crate::bundling::types::BundleState,
+        status: BundleStatus,
+    ) -> Result<()> {
+        let metadata = serde_json::to_string(state)?;
+        sqlx::query(
+            r#"
+            INSERT INTO bundle_states (bundle_id, state, metadata, updated_at)
+            VALUES (?1, ?2, ?3, datetime('now'))
+            ON CONFLICT(bundle_id) DO UPDATE SET
+                state = excluded.state,
+                metadata = excluded.metadata,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&state.bundle_branch)
+        .bind(status.as_str())
+        .bind(metadata)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO bundle_steps
+                (bundle_id, status, operation, completed_branches, failed_branches, pr_number)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(&state.bundle_branch)
+        .bind(status.as_str())
+        .bind(&state.current_operation)
+        .bind(state.completed_branches.len() as i64)
+        .bind(state.failed_branches.len() as i64)
+        .bind(state.pr_number.map(|pr| pr as i64))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Saved steps of one bundle run, oldest first
+    pub async fn get_bundle_steps(&self, bundle_id: &str) -> Result<Vec<BundleStep>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT status, operation, completed_branches, failed_branches, pr_number,
+                   CAST(recorded_at AS TEXT) AS recorded_at
+            FROM bundle_steps
+            WHERE bundle_id = ?1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(bundle_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let status: String = row.get("status");
+                Some(BundleStep {
+                    status: BundleStatus::parse(&status)?,
+                    operation: row.get("operation"),
+                    completed_branches: row.get::<i64, _>("completed_branches") as usize,
+                    failed_branches: row.get::<i64, _>("failed_branches") as usize,
+                    pr_number: row.get::<Option<i64>, _>("pr_number").map(|pr| pr as u64),
+                    recorded_at: row.get("recorded_at"),
+                })
+            })
+            .collect())
+    }
+
+    /// Most recently updated bundle that was interrupted while in progress
+    pub async fn get_interrupted_bundle(&self) -> Result<Option<BundleHistoryEntry>> {
+        let row = sqlx::query(
+            r#"
+            SELECT bundle_id, state, metadata, created_at, updated_at
+            FROM bundle_states
+            WHERE state = ?1
+            ORDER BY updated_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(BundleStatus::InProgress.as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.and_then(|row| Self::bundle_history_entry(&row)))
+    }
+
+    /// Past and running bundles, newest first
+    pub async fn get_bundle_history(&self, limit: u32) -> Result<Vec<BundleHistoryEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT bundle_id, state, metadata, created_at, updated_at
+            FROM bundle_states
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().filter_map(Self::bundle_history_entry).collect())
+    }
+
+    fn bundle_history_entry(row: &sqlx::sqlite::SqliteRow) -> Option<BundleHistoryEntry> {
+        let status: String = row.get("state");
+        let metadata: Option<String> = row.get("metadata");
+        Some(BundleHistoryEntry {
+            bundle_id: row.get("bundle_id"),
+            status: BundleStatus::parse(&status)?,
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            state: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+        })
+    }
+
     /// Get all active bundle states
     pub async fn get_active_bundles(&self) -> Result<Vec<BundleState>> {
         let rows = sqlx::query(
             r#"
             SELECT bundle_id, state, metadata, updated_at
             FROM bundle_states
-            WHERE state != 'completed' AND state != 'failed'
+            WHERE state IN ('in_progress', 'paused')
             ORDER BY updated_at ASC
             "#,
         )
@@ -156,7 +544,7 @@ impl DatabaseManager {
         let deleted_bundles = sqlx::query(
             r#"
             DELETE FROM bundle_states
-            WHERE state IN ('completed', 'failed')
+            WHERE state NOT IN ('in_progress', 'paused')
             AND updated_at < datetime('now', '-' || ?1 || ' days')
             "#,
         )
@@ -190,6 +578,18 @@ pub struct AgentState {
     pub updated_at: String,
 }
 
+/// One saved step of a bundle run
+#[cfg(feature = "database")]
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleStep {
+    pub status: BundleStatus,
+    pub operation: Option<String>,
+    pub completed_branches: usize,
+    pub failed_branches: usize,
+    pub pr_number: Option<u64>,
+    pub recorded_at: String,
+}
+
 #[cfg(feature = "database")]
 #[derive(Debug, Clone)]
 pub struct BundleState {
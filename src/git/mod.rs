@@ -4,5 +4,6 @@
 //! replacing shell-based git commands with proper libgit2 bindings.
 
 pub mod operations;
+pub mod secrets;
 
 pub use operations::{Git2Operations, GitHubRepoInfo, GitOperations};
@@ -182,6 +182,13 @@ impl GitOperations for Git2Operations {
     }
 
     fn push(&self, remote_name: &str, branch: &str) -> Result<()> {
+        super::secrets::ensure_no_secrets(
+            &self.repo,
+            branch,
+            remote_name,
+            &crate::config::base_branch(),
+        )?;
+
         let mut remote = self
             .repo
             .find_remote(remote_name)
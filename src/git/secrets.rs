@@ -0,0 +1,469 @@
+//! Secret scanning of commits before they are pushed
+//!
+//! Every commit a push would publish is diffed against each of its parents and
+//! the added lines are checked against the built-in rules, custom rules from
+//! `[secret_scanning]` and an entropy detector. Paths or values matching a
+//! line of the allowlist file are ignored.
+
+use crate::config::{SecretRuleConfig, SecretScanningConfig};
+use crate::github::labels::HUMAN_ONLY_LABEL;
+use anyhow::{Context, Result};
+use git2::{Delta, DiffFormat, Oid, Repository, Sort};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+const BUILT_IN_RULES: &[(&str, &str)] = &[
+    (
+        "GitHub token",
+        r"\b(?:ghp|gho|ghu|ghs|ghr)_[A-Za-z0-9]{36}\b",
+    ),
+    (
+        "GitHub fine-grained token",
+        r"\bgithub_pat_[A-Za-z0-9_]{82}\b",
+    ),
+    ("AWS access key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    (
+        "AWS secret key",
+        r#"(?i)aws_?secret_?(?:access_?)?key["']?\s*[:=]\s*["']?[A-Za-z0-9/+=]{40}"#,
+    ),
+    (
+        "Private key",
+        r"-----BEGIN (?:RSA |EC |DSA |OPENSSH |PGP |ENCRYPTED )?PRIVATE KEY(?: BLOCK)?-----",
+    ),
+];
+
+/// `.env` files hold credentials; templates for them don't
+const ENV_FILE_RULE: &str = "Environment file";
+const ENV_TEMPLATE_SUFFIXES: &[&str] = &[".example", ".sample", ".template", ".dist"];
+
+/// Lockfiles are full of checksums that look random by design
+const ENTROPY_EXEMPT_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Gemfile.lock",
+    "go.sum",
+];
+
+struct SecretRule {
+    name: String,
+    pattern: Regex,
+}
+
+/// A suspected secret in a commit about to be pushed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SecretFinding {
+    pub rule: String,
+    pub file: String,
+    /// Line in the file, `None` when the whole file is the finding
+    pub line: Option<u32>,
+    /// The matched value with all but its first characters masked
+    pub masked: String,
+    pub commit: String,
+}
+
+impl fmt::Display for SecretFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}", self.file, line)?,
+            None => write!(f, "{}", self.file)?,
+        }
+        write!(
+            f,
+            " {} {} (commit {})",
+            self.rule,
+            self.masked,
+            &self.commit[..self.commit.len().min(8)]
+        )
+    }
+}
+
+/// Error returned when a push was refused because of secrets
+///
+/// Callers that know the issue behind the branch downcast to this to flag it.
+#[derive(Debug, Clone)]
+pub struct SecretsDetected {
+    pub branch: String,
+    pub findings: Vec<SecretFinding>,
+}
+
+impl fmt::Display for SecretsDetected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Push of {} blocked: {} suspected secret(s)",
+            self.branch,
+            self.findings.len()
+        )?;
+        for finding in &self.findings {
+            write!(f, "\n   🔑 {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SecretsDetected {}
+
+impl SecretsDetected {
+    /// Comment for the issue whose branch was blocked
+    pub fn issue_comment(&self) -> String {
+        let mut comment = format!(
+            "🔐 **Push blocked: suspected secrets**\n\n\
+            `{}` was not pushed because these commits appear to contain credentials:\n\n\
+            | File | Rule | Value | Commit |\n|---|---|---|---|\n",
+            self.branch
+        );
+        for finding in &self.findings {
+            let location = match finding.line {
+                Some(line) => format!("{}:{}", finding.file, line),
+                None => finding.file.clone(),
+            };
+            comment.push_str(&format!(
+                "| `{}` | {} | `{}` | {} |\n",
+                location,
+                finding.rule,
+                finding.masked,
+                &finding.commit[..finding.commit.len().min(8)]
+            ));
+        }
+        comment.push_str(&format!(
+            "\nThe issue is labelled `{HUMAN_ONLY_LABEL}` until someone checks it. Rotate any \
+            real credential and rewrite the branch history to drop it; false positives can be \
+            added to the allowlist file.\n"
+        ));
+        comment
+    }
+}
+
+/// Pull the `SecretsDetected` out of an error chain, if that is what it is
+pub fn secrets_detected(error: &anyhow::Error) -> Option<&SecretsDetected> {
+    error.chain().find_map(|cause| cause.downcast_ref())
+}
+
+pub struct SecretScanner {
+    rules: Vec<SecretRule>,
+    entropy_threshold: Option<f64>,
+    entropy_min_length: usize,
+    allowlist: Vec<Regex>,
+}
+
+impl SecretScanner {
+    /// Built-in rules plus the custom rules and entropy settings from config
+    pub fn new(config: &SecretScanningConfig) -> Result<Self> {
+        let built_in = BUILT_IN_RULES
+            .iter()
+            .map(|(name, pattern)| SecretRuleConfig {
+                name: name.to_string(),
+                pattern: pattern.to_string(),
+            });
+        let rules = built_in
+            .chain(config.rules.iter().cloned())
+            .map(|rule| {
+                Ok(SecretRule {
+                    pattern: Regex::new(&rule.pattern)
+                        .with_context(|| format!("Invalid secret rule '{}'", rule.name))?,
+                    name: rule.name,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            rules,
+            entropy_threshold: config.entropy_detection.then_some(config.entropy_threshold),
+            entropy_min_length: config.entropy_min_length,
+            allowlist: Vec::new(),
+        })
+    }
+
+    /// Allowlist regexes, one per line; blank lines and `#` comments are skipped
+    pub fn with_allowlist(mut self, allowlist: &str) -> Result<Self> {
+        for entry in allowlist
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        {
+            self.allowlist.push(
+                Regex::new(entry).with_context(|| format!("Invalid allowlist entry '{entry}'"))?,
+            );
+        }
+        Ok(self)
+    }
+
+    /// Scanner for a repository, from the loaded config and its allowlist file
+    pub fn for_repository(root: &Path) -> Result<Option<Self>> {
+        let config = crate::config::config()
+            .map(|c| c.secret_scanning.clone())
+            .unwrap_or_default();
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let scanner = Self::new(&config)?;
+        match std::fs::read_to_string(root.join(&config.allowlist_file)) {
+            Ok(allowlist) => scanner.with_allowlist(&allowlist).map(Some),
+            Err(_) => Ok(Some(scanner)),
+        }
+    }
+
+    /// Findings on one added line
+    pub fn scan_line(&self, file: &str, line: Option<u32>, text: &str) -> Vec<SecretFinding> {
+        if self.is_allowed(file) {
+            return Vec::new();
+        }
+
+        let mut findings = Vec::new();
+        let mut matched = Vec::new();
+        for rule in &self.rules {
+            for found in rule.pattern.find_iter(text) {
+                matched.push(found.range());
+                if !self.is_allowed(found.as_str()) {
+                    findings.push(finding(&rule.name, file, line, found.as_str()));
+                }
+            }
+        }
+
+        let file_name = file.rsplit('/').next().unwrap_or(file);
+        let threshold = self
+            .entropy_threshold
+            .filter(|_| !ENTROPY_EXEMPT_FILES.contains(&file_name));
+        if let Some(threshold) = threshold {
+            for (start, token) in tokens(text) {
+                let overlaps = matched
+                    .iter()
+                    .any(|range| start < range.end && range.start < start + token.len());
+                if !overlaps
+                    && token.len() >= self.entropy_min_length
+                    && looks_random(token)
+                    && shannon_entropy(token) >= threshold
+                    && !self.is_allowed(token)
+                {
+                    findings.push(finding("High-entropy string", file, line, token));
+                }
+            }
+        }
+        findings
+    }
+
+    /// Findings in the commits on `branch` that `remote` does not have yet
+    ///
+    /// Commits already on the remote branch, or on the remote's `base_branch`
+    /// for a branch never pushed, are not scanned again.
+    pub fn scan_push(
+        &self,
+        repo: &Repository,
+        branch: &str,
+        remote: &str,
+        base_branch: &str,
+    ) -> Result<Vec<SecretFinding>> {
+        let tip = repo.refname_to_id(&format!("refs/heads/{branch}"))?;
+        let known = [
+            format!("refs/remotes/{remote}/{branch}"),
+            format!("refs/remotes/{remote}/{base_branch}"),
+            format!("refs/heads/{base_branch}"),
+        ]
+        .iter()
+        .find_map(|name| repo.refname_to_id(name).ok());
+        self.scan_commits(repo, tip, known)
+    }
+
+    /// Findings in the commits reachable from `tip` but not from `exclude`
+    pub fn scan_commits(
+        &self,
+        repo: &Repository,
+        tip: Oid,
+        exclude: Option<Oid>,
+    ) -> Result<Vec<SecretFinding>> {
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+        walk.push(tip)?;
+        if let Some(exclude) = exclude {
+            walk.hide(exclude)?;
+        }
+
+        let mut findings: Vec<SecretFinding> = Vec::new();
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            for found in self.scan_commit(repo, &commit)? {
+                let seen = findings.iter().any(|f| {
+                    f.rule == found.rule
+                        && f.file == found.file
+                        && f.line == found.line
+                        && f.masked == found.masked
+                });
+                if !seen {
+                    findings.push(found);
+                }
+            }
+        }
+        Ok(findings)
+    }
+
+    /// Findings in what `commit` adds over each of its parents
+    ///
+    /// A merge is diffed against every parent, so content brought in from the
+    /// other side or written while resolving conflicts is scanned too.
+    fn scan_commit(&self, repo: &Repository, commit: &git2::Commit) -> Result<Vec<SecretFinding>> {
+        if commit.parent_count() == 0 {
+            return self.scan_diff(repo, commit, None);
+        }
+        let mut findings = Vec::new();
+        for parent in commit.parents() {
+            findings.extend(self.scan_diff(repo, commit, Some(&parent.tree()?))?);
+        }
+        Ok(findings)
+    }
+
+    fn scan_diff(
+        &self,
+        repo: &Repository,
+        commit: &git2::Commit,
+        parent_tree: Option<&git2::Tree>,
+    ) -> Result<Vec<SecretFinding>> {
+        let diff = repo.diff_tree_to_tree(parent_tree, Some(&commit.tree()?), None)?;
+        let commit_id = commit.id().to_string();
+        let mut findings = Vec::new();
+
+        for delta in diff.deltas() {
+            if !matches!(
+                delta.status(),
+                Delta::Added | Delta::Modified | Delta::Renamed | Delta::Copied
+            ) {
+                continue;
+            }
+            if let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) {
+                if is_env_file(path) && !self.is_allowed(path) {
+                    findings.push(SecretFinding {
+                        rule: ENV_FILE_RULE.to_string(),
+                        file: path.to_string(),
+                        line: None,
+                        masked: "(whole file)".to_string(),
+                        commit: commit_id.clone(),
+                    });
+                }
+            }
+        }
+
+        diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+            if line.origin() != '+' {
+                return true;
+            }
+            let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) else {
+                return true;
+            };
+            let text = String::from_utf8_lossy(line.content());
+            for mut found in self.scan_line(path, line.new_lineno(), &text) {
+                found.commit = commit_id.clone();
+                findings.push(found);
+            }
+            true
+        })?;
+        Ok(findings)
+    }
+
+    fn is_allowed(&self, value: &str) -> bool {
+        self.allowlist.iter().any(|entry| entry.is_match(value))
+    }
+}
+
+/// Refuse with `SecretsDetected` when the commits a push would publish contain secrets
+///
+/// Commits already on `base_branch` are taken as published when the branch has
+/// never been pushed.
+pub fn ensure_no_secrets(
+    repo: &Repository,
+    branch: &str,
+    remote: &str,
+    base_branch: &str,
+) -> Result<()> {
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    let Some(scanner) = SecretScanner::for_repository(root)? else {
+        return Ok(());
+    };
+    let findings = scanner.scan_push(repo, branch, remote, base_branch)?;
+    if findings.is_empty() {
+        return Ok(());
+    }
+    Err(SecretsDetected {
+        branch: branch.to_string(),
+        findings,
+    }
+    .into())
+}
+
+fn finding(rule: &str, file: &str, line: Option<u32>, value: &str) -> SecretFinding {
+    SecretFinding {
+        rule: rule.to_string(),
+        file: file.to_string(),
+        line,
+        masked: mask(value),
+        commit: String::new(),
+    }
+}
+
+/// Keep the first four characters, enough to tell which kind of secret it is
+pub fn mask(secret: &str) -> String {
+    let visible: String = if secret.chars().count() > 8 {
+        secret.chars().take(4).collect()
+    } else {
+        String::new()
+    };
+    let hidden = secret.chars().count() - visible.chars().count();
+    format!("{visible}{}", "*".repeat(hidden.min(16)))
+}
+
+fn is_env_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    (name == ".env" || name.starts_with(".env."))
+        && !ENV_TEMPLATE_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+/// Runs of characters that can make up a key or token, with their offsets
+fn tokens(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = None;
+    let mut spans = Vec::new();
+    for (index, c) in text.char_indices() {
+        let part = c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '_' | '-');
+        match (part, start) {
+            (true, None) => start = Some(index),
+            (false, Some(from)) => {
+                spans.push((from, &text[from..index]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        spans.push((from, &text[from..]));
+    }
+    spans.into_iter()
+}
+
+/// Mixed letters and digits; rules out words, paths and plain numbers
+fn looks_random(token: &str) -> bool {
+    token.chars().any(|c| c.is_ascii_digit())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_uppercase())
+}
+
+/// Shannon entropy in bits per character
+pub fn shannon_entropy(value: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let length = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / length;
+            -p * p.log2()
+        })
+        .sum()
+}
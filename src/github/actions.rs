@@ -2,6 +2,7 @@
 use super::errors::GitHubError;
 use async_trait::async_trait;
 use octocrab::Octocrab;
+use serde::Deserialize;
 use serde_json::json;
 use tracing::{debug, info, warn};
 
@@ -50,6 +51,80 @@ pub struct WorkflowRun {
     pub workflow_name: String,
 }
 
+/// A job that failed in a workflow run
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedJob {
+    pub id: u64,
+    pub run_id: u64,
+    pub name: String,
+    pub html_url: String,
+    /// First step that failed, when GitHub reports one
+    pub failed_step: Option<String>,
+}
+
+/// An artifact uploaded by a workflow run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunArtifact {
+    pub id: u64,
+    pub name: String,
+    pub expired: bool,
+}
+
+#[derive(Deserialize)]
+struct RunsPage {
+    workflow_runs: Vec<RunEntry>,
+}
+
+#[derive(Deserialize)]
+struct RunEntry {
+    id: u64,
+    conclusion: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JobsPage {
+    jobs: Vec<JobEntry>,
+}
+
+#[derive(Deserialize)]
+struct JobEntry {
+    id: u64,
+    run_id: u64,
+    name: String,
+    #[serde(default)]
+    html_url: Option<String>,
+    conclusion: Option<String>,
+    #[serde(default)]
+    steps: Vec<StepEntry>,
+}
+
+#[derive(Deserialize)]
+struct StepEntry {
+    name: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ArtifactsPage {
+    artifacts: Vec<ArtifactEntry>,
+}
+
+#[derive(Deserialize)]
+struct ArtifactEntry {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    expired: bool,
+}
+
+/// Conclusions that mean a run or job did not pass
+fn is_failed(conclusion: Option<&str>) -> bool {
+    matches!(
+        conclusion,
+        Some("failure" | "timed_out" | "startup_failure")
+    )
+}
+
 #[async_trait]
 pub trait GitHubActions {
     /// Trigger a workflow by filename with optional inputs
@@ -86,6 +161,116 @@ impl ActionsHandler {
             repo,
         }
     }
+
+    /// Failed jobs of every failed workflow run on a commit
+    pub async fn failed_jobs(&self, head_sha: &str) -> Result<Vec<FailedJob>, GitHubError> {
+        debug!(head_sha = head_sha, "Fetching failed workflow jobs");
+
+        let runs: RunsPage = self
+            .octocrab
+            .get(
+                format!("/repos/{}/{}/actions/runs", self.owner, self.repo),
+                Some(&json!({ "head_sha": head_sha, "per_page": 100 })),
+            )
+            .await?;
+
+        let mut failed = Vec::new();
+        for run in runs
+            .workflow_runs
+            .iter()
+            .filter(|run| is_failed(run.conclusion.as_deref()))
+        {
+            let jobs: JobsPage = self
+                .octocrab
+                .get(
+                    format!(
+                        "/repos/{}/{}/actions/runs/{}/jobs",
+                        self.owner, self.repo, run.id
+                    ),
+                    Some(&json!({ "filter": "latest", "per_page": 100 })),
+                )
+                .await?;
+            failed.extend(
+                jobs.jobs
+                    .into_iter()
+                    .filter(|job| is_failed(job.conclusion.as_deref()))
+                    .map(|job| FailedJob {
+                        failed_step: job
+                            .steps
+                            .into_iter()
+                            .find(|step| is_failed(step.conclusion.as_deref()))
+                            .map(|step| step.name),
+                        html_url: job.html_url.unwrap_or_else(|| {
+                            format!(
+                                "https://github.com/{}/{}/actions/runs/{}/job/{}",
+                                self.owner, self.repo, job.run_id, job.id
+                            )
+                        }),
+                        id: job.id,
+                        run_id: job.run_id,
+                        name: job.name,
+                    }),
+            );
+        }
+        Ok(failed)
+    }
+
+    /// Plain text log of a job
+    pub async fn job_log(&self, job_id: u64) -> Result<String, GitHubError> {
+        debug!(job_id = job_id, "Downloading job log");
+
+        // GitHub answers with a redirect to short-lived log storage
+        let response = self
+            .octocrab
+            ._get(format!(
+                "/repos/{}/{}/actions/jobs/{}/logs",
+                self.owner, self.repo, job_id
+            ))
+            .await?;
+        let response = self.octocrab.follow_location_to_data(response).await?;
+        let response = octocrab::map_github_error(response).await?;
+        Ok(self.octocrab.body_to_string(response).await?)
+    }
+
+    /// Artifacts a workflow run uploaded
+    pub async fn run_artifacts(&self, run_id: u64) -> Result<Vec<RunArtifact>, GitHubError> {
+        let page: ArtifactsPage = self
+            .octocrab
+            .get(
+                format!(
+                    "/repos/{}/{}/actions/runs/{}/artifacts",
+                    self.owner, self.repo, run_id
+                ),
+                Some(&json!({ "per_page": 100 })),
+            )
+            .await?;
+        Ok(page
+            .artifacts
+            .into_iter()
+            .map(|artifact| RunArtifact {
+                id: artifact.id,
+                name: artifact.name,
+                expired: artifact.expired,
+            })
+            .collect())
+    }
+
+    /// Zip archive of an artifact
+    pub async fn download_artifact(&self, artifact_id: u64) -> Result<Vec<u8>, GitHubError> {
+        debug!(artifact_id = artifact_id, "Downloading artifact");
+
+        let data = self
+            .octocrab
+            .actions()
+            .download_artifact(
+                &self.owner,
+                &self.repo,
+                artifact_id.into(),
+                octocrab::params::actions::ArchiveFormat::Zip,
+            )
+            .await?;
+        Ok(data.to_vec())
+    }
 }
 
 #[async_trait]
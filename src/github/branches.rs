@@ -39,9 +39,16 @@ impl BranchHandler {
     }
 
     /// Delete a branch
-    pub async fn delete_branch(&self, _branch_name: &str) -> Result<(), GitHubError> {
-        // Branch deletion would use GitHub API
-        println!("🗑️  Branch deletion attempted via GitHub API (placeholder implementation)");
+    pub async fn delete_branch(&self, branch_name: &str) -> Result<(), GitHubError> {
+        self.octocrab
+            .repos(&self.owner, &self.repo)
+            .delete_ref(&octocrab::params::repos::Reference::Branch(
+                branch_name.to_string(),
+            ))
+            .await
+            .map_err(GitHubError::ApiError)?;
+
+        println!("🗑️  Deleted remote branch {branch_name}");
         Ok(())
     }
 
@@ -706,6 +706,15 @@ impl GitHubClient {
         self.pulls.get_pull_request(pr_number).await
     }
 
+    pub async fn update_pull_request(
+        &self,
+        pr_number: u64,
+        title: &str,
+        body: &str,
+    ) -> Result<octocrab::models::pulls::PullRequest, GitHubError> {
+        self.pulls.update_pull_request(pr_number, title, body).await
+    }
+
     /// Check if a PR is ready for merging
     pub async fn is_pr_mergeable(
         &self,
@@ -789,6 +798,10 @@ impl GitHubClient {
         self.issues.remove_label(issue_number, label).await
     }
 
+    pub async fn close_issue(&self, issue_number: u64) -> Result<(), GitHubError> {
+        self.issues.close_issue(issue_number).await
+    }
+
     pub async fn create_issue(
         &self,
         title: &str,
@@ -141,6 +141,18 @@ impl IssueHandler {
         Ok(())
     }
 
+    /// Close an issue
+    pub async fn close_issue(&self, issue_number: u64) -> Result<(), GitHubError> {
+        self.octocrab
+            .issues(&self.owner, &self.repo)
+            .update(issue_number)
+            .state(octocrab::models::IssueState::Closed)
+            .send()
+            .await
+            .map_err(GitHubError::ApiError)?;
+        Ok(())
+    }
+
     /// Create a new issue
     pub async fn create_issue(
         &self,
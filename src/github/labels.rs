@@ -0,0 +1,7 @@
+//! Labels that route issues between agents, bundling and humans
+
+/// Label `land` puts on an issue once its work is up for bundling
+pub const REVIEW_LABEL: &str = "route:review";
+
+/// Label that takes an issue out of agent routing until a human has looked
+pub const HUMAN_ONLY_LABEL: &str = "route:human-only";
@@ -4,6 +4,7 @@ pub mod client;
 pub mod comments;
 pub mod errors;
 pub mod issues;
+pub mod labels;
 pub mod pulls;
 pub mod retry;
 pub mod types;
@@ -21,12 +21,59 @@ pub struct PullRequestStatus {
     pub state: String,
     pub mergeable: Option<bool>,
     pub merged: bool,
-    pub ci_status: String,
+    pub ci_status: CiStatus,
     pub approved_reviews: usize,
     pub requested_changes: usize,
     pub head_sha: String,
 }
 
+/// Combined result of the check runs and commit statuses on a PR head
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiStatus {
+    /// Every check passed
+    Success,
+    /// Checks are queued or still running
+    Pending,
+    /// At least one check failed
+    Failure,
+    /// No checks report on the commit
+    NoChecks,
+}
+
+impl CiStatus {
+    /// State of a single check run
+    pub fn from_check_run(completed: bool, conclusion: Option<&str>) -> Self {
+        match (completed, conclusion) {
+            (false, _) => Self::Pending,
+            (true, Some("success" | "neutral" | "skipped")) => Self::Success,
+            (true, _) => Self::Failure,
+        }
+    }
+
+    /// Combine individual check states: any failure wins, then anything pending
+    pub fn combine(states: impl IntoIterator<Item = CiStatus>) -> Self {
+        states
+            .into_iter()
+            .fold(Self::NoChecks, |combined, state| match (combined, state) {
+                (Self::Failure, _) | (_, Self::Failure) => Self::Failure,
+                (Self::Pending, _) | (_, Self::Pending) => Self::Pending,
+                (Self::Success, _) | (_, Self::Success) => Self::Success,
+                _ => Self::NoChecks,
+            })
+    }
+}
+
+impl std::fmt::Display for CiStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Success => "success",
+            Self::Pending => "pending",
+            Self::Failure => "failure",
+            Self::NoChecks => "no checks",
+        })
+    }
+}
+
 #[allow(dead_code)] // PR functionality for future GitHub integration
 impl PullRequestHandler {
     pub fn new(octocrab: Octocrab, owner: String, repo: String) -> Self {
@@ -62,6 +109,40 @@ impl PullRequestHandler {
         Ok(pr)
     }
 
+    /// Replace the title and body of an existing pull request
+    pub async fn update_pull_request(
+        &self,
+        pr_number: u64,
+        title: &str,
+        body: &str,
+    ) -> Result<octocrab::models::pulls::PullRequest, GitHubError> {
+        let pr = self
+            .octocrab
+            .pulls(&self.owner, &self.repo)
+            .update(pr_number)
+            .title(title)
+            .body(body)
+            .send()
+            .await?;
+
+        println!("📝 Updated PR #{pr_number}: {title}");
+        Ok(pr)
+    }
+
+    /// Ask users and teams (by slug) to review a pull request
+    pub async fn request_reviewers(
+        &self,
+        pr_number: u64,
+        users: Vec<String>,
+        teams: Vec<String>,
+    ) -> Result<(), GitHubError> {
+        self.octocrab
+            .pulls(&self.owner, &self.repo)
+            .request_reviews(pr_number, users, teams)
+            .await?;
+        Ok(())
+    }
+
     /// Get a specific pull request by number
     pub async fn get_pull_request(
         &self,
@@ -110,8 +191,11 @@ impl PullRequestHandler {
     pub async fn get_pr_status(&self, pr_number: u64) -> Result<PullRequestStatus, GitHubError> {
         let pr = self.get_pull_request(pr_number).await?;
 
-        // Get commit status for the PR head - simplified for compatibility
-        let ci_status = "unknown".to_string();
+        // A failed lookup counts as pending so nothing merges on missing information
+        let ci_status = self
+            .get_ci_status(&pr.head.sha)
+            .await
+            .unwrap_or(CiStatus::Pending);
 
         // Check reviews
         let reviews_result = self
@@ -192,6 +276,35 @@ impl PullRequestHandler {
         })
     }
 
+    /// Combined CI state of a commit, from both check runs and commit statuses
+    pub async fn get_ci_status(&self, sha: &str) -> Result<CiStatus, GitHubError> {
+        let check_runs = self
+            .octocrab
+            .checks(&self.owner, &self.repo)
+            .list_check_runs_for_git_ref(octocrab::params::repos::Commitish(sha.to_string()))
+            .per_page(100)
+            .send()
+            .await?;
+
+        let combined: octocrab::models::CombinedStatus = self
+            .octocrab
+            .get(
+                format!("/repos/{}/{}/commits/{}/status", self.owner, self.repo, sha),
+                None::<&()>,
+            )
+            .await?;
+
+        let runs = check_runs.check_runs.iter().map(|run| {
+            CiStatus::from_check_run(run.completed_at.is_some(), run.conclusion.as_deref())
+        });
+        let statuses = combined.statuses.iter().map(|status| match status.state {
+            octocrab::models::StatusState::Success => CiStatus::Success,
+            octocrab::models::StatusState::Pending => CiStatus::Pending,
+            _ => CiStatus::Failure,
+        });
+        Ok(CiStatus::combine(runs.chain(statuses)))
+    }
+
     /// Merge a pull request
     pub async fn merge_pull_request(
         &self,
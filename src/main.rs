@@ -8,6 +8,7 @@ mod agents;
 #[cfg(feature = "autonomous")]
 mod autonomous;
 mod bundling;
+mod ci;
 mod cli;
 mod config;
 mod database;
@@ -28,14 +29,17 @@ use cli::commands::{
     actions::ActionsCommand,
     agent::{
         AgentDiagnoseCommand, AgentForceResetCommand, AgentRecoverCommand, AgentStatusCommand,
-        AgentValidateCommand,
+        AgentValidateCommand, RecoveryQuery,
     },
     bundle::BundleCommand,
     doctor::DoctorCommand,
+    history::HistoryCommand,
     init::InitCommand,
     land::LandCommand,
+    merge::MergeCommand,
     peek::PeekCommand,
     pop::PopCommand,
+    preflight::PreflightCommand,
     reset::ResetCommand,
     route::RouteCommand,
     show_how_to_get_work,
@@ -48,8 +52,22 @@ use database::init_database;
 use shutdown::ShutdownCoordinator;
 use telemetry::init_telemetry;
 
+#[cfg(all(feature = "database", feature = "autonomous"))]
+use cli::commands::db::DbImportStateCommand;
+#[cfg(feature = "autonomous")]
+use cli::{
+    commands::run::RunCommand,
+    commands::state::{StateExportCommand, StateGcCommand, StateImportCommand},
+    StateCommands,
+};
+#[cfg(feature = "database")]
+use cli::commands::db::{
+    DbBackupCommand, DbExportCommand, DbMigrateCommand, DbStatusCommand, DbVacuumCommand,
+};
 #[cfg(feature = "metrics")]
 use cli::commands::metrics::{ExportMetricsCommand, MetricsCommand};
+#[cfg(feature = "database")]
+use cli::DbCommands;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -63,16 +81,18 @@ async fn main() -> Result<()> {
         eprintln!("Warning: Failed to initialize telemetry: {e}");
     }
 
-    // Initialize database (if enabled)
-    if let Err(e) = init_database().await {
-        eprintln!("Warning: Failed to initialize database: {e}");
+    let cli = Cli::parse();
+
+    // Initialize database (if enabled); `db` manages the schema itself
+    if !manages_database(&cli) {
+        if let Err(e) = init_database().await {
+            eprintln!("Warning: Failed to initialize database: {e}");
+        }
     }
 
     // Create shutdown coordinator for graceful shutdowns
     let _shutdown_coordinator = ShutdownCoordinator::new();
 
-    let cli = Cli::parse();
-
     let result = match cli.command {
         // Default behavior: cargo run (no subcommand) - explain how to get work
         None => show_how_to_get_work().await,
@@ -124,9 +144,13 @@ async fn main() -> Result<()> {
             days,
             dry_run,
             verbose,
+            force,
+            split,
         }) => {
             LandCommand::new(!open_only, days, dry_run, verbose)
                 .with_ci_mode(cli.ci_mode)
+                .with_force(force)
+                .with_split(split)
                 .execute()
                 .await
         }
@@ -135,9 +159,64 @@ async fn main() -> Result<()> {
             dry_run,
             verbose,
             diagnose,
+            conflict_strategy,
+            assembly_mode,
+            continue_bundle,
+            abort,
+            resume,
+            history,
+            rebuild,
+            drop,
         }) => {
             BundleCommand::new(force, dry_run, verbose, diagnose)
                 .with_ci_mode(cli.ci_mode)
+                .with_conflict_strategy(conflict_strategy)
+                .with_assembly_mode(assembly_mode)
+                .with_continue(continue_bundle)
+                .with_abort(abort)
+                .with_resume(resume)
+                .with_history(history)
+                .with_rebuild(rebuild.zip(drop))
+                .execute()
+                .await
+        }
+        Some(Commands::Merge {
+            pr,
+            method,
+            watch,
+            interval,
+            dry_run,
+        }) => {
+            MergeCommand::new(dry_run)
+                .with_ci_mode(cli.ci_mode)
+                .with_pr(pr)
+                .with_method(method)
+                .with_watch(watch, interval)
+                .execute()
+                .await
+        }
+        Some(Commands::Preflight {
+            branch,
+            fix,
+            format,
+        }) => {
+            PreflightCommand::new(format)
+                .with_ci_mode(cli.ci_mode)
+                .with_branch(branch)
+                .with_fix(fix)
+                .execute()
+                .await
+        }
+        Some(Commands::History {
+            agent,
+            issue,
+            since,
+            format,
+        }) => {
+            HistoryCommand::new(format)
+                .with_agent(agent)
+                .with_issue(issue)
+                .with_since(since)
                 .execute()
                 .await
         }
@@ -195,8 +274,23 @@ async fn main() -> Result<()> {
                 agent,
                 all,
                 dry_run,
+                explain,
+                status,
+                subject,
+                message,
+                files,
+                count,
             } => {
+                let explain = explain.map(|error_type| RecoveryQuery {
+                    error_type,
+                    status,
+                    subject,
+                    message,
+                    files,
+                    count,
+                });
                 AgentRecoverCommand::new(agent.clone(), all, dry_run)
+                    .with_explain(explain)
                     .with_ci_mode(cli.ci_mode)
                     .execute()
                     .await
@@ -217,6 +311,74 @@ async fn main() -> Result<()> {
                     .await
             }
         },
+        #[cfg(feature = "database")]
+        Some(Commands::Db { url, command }) => match command {
+            DbCommands::Status { format } => {
+                DbStatusCommand::new(format).with_url(url).execute().await
+            }
+            DbCommands::Migrate { target, dry_run } => {
+                DbMigrateCommand::new(target, dry_run)
+                    .with_url(url)
+                    .execute()
+                    .await
+            }
+            DbCommands::Vacuum => DbVacuumCommand::new().with_url(url).execute().await,
+            DbCommands::Backup { output } => {
+                DbBackupCommand::new(output).with_url(url).execute().await
+            }
+            DbCommands::Export { output, table } => {
+                DbExportCommand::new(table, output)
+                    .with_url(url)
+                    .execute()
+                    .await
+            }
+            #[cfg(feature = "autonomous")]
+            DbCommands::ImportState { from } => {
+                DbImportStateCommand::new(from)
+                    .with_url(url)
+                    .execute()
+                    .await
+            }
+        },
+        #[cfg(feature = "autonomous")]
+        Some(Commands::Run {
+            agent,
+            max_hours,
+            until_idle,
+        }) => RunCommand::new(agent, max_hours, until_idle).execute().await,
+        #[cfg(feature = "autonomous")]
+        Some(Commands::State { state_dir, command }) => match command {
+            StateCommands::Gc { dry_run, agent } => {
+                StateGcCommand::new(dry_run, agent)
+                    .with_state_dir(state_dir)
+                    .execute()
+                    .await
+            }
+            StateCommands::Export {
+                agent,
+                output,
+                branch,
+                repo,
+            } => {
+                StateExportCommand::new(agent, output)
+                    .with_branch(branch)
+                    .with_workspace(repo)
+                    .with_state_dir(state_dir)
+                    .execute()
+                    .await
+            }
+            StateCommands::Import {
+                archive,
+                force,
+                repo,
+            } => {
+                StateImportCommand::new(archive, force)
+                    .with_workspace(repo)
+                    .with_state_dir(state_dir)
+                    .execute()
+                    .await
+            }
+        },
         Some(Commands::Doctor { format, verbose }) => {
             DoctorCommand::new(format, verbose)
                 .with_ci_mode(cli.ci_mode)
@@ -231,3 +393,13 @@ async fn main() -> Result<()> {
 
     result
 }
+
+#[cfg(feature = "database")]
+fn manages_database(cli: &Cli) -> bool {
+    matches!(cli.command, Some(Commands::Db { .. }))
+}
+
+#[cfg(not(feature = "database"))]
+fn manages_database(_cli: &Cli) -> bool {
+    false
+}
@@ -1,7 +1,7 @@
 use super::storage::MetricsStorage;
 use super::types::*;
 use crate::github::GitHubError;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct MetricsAnalyzer {
@@ -21,6 +21,10 @@ impl MetricsAnalyzer {
         }
     }
 
+    pub fn with_storage(storage: MetricsStorage) -> Self {
+        Self { storage }
+    }
+
     pub async fn calculate_metrics(
         &self,
         lookback_hours: Option<u64>,
@@ -41,88 +45,40 @@ impl MetricsAnalyzer {
             .filter(|attempt| cutoff_time.is_none_or(|cutoff| attempt.attempt_time >= cutoff))
             .collect();
 
-        let total_attempts = filtered_attempts.len() as u64;
-        let successful_integrations = filtered_attempts
-            .iter()
-            .filter(|a| matches!(a.outcome, IntegrationOutcome::Success))
-            .count() as u64;
-        let failed_integrations = filtered_attempts
-            .iter()
-            .filter(|a| matches!(a.outcome, IntegrationOutcome::Failed))
-            .count() as u64;
-
-        let overall_success_rate = if total_attempts > 0 {
-            successful_integrations as f64 / total_attempts as f64
-        } else {
-            0.0
-        };
-
-        // Calculate average time to merge for successful integrations
-        let successful_durations: Vec<u64> = filtered_attempts
-            .iter()
-            .filter(|a| matches!(a.outcome, IntegrationOutcome::Success))
-            .filter_map(|a| a.duration_seconds)
-            .collect();
-
-        let average_time_to_merge_seconds = if !successful_durations.is_empty() {
-            Some(
-                successful_durations.iter().sum::<u64>() as f64 / successful_durations.len() as f64,
-            )
-        } else {
-            None
-        };
-
-        // Calculate per-agent metrics
-        let mut agent_stats: HashMap<String, (u64, u64, u64, Vec<u64>, Option<u64>)> =
-            HashMap::new();
-
-        for attempt in &filtered_attempts {
-            let entry =
-                agent_stats
-                    .entry(attempt.agent_id.clone())
-                    .or_insert((0, 0, 0, Vec::new(), None));
-
-            entry.0 += 1; // total attempts
-
-            match attempt.outcome {
-                IntegrationOutcome::Success => entry.1 += 1,
-                IntegrationOutcome::Failed => entry.2 += 1,
-                IntegrationOutcome::InProgress => {}
-            }
-
-            if let Some(duration) = attempt.duration_seconds {
-                entry.3.push(duration);
+        // Long windows come from rollups, which outlive the raw records
+        let by_agent = match self
+            .storage
+            .load_summaries(MetricKind::Integration, lookback_hours)
+            .await?
+        {
+            Some(summaries) => summaries,
+            None => {
+                let mut summaries: BTreeMap<String, MetricSummary> = BTreeMap::new();
+                for attempt in &filtered_attempts {
+                    attempt.summarize(summaries.entry(attempt.agent_id.clone()).or_default());
+                }
+                summaries
             }
+        };
 
-            // Update last activity
-            entry.4 = Some(match entry.4 {
-                Some(last) => last.max(attempt.attempt_time),
-                None => attempt.attempt_time,
-            });
+        let mut overall = MetricSummary::default();
+        for summary in by_agent.values() {
+            overall.merge(summary);
         }
 
-        let agent_metrics: HashMap<String, AgentMetrics> = agent_stats
+        let agent_metrics: HashMap<String, AgentMetrics> = by_agent
             .into_iter()
-            .map(
-                |(agent_id, (total, success, failed, durations, last_activity))| {
-                    let average_completion_time_seconds = if !durations.is_empty() {
-                        Some(durations.iter().sum::<u64>() as f64 / durations.len() as f64)
-                    } else {
-                        None
-                    };
-
-                    let metrics = AgentMetrics {
-                        agent_id: agent_id.clone(),
-                        total_attempts: total,
-                        successful_integrations: success,
-                        failed_integrations: failed,
-                        average_completion_time_seconds,
-                        last_activity,
-                    };
-
-                    (agent_id, metrics)
-                },
-            )
+            .map(|(agent_id, summary)| {
+                let metrics = AgentMetrics {
+                    agent_id: agent_id.clone(),
+                    total_attempts: summary.samples,
+                    successful_integrations: summary.successes,
+                    failed_integrations: summary.failures,
+                    average_completion_time_seconds: summary.average(),
+                    last_activity: summary.last_seen,
+                };
+                (agent_id, metrics)
+            })
             .collect();
 
         // Get recent attempts (last 20)
@@ -131,11 +87,12 @@ impl MetricsAnalyzer {
         recent_attempts.truncate(20);
 
         Ok(IntegrationMetrics {
-            overall_success_rate,
-            total_attempts,
-            successful_integrations,
-            failed_integrations,
-            average_time_to_merge_seconds,
+            overall_success_rate: overall.success_rate(),
+            total_attempts: overall.samples,
+            successful_integrations: overall.successes,
+            failed_integrations: overall.failures,
+            // Only successful attempts have reached a merge
+            average_time_to_merge_seconds: overall.success_average(),
             agent_metrics,
             recent_attempts,
         })
@@ -145,7 +102,8 @@ impl MetricsAnalyzer {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<String, GitHubError> {
-        let performance_reporter = super::performance::PerformanceReporter::new();
+        let performance_reporter =
+            super::performance::PerformanceReporter::with_storage(self.storage.clone());
         performance_reporter
             .format_performance_report(lookback_hours)
             .await
@@ -159,11 +117,38 @@ impl MetricsAnalyzer {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<HashMap<String, serde_json::Value>, GitHubError> {
-        let routing_metrics = self.storage.load_routing_metrics(lookback_hours).await?;
-        let utilization_metrics = self
+        let routing = match self
             .storage
-            .load_agent_utilization_metrics(lookback_hours)
-            .await?;
+            .load_summaries(MetricKind::Routing, lookback_hours)
+            .await?
+        {
+            Some(summaries) => merged(summaries.values()),
+            None => {
+                let mut summary = MetricSummary::default();
+                for metrics in self.storage.load_routing_metrics(lookback_hours).await? {
+                    metrics.summarize(&mut summary);
+                }
+                summary
+            }
+        };
+        let utilization = match self
+            .storage
+            .load_summaries(MetricKind::Utilization, lookback_hours)
+            .await?
+        {
+            Some(summaries) => merged(summaries.values()),
+            None => {
+                let mut summary = MetricSummary::default();
+                for metrics in self
+                    .storage
+                    .load_agent_utilization_metrics(lookback_hours)
+                    .await?
+                {
+                    metrics.summarize(&mut summary);
+                }
+                summary
+            }
+        };
         let bottlenecks = self
             .storage
             .load_performance_bottlenecks(lookback_hours)
@@ -171,10 +156,18 @@ impl MetricsAnalyzer {
 
         Ok(
             super::reports::MetricsReporter::export_metrics_for_monitoring(
-                &routing_metrics,
-                &utilization_metrics,
+                &routing,
+                &utilization,
                 &bottlenecks,
             ),
         )
     }
 }
+
+fn merged<'a>(summaries: impl IntoIterator<Item = &'a MetricSummary>) -> MetricSummary {
+    let mut total = MetricSummary::default();
+    for summary in summaries {
+        total.merge(summary);
+    }
+    total
+}
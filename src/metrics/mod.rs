@@ -5,6 +5,8 @@ pub mod analysis;
 pub mod bottleneck;
 pub mod performance;
 pub mod reports;
+#[cfg(feature = "database")]
+pub mod sqlite;
 pub mod storage;
 pub mod tracking;
 pub mod types;
@@ -28,12 +30,17 @@ impl MetricsTracker {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<IntegrationMetrics, GitHubError> {
-        let analyzer = MetricsAnalyzer::new();
+        let analyzer = MetricsAnalyzer::with_storage(self.storage.clone());
         analyzer.calculate_metrics(lookback_hours).await
     }
 
+    /// Where `metrics` reads a window of `lookback_hours` from
+    pub async fn describe_source(&self, lookback_hours: Option<u64>) -> String {
+        self.storage.describe_source(lookback_hours).await
+    }
+
     pub fn format_metrics_report(&self, metrics: &IntegrationMetrics, detailed: bool) -> String {
-        let analyzer = MetricsAnalyzer::new();
+        let analyzer = MetricsAnalyzer::with_storage(self.storage.clone());
         analyzer.format_metrics_report(metrics, detailed)
     }
 
@@ -41,7 +48,7 @@ impl MetricsTracker {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<String, GitHubError> {
-        let analyzer = MetricsAnalyzer::new();
+        let analyzer = MetricsAnalyzer::with_storage(self.storage.clone());
         analyzer.format_performance_report(lookback_hours).await
     }
 
@@ -49,7 +56,7 @@ impl MetricsTracker {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<HashMap<String, serde_json::Value>, GitHubError> {
-        let analyzer = MetricsAnalyzer::new();
+        let analyzer = MetricsAnalyzer::with_storage(self.storage.clone());
         analyzer.export_metrics_for_monitoring(lookback_hours).await
     }
 }
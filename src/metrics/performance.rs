@@ -20,6 +20,10 @@ impl PerformanceReporter {
         }
     }
 
+    pub fn with_storage(storage: MetricsStorage) -> Self {
+        Self { storage }
+    }
+
     pub async fn format_performance_report(
         &self,
         lookback_hours: Option<u64>,
@@ -142,52 +142,36 @@ impl MetricsReporter {
         report
     }
 
+    /// Monitoring values from routing and utilization summaries, which come
+    /// from raw records or rollups depending on the window
     pub fn export_metrics_for_monitoring(
-        routing_metrics: &[RoutingMetrics],
-        utilization_metrics: &[AgentUtilizationMetrics],
+        routing: &MetricSummary,
+        utilization: &MetricSummary,
         bottlenecks: &[PerformanceBottleneck],
     ) -> HashMap<String, serde_json::Value> {
         let mut export = HashMap::new();
 
         // Export routing metrics
-        if !routing_metrics.is_empty() {
-            let avg_routing_time: f64 = routing_metrics
-                .iter()
-                .map(|m| m.routing_duration_ms as f64)
-                .sum::<f64>()
-                / routing_metrics.len() as f64;
-
+        if routing.samples > 0 {
             export.insert(
                 "routing_avg_duration_ms".to_string(),
                 serde_json::Value::Number(
-                    serde_json::Number::from_f64(avg_routing_time)
+                    serde_json::Number::from_f64(routing.average().unwrap_or(0.0))
                         .unwrap_or(serde_json::Number::from(0)),
                 ),
             );
 
-            let success_rate = routing_metrics
-                .iter()
-                .filter(|m| matches!(m.decision_outcome, RoutingDecision::TaskAssigned { .. }))
-                .count() as f64
-                / routing_metrics.len() as f64;
-
             export.insert(
                 "routing_success_rate".to_string(),
                 serde_json::Value::Number(
-                    serde_json::Number::from_f64(success_rate)
+                    serde_json::Number::from_f64(routing.success_rate())
                         .unwrap_or(serde_json::Number::from(0)),
                 ),
             );
         }
 
         // Export agent utilization
-        if !utilization_metrics.is_empty() {
-            let avg_utilization: f64 = utilization_metrics
-                .iter()
-                .map(|m| m.utilization_percentage)
-                .sum::<f64>()
-                / utilization_metrics.len() as f64;
-
+        if let Some(avg_utilization) = utilization.average() {
             export.insert(
                 "agent_avg_utilization_percentage".to_string(),
                 serde_json::Value::Number(
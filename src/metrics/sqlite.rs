@@ -0,0 +1,618 @@
+//! SQLite metrics storage
+//!
+//! Raw records go to their own tables and, in the same transaction, into the
+//! hourly and daily buckets of `metrics_rollups`. Long time windows are then
+//! answered from a few hundred rollup rows instead of every raw record, and
+//! raw records can be pruned without losing history.
+
+use super::types::*;
+use crate::config::MetricsConfig;
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+
+/// When retention last ran in this process, so inserts only prune once an hour
+static LAST_RETENTION: AtomicU64 = AtomicU64::new(0);
+
+/// Bucket size of a rollup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollupGranularity {
+    Hour,
+    Day,
+}
+
+impl RollupGranularity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupGranularity::Hour => "hour",
+            RollupGranularity::Day => "day",
+        }
+    }
+
+    fn seconds(&self) -> u64 {
+        match self {
+            RollupGranularity::Hour => HOUR,
+            RollupGranularity::Day => DAY,
+        }
+    }
+
+    /// Start of the bucket holding `timestamp`
+    pub fn bucket_start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.seconds()
+    }
+}
+
+/// Rows deleted by one retention pass
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RetentionReport {
+    pub raw_records: u64,
+    pub hourly_rollups: u64,
+    pub daily_rollups: u64,
+}
+
+/// Metrics tables in the state database
+#[derive(Debug, Clone)]
+pub struct SqliteMetricsStore {
+    pool: SqlitePool,
+    config: MetricsConfig,
+}
+
+impl SqliteMetricsStore {
+    pub fn new(pool: SqlitePool, config: MetricsConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Rollups answering a window of `lookback_hours`, or `None` when the
+    /// window is short enough to read raw records
+    ///
+    /// Windows start at a bucket boundary, so they may include up to one
+    /// extra hour or day.
+    pub fn granularity_for(&self, lookback_hours: Option<u64>) -> Option<RollupGranularity> {
+        match lookback_hours {
+            Some(hours) if hours <= self.config.rollup_after_hours => None,
+            Some(hours) if hours <= u64::from(self.config.hourly_retention_days) * 24 => {
+                Some(RollupGranularity::Hour)
+            }
+            _ => Some(RollupGranularity::Day),
+        }
+    }
+
+    pub async fn insert_routing(&self, metrics: &RoutingMetrics) -> Result<()> {
+        let (outcome, issue_number, agent_id, reason) = match &metrics.decision_outcome {
+            RoutingDecision::TaskAssigned {
+                issue_number,
+                agent_id,
+            } => (
+                "task_assigned",
+                Some(*issue_number as i64),
+                Some(agent_id.as_str()),
+                None,
+            ),
+            RoutingDecision::NoTasksAvailable => ("no_tasks_available", None, None, None),
+            RoutingDecision::NoAgentsAvailable => ("no_agents_available", None, None, None),
+            RoutingDecision::FilteredOut { reason } => {
+                ("filtered_out", None, None, Some(reason.as_str()))
+            }
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO routing_decisions
+                (correlation_id, decided_at, duration_ms, issues_evaluated, agents_available,
+                 outcome, issue_number, agent_id, reason, bottlenecks)
+            VALUES (?1, datetime(?2, 'unixepoch'), ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )
+        .bind(&metrics.correlation_id)
+        .bind(metrics.routing_start_time as i64)
+        .bind(metrics.routing_duration_ms as i64)
+        .bind(metrics.issues_evaluated as i64)
+        .bind(metrics.agents_available as i64)
+        .bind(outcome)
+        .bind(issue_number)
+        .bind(agent_id)
+        .bind(reason)
+        .bind(serde_json::to_string(&metrics.bottlenecks_detected)?)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut summary = MetricSummary::default();
+        metrics.summarize(&mut summary);
+        roll_up(&mut tx, MetricKind::Routing, "", &summary).await?;
+        tx.commit().await?;
+        self.apply_retention_if_due().await;
+        Ok(())
+    }
+
+    pub async fn insert_utilization(&self, metrics: &AgentUtilizationMetrics) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO agent_utilization
+                (agent_id, recorded_at, current_capacity, max_capacity,
+                 utilization_percentage, active_issues, state)
+            VALUES (?1, datetime(?2, 'unixepoch'), ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(&metrics.agent_id)
+        .bind(metrics.timestamp as i64)
+        .bind(metrics.current_capacity as i64)
+        .bind(metrics.max_capacity as i64)
+        .bind(metrics.utilization_percentage)
+        .bind(serde_json::to_string(&metrics.active_issues)?)
+        .bind(&metrics.state)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut summary = MetricSummary::default();
+        metrics.summarize(&mut summary);
+        roll_up(
+            &mut tx,
+            MetricKind::Utilization,
+            &metrics.agent_id,
+            &summary,
+        )
+        .await?;
+        tx.commit().await?;
+        self.apply_retention_if_due().await;
+        Ok(())
+    }
+
+    pub async fn insert_coordination(&self, decision: &CoordinationDecision) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO coordination_decisions
+                (correlation_id, decided_at, operation, agent_id, issue_number,
+                 rationale, execution_time_ms, success, metadata)
+            VALUES (?1, datetime(?2, 'unixepoch'), ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(&decision.correlation_id)
+        .bind(decision.timestamp as i64)
+        .bind(&decision.operation)
+        .bind(&decision.agent_id)
+        .bind(decision.issue_number.map(|i| i as i64))
+        .bind(&decision.decision_rationale)
+        .bind(decision.execution_time_ms as i64)
+        .bind(decision.success)
+        .bind(serde_json::to_string(&decision.metadata)?)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut summary = MetricSummary::default();
+        decision.summarize(&mut summary);
+        roll_up(
+            &mut tx,
+            MetricKind::Coordination,
+            &decision.operation,
+            &summary,
+        )
+        .await?;
+        tx.commit().await?;
+        self.apply_retention_if_due().await;
+        Ok(())
+    }
+
+    pub async fn insert_integration(&self, attempt: &IntegrationAttempt) -> Result<()> {
+        let details = match &attempt.quality_gates {
+            Some(report) => Some(serde_json::to_string(report)?),
+            None => None,
+        };
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO integration_attempts
+                (correlation_id, issue_number, agent_id, attempted_at, phase, outcome,
+                 duration_seconds, error_message, pr_number, details)
+            VALUES (?1, ?2, ?3, datetime(?4, 'unixepoch'), ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )
+        .bind(&attempt.correlation_id)
+        .bind(attempt.issue_number as i64)
+        .bind(&attempt.agent_id)
+        .bind(attempt.attempt_time as i64)
+        .bind(variant_name(&attempt.phase))
+        .bind(variant_name(&attempt.outcome))
+        .bind(attempt.duration_seconds.map(|d| d as i64))
+        .bind(&attempt.error_message)
+        .bind(attempt.pr_number.map(|pr| pr as i64))
+        .bind(details)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut summary = MetricSummary::default();
+        attempt.summarize(&mut summary);
+        roll_up(
+            &mut tx,
+            MetricKind::Integration,
+            &attempt.agent_id,
+            &summary,
+        )
+        .await?;
+        tx.commit().await?;
+        self.apply_retention_if_due().await;
+        Ok(())
+    }
+
+    pub async fn insert_bottleneck(&self, bottleneck: &PerformanceBottleneck) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO performance_bottlenecks
+                (detected_at, bottleneck_type, severity, description, suggested_action, metrics)
+            VALUES (datetime(?1, 'unixepoch'), ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(bottleneck.detected_at as i64)
+        .bind(variant_name(&bottleneck.bottleneck_type))
+        .bind(variant_name(&bottleneck.severity))
+        .bind(&bottleneck.description)
+        .bind(&bottleneck.suggested_action)
+        .bind(serde_json::to_string(&bottleneck.metrics)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Routing records since `since` (Unix time), oldest first
+    pub async fn load_routing(&self, since: u64) -> Result<Vec<RoutingMetrics>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT correlation_id, CAST(strftime('%s', decided_at) AS INTEGER) AS at,
+                   duration_ms, issues_evaluated, agents_available, outcome,
+                   issue_number, agent_id, reason, bottlenecks
+            FROM routing_decisions
+            WHERE decided_at >= datetime(?1, 'unixepoch')
+            ORDER BY decided_at, id
+            "#,
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let outcome: String = row.get("outcome");
+                let decision_outcome = match outcome.as_str() {
+                    "task_assigned" => RoutingDecision::TaskAssigned {
+                        issue_number: row.get::<Option<i64>, _>("issue_number").unwrap_or(0) as u64,
+                        agent_id: row.get::<Option<String>, _>("agent_id").unwrap_or_default(),
+                    },
+                    "no_agents_available" => RoutingDecision::NoAgentsAvailable,
+                    "filtered_out" => RoutingDecision::FilteredOut {
+                        reason: row.get::<Option<String>, _>("reason").unwrap_or_default(),
+                    },
+                    _ => RoutingDecision::NoTasksAvailable,
+                };
+                RoutingMetrics {
+                    correlation_id: row.get("correlation_id"),
+                    routing_start_time: row.get::<i64, _>("at") as u64,
+                    routing_duration_ms: row.get::<i64, _>("duration_ms") as u64,
+                    issues_evaluated: row.get::<i64, _>("issues_evaluated") as u64,
+                    agents_available: row.get::<i64, _>("agents_available") as u64,
+                    decision_outcome,
+                    bottlenecks_detected: parse_json(row.get("bottlenecks")),
+                }
+            })
+            .collect())
+    }
+
+    pub async fn load_utilization(&self, since: u64) -> Result<Vec<AgentUtilizationMetrics>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT agent_id, CAST(strftime('%s', recorded_at) AS INTEGER) AS at,
+                   current_capacity, max_capacity, utilization_percentage, active_issues, state
+            FROM agent_utilization
+            WHERE recorded_at >= datetime(?1, 'unixepoch')
+            ORDER BY recorded_at, id
+            "#,
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| AgentUtilizationMetrics {
+                agent_id: row.get("agent_id"),
+                timestamp: row.get::<i64, _>("at") as u64,
+                current_capacity: row.get::<i64, _>("current_capacity") as u32,
+                max_capacity: row.get::<i64, _>("max_capacity") as u32,
+                utilization_percentage: row.get("utilization_percentage"),
+                active_issues: parse_json(row.get("active_issues")),
+                state: row.get("state"),
+            })
+            .collect())
+    }
+
+    pub async fn load_coordination(&self, since: u64) -> Result<Vec<CoordinationDecision>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT correlation_id, CAST(strftime('%s', decided_at) AS INTEGER) AS at,
+                   operation, agent_id, issue_number, rationale, execution_time_ms,
+                   success, metadata
+            FROM coordination_decisions
+            WHERE decided_at >= datetime(?1, 'unixepoch')
+            ORDER BY decided_at, id
+            "#,
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| CoordinationDecision {
+                correlation_id: row.get("correlation_id"),
+                timestamp: row.get::<i64, _>("at") as u64,
+                operation: row.get("operation"),
+                agent_id: row.get("agent_id"),
+                issue_number: row.get::<Option<i64>, _>("issue_number").map(|i| i as u64),
+                decision_rationale: row.get("rationale"),
+                execution_time_ms: row.get::<i64, _>("execution_time_ms") as u64,
+                success: row.get("success"),
+                metadata: parse_json(row.get("metadata")),
+            })
+            .collect())
+    }
+
+    pub async fn load_integration(&self, since: u64) -> Result<Vec<IntegrationAttempt>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT correlation_id, issue_number, agent_id,
+                   CAST(strftime('%s', attempted_at) AS INTEGER) AS at, phase, outcome,
+                   duration_seconds, error_message, pr_number, details
+            FROM integration_attempts
+            WHERE attempted_at >= datetime(?1, 'unixepoch')
+            ORDER BY attempted_at, id
+            "#,
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                Some(IntegrationAttempt {
+                    correlation_id: row.get("correlation_id"),
+                    issue_number: row.get::<i64, _>("issue_number") as u64,
+                    agent_id: row.get("agent_id"),
+                    attempt_time: row.get::<i64, _>("at") as u64,
+                    phase: parse_variant(row.get("phase"))?,
+                    outcome: parse_variant(row.get("outcome"))?,
+                    duration_seconds: row
+                        .get::<Option<i64>, _>("duration_seconds")
+                        .map(|d| d as u64),
+                    error_message: row.get("error_message"),
+                    pr_number: row.get::<Option<i64>, _>("pr_number").map(|pr| pr as u64),
+                    quality_gates: row
+                        .get::<Option<String>, _>("details")
+                        .and_then(|details| serde_json::from_str(&details).ok()),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn load_bottlenecks(&self, since: u64) -> Result<Vec<PerformanceBottleneck>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT CAST(strftime('%s', detected_at) AS INTEGER) AS at, bottleneck_type,
+                   severity, description, suggested_action, metrics
+            FROM performance_bottlenecks
+            WHERE detected_at >= datetime(?1, 'unixepoch')
+            ORDER BY detected_at, id
+            "#,
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                Some(PerformanceBottleneck {
+                    detected_at: row.get::<i64, _>("at") as u64,
+                    bottleneck_type: parse_variant(row.get("bottleneck_type"))?,
+                    severity: parse_variant(row.get("severity"))?,
+                    description: row.get("description"),
+                    suggested_action: row.get("suggested_action"),
+                    metrics: parse_json::<HashMap<String, f64>>(row.get("metrics")),
+                })
+            })
+            .collect())
+    }
+
+    /// Rollups of `kind` from the bucket holding `since` onwards, per dimension
+    pub async fn load_rollups(
+        &self,
+        kind: MetricKind,
+        granularity: RollupGranularity,
+        since: u64,
+    ) -> Result<BTreeMap<String, MetricSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT dimension,
+                   SUM(sample_count) AS samples, SUM(success_count) AS successes,
+                   SUM(failure_count) AS failures, SUM(value_sum) AS value_sum,
+                   SUM(value_count) AS value_count, MAX(value_max) AS value_max,
+                   SUM(success_value_sum) AS success_value_sum,
+                   SUM(success_value_count) AS success_value_count,
+                   MAX(last_seen) AS last_seen
+            FROM metrics_rollups
+            WHERE granularity = ?1 AND metric = ?2 AND bucket_start >= ?3
+            GROUP BY dimension
+            "#,
+        )
+        .bind(granularity.as_str())
+        .bind(kind.as_str())
+        .bind(granularity.bucket_start(since) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let summary = MetricSummary {
+                    samples: row.get::<i64, _>("samples") as u64,
+                    successes: row.get::<i64, _>("successes") as u64,
+                    failures: row.get::<i64, _>("failures") as u64,
+                    value_sum: row.get("value_sum"),
+                    value_count: row.get::<i64, _>("value_count") as u64,
+                    value_max: row.get("value_max"),
+                    success_value_sum: row.get("success_value_sum"),
+                    success_value_count: row.get::<i64, _>("success_value_count") as u64,
+                    last_seen: row.get::<Option<i64>, _>("last_seen").map(|t| t as u64),
+                };
+                (row.get("dimension"), summary)
+            })
+            .collect())
+    }
+
+    /// Delete raw records and rollups older than their retention; 0 days keeps them
+    pub async fn apply_retention(&self, now: u64) -> Result<RetentionReport> {
+        let cutoff = |days: u32| now.saturating_sub(u64::from(days) * DAY) as i64;
+        let mut report = RetentionReport::default();
+        let mut tx = self.pool.begin().await?;
+
+        if self.config.raw_retention_days > 0 {
+            let cutoff = cutoff(self.config.raw_retention_days);
+            for (table, column) in [
+                ("routing_decisions", "decided_at"),
+                ("agent_utilization", "recorded_at"),
+                ("coordination_decisions", "decided_at"),
+                ("integration_attempts", "attempted_at"),
+                ("performance_bottlenecks", "detected_at"),
+            ] {
+                report.raw_records += sqlx::query(&format!(
+                    "DELETE FROM {table} WHERE {column} < datetime(?1, 'unixepoch')"
+                ))
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            }
+        }
+        for (granularity, days) in [
+            (RollupGranularity::Hour, self.config.hourly_retention_days),
+            (RollupGranularity::Day, self.config.daily_retention_days),
+        ] {
+            if days == 0 {
+                continue;
+            }
+            let deleted = sqlx::query(
+                "DELETE FROM metrics_rollups WHERE granularity = ?1 AND bucket_start < ?2",
+            )
+            .bind(granularity.as_str())
+            .bind(cutoff(days))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            match granularity {
+                RollupGranularity::Hour => report.hourly_rollups = deleted,
+                RollupGranularity::Day => report.daily_rollups = deleted,
+            }
+        }
+
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    /// Prune at most once an hour per process; failures only warn, as the
+    /// record itself is already stored
+    async fn apply_retention_if_due(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let last = LAST_RETENTION.load(Ordering::Relaxed);
+        if now < last + HOUR
+            || LAST_RETENTION
+                .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        match self.apply_retention(now).await {
+            Ok(report) if report != RetentionReport::default() => {
+                tracing::info!(?report, "Pruned expired metrics")
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "Failed to prune expired metrics"),
+        }
+    }
+}
+
+/// Add `summary` to the hourly and daily buckets holding its last sample
+async fn roll_up(
+    tx: &mut Transaction<'_, Sqlite>,
+    kind: MetricKind,
+    dimension: &str,
+    summary: &MetricSummary,
+) -> Result<()> {
+    let at = summary.last_seen.unwrap_or_default();
+    for granularity in [RollupGranularity::Hour, RollupGranularity::Day] {
+        sqlx::query(
+            r#"
+            INSERT INTO metrics_rollups
+                (granularity, bucket_start, metric, dimension, sample_count, success_count,
+                 failure_count, value_sum, value_count, value_max, success_value_sum,
+                 success_value_count, last_seen)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT (granularity, bucket_start, metric, dimension) DO UPDATE SET
+                sample_count = sample_count + excluded.sample_count,
+                success_count = success_count + excluded.success_count,
+                failure_count = failure_count + excluded.failure_count,
+                value_sum = value_sum + excluded.value_sum,
+                value_count = value_count + excluded.value_count,
+                value_max = MAX(COALESCE(value_max, excluded.value_max),
+                                COALESCE(excluded.value_max, value_max)),
+                success_value_sum = success_value_sum + excluded.success_value_sum,
+                success_value_count = success_value_count + excluded.success_value_count,
+                last_seen = MAX(last_seen, excluded.last_seen)
+            "#,
+        )
+        .bind(granularity.as_str())
+        .bind(granularity.bucket_start(at) as i64)
+        .bind(kind.as_str())
+        .bind(dimension)
+        .bind(summary.samples as i64)
+        .bind(summary.successes as i64)
+        .bind(summary.failures as i64)
+        .bind(summary.value_sum)
+        .bind(summary.value_count as i64)
+        .bind(summary.value_max)
+        .bind(summary.success_value_sum)
+        .bind(summary.success_value_count as i64)
+        .bind(at as i64)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Name of a unit enum variant as serde writes it
+fn variant_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_variant<T: serde::de::DeserializeOwned>(name: String) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name)).ok()
+}
+
+fn parse_json<T: serde::de::DeserializeOwned + Default>(json: Option<String>) -> T {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
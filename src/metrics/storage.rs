@@ -1,11 +1,21 @@
+#[cfg(feature = "database")]
+use super::sqlite::{RollupGranularity, SqliteMetricsStore};
 use super::types::*;
 use crate::github::GitHubError;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "database")]
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
-#[derive(Debug)]
+/// Metrics records, kept in the database when one is configured and in JSONL
+/// files under `.my-little-soda/metrics` otherwise
+#[derive(Debug, Clone)]
 pub struct MetricsStorage {
     pub(super) storage_path: PathBuf,
+    /// Store to use instead of the configured database
+    #[cfg(feature = "database")]
+    database: Option<SqliteMetricsStore>,
 }
 
 impl Default for MetricsStorage {
@@ -17,10 +27,81 @@ impl Default for MetricsStorage {
 impl MetricsStorage {
     pub fn new() -> Self {
         let storage_path = PathBuf::from(".my-little-soda/metrics");
-        Self { storage_path }
+        Self {
+            storage_path,
+            #[cfg(feature = "database")]
+            database: None,
+        }
+    }
+
+    /// Storage in the given database regardless of configuration
+    #[cfg(feature = "database")]
+    pub fn with_database(pool: sqlx::SqlitePool, config: crate::config::MetricsConfig) -> Self {
+        Self {
+            database: Some(SqliteMetricsStore::new(pool, config)),
+            ..Self::new()
+        }
+    }
+
+    /// The database store, if one was given or the database is initialized
+    #[cfg(feature = "database")]
+    async fn sqlite(&self) -> Option<SqliteMetricsStore> {
+        if let Some(store) = &self.database {
+            return Some(store.clone());
+        }
+        let db = crate::database::database().await?;
+        let guard = db.read().await;
+        let manager = guard.as_ref()?;
+        let config = crate::config::config()
+            .map(|c| c.metrics.clone())
+            .unwrap_or_default();
+        Some(SqliteMetricsStore::new(manager.pool().clone(), config))
+    }
+
+    /// Where a window of `lookback_hours` is read from
+    pub async fn describe_source(&self, lookback_hours: Option<u64>) -> String {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return match store.granularity_for(lookback_hours) {
+                Some(granularity) => format!("database, {} rollups", granularity.as_str()),
+                None => "database, raw records".to_string(),
+            };
+        }
+        let _ = lookback_hours;
+        format!("JSONL files in {}", self.storage_path.display())
+    }
+
+    /// Rollups of `kind` per agent or operation for a long window, or `None`
+    /// when the window should be computed from raw records
+    pub async fn load_summaries(
+        &self,
+        kind: MetricKind,
+        lookback_hours: Option<u64>,
+    ) -> Result<Option<BTreeMap<String, MetricSummary>>, GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            let Some(granularity) = store.granularity_for(lookback_hours) else {
+                return Ok(None);
+            };
+            let since = match (lookback_hours, granularity) {
+                (None, RollupGranularity::Day) => 0,
+                _ => cutoff(lookback_hours),
+            };
+            return store
+                .load_rollups(kind, granularity, since)
+                .await
+                .map(Some)
+                .map_err(database_error);
+        }
+        let _ = (kind, lookback_hours);
+        Ok(None)
     }
 
     pub async fn store_routing_metrics(&self, metrics: RoutingMetrics) -> Result<(), GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store.insert_routing(&metrics).await.map_err(database_error);
+        }
         let file_path = self.storage_path.join("routing_metrics.jsonl");
         self.store_jsonl_entry(&file_path, &metrics).await
     }
@@ -29,6 +110,13 @@ impl MetricsStorage {
         &self,
         metrics: AgentUtilizationMetrics,
     ) -> Result<(), GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .insert_utilization(&metrics)
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("agent_utilization.jsonl");
         self.store_jsonl_entry(&file_path, &metrics).await
     }
@@ -37,6 +125,13 @@ impl MetricsStorage {
         &self,
         decision: CoordinationDecision,
     ) -> Result<(), GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .insert_coordination(&decision)
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("coordination_decisions.jsonl");
         self.store_jsonl_entry(&file_path, &decision).await
     }
@@ -45,6 +140,13 @@ impl MetricsStorage {
         &self,
         bottleneck: PerformanceBottleneck,
     ) -> Result<(), GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .insert_bottleneck(&bottleneck)
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("performance_bottlenecks.jsonl");
         self.store_jsonl_entry(&file_path, &bottleneck).await
     }
@@ -53,6 +155,13 @@ impl MetricsStorage {
         &self,
         attempt: IntegrationAttempt,
     ) -> Result<(), GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .insert_integration(&attempt)
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("integration_attempts.jsonl");
         let attempt_json = serde_json::to_string(&attempt).map_err(|e| {
             GitHubError::NotImplemented(format!("Failed to serialize attempt: {e}"))
@@ -120,6 +229,10 @@ impl MetricsStorage {
     }
 
     pub async fn load_integration_attempts(&self) -> Result<Vec<IntegrationAttempt>, GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store.load_integration(0).await.map_err(database_error);
+        }
         let file_path = self.storage_path.join("integration_attempts.jsonl");
 
         match fs::read_to_string(&file_path).await {
@@ -148,6 +261,13 @@ impl MetricsStorage {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<Vec<RoutingMetrics>, GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .load_routing(cutoff(lookback_hours))
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("routing_metrics.jsonl");
         self.load_jsonl_entries(&file_path, lookback_hours).await
     }
@@ -156,6 +276,13 @@ impl MetricsStorage {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<Vec<AgentUtilizationMetrics>, GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .load_utilization(cutoff(lookback_hours))
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("agent_utilization.jsonl");
         self.load_jsonl_entries(&file_path, lookback_hours).await
     }
@@ -164,6 +291,13 @@ impl MetricsStorage {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<Vec<CoordinationDecision>, GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .load_coordination(cutoff(lookback_hours))
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("coordination_decisions.jsonl");
         self.load_jsonl_entries(&file_path, lookback_hours).await
     }
@@ -172,6 +306,13 @@ impl MetricsStorage {
         &self,
         lookback_hours: Option<u64>,
     ) -> Result<Vec<PerformanceBottleneck>, GitHubError> {
+        #[cfg(feature = "database")]
+        if let Some(store) = self.sqlite().await {
+            return store
+                .load_bottlenecks(cutoff(lookback_hours))
+                .await
+                .map_err(database_error);
+        }
         let file_path = self.storage_path.join("performance_bottlenecks.jsonl");
         self.load_jsonl_entries(&file_path, lookback_hours).await
     }
@@ -214,3 +355,20 @@ impl MetricsStorage {
         }
     }
 }
+
+/// Unix time `lookback_hours` ago, 0 for no limit
+#[cfg(feature = "database")]
+fn cutoff(lookback_hours: Option<u64>) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    lookback_hours.map_or(0, |hours| now.saturating_sub(hours * 3600))
+}
+
+#[cfg(feature = "database")]
+fn database_error(e: anyhow::Error) -> GitHubError {
+    GitHubError::IoError(std::io::Error::other(format!(
+        "Metrics database error: {e}"
+    )))
+}
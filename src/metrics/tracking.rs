@@ -1,5 +1,6 @@
 use super::storage::MetricsStorage;
 use super::types::*;
+use crate::agent_lifecycle::quality_gates::QualityGateReport;
 use crate::github::GitHubError;
 use crate::telemetry::generate_correlation_id;
 use std::collections::HashMap;
@@ -18,9 +19,11 @@ impl Default for MetricsTracker {
 
 impl MetricsTracker {
     pub fn new() -> Self {
-        Self {
-            storage: MetricsStorage::new(),
-        }
+        Self::with_storage(MetricsStorage::new())
+    }
+
+    pub fn with_storage(storage: MetricsStorage) -> Self {
+        Self { storage }
     }
 
     pub async fn track_routing_metrics(
@@ -176,6 +179,7 @@ impl MetricsTracker {
             duration_seconds: duration.map(|d| d.as_secs()),
             error_message,
             pr_number,
+            quality_gates: None,
         };
 
         // Log structured metrics for telemetry
@@ -194,6 +198,48 @@ impl MetricsTracker {
         Ok(())
     }
 
+    /// Record a bottling attempt together with its quality gate report
+    pub async fn track_quality_gates(
+        &self,
+        issue_number: u64,
+        agent_id: &str,
+        report: &QualityGateReport,
+    ) -> Result<(), GitHubError> {
+        let failures: Vec<&str> = report.failures().iter().map(|r| r.name.as_str()).collect();
+        let attempt = IntegrationAttempt {
+            correlation_id: generate_correlation_id(),
+            issue_number,
+            agent_id: agent_id.to_string(),
+            attempt_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            phase: IntegrationPhase::WorkCompletion,
+            outcome: if failures.is_empty() {
+                IntegrationOutcome::Success
+            } else {
+                IntegrationOutcome::Failed
+            },
+            duration_seconds: Some(report.duration_ms / 1000),
+            error_message: (!failures.is_empty())
+                .then(|| format!("quality gates failed: {}", failures.join(", "))),
+            pr_number: None,
+            quality_gates: Some(report.clone()),
+        };
+
+        tracing::info!(
+            issue.number = issue_number,
+            agent.id = agent_id,
+            passed = failures.is_empty(),
+            correlation.id = &attempt.correlation_id,
+            "Quality gates tracked"
+        );
+
+        self.storage.store_integration_attempt(attempt).await?;
+
+        Ok(())
+    }
+
     pub async fn detect_and_store_bottlenecks(
         &self,
     ) -> Result<Vec<PerformanceBottleneck>, GitHubError> {
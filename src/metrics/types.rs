@@ -1,3 +1,4 @@
+use crate::agent_lifecycle::quality_gates::QualityGateReport;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -12,6 +13,9 @@ pub struct IntegrationAttempt {
     pub duration_seconds: Option<u64>,
     pub error_message: Option<String>,
     pub pr_number: Option<u64>,
+    /// Quality gate results from bottling, on `WorkCompletion` attempts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_gates: Option<QualityGateReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,3 +122,144 @@ pub struct IntegrationMetrics {
     pub agent_metrics: HashMap<String, AgentMetrics>,
     pub recent_attempts: Vec<IntegrationAttempt>,
 }
+
+/// Which kind of record a rollup aggregates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    Routing,
+    Utilization,
+    Coordination,
+    Integration,
+}
+
+impl MetricKind {
+    #[cfg(feature = "database")]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Routing => "routing",
+            MetricKind::Utilization => "utilization",
+            MetricKind::Coordination => "coordination",
+            MetricKind::Integration => "integration",
+        }
+    }
+}
+
+/// Running aggregate of one metric, as kept in hourly and daily rollups
+///
+/// The value is the routing or coordination time in ms, the integration
+/// duration in seconds or the utilization percentage.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummary {
+    pub samples: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub value_sum: f64,
+    pub value_count: u64,
+    pub value_max: Option<f64>,
+    /// Values of successful samples only, for time to merge
+    pub success_value_sum: f64,
+    pub success_value_count: u64,
+    pub last_seen: Option<u64>,
+}
+
+impl MetricSummary {
+    /// Add one sample; `success` is `None` for samples without an outcome
+    pub fn record(&mut self, at: u64, success: Option<bool>, value: Option<f64>) {
+        self.samples += 1;
+        match success {
+            Some(true) => self.successes += 1,
+            Some(false) => self.failures += 1,
+            None => {}
+        }
+        if let Some(value) = value {
+            self.value_sum += value;
+            self.value_count += 1;
+            self.value_max = Some(self.value_max.map_or(value, |max| max.max(value)));
+            if success == Some(true) {
+                self.success_value_sum += value;
+                self.success_value_count += 1;
+            }
+        }
+        self.last_seen = Some(self.last_seen.map_or(at, |last| last.max(at)));
+    }
+
+    /// Combine with the summary of another bucket
+    pub fn merge(&mut self, other: &MetricSummary) {
+        self.samples += other.samples;
+        self.successes += other.successes;
+        self.failures += other.failures;
+        self.value_sum += other.value_sum;
+        self.value_count += other.value_count;
+        self.value_max = match (self.value_max, other.value_max) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.success_value_sum += other.success_value_sum;
+        self.success_value_count += other.success_value_count;
+        self.last_seen = self.last_seen.max(other.last_seen);
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        (self.value_count > 0).then(|| self.value_sum / self.value_count as f64)
+    }
+
+    pub fn success_average(&self) -> Option<f64> {
+        (self.success_value_count > 0)
+            .then(|| self.success_value_sum / self.success_value_count as f64)
+    }
+
+    /// Successes over all samples, 0 when there are none
+    pub fn success_rate(&self) -> f64 {
+        if self.samples > 0 {
+            self.successes as f64 / self.samples as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl RoutingMetrics {
+    pub fn summarize(&self, summary: &mut MetricSummary) {
+        summary.record(
+            self.routing_start_time,
+            Some(matches!(
+                self.decision_outcome,
+                RoutingDecision::TaskAssigned { .. }
+            )),
+            Some(self.routing_duration_ms as f64),
+        );
+    }
+}
+
+impl AgentUtilizationMetrics {
+    pub fn summarize(&self, summary: &mut MetricSummary) {
+        summary.record(self.timestamp, None, Some(self.utilization_percentage));
+    }
+}
+
+impl CoordinationDecision {
+    #[cfg(feature = "database")]
+    pub fn summarize(&self, summary: &mut MetricSummary) {
+        summary.record(
+            self.timestamp,
+            Some(self.success),
+            Some(self.execution_time_ms as f64),
+        );
+    }
+}
+
+impl IntegrationAttempt {
+    pub fn summarize(&self, summary: &mut MetricSummary) {
+        let success = match self.outcome {
+            IntegrationOutcome::Success => Some(true),
+            IntegrationOutcome::Failed => Some(false),
+            IntegrationOutcome::InProgress => None,
+        };
+        summary.record(
+            self.attempt_time,
+            success,
+            self.duration_seconds.map(|d| d as f64),
+        );
+    }
+}
@@ -74,6 +74,25 @@ impl ShutdownCoordinator {
     }
 }
 
+/// Wait for SIGINT or SIGTERM (Ctrl-C where there are no Unix signals), returning its name
+pub async fn shutdown_signal() -> Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result.map(|_| "SIGINT").map_err(Into::into),
+            _ = terminate.recv() => Ok("SIGTERM"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await?;
+        Ok("Ctrl-C")
+    }
+}
+
 /// Cancel any ongoing git operations
 async fn cancel_git_operations() -> Result<()> {
     info!("Cancelling ongoing git operations...");
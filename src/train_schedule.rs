@@ -4,7 +4,10 @@
 //! PRs are bundled at 10-minute intervals (:00, :10, :20, :30, :40, :50)
 //! but only when clambake land is manually triggered at/after departure time.
 
+use crate::bundling::unblocker::BLOCKED_LABEL;
+use crate::github::labels::REVIEW_LABEL;
 use chrono::{DateTime, Local, Timelike};
+use serde::{Deserialize, Serialize};
 use std::process::Command;
 
 #[derive(Debug, Clone)]
@@ -27,11 +30,52 @@ pub enum ScheduleStatus {
     Waiting,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueuedBranch {
     pub branch_name: String,
     pub issue_number: u64,
     pub description: String,
+    /// Issues this one declares it depends on ("Depends on #N" in the issue body)
+    #[serde(default)]
+    pub depends_on: Vec<u64>,
+}
+
+/// Title and declared dependencies of an issue
+#[derive(Debug, Clone)]
+struct IssueDetails {
+    title: String,
+    depends_on: Vec<u64>,
+}
+
+/// Issue numbers referenced on dependency lines of an issue body
+///
+/// Recognises lines such as `Depends on #12`, `Blocked by #3, #4` and
+/// `Requires #7`; references elsewhere in the body are ignored.
+pub fn parse_issue_dependencies(body: &str) -> Vec<u64> {
+    const KEYWORDS: [&str; 3] = ["depends on", "blocked by", "requires"];
+
+    let mut dependencies = Vec::new();
+    for line in body.lines() {
+        let lower = line
+            .trim_start_matches(['-', '*', ' ', '\t'])
+            .to_lowercase();
+        let Some(keyword) = KEYWORDS.iter().find(|k| lower.starts_with(*k)) else {
+            continue;
+        };
+
+        for reference in lower[keyword.len()..].split('#').skip(1) {
+            let digits: String = reference
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            if let Ok(issue) = digits.parse::<u64>() {
+                if !dependencies.contains(&issue) {
+                    dependencies.push(issue);
+                }
+            }
+        }
+    }
+    dependencies
 }
 
 impl TrainSchedule {
@@ -119,14 +163,18 @@ impl TrainSchedule {
                     if let Ok(issue_number) = issue_number_str.parse::<u64>() {
                         // Check if this branch has work ready for bundling (handles both local and remote)
                         if Self::branch_has_completed_work(&branch).await? {
-                            let description = Self::get_branch_description(issue_number)
+                            let details = Self::get_issue_details(issue_number)
                                 .await
-                                .unwrap_or_else(|_| "Work completed".to_string());
+                                .unwrap_or_else(|_| IssueDetails {
+                                    title: "Work completed".to_string(),
+                                    depends_on: Vec::new(),
+                                });
 
                             queued_branches.push(QueuedBranch {
                                 branch_name: branch.to_string(),
                                 issue_number,
-                                description,
+                                description: details.title,
+                                depends_on: details.depends_on,
                             });
                         }
                     }
@@ -134,6 +182,13 @@ impl TrainSchedule {
             }
         }
 
+        // The branch set is unordered; bundling reorders by dependencies and overlap later
+        queued_branches.sort_by(|a, b| {
+            a.issue_number
+                .cmp(&b.issue_number)
+                .then_with(|| a.branch_name.cmp(&b.branch_name))
+        });
+
         Ok(queued_branches)
     }
 
@@ -164,12 +219,21 @@ impl TrainSchedule {
             .output()?;
 
         if output.status.success() {
-            let json_str = String::from_utf8_lossy(&output.stdout);
-            let is_open = json_str.contains("\"state\":\"OPEN\"");
-            let has_route_review = json_str.contains("\"name\":\"route:review\"");
+            let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+            let is_open = json["state"].as_str() == Some("OPEN");
+            let has_label = |name: &str| {
+                json["labels"]
+                    .as_array()
+                    .is_some_and(|labels| labels.iter().any(|l| l["name"].as_str() == Some(name)))
+            };
+
+            // Conflicted with an earlier bundle; waits for its unblocker to land
+            if is_open && has_label(BLOCKED_LABEL) {
+                return Ok(false);
+            }
 
             // If issue has route:review label, it's definitely ready for bundling
-            if is_open && has_route_review {
+            if is_open && has_label(REVIEW_LABEL) {
                 return Ok(true);
             }
         }
@@ -216,29 +280,37 @@ impl TrainSchedule {
     async fn get_branch_description(
         issue_number: u64,
     ) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(Self::get_issue_details(issue_number).await?.title)
+    }
+
+    /// Fetch the issue title and the dependencies declared in its body
+    async fn get_issue_details(
+        issue_number: u64,
+    ) -> Result<IssueDetails, Box<dyn std::error::Error>> {
         let output = Command::new("gh")
             .args([
                 "issue",
                 "view",
                 &issue_number.to_string(),
                 "--json",
-                "title",
+                "title,body",
             ])
             .output()?;
 
         if output.status.success() {
-            let json_str = String::from_utf8_lossy(&output.stdout);
-            // Simple JSON parsing for title field
-            if let Some(start) = json_str.find("\"title\":\"") {
-                let start = start + 9; // length of "title":"
-                if let Some(end) = json_str[start..].find("\"}") {
-                    let title = &json_str[start..start + end];
-                    return Ok(title.to_string());
-                }
+            let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+            if let Some(title) = json["title"].as_str() {
+                return Ok(IssueDetails {
+                    title: title.to_string(),
+                    depends_on: parse_issue_dependencies(json["body"].as_str().unwrap_or("")),
+                });
             }
         }
 
-        Ok(format!("Issue #{issue_number}"))
+        Ok(IssueDetails {
+            title: format!("Issue #{issue_number}"),
+            depends_on: Vec::new(),
+        })
     }
 
     /// Format the schedule for display
@@ -342,6 +414,7 @@ impl TrainSchedule {
                                         description: format!(
                                             "{description} ({departure_delay} min overdue)"
                                         ),
+                                        depends_on: Vec::new(),
                                     });
                                 }
                             }
@@ -0,0 +1,308 @@
+//! Agent state rehydration tests
+//!
+//! Covers deriving the state machine from GitHub and git facts, reporting the
+//! places they disagree as stuck agent patterns, and reading agent branches
+//! out of a repository.
+
+use git2::Repository;
+use my_little_soda::agent_lifecycle::rehydration::{
+    rehydrate, scan_branches, AgentBranch, LifecycleFacts, OpenBundle, OpenIssue,
+};
+use my_little_soda::agent_lifecycle::state_machine::StuckAgentPattern;
+use my_little_soda::agent_lifecycle::AgentEvent;
+use my_little_soda::github::labels::REVIEW_LABEL;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::commit_path;
+
+fn issue(number: u64, labels: &[&str]) -> OpenIssue {
+    OpenIssue {
+        number,
+        labels: labels.iter().map(|l| l.to_string()).collect(),
+    }
+}
+
+fn branch(name: &str, local: bool, on_remote: bool, commits_ahead: u32) -> AgentBranch {
+    let (agent_id, rest) = name.split_once('/').unwrap();
+    AgentBranch {
+        name: name.to_string(),
+        agent_id: agent_id.to_string(),
+        issue: rest.split('-').next().unwrap().parse().unwrap(),
+        local,
+        on_remote,
+        commits_ahead,
+    }
+}
+
+fn patterns(facts: &LifecycleFacts) -> Vec<StuckAgentPattern> {
+    rehydrate("agent001", facts)
+        .inconsistencies
+        .into_iter()
+        .map(|i| i.pattern)
+        .collect()
+}
+
+#[test]
+fn test_agent_without_labels_or_branches_is_idle() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(5, &["route:ready"])],
+        ..Default::default()
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "idle");
+    assert!(rehydration.machine.inner().is_available());
+    assert!(rehydration.replayed.is_empty());
+    assert!(rehydration.inconsistencies.is_empty());
+}
+
+#[test]
+fn test_labeled_issue_with_fresh_branch_is_assigned() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(12, &["route:ready", "agent001"])],
+        branches: vec![branch("agent001/12-fix-login", true, false, 0)],
+        ..Default::default()
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "assigned");
+    assert_eq!(rehydration.machine.inner().current_issue(), Some(12));
+    assert_eq!(
+        rehydration.machine.inner().current_branch(),
+        Some("agent001/12-fix-login")
+    );
+    assert!(rehydration.inconsistencies.is_empty());
+}
+
+#[test]
+fn test_working_agent_can_complete_work_after_rehydration() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(12, &["route:ready", "agent001"])],
+        branches: vec![branch("agent001/12-fix-login", true, true, 3)],
+        ..Default::default()
+    };
+
+    let mut rehydration = rehydrate("agent001", &facts);
+    assert_eq!(rehydration.machine.state().name(), "working");
+    assert_eq!(rehydration.machine.inner().commits_ahead(), 3);
+    assert!(rehydration.inconsistencies.is_empty());
+
+    // What `land` sends next used to hit an idle machine and be ignored
+    rehydration.machine.handle(&AgentEvent::CompleteWork);
+    assert_eq!(rehydration.machine.state().name(), "landed");
+}
+
+#[test]
+fn test_landed_issue_still_labeled_is_reported_and_bundled() {
+    let facts = LifecycleFacts {
+        open_issues: vec![
+            issue(12, &[REVIEW_LABEL, "agent001"]),
+            issue(14, &[REVIEW_LABEL]),
+        ],
+        branches: vec![
+            branch("agent001/12-fix-login", true, true, 2),
+            branch("agent001/14-docs", false, true, 1),
+        ],
+        bundles: vec![OpenBundle {
+            pr_number: 40,
+            issues: vec![12, 14],
+        }],
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "bundled");
+    assert_eq!(
+        rehydration.replayed.last(),
+        Some(&AgentEvent::Bundle {
+            bundle_pr: 40,
+            issues: vec![12, 14],
+        })
+    );
+    // The landed branch without a label is the normal case after `land`
+    assert_eq!(
+        patterns(&facts),
+        vec![StuckAgentPattern::LandedButNotFreed {
+            agent_id: "agent001".to_string(),
+            issue: 12,
+        }]
+    );
+}
+
+#[test]
+fn test_label_without_branch_keeps_the_assignment() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(7, &["agent001"])],
+        ..Default::default()
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "assigned");
+    assert_eq!(
+        rehydration.machine.inner().current_branch(),
+        Some("agent001/7")
+    );
+    assert_eq!(
+        patterns(&facts),
+        vec![StuckAgentPattern::LabeledButNoBranch {
+            agent_id: "agent001".to_string(),
+            issue: 7,
+        }]
+    );
+}
+
+#[test]
+fn test_branch_for_unlabeled_open_issue_is_reported() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(8, &["route:ready"]), issue(9, &["agent002"])],
+        branches: vec![
+            branch("agent001/8-started", true, false, 1),
+            // Issue 3 is closed; its branch is only waiting for cleanup
+            branch("agent001/3-merged", false, true, 0),
+            branch("agent001/9-taken", true, false, 0),
+            branch("agent002/9-taken", true, false, 2),
+        ],
+        ..Default::default()
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "idle");
+    assert_eq!(
+        patterns(&facts),
+        vec![
+            StuckAgentPattern::BranchButNoLabel {
+                agent_id: "agent001".to_string(),
+                branch: "agent001/8-started".to_string(),
+            },
+            StuckAgentPattern::BranchButNoLabel {
+                agent_id: "agent001".to_string(),
+                branch: "agent001/9-taken".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        rehydrate("agent002", &facts).machine.state().name(),
+        "working"
+    );
+}
+
+#[test]
+fn test_pushed_branch_with_nothing_ahead_is_reported() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(21, &["agent001"])],
+        branches: vec![branch("agent001/21-lost", false, true, 0)],
+        ..Default::default()
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "assigned");
+    assert_eq!(
+        patterns(&facts),
+        vec![StuckAgentPattern::WorkingButNoCommits {
+            agent_id: "agent001".to_string(),
+            issue: 21,
+        }]
+    );
+}
+
+#[test]
+fn test_several_labeled_issues_follow_the_one_with_work() {
+    let facts = LifecycleFacts {
+        open_issues: vec![issue(4, &["agent001"]), issue(6, &["agent001"])],
+        branches: vec![
+            branch("agent001/4-stale", true, false, 0),
+            branch("agent001/6-active", true, false, 5),
+        ],
+        ..Default::default()
+    };
+
+    let rehydration = rehydrate("agent001", &facts);
+
+    assert_eq!(rehydration.machine.state().name(), "working");
+    assert_eq!(rehydration.machine.inner().current_issue(), Some(6));
+    assert_eq!(
+        patterns(&facts),
+        vec![StuckAgentPattern::AssignedToSeveralIssues {
+            agent_id: "agent001".to_string(),
+            issues: vec![4, 6],
+        }]
+    );
+    assert_eq!(
+        patterns(&facts)[0].to_string(),
+        "agent001 is labeled on several issues: #4, #6"
+    );
+}
+
+#[test]
+fn test_branches_are_read_from_local_and_remote_refs() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let base = commit_path(&repo, "refs/heads/main", None, "README.md");
+    let remote_main = commit_path(&repo, "refs/remotes/origin/main", Some(base), "main.txt");
+
+    // Pushed, with one more commit made locally since
+    let pushed = commit_path(
+        &repo,
+        "refs/remotes/origin/agent001/3-pushed",
+        Some(remote_main),
+        "a",
+    );
+    commit_path(&repo, "refs/heads/agent001/3-pushed", Some(pushed), "b");
+    commit_path(
+        &repo,
+        "refs/remotes/origin/agent002/4-remote",
+        Some(remote_main),
+        "c",
+    );
+    commit_path(&repo, "refs/heads/agent001/5-local", Some(base), "d");
+    commit_path(&repo, "refs/heads/feature/other", Some(base), "e");
+    commit_path(
+        &repo,
+        "refs/remotes/upstream/agent001/6-elsewhere",
+        Some(base),
+        "f",
+    );
+
+    let branches = scan_branches(&repo, "origin", "main").unwrap();
+
+    assert_eq!(
+        branches,
+        vec![
+            AgentBranch {
+                name: "agent001/3-pushed".to_string(),
+                agent_id: "agent001".to_string(),
+                issue: 3,
+                local: true,
+                on_remote: true,
+                commits_ahead: 2,
+            },
+            // Counted against origin/main, which the local main is behind
+            AgentBranch {
+                name: "agent001/5-local".to_string(),
+                agent_id: "agent001".to_string(),
+                issue: 5,
+                local: true,
+                on_remote: false,
+                commits_ahead: 1,
+            },
+            AgentBranch {
+                name: "agent002/4-remote".to_string(),
+                agent_id: "agent002".to_string(),
+                issue: 4,
+                local: false,
+                on_remote: true,
+                commits_ahead: 1,
+            },
+        ]
+    );
+
+    let error = scan_branches(&repo, "origin", "trunk").unwrap_err();
+    assert!(error.to_string().contains("Base branch trunk not found"));
+}
@@ -48,6 +48,7 @@ fn create_extended_test_configs(
         compress_old_states: false,
         backup_retention_days: 7,
         enable_integrity_checks: true,
+        ..PersistenceConfig::default()
     };
 
     let coordination_config = CoordinationConfig {
@@ -154,6 +155,7 @@ async fn test_autonomous_workflow_persistence_across_restarts() -> Result<()> {
                 hostname: "test-host".to_string(),
             },
             last_persisted: Utc::now(),
+            history_summary: None,
         };
 
         // Save state
@@ -443,6 +445,7 @@ async fn test_checkpoint_recovery_scenarios() -> Result<()> {
                     hostname: "test-host".to_string(),
                 },
                 last_persisted: Utc::now(),
+                history_summary: None,
             };
 
             let checkpoint_id = persistence_manager
@@ -603,6 +606,7 @@ async fn test_checkpoint_performance() -> Result<()> {
                 hostname: "perf-test-host".to_string(),
             },
             last_persisted: Utc::now(),
+            history_summary: None,
         };
 
         // Test persistence manager performance
@@ -781,6 +785,7 @@ async fn test_workflow_state_continuity() -> Result<()> {
                 hostname: "test-host".to_string(),
             },
             last_persisted: Utc::now(),
+            history_summary: None,
         };
 
         let checkpoint_id = persistence_manager
@@ -0,0 +1,479 @@
+//! Unattended run loop tests
+//!
+//! Drives `AutonomousRunner` against a fake backend over a real repository,
+//! with `sh` scripts standing in for the agent: bottling finished work,
+//! recovering from and escalating failures, stopping on drift, and resuming
+//! from the checkpoint after a stop. Only built with `--features autonomous`.
+
+#![cfg(feature = "autonomous")]
+
+use async_trait::async_trait;
+use git2::Repository;
+use my_little_soda::autonomous::persistence::{PersistenceConfig, StatePersistenceManager};
+use my_little_soda::autonomous::runner::{
+    branch_progress, AgentLauncher, AutonomousRunner, BranchProgress, RunBackend, RunLimits,
+    RunTask, StopReason,
+};
+use my_little_soda::autonomous::{
+    AutonomousWorkflowState, ErrorType, Issue, Priority, RecoveryRules,
+};
+use my_little_soda::config::{AgentProcessConfig, EscalationConfig, RecoveryConfig};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::sync::watch;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::git;
+
+/// An agent that commits one file for its issue
+const COMMITTING_AGENT: &str =
+    "echo done > \"issue-$MY_LITTLE_SODA_ISSUE\" && git add -A && git commit -qm \"Fix #$MY_LITTLE_SODA_ISSUE\"";
+
+fn repository() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    git(dir.path(), &["init", "-q", "-b", "main"]);
+    git(dir.path(), &["config", "user.name", "Test Agent"]);
+    git(dir.path(), &["config", "user.email", "agent@example.com"]);
+    std::fs::write(dir.path().join("README.md"), "readme").unwrap();
+    git(dir.path(), &["add", "-A"]);
+    git(dir.path(), &["commit", "-qm", "Initial commit"]);
+    dir
+}
+
+fn issue(number: u64) -> Issue {
+    Issue {
+        number,
+        title: format!("Task {number}"),
+        body: String::new(),
+        labels: vec!["route:ready".to_string()],
+        priority: Priority::Medium,
+        estimated_hours: None,
+    }
+}
+
+#[derive(Default)]
+struct Calls {
+    resumed: Vec<String>,
+    bottled: Vec<u64>,
+    escalated: Vec<(u64, String)>,
+    targets: Vec<EscalationConfig>,
+}
+
+struct FakeBackend {
+    repo: PathBuf,
+    queue: Mutex<Vec<u64>>,
+    drift: Option<String>,
+    calls: Arc<Mutex<Calls>>,
+}
+
+impl FakeBackend {
+    fn new(repo: &Path, queue: Vec<u64>) -> (Self, Arc<Mutex<Calls>>) {
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        let backend = Self {
+            repo: repo.to_path_buf(),
+            queue: Mutex::new(queue),
+            drift: None,
+            calls: calls.clone(),
+        };
+        (backend, calls)
+    }
+}
+
+#[async_trait(?Send)]
+impl RunBackend for FakeBackend {
+    async fn pop_task(&self, agent_id: &str) -> anyhow::Result<Option<RunTask>> {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.is_empty() {
+            return Ok(None);
+        }
+        let number = queue.remove(0);
+        let branch = format!("{agent_id}/{number}-task");
+        git(&self.repo, &["checkout", "-q", "-b", &branch, "main"]);
+        Ok(Some(RunTask {
+            issue: issue(number),
+            branch,
+        }))
+    }
+
+    async fn resume_task(&self, task: &RunTask) -> anyhow::Result<()> {
+        git(&self.repo, &["checkout", "-q", &task.branch]);
+        self.calls.lock().unwrap().resumed.push(task.branch.clone());
+        Ok(())
+    }
+
+    async fn progress(&self, task: &RunTask) -> anyhow::Result<BranchProgress> {
+        branch_progress(
+            &Repository::open(&self.repo)?,
+            &task.branch,
+            "origin",
+            "main",
+        )
+    }
+
+    async fn check_drift(
+        &self,
+        _state: &AutonomousWorkflowState,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(self.drift.clone())
+    }
+
+    async fn bottle(&self, task: &RunTask) -> anyhow::Result<()> {
+        self.calls.lock().unwrap().bottled.push(task.issue.number);
+        Ok(())
+    }
+
+    async fn escalate(
+        &self,
+        task: &RunTask,
+        reason: &str,
+        target: &EscalationConfig,
+    ) -> anyhow::Result<()> {
+        let mut calls = self.calls.lock().unwrap();
+        calls
+            .escalated
+            .push((task.issue.number, reason.to_string()));
+        calls.targets.push(target.clone());
+        Ok(())
+    }
+}
+
+fn agent(repo: &Path, script: &str) -> AgentLauncher {
+    AgentLauncher::new("sh")
+        .with_args(vec![
+            "-c".to_string(),
+            script.to_string(),
+            "agent".to_string(),
+        ])
+        .with_work_dir(repo.to_path_buf())
+}
+
+fn persistence(dir: &Path) -> StatePersistenceManager {
+    StatePersistenceManager::new(PersistenceConfig {
+        persistence_directory: dir.to_path_buf(),
+        ..PersistenceConfig::default()
+    })
+}
+
+fn limits() -> RunLimits {
+    RunLimits {
+        max_work_hours: 1,
+        max_recovery_attempts: 1,
+        monitoring_interval: Duration::from_millis(50),
+        idle_poll_interval: Duration::from_millis(10),
+        stop_when_idle: true,
+    }
+}
+
+fn runner(backend: FakeBackend, launcher: AgentLauncher, state_dir: &Path) -> AutonomousRunner {
+    AutonomousRunner::new(
+        "agent001",
+        Box::new(backend),
+        launcher,
+        persistence(state_dir),
+    )
+    .with_limits(limits())
+}
+
+fn never_stop() -> watch::Receiver<bool> {
+    watch::channel(false).1
+}
+
+#[tokio::test]
+async fn test_finished_tasks_are_bottled_until_the_queue_is_empty() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![3, 5]);
+    let mut runner = runner(backend, agent(repo.path(), COMMITTING_AGENT), state.path());
+
+    let summary = runner.run(never_stop()).await.unwrap();
+
+    assert_eq!(summary.bottled, vec![3, 5]);
+    assert!(summary.escalated.is_empty());
+    assert_eq!(summary.stopped, StopReason::Idle);
+    assert_eq!(calls.lock().unwrap().bottled, vec![3, 5]);
+
+    // The checkpoint records the real issues, not placeholders
+    let saved = persistence(state.path())
+        .load_state("agent001")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(saved.current_state.is_none());
+    let assigned: Vec<(u64, String)> = saved
+        .state_history
+        .iter()
+        .filter_map(|record| match &record.to_state {
+            AutonomousWorkflowState::Assigned {
+                issue, workspace, ..
+            } => Some((issue.number, workspace.branch_name.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        assigned,
+        vec![
+            (3, "agent001/3-task".to_string()),
+            (5, "agent001/5-task".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_assigned_workspace_records_the_configured_base_branch() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let (backend, _calls) = FakeBackend::new(repo.path(), vec![6]);
+    let mut runner = runner(backend, agent(repo.path(), COMMITTING_AGENT), state.path())
+        .with_base_branch("develop");
+
+    runner.run(never_stop()).await.unwrap();
+
+    let saved = persistence(state.path())
+        .load_state("agent001")
+        .await
+        .unwrap()
+        .unwrap();
+    let bases: Vec<String> = saved
+        .state_history
+        .iter()
+        .filter_map(|record| match &record.to_state {
+            AutonomousWorkflowState::Assigned { workspace, .. } => {
+                Some(workspace.base_branch.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(bases, vec!["develop".to_string()]);
+}
+
+#[tokio::test]
+async fn test_failed_agent_is_relaunched_with_the_error() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let marker = state.path().join("failed-once");
+    // Fails the first time, then commits once told what went wrong
+    let script = format!(
+        "if [ -f '{marker}' ]; then case \"$1\" in *'previous attempt failed'*'could not compile'*) {COMMITTING_AGENT};; *) exit 3;; esac; \
+         else touch '{marker}'; echo 'error: could not compile' >&2; exit 1; fi",
+        marker = marker.display()
+    );
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![8]);
+    let mut runner = runner(backend, agent(repo.path(), &script), state.path());
+
+    let summary = runner.run(never_stop()).await.unwrap();
+
+    assert_eq!(summary.bottled, vec![8]);
+    assert!(calls.lock().unwrap().escalated.is_empty());
+    let saved = persistence(state.path())
+        .load_state("agent001")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved.recovery_history.len(), 1);
+    let attempt = &saved.recovery_history[0];
+    assert!(attempt.success);
+    assert!(matches!(
+        &attempt.error_type,
+        ErrorType::BuildFailure { stage, error }
+            if stage == "agent" && error == "error: could not compile"
+    ));
+}
+
+#[tokio::test]
+async fn test_task_is_escalated_once_recovery_attempts_run_out() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    // Exits cleanly without committing anything
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![9, 10]);
+    let mut runner = runner(backend, agent(repo.path(), "true"), state.path());
+
+    let summary = runner.run(never_stop()).await.unwrap();
+
+    assert!(summary.bottled.is_empty());
+    assert_eq!(summary.escalated, vec![9, 10]);
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.escalated.len(), 2);
+    assert_eq!(
+        calls.escalated[0],
+        (
+            9,
+            "Gave up after 1 recovery attempt(s): expected commits on agent001/9-task, \
+             but the agent exited without committing"
+                .to_string()
+        )
+    );
+    drop(calls);
+    let saved = persistence(state.path())
+        .load_state("agent001")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(saved.current_state.is_none());
+}
+
+#[tokio::test]
+async fn test_configured_rule_decides_recovery_and_escalation_target() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let config: RecoveryConfig = toml::from_str(
+        r#"
+        [[rules]]
+        name = "no-commits"
+        error_type = "state_inconsistency"
+        message = "without committing"
+        strategies = [
+            { action = "fix", fix = "configuration_adjustment" },
+            { action = "escalate", urgency = "high", context = "Agent keeps stopping early" },
+        ]
+        escalation = { labels = ["needs-triage"], mention = ["@org/maintainers"] }
+        "#,
+    )
+    .unwrap();
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![12]);
+    let mut runner = runner(backend, agent(repo.path(), "true"), state.path())
+        .with_limits(RunLimits {
+            max_recovery_attempts: 5,
+            ..limits()
+        })
+        .with_recovery_rules(RecoveryRules::from_config(&config).unwrap());
+
+    let summary = runner.run(never_stop()).await.unwrap();
+
+    // Relaunched once to fix it, then escalated by the rule's second strategy
+    assert_eq!(summary.escalated, vec![12]);
+    let calls = calls.lock().unwrap();
+    assert!(calls.escalated[0]
+        .1
+        .starts_with("Agent keeps stopping early: expected commits"));
+    assert_eq!(calls.targets[0].labels, vec!["needs-triage".to_string()]);
+    assert_eq!(
+        calls.targets[0].mention,
+        vec!["@org/maintainers".to_string()]
+    );
+    assert!(calls.targets[0].comment);
+}
+
+#[tokio::test]
+async fn test_agent_is_stopped_when_it_runs_past_its_timeout() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![4]);
+    let launcher = agent(repo.path(), "exec sleep 30").with_timeout(Duration::from_millis(200));
+    let mut runner = runner(backend, launcher, state.path());
+
+    let summary = tokio::time::timeout(Duration::from_secs(10), runner.run(never_stop()))
+        .await
+        .expect("the agent should have been stopped")
+        .unwrap();
+
+    assert_eq!(summary.escalated, vec![4]);
+    assert!(calls.lock().unwrap().escalated[0]
+        .1
+        .ends_with("agent failed: timed out after 200ms"));
+}
+
+#[tokio::test]
+async fn test_drift_needing_a_person_abandons_the_task() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let (mut backend, calls) = FakeBackend::new(repo.path(), vec![6]);
+    backend.drift = Some("Issue #6 was closed on GitHub".to_string());
+    let mut runner = runner(backend, agent(repo.path(), "exec sleep 30"), state.path());
+
+    let summary = runner.run(never_stop()).await.unwrap();
+
+    assert_eq!(summary.escalated, vec![6]);
+    assert_eq!(
+        calls.lock().unwrap().escalated,
+        vec![(6, "Issue #6 was closed on GitHub".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_stopped_run_resumes_the_task_from_its_checkpoint() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+
+    let (backend, _) = FakeBackend::new(repo.path(), vec![7]);
+    let mut first = runner(backend, agent(repo.path(), "exec sleep 30"), state.path());
+    let (stop, stopped) = watch::channel(false);
+    let (summary, _) = tokio::join!(first.run(stopped), async {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        stop.send(true).unwrap();
+    });
+    let summary = summary.unwrap();
+    assert_eq!(summary.stopped, StopReason::Requested);
+    assert!(summary.bottled.is_empty() && summary.escalated.is_empty());
+
+    let saved = persistence(state.path())
+        .load_state("agent001")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        saved.current_state,
+        Some(AutonomousWorkflowState::InProgress { ref issue, .. }) if issue.number == 7
+    ));
+
+    // A fresh process with nothing queued picks issue 7 up again
+    git(repo.path(), &["checkout", "-q", "main"]);
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![]);
+    let mut second = runner(backend, agent(repo.path(), COMMITTING_AGENT), state.path());
+    let summary = second.run(never_stop()).await.unwrap();
+
+    assert_eq!(summary.resumed, Some(7));
+    assert_eq!(summary.bottled, vec![7]);
+    assert_eq!(
+        calls.lock().unwrap().resumed,
+        vec!["agent001/7-task".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_run_refuses_to_start_agents_unless_enabled() {
+    let repo = repository();
+    let state = TempDir::new().unwrap();
+    let config = AgentProcessConfig {
+        claude_code_path: "claude-code".to_string(),
+        timeout_minutes: 30,
+        cleanup_on_failure: true,
+        work_dir_prefix: ".my-little-soda/agents".to_string(),
+        enable_real_agents: false,
+    };
+    let (backend, calls) = FakeBackend::new(repo.path(), vec![1]);
+    let mut runner = runner(backend, AgentLauncher::from_config(&config), state.path());
+
+    let error = runner.run(never_stop()).await.unwrap_err();
+
+    assert!(error.to_string().contains("enable_real_agents = true"));
+    assert!(calls.lock().unwrap().bottled.is_empty());
+}
+
+#[test]
+fn test_branch_progress_counts_commits_and_files_beyond_the_base() {
+    let repo = repository();
+    git(repo.path(), &["checkout", "-q", "-b", "agent001/2-work"]);
+    for file in ["a.txt", "b.txt"] {
+        std::fs::write(repo.path().join(file), file).unwrap();
+        git(repo.path(), &["add", "-A"]);
+        git(repo.path(), &["commit", "-qm", file]);
+    }
+    // Work landing on main after the branch was cut does not count
+    git(repo.path(), &["checkout", "-q", "main"]);
+    std::fs::write(repo.path().join("c.txt"), "c").unwrap();
+    git(repo.path(), &["add", "-A"]);
+    git(repo.path(), &["commit", "-qm", "c"]);
+
+    let repository = Repository::open(repo.path()).unwrap();
+    assert_eq!(
+        branch_progress(&repository, "agent001/2-work", "origin", "main").unwrap(),
+        BranchProgress {
+            commits: 2,
+            files_changed: 2,
+        }
+    );
+    assert!(branch_progress(&repository, "agent001/3-missing", "origin", "main").is_err());
+}
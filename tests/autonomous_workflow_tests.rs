@@ -266,6 +266,7 @@ async fn test_state_persistence() {
         compress_old_states: false,
         backup_retention_days: 1,
         enable_integrity_checks: true,
+        ..PersistenceConfig::default()
     };
 
     let manager = StatePersistenceManager::new(config);
@@ -304,6 +305,7 @@ async fn test_state_persistence() {
             hostname: "test-host".to_string(),
         },
         last_persisted: chrono::Utc::now(),
+        history_summary: None,
     };
 
     // Test save
@@ -387,6 +389,7 @@ async fn test_checkpoint_operations() {
             hostname: "test-host".to_string(),
         },
         last_persisted: chrono::Utc::now(),
+        history_summary: None,
     };
 
     // Create checkpoint
@@ -0,0 +1,175 @@
+//! Assembly mode tests for bundle creation
+//!
+//! Verifies that squash and rebase modes produce the expected history on the
+//! bundle branch compared to plain cherry-picking.
+
+use my_little_soda::bundling::git_ops::{
+    AssemblyMode, CherryPickOutcome, ConflictStrategy, GitOperations,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::{bundle_repo, commit_files_by};
+
+/// Repository where `main` already contains `fix.txt`, and `agent001/5` has three
+/// commits: two "wip" commits and one re-adding the same `fix.txt` that main has.
+fn setup_repo() -> (TempDir, GitOperations) {
+    bundle_repo(|repo| {
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Bundler Bot").unwrap();
+        config.set_str("user.email", "bundler@example.com").unwrap();
+
+        let base = commit_files_by(
+            repo,
+            "main",
+            None,
+            &[("readme.txt", "hello\n")],
+            "Initial commit",
+            "Maintainer",
+        );
+        let first = commit_files_by(
+            repo,
+            "agent001/5",
+            Some(base),
+            &[("feature.txt", "draft\n")],
+            "wip",
+            "Agent 001",
+        );
+        let second = commit_files_by(
+            repo,
+            "agent001/5",
+            Some(first),
+            &[("feature.txt", "final\n")],
+            "wip again",
+            "Agent 001",
+        );
+        commit_files_by(
+            repo,
+            "agent001/5",
+            Some(second),
+            &[("fix.txt", "shared fix\n")],
+            "Apply shared fix",
+            "Agent 001",
+        );
+        commit_files_by(
+            repo,
+            "main",
+            Some(base),
+            &[("fix.txt", "shared fix\n")],
+            "Shared fix landed on main",
+            "Maintainer",
+        );
+    })
+}
+
+fn head(git_ops: &GitOperations) -> git2::Commit<'_> {
+    git_ops.repo.head().unwrap().peel_to_commit().unwrap()
+}
+
+#[test]
+fn test_squash_creates_one_commit_per_issue() {
+    let (dir, git_ops) = setup_repo();
+    let base = head(&git_ops).id();
+
+    let message = "Add feature (#5)\n\nCloses #5\n";
+    let outcome = git_ops
+        .squash_branch("agent001/5", message, ConflictStrategy::IndividualFallback)
+        .unwrap();
+    match outcome {
+        CherryPickOutcome::Applied { commits } => assert_eq!(commits.len(), 1),
+        other => panic!("Expected Applied, got {other:?}"),
+    }
+
+    let squashed = head(&git_ops);
+    assert_eq!(squashed.message(), Some(message));
+    assert_eq!(squashed.author().name(), Some("Agent 001"));
+    assert_eq!(squashed.parent_id(0).unwrap(), base);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("feature.txt")).unwrap(),
+        "final\n"
+    );
+}
+
+#[test]
+fn test_rebase_keeps_authors_and_drops_emptied_commits() {
+    let (_dir, git_ops) = setup_repo();
+
+    let outcome = git_ops
+        .rebase_branch("agent001/5", ConflictStrategy::IndividualFallback)
+        .unwrap();
+    match outcome {
+        CherryPickOutcome::Applied { commits } => assert_eq!(commits.len(), 2),
+        other => panic!("Expected Applied, got {other:?}"),
+    }
+
+    let tip = head(&git_ops);
+    assert_eq!(tip.summary(), Some("wip again"));
+    assert_eq!(tip.author().name(), Some("Agent 001"));
+    assert_eq!(tip.committer().name(), Some("Bundler Bot"));
+}
+
+#[test]
+fn test_cherry_pick_keeps_every_commit() {
+    let (_dir, git_ops) = setup_repo();
+
+    let outcome = git_ops
+        .cherry_pick_branch("agent001/5", ConflictStrategy::IndividualFallback)
+        .unwrap();
+    match outcome {
+        CherryPickOutcome::Applied { commits } => assert_eq!(commits.len(), 3),
+        other => panic!("Expected Applied, got {other:?}"),
+    }
+    assert_eq!(head(&git_ops).committer().name(), Some("Agent 001"));
+}
+
+#[test]
+fn test_squash_conflict_resumes_with_squash_message() {
+    let (dir, git_ops) = setup_repo();
+    let repo = &git_ops.repo;
+
+    // Another branch already touched feature.txt on the bundle
+    let bundle_head = head(&git_ops).id();
+    commit_files_by(
+        repo,
+        "bundle/test",
+        Some(bundle_head),
+        &[("feature.txt", "other\n")],
+        "Other issue",
+        "Agent 002",
+    );
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+
+    let message = "Add feature (#5)\n\nCloses #5\n";
+    let conflicted = match git_ops
+        .squash_branch("agent001/5", message, ConflictStrategy::ManualResolve)
+        .unwrap()
+    {
+        CherryPickOutcome::AwaitingResolution { commit, .. } => commit,
+        other => panic!("Expected AwaitingResolution, got {other:?}"),
+    };
+
+    std::fs::write(dir.path().join("feature.txt"), "merged\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("feature.txt")).unwrap();
+    index.write().unwrap();
+
+    let outcome = git_ops
+        .continue_cherry_pick("agent001/5", conflicted, &AssemblyMode::Squash)
+        .unwrap();
+    match outcome {
+        CherryPickOutcome::Applied { commits } => assert_eq!(commits.len(), 1),
+        other => panic!("Expected Applied, got {other:?}"),
+    }
+    assert_eq!(head(&git_ops).message(), Some(message));
+}
+
+#[test]
+fn test_assembly_mode_config_values() {
+    let mode: AssemblyMode = serde_json::from_str("\"squash\"").unwrap();
+    assert_eq!(mode, AssemblyMode::Squash);
+    assert_eq!(AssemblyMode::default(), AssemblyMode::CherryPick);
+    assert!(AssemblyMode::Rebase.description().starts_with("rebase"));
+}
@@ -0,0 +1,224 @@
+//! Conflict strategy tests for bundle assembly
+//!
+//! Builds throwaway repositories with agent branches that do and don't conflict,
+//! and checks how `GitOperations::cherry_pick_branch` handles each strategy.
+
+use git2::{Oid, RepositoryState};
+use my_little_soda::bundling::git_ops::{
+    AssemblyMode, CherryPickOutcome, ConflictStrategy, GitOperations,
+};
+use my_little_soda::config::BundleConfig;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::{bundle_repo, commit_files};
+
+/// Repository with `main` plus three agent branches:
+/// - agent001/1 edits `shared.txt`
+/// - agent001/2 edits the same line of `shared.txt`, then adds `extra.txt`
+/// - agent001/3 adds an unrelated `docs.txt`
+fn setup_repo() -> (TempDir, GitOperations) {
+    bundle_repo(|repo| {
+        let base = commit_files(
+            repo,
+            "main",
+            None,
+            &[("shared.txt", "line one\n")],
+            "Initial commit",
+        );
+        commit_files(
+            repo,
+            "agent001/1",
+            Some(base),
+            &[("shared.txt", "line one from issue 1\n")],
+            "Fix issue 1",
+        );
+        let conflicting = commit_files(
+            repo,
+            "agent001/2",
+            Some(base),
+            &[("shared.txt", "line one from issue 2\n")],
+            "Fix issue 2",
+        );
+        commit_files(
+            repo,
+            "agent001/2",
+            Some(conflicting),
+            &[("extra.txt", "extra\n")],
+            "Follow-up for issue 2",
+        );
+        commit_files(
+            repo,
+            "agent001/3",
+            Some(base),
+            &[("docs.txt", "docs\n")],
+            "Docs for issue 3",
+        );
+    })
+}
+
+fn head_id(git_ops: &GitOperations) -> Oid {
+    git_ops.repo.head().unwrap().peel_to_commit().unwrap().id()
+}
+
+fn read_file(dir: &TempDir, path: &str) -> String {
+    std::fs::read_to_string(dir.path().join(path)).unwrap()
+}
+
+#[test]
+fn test_skip_conflicts_rolls_back_only_the_conflicting_branch() {
+    let (dir, git_ops) = setup_repo();
+
+    let outcome = git_ops
+        .cherry_pick_branch("agent001/1", ConflictStrategy::SkipConflicts)
+        .unwrap();
+    assert!(matches!(outcome, CherryPickOutcome::Applied { ref commits } if commits.len() == 1));
+    let after_first = head_id(&git_ops);
+
+    let outcome = git_ops
+        .cherry_pick_branch("agent001/2", ConflictStrategy::SkipConflicts)
+        .unwrap();
+    match outcome {
+        CherryPickOutcome::Conflicted {
+            conflicted_files, ..
+        } => assert_eq!(conflicted_files, vec!["shared.txt".to_string()]),
+        other => panic!("Expected Conflicted, got {other:?}"),
+    }
+
+    // The bundle branch is back where it was and clean
+    assert_eq!(head_id(&git_ops), after_first);
+    assert_eq!(git_ops.repo.state(), RepositoryState::Clean);
+    assert!(!git_ops.repo.index().unwrap().has_conflicts());
+    assert_eq!(read_file(&dir, "shared.txt"), "line one from issue 1\n");
+    assert!(!Path::new(&dir.path().join("extra.txt")).exists());
+
+    // Later branches still bundle
+    let outcome = git_ops
+        .cherry_pick_branch("agent001/3", ConflictStrategy::SkipConflicts)
+        .unwrap();
+    assert!(matches!(outcome, CherryPickOutcome::Applied { .. }));
+    assert_eq!(read_file(&dir, "docs.txt"), "docs\n");
+}
+
+#[test]
+fn test_individual_fallback_leaves_bundle_branch_clean() {
+    let (_dir, git_ops) = setup_repo();
+
+    git_ops
+        .cherry_pick_branch("agent001/1", ConflictStrategy::IndividualFallback)
+        .unwrap();
+    let after_first = head_id(&git_ops);
+
+    let outcome = git_ops
+        .cherry_pick_branch("agent001/2", ConflictStrategy::IndividualFallback)
+        .unwrap();
+    assert!(matches!(outcome, CherryPickOutcome::Conflicted { .. }));
+    assert_eq!(head_id(&git_ops), after_first);
+    assert_eq!(git_ops.repo.state(), RepositoryState::Clean);
+}
+
+#[test]
+fn test_manual_resolve_pauses_and_continues_after_resolution() {
+    let (dir, git_ops) = setup_repo();
+
+    git_ops
+        .cherry_pick_branch("agent001/1", ConflictStrategy::ManualResolve)
+        .unwrap();
+
+    let outcome = git_ops
+        .cherry_pick_branch("agent001/2", ConflictStrategy::ManualResolve)
+        .unwrap();
+    let conflicted_commit = match outcome {
+        CherryPickOutcome::AwaitingResolution {
+            applied,
+            commit,
+            conflicted_files,
+        } => {
+            assert!(applied.is_empty());
+            assert_eq!(conflicted_files, vec!["shared.txt".to_string()]);
+            commit
+        }
+        other => panic!("Expected AwaitingResolution, got {other:?}"),
+    };
+    assert!(git_ops.repo.index().unwrap().has_conflicts());
+
+    // Continuing before the conflict is resolved keeps the bundle paused
+    let outcome = git_ops
+        .continue_cherry_pick("agent001/2", conflicted_commit, &AssemblyMode::CherryPick)
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        CherryPickOutcome::AwaitingResolution { .. }
+    ));
+
+    // Resolve like a human would: edit the file and stage it
+    std::fs::write(dir.path().join("shared.txt"), "line one from both\n").unwrap();
+    let mut index = git_ops.repo.index().unwrap();
+    index.add_path(Path::new("shared.txt")).unwrap();
+    index.write().unwrap();
+
+    let outcome = git_ops
+        .continue_cherry_pick("agent001/2", conflicted_commit, &AssemblyMode::CherryPick)
+        .unwrap();
+    match outcome {
+        CherryPickOutcome::Applied { commits } => assert_eq!(commits.len(), 2),
+        other => panic!("Expected Applied, got {other:?}"),
+    }
+
+    assert_eq!(git_ops.repo.state(), RepositoryState::Clean);
+    assert_eq!(read_file(&dir, "extra.txt"), "extra\n");
+
+    let head = git_ops.repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.summary(), Some("Follow-up for issue 2"));
+    let resolution = head.parent(0).unwrap();
+    assert_eq!(resolution.summary(), Some("Fix issue 2"));
+    assert_eq!(resolution.author().name(), Some("Test Agent"));
+}
+
+#[test]
+fn test_abort_cherry_pick_discards_conflicted_index() {
+    let (dir, git_ops) = setup_repo();
+
+    git_ops
+        .cherry_pick_branch("agent001/1", ConflictStrategy::ManualResolve)
+        .unwrap();
+    let after_first = head_id(&git_ops);
+    git_ops
+        .cherry_pick_branch("agent001/2", ConflictStrategy::ManualResolve)
+        .unwrap();
+
+    git_ops.abort_cherry_pick().unwrap();
+
+    assert_eq!(head_id(&git_ops), after_first);
+    assert_eq!(git_ops.repo.state(), RepositoryState::Clean);
+    assert!(!git_ops.repo.index().unwrap().has_conflicts());
+    assert_eq!(read_file(&dir, "shared.txt"), "line one from issue 1\n");
+}
+
+#[test]
+fn test_conflict_strategy_from_config() {
+    let config: BundleConfig = toml::from_str(
+        r#"
+        max_queue_size = 50
+        processing_timeout_seconds = 1800
+        conflict_strategy = "skip_conflicts"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.conflict_strategy, ConflictStrategy::SkipConflicts);
+
+    // Existing configs without the key keep the old behaviour
+    let config: BundleConfig = toml::from_str(
+        r#"
+        max_queue_size = 50
+        processing_timeout_seconds = 1800
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.conflict_strategy,
+        ConflictStrategy::IndividualFallback
+    );
+}
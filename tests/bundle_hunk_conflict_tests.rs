@@ -0,0 +1,150 @@
+//! Hunk-level conflict prediction tests
+//!
+//! Branches that edit different parts of the same file should bundle, while
+//! branches editing the same lines should be reported with their hunks.
+
+use my_little_soda::bundling::git_ops::{GitOperations, HunkRange};
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::{bundle_repo, commit_files};
+
+/// Twenty numbered lines with the given (1-based) lines replaced
+fn numbered_lines(replacements: &[(usize, &str)]) -> String {
+    (1..=20)
+        .map(|n| {
+            replacements
+                .iter()
+                .find(|(line, _)| *line == n)
+                .map(|(_, text)| format!("{text}\n"))
+                .unwrap_or_else(|| format!("line {n}\n"))
+        })
+        .collect()
+}
+
+/// Repository with `main` plus three agent branches editing `lib.txt`:
+/// - agent001/1 edits line 2
+/// - agent001/2 edits line 18
+/// - agent001/3 edits line 2 differently from agent001/1
+fn setup_repo() -> (TempDir, GitOperations) {
+    bundle_repo(|repo| {
+        let original = numbered_lines(&[]);
+        let base = commit_files(
+            repo,
+            "main",
+            None,
+            &[("lib.txt", &original)],
+            "Initial commit",
+        );
+        commit_files(
+            repo,
+            "agent001/1",
+            Some(base),
+            &[("lib.txt", &numbered_lines(&[(2, "issue 1")]))],
+            "Fix issue 1",
+        );
+        commit_files(
+            repo,
+            "agent001/2",
+            Some(base),
+            &[("lib.txt", &numbered_lines(&[(18, "issue 2")]))],
+            "Fix issue 2",
+        );
+        commit_files(
+            repo,
+            "agent001/3",
+            Some(base),
+            &[("lib.txt", &numbered_lines(&[(2, "issue 3")]))],
+            "Fix issue 3",
+        );
+    })
+}
+
+fn branches(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[test]
+fn test_distant_edits_to_same_file_are_bundle_safe() {
+    let (_dir, git_ops) = setup_repo();
+
+    for trial_merge in [true, false] {
+        let report = git_ops
+            .analyze_bundle_conflicts_with(
+                &branches(&["agent001/1", "agent001/2"]),
+                "main",
+                trial_merge,
+            )
+            .unwrap();
+
+        assert!(report.is_bundle_safe);
+        assert!(report.potential_conflicts.is_empty());
+        assert!(report.conflicting_hunks.is_empty());
+        assert_eq!(report.safe_files, vec!["lib.txt".to_string()]);
+        assert_eq!(report.compatibility_score, 100.0);
+    }
+}
+
+#[test]
+fn test_overlapping_hunks_are_reported_with_line_ranges() {
+    let (_dir, git_ops) = setup_repo();
+
+    let report = git_ops
+        .analyze_bundle_conflicts_with(
+            &branches(&["agent001/1", "agent001/2", "agent001/3"]),
+            "main",
+            false,
+        )
+        .unwrap();
+
+    assert!(!report.is_bundle_safe);
+    assert_eq!(report.conflicting_hunks.len(), 1);
+    let conflict = &report.conflicting_hunks[0];
+    assert_eq!(conflict.file, "lib.txt");
+    assert_eq!(conflict.first_branch, "agent001/1");
+    assert_eq!(conflict.second_branch, "agent001/3");
+    assert_eq!(conflict.first_hunks, vec![HunkRange { start: 2, end: 2 }]);
+    assert_eq!(conflict.second_hunks, vec![HunkRange { start: 2, end: 2 }]);
+    assert!(!conflict.confirmed_by_trial_merge);
+
+    // Only the two branches touching line 2 count against the score
+    assert_eq!(
+        report.potential_conflicts["lib.txt"],
+        branches(&["agent001/1", "agent001/3"])
+    );
+    assert!((report.compatibility_score - 100.0 / 3.0).abs() < 0.01);
+}
+
+#[test]
+fn test_trial_merge_confirms_real_conflicts() {
+    let (_dir, git_ops) = setup_repo();
+
+    let report = git_ops
+        .analyze_bundle_conflicts_with(&branches(&["agent001/1", "agent001/3"]), "main", true)
+        .unwrap();
+    assert_eq!(report.conflicting_hunks.len(), 1);
+    assert!(report.conflicting_hunks[0].confirmed_by_trial_merge);
+
+    let conflicts = git_ops
+        .trial_merge_conflicts("agent001/1", "agent001/2")
+        .unwrap();
+    assert!(conflicts.is_empty());
+}
+
+#[test]
+fn test_hunk_range_overlap() {
+    let edit = HunkRange::from_old_side(5, 3);
+    assert_eq!(edit, HunkRange { start: 5, end: 7 });
+    assert!(edit.overlaps(&HunkRange { start: 7, end: 9 }));
+    // Adjacent edits conflict in git's merge too
+    assert!(edit.overlaps(&HunkRange { start: 8, end: 8 }));
+    assert!(!edit.overlaps(&HunkRange { start: 10, end: 12 }));
+
+    // Pure insertions are anchored on the line they follow
+    assert_eq!(
+        HunkRange::from_old_side(4, 0),
+        HunkRange { start: 4, end: 4 }
+    );
+    assert_eq!(edit.to_string(), "L5-7");
+}
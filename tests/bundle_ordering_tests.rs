@@ -0,0 +1,136 @@
+//! Queued branch ordering and partitioning tests
+
+use my_little_soda::bundling::ordering::{order_branches, partition_branches, BranchFiles};
+use my_little_soda::train_schedule::{parse_issue_dependencies, QueuedBranch};
+use std::collections::HashSet;
+
+fn queued(issue_number: u64, depends_on: &[u64]) -> QueuedBranch {
+    QueuedBranch {
+        branch_name: format!("agent001/{issue_number}"),
+        issue_number,
+        description: format!("Issue {issue_number}"),
+        depends_on: depends_on.to_vec(),
+    }
+}
+
+fn files(entries: &[(u64, &[&str])]) -> BranchFiles {
+    entries
+        .iter()
+        .map(|(issue, paths)| {
+            (
+                format!("agent001/{issue}"),
+                paths.iter().map(|p| p.to_string()).collect::<HashSet<_>>(),
+            )
+        })
+        .collect()
+}
+
+fn issues(branches: &[QueuedBranch]) -> Vec<u64> {
+    branches.iter().map(|b| b.issue_number).collect()
+}
+
+#[test]
+fn test_non_overlapping_branches_go_first_then_issue_number() {
+    let branches = vec![
+        queued(5, &[]),
+        queued(2, &[]),
+        queued(9, &[]),
+        queued(1, &[]),
+    ];
+    let files = files(&[
+        (5, &["src/a.rs"]),
+        (2, &["src/shared.rs"]),
+        (9, &["src/b.rs"]),
+        (1, &["src/shared.rs", "src/c.rs"]),
+    ]);
+
+    let order = order_branches(&branches, &files);
+    assert_eq!(issues(&order.branches), vec![5, 9, 1, 2]);
+    assert!(order.dependency_cycle.is_empty());
+}
+
+#[test]
+fn test_dependencies_come_before_dependents() {
+    // #1 depends on #7 even though it has the lower number and touches nothing shared
+    let branches = vec![queued(1, &[7]), queued(4, &[]), queued(7, &[])];
+    let files = files(&[(1, &["a.rs"]), (4, &["b.rs"]), (7, &["c.rs"])]);
+
+    let order = order_branches(&branches, &files);
+    assert_eq!(issues(&order.branches), vec![4, 7, 1]);
+
+    // Dependencies on issues outside the queue are ignored
+    let order = order_branches(&[queued(3, &[99]), queued(2, &[])], &BranchFiles::new());
+    assert_eq!(issues(&order.branches), vec![2, 3]);
+}
+
+#[test]
+fn test_dependency_cycle_is_reported_and_still_bundled() {
+    let branches = vec![queued(1, &[2]), queued(2, &[1]), queued(3, &[])];
+
+    let order = order_branches(&branches, &BranchFiles::new());
+    assert_eq!(issues(&order.branches), vec![3, 1, 2]);
+    assert_eq!(order.dependency_cycle, vec![1, 2]);
+}
+
+#[test]
+fn test_ordering_is_deterministic() {
+    let files = files(&[(3, &["x.rs"]), (1, &["x.rs"]), (2, &["y.rs"])]);
+    let forward = vec![queued(1, &[]), queued(2, &[]), queued(3, &[])];
+    let backward: Vec<QueuedBranch> = forward.iter().rev().cloned().collect();
+
+    assert_eq!(
+        issues(&order_branches(&forward, &files).branches),
+        issues(&order_branches(&backward, &files).branches)
+    );
+}
+
+#[test]
+fn test_partition_keeps_overlapping_and_dependent_branches_together() {
+    let branches = vec![
+        queued(1, &[]),
+        queued(2, &[]),
+        queued(3, &[]),
+        queued(4, &[3]),
+        queued(5, &[]),
+    ];
+    let files = files(&[
+        (1, &["a.rs"]),
+        (2, &["a.rs"]),
+        (3, &["b.rs"]),
+        (4, &["c.rs"]),
+        (5, &["d.rs"]),
+    ]);
+
+    let ordered = order_branches(&branches, &files).branches;
+    let bundles = partition_branches(&ordered, &files, 2);
+
+    let bundle_issues: Vec<Vec<u64>> = bundles.iter().map(|b| issues(b)).collect();
+    assert!(bundle_issues.iter().any(|b| b == &vec![1, 2]));
+    assert!(bundle_issues.iter().any(|b| b == &vec![3, 4]));
+    assert!(bundle_issues.iter().any(|b| b == &vec![5]));
+    assert_eq!(bundle_issues.iter().map(Vec::len).sum::<usize>(), 5);
+
+    // No file is touched by more than one bundle
+    let mut seen = HashSet::new();
+    for bundle in &bundles {
+        let bundle_files: HashSet<&String> = bundle
+            .iter()
+            .flat_map(|b| files[&b.branch_name].iter())
+            .collect();
+        for file in bundle_files {
+            assert!(seen.insert(file.clone()), "{file} is in two bundles");
+        }
+    }
+}
+
+#[test]
+fn test_parse_issue_dependencies() {
+    let body = "Implements the parser.\n\n\
+        Depends on #12 and #15\n\
+        - Blocked by #3\n\
+        requires #12\n\
+        See also #40";
+
+    assert_eq!(parse_issue_dependencies(body), vec![12, 15, 3]);
+    assert!(parse_issue_dependencies("Mentions #5 in passing").is_empty());
+}
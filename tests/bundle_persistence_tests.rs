@@ -0,0 +1,184 @@
+//! Bundle persistence tests
+//!
+//! The database-backed tests only run with `--features database`.
+
+use my_little_soda::bundling::git_ops::{AssemblyMode, ConflictStrategy};
+use my_little_soda::bundling::types::{BundleState, BundleStatus};
+use my_little_soda::train_schedule::QueuedBranch;
+
+fn bundle_state(bundle_branch: &str) -> BundleState {
+    let queued_branches: Vec<QueuedBranch> = (1..=2)
+        .map(|issue_number| QueuedBranch {
+            branch_name: format!("agent001/{issue_number}"),
+            issue_number,
+            description: format!("Issue {issue_number}"),
+            depends_on: Vec::new(),
+        })
+        .collect();
+
+    BundleState {
+        bundle_branch: bundle_branch.to_string(),
+        base_branch: "main".to_string(),
+        target_branches: queued_branches
+            .iter()
+            .map(|b| b.branch_name.clone())
+            .collect(),
+        queued_branches,
+        completed_branches: vec!["agent001/1".to_string()],
+        failed_branches: Vec::new(),
+        current_operation: None,
+        audit_trail: Vec::new(),
+        recovery_data: None,
+        conflict_strategy: ConflictStrategy::SkipConflicts,
+        assembly_mode: AssemblyMode::CherryPick,
+        pending_conflict: None,
+        conflict_report: None,
+        pushed: false,
+        pr_number: None,
+        labels_applied: false,
+    }
+}
+
+#[test]
+fn test_branch_handed_to_an_unblocker_gets_no_individual_pr() {
+    let mut state = bundle_state("bundle/unblocked");
+    state.drop_branch("agent001/2");
+
+    // The individual-PR fallback opens one PR per remaining queued branch
+    let fallback: Vec<&str> = state
+        .queued_branches
+        .iter()
+        .map(|b| b.branch_name.as_str())
+        .collect();
+    assert_eq!(fallback, vec!["agent001/1"]);
+    assert_eq!(state.target_branches, vec!["agent001/1".to_string()]);
+}
+
+#[cfg(feature = "database")]
+#[test]
+fn test_bundle_status_round_trips_through_strings() {
+    for status in [
+        BundleStatus::InProgress,
+        BundleStatus::Paused,
+        BundleStatus::Completed,
+        BundleStatus::Failed,
+        BundleStatus::FellBack,
+        BundleStatus::Aborted,
+    ] {
+        assert_eq!(BundleStatus::parse(status.as_str()), Some(status));
+    }
+    assert_eq!(BundleStatus::parse("unknown"), None);
+}
+
+#[test]
+fn test_state_from_before_persistence_still_loads() {
+    let mut json = serde_json::to_value(bundle_state("bundle/old")).unwrap();
+    let object = json.as_object_mut().unwrap();
+    for key in ["pushed", "pr_number", "labels_applied", "conflict_report"] {
+        object.remove(key);
+    }
+
+    let state: BundleState = serde_json::from_value(json).unwrap();
+    assert!(!state.pushed);
+    assert_eq!(state.pr_number, None);
+}
+
+#[cfg(feature = "database")]
+mod database {
+    use super::bundle_state;
+    use my_little_soda::bundling::types::BundleStatus;
+    use my_little_soda::database::DatabaseManager;
+    use tempfile::TempDir;
+
+    async fn database() -> (TempDir, DatabaseManager) {
+        let dir = TempDir::new().unwrap();
+        let url = format!("sqlite://{}", dir.path().join("state.db").display());
+        let manager = DatabaseManager::new(&url, true).await.unwrap();
+        (dir, manager)
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_bundle_is_found_for_resume() {
+        let (_dir, db) = database().await;
+
+        let mut state = bundle_state("bundle/a");
+        db.save_bundle(&state, BundleStatus::InProgress)
+            .await
+            .unwrap();
+
+        let entry = db.get_interrupted_bundle().await.unwrap().unwrap();
+        assert_eq!(entry.bundle_id, "bundle/a");
+        assert_eq!(entry.status, BundleStatus::InProgress);
+        let restored = entry.state.unwrap();
+        assert_eq!(restored.completed_branches, vec!["agent001/1".to_string()]);
+        assert_eq!(restored.conflict_strategy, state.conflict_strategy);
+
+        // Once finished there is nothing left to resume
+        state.pushed = true;
+        state.pr_number = Some(42);
+        db.save_bundle(&state, BundleStatus::Completed)
+            .await
+            .unwrap();
+        assert!(db.get_interrupted_bundle().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_history_lists_every_bundle_with_outcome() {
+        let (_dir, db) = database().await;
+
+        db.save_bundle(&bundle_state("bundle/a"), BundleStatus::Completed)
+            .await
+            .unwrap();
+        db.save_bundle(&bundle_state("bundle/b"), BundleStatus::FellBack)
+            .await
+            .unwrap();
+        // Updating a bundle keeps a single row for it
+        db.save_bundle(&bundle_state("bundle/b"), BundleStatus::Failed)
+            .await
+            .unwrap();
+
+        let history = db.get_bundle_history(10).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].bundle_id, "bundle/b");
+        assert_eq!(history[0].status, BundleStatus::Failed);
+        assert_eq!(history[1].bundle_id, "bundle/a");
+        assert_eq!(history[1].status, BundleStatus::Completed);
+        assert!(history.iter().all(|entry| entry.state.is_some()));
+
+        assert_eq!(db.get_bundle_history(1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_every_save_is_kept_as_a_step() {
+        let (_dir, db) = database().await;
+
+        let mut state = bundle_state("bundle/a");
+        state.current_operation = Some("cherry-pick agent001/2".to_string());
+        db.save_bundle(&state, BundleStatus::InProgress)
+            .await
+            .unwrap();
+        state.completed_branches.push("agent001/2".to_string());
+        state.pr_number = Some(42);
+        db.save_bundle(&state, BundleStatus::Completed)
+            .await
+            .unwrap();
+
+        let steps = db.get_bundle_steps("bundle/a").await.unwrap();
+        let summary: Vec<(BundleStatus, usize, Option<u64>)> = steps
+            .iter()
+            .map(|s| (s.status, s.completed_branches, s.pr_number))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (BundleStatus::InProgress, 1, None),
+                (BundleStatus::Completed, 2, Some(42)),
+            ]
+        );
+        assert_eq!(
+            steps[0].operation.as_deref(),
+            Some("cherry-pick agent001/2")
+        );
+        assert!(db.get_bundle_steps("bundle/b").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Bundle PR description tests
+//!
+//! Checks the per-issue changelog collected from git and the structure of the
+//! rendered PR body, including the hidden manifest block.
+
+use chrono::Utc;
+use git2::Repository;
+use my_little_soda::bundling::git_ops::{
+    AssemblyMode, BranchChangelog, ConflictCompatibilityReport, FileChangeStat, GitOperations,
+    HunkConflict, HunkRange,
+};
+use my_little_soda::bundling::pr_body::{BundlePrDescription, IssueChangelog};
+use my_little_soda::bundling::types::{BundleManifest, BundleWindow};
+use my_little_soda::train_schedule::QueuedBranch;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::commit_files;
+
+fn queued(issue_number: u64, description: &str) -> QueuedBranch {
+    QueuedBranch {
+        branch_name: format!("agent001/{issue_number}"),
+        issue_number,
+        description: description.to_string(),
+        depends_on: Vec::new(),
+    }
+}
+
+fn file(path: &str, insertions: usize, deletions: usize) -> FileChangeStat {
+    FileChangeStat {
+        path: path.to_string(),
+        insertions,
+        deletions,
+        binary: false,
+    }
+}
+
+fn description() -> BundlePrDescription {
+    let mut report = ConflictCompatibilityReport::new();
+    report.compatibility_score = 100.0;
+    report.analyzed_branches = vec!["agent001/1".to_string(), "agent001/2".to_string()];
+    report.conflicting_hunks.push(HunkConflict {
+        file: "src/lib.rs".to_string(),
+        first_branch: "agent001/1".to_string(),
+        second_branch: "agent001/2".to_string(),
+        first_hunks: vec![HunkRange { start: 3, end: 4 }],
+        second_hunks: vec![HunkRange { start: 5, end: 5 }],
+        confirmed_by_trial_merge: false,
+    });
+
+    BundlePrDescription {
+        owner: "octo".to_string(),
+        repo: "soda".to_string(),
+        bundle_branch: "bundle/20250101_1000__issues_1_2".to_string(),
+        base_branch: "main".to_string(),
+        assembly_mode: AssemblyMode::CherryPick,
+        window: BundleWindow::current(),
+        created_at: Utc::now(),
+        issues: vec![
+            IssueChangelog {
+                branch: queued(1, "Fix parser"),
+                changelog: BranchChangelog {
+                    commits: vec!["abcd1234 Fix parser".to_string()],
+                    files: vec![file("src/lib.rs", 3, 1), file("README.md", 2, 0)],
+                },
+            },
+            IssueChangelog {
+                branch: queued(2, "Handle --> in titles"),
+                changelog: BranchChangelog {
+                    commits: vec![
+                        "1234abcd First pass".to_string(),
+                        "5678ef90 Second pass".to_string(),
+                    ],
+                    files: vec![file("src/lib.rs", 1, 1)],
+                },
+            },
+        ],
+        skipped: vec![queued(3, "Conflicting work")],
+        dropped: Vec::new(),
+        conflict_report: Some(report),
+    }
+}
+
+#[test]
+fn test_branch_changelog_lists_commits_and_diffstat() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    let base = commit_files(
+        &repo,
+        "main",
+        None,
+        &[("lib.txt", "one\ntwo\nthree\n")],
+        "Initial commit",
+    );
+    let first = commit_files(
+        &repo,
+        "agent001/7",
+        Some(base),
+        &[("lib.txt", "one\n2\nthree\n")],
+        "Rename two",
+    );
+    commit_files(
+        &repo,
+        "agent001/7",
+        Some(first),
+        &[("new.txt", "a\nb\n")],
+        "Add new file",
+    );
+    // Work landing on main afterwards doesn't count towards the branch
+    commit_files(
+        &repo,
+        "main",
+        Some(base),
+        &[("other.txt", "x\n")],
+        "Unrelated",
+    );
+
+    let git_ops = GitOperations::with_repository(repo);
+    let changelog = git_ops.branch_changelog("agent001/7", "main").unwrap();
+
+    assert_eq!(changelog.commits.len(), 2);
+    assert!(changelog.commits[0].ends_with(" Rename two"));
+    assert!(changelog.commits[1].ends_with(" Add new file"));
+    assert_eq!(
+        changelog.files,
+        vec![file("lib.txt", 1, 1), file("new.txt", 2, 0)]
+    );
+    assert_eq!(changelog.insertions(), 3);
+    assert_eq!(changelog.deletions(), 1);
+}
+
+#[test]
+fn test_pr_body_has_section_per_issue() {
+    let body = description().render();
+
+    assert!(body.contains("### #1: Fix parser"));
+    assert!(body.contains("Closes #1"));
+    assert!(body.contains("Closes #2"));
+    assert!(body.contains("https://github.com/octo/soda/issues/2"));
+    assert!(body.contains("- `abcd1234` Fix parser"));
+    assert!(body.contains("**Diffstat:** 2 files changed, +5 -1"));
+    assert!(body.contains("**Changes:** 2 files changed, +6 -2"));
+    // Skipped issues must not be closed by the bundle
+    assert!(!body.contains("Closes #3"));
+    assert!(body.contains("## Skipped Due to Conflicts"));
+}
+
+#[test]
+fn test_pr_body_file_table_and_conflict_summary() {
+    let body = description().render();
+
+    assert!(body.contains("## File Changes"));
+    assert!(body.contains("| `src/lib.rs` | #1, #2 | 4 | 2 |"));
+    assert!(body.contains("| `README.md` | #1 | 2 | 0 |"));
+    assert!(body.contains("## Conflict Analysis"));
+    assert!(body.contains("**Compatibility Score:** 100.0%"));
+    assert!(body.contains("- `src/lib.rs`: `agent001/1` [L3-4] vs `agent001/2` [L5]"));
+}
+
+#[test]
+fn test_pr_body_manifest_round_trips() {
+    let description = description();
+    let body = description.render();
+
+    let manifest = BundleManifest::from_pr_body(&body).expect("manifest block");
+    assert_eq!(manifest, description.manifest());
+    assert_eq!(manifest.version, BundleManifest::VERSION);
+    assert_eq!(
+        manifest
+            .issues
+            .iter()
+            .map(|i| i.issue_number)
+            .collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(manifest.issues[0].files, vec!["src/lib.rs", "README.md"]);
+    assert_eq!(manifest.issues[1].title, "Handle --> in titles");
+    assert_eq!(manifest.skipped[0].issue_number, 3);
+
+    assert!(BundleManifest::from_pr_body("Plain PR body").is_none());
+}
+
+#[test]
+fn test_pr_body_lists_dropped_issues() {
+    let mut description = description();
+    description.dropped = vec![queued(4, "Reverted after review")];
+    let body = description.render();
+
+    assert!(body.contains("## Dropped After Review"));
+    assert!(body.contains("**Issue #4**: Reverted after review"));
+    assert!(!body.contains("Closes #4"));
+
+    let manifest = BundleManifest::from_pr_body(&body).unwrap();
+    assert_eq!(manifest.dropped[0].issue_number, 4);
+}
@@ -0,0 +1,262 @@
+//! Bundle rebuild tests
+//!
+//! Covers planning a rebuild from a bundle manifest, carrying review feedback
+//! over to the dropped issue, and the force-push lease on the bundle branch.
+
+use chrono::Utc;
+use git2::Repository;
+use my_little_soda::bundling::git_ops::{AssemblyMode, GitOperations};
+use my_little_soda::bundling::rebuild::{
+    drop_comment, dropped_branch_name, feedback_for_issue, labels_to_remove_on_drop,
+    mentions_issue, RebuildPlan, ReviewerComment,
+};
+use my_little_soda::bundling::types::{BundleManifest, BundleManifestIssue};
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::commit_file;
+
+fn entry(issue_number: u64, files: &[&str]) -> BundleManifestIssue {
+    BundleManifestIssue {
+        issue_number,
+        branch_name: format!("agent001/{issue_number}"),
+        title: format!("Issue {issue_number}"),
+        commits: Vec::new(),
+        files: files.iter().map(|f| f.to_string()).collect(),
+        insertions: 0,
+        deletions: 0,
+    }
+}
+
+fn manifest() -> BundleManifest {
+    BundleManifest {
+        version: BundleManifest::VERSION,
+        bundle_branch: "bundle/20250101_1000__issues_1_2_3".to_string(),
+        base_branch: "main".to_string(),
+        assembly_mode: AssemblyMode::CherryPick,
+        created_at: Utc::now(),
+        issues: vec![
+            entry(1, &["src/a.rs"]),
+            entry(2, &["src/b.rs"]),
+            entry(3, &["src/c.rs"]),
+        ],
+        skipped: vec![entry(4, &[])],
+        dropped: vec![entry(5, &[])],
+        compatibility_score: Some(100.0),
+    }
+}
+
+fn comment(body: &str, path: Option<&str>) -> ReviewerComment {
+    ReviewerComment {
+        author: "reviewer".to_string(),
+        body: body.to_string(),
+        path: path.map(|p| p.to_string()),
+        url: "https://github.com/octo/soda/pull/9#comment".to_string(),
+    }
+}
+
+#[test]
+fn test_plan_keeps_remaining_issues_in_order() {
+    let plan = RebuildPlan::new(&manifest(), 2).unwrap();
+
+    assert_eq!(plan.dropped.issue_number, 2);
+    assert_eq!(
+        plan.remaining
+            .iter()
+            .map(|b| b.issue_number)
+            .collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+    assert_eq!(plan.remaining[0].branch_name, "agent001/1");
+    assert_eq!(plan.skipped[0].issue_number, 4);
+    // Earlier drops are remembered alongside this one
+    assert_eq!(
+        plan.all_dropped
+            .iter()
+            .map(|b| b.issue_number)
+            .collect::<Vec<_>>(),
+        vec![5, 2]
+    );
+}
+
+#[test]
+fn test_plan_rejects_issues_outside_the_bundle() {
+    let manifest = manifest();
+    let error = |issue| RebuildPlan::new(&manifest, issue).unwrap_err().to_string();
+
+    assert!(error(5).contains("already dropped"));
+    assert!(error(4).contains("skipped"));
+    assert!(error(42).contains("not part of"));
+
+    let mut single = manifest.clone();
+    single.issues.truncate(1);
+    assert!(RebuildPlan::new(&single, 1)
+        .unwrap_err()
+        .to_string()
+        .contains("close the PR"));
+}
+
+#[test]
+fn test_feedback_is_matched_by_file_or_mention() {
+    let comments = vec![
+        comment("Off-by-one here", Some("src/b.rs")),
+        comment("Unrelated nit", Some("src/a.rs")),
+        comment("#2 breaks the parser, please drop it", None),
+        comment("#21 looks fine", None),
+        comment("LGTM overall", None),
+    ];
+
+    let feedback = feedback_for_issue(&entry(2, &["src/b.rs"]), &comments);
+    assert_eq!(
+        feedback.iter().map(|c| c.body.as_str()).collect::<Vec<_>>(),
+        vec!["Off-by-one here", "#2 breaks the parser, please drop it"]
+    );
+}
+
+#[test]
+fn test_mentions_issue_needs_a_whole_reference() {
+    assert!(mentions_issue("See #12.", 12));
+    assert!(mentions_issue("(#12)", 12));
+    assert!(!mentions_issue("See #123", 12));
+    assert!(!mentions_issue("other/repo#12", 12));
+    assert!(!mentions_issue("No reference", 12));
+}
+
+#[test]
+fn test_dropped_issue_loses_review_and_agent_labels() {
+    let labels: Vec<String> = [
+        "route:review",
+        "agent001",
+        "route:priority-high",
+        "agents-docs",
+    ]
+    .iter()
+    .map(|l| l.to_string())
+    .collect();
+
+    assert_eq!(
+        labels_to_remove_on_drop(&labels),
+        vec!["route:review".to_string(), "agent001".to_string()]
+    );
+}
+
+#[test]
+fn test_drop_comment_quotes_feedback() {
+    let text = drop_comment(
+        9,
+        &[comment("Needs a test\nfor empty input", Some("src/b.rs"))],
+    );
+
+    assert!(text.contains("Dropped from bundle PR #9"));
+    assert!(text.contains("route:ready"));
+    assert!(text.contains("**@reviewer** on `src/b.rs`"));
+    assert!(text.contains("> Needs a test\n> for empty input\n"));
+
+    assert!(drop_comment(9, &[]).contains("No review comments referenced this issue"));
+}
+
+#[test]
+fn test_force_push_with_lease_refuses_stale_remote() {
+    let remote_dir = TempDir::new().unwrap();
+    Repository::init_bare(remote_dir.path()).unwrap();
+
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+
+    let base = commit_file(&repo, "main", None, "file.txt", "base\n");
+    let first = commit_file(&repo, "bundle/x", Some(base), "file.txt", "first\n");
+    repo.find_remote("origin")
+        .unwrap()
+        .push(&["refs/heads/bundle/x:refs/heads/bundle/x"], None)
+        .unwrap();
+
+    // Rewrite the bundle branch on top of main
+    let rewritten = commit_file(&repo, "bundle/x", Some(base), "file.txt", "rewritten\n");
+    let mut git_ops = GitOperations::with_repository(repo);
+
+    // A lease on the wrong commit leaves the remote alone
+    assert!(git_ops
+        .force_push_with_lease("bundle/x", "origin", base)
+        .is_err());
+    let remote = Repository::open_bare(remote_dir.path()).unwrap();
+    let remote_tip = |remote: &Repository| {
+        remote
+            .find_reference("refs/heads/bundle/x")
+            .unwrap()
+            .target()
+            .unwrap()
+    };
+    assert_eq!(remote_tip(&remote), first);
+
+    git_ops
+        .force_push_with_lease("bundle/x", "origin", first)
+        .unwrap();
+    assert_eq!(remote_tip(&remote), rewritten);
+}
+
+#[test]
+fn test_dropped_branch_leaves_the_agent_namespace() {
+    let remote_dir = TempDir::new().unwrap();
+    Repository::init_bare(remote_dir.path()).unwrap();
+
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+    let base = commit_file(&repo, "main", None, "file.txt", "base\n");
+    let work = commit_file(&repo, "agent001/2", Some(base), "file.txt", "rejected\n");
+    repo.set_head("refs/heads/main").unwrap();
+    repo.find_remote("origin")
+        .unwrap()
+        .push(&["refs/heads/agent001/2:refs/heads/agent001/2"], None)
+        .unwrap();
+    repo.reference("refs/remotes/origin/agent001/2", work, true, "fetch")
+        .unwrap();
+
+    let dropped = dropped_branch_name("agent001/2");
+    let git_ops = GitOperations::with_repository(repo);
+    git_ops
+        .rename_branch("agent001/2", &dropped, "origin")
+        .unwrap();
+
+    // The train schedule queues local `agent*` and `origin/agent*` branches
+    let repo = Repository::open(dir.path()).unwrap();
+    let remote = Repository::open_bare(remote_dir.path()).unwrap();
+    let agent_refs = |repo: &Repository, glob: &str| repo.references_glob(glob).unwrap().count();
+    assert_eq!(agent_refs(&repo, "refs/heads/agent*"), 0);
+    assert_eq!(agent_refs(&repo, "refs/remotes/origin/agent*"), 0);
+    assert_eq!(agent_refs(&remote, "refs/heads/agent*"), 0);
+
+    // The rejected commits are kept on the renamed branch
+    let kept = |repo: &Repository| {
+        repo.find_reference(&format!("refs/heads/{dropped}"))
+            .unwrap()
+            .target()
+            .unwrap()
+    };
+    assert_eq!(kept(&repo), work);
+    assert_eq!(kept(&remote), work);
+}
+
+#[test]
+fn test_reset_branch_to_base_moves_branch_back() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let base = commit_file(&repo, "main", None, "file.txt", "base\n");
+    commit_file(&repo, "bundle/x", Some(base), "file.txt", "work\n");
+    repo.set_head("refs/heads/main").unwrap();
+
+    let git_ops = GitOperations::with_repository(repo);
+    git_ops.reset_branch_to_base("bundle/x", "main").unwrap();
+
+    let repo = Repository::open(dir.path()).unwrap();
+    let tip = repo
+        .find_reference("refs/heads/bundle/x")
+        .unwrap()
+        .target()
+        .unwrap();
+    assert_eq!(tip, base);
+}
@@ -0,0 +1,208 @@
+//! Pre-bundle refresh tests
+//!
+//! Covers detecting queued branches that fell behind the base, rebasing them in
+//! a scratch worktree and flagging the ones that need a manual rebase.
+
+use git2::Repository;
+use my_little_soda::bundling::git_ops::{BranchRebase, GitOperations};
+use my_little_soda::bundling::refresh::{
+    needs_rebase_comment, BranchRefresh, RefreshOutcome, NEEDS_REBASE_LABEL,
+};
+use my_little_soda::train_schedule::QueuedBranch;
+use my_little_soda::PreFlightIssue;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::{commit_file, diverged_repo, tip};
+
+fn queued(issue_number: u64) -> QueuedBranch {
+    QueuedBranch {
+        branch_name: format!("agent001/{issue_number}"),
+        issue_number,
+        description: "Work".to_string(),
+        depends_on: Vec::new(),
+    }
+}
+
+#[test]
+fn test_behind_base_counts_missing_commits() {
+    let (dir, _, _) = diverged_repo("agent.txt");
+    let git_ops = GitOperations::with_repository(Repository::open(dir.path()).unwrap());
+
+    assert_eq!(
+        git_ops.behind_base("agent001/1", "main").unwrap(),
+        Some(PreFlightIssue::BehindMain { commits: 2 })
+    );
+    assert_eq!(git_ops.behind_base("main", "main").unwrap(), None);
+}
+
+#[test]
+fn test_clean_rebase_happens_in_a_scratch_worktree() {
+    let (dir, work, main) = diverged_repo("agent.txt");
+    let git_ops = GitOperations::with_repository(Repository::open(dir.path()).unwrap());
+
+    let BranchRebase::Rebased { tip: rebased } =
+        git_ops.rebase_in_worktree("agent001/1", "main").unwrap()
+    else {
+        panic!("expected a clean rebase");
+    };
+
+    let repo = Repository::open(dir.path()).unwrap();
+    let rebased_commit = repo.find_commit(rebased).unwrap();
+    assert_eq!(rebased_commit.parent_id(0).unwrap(), main);
+    assert!(rebased_commit
+        .tree()
+        .unwrap()
+        .get_name("agent.txt")
+        .is_some());
+    assert_eq!(rebased_commit.author().name(), Some("Test Agent"));
+
+    // The branch only moves when asked, and nothing of the scratch worktree is left
+    assert_eq!(tip(&repo, "refs/heads/agent001/1"), work);
+    assert!(repo.worktrees().unwrap().is_empty());
+    assert_eq!(
+        repo.branches(Some(git2::BranchType::Local))
+            .unwrap()
+            .count(),
+        2
+    );
+
+    git_ops.set_branch_tip("agent001/1", rebased).unwrap();
+    assert_eq!(tip(&repo, "refs/heads/agent001/1"), rebased);
+    assert_eq!(git_ops.behind_base("agent001/1", "main").unwrap(), None);
+}
+
+#[test]
+fn test_conflicting_rebase_leaves_branch_untouched() {
+    let (dir, work, _) = diverged_repo("README.md");
+    let git_ops = GitOperations::with_repository(Repository::open(dir.path()).unwrap());
+
+    match git_ops.rebase_in_worktree("agent001/1", "main").unwrap() {
+        BranchRebase::Conflicted {
+            commit,
+            conflicted_files,
+        } => {
+            assert_eq!(commit, work);
+            assert_eq!(conflicted_files, vec!["README.md".to_string()]);
+        }
+        other => panic!("expected a conflict, got {other:?}"),
+    }
+
+    let repo = Repository::open(dir.path()).unwrap();
+    assert_eq!(tip(&repo, "refs/heads/agent001/1"), work);
+    assert!(repo.worktrees().unwrap().is_empty());
+}
+
+#[test]
+fn test_checked_out_branch_is_not_moved() {
+    let (dir, _, main) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+    repo.set_head("refs/heads/agent001/1").unwrap();
+    let git_ops = GitOperations::with_repository(repo);
+
+    let error = git_ops.set_branch_tip("agent001/1", main).unwrap_err();
+    assert!(error.to_string().contains("checked out"));
+}
+
+#[test]
+fn test_rebased_branch_is_pushed_with_a_lease_on_the_remote_tip() {
+    let remote_dir = TempDir::new().unwrap();
+    Repository::init_bare(remote_dir.path()).unwrap();
+
+    let (dir, work, _) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+    repo.find_remote("origin")
+        .unwrap()
+        .push(&["refs/heads/agent001/1:refs/heads/agent001/1"], None)
+        .unwrap();
+    // What a fetch would have recorded
+    repo.reference("refs/remotes/origin/agent001/1", work, true, "fetch")
+        .unwrap();
+
+    let mut git_ops = GitOperations::with_repository(repo);
+    assert_eq!(git_ops.remote_tip("agent001/1", "origin"), Some(work));
+    assert_eq!(git_ops.remote_tip("agent001/2", "origin"), None);
+
+    let BranchRebase::Rebased { tip: rebased } =
+        git_ops.rebase_in_worktree("agent001/1", "main").unwrap()
+    else {
+        panic!("expected a clean rebase");
+    };
+    git_ops.set_branch_tip("agent001/1", rebased).unwrap();
+    git_ops
+        .force_push_with_lease("agent001/1", "origin", work)
+        .unwrap();
+
+    let remote = Repository::open_bare(remote_dir.path()).unwrap();
+    assert_eq!(tip(&remote, "refs/heads/agent001/1"), rebased);
+}
+
+#[test]
+fn test_fetch_refreshes_a_stale_lease() {
+    let remote_dir = TempDir::new().unwrap();
+    Repository::init_bare(remote_dir.path()).unwrap();
+
+    let (dir, work, main) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+    // Someone else pushed on top of the branch since the last fetch
+    let pushed = commit_file(&repo, "elsewhere", Some(work), "other.txt", "other\n");
+    repo.find_remote("origin")
+        .unwrap()
+        .push(&["refs/heads/elsewhere:refs/heads/agent001/1"], None)
+        .unwrap();
+    repo.reference("refs/remotes/origin/agent001/1", work, true, "fetch")
+        .unwrap();
+
+    let git_ops = GitOperations::with_repository(repo);
+    git_ops
+        .fetch_branches(
+            &["agent001/1".to_string(), "agent001/2".to_string()],
+            "origin",
+        )
+        .unwrap();
+
+    assert_eq!(git_ops.remote_tip("agent001/1", "origin"), Some(pushed));
+    assert_eq!(git_ops.remote_tip("agent001/2", "origin"), None);
+    // The local branch lacks the pushed commit, so it must not be rebased over it
+    assert!(!git_ops.is_ancestor(pushed, work).unwrap());
+    assert!(git_ops.is_ancestor(work, pushed).unwrap());
+    assert!(git_ops.is_ancestor(main, main).unwrap());
+}
+
+#[test]
+fn test_only_branches_needing_resolution_are_left_out() {
+    let refresh = |outcome| BranchRefresh {
+        branch: queued(4),
+        outcome,
+    };
+
+    assert!(refresh(RefreshOutcome::UpToDate).is_bundleable());
+    assert!(refresh(RefreshOutcome::Rebased {
+        behind: 3,
+        pushed: true
+    })
+    .is_bundleable());
+    assert!(refresh(RefreshOutcome::Failed("offline".to_string())).is_bundleable());
+
+    let conflicted = refresh(RefreshOutcome::NeedsResolution {
+        behind: 3,
+        conflicted_files: vec!["src/lib.rs".to_string()],
+    });
+    assert!(!conflicted.is_bundleable());
+    assert!(conflicted.summary().contains("conflicts in src/lib.rs"));
+}
+
+#[test]
+fn test_needs_rebase_comment_explains_the_fix() {
+    let comment = needs_rebase_comment(&queued(4), "main", 3, &["src/lib.rs".to_string()]);
+
+    assert!(comment.contains("`agent001/4` is 3 commit(s) behind `main`"));
+    assert!(comment.contains("- `src/lib.rs`"));
+    assert!(comment.contains("git rebase origin/main"));
+    assert!(comment.contains(NEEDS_REBASE_LABEL));
+}
@@ -0,0 +1,137 @@
+//! Bundle unblocker tests
+//!
+//! Covers describing a bundle conflict as a `route:unblocker` issue, finding
+//! the branches on the other side of it and re-queuing the blocked issue.
+
+use my_little_soda::bundling::ordering::BranchFiles;
+use my_little_soda::bundling::unblocker::{
+    conflicting_branches, rebase_instructions, UnblockerTask, BLOCKED_LABEL, UNBLOCKER_MARKER,
+};
+use my_little_soda::train_schedule::QueuedBranch;
+
+fn branch(issue_number: u64) -> QueuedBranch {
+    QueuedBranch {
+        branch_name: format!("agent001/{issue_number}"),
+        issue_number,
+        description: format!("Issue {issue_number}"),
+        depends_on: Vec::new(),
+    }
+}
+
+fn task() -> UnblockerTask {
+    UnblockerTask {
+        conflicted_branch: branch(12),
+        conflicting_branches: vec![branch(7), branch(9)],
+        conflicted_files: vec!["src/parser.rs".to_string()],
+        commit: Some("0123456789abcdef".to_string()),
+        bundle_branch: "bundle/20250101_1000__issues_7_9_12".to_string(),
+        base_branch: "main".to_string(),
+        base_sha: "feedface".to_string(),
+    }
+}
+
+fn files(entries: &[(&str, &[&str])]) -> BranchFiles {
+    entries
+        .iter()
+        .map(|(branch, files)| {
+            (
+                branch.to_string(),
+                files.iter().map(|f| f.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_original_issues_lead_with_the_conflicted_issue() {
+    let mut task = task();
+    task.conflicting_branches.push(branch(7));
+    assert_eq!(task.original_issues(), vec![12, 7, 9]);
+}
+
+#[test]
+fn test_issue_body_describes_the_conflict() {
+    let body = task().issue_body();
+
+    assert!(body.contains("`agent001/12` (#12) could not be applied"));
+    assert!(body.contains("`main` at `feedface`"));
+    assert!(body.contains("`agent001/7` (#7), `agent001/9` (#9)"));
+    assert!(body.contains("- `src/parser.rs`"));
+    assert!(body.contains("git checkout -b repro/12 feedface"));
+    assert!(body.contains("git cherry-pick feedface..origin/agent001/7\n"));
+    assert!(body.contains("git cherry-pick feedface..origin/agent001/12  # conflicts here"));
+    assert!(body.contains("**Blocks**: #12"));
+    assert!(body.contains("**Related**: #12, #7, #9"));
+    assert!(body.contains(UNBLOCKER_MARKER));
+}
+
+#[test]
+fn test_issue_body_without_other_branches_blames_the_base() {
+    let mut task = task();
+    task.conflicting_branches.clear();
+    task.commit = None;
+
+    let body = task.issue_body();
+    assert!(body.contains("**Conflicts with**: the bundle base"));
+    assert!(!body.contains("Conflicting commit"));
+}
+
+#[test]
+fn test_task_round_trips_through_the_issue_body() {
+    let parsed = UnblockerTask::from_issue_body(&task().issue_body()).unwrap();
+
+    assert_eq!(parsed.conflicted_branch.branch_name, "agent001/12");
+    assert_eq!(parsed.original_issues(), vec![12, 7, 9]);
+    assert_eq!(parsed.base_sha, "feedface");
+    assert!(UnblockerTask::from_issue_body("A hand-written unblocker").is_none());
+}
+
+#[test]
+fn test_conflicting_branches_share_a_conflicted_file() {
+    let applied = vec![branch(7), branch(8), branch(9)];
+    let files = files(&[
+        ("agent001/7", &["src/parser.rs", "README.md"]),
+        ("agent001/8", &["src/lexer.rs"]),
+        ("agent001/9", &["src/parser.rs"]),
+    ]);
+
+    let conflicting = conflicting_branches(&["src/parser.rs".to_string()], &applied, &files);
+    assert_eq!(
+        conflicting
+            .iter()
+            .map(|b| b.issue_number)
+            .collect::<Vec<_>>(),
+        vec![7, 9]
+    );
+    assert!(conflicting_branches(&["docs/x.md".to_string()], &applied, &files).is_empty());
+}
+
+#[test]
+fn test_comments_link_originals_and_carry_rebase_instructions() {
+    let task = task();
+
+    let blocked = task.blocked_comment(40, 12);
+    assert!(blocked.contains("Blocked by #40"));
+    assert!(blocked.contains(BLOCKED_LABEL));
+    assert!(blocked.contains("`src/parser.rs`"));
+
+    let involved = task.blocked_comment(40, 7);
+    assert!(involved.contains("Involved in #40"));
+    assert!(involved.contains("`agent001/12` (#12)"));
+
+    let requeue = task.requeue_comment(40, 41);
+    assert!(requeue.contains("Unblocked by #40"));
+    assert!(requeue.contains("PR #41"));
+    assert!(requeue.contains(&rebase_instructions("agent001/12", "main")));
+}
+
+#[test]
+fn test_rebase_instructions() {
+    assert_eq!(
+        rebase_instructions("agent002/5", "main"),
+        "git fetch origin\n\
+        git checkout agent002/5\n\
+        git rebase origin/main\n\
+        git push --force-with-lease origin agent002/5\n"
+    );
+}
@@ -0,0 +1,413 @@
+//! CI log parsing and failure ingestion, against saved GitHub Actions logs
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use my_little_soda::ci::ingest::CiLogSource;
+use my_little_soda::ci::junit::xml_reports_in_zip;
+use my_little_soda::ci::{
+    ingest_failures, CargoTestParser, FailureKind, FailureParser, FailureParsers, JestParser,
+    JunitParser, ParsedFailure, PytestParser, RustcDiagnosticParser, CI_FAILURE_MARKER,
+};
+use my_little_soda::github::actions::FailedJob;
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!(
+        "{}/tests/fixtures/ci_logs/{name}",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap()
+}
+
+fn parse_with(parser: impl FailureParser + 'static, log: &str) -> Vec<ParsedFailure> {
+    FailureParsers::empty()
+        .with_parser(Box::new(parser))
+        .parse(log)
+}
+
+#[test]
+fn cargo_test_failures_carry_panic_location_and_suite() {
+    let failures = parse_with(CargoTestParser, &fixture("cargo_test.log"));
+
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures[0].name, "lexer::tests::reads_numbers");
+    assert_eq!(failures[0].suite.as_deref(), Some("src/lib.rs"));
+    assert_eq!(failures[0].message, "assertion `left == right` failed");
+    assert_eq!(failures[0].location().as_deref(), Some("src/lexer.rs:88"));
+
+    // Panic message format of older toolchains
+    assert_eq!(failures[1].name, "roundtrip_unicode");
+    assert_eq!(failures[1].suite.as_deref(), Some("tests/roundtrip.rs"));
+    assert_eq!(
+        failures[1].message,
+        "called `Result::unwrap()` on an `Err` value: InvalidUtf8"
+    );
+    assert_eq!(
+        failures[1].location().as_deref(),
+        Some("tests/roundtrip.rs:21")
+    );
+}
+
+#[test]
+fn rustc_errors_and_denied_lints_are_told_apart() {
+    let failures = parse_with(RustcDiagnosticParser, &fixture("clippy.log"));
+
+    // Warnings and the "could not compile" summary are not failures
+    assert_eq!(failures.len(), 2, "{failures:#?}");
+    assert_eq!(failures[0].kind, FailureKind::Compile);
+    assert_eq!(failures[0].name, "E0425");
+    assert_eq!(
+        failures[0].message,
+        "cannot find value `tokn` in this scope"
+    );
+    assert_eq!(failures[0].location().as_deref(), Some("src/parser.rs:142"));
+
+    assert_eq!(failures[1].kind, FailureKind::Lint);
+    assert_eq!(failures[1].name, "clippy::if_same_then_else");
+    assert_eq!(failures[1].location().as_deref(), Some("src/lexer.rs:57"));
+}
+
+#[test]
+fn pytest_summary_lines_find_the_traceback_line() {
+    let failures = parse_with(PytestParser, &fixture("pytest.log"));
+
+    assert_eq!(failures.len(), 2);
+    assert_eq!(
+        failures[0].name,
+        "tests/test_report.py::test_totals_are_rounded"
+    );
+    assert_eq!(failures[0].message, "assert 0.30000000000000004 == 0.3");
+    assert_eq!(
+        failures[0].location().as_deref(),
+        Some("tests/test_report.py:27")
+    );
+    assert_eq!(failures[1].name, "tests/test_export.py::test_export_csv");
+    assert_eq!(failures[1].message, "KeyError: 'DATABASE_URL'");
+    assert_eq!(
+        failures[1].location().as_deref(),
+        Some("tests/test_export.py:9")
+    );
+}
+
+#[test]
+fn jest_blocks_are_reported_once_with_their_test_file_frame() {
+    let failures = parse_with(JestParser, &fixture("jest.log"));
+
+    let names: Vec<&str> = failures.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Button › renders the label",
+            "Button › calls onClick once",
+            "src/api/client.test.js",
+        ]
+    );
+    assert_eq!(
+        failures[0].message,
+        "expect(received).toBe(expected) // Object.is equality"
+    );
+    assert_eq!(
+        failures[0].location().as_deref(),
+        Some("src/components/Button.test.js:12")
+    );
+    assert_eq!(
+        failures[1].location().as_deref(),
+        Some("src/components/Button.test.js:21")
+    );
+    // A suite that failed to load only points at library frames
+    assert_eq!(
+        failures[2].message,
+        "Cannot find module './config' from 'src/api/client.js'"
+    );
+    assert_eq!(
+        failures[2].location().as_deref(),
+        Some("src/api/client.test.js")
+    );
+}
+
+#[test]
+fn junit_reports_list_failed_and_errored_cases() {
+    let failures = JunitParser.parse(&fixture("junit.xml"));
+
+    assert_eq!(failures.len(), 3, "{failures:#?}");
+    assert_eq!(
+        failures[0].name,
+        "tests.checkout_test::test_applies_discount"
+    );
+    assert_eq!(failures[0].suite.as_deref(), Some("checkout"));
+    assert_eq!(failures[0].message, "AssertionError: 90 != 85 & rounding");
+    assert_eq!(
+        failures[0].location().as_deref(),
+        Some("tests/checkout_test.py:41")
+    );
+
+    // Without a message attribute the first line of the body is used
+    assert_eq!(
+        failures[1].name,
+        "tests.checkout_test::test_payment_timeout"
+    );
+    assert_eq!(
+        failures[1].message,
+        "TimeoutError: gateway did not answer in 5s"
+    );
+
+    assert_eq!(failures[2].name, "reserves <0> items");
+    assert_eq!(failures[2].suite.as_deref(), Some("inventory"));
+    assert_eq!(failures[2].message, "test failure");
+}
+
+#[test]
+fn zip_artifacts_yield_their_xml_reports() {
+    let archive = std::fs::read(format!(
+        "{}/tests/fixtures/ci_logs/test-results.zip",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+
+    let reports = xml_reports_in_zip(&archive).unwrap();
+    let names: Vec<&str> = reports.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["reports/junit.xml", "coverage.xml"]);
+    assert_eq!(reports[0].1, fixture("junit.xml"));
+
+    assert!(xml_reports_in_zip(b"not a zip").is_err());
+}
+
+#[test]
+fn custom_parsers_plug_into_the_set() {
+    struct Shellcheck;
+    impl FailureParser for Shellcheck {
+        fn name(&self) -> &'static str {
+            "shellcheck"
+        }
+
+        fn parse(&self, log: &str) -> Vec<ParsedFailure> {
+            log.lines()
+                .filter(|l| l.contains("curl: (22)"))
+                .map(|l| ParsedFailure::new(FailureKind::Test, "deploy", l.trim()))
+                .collect()
+        }
+    }
+
+    let parsers = FailureParsers::default().with_parser(Box::new(Shellcheck));
+    let failures = parsers.parse(&fixture("deploy.log"));
+    assert_eq!(failures.len(), 1);
+    assert_eq!(
+        failures[0].message,
+        "curl: (22) The requested URL returned error: 403"
+    );
+}
+
+/// Failed jobs and logs served from the fixtures
+struct FixtureSource {
+    jobs: Vec<(FailedJob, &'static str)>,
+    junit_runs: Vec<u64>,
+}
+
+fn job(id: u64, run_id: u64, name: &str, step: &str) -> FailedJob {
+    FailedJob {
+        id,
+        run_id,
+        name: name.to_string(),
+        html_url: format!("https://github.com/acme/parser/actions/runs/{run_id}/job/{id}"),
+        failed_step: Some(step.to_string()),
+    }
+}
+
+#[async_trait]
+impl CiLogSource for FixtureSource {
+    async fn failed_jobs(&self, _head_sha: &str) -> Result<Vec<FailedJob>> {
+        Ok(self.jobs.iter().map(|(job, _)| job.clone()).collect())
+    }
+
+    async fn job_log(&self, job_id: u64) -> Result<String> {
+        match self.jobs.iter().find(|(job, _)| job.id == job_id) {
+            Some((_, "")) | None => Err(anyhow!("log expired")),
+            Some((_, log)) => Ok(fixture(log)),
+        }
+    }
+
+    async fn junit_reports(&self, run_id: u64) -> Result<Vec<(String, String)>> {
+        Ok(if self.junit_runs.contains(&run_id) {
+            vec![("junit.xml".to_string(), fixture("junit.xml"))]
+        } else {
+            Vec::new()
+        })
+    }
+}
+
+const SHA: &str = "9f2c4e1b7a3d5f60c8e2b4a6d1f3e5c7a9b0d2e4";
+
+#[tokio::test]
+async fn ingestion_reports_every_failed_job() {
+    let source = FixtureSource {
+        jobs: vec![
+            (job(11, 1, "test (ubuntu)", "Run tests"), "cargo_test.log"),
+            (job(12, 1, "lint", "Clippy"), "clippy.log"),
+            (job(21, 2, "python", "pytest"), ""),
+            (job(31, 3, "deploy-preview", "Deploy"), "deploy.log"),
+        ],
+        junit_runs: vec![2],
+    };
+
+    let report = ingest_failures(&source, SHA, &FailureParsers::default())
+        .await
+        .unwrap();
+
+    assert_eq!(report.jobs.len(), 4);
+    assert_eq!(report.jobs[0].failures.len(), 2);
+    assert_eq!(report.jobs[1].failures.len(), 2);
+    // The log expired, but the run's JUnit report explains the job
+    assert_eq!(report.jobs[2].failures.len(), 3);
+    assert_eq!(report.jobs[2].error_line, None);
+    assert!(report.jobs[3].failures.is_empty());
+    assert_eq!(
+        report.jobs[3].error_line.as_deref(),
+        Some("Process completed with exit code 22.")
+    );
+
+    let comment = report.summary_comment();
+    assert!(comment.starts_with("🔴 **CI failed on `9f2c4e1`**: 4 job(s)"));
+    assert!(comment.contains(
+        "**[test (ubuntu)](https://github.com/acme/parser/actions/runs/1/job/11)**, step `Run tests`"
+    ));
+    assert!(comment.contains(
+        "- `lexer::tests::reads_numbers` at `src/lexer.rs:88`: assertion `left == right` failed"
+    ));
+    assert!(comment.contains("- `clippy::if_same_then_else` at `src/lexer.rs:57`"));
+    assert!(comment.contains("- Process completed with exit code 22."));
+    assert!(comment.contains(&format!("{CI_FAILURE_MARKER} {SHA} -->")));
+}
+
+#[test]
+fn long_failure_lists_are_cut_short_in_the_comment() {
+    let log: String = (0..14)
+        .map(|n| format!("test suite::case_{n} ... FAILED\n"))
+        .collect();
+    let failures = FailureParsers::default().parse(&log);
+    assert_eq!(failures.len(), 14);
+
+    let report = my_little_soda::ci::CiFailureReport {
+        head_sha: SHA.to_string(),
+        jobs: vec![my_little_soda::ci::JobFailures {
+            job: "test".to_string(),
+            step: None,
+            url: "https://example.invalid".to_string(),
+            failures,
+            error_line: None,
+        }],
+    };
+    let comment = report.summary_comment();
+    assert!(comment.contains("`suite::case_9`"));
+    assert!(!comment.contains("`suite::case_10`"));
+    assert!(comment.contains("- …and 4 more"));
+}
+
+#[cfg(feature = "autonomous")]
+mod recovery {
+    use super::*;
+    use my_little_soda::autonomous::error_recovery::{FixType, RecoveryStrategy};
+    use my_little_soda::autonomous::{AutonomousErrorRecovery, ErrorType};
+
+    async fn report_for(
+        jobs: Vec<(FailedJob, &'static str)>,
+    ) -> my_little_soda::ci::CiFailureReport {
+        let source = FixtureSource {
+            jobs,
+            junit_runs: Vec::new(),
+        };
+        ingest_failures(&source, SHA, &FailureParsers::default())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn failing_tests_ask_for_a_test_fix() {
+        let report = report_for(vec![(job(1, 1, "test", "Run tests"), "cargo_test.log")]).await;
+
+        let error = report.error_type().unwrap();
+        let ErrorType::TestFailure {
+            test_suite,
+            failed_tests,
+        } = &error
+        else {
+            panic!("expected a test failure, got {error:?}");
+        };
+        assert_eq!(test_suite, "src/lib.rs");
+        assert_eq!(
+            failed_tests,
+            &["lexer::tests::reads_numbers", "roundtrip_unicode"]
+        );
+        let workflow = report.workflow_failures();
+        assert_eq!(workflow.len(), 1);
+        assert!(workflow[0].auto_fixable);
+        assert_eq!(workflow[0].step, "Run tests");
+    }
+
+    #[tokio::test]
+    async fn cargo_test_logs_pick_the_test_failure_rule() {
+        let report = report_for(vec![(job(1, 1, "test", "Run tests"), "cargo_test.log")]).await;
+
+        let plan = report
+            .recovery_plan(&AutonomousErrorRecovery::without_client())
+            .unwrap();
+        assert_eq!(plan.rule, "tests-few");
+        assert!(matches!(
+            plan.first(),
+            RecoveryStrategy::AutomatedFix {
+                fix_type: FixType::TestFailureFix,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn build_breakage_outranks_failing_tests() {
+        let report = report_for(vec![
+            (job(1, 1, "test", "Run tests"), "cargo_test.log"),
+            (job(2, 1, "lint", "Clippy"), "clippy.log"),
+        ])
+        .await;
+
+        let Some(ErrorType::BuildFailure { stage, error }) = report.error_type() else {
+            panic!("expected a build failure");
+        };
+        assert_eq!(stage, "compile");
+        assert_eq!(
+            error,
+            "E0425: cannot find value `tokn` in this scope at src/parser.rs:142"
+        );
+    }
+
+    #[tokio::test]
+    async fn denied_lints_ask_for_formatting_fixes() {
+        let mut report = report_for(vec![(job(2, 1, "lint", "Clippy"), "clippy.log")]).await;
+        report.jobs[0]
+            .failures
+            .retain(|f| f.kind == FailureKind::Lint);
+
+        let error = report.error_type().unwrap();
+        assert!(matches!(&error, ErrorType::BuildFailure { stage, .. } if stage == "clippy"));
+        let plan = report
+            .recovery_plan(&AutonomousErrorRecovery::without_client())
+            .unwrap();
+        assert!(matches!(
+            plan.first(),
+            RecoveryStrategy::AutomatedFix {
+                fix_type: FixType::CodeFormatting,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn unparsed_jobs_are_reported_as_ci_failures() {
+        let report = report_for(vec![(job(3, 1, "deploy-preview", "Deploy"), "deploy.log")]).await;
+
+        let Some(ErrorType::CIFailure { job, step, error }) = report.error_type() else {
+            panic!("expected a CI failure");
+        };
+        assert_eq!(job, "deploy-preview");
+        assert_eq!(step, "Deploy");
+        assert_eq!(error, "Process completed with exit code 22.");
+        assert!(!report.workflow_failures()[0].auto_fixable);
+    }
+}
@@ -0,0 +1,151 @@
+//! Database schema and maintenance tests
+//!
+//! Covers moving the schema between versions, refusing a schema from a newer
+//! build, and the export and backup used by `db`. Only built with
+//! `--features database`.
+
+#![cfg(feature = "database")]
+
+use my_little_soda::cli::commands::db::{format_status, DbExportCommand, DbMigrateCommand};
+use my_little_soda::database::DatabaseManager;
+use tempfile::TempDir;
+
+fn url(dir: &TempDir) -> String {
+    format!("sqlite://{}", dir.path().join("state.db").display())
+}
+
+async fn tables(db: &DatabaseManager) -> Vec<String> {
+    db.tables().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_migrations_apply_revert_and_reapply() {
+    let dir = TempDir::new().unwrap();
+    let db = DatabaseManager::connect(&url(&dir)).await.unwrap();
+
+    let status = db.schema_status().await.unwrap();
+    assert_eq!(status.current_version, 0);
+    assert_eq!(status.pending().len() as i64, status.latest_known);
+    assert!(status.migrations.iter().all(|m| m.reversible));
+
+    let status = db.migrate(Some(2)).await.unwrap();
+    assert_eq!(status.current_version, 2);
+    assert!(tables(&db).await.contains(&"claims".to_string()));
+    assert!(!tables(&db).await.contains(&"routing_decisions".to_string()));
+
+    let status = db.migrate(None).await.unwrap();
+    assert_eq!(status.current_version, status.latest_known);
+    assert!(status.pending().is_empty());
+    for table in [
+        "agent_states",
+        "bundle_states",
+        "claims",
+        "integration_attempts",
+        "routing_decisions",
+        "bundle_steps",
+        "issue_snapshots",
+    ] {
+        assert!(tables(&db).await.contains(&table.to_string()), "{table}");
+    }
+
+    let status = db.migrate(Some(1)).await.unwrap();
+    assert_eq!(status.current_version, 1);
+    assert_eq!(tables(&db).await, vec!["agent_states", "bundle_states"]);
+
+    let status = db.migrate(Some(0)).await.unwrap();
+    assert_eq!(status.current_version, 0);
+    assert!(tables(&db).await.is_empty());
+
+    assert!(db.migrate(Some(status.latest_known + 1)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_newer_schema_is_refused() {
+    let dir = TempDir::new().unwrap();
+    let db = DatabaseManager::new(&url(&dir), true).await.unwrap();
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+         VALUES (999, 'from the future', 1, x'00', 0)",
+    )
+    .execute(db.pool())
+    .await
+    .unwrap();
+
+    let status = db.schema_status().await.unwrap();
+    assert!(status.is_newer());
+    assert_eq!(status.unknown_applied, vec![999]);
+    assert!(format_status(&status).contains("newer my-little-soda"));
+
+    let refused = DatabaseManager::new(&url(&dir), true)
+        .await
+        .err()
+        .expect("newer schema must be refused")
+        .to_string();
+    assert!(refused.contains("newer than this build"), "{refused}");
+    assert!(db.migrate(None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_dry_run_leaves_the_schema_alone() {
+    let dir = TempDir::new().unwrap();
+    let status = DbMigrateCommand::new(None, true)
+        .with_url(Some(url(&dir)))
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(status.current_version, 0);
+
+    let status = DbMigrateCommand::new(Some(3), false)
+        .with_url(Some(url(&dir)))
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(status.current_version, 3);
+    assert_eq!(status.pending().len() as i64, status.latest_known - 3);
+}
+
+#[tokio::test]
+async fn test_export_and_backup() {
+    let dir = TempDir::new().unwrap();
+    let db = DatabaseManager::new(&url(&dir), true).await.unwrap();
+    sqlx::query(
+        "INSERT INTO claims (agent_id, issue_number, branch, lease_expires_at) \
+         VALUES ('agent001', 7, 'agent001/7', '2030-01-01 00:00:00')",
+    )
+    .execute(db.pool())
+    .await
+    .unwrap();
+
+    let claims = DbExportCommand::new(Some("claims".to_string()), None)
+        .with_url(Some(url(&dir)))
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(claims[0]["agent_id"], "agent001");
+    assert_eq!(claims[0]["issue_number"], 7);
+    assert!(claims[0]["released_at"].is_null());
+
+    let everything = DbExportCommand::new(None, None)
+        .with_url(Some(url(&dir)))
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(everything["claims"].as_array().unwrap().len(), 1);
+    assert!(everything["issue_snapshots"].as_array().unwrap().is_empty());
+    assert!(everything.get("_sqlx_migrations").is_none());
+
+    // Table names are spliced into SQL, so unknown ones are rejected
+    assert!(db.export_table("claims; DROP TABLE claims").await.is_err());
+
+    let backup = dir.path().join("backups/copy.db");
+    db.backup(&backup).await.unwrap();
+    let copy = DatabaseManager::new(&format!("sqlite://{}", backup.display()), false)
+        .await
+        .unwrap();
+    assert_eq!(copy.export_table("claims").await.unwrap().len(), 1);
+    assert!(db.backup(&backup).await.is_err());
+
+    // Rebuilding may lay pages out differently, but never leaves any free
+    let (_, after) = db.vacuum().await.unwrap();
+    assert_eq!(after.free_bytes, 0);
+}
@@ -0,0 +1,186 @@
+//! Diff size guardrail tests
+//!
+//! Covers the limits, the per-directory breakdown, the split plan and the
+//! comments explaining a refusal.
+
+use my_little_soda::bundling::git_ops::{BranchChangelog, FileChangeStat};
+use my_little_soda::bundling::size_limits::{combined_files, SizeReport, SIZE_MARKER, SPLIT_LABEL};
+use my_little_soda::config::{DiffLimits, DiffLimitsConfig};
+
+fn file(path: &str, lines: usize) -> FileChangeStat {
+    FileChangeStat {
+        path: path.to_string(),
+        insertions: lines,
+        deletions: 0,
+        binary: false,
+    }
+}
+
+fn binary(path: &str) -> FileChangeStat {
+    FileChangeStat {
+        path: path.to_string(),
+        insertions: 0,
+        deletions: 0,
+        binary: true,
+    }
+}
+
+fn limits(lines: usize, files: usize, binaries: usize) -> DiffLimits {
+    DiffLimits {
+        max_changed_lines: lines,
+        max_changed_files: files,
+        max_binary_files: binaries,
+    }
+}
+
+#[test]
+fn test_each_limit_is_checked_and_zero_disables_it() {
+    let files = vec![
+        file("src/a.rs", 80),
+        file("src/b.rs", 40),
+        binary("assets/logo.png"),
+        binary("assets/icon.png"),
+    ];
+
+    let report = SizeReport::check("agent001/1", files.clone(), &limits(100, 3, 1));
+    let limits_hit: Vec<&str> = report.violations.iter().map(|v| v.limit.as_str()).collect();
+    assert_eq!(
+        limits_hit,
+        vec!["changed lines", "changed files", "binary files"]
+    );
+    assert_eq!(report.violations[0].actual, 120);
+    assert_eq!(report.violations[0].max, 100);
+
+    let unlimited = SizeReport::check("agent001/1", files, &limits(0, 0, 0));
+    assert!(!unlimited.is_oversized());
+}
+
+#[test]
+fn test_breakdown_is_by_top_level_directory_largest_first() {
+    let report = SizeReport::check(
+        "agent001/1",
+        vec![
+            file("docs/guide.md", 10),
+            file("src/cli/mod.rs", 300),
+            file("src/lib.rs", 20),
+            file("README.md", 5),
+        ],
+        &limits(100, 0, 0),
+    );
+
+    let breakdown: Vec<(String, usize, usize)> = report
+        .by_directory()
+        .into_iter()
+        .map(|s| (s.directory, s.files, s.lines))
+        .collect();
+    assert_eq!(
+        breakdown,
+        vec![
+            ("src".to_string(), 2, 320),
+            ("docs".to_string(), 1, 10),
+            ("(root)".to_string(), 1, 5),
+        ]
+    );
+}
+
+#[test]
+fn test_split_plan_descends_into_directories_that_are_still_too_big() {
+    let limits = limits(100, 0, 0);
+    let report = SizeReport::check(
+        "agent001/1",
+        vec![
+            file("src/bundling/bundler.rs", 90),
+            file("src/bundling/git_ops.rs", 60),
+            file("src/cli/land.rs", 70),
+            file("src/lib.rs", 5),
+            file("tests/bundle_tests.rs", 40),
+        ],
+        &limits,
+    );
+
+    let plan: Vec<(String, usize)> = report
+        .split_plan(&limits)
+        .into_iter()
+        .map(|g| (g.directory, g.files.len()))
+        .collect();
+    assert_eq!(
+        plan,
+        vec![
+            // Files directly under a split directory stay grouped under it
+            ("src".to_string(), 1),
+            ("src/bundling".to_string(), 2),
+            ("src/cli".to_string(), 1),
+            ("tests".to_string(), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_bundle_totals_sum_a_file_changed_by_several_branches() {
+    let first = BranchChangelog {
+        commits: Vec::new(),
+        files: vec![file("src/lib.rs", 10), file("src/a.rs", 5)],
+    };
+    let second = BranchChangelog {
+        commits: Vec::new(),
+        files: vec![file("src/lib.rs", 7), binary("logo.png")],
+    };
+
+    let files = combined_files([&first, &second]);
+    assert_eq!(files.len(), 3);
+    let lib = files.iter().find(|f| f.path == "src/lib.rs").unwrap();
+    assert_eq!(lib.insertions, 17);
+
+    let report = SizeReport::check("bundle", files, &limits(20, 0, 0));
+    assert_eq!(report.changed_lines(), 22);
+    assert!(report.is_oversized());
+}
+
+#[test]
+fn test_refusal_comment_and_split_issues() {
+    let limits = limits(50, 0, 0);
+    let report = SizeReport::check(
+        "agent002/9",
+        vec![file("src/a/x.rs", 40), file("src/b/y.rs", 40)],
+        &limits,
+    );
+
+    let comment = report.issue_comment(&limits);
+    assert!(comment.contains("`agent002/9` changes 2 file(s) and 80 line(s)"));
+    assert!(comment.contains("- 80 changed lines (limit 50)"));
+    assert!(comment.contains("| `src` | 2 | 80 |"));
+    assert!(comment.contains(SPLIT_LABEL));
+    assert!(comment.contains(&format!("{SIZE_MARKER} agent002/9 -->")));
+
+    let plan = report.split_plan(&limits);
+    assert_eq!(plan.len(), 2);
+    assert_eq!(
+        plan[0].issue_title(Some(9)),
+        "Split of #9: changes in src/a"
+    );
+    let body = plan[0].issue_body(Some(9), "agent002/9");
+    assert!(body.contains("- `src/a/x.rs`"));
+    assert!(body.contains("Related to #9"));
+
+    assert!(report.format_text(&limits).contains("Suggested split:"));
+}
+
+#[test]
+fn test_config_defaults() {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        #[serde(default)]
+        diff_limits: DiffLimitsConfig,
+    }
+
+    let wrapper: Wrapper = toml::from_str("").unwrap();
+    assert!(wrapper.diff_limits.enabled);
+    assert_eq!(wrapper.diff_limits.branch, DiffLimits::branch_defaults());
+    assert_eq!(wrapper.diff_limits.bundle, DiffLimits::bundle_defaults());
+
+    let parsed: Wrapper =
+        toml::from_str("[diff_limits.branch]\nmax_changed_lines = 200\n").unwrap();
+    assert_eq!(parsed.diff_limits.branch.max_changed_lines, 200);
+    // Limits left out of a section are off
+    assert_eq!(parsed.diff_limits.branch.max_changed_files, 0);
+}
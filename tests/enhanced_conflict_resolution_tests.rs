@@ -88,6 +88,7 @@ async fn test_enhanced_pr_description_generation() {
         branch_name: "agent001/123".to_string(),
         issue_number: 123,
         description: "Fix critical bug".to_string(),
+        depends_on: Vec::new(),
     };
 
     // The PR body generation is internal to BundleManager, so we just verify
@@ -0,0 +1,149 @@
+//! Scratch git repositories built commit by commit
+//!
+//! Shared by the bundling, pre-flight, secret-scanning, state and run loop
+//! tests, which pull this file in with `#[path = "fixtures/git_repos.rs"]`;
+//! each uses only some of it.
+#![allow(dead_code)]
+
+use git2::{Oid, Repository, Signature};
+use my_little_soda::bundling::git_ops::GitOperations;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Run the git CLI in `dir` as the test agent, returning its trimmed stdout
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Test Agent",
+            "-c",
+            "user.email=agent@example.com",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Commit `files` on top of `parent` as `author` and point `reference` at it
+///
+/// The reference is forced, so a branch can also be rewritten onto another parent.
+pub fn commit_ref_by(
+    repo: &Repository,
+    reference: &str,
+    parent: Option<Oid>,
+    files: &[(&str, &str)],
+    message: &str,
+    author: &str,
+) -> Oid {
+    let signature = Signature::now(author, "agent@example.com").unwrap();
+    let parent_commit = parent.map(|oid| repo.find_commit(oid).unwrap());
+    let parent_tree = parent_commit.as_ref().map(|c| c.tree().unwrap());
+    let mut builder = repo.treebuilder(parent_tree.as_ref()).unwrap();
+    for (path, content) in files {
+        builder
+            .insert(path, repo.blob(content.as_bytes()).unwrap(), 0o100644)
+            .unwrap();
+    }
+    let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+    let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
+    let oid = repo
+        .commit(None, &signature, &signature, message, &tree, &parents)
+        .unwrap();
+    repo.reference(reference, oid, true, message).unwrap();
+    oid
+}
+
+/// Commit `files` on top of `parent` and point `reference` at it
+pub fn commit_ref(
+    repo: &Repository,
+    reference: &str,
+    parent: Option<Oid>,
+    files: &[(&str, &str)],
+    message: &str,
+) -> Oid {
+    commit_ref_by(repo, reference, parent, files, message, "Test Agent")
+}
+
+/// Commit `path`, holding its own name, on top of `parent` and point `reference` at it
+pub fn commit_path(repo: &Repository, reference: &str, parent: Option<Oid>, path: &str) -> Oid {
+    commit_ref(repo, reference, parent, &[(path, path)], path)
+}
+
+/// Commit `files` on top of `parent` as `author`, moving `branch` to it
+pub fn commit_files_by(
+    repo: &Repository,
+    branch: &str,
+    parent: Option<Oid>,
+    files: &[(&str, &str)],
+    message: &str,
+    author: &str,
+) -> Oid {
+    let reference = format!("refs/heads/{branch}");
+    commit_ref_by(repo, &reference, parent, files, message, author)
+}
+
+/// Commit `files` on top of `parent`, moving `branch` to it
+pub fn commit_files(
+    repo: &Repository,
+    branch: &str,
+    parent: Option<Oid>,
+    files: &[(&str, &str)],
+    message: &str,
+) -> Oid {
+    commit_files_by(repo, branch, parent, files, message, "Test Agent")
+}
+
+/// Commit `path` with `content` on top of `parent`, moving `branch` to it
+pub fn commit_file(
+    repo: &Repository,
+    branch: &str,
+    parent: Option<Oid>,
+    path: &str,
+    content: &str,
+) -> Oid {
+    commit_files(repo, branch, parent, &[(path, content)], content)
+}
+
+/// Commit a reference points at
+pub fn tip(repo: &Repository, reference: &str) -> Oid {
+    repo.find_reference(reference).unwrap().target().unwrap()
+}
+
+/// agent001/1 adds one commit to `agent_path`; main then moves on by two
+///
+/// Returns the repository with `main` checked out, the agent commit and the
+/// main tip.
+pub fn diverged_repo(agent_path: &str) -> (TempDir, Oid, Oid) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let base = commit_file(&repo, "main", None, "README.md", "base\n");
+    let work = commit_file(&repo, "agent001/1", Some(base), agent_path, "agent\n");
+    let main = commit_file(&repo, "main", Some(base), "main.txt", "main 1\n");
+    let main = commit_file(&repo, "main", Some(main), "README.md", "main 2\n");
+    repo.set_head("refs/heads/main").unwrap();
+    (dir, work, main)
+}
+
+/// Repository whose history `seed` builds, with `main` checked out and
+/// `bundle/test` cut from it and checked out, ready to assemble a bundle on
+pub fn bundle_repo(seed: impl FnOnce(&Repository)) -> (TempDir, GitOperations) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    seed(&repo);
+    repo.set_head("refs/heads/main").unwrap();
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+
+    let mut git_ops = GitOperations::with_repository(repo);
+    git_ops.create_bundle_branch("bundle/test", "main").unwrap();
+    git_ops.checkout_branch("bundle/test").unwrap();
+    (dir, git_ops)
+}
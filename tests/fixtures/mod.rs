@@ -2,6 +2,7 @@
 use octocrab::models::issues::Issue;
 
 pub mod automated_validators;
+pub mod git_repos;
 pub mod init_integration;
 pub mod repository_states;
 pub mod test_harness;
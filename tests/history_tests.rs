@@ -0,0 +1,226 @@
+//! Lifecycle event log tests
+//!
+//! Covers recording events handed to the agent state machine, filtering them
+//! for `history` and the text, JSON and CSV output.
+
+use chrono::{Duration, TimeZone, Utc};
+use my_little_soda::agent_lifecycle::events::{
+    format_text, parse_since, to_csv, EventFilter, EventStore, LifecycleEvent,
+};
+use my_little_soda::agent_lifecycle::{AgentEvent, AgentStateMachine};
+use my_little_soda::cli::commands::history::HistoryCommand;
+use my_little_soda::cli::HistoryFormat;
+use statig::prelude::*;
+use tempfile::TempDir;
+
+fn store(dir: &TempDir) -> EventStore {
+    EventStore::new(dir.path().join("logs/events.jsonl"))
+}
+
+fn assign(agent: &str, issue: u64) -> AgentEvent {
+    AgentEvent::Assign {
+        agent_id: agent.to_string(),
+        issue,
+        branch: format!("{agent}/{issue}"),
+    }
+}
+
+/// Run one agent through a full cycle on `issue`
+fn work_on(store: &EventStore, agent: &str, issue: u64) {
+    let mut machine = AgentStateMachine::new(agent.to_string()).state_machine();
+    for event in [
+        assign(agent, issue),
+        AgentEvent::StartWork { commits_ahead: 2 },
+        AgentEvent::CompleteWork,
+        AgentEvent::Abandon,
+    ] {
+        store.dispatch(&mut machine, &event, Some("corr-1"));
+    }
+}
+
+#[test]
+fn test_every_event_is_recorded_with_its_states() {
+    let dir = TempDir::new().unwrap();
+    let store = store(&dir);
+    work_on(&store, "agent001", 412);
+
+    let events = store.query(&EventFilter::default()).unwrap();
+    let states: Vec<(&str, &str, &str)> = events
+        .iter()
+        .map(|e| (e.event.kind(), e.from_state.as_str(), e.to_state.as_str()))
+        .collect();
+    assert_eq!(
+        states,
+        vec![
+            ("assign", "idle", "assigned"),
+            ("start_work", "assigned", "working"),
+            ("complete_work", "working", "landed"),
+            ("abandon", "landed", "idle"),
+        ]
+    );
+    // Resetting clears the machine, but the event still names what was dropped
+    assert_eq!(events[3].issue, Some(412));
+    assert_eq!(events[3].branch.as_deref(), Some("agent001/412"));
+    assert!(events
+        .iter()
+        .all(|e| e.correlation_id.as_deref() == Some("corr-1") && !e.host.is_empty()));
+}
+
+#[test]
+fn test_ignored_events_are_recorded_without_a_transition() {
+    let dir = TempDir::new().unwrap();
+    let store = store(&dir);
+    let mut machine = AgentStateMachine::new("agent001".to_string()).state_machine();
+
+    // Completing work the machine never saw assigned is ignored, but not silently
+    let recorded = store.dispatch(&mut machine, &AgentEvent::CompleteWork, None);
+    assert_eq!(recorded.from_state, "idle");
+    assert!(!recorded.transitioned());
+    assert!(format_text(&[recorded]).contains("idle (unchanged)"));
+}
+
+#[test]
+fn test_filters_by_agent_issue_and_time() {
+    let dir = TempDir::new().unwrap();
+    let store = store(&dir);
+    work_on(&store, "agent001", 412);
+    work_on(&store, "agent002", 7);
+
+    let for_issue = store
+        .query(&EventFilter {
+            issue: Some(412),
+            ..EventFilter::default()
+        })
+        .unwrap();
+    assert_eq!(for_issue.len(), 4);
+    assert!(for_issue.iter().all(|e| e.agent_id == "agent001"));
+
+    let for_agent = store
+        .query(&EventFilter {
+            agent: Some("agent002".to_string()),
+            ..EventFilter::default()
+        })
+        .unwrap();
+    assert_eq!(for_agent.len(), 4);
+
+    let future = store
+        .query(&EventFilter {
+            since: Some(Utc::now() + Duration::hours(1)),
+            ..EventFilter::default()
+        })
+        .unwrap();
+    assert!(future.is_empty());
+}
+
+#[test]
+fn test_bundle_events_match_every_bundled_issue() {
+    let event = LifecycleEvent {
+        timestamp: Utc::now(),
+        agent_id: "agent001".to_string(),
+        event: AgentEvent::Bundle {
+            bundle_pr: 90,
+            issues: vec![412, 413],
+        },
+        from_state: "landed".to_string(),
+        to_state: "bundled".to_string(),
+        issue: Some(412),
+        branch: Some("agent001/412".to_string()),
+        correlation_id: None,
+        host: "build-1".to_string(),
+    };
+    let filter = |issue| EventFilter {
+        issue: Some(issue),
+        ..EventFilter::default()
+    };
+    assert!(filter(413).matches(&event));
+    assert!(!filter(414).matches(&event));
+}
+
+#[test]
+fn test_unreadable_lines_are_skipped_and_missing_log_is_empty() {
+    let dir = TempDir::new().unwrap();
+    let store = store(&dir);
+    assert!(store.query(&EventFilter::default()).unwrap().is_empty());
+
+    work_on(&store, "agent001", 1);
+    let path = dir.path().join("logs/events.jsonl");
+    let mut contents = std::fs::read_to_string(&path).unwrap();
+    contents.push_str("{\"timestamp\": \"2024-\n");
+    std::fs::write(&path, contents).unwrap();
+    work_on(&store, "agent001", 2);
+
+    assert_eq!(store.query(&EventFilter::default()).unwrap().len(), 8);
+}
+
+#[test]
+fn test_since_accepts_ages_dates_and_times() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap();
+    assert_eq!(
+        parse_since("30m", now).unwrap(),
+        now - Duration::minutes(30)
+    );
+    assert_eq!(parse_since("12h", now).unwrap(), now - Duration::hours(12));
+    assert_eq!(parse_since("1d", now).unwrap(), now - Duration::days(1));
+    assert_eq!(
+        parse_since("2024-05-01", now).unwrap(),
+        Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_since("2024-05-01T08:30:00+02:00", now).unwrap(),
+        Utc.with_ymd_and_hms(2024, 5, 1, 6, 30, 0).unwrap()
+    );
+    for invalid in ["", "yesterday", "5x", "h", "5é"] {
+        assert!(parse_since(invalid, now).is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_output_formats() {
+    let dir = TempDir::new().unwrap();
+    let store = store(&dir);
+    let mut machine = AgentStateMachine::new("agent001".to_string()).state_machine();
+    store.dispatch(
+        &mut machine,
+        &AgentEvent::Assign {
+            agent_id: "agent001".to_string(),
+            issue: 412,
+            branch: "agent001/412-fix,\"quoted\"".to_string(),
+        },
+        Some("corr-9"),
+    );
+
+    let command = HistoryCommand {
+        store: store.clone(),
+        ..HistoryCommand::new(HistoryFormat::Csv)
+            .with_issue(Some(412))
+            .with_since(Some("1d".to_string()))
+    };
+    let events = command.run().unwrap();
+    assert_eq!(events.len(), 1);
+
+    let csv = command.render(&events).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "timestamp,agent_id,event,from_state,to_state,issue,branch,correlation_id,host"
+    );
+    assert!(lines[1].contains(
+        ",agent001,assign,idle,assigned,412,\"agent001/412-fix,\"\"quoted\"\"\",corr-9,"
+    ));
+
+    let json = HistoryCommand::new(HistoryFormat::Json)
+        .render(&events)
+        .unwrap();
+    let restored: Vec<LifecycleEvent> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, events);
+
+    let text = HistoryCommand::new(HistoryFormat::Text)
+        .render(&events)
+        .unwrap();
+    assert!(text.contains("agent001  assign"));
+    assert!(text.contains("idle → assigned"));
+    assert!(text.contains(" #412 "));
+
+    assert_eq!(to_csv(&[]).lines().count(), 1);
+    assert_eq!(format_text(&[]), "No lifecycle events recorded\n");
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "testing")]
+
 use mockall::predicate::*;
 /// Tests for init command authentication edge cases and diagnostics
 ///
@@ -0,0 +1,239 @@
+//! Merge phase tests
+//!
+//! Covers classifying bundle and agent PRs, finding the issues they close,
+//! deciding when a PR is ready to land and which merge method it gets.
+
+use my_little_soda::agents::integrator::{
+    closing_references, labels_to_strip, pr_issues, MergeCandidate, MergeMethod, PrKind, Readiness,
+};
+use my_little_soda::config::MergeConfig;
+use my_little_soda::github::pulls::CiStatus;
+
+fn labels(names: &[&str]) -> Vec<String> {
+    names.iter().map(|l| l.to_string()).collect()
+}
+
+fn candidate() -> MergeCandidate {
+    MergeCandidate {
+        pr_number: 7,
+        title: "Fix parser".to_string(),
+        head_branch: "agent001/12-fix-parser".to_string(),
+        head_sha: "0123456789abcdef0123456789abcdef01234567".to_string(),
+        kind: PrKind::Agent {
+            agent_id: "agent001".to_string(),
+            issue_number: 12,
+        },
+        draft: false,
+        mergeable: Some(true),
+        approvals: 1,
+        changes_requested: 0,
+        ci: CiStatus::Success,
+        labels: Vec::new(),
+        issues: pr_issues(
+            &PrKind::Agent {
+                agent_id: "agent001".to_string(),
+                issue_number: 12,
+            },
+            "",
+        ),
+    }
+}
+
+#[test]
+fn test_pr_kind_from_branch() {
+    assert_eq!(
+        PrKind::from_branch("bundle/20250101_1000__issues_1_2"),
+        Some(PrKind::Bundle)
+    );
+    assert_eq!(
+        PrKind::from_branch("agent001/12-fix-parser"),
+        Some(PrKind::Agent {
+            agent_id: "agent001".to_string(),
+            issue_number: 12
+        })
+    );
+    assert_eq!(
+        PrKind::from_branch("agent002/40"),
+        Some(PrKind::Agent {
+            agent_id: "agent002".to_string(),
+            issue_number: 40
+        })
+    );
+    assert_eq!(PrKind::from_branch("feature/login"), None);
+    assert_eq!(PrKind::from_branch("agent001/not-a-number"), None);
+    assert_eq!(PrKind::from_branch("agents/12"), None);
+}
+
+#[test]
+fn test_closing_references() {
+    let body = "Closes #3\nfixes: #4 and resolved #3\nSee #99\nFIXES #5";
+    assert_eq!(closing_references(body), vec![3, 4, 5]);
+    assert!(closing_references("Mentions #3 only").is_empty());
+}
+
+#[test]
+fn test_pr_issues_merges_agent_branch_with_closing_references() {
+    let kind = PrKind::Agent {
+        agent_id: "agent001".to_string(),
+        issue_number: 12,
+    };
+    let issues = pr_issues(&kind, "Fixes #12\nAlso closes #15");
+
+    assert_eq!(
+        issues.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+        vec![12, 15]
+    );
+    assert!(issues.iter().all(|i| i.agent_id == "agent001"));
+}
+
+#[test]
+fn test_bundle_without_manifest_falls_back_to_closing_references() {
+    let issues = pr_issues(&PrKind::Bundle, "Closes #1\nCloses #2");
+    assert_eq!(
+        issues.iter().map(|i| i.issue_number).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(issues[0].agent_id, "unknown");
+}
+
+#[test]
+fn test_labels_to_strip_keeps_unrelated_labels() {
+    let stripped = labels_to_strip(&labels(&[
+        "route:review",
+        "agent003",
+        "bug",
+        "route:priority-high",
+        "agents-docs",
+    ]));
+    assert_eq!(
+        stripped,
+        labels(&["route:review", "agent003", "route:priority-high"])
+    );
+}
+
+#[test]
+fn test_readiness_waits_for_reviews_and_checks() {
+    let config = MergeConfig::default();
+    assert_eq!(candidate().readiness(&config), Readiness::Ready);
+
+    let waiting = |change: fn(&mut MergeCandidate), config: &MergeConfig| {
+        let mut pr = candidate();
+        change(&mut pr);
+        matches!(pr.readiness(config), Readiness::Waiting(_))
+    };
+
+    assert!(waiting(|pr| pr.draft = true, &config));
+    assert!(waiting(|pr| pr.approvals = 0, &config));
+    assert!(waiting(|pr| pr.changes_requested = 1, &config));
+    assert!(waiting(|pr| pr.ci = CiStatus::Pending, &config));
+    assert!(waiting(|pr| pr.ci = CiStatus::Failure, &config));
+    assert!(waiting(|pr| pr.ci = CiStatus::NoChecks, &config));
+    assert!(waiting(|pr| pr.mergeable = Some(false), &config));
+    assert!(waiting(|pr| pr.mergeable = None, &config));
+
+    let relaxed = MergeConfig {
+        required_approvals: 0,
+        allow_no_checks: true,
+        ..MergeConfig::default()
+    };
+    assert!(!waiting(
+        |pr| {
+            pr.approvals = 0;
+            pr.ci = CiStatus::NoChecks;
+        },
+        &relaxed
+    ));
+}
+
+#[test]
+fn test_merge_method_prefers_pr_labels_then_issue_labels() {
+    let mut config = MergeConfig::default();
+    config
+        .label_methods
+        .insert("merge:rebase".to_string(), MergeMethod::Rebase);
+    config
+        .label_methods
+        .insert("route:priority-high".to_string(), MergeMethod::Merge);
+
+    let mut pr = candidate();
+    assert_eq!(pr.merge_method(&config), MergeMethod::Squash);
+
+    pr.issues[0].labels = labels(&["route:priority-high"]);
+    assert_eq!(pr.merge_method(&config), MergeMethod::Merge);
+
+    pr.labels = labels(&["merge:rebase"]);
+    assert_eq!(pr.merge_method(&config), MergeMethod::Rebase);
+}
+
+#[test]
+fn test_branches_to_delete_include_bundled_agent_branches() {
+    let mut pr = candidate();
+    pr.head_branch = "bundle/x".to_string();
+    pr.kind = PrKind::Bundle;
+    pr.issues[0].branch_name = Some("agent001/12".to_string());
+
+    assert_eq!(
+        pr.branches_to_delete(),
+        vec!["bundle/x".to_string(), "agent001/12".to_string()]
+    );
+    assert_eq!(
+        candidate().branches_to_delete(),
+        vec![candidate().head_branch]
+    );
+}
+
+#[test]
+fn test_ci_status_combination() {
+    assert_eq!(
+        CiStatus::from_check_run(true, Some("success")),
+        CiStatus::Success
+    );
+    assert_eq!(
+        CiStatus::from_check_run(true, Some("skipped")),
+        CiStatus::Success
+    );
+    assert_eq!(
+        CiStatus::from_check_run(true, Some("timed_out")),
+        CiStatus::Failure
+    );
+    assert_eq!(CiStatus::from_check_run(false, None), CiStatus::Pending);
+
+    assert_eq!(CiStatus::combine([]), CiStatus::NoChecks);
+    assert_eq!(
+        CiStatus::combine([CiStatus::Success, CiStatus::Pending]),
+        CiStatus::Pending
+    );
+    assert_eq!(
+        CiStatus::combine([CiStatus::Pending, CiStatus::Failure, CiStatus::Success]),
+        CiStatus::Failure
+    );
+    assert_eq!(
+        CiStatus::combine([CiStatus::Success, CiStatus::Success]),
+        CiStatus::Success
+    );
+}
+
+#[test]
+fn test_merge_config_defaults_when_section_missing() {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        #[serde(default)]
+        merge: MergeConfig,
+    }
+
+    let wrapper: Wrapper = toml::from_str("").unwrap();
+    assert_eq!(wrapper.merge.default_method, MergeMethod::Squash);
+    assert_eq!(wrapper.merge.required_approvals, 1);
+    assert!(!wrapper.merge.allow_no_checks);
+    assert!(wrapper.merge.delete_branches);
+
+    let parsed: Wrapper = toml::from_str(
+        "[merge]\ndefault_method = \"rebase\"\n[merge.label_methods]\n\"route:priority-high\" = \"merge\"\n",
+    )
+    .unwrap();
+    assert_eq!(parsed.merge.default_method, MergeMethod::Rebase);
+    assert_eq!(
+        parsed.merge.label_methods.get("route:priority-high"),
+        Some(&MergeMethod::Merge)
+    );
+}
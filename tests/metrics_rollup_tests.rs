@@ -0,0 +1,333 @@
+//! Database metrics storage tests
+//!
+//! Covers raw records and their hourly and daily rollups, answering long
+//! windows from rollups and retention. Only built with
+//! `--features metrics,database`.
+
+#![cfg(all(feature = "metrics", feature = "database"))]
+
+use my_little_soda::config::MetricsConfig;
+use my_little_soda::database::DatabaseManager;
+use my_little_soda::metrics::sqlite::{RollupGranularity, SqliteMetricsStore};
+use my_little_soda::metrics::storage::MetricsStorage;
+use my_little_soda::metrics::{
+    AgentUtilizationMetrics, IntegrationAttempt, IntegrationOutcome, IntegrationPhase, MetricKind,
+    MetricSummary, MetricsTracker, RoutingDecision, RoutingMetrics,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+async fn database(dir: &TempDir) -> DatabaseManager {
+    let url = format!("sqlite://{}", dir.path().join("state.db").display());
+    DatabaseManager::new(&url, true).await.unwrap()
+}
+
+fn attempt(agent: &str, at: u64, outcome: IntegrationOutcome, duration: u64) -> IntegrationAttempt {
+    IntegrationAttempt {
+        correlation_id: format!("corr-{at}"),
+        issue_number: 7,
+        agent_id: agent.to_string(),
+        attempt_time: at,
+        phase: IntegrationPhase::Merged,
+        outcome,
+        duration_seconds: Some(duration),
+        error_message: None,
+        pr_number: Some(90),
+        quality_gates: None,
+    }
+}
+
+fn routing(at: u64, duration_ms: u64, assigned: bool) -> RoutingMetrics {
+    RoutingMetrics {
+        correlation_id: format!("route-{at}"),
+        routing_start_time: at,
+        routing_duration_ms: duration_ms,
+        issues_evaluated: 3,
+        agents_available: 1,
+        decision_outcome: if assigned {
+            RoutingDecision::TaskAssigned {
+                issue_number: 7,
+                agent_id: "agent001".to_string(),
+            }
+        } else {
+            RoutingDecision::FilteredOut {
+                reason: "blocked".to_string(),
+            }
+        },
+        bottlenecks_detected: vec!["slow".to_string()],
+    }
+}
+
+#[test]
+fn test_summaries_merge_like_recording_every_sample() {
+    let mut first = MetricSummary::default();
+    first.record(10, Some(true), Some(100.0));
+    first.record(20, Some(false), None);
+    let mut second = MetricSummary::default();
+    second.record(30, None, Some(40.0));
+
+    let mut all = MetricSummary::default();
+    all.record(10, Some(true), Some(100.0));
+    all.record(20, Some(false), None);
+    all.record(30, None, Some(40.0));
+
+    first.merge(&second);
+    assert_eq!(first, all);
+    assert_eq!(all.samples, 3);
+    assert_eq!(all.average(), Some(70.0));
+    assert_eq!(all.success_average(), Some(100.0));
+    assert_eq!(all.value_max, Some(100.0));
+    assert_eq!(all.last_seen, Some(30));
+    assert!((all.success_rate() - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(MetricSummary::default().success_rate(), 0.0);
+}
+
+#[tokio::test]
+async fn test_records_round_trip_and_roll_up_per_hour_and_day() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let store = SqliteMetricsStore::new(db.pool().clone(), MetricsConfig::default());
+    let hour = RollupGranularity::Hour.bucket_start(now()) - 2 * HOUR;
+
+    store
+        .insert_routing(&routing(hour + 10, 300, true))
+        .await
+        .unwrap();
+    store
+        .insert_routing(&routing(hour + 20, 900, false))
+        .await
+        .unwrap();
+    store
+        .insert_routing(&routing(hour + HOUR, 600, true))
+        .await
+        .unwrap();
+
+    let raw = store.load_routing(hour).await.unwrap();
+    assert_eq!(raw.len(), 3);
+    assert!(matches!(
+        &raw[1].decision_outcome,
+        RoutingDecision::FilteredOut { reason } if reason == "blocked"
+    ));
+    assert_eq!(raw[0].routing_start_time, hour + 10);
+    assert_eq!(raw[0].bottlenecks_detected, vec!["slow".to_string()]);
+
+    // Two hourly buckets, each only holding its own records
+    let last_hour = store
+        .load_rollups(MetricKind::Routing, RollupGranularity::Hour, hour + HOUR)
+        .await
+        .unwrap();
+    assert_eq!(last_hour[""].samples, 1);
+
+    let hourly = store
+        .load_rollups(MetricKind::Routing, RollupGranularity::Hour, hour)
+        .await
+        .unwrap();
+    let daily = store
+        .load_rollups(MetricKind::Routing, RollupGranularity::Day, hour)
+        .await
+        .unwrap();
+    let mut from_raw = MetricSummary::default();
+    for record in &raw {
+        record.summarize(&mut from_raw);
+    }
+    assert_eq!(hourly[""], from_raw);
+    assert_eq!(daily[""].samples, 3);
+    assert_eq!(hourly[""].successes, 2);
+    assert_eq!(hourly[""].value_max, Some(900.0));
+    assert_eq!(hourly[""].average(), Some(600.0));
+
+    store
+        .insert_utilization(&AgentUtilizationMetrics {
+            agent_id: "agent001".to_string(),
+            timestamp: hour,
+            current_capacity: 1,
+            max_capacity: 2,
+            utilization_percentage: 50.0,
+            active_issues: vec![7],
+            state: "Working".to_string(),
+        })
+        .await
+        .unwrap();
+    let utilization = store
+        .load_rollups(MetricKind::Utilization, RollupGranularity::Hour, hour)
+        .await
+        .unwrap();
+    assert_eq!(utilization["agent001"].average(), Some(50.0));
+    assert_eq!(
+        store.load_utilization(0).await.unwrap()[0].active_issues,
+        vec![7]
+    );
+}
+
+#[tokio::test]
+async fn test_long_windows_are_answered_from_rollups() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let config = MetricsConfig {
+        raw_retention_days: 3,
+        ..MetricsConfig::default()
+    };
+    let storage = MetricsStorage::with_database(db.pool().clone(), config.clone());
+    let now = now();
+
+    for record in [
+        attempt("agent001", now - 10 * DAY, IntegrationOutcome::Success, 600),
+        attempt("agent001", now - 9 * DAY, IntegrationOutcome::Failed, 60),
+        attempt("agent002", now - HOUR, IntegrationOutcome::Success, 1200),
+    ] {
+        storage.store_integration_attempt(record).await.unwrap();
+    }
+
+    // Inserts may already have pruned, so only the outcome is certain
+    let store = SqliteMetricsStore::new(db.pool().clone(), config);
+    store.apply_retention(now).await.unwrap();
+    assert_eq!(store.load_integration(0).await.unwrap().len(), 1);
+
+    let tracker = MetricsTracker::with_storage(storage.clone());
+
+    // A day is short enough for raw records, which only hold the recent attempt
+    assert_eq!(
+        tracker.describe_source(Some(24)).await,
+        "database, raw records"
+    );
+    let day = tracker.calculate_metrics(Some(24)).await.unwrap();
+    assert_eq!(day.total_attempts, 1);
+
+    // Two weeks come from hourly rollups and still see the pruned attempts
+    assert_eq!(
+        tracker.describe_source(Some(14 * 24)).await,
+        "database, hour rollups"
+    );
+    let fortnight = tracker.calculate_metrics(Some(14 * 24)).await.unwrap();
+    assert_eq!(fortnight.total_attempts, 3);
+    assert_eq!(fortnight.successful_integrations, 2);
+    assert_eq!(fortnight.failed_integrations, 1);
+    assert_eq!(fortnight.average_time_to_merge_seconds, Some(900.0));
+    let agent001 = &fortnight.agent_metrics["agent001"];
+    assert_eq!(agent001.total_attempts, 2);
+    assert_eq!(agent001.average_completion_time_seconds, Some(330.0));
+    assert_eq!(agent001.last_activity, Some(now - 9 * DAY));
+    // Recent attempts can only list what is still kept raw
+    assert_eq!(fortnight.recent_attempts.len(), 1);
+
+    assert_eq!(
+        tracker.describe_source(Some(365 * 24)).await,
+        "database, day rollups"
+    );
+    assert_eq!(
+        tracker
+            .calculate_metrics(Some(365 * 24))
+            .await
+            .unwrap()
+            .total_attempts,
+        3
+    );
+}
+
+#[tokio::test]
+async fn test_export_reads_rollups_for_long_windows() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let storage = MetricsStorage::with_database(db.pool().clone(), MetricsConfig::default());
+    let now = now();
+    storage
+        .store_routing_metrics(routing(now - 5 * DAY, 1000, true))
+        .await
+        .unwrap();
+    storage
+        .store_routing_metrics(routing(now - HOUR, 3000, false))
+        .await
+        .unwrap();
+
+    let tracker = MetricsTracker::with_storage(storage);
+    let recent = tracker
+        .export_metrics_for_monitoring(Some(24))
+        .await
+        .unwrap();
+    assert_eq!(recent["routing_avg_duration_ms"], 3000.0);
+    assert_eq!(recent["routing_success_rate"], 0.0);
+    assert!(!recent.contains_key("agent_avg_utilization_percentage"));
+
+    let week = tracker
+        .export_metrics_for_monitoring(Some(7 * 24))
+        .await
+        .unwrap();
+    assert_eq!(week["routing_avg_duration_ms"], 2000.0);
+    assert_eq!(week["routing_success_rate"], 0.5);
+}
+
+#[tokio::test]
+async fn test_retention_keeps_rollups_longer_than_raw_records() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let store = SqliteMetricsStore::new(
+        db.pool().clone(),
+        MetricsConfig {
+            raw_retention_days: 7,
+            hourly_retention_days: 30,
+            daily_retention_days: 0,
+            rollup_after_hours: 48,
+        },
+    );
+    let now = now();
+    for age_days in [1, 10, 40, 400] {
+        store
+            .insert_integration(&attempt(
+                "agent001",
+                now - age_days * DAY,
+                IntegrationOutcome::Success,
+                60,
+            ))
+            .await
+            .unwrap();
+    }
+
+    let report = store.apply_retention(now).await.unwrap();
+    assert_eq!(report.raw_records, 3);
+    assert_eq!(report.hourly_rollups, 2);
+    // 0 days keeps daily rollups forever
+    assert_eq!(report.daily_rollups, 0);
+    let daily = store
+        .load_rollups(MetricKind::Integration, RollupGranularity::Day, 0)
+        .await
+        .unwrap();
+    assert_eq!(daily["agent001"].samples, 4);
+
+    assert_eq!(store.granularity_for(Some(48)), None);
+    assert_eq!(
+        store.granularity_for(Some(30 * 24)),
+        Some(RollupGranularity::Hour)
+    );
+    assert_eq!(
+        store.granularity_for(Some(31 * 24)),
+        Some(RollupGranularity::Day)
+    );
+    assert_eq!(store.granularity_for(None), Some(RollupGranularity::Day));
+}
+
+#[test]
+fn test_config_section() {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        #[serde(default)]
+        metrics: MetricsConfig,
+    }
+
+    let wrapper: Wrapper = toml::from_str("").unwrap();
+    assert_eq!(wrapper.metrics, MetricsConfig::default());
+    assert_eq!(wrapper.metrics.rollup_after_hours, 48);
+
+    let parsed: Wrapper = toml::from_str("[metrics]\nraw_retention_days = 3\n").unwrap();
+    assert_eq!(parsed.metrics.raw_retention_days, 3);
+    assert_eq!(parsed.metrics.hourly_retention_days, 90);
+}
@@ -0,0 +1,195 @@
+//! Pre-flight check tests
+//!
+//! Covers detecting each `PreFlightIssue` from git state, the fixes offered
+//! for them and which findings block bottling.
+
+use git2::Repository;
+use my_little_soda::agent_lifecycle::detector::{
+    detect_label_mismatch, PreflightDetector, PreflightReport,
+};
+use my_little_soda::agent_lifecycle::executor::PreflightExecutor;
+use my_little_soda::agent_lifecycle::types::{PreFlightFix, PreFlightSeverity};
+use my_little_soda::PreFlightIssue;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::{commit_file, diverged_repo};
+
+#[test]
+fn test_missing_branch_is_the_only_finding() {
+    let (dir, _, _) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+
+    let issues = PreflightDetector::new(&repo).detect("agent001/9").unwrap();
+
+    assert_eq!(
+        issues,
+        vec![PreFlightIssue::BranchMissing {
+            branch: "agent001/9".to_string()
+        }]
+    );
+}
+
+#[test]
+fn test_never_pushed_branch_reports_its_work_as_unpushed() {
+    let (dir, _, _) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+
+    let issues = PreflightDetector::new(&repo).detect("agent001/1").unwrap();
+
+    assert_eq!(
+        issues,
+        vec![
+            PreFlightIssue::UnpushedCommits { count: 1 },
+            PreFlightIssue::BehindMain { commits: 2 },
+        ]
+    );
+}
+
+#[test]
+fn test_unpushed_commits_are_counted_against_the_remote_tracking_ref() {
+    let (dir, work, _) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+    let main = repo.refname_to_id("refs/heads/main").unwrap();
+    repo.reference("refs/remotes/origin/main", main, true, "fetch")
+        .unwrap();
+    repo.reference("refs/remotes/origin/agent001/1", work, true, "fetch")
+        .unwrap();
+
+    let detector = PreflightDetector::new(&repo);
+    assert_eq!(
+        detector.detect("agent001/1").unwrap(),
+        vec![PreFlightIssue::BehindMain { commits: 2 }]
+    );
+
+    commit_file(&repo, "agent001/1", Some(work), "more.txt", "more\n");
+    assert!(detector
+        .detect("agent001/1")
+        .unwrap()
+        .contains(&PreFlightIssue::UnpushedCommits { count: 1 }));
+}
+
+#[test]
+fn test_conflicts_with_the_base_are_detected_without_checking_out() {
+    let (dir, _, _) = diverged_repo("README.md");
+    let repo = Repository::open(dir.path()).unwrap();
+
+    let issues = PreflightDetector::new(&repo).detect("agent001/1").unwrap();
+
+    assert!(issues.contains(&PreFlightIssue::MergeConflicts {
+        files: vec!["README.md".to_string()]
+    }));
+}
+
+#[test]
+fn test_label_mismatch_only_when_the_agent_label_is_gone() {
+    let labels = vec!["route:ready".to_string(), "agent001".to_string()];
+    assert_eq!(detect_label_mismatch("agent001", &labels), None);
+
+    let labels = vec!["route:ready".to_string()];
+    let issue = detect_label_mismatch("agent001", &labels).unwrap();
+    assert_eq!(
+        issue.fix(),
+        Some(PreFlightFix::Relabel {
+            add: vec!["agent001".to_string()]
+        })
+    );
+}
+
+#[test]
+fn test_severities_and_offered_fixes() {
+    let unpushed = PreFlightIssue::UnpushedCommits { count: 2 };
+    assert_eq!(unpushed.severity(), PreFlightSeverity::Warning);
+    assert_eq!(unpushed.fix(), Some(PreFlightFix::Push));
+
+    let behind = PreFlightIssue::BehindMain { commits: 3 };
+    assert_eq!(behind.severity(), PreFlightSeverity::Warning);
+    assert_eq!(behind.fix(), Some(PreFlightFix::Rebase));
+
+    let conflicts = PreFlightIssue::MergeConflicts {
+        files: vec!["src/lib.rs".to_string()],
+    };
+    assert_eq!(conflicts.severity(), PreFlightSeverity::Error);
+    assert_eq!(conflicts.fix(), None);
+
+    let json = serde_json::to_value(&behind).unwrap();
+    assert_eq!(json["kind"], "behind_main");
+    assert_eq!(json["commits"], 3);
+}
+
+#[test]
+fn test_rebase_is_not_offered_while_conflicted() {
+    let report = PreflightReport::new(
+        "agent001/1",
+        "agent001",
+        1,
+        vec![
+            PreFlightIssue::BehindMain { commits: 2 },
+            PreFlightIssue::MergeConflicts {
+                files: vec!["README.md".to_string()],
+            },
+        ],
+    );
+
+    assert_eq!(report.findings[0].fix, None);
+    assert_eq!(report.blocking().len(), 1);
+    let text = report.format_text();
+    assert!(text.contains("❌"));
+    assert!(!text.contains("fixable"));
+}
+
+#[test]
+fn test_warnings_do_not_block() {
+    let report = PreflightReport::new(
+        "agent001/1",
+        "agent001",
+        1,
+        vec![PreFlightIssue::UnpushedCommits { count: 1 }],
+    );
+    assert!(report.blocking().is_empty());
+    assert!(!report.is_clean());
+    assert!(report.format_text().contains("fixable: push"));
+
+    let clean = PreflightReport::new("agent001/1", "agent001", 1, Vec::new());
+    assert!(clean.is_clean());
+    assert!(clean.format_text().contains("All checks passed"));
+}
+
+#[test]
+fn test_git_fixes_rebase_and_push_the_branch() {
+    let remote_dir = TempDir::new().unwrap();
+    Repository::init_bare(remote_dir.path()).unwrap();
+
+    let (dir, _, _) = diverged_repo("agent.txt");
+    let repo = Repository::open(dir.path()).unwrap();
+    repo.remote("origin", remote_dir.path().to_str().unwrap())
+        .unwrap();
+    // The rebase needs a worktree that matches HEAD
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    for (key, value) in [
+        ("user.name", "Test Agent"),
+        ("user.email", "agent@example.com"),
+    ] {
+        Command::new("git")
+            .args(["config", key, value])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+    }
+
+    let issues = PreflightDetector::new(&repo).detect("agent001/1").unwrap();
+    let mut report = PreflightReport::new("agent001/1", "agent001", 1, issues);
+    PreflightExecutor::new(dir.path()).apply_git_fixes(&mut report);
+
+    assert!(report.is_clean(), "{}", report.format_text());
+    let remote = Repository::open_bare(remote_dir.path()).unwrap();
+    let pushed = remote.refname_to_id("refs/heads/agent001/1").unwrap();
+    let main = repo.refname_to_id("refs/heads/main").unwrap();
+    assert_eq!(
+        remote.find_commit(pushed).unwrap().parent_id(0).unwrap(),
+        main
+    );
+}
@@ -0,0 +1,218 @@
+//! Protected path policy tests
+//!
+//! Covers glob matching, config rules, CODEOWNERS parsing and the comment and
+//! reviewers for a branch that touches protected paths.
+
+use my_little_soda::bundling::path_policy::{glob_to_regex, PathPolicy, PROTECTED_MARKER};
+use my_little_soda::config::{ProtectedPathRule, ProtectedPathsConfig};
+use tempfile::TempDir;
+
+fn config(rules: Vec<ProtectedPathRule>) -> ProtectedPathsConfig {
+    ProtectedPathsConfig {
+        rules,
+        ..ProtectedPathsConfig::default()
+    }
+}
+
+fn owned(pattern: &str, owner: &str) -> ProtectedPathRule {
+    ProtectedPathRule {
+        owners: vec![owner.to_string()],
+        ..ProtectedPathRule::new(pattern)
+    }
+}
+
+fn protected(policy: &PathPolicy, files: &[&str]) -> Vec<String> {
+    policy
+        .check("agent001/1", files.iter().copied())
+        .paths
+        .into_iter()
+        .map(|p| p.path)
+        .collect()
+}
+
+#[test]
+fn test_glob_patterns_follow_codeowners_syntax() {
+    let matches = |pattern: &str, path: &str| glob_to_regex(pattern).unwrap().is_match(path);
+
+    // No slash: any depth, and a directory match covers its contents
+    assert!(matches("migrations", "db/migrations/001.sql"));
+    assert!(matches("*.sql", "db/schema.sql"));
+    // A slash anchors at the root
+    assert!(matches("/src/auth", "src/auth/token.rs"));
+    assert!(!matches("/src/auth", "lib/src/auth/token.rs"));
+    assert!(matches(".github/workflows/", ".github/workflows/ci.yml"));
+    // A trailing slash only matches directories
+    assert!(!matches("deploy/", "deploy"));
+    assert!(matches("deploy/", "deploy/prod.yml"));
+    // Single star stays within one directory, double star crosses them
+    assert!(!matches("src/*.rs", "src/auth/token.rs"));
+    assert!(matches("src/**/token.rs", "src/token.rs"));
+    assert!(matches("src/**/token.rs", "src/a/b/token.rs"));
+    assert!(matches("docs/**", "docs/guide/intro.md"));
+    assert!(!matches("docs?", "docs/a.md"));
+
+    assert!(glob_to_regex("/").is_err());
+}
+
+#[test]
+fn test_default_rules_protect_workflows_and_migrations() {
+    let policy = PathPolicy::new(&ProtectedPathsConfig::default()).unwrap();
+    assert_eq!(
+        protected(
+            &policy,
+            &[
+                ".github/workflows/ci.yml",
+                "src/lib.rs",
+                "db/migrations/002_users.sql",
+                "README.md",
+            ]
+        ),
+        vec![".github/workflows/ci.yml", "db/migrations/002_users.sql"]
+    );
+
+    let disabled = PathPolicy::new(&ProtectedPathsConfig {
+        enabled: false,
+        ..ProtectedPathsConfig::default()
+    })
+    .unwrap();
+    assert!(protected(&disabled, &[".github/workflows/ci.yml"]).is_empty());
+    // Only a policy with rules holds back branches whose changes can't be read
+    assert!(!policy.is_empty());
+    assert!(disabled.is_empty());
+}
+
+#[test]
+fn test_codeowners_protects_owned_paths_with_last_match_winning() {
+    let policy = PathPolicy::new(&config(vec![owned("src/auth/", "@alice")]))
+        .unwrap()
+        .with_codeowners(
+            "# Owners\n\
+         *.md @docs-team\n\
+         /src/auth/ @acme/security  # login and sessions\n\
+         /src/auth/generated/\n",
+        )
+        .unwrap();
+
+    let change = policy.check(
+        "agent002/4",
+        [
+            "src/auth/session.rs",
+            "src/auth/generated/schema.rs",
+            "README.md",
+            "src/lib.rs",
+        ],
+    );
+    let paths: Vec<(&str, &str, Vec<String>)> = change
+        .paths
+        .iter()
+        .map(|p| (p.path.as_str(), p.rule.as_str(), p.owners.clone()))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            (
+                "src/auth/session.rs",
+                "src/auth/",
+                vec!["@acme/security".to_string(), "@alice".to_string()]
+            ),
+            // The later ownerless line clears CODEOWNERS, the config rule still applies
+            (
+                "src/auth/generated/schema.rs",
+                "src/auth/",
+                vec!["@alice".to_string()]
+            ),
+            ("README.md", "*.md", vec!["@docs-team".to_string()]),
+        ]
+    );
+
+    let (users, teams) = change.reviewers();
+    assert_eq!(users, vec!["alice", "docs-team"]);
+    assert_eq!(teams, vec!["security"]);
+}
+
+#[test]
+fn test_codeowners_is_read_only_when_enabled() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join(".github")).unwrap();
+    std::fs::write(dir.path().join(".github/CODEOWNERS"), "/infra/ @ops\n").unwrap();
+
+    let without = PathPolicy::for_repository(dir.path(), &config(Vec::new())).unwrap();
+    assert!(protected(&without, &["infra/main.tf"]).is_empty());
+    assert!(without.is_empty());
+
+    let with = PathPolicy::for_repository(
+        dir.path(),
+        &ProtectedPathsConfig {
+            use_codeowners: true,
+            ..config(Vec::new())
+        },
+    )
+    .unwrap();
+    assert_eq!(protected(&with, &["infra/main.tf"]), vec!["infra/main.tf"]);
+    assert!(!with.is_empty());
+
+    let missing = PathPolicy::for_repository(
+        dir.path(),
+        &ProtectedPathsConfig {
+            use_codeowners: true,
+            codeowners_file: Some("docs/CODEOWNERS".to_string()),
+            ..config(Vec::new())
+        },
+    )
+    .unwrap();
+    assert!(protected(&missing, &["infra/main.tf"]).is_empty());
+}
+
+#[test]
+fn test_comment_lists_paths_and_owners() {
+    let policy = PathPolicy::new(&config(vec![
+        owned(".github/workflows/", "@acme/devops"),
+        ProtectedPathRule::new("migrations/"),
+    ]))
+    .unwrap();
+    let change = policy.check(
+        "agent001/12",
+        [".github/workflows/release.yml", "migrations/003.sql"],
+    );
+
+    let comment = change.issue_comment();
+    assert!(comment.contains("`agent001/12` changes paths"));
+    assert!(comment
+        .contains("| `.github/workflows/release.yml` | `.github/workflows/` | @acme/devops |"));
+    assert!(comment.contains("| `migrations/003.sql` | `migrations/` | - |"));
+    assert!(comment.contains("route:human-only"));
+    assert!(comment.contains("with @acme/devops requested as reviewers"));
+    assert!(comment.contains(&format!("{PROTECTED_MARKER} agent001/12 -->")));
+
+    let body = change.pr_body(12);
+    assert!(body.contains("- `migrations/003.sql`"));
+    assert!(body.contains("Fixes #12"));
+}
+
+#[test]
+fn test_config_section() {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        #[serde(default)]
+        protected_paths: ProtectedPathsConfig,
+    }
+
+    let wrapper: Wrapper = toml::from_str("").unwrap();
+    assert!(wrapper.protected_paths.enabled);
+    assert!(!wrapper.protected_paths.use_codeowners);
+    assert_eq!(wrapper.protected_paths.rules.len(), 2);
+
+    let parsed: Wrapper = toml::from_str(
+        "[protected_paths]\nuse_codeowners = true\n\
+         [[protected_paths.rules]]\npattern = \"src/auth/\"\nowners = [\"@acme/security\"]\n",
+    )
+    .unwrap();
+    assert!(parsed.protected_paths.use_codeowners);
+    assert_eq!(
+        parsed.protected_paths.rules,
+        vec![owned("src/auth/", "@acme/security")]
+    );
+
+    let broken = config(vec![ProtectedPathRule::new("/")]);
+    assert!(PathPolicy::new(&broken).is_err());
+}
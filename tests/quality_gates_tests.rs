@@ -0,0 +1,179 @@
+//! Quality gate tests
+//!
+//! Covers the `[quality_gates]` config, ecosystem defaults, running gates with
+//! timeouts and the report posted on the issue.
+
+use my_little_soda::agent_lifecycle::quality_gates::{
+    suggested_gates, GateStatus, QualityGateReport, QualityGateRunner,
+};
+use my_little_soda::config::{QualityGate, QualityGatesConfig};
+use tempfile::TempDir;
+
+fn gates(gates: Vec<QualityGate>) -> QualityGatesConfig {
+    QualityGatesConfig {
+        enabled: true,
+        gates,
+    }
+}
+
+#[test]
+fn test_suggested_gates_follow_the_ecosystem() {
+    let names = |files: &[&str]| -> Vec<String> {
+        suggested_gates(|path| files.contains(&path))
+            .into_iter()
+            .map(|gate| gate.name)
+            .collect()
+    };
+
+    assert_eq!(names(&["Cargo.toml"]), vec!["fmt", "clippy", "test"]);
+    assert_eq!(names(&["package.json"]), vec!["npm-test"]);
+    assert_eq!(names(&["pyproject.toml"]), vec!["pytest"]);
+    assert_eq!(names(&["go.mod"]), vec!["go-test"]);
+    assert_eq!(
+        names(&["package.json", "requirements.txt"]),
+        vec!["npm-test", "pytest"]
+    );
+    assert!(names(&["README.md"]).is_empty());
+
+    let cargo = suggested_gates(|path| path == "Cargo.toml");
+    assert_eq!(cargo[0].command, "cargo fmt --all -- --check");
+    assert!(cargo.iter().all(|gate| gate.required));
+}
+
+#[test]
+fn test_config_section_defaults() {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        #[serde(default)]
+        quality_gates: QualityGatesConfig,
+    }
+
+    let wrapper: Wrapper = toml::from_str("").unwrap();
+    assert!(wrapper.quality_gates.enabled);
+    assert!(wrapper.quality_gates.gates.is_empty());
+
+    let parsed: Wrapper = toml::from_str(
+        "[quality_gates]\n\
+         [[quality_gates.gates]]\nname = \"fmt\"\ncommand = \"cargo fmt --check\"\n\
+         [[quality_gates.gates]]\nname = \"lint\"\ncommand = \"npm run lint\"\n\
+         timeout_seconds = 60\nrequired = false\nworking_dir = \"web\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        parsed.quality_gates.gates,
+        vec![
+            QualityGate::new("fmt", "cargo fmt --check"),
+            QualityGate {
+                required: false,
+                working_dir: Some("web".to_string()),
+                ..QualityGate::new("lint", "npm run lint").with_timeout(60)
+            },
+        ]
+    );
+    assert_eq!(parsed.quality_gates.gates[0].timeout_seconds, 600);
+}
+
+#[tokio::test]
+async fn test_passing_gates_run_in_order() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("web")).unwrap();
+    std::fs::write(dir.path().join("web/marker"), "").unwrap();
+
+    let report = QualityGateRunner::new(dir.path())
+        .run(
+            &gates(vec![
+                QualityGate::new("first", "echo one"),
+                QualityGate {
+                    working_dir: Some("web".to_string()),
+                    ..QualityGate::new("in-dir", "test -f marker")
+                },
+            ]),
+            "agent001/1",
+        )
+        .await;
+
+    assert!(report.passed());
+    assert_eq!(report.results.len(), 2);
+    assert!(report.results.iter().all(|r| r.passed()));
+    assert_eq!(report.results[0].output_tail, "one");
+}
+
+#[tokio::test]
+async fn test_required_failure_skips_the_rest() {
+    let dir = TempDir::new().unwrap();
+
+    let report = QualityGateRunner::new(dir.path())
+        .run(
+            &gates(vec![
+                QualityGate {
+                    required: false,
+                    ..QualityGate::new("lint", "echo warning >&2; exit 3")
+                },
+                QualityGate::new("build", "echo broken; exit 2"),
+                QualityGate::new("test", "echo never"),
+            ]),
+            "agent001/1",
+        )
+        .await;
+
+    assert!(!report.passed());
+    assert_eq!(
+        report.results[0].status,
+        GateStatus::Failed { exit_code: Some(3) }
+    );
+    assert_eq!(
+        report.results[1].status,
+        GateStatus::Failed { exit_code: Some(2) }
+    );
+    assert_eq!(report.results[1].output_tail, "broken");
+    assert_eq!(report.results[2].status, GateStatus::Skipped);
+
+    // Only the required gate that ran counts as a failure
+    let failures: Vec<&str> = report.failures().iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(failures, vec!["build"]);
+}
+
+#[tokio::test]
+async fn test_slow_gate_times_out() {
+    let dir = TempDir::new().unwrap();
+
+    let report = QualityGateRunner::new(dir.path())
+        .run(
+            &gates(vec![QualityGate::new("slow", "sleep 10").with_timeout(1)]),
+            "agent001/1",
+        )
+        .await;
+
+    assert_eq!(report.results[0].status, GateStatus::TimedOut);
+    assert!(report.results[0].duration_ms < 5000);
+    assert!(!report.passed());
+}
+
+#[tokio::test]
+async fn test_report_comment_and_serialization() {
+    let dir = TempDir::new().unwrap();
+    let report = QualityGateRunner::new(dir.path())
+        .run(
+            &gates(vec![
+                QualityGate::new("fmt", "true"),
+                QualityGate::new("test", "echo 'test foo ... FAILED'; exit 101"),
+            ]),
+            "agent001/7",
+        )
+        .await;
+
+    let comment = report.format_comment();
+    assert!(comment.starts_with("❌ **Quality gates failed**"));
+    assert!(comment.contains("Branch `agent001/7`"));
+    assert!(comment.contains("| ✅ fmt | `true` | passed |"));
+    assert!(comment.contains("failed (exit 101)"));
+    assert!(comment.contains("<details><summary>test output</summary>"));
+    assert!(comment.contains("test foo ... FAILED"));
+    assert!(report.format_text().contains("❌ test: failed (exit 101)"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["results"][1]["status"], "failed");
+    assert_eq!(json["results"][1]["exit_code"], 101);
+    let restored: QualityGateReport = serde_json::from_value(json).unwrap();
+    assert_eq!(restored.results[1].status, report.results[1].status);
+}
@@ -0,0 +1,421 @@
+//! Declarative recovery rules tests
+//!
+//! The built-in rules must map every error the way the workflow always has;
+//! rules from `[recovery]` config come first, can match on status codes, file
+//! globs and message regexes, and `agent recover --explain` shows which rule
+//! fires and why the others did not. Only built with `--features autonomous`.
+
+#![cfg(feature = "autonomous")]
+
+use my_little_soda::autonomous::error_recovery::{
+    AlternativeApproach, ConfidenceLevel, FixType, UrgencyLevel,
+};
+use my_little_soda::autonomous::{
+    AbandonmentReason, AutonomousErrorRecovery, ErrorFacts, ErrorType, RecoveryRules,
+    RecoveryStrategy,
+};
+use my_little_soda::config::RecoveryConfig;
+
+fn rules(toml: &str) -> RecoveryRules {
+    let config: RecoveryConfig = toml::from_str(toml).unwrap();
+    RecoveryRules::from_config(&config).unwrap()
+}
+
+fn github_error(status: u16, message: &str) -> ErrorType {
+    ErrorType::GitHubAPIError {
+        endpoint: "/repos/o/r/pulls".to_string(),
+        status,
+        message: message.to_string(),
+    }
+}
+
+fn merge_conflict(files: &[&str], conflict_count: u32) -> ErrorType {
+    ErrorType::MergeConflict {
+        files: files.iter().map(|f| f.to_string()).collect(),
+        conflict_count,
+    }
+}
+
+#[test]
+fn test_builtin_rules_pick_the_same_strategies_as_before() {
+    let git = |operation: &str| ErrorType::GitOperationFailed {
+        operation: operation.to_string(),
+        error: "failed".to_string(),
+    };
+    let ci = |error: &str| ErrorType::CIFailure {
+        job: "check".to_string(),
+        step: "run".to_string(),
+        error: error.to_string(),
+    };
+    let tests = |count: usize| ErrorType::TestFailure {
+        test_suite: "unit".to_string(),
+        failed_tests: (0..count).map(|i| format!("test_{i}")).collect(),
+    };
+    let build = |stage: &str, error: &str| ErrorType::BuildFailure {
+        stage: stage.to_string(),
+        error: error.to_string(),
+    };
+    let cases = [
+        (git("push"), "git-sync-retry"),
+        (git("rebase"), "git-merge-fix"),
+        (git("checkout"), "git-manual"),
+        (github_error(429, "slow down"), "github-rate-limited"),
+        (github_error(502, "bad gateway"), "github-server-error"),
+        (github_error(404, "not found"), "github-other"),
+        (merge_conflict(&["src/lib.rs"], 2), "merge-conflict-small"),
+        (
+            merge_conflict(&["db/migrations/001.sql"], 1),
+            "merge-conflict-migrations",
+        ),
+        (merge_conflict(&["a.rs", "b.rs"], 9), "merge-conflict-large"),
+        (ci("2 tests failed"), "ci-tests"),
+        (ci("could not compile"), "ci-build"),
+        (ci("deploy rejected"), "ci-other"),
+        (tests(3), "tests-few"),
+        (tests(4), "tests-many"),
+        (
+            build("dependencies", "resolve failed"),
+            "build-dependencies",
+        ),
+        (build("clippy", "lint needless_return"), "build-formatting"),
+        (build("compile", "E0308"), "build-other"),
+        (
+            ErrorType::DependencyIssue {
+                dependency: "serde".to_string(),
+                version_conflict: true,
+            },
+            "dependency-conflict",
+        ),
+        (
+            ErrorType::DependencyIssue {
+                dependency: "serde".to_string(),
+                version_conflict: false,
+            },
+            "dependency-retry",
+        ),
+        (
+            ErrorType::NetworkIssue {
+                service: "api.github.com".to_string(),
+                timeout: true,
+            },
+            "network-timeout",
+        ),
+        (
+            ErrorType::NetworkIssue {
+                service: "api.github.com".to_string(),
+                timeout: false,
+            },
+            "network-unreachable",
+        ),
+        (
+            ErrorType::WorkspaceCorruption {
+                files_affected: vec!["a".to_string()],
+            },
+            "workspace-repair",
+        ),
+        (
+            ErrorType::WorkspaceCorruption {
+                files_affected: (0..6).map(|i| i.to_string()).collect(),
+            },
+            "workspace-reset",
+        ),
+        (
+            ErrorType::StateInconsistency {
+                expected_state: "assigned".to_string(),
+                actual_state: "unassigned".to_string(),
+            },
+            "state-inconsistency",
+        ),
+    ];
+
+    let rules = RecoveryRules::builtin();
+    for (error, rule) in &cases {
+        assert_eq!(rules.plan_for(error).rule, *rule, "for {error:?}");
+    }
+}
+
+#[test]
+fn test_builtin_strategies_keep_their_parameters() {
+    let Some(RecoveryStrategy::RetryWithBackoff {
+        max_attempts: 5,
+        base_delay_ms: 2000,
+        max_delay_ms: 30000,
+    }) = RecoveryRules::builtin()
+        .plan_for(&github_error(429, "slow down"))
+        .strategy(1)
+    else {
+        panic!("rate limits should be retried five times");
+    };
+
+    let RecoveryStrategy::Escalate {
+        urgency: UrgencyLevel::High,
+        context,
+    } = AutonomousErrorRecovery::without_client()
+        .determine_recovery_strategy(&merge_conflict(&["a.rs", "b.rs"], 9))
+    else {
+        panic!("large conflicts should be escalated");
+    };
+    assert_eq!(context, "Complex merge conflicts in 2 files");
+
+    let RecoveryStrategy::AbandonAndReset {
+        reason: AbandonmentReason::CriticalFailure { error },
+    } = AutonomousErrorRecovery::without_client().determine_recovery_strategy(
+        &ErrorType::WorkspaceCorruption {
+            files_affected: (0..6).map(|i| i.to_string()).collect(),
+        },
+    )
+    else {
+        panic!("badly corrupted workspaces should be reset");
+    };
+    assert_eq!(error, "Workspace corruption");
+
+    let RecoveryStrategy::Escalate { context, .. } = AutonomousErrorRecovery::without_client()
+        .determine_recovery_strategy(&ErrorType::CIFailure {
+            job: "deploy".to_string(),
+            step: "upload".to_string(),
+            error: "permission denied".to_string(),
+        })
+    else {
+        panic!("unknown CI failures should be escalated");
+    };
+    assert_eq!(context, "CI failure in deploy: permission denied");
+}
+
+#[test]
+fn test_configured_rules_come_before_the_builtin_ones() {
+    let rules = rules(
+        r#"
+        [[rules]]
+        name = "forbidden"
+        error_type = "github_api"
+        status = [401, "403-403"]
+        message = "(?i)rate limit"
+        strategies = [{ action = "retry" }]
+        max_attempts = 4
+        backoff = { base_delay_ms = 60000, max_delay_ms = 600000 }
+
+        [[rules]]
+        name = "schema"
+        error_type = "merge_conflict"
+        files = ["schema/**/*.graphql"]
+        strategies = [{ action = "escalate", urgency = "critical", context = "Schema conflict in {file_count} file(s)" }]
+        escalation = { labels = ["schema-review"], comment = false }
+        "#,
+    );
+
+    let plan = rules.plan_for(&github_error(403, "API rate limit exceeded"));
+    assert_eq!(plan.rule, "forbidden");
+    assert_eq!(plan.max_attempts, 4);
+    let Some(RecoveryStrategy::RetryWithBackoff {
+        max_attempts: 4,
+        base_delay_ms: 60000,
+        max_delay_ms: 600000,
+    }) = plan.strategy(4)
+    else {
+        panic!("the configured backoff should be used");
+    };
+    assert!(plan.strategy(5).is_none());
+
+    // Other 403s and other statuses fall through to the built-in rules
+    assert_eq!(
+        rules.plan_for(&github_error(403, "forbidden")).rule,
+        "github-other"
+    );
+    assert_eq!(
+        rules.plan_for(&github_error(500, "rate limit")).rule,
+        "github-server-error"
+    );
+
+    let plan = rules.plan_for(&merge_conflict(&["schema/v2/user.graphql"], 1));
+    assert_eq!(plan.rule, "schema");
+    assert_eq!(plan.escalation.labels, vec!["schema-review".to_string()]);
+    assert!(!plan.escalation.comment);
+    let RecoveryStrategy::Escalate {
+        urgency: UrgencyLevel::Critical,
+        context,
+    } = plan.first()
+    else {
+        panic!("schema conflicts should be escalated");
+    };
+    assert_eq!(context, "Schema conflict in 1 file(s)");
+    assert_eq!(
+        rules.plan_for(&merge_conflict(&["src/schema.rs"], 1)).rule,
+        "merge-conflict-small"
+    );
+}
+
+#[test]
+fn test_strategies_are_tried_in_order_and_the_last_repeats() {
+    let rules = rules(
+        r#"
+        [[rules]]
+        name = "flaky"
+        error_type = "test_failure"
+        strategies = [
+            { action = "retry" },
+            { action = "fix", fix = "test_failure_fix", confidence = "low" },
+            { action = "fallback", alternative = "different_implementation" },
+        ]
+        max_attempts = 4
+        "#,
+    );
+    let plan = rules.plan_for(&ErrorType::TestFailure {
+        test_suite: "unit".to_string(),
+        failed_tests: vec!["test_a".to_string()],
+    });
+
+    assert!(plan.strategy(0).is_none());
+    assert!(matches!(
+        plan.strategy(1),
+        Some(RecoveryStrategy::RetryWithBackoff { .. })
+    ));
+    assert!(matches!(
+        plan.strategy(2),
+        Some(RecoveryStrategy::AutomatedFix {
+            fix_type: FixType::TestFailureFix,
+            confidence: ConfidenceLevel::Low,
+        })
+    ));
+    for attempt in [3, 4] {
+        assert!(matches!(
+            plan.strategy(attempt),
+            Some(RecoveryStrategy::Fallback {
+                alternative: AlternativeApproach::DifferentImplementation,
+            })
+        ));
+    }
+    assert!(plan.strategy(5).is_none());
+}
+
+#[test]
+fn test_without_builtin_rules_unmatched_errors_are_escalated() {
+    let rules = rules(
+        r#"
+        builtin_rules = false
+
+        [[rules]]
+        name = "network"
+        error_type = "network_issue"
+        strategies = [{ action = "retry" }]
+        "#,
+    );
+    // Nothing fires, so every loaded rule is either checked or skipped
+    let explanation = rules.explain(&ErrorFacts::from(&github_error(429, "slow down")));
+    assert_eq!(explanation.checked.len() + explanation.skipped, 1);
+
+    let plan = rules.plan_for(&github_error(429, "slow down"));
+    assert_eq!(plan.rule, "unmatched");
+    let RecoveryStrategy::Escalate { context, .. } = plan.first() else {
+        panic!("unmatched errors should be escalated");
+    };
+    assert_eq!(context, "No recovery rule matches this github_api error");
+}
+
+#[test]
+fn test_explain_shows_the_rules_checked_and_the_one_that_fires() {
+    let rules = rules(
+        r#"
+        [[rules]]
+        name = "lockfile"
+        error_type = "merge_conflict"
+        files = ["Cargo.lock"]
+        strategies = [{ action = "fix", fix = "dependency_update", confidence = "high" }]
+        "#,
+    );
+    let facts = ErrorFacts::new("merge_conflict")
+        .unwrap()
+        .with_files(vec!["src/lib.rs".to_string(), "src/main.rs".to_string()])
+        .with_count(8);
+
+    let explanation = rules.explain(&facts);
+
+    assert_eq!(explanation.fired(), Some("merge-conflict-large"));
+    let checked: Vec<(&str, Option<&str>)> = explanation
+        .checked
+        .iter()
+        .map(|check| (check.rule.as_str(), check.mismatch.as_deref()))
+        .collect();
+    assert_eq!(
+        checked,
+        vec![
+            ("lockfile", Some("no file matches Cargo.lock")),
+            (
+                "merge-conflict-migrations",
+                Some("no file matches *migration*")
+            ),
+            ("merge-conflict-small", Some("count 8 is over 5")),
+            ("merge-conflict-large", None),
+        ]
+    );
+    assert!(explanation.skipped > 0);
+
+    let text = explanation.to_string();
+    assert!(text.contains("✗ lockfile: no file matches Cargo.lock"));
+    assert!(text.contains("✓ merge-conflict-large (built-in)"));
+    assert!(text.contains("Rule 'merge-conflict-large' fires:"));
+    assert!(text.contains("1. escalate (High urgency): Complex merge conflicts in 2 files"));
+    assert!(text.contains("Then escalated to: label `route:human-only`, issue comment"));
+}
+
+#[test]
+fn test_unknown_error_types_are_rejected() {
+    let error = ErrorFacts::new("gremlins").unwrap_err().to_string();
+    assert!(error.contains("Unknown error type 'gremlins'"));
+    assert!(error.contains("github_api"));
+}
+
+#[test]
+fn test_invalid_rules_are_reported_by_name() {
+    let invalid = [
+        (
+            r#"
+            [[rules]]
+            name = "bad-regex"
+            message = "(unclosed"
+            strategies = [{ action = "retry" }]
+            "#,
+            "bad-regex",
+        ),
+        (
+            r#"
+            [[rules]]
+            name = "bad-fix"
+            strategies = [{ action = "fix", fix = "magic" }]
+            "#,
+            "Unknown fix 'magic'",
+        ),
+        (
+            r#"
+            [[rules]]
+            name = "bad-status"
+            status = ["599-500"]
+            strategies = [{ action = "retry" }]
+            "#,
+            "Invalid status range '599-500'",
+        ),
+        (
+            r#"
+            [[rules]]
+            name = "bad-kind"
+            error_type = "ci"
+            strategies = [{ action = "retry" }]
+            "#,
+            "Unknown error type 'ci'",
+        ),
+        (
+            r#"
+            [[rules]]
+            name = "no-strategies"
+            strategies = []
+            "#,
+            "No strategies given",
+        ),
+    ];
+
+    for (toml, expected) in invalid {
+        let config: RecoveryConfig = toml::from_str(toml).unwrap();
+        let error = format!("{:#}", RecoveryRules::from_config(&config).unwrap_err());
+        assert!(error.contains("Invalid recovery rule"), "{error}");
+        assert!(error.contains(expected), "{error}");
+    }
+}
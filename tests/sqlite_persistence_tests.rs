@@ -0,0 +1,305 @@
+//! SQLite workflow state persistence tests
+//!
+//! Covers saving and checkpointing into the database, the hashes stored with
+//! every row, retention, and importing directories written by the filesystem
+//! backend. Only built with `--features autonomous,database`.
+
+#![cfg(all(feature = "autonomous", feature = "database"))]
+
+use chrono::Utc;
+use my_little_soda::autonomous::persistence::{
+    create_persistence, CheckpointMetadata, CheckpointReason, FileSystemPersistence,
+    PersistenceBackend, PersistenceConfig, PersistenceError, PersistentWorkflowState,
+    StatePersistence,
+};
+use my_little_soda::autonomous::sqlite_persistence::SqlitePersistence;
+use my_little_soda::autonomous::{AutonomousWorkflowState, Issue, Priority};
+use my_little_soda::cli::commands::db::DbImportStateCommand;
+use my_little_soda::database::DatabaseManager;
+use my_little_soda::StatePersistenceManager;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+async fn database(dir: &TempDir) -> DatabaseManager {
+    DatabaseManager::new(&url(dir), true).await.unwrap()
+}
+
+fn url(dir: &TempDir) -> String {
+    format!("sqlite://{}", dir.path().join("state.db").display())
+}
+
+fn persistence(db: &DatabaseManager) -> SqlitePersistence {
+    SqlitePersistence::new(PersistenceConfig::default()).with_pool(db.pool().clone())
+}
+
+fn state(agent: &str) -> PersistentWorkflowState {
+    PersistentWorkflowState {
+        version: "1.0.0".to_string(),
+        agent_id: agent.to_string(),
+        current_state: Some(AutonomousWorkflowState::Unassigned {
+            issue: Issue {
+                number: 412,
+                title: "Persist in SQLite".to_string(),
+                body: String::new(),
+                labels: vec![],
+                priority: Priority::Medium,
+                estimated_hours: None,
+            },
+        }),
+        start_time: Some(Utc::now() - chrono::Duration::minutes(90)),
+        max_work_hours: 8,
+        state_history: vec![],
+        recovery_history: vec![],
+        checkpoint_metadata: CheckpointMetadata {
+            checkpoint_id: "initial".to_string(),
+            creation_reason: CheckpointReason::PeriodicSave,
+            integrity_hash: String::new(),
+            agent_pid: None,
+            hostname: "build-1".to_string(),
+        },
+        last_persisted: Utc::now(),
+        history_summary: None,
+    }
+}
+
+#[tokio::test]
+async fn test_save_load_and_restore_saved_checkpoints() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let persistence = persistence(&db);
+    assert!(persistence.load_state("agent001").await.unwrap().is_none());
+
+    let first = persistence
+        .save_state(&state("agent001"), CheckpointReason::StateTransition)
+        .await
+        .unwrap();
+    let second = persistence
+        .save_state(&state("agent001"), CheckpointReason::BeforeShutdown)
+        .await
+        .unwrap();
+
+    let loaded = persistence.load_state("agent001").await.unwrap().unwrap();
+    assert_eq!(loaded.checkpoint_metadata.checkpoint_id, second);
+    assert!(persistence.verify_integrity(&loaded).await.unwrap());
+
+    // Every save can be restored, newest listed first
+    let checkpoints = persistence.list_checkpoints("agent001").await.unwrap();
+    let ids: Vec<&str> = checkpoints
+        .iter()
+        .map(|c| c.checkpoint_id.as_str())
+        .collect();
+    assert_eq!(ids, vec![second.as_str(), first.as_str()]);
+    assert!(matches!(
+        checkpoints[0].reason,
+        CheckpointReason::BeforeShutdown
+    ));
+    let summary = &checkpoints[0].state_summary;
+    assert_eq!(summary.current_state_type.as_deref(), Some("Unassigned"));
+    assert!(summary.uptime_minutes.unwrap() >= 89);
+    assert!(checkpoints[0].file_size > 0);
+
+    let restored = persistence
+        .restore_from_checkpoint("agent001", &first)
+        .await
+        .unwrap();
+    assert_eq!(restored.checkpoint_metadata.checkpoint_id, first);
+    assert!(persistence
+        .list_checkpoints("agent002")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_checkpoints_and_missing_ones() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let persistence = persistence(&db);
+
+    let id = persistence
+        .create_checkpoint(&state("agent001"), CheckpointReason::BeforeRecovery)
+        .await
+        .unwrap();
+    // A checkpoint alone is not the agent's current state
+    assert!(persistence.load_state("agent001").await.unwrap().is_none());
+    assert_eq!(
+        persistence
+            .restore_from_checkpoint("agent001", &id)
+            .await
+            .unwrap()
+            .agent_id,
+        "agent001"
+    );
+
+    let missing = persistence
+        .restore_from_checkpoint("agent001", "nope")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        missing,
+        PersistenceError::IoError(e) if e.kind() == std::io::ErrorKind::NotFound
+    ));
+}
+
+#[tokio::test]
+async fn test_rows_changed_behind_our_back_are_refused() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let persistence = persistence(&db);
+    let id = persistence
+        .save_state(&state("agent001"), CheckpointReason::UserRequested)
+        .await
+        .unwrap();
+
+    for table in ["workflow_states", "workflow_checkpoints"] {
+        sqlx::query(&format!(
+            "UPDATE {table} SET state = replace(state, 'agent001', 'agent009')"
+        ))
+        .execute(db.pool())
+        .await
+        .unwrap();
+    }
+
+    assert!(matches!(
+        persistence.load_state("agent001").await,
+        Err(PersistenceError::StateCorruption { .. })
+    ));
+    assert!(matches!(
+        persistence.restore_from_checkpoint("agent001", &id).await,
+        Err(PersistenceError::StateCorruption { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_cleanup_drops_checkpoints_past_retention() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let persistence = persistence(&db);
+    let old = persistence
+        .create_checkpoint(&state("agent001"), CheckpointReason::PeriodicSave)
+        .await
+        .unwrap();
+    let recent = persistence
+        .create_checkpoint(&state("agent001"), CheckpointReason::PeriodicSave)
+        .await
+        .unwrap();
+    sqlx::query(
+        "UPDATE workflow_checkpoints SET created_at = datetime('now', '-30 days') WHERE checkpoint_id = ?1",
+    )
+    .bind(&old)
+    .execute(db.pool())
+    .await
+    .unwrap();
+
+    persistence.cleanup_old_data("agent001").await.unwrap();
+    let left = persistence.list_checkpoints("agent001").await.unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].checkpoint_id, recent);
+}
+
+#[tokio::test]
+async fn test_import_filesystem_state_directory() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let state_dir = dir.path().join("autonomous_state");
+    let files = FileSystemPersistence::new(PersistenceConfig {
+        persistence_directory: state_dir.clone(),
+        ..PersistenceConfig::default()
+    });
+    let saved = files
+        .save_state(&state("agent001"), CheckpointReason::PeriodicSave)
+        .await
+        .unwrap();
+    let checkpoint = files
+        .create_checkpoint(&state("agent001"), CheckpointReason::BeforeRecovery)
+        .await
+        .unwrap();
+    std::fs::write(
+        state_dir.join("agent001_checkpoints/broken.checkpoint.json"),
+        "{",
+    )
+    .unwrap();
+
+    let command = DbImportStateCommand::new(Some(state_dir.clone())).with_url(Some(url(&dir)));
+    let report = command.run().await.unwrap();
+    assert_eq!(report.states_imported, 1);
+    assert_eq!(report.checkpoints_imported, 1);
+    assert_eq!(report.already_present, 0);
+    assert_eq!(report.failed.len(), 1);
+
+    let persistence = persistence(&db);
+    let loaded = persistence.load_state("agent001").await.unwrap().unwrap();
+    assert_eq!(loaded.checkpoint_metadata.checkpoint_id, saved);
+    let restored = persistence
+        .restore_from_checkpoint("agent001", &checkpoint)
+        .await
+        .unwrap();
+    assert!(matches!(
+        restored.checkpoint_metadata.creation_reason,
+        CheckpointReason::BeforeRecovery
+    ));
+
+    // Importing again leaves everything as it was
+    let again = command.run().await.unwrap();
+    assert_eq!(again.states_imported + again.checkpoints_imported, 0);
+    assert_eq!(again.already_present, 2);
+
+    assert!(DbImportStateCommand::new(Some(dir.path().join("missing")))
+        .with_url(Some(url(&dir)))
+        .run()
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_manager_uses_the_given_backend() {
+    let dir = TempDir::new().unwrap();
+    let db = database(&dir).await;
+    let config = PersistenceConfig {
+        backend: PersistenceBackend::Sqlite,
+        persistence_directory: dir.path().join("unused"),
+        ..PersistenceConfig::default()
+    };
+    let manager = StatePersistenceManager::with_persistence(
+        Arc::new(SqlitePersistence::new(config.clone()).with_pool(db.pool().clone())),
+        config.clone(),
+    );
+    manager
+        .save_state(&state("agent001"), CheckpointReason::UserRequested)
+        .await
+        .unwrap();
+    assert!(manager.load_state("agent001").await.unwrap().is_some());
+    assert!(!dir.path().join("unused").exists());
+
+    // Without a [database] the SQLite backend reports why it cannot save
+    let configured = create_persistence(&config);
+    assert!(matches!(
+        configured
+            .save_state(&state("agent001"), CheckpointReason::UserRequested)
+            .await,
+        Err(PersistenceError::DatabaseError { .. })
+    ));
+}
+
+#[test]
+fn test_backend_config() {
+    let config: PersistenceConfig = serde_json::from_value(serde_json::json!({
+        "enable_persistence": true,
+        "persistence_directory": ".my-little-soda/autonomous_state",
+        "auto_save_interval_minutes": 5,
+        "max_state_history_entries": 10,
+        "max_recovery_history_entries": 10,
+        "compress_old_states": false,
+        "backup_retention_days": 7,
+        "enable_integrity_checks": true
+    }))
+    .unwrap();
+    assert_eq!(config.backend, PersistenceBackend::Filesystem);
+
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        persistence_backend: PersistenceBackend,
+    }
+    let parsed: Wrapper = toml::from_str("persistence_backend = \"sqlite\"").unwrap();
+    assert_eq!(parsed.persistence_backend, PersistenceBackend::Sqlite);
+}
@@ -0,0 +1,358 @@
+//! Portable state export/import tests
+//!
+//! Moves an agent between two clones of the same bare remote: the archive
+//! round trip, restoring unpushed commits from the git bundle, and the checks
+//! against GitHub and existing local state. Only built with
+//! `--features autonomous`.
+
+#![cfg(feature = "autonomous")]
+
+use chrono::Utc;
+use my_little_soda::autonomous::persistence::{
+    CheckpointMetadata, CheckpointReason, FileSystemPersistence, PersistenceConfig,
+    PersistentWorkflowState, StatePersistence,
+};
+use my_little_soda::autonomous::state_archive::{
+    validate_against_github, AgentWorkspace, BranchRestore, GitHubFacts, StateArchive,
+};
+use my_little_soda::autonomous::{AutonomousWorkflowState, Issue, Priority};
+use my_little_soda::cli::commands::state::{StateExportCommand, StateImportCommand};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+#[path = "fixtures/git_repos.rs"]
+mod git_repos;
+use git_repos::git;
+
+const BRANCH: &str = "agent001/7-move-agent";
+
+fn commit(dir: &Path, file: &str) {
+    std::fs::write(dir.join(file), file).unwrap();
+    git(dir, &["add", file]);
+    git(dir, &["commit", "-q", "-m", file]);
+}
+
+/// A bare remote and a laptop clone working on `BRANCH` with `unpushed` local commits
+fn laptop(unpushed: usize) -> (TempDir, TempDir) {
+    let origin = TempDir::new().unwrap();
+    git(
+        origin.path(),
+        &["init", "-q", "--bare", "--initial-branch=main"],
+    );
+    let laptop = TempDir::new().unwrap();
+    git(
+        laptop.path(),
+        &["clone", "-q", &origin.path().to_string_lossy(), "."],
+    );
+    git(laptop.path(), &["checkout", "-q", "-b", "main"]);
+    commit(laptop.path(), "README.md");
+    git(laptop.path(), &["push", "-q", "origin", "main"]);
+    git(laptop.path(), &["checkout", "-q", "-b", BRANCH]);
+    commit(laptop.path(), "pushed.txt");
+    git(laptop.path(), &["push", "-q", "origin", BRANCH]);
+    for n in 0..unpushed {
+        commit(laptop.path(), &format!("unpushed-{n}.txt"));
+    }
+    (origin, laptop)
+}
+
+/// Another machine's clone of the same remote
+fn server(origin: &TempDir) -> TempDir {
+    let server = TempDir::new().unwrap();
+    git(
+        server.path(),
+        &["clone", "-q", &origin.path().to_string_lossy(), "."],
+    );
+    server
+}
+
+fn persistence(dir: &TempDir) -> Arc<FileSystemPersistence> {
+    Arc::new(FileSystemPersistence::new(PersistenceConfig {
+        persistence_directory: dir.path().to_path_buf(),
+        ..PersistenceConfig::default()
+    }))
+}
+
+fn state() -> PersistentWorkflowState {
+    PersistentWorkflowState {
+        version: "1.0.0".to_string(),
+        agent_id: "agent001".to_string(),
+        current_state: Some(AutonomousWorkflowState::Unassigned {
+            issue: Issue {
+                number: 7,
+                title: "Move the agent".to_string(),
+                body: String::new(),
+                labels: vec!["agent001".to_string()],
+                priority: Priority::Medium,
+                estimated_hours: None,
+            },
+        }),
+        start_time: Some(Utc::now()),
+        max_work_hours: 8,
+        state_history: vec![],
+        recovery_history: vec![],
+        checkpoint_metadata: CheckpointMetadata {
+            checkpoint_id: "initial".to_string(),
+            creation_reason: CheckpointReason::PeriodicSave,
+            integrity_hash: String::new(),
+            agent_pid: None,
+            hostname: "laptop".to_string(),
+        },
+        last_persisted: Utc::now(),
+        history_summary: None,
+    }
+}
+
+fn facts() -> GitHubFacts {
+    GitHubFacts {
+        repository: "example/repo".to_string(),
+        issue_open: Some(true),
+        issue_labels: vec!["agent001".to_string()],
+        branch_on_remote: true,
+    }
+}
+
+async fn export(laptop: &TempDir, state_dir: &TempDir, output: &Path) -> StateArchive {
+    StateExportCommand::new("agent001".to_string(), Some(output.to_path_buf()))
+        .with_workspace(laptop.path().to_path_buf())
+        .with_persistence(persistence(state_dir))
+        .run()
+        .await
+        .unwrap()
+        .1
+}
+
+#[tokio::test]
+async fn test_agent_moves_with_unpushed_commits_and_checkpoints() {
+    let (origin, laptop) = laptop(2);
+    let laptop_state = TempDir::new().unwrap();
+    let files = persistence(&laptop_state);
+    files
+        .create_checkpoint(&state(), CheckpointReason::StateTransition)
+        .await
+        .unwrap();
+    files
+        .create_checkpoint(&state(), CheckpointReason::BeforeShutdown)
+        .await
+        .unwrap();
+    files
+        .save_state(&state(), CheckpointReason::BeforeShutdown)
+        .await
+        .unwrap();
+
+    let out = TempDir::new().unwrap();
+    let archive_path = out.path().join("agent001.tar.gz");
+    let exported = export(&laptop, &laptop_state, &archive_path).await;
+    let head = git(laptop.path(), &["rev-parse", "HEAD"]);
+    assert_eq!(exported.manifest.branch.as_deref(), Some(BRANCH));
+    assert_eq!(exported.manifest.issue, Some(7));
+    assert_eq!(
+        exported.manifest.head_commit.as_deref(),
+        Some(head.as_str())
+    );
+    assert_eq!(exported.manifest.unpushed_commits, 2);
+    assert_eq!(exported.manifest.checkpoints, 2);
+    let issue = exported.agent_state.current_issue.as_ref().unwrap();
+    assert_eq!(issue.title, "Move the agent");
+
+    let archive = StateArchive::read_from(&archive_path).unwrap();
+    assert_eq!(archive.manifest, exported.manifest);
+    assert_eq!(archive.git_bundle, exported.git_bundle);
+    let ids = |archive: &StateArchive| -> Vec<String> {
+        archive
+            .checkpoints
+            .iter()
+            .map(|c| c.checkpoint_metadata.checkpoint_id.clone())
+            .collect()
+    };
+    assert_eq!(ids(&archive), ids(&exported));
+
+    let server = server(&origin);
+    let server_state = TempDir::new().unwrap();
+    let import = StateImportCommand::new(archive_path.clone(), false)
+        .with_workspace(server.path().to_path_buf())
+        .with_persistence(persistence(&server_state));
+    let outcome = import.restore(&archive, &facts()).await.unwrap();
+    assert!(outcome.overridden.is_empty());
+    assert_eq!(outcome.checkpoints, 2);
+    assert!(matches!(
+        outcome.branch,
+        Some(BranchRestore::FromBundle { ref branch, head: restored })
+            if branch == BRANCH && restored.to_string() == head
+    ));
+    assert_eq!(git(server.path(), &["rev-parse", BRANCH]), head);
+
+    let moved = persistence(&server_state);
+    assert!(moved.load_state("agent001").await.unwrap().is_some());
+    assert_eq!(moved.list_checkpoints("agent001").await.unwrap().len(), 2);
+
+    // A second import would overwrite what is now the agent's state here
+    let again = import.restore(&archive, &facts()).await.unwrap_err();
+    assert!(again.to_string().contains("pass --force"), "{again}");
+}
+
+#[tokio::test]
+async fn test_pushed_branch_is_restored_from_the_remote() {
+    let (origin, laptop) = laptop(0);
+    let laptop_state = TempDir::new().unwrap();
+    let out = TempDir::new().unwrap();
+    let archive_path = out.path().join("agent001.tar.gz");
+    let exported = export(&laptop, &laptop_state, &archive_path).await;
+    assert_eq!(exported.manifest.unpushed_commits, 0);
+    assert!(exported.git_bundle.is_none());
+    assert!(exported.workflow_state.is_none());
+
+    let server = server(&origin);
+    let outcome = StateImportCommand::new(archive_path, false)
+        .with_workspace(server.path().to_path_buf())
+        .with_persistence(persistence(&TempDir::new().unwrap()))
+        .restore(&exported, &facts())
+        .await
+        .unwrap();
+    assert!(matches!(
+        outcome.branch,
+        Some(BranchRestore::FromRemote { .. })
+    ));
+    assert_eq!(
+        git(server.path(), &["rev-parse", BRANCH]),
+        git(laptop.path(), &["rev-parse", "HEAD"])
+    );
+}
+
+#[tokio::test]
+async fn test_diverged_local_branch_is_left_alone() {
+    let (origin, laptop) = laptop(1);
+    let out = TempDir::new().unwrap();
+    let archive_path = out.path().join("agent001.tar.gz");
+    let exported = export(&laptop, &TempDir::new().unwrap(), &archive_path).await;
+
+    let server = server(&origin);
+    git(server.path(), &["checkout", "-q", BRANCH]);
+    commit(server.path(), "other-work.txt");
+    let local = git(server.path(), &["rev-parse", BRANCH]);
+
+    let error = StateImportCommand::new(archive_path, false)
+        .with_workspace(server.path().to_path_buf())
+        .with_persistence(persistence(&TempDir::new().unwrap()))
+        .restore(&exported, &facts())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("already exists here"), "{error}");
+    assert_eq!(git(server.path(), &["rev-parse", BRANCH]), local);
+}
+
+#[tokio::test]
+async fn test_github_disagreement_blocks_import_unless_forced() {
+    let (origin, laptop) = laptop(1);
+    let out = TempDir::new().unwrap();
+    let archive_path = out.path().join("agent001.tar.gz");
+    let exported = export(&laptop, &TempDir::new().unwrap(), &archive_path).await;
+    let closed = GitHubFacts {
+        issue_open: Some(false),
+        ..facts()
+    };
+
+    let server = server(&origin);
+    let refused = StateImportCommand::new(archive_path.clone(), false)
+        .with_workspace(server.path().to_path_buf())
+        .with_persistence(persistence(&TempDir::new().unwrap()))
+        .restore(&exported, &closed)
+        .await
+        .unwrap_err();
+    assert!(refused.to_string().contains("Issue #7 has been closed"));
+    assert!(git(server.path(), &["branch", "--list", BRANCH]).is_empty());
+
+    let forced = StateImportCommand::new(archive_path, true)
+        .with_workspace(server.path().to_path_buf())
+        .with_persistence(persistence(&TempDir::new().unwrap()))
+        .restore(&exported, &closed)
+        .await
+        .unwrap();
+    assert_eq!(
+        forced.overridden,
+        vec!["Issue #7 has been closed".to_string()]
+    );
+}
+
+#[test]
+fn test_validation_against_github() {
+    let (_origin, laptop) = laptop(0);
+    let workspace = AgentWorkspace::open(laptop.path(), "origin").unwrap();
+    assert_eq!(
+        workspace.agent_branch("agent001").unwrap().as_deref(),
+        Some(BRANCH)
+    );
+    assert_eq!(workspace.agent_branch("agent002").unwrap(), None);
+
+    let manifest = my_little_soda::autonomous::state_archive::ArchiveManifest {
+        format_version: 1,
+        agent_id: "agent001".to_string(),
+        exported_at: Utc::now(),
+        hostname: "laptop".to_string(),
+        repository: Some("example/repo".to_string()),
+        branch: Some(BRANCH.to_string()),
+        issue: Some(7),
+        head_commit: None,
+        unpushed_commits: 0,
+        checkpoints: 0,
+    };
+    assert!(validate_against_github(&manifest, &facts()).is_empty());
+
+    let problems = validate_against_github(
+        &manifest,
+        &GitHubFacts {
+            repository: "example/fork".to_string(),
+            issue_open: None,
+            issue_labels: vec![],
+            branch_on_remote: false,
+        },
+    );
+    assert_eq!(
+        problems,
+        vec![
+            "Exported from example/repo, but this checkout works on example/fork".to_string(),
+            "Issue #7 no longer exists".to_string(),
+            format!("{BRANCH} has no unpushed commits in the archive and is not on GitHub"),
+        ]
+    );
+    assert_eq!(
+        validate_against_github(
+            &manifest,
+            &GitHubFacts {
+                issue_labels: vec!["agent002".to_string()],
+                ..facts()
+            }
+        ),
+        vec!["Issue #7 is no longer labeled agent001".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_unusable_archives_and_exports() {
+    let (_origin, laptop) = laptop(0);
+    git(laptop.path(), &["checkout", "-q", "main"]);
+    let out = TempDir::new().unwrap();
+
+    let nothing =
+        StateExportCommand::new("agent002".to_string(), Some(out.path().join("a.tar.gz")))
+            .with_workspace(laptop.path().to_path_buf())
+            .with_persistence(persistence(&TempDir::new().unwrap()))
+            .run()
+            .await
+            .unwrap_err();
+    assert!(
+        nothing.to_string().contains("Nothing to export"),
+        "{nothing}"
+    );
+
+    let archive_path = out.path().join("agent001.tar.gz");
+    let mut archive = export(&laptop, &TempDir::new().unwrap(), &archive_path).await;
+    archive.manifest.format_version = 99;
+    archive.write_to(&archive_path).unwrap();
+    let newer = StateArchive::read_from(&archive_path).unwrap_err();
+    assert!(newer.to_string().contains("archive format 99"), "{newer}");
+
+    std::fs::write(&archive_path, "not an archive").unwrap();
+    assert!(StateArchive::read_from(&archive_path).is_err());
+}
@@ -0,0 +1,343 @@
+//! Checkpoint retention and `state gc` tests
+//!
+//! Covers compressing older checkpoints, expiring them by age and count,
+//! summarizing pruned history, and the dry run of `state gc`. Only built with
+//! `--features autonomous`.
+
+#![cfg(feature = "autonomous")]
+
+use chrono::Utc;
+use my_little_soda::autonomous::persistence::{
+    CheckpointMetadata, CheckpointReason, FileSystemPersistence, PersistenceBackend,
+    PersistenceConfig, PersistentWorkflowState, StatePersistence,
+};
+use my_little_soda::autonomous::workflow_state_machine::StateTransitionRecord;
+use my_little_soda::autonomous::{
+    AbandonmentReason, AutonomousEvent, AutonomousWorkflowState, Issue, Priority,
+};
+use my_little_soda::cli::commands::state::{format_plans, StateGcCommand};
+use my_little_soda::config::WorkContinuityConfig;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn config(dir: &TempDir) -> PersistenceConfig {
+    PersistenceConfig {
+        persistence_directory: dir.path().to_path_buf(),
+        ..PersistenceConfig::default()
+    }
+}
+
+fn issue() -> Issue {
+    Issue {
+        number: 45,
+        title: "Keep state small".to_string(),
+        body: String::new(),
+        labels: vec![],
+        priority: Priority::Medium,
+        estimated_hours: None,
+    }
+}
+
+fn state(agent: &str) -> PersistentWorkflowState {
+    PersistentWorkflowState {
+        version: "1.0.0".to_string(),
+        agent_id: agent.to_string(),
+        current_state: Some(AutonomousWorkflowState::Unassigned { issue: issue() }),
+        start_time: Some(Utc::now()),
+        max_work_hours: 8,
+        state_history: vec![],
+        recovery_history: vec![],
+        checkpoint_metadata: CheckpointMetadata {
+            checkpoint_id: "initial".to_string(),
+            creation_reason: CheckpointReason::PeriodicSave,
+            integrity_hash: String::new(),
+            agent_pid: None,
+            hostname: "build-1".to_string(),
+        },
+        last_persisted: Utc::now(),
+        history_summary: None,
+    }
+}
+
+fn checkpoint_files(dir: &TempDir, agent: &str) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir.path().join(format!("{agent}_checkpoints")))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+fn checkpoint_path(dir: &TempDir, agent: &str, id: &str) -> PathBuf {
+    dir.path()
+        .join(format!("{agent}_checkpoints"))
+        .join(format!("{id}.checkpoint.json"))
+}
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Make a checkpoint look `seconds` old
+fn set_age(path: &Path, seconds: u64) {
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(seconds))
+        .unwrap();
+}
+
+/// Checkpoints `count` times, each one a second older than the next
+async fn checkpoints(
+    persistence: &FileSystemPersistence,
+    dir: &TempDir,
+    agent: &str,
+    count: u64,
+) -> Vec<String> {
+    let mut ids = Vec::new();
+    for age in (0..count).rev() {
+        let id = persistence
+            .create_checkpoint(&state(agent), CheckpointReason::PeriodicSave)
+            .await
+            .unwrap();
+        let path = checkpoint_path(dir, agent, &id);
+        if path.exists() {
+            set_age(&path, age + 1);
+        }
+        ids.push(id);
+    }
+    ids
+}
+
+#[tokio::test]
+async fn test_older_checkpoints_are_compressed_and_still_readable() {
+    let dir = TempDir::new().unwrap();
+    let persistence = FileSystemPersistence::new(PersistenceConfig {
+        uncompressed_checkpoints: 2,
+        ..config(&dir)
+    });
+    let ids = checkpoints(&persistence, &dir, "agent001", 4).await;
+    persistence.cleanup_old_data("agent001").await.unwrap();
+
+    // The two newest stay plain, the others are archived
+    let files = checkpoint_files(&dir, "agent001");
+    assert_eq!(
+        files.iter().filter(|f| f.ends_with(".json.gz")).count(),
+        2,
+        "{files:?}"
+    );
+    assert!(files.contains(&format!("{}.checkpoint.json", ids[3])));
+    assert!(files.contains(&format!("{}.checkpoint.json.gz", ids[0])));
+
+    let restored = persistence
+        .restore_from_checkpoint("agent001", &ids[0])
+        .await
+        .unwrap();
+    assert_eq!(restored.checkpoint_metadata.checkpoint_id, ids[0]);
+
+    let listed = persistence.list_checkpoints("agent001").await.unwrap();
+    assert_eq!(listed.len(), 4);
+    assert_eq!(listed[0].checkpoint_id, ids[3]);
+    assert_eq!(listed[3].checkpoint_id, ids[0]);
+    assert_eq!(
+        listed[3].state_summary.current_state_type.as_deref(),
+        Some("Unassigned")
+    );
+}
+
+#[tokio::test]
+async fn test_checkpoints_expire_by_age_and_count() {
+    let dir = TempDir::new().unwrap();
+    let persistence = FileSystemPersistence::new(PersistenceConfig {
+        max_checkpoints_per_agent: 3,
+        compress_old_states: false,
+        ..config(&dir)
+    });
+    let ids = checkpoints(&persistence, &dir, "agent001", 3).await;
+    set_age(&checkpoint_path(&dir, "agent001", &ids[0]), 30 * DAY);
+
+    let plan = persistence.plan_cleanup("agent001").await.unwrap();
+    assert_eq!(plan.expired.len(), 1);
+    assert_eq!(plan.expired[0].checkpoint_id, ids[0]);
+    assert!(plan.over_limit.is_empty());
+    assert!(plan.to_compress.is_empty());
+
+    persistence.cleanup_old_data("agent001").await.unwrap();
+    assert_eq!(checkpoint_files(&dir, "agent001").len(), 2);
+    set_age(&checkpoint_path(&dir, "agent001", &ids[1]), 2 * DAY);
+    set_age(&checkpoint_path(&dir, "agent001", &ids[2]), DAY);
+
+    // New checkpoints push the oldest ones over the limit as they are taken
+    let newer = checkpoints(&persistence, &dir, "agent001", 2).await;
+    let left: Vec<String> = persistence
+        .list_checkpoints("agent001")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.checkpoint_id)
+        .collect();
+    assert_eq!(
+        left,
+        vec![newer[1].clone(), newer[0].clone(), ids[2].clone()]
+    );
+}
+
+#[tokio::test]
+async fn test_zero_retention_days_keeps_old_checkpoints() {
+    let dir = TempDir::new().unwrap();
+    let persistence = FileSystemPersistence::new(PersistenceConfig {
+        backup_retention_days: 0,
+        max_checkpoints_per_agent: 0,
+        compress_old_states: false,
+        ..config(&dir)
+    });
+    let ids = checkpoints(&persistence, &dir, "agent001", 2).await;
+    set_age(&checkpoint_path(&dir, "agent001", &ids[0]), 365 * DAY);
+
+    assert!(persistence
+        .plan_cleanup("agent001")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_pruned_history_is_summarized() {
+    let dir = TempDir::new().unwrap();
+    let persistence = FileSystemPersistence::new(PersistenceConfig {
+        max_state_history_entries: 2,
+        ..config(&dir)
+    });
+    let mut long_running = state("agent001");
+    let started = Utc::now() - chrono::Duration::hours(3);
+    for hour in 0..5 {
+        long_running.state_history.push(StateTransitionRecord {
+            from_state: None,
+            to_state: if hour % 2 == 0 {
+                AutonomousWorkflowState::Unassigned { issue: issue() }
+            } else {
+                AutonomousWorkflowState::Abandoned {
+                    issue: issue(),
+                    reason: AbandonmentReason::RequirementsChanged,
+                }
+            },
+            event: AutonomousEvent::StartWork,
+            timestamp: started + chrono::Duration::hours(hour),
+            duration_ms: 10,
+        });
+    }
+    persistence
+        .save_state(&long_running, CheckpointReason::PeriodicSave)
+        .await
+        .unwrap();
+
+    let saved = persistence.load_state("agent001").await.unwrap().unwrap();
+    assert_eq!(saved.state_history.len(), 2);
+    let summary = saved.history_summary.unwrap();
+    assert_eq!(summary.transitions, 3);
+    assert_eq!(summary.states_entered["Unassigned"], 2);
+    assert_eq!(summary.states_entered["Abandoned"], 1);
+    assert_eq!(summary.earliest, Some(started));
+    assert_eq!(summary.latest, Some(started + chrono::Duration::hours(2)));
+}
+
+#[tokio::test]
+async fn test_gc_dry_run_reports_without_changing_anything() {
+    let dir = TempDir::new().unwrap();
+    let persistence = Arc::new(FileSystemPersistence::new(PersistenceConfig {
+        uncompressed_checkpoints: 1,
+        ..config(&dir)
+    }));
+    // Checkpoints taken directly, so retention has not run on them yet
+    let ids = checkpoints(&persistence, &dir, "agent001", 1).await;
+    set_age(&checkpoint_path(&dir, "agent001", &ids[0]), 30 * DAY);
+    let agent002 = checkpoints(&persistence, &dir, "agent002", 1).await;
+    std::fs::copy(
+        checkpoint_path(&dir, "agent002", &agent002[0]),
+        checkpoint_path(&dir, "agent002", "1_older"),
+    )
+    .unwrap();
+    set_age(&checkpoint_path(&dir, "agent002", "1_older"), DAY);
+    let before = (
+        checkpoint_files(&dir, "agent001"),
+        checkpoint_files(&dir, "agent002"),
+    );
+
+    let plans = StateGcCommand::new(true, None)
+        .with_persistence(persistence.clone())
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(plans.len(), 2);
+    assert_eq!(plans[0].agent_id, "agent001");
+    assert_eq!(plans[0].expired.len(), 1);
+    assert!(plans[0].expired[0].bytes > 0);
+    assert_eq!(plans[1].to_compress.len(), 1);
+    assert_eq!(plans[1].to_compress[0].checkpoint_id, "1_older");
+    let report = format_plans(&plans, true);
+    assert!(
+        report.contains("agent001: would delete 1 expired ("),
+        "{report}"
+    );
+    assert!(report.contains("agent002: would compress 1 ("), "{report}");
+    assert!(report.contains("🧹 Would free "), "{report}");
+    assert_eq!(
+        before,
+        (
+            checkpoint_files(&dir, "agent001"),
+            checkpoint_files(&dir, "agent002"),
+        )
+    );
+
+    let done = StateGcCommand::new(false, Some("agent002".to_string()))
+        .with_persistence(persistence.clone())
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(done.len(), 1);
+    assert!(done[0].compressed_bytes.unwrap() > 0);
+    assert!(checkpoint_files(&dir, "agent002").contains(&"1_older.checkpoint.json.gz".to_string()));
+    assert_eq!(checkpoint_files(&dir, "agent001"), before.0);
+    assert!(format_plans(&done, false).contains("compressed 1 checkpoint(s) from "));
+
+    let empty = TempDir::new().unwrap();
+    let nothing = StateGcCommand::new(true, None)
+        .with_persistence(Arc::new(FileSystemPersistence::new(config(&empty))))
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(
+        format_plans(&nothing, true),
+        "No autonomous workflow state found\n"
+    );
+}
+
+#[test]
+fn test_persistence_config_from_work_continuity() {
+    let continuity: WorkContinuityConfig = toml::from_str(
+        r#"
+        enable_continuity = true
+        state_file_path = ".my-little-soda/agent-state.json"
+        backup_interval_minutes = 10
+        max_recovery_attempts = 3
+        validation_timeout_seconds = 30
+        force_fresh_start_after_hours = 24
+        preserve_partial_work = true
+        backup_retention_days = 14
+        max_checkpoints_per_agent = 20
+        "#,
+    )
+    .unwrap();
+    assert!(continuity.compress_old_states);
+
+    let config = PersistenceConfig::from_work_continuity(&continuity);
+    assert_eq!(
+        config.persistence_directory,
+        PathBuf::from(".my-little-soda")
+    );
+    assert_eq!(config.auto_save_interval_minutes, 10);
+    assert_eq!(config.backup_retention_days, 14);
+    assert_eq!(config.max_checkpoints_per_agent, 20);
+    assert_eq!(config.backend, PersistenceBackend::Filesystem);
+}
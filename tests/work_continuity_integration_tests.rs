@@ -40,6 +40,7 @@ mod tests {
             compress_old_states: false, // Disabled for tests
             backup_retention_days: 1,
             enable_integrity_checks: true,
+            ..PersistenceConfig::default()
         };
 
         (continuity_config, persistence_config)